@@ -15,12 +15,23 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 use clap::Parser;
 use pctx::Cli;
+use pctx_code_mode::isolation;
 
 #[tokio::main]
 async fn main() {
     // Install default crypto provider for rustls (required for TLS/HTTPS in Deno)
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
 
+    // child process of an execution with `--isolation process`
+    if isolation::is_child() {
+        let res = tokio::task::spawn_blocking(|| isolation::run_child(None)).await;
+        if let Err(e) = res.map_err(std::io::Error::other).and_then(|r| r) {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let cli = Cli::parse();
 
     if let Err(e) = cli.handle().await {
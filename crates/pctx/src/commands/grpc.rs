@@ -0,0 +1,56 @@
+use anyhow::Result;
+use clap::Parser;
+use std::time::Duration;
+
+use pctx_session_server::{
+    AppState, ExecutionQueueConfig, LocalBackend, SessionLimits, auth::AuthConfig,
+};
+
+#[derive(Debug, Clone, Parser)]
+pub struct GrpcCmd {
+    /// Port to listen on
+    #[arg(short, long, default_value = "50051")]
+    pub port: u16,
+
+    /// Host address to bind to (use 0.0.0.0 for external access)
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Seconds local tool callbacks may take
+    #[arg(long, default_value = "30")]
+    pub callback_timeout: f64,
+
+    /// Maximum number of tools a session can register
+    #[arg(long)]
+    pub max_tools_per_session: Option<usize>,
+
+    /// Maximum number of concurrently running code executions, further executions are queued
+    #[arg(long)]
+    pub max_concurrent_executions: Option<usize>,
+
+    /// Maximum number of queued code executions, further executions are rejected
+    #[arg(long, default_value = "100")]
+    pub max_queued_executions: usize,
+
+    /// Token clients must send in the `authorization` metadata, can be repeated
+    #[arg(long = "auth-token", env = "PCTX_AUTH_TOKENS", value_delimiter = ',')]
+    pub auth_tokens: Vec<String>,
+}
+
+impl GrpcCmd {
+    pub(crate) async fn handle(&self) -> Result<()> {
+        let state = AppState::new(LocalBackend::default())
+            .with_auth(AuthConfig::default().with_tokens(self.auth_tokens.clone()))
+            .with_limits(SessionLimits {
+                callback_timeout: Duration::from_secs_f64(self.callback_timeout),
+                max_tools: self.max_tools_per_session,
+                max_pending_executions: None,
+            })
+            .with_execution_queue(ExecutionQueueConfig {
+                max_concurrent: self.max_concurrent_executions,
+                max_queued: self.max_queued_executions,
+            });
+
+        pctx_grpc_server::start_grpc_server(&self.host, self.port, state).await
+    }
+}
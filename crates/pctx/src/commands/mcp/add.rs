@@ -14,7 +14,7 @@ use crate::{
 };
 use pctx_config::{
     Config,
-    auth::{AuthConfig, SecretString},
+    auth::{AuthConfig, BearerAuth, HeadersAuth, SecretString},
     server::{McpConnectionError, ServerConfig},
 };
 
@@ -103,16 +103,16 @@ impl AddCmd {
         // apply authentication for HTTP servers only (clap ensures bearer & header are mutually exclusive)
         if server.http().is_some() {
             let auth = if let Some(bearer) = &self.bearer {
-                Some(AuthConfig::Bearer {
+                Some(AuthConfig::Bearer(BearerAuth {
                     token: bearer.clone(),
-                })
+                }))
             } else if let Some(headers) = &self.header {
-                Some(AuthConfig::Headers {
+                Some(AuthConfig::Headers(HeadersAuth {
                     headers: headers
                         .iter()
                         .map(|h| (h.name.clone(), h.value.clone()))
                         .collect(),
-                })
+                }))
             } else {
                 let add_auth = inquire::Confirm::new(
                     "Do you want to add authentication interactively?",
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use pctx_codegen::case::Case;
+use pctx_config::{
+    Config,
+    auth::{
+        AuthConfig, AuthSecret, BearerAuth, SecretString, keychain_contains, remove_from_keychain,
+        write_to_keychain,
+    },
+};
+use tracing::info;
+
+use crate::utils::styles::{fmt_bold, fmt_dimmed, fmt_green, fmt_red, fmt_success};
+
+/// Manage credentials of upstream MCP servers stored in the system keychain
+#[derive(Debug, Subcommand)]
+pub enum AuthCommands {
+    /// Store a server credential in the keychain & reference it from the config
+    Login(LoginCmd),
+    /// Remove the keychain credentials of a server
+    Logout(LogoutCmd),
+    /// Show the auth configuration & stored keychain credentials of each server
+    Status(StatusCmd),
+}
+
+impl AuthCommands {
+    pub(crate) fn handle(&self, cfg: Config) -> Result<Config> {
+        match self {
+            AuthCommands::Login(cmd) => cmd.handle(cfg),
+            AuthCommands::Logout(cmd) => cmd.handle(cfg),
+            AuthCommands::Status(cmd) => cmd.handle(cfg),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct LoginCmd {
+    /// Name of the HTTP server to log in to
+    pub name: String,
+
+    /// Bearer token (or OAuth client secret) to store, prompted for if omitted
+    #[arg(long)]
+    pub token: Option<String>,
+}
+
+impl LoginCmd {
+    pub(crate) fn handle(&self, mut cfg: Config) -> Result<Config> {
+        let server = cfg
+            .servers
+            .iter_mut()
+            .find(|s| s.name == self.name)
+            .with_context(|| format!("Server '{}' not found", self.name))?;
+        let http = server
+            .http_mut()
+            .with_context(|| format!("Server '{}' is not an HTTP server", self.name))?;
+
+        // OAuth servers keep their client configuration, only the client secret is stored
+        let (key, what) = if let Some(AuthConfig::OAuthClientCredentials(oauth)) = &mut http.auth {
+            let key = Case::Snake.sanitize(format!("{}_client_secret", self.name));
+            oauth.client_secret = SecretString::new_secret(AuthSecret::Keychain(key.clone()));
+            (key, "OAuth client secret")
+        } else {
+            let key = Case::Snake.sanitize(format!("{}_bearer", self.name));
+            http.auth = Some(AuthConfig::Bearer(BearerAuth {
+                token: SecretString::new_secret(AuthSecret::Keychain(key.clone())),
+            }));
+            (key, "bearer token")
+        };
+
+        let secret = match &self.token {
+            Some(token) => token.clone(),
+            None => inquire::Password::new(&format!("Enter the {what}:"))
+                .without_confirmation()
+                .with_validator(inquire::min_length!(1, "must be at least 1 character"))
+                .prompt()?,
+        };
+        write_to_keychain(&key, &secret)?;
+        cfg.save()?;
+
+        info!(
+            "{}",
+            fmt_success(&format!(
+                "{what} of {name} stored in keychain as {key}, referenced from {path}",
+                name = fmt_bold(&self.name),
+                key = fmt_bold(&key),
+                path = fmt_dimmed(cfg.path().as_str()),
+            ))
+        );
+
+        Ok(cfg)
+    }
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct LogoutCmd {
+    /// Name of the server to log out of
+    pub name: String,
+}
+
+impl LogoutCmd {
+    pub(crate) fn handle(&self, mut cfg: Config) -> Result<Config> {
+        let server = cfg
+            .servers
+            .iter_mut()
+            .find(|s| s.name == self.name)
+            .with_context(|| format!("Server '{}' not found", self.name))?;
+        let Some(auth) = server.http().and_then(|h| h.auth.clone()) else {
+            info!("{} has no auth configured", fmt_bold(&self.name));
+            return Ok(cfg);
+        };
+
+        for key in auth.keychain_keys() {
+            remove_from_keychain(&key)?;
+            info!("Removed {} from keychain", fmt_bold(&key));
+        }
+
+        // bearer tokens are fully replaced on login, other auth types keep their configuration
+        if matches!(auth, AuthConfig::Bearer(_)) {
+            server.set_auth(None);
+            cfg.save()?;
+        }
+
+        info!(
+            "{}",
+            fmt_success(&format!("Logged out of {}", fmt_bold(&self.name)))
+        );
+
+        Ok(cfg)
+    }
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct StatusCmd {
+    /// Only show the status of this server
+    pub name: Option<String>,
+}
+
+impl StatusCmd {
+    pub(crate) fn handle(&self, cfg: Config) -> Result<Config> {
+        let http_servers: Vec<_> = cfg
+            .servers
+            .iter()
+            .filter(|s| self.name.as_ref().is_none_or(|n| n == &s.name))
+            .filter_map(|s| s.http().map(|h| (s, h)))
+            .collect();
+        if http_servers.is_empty() {
+            info!("No HTTP MCP servers configured");
+            return Ok(cfg);
+        }
+
+        for (server, http) in http_servers {
+            let Some(auth) = &http.auth else {
+                info!("{} {}", fmt_bold(&server.name), fmt_dimmed("no auth"));
+                continue;
+            };
+
+            info!("{} {}", fmt_bold(&server.name), fmt_dimmed(auth.kind()));
+            for key in auth.keychain_keys() {
+                let stored = match keychain_contains(&key) {
+                    Ok(true) => fmt_green("stored"),
+                    Ok(false) => fmt_red("missing"),
+                    Err(e) => fmt_red(&format!("unavailable: {e}")),
+                };
+                info!("  keychain {key}: {stored}");
+            }
+        }
+
+        Ok(cfg)
+    }
+}
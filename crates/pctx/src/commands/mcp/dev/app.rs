@@ -5,18 +5,24 @@ use std::{
     time::Instant,
 };
 
-use anyhow::Result;
-use camino::Utf8PathBuf;
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
 use chrono::{DateTime, Utc};
 use pctx_codegen::{Tool, ToolSet};
-use pctx_config::logger::LogLevel;
+use pctx_config::{Config, logger::LogLevel, skill::SkillConfig};
 use ratatui::{layout::Rect, widgets::ListState};
+use serde::Serialize;
 
 use super::log_entry::LogEntry;
-use pctx_code_mode::CodeMode;
+use pctx_code_mode::{
+    CodeMode,
+    model::{ExecuteInput, Skill},
+};
 
 // -------- APP STATE & CONTROLS ---------
 
+const DEFAULT_USAGE_OUT: &str = "pctx-usage.json";
+
 #[derive(Clone)]
 pub(super) enum AppMessage {
     ServerStarting,
@@ -34,11 +40,9 @@ pub(super) enum FocusPanel {
     Documentation,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub(super) struct ToolUsage {
-    #[allow(dead_code)]
     pub(super) tool_name: String,
-    #[allow(dead_code)]
     pub(super) server_name: String,
     pub(super) count: usize,
     pub(super) last_used: DateTime<Utc>,
@@ -68,6 +72,10 @@ pub(super) struct App {
 
     // Tool usage tracking
     pub(super) tool_usage: HashMap<String, ToolUsage>,
+    pub(super) usage_out: Option<Utf8PathBuf>,
+
+    // Code & input of the last successful execution, promoted to a skill with `s`
+    pub(super) last_execution: Option<ExecuteInput>,
 
     // Panel boundaries for mouse click detection
     pub(super) tools_rect: Option<Rect>,
@@ -96,6 +104,8 @@ impl App {
             selected_namespace_index: 0,
             detail_scroll_offset: 0,
             tool_usage: HashMap::new(),
+            usage_out: None,
+            last_execution: None,
             tools_rect: None,
             logs_rect: None,
             namespace_rects: Vec::new(),
@@ -139,6 +149,7 @@ impl App {
             if let Ok(entry) = serde_json::from_str::<LogEntry>(&line) {
                 // Track tool usage from logs
                 self.track_tool_usage(&entry);
+                self.track_execution(&entry);
 
                 self.logs.push(entry);
 
@@ -192,7 +203,8 @@ impl App {
                     // Find all method calls for this server
                     for tool in &tool_set.tools {
                         // Check if this function is called in the code
-                        let method_pattern = format!("{}.{}(", &tool_set.namespace, &tool.fn_name);
+                        let method_pattern =
+                            format!("{}.{}(", tool_set.tool_namespace(tool), &tool.fn_name);
                         tracing::trace!(
                             "Checking for method pattern '{}' for tool '{}'",
                             method_pattern,
@@ -202,7 +214,7 @@ impl App {
                         if code_from_llm.contains(&method_pattern) {
                             tracing::trace!(
                                 "✓ Found tool usage: {}.{} (tool_name={})",
-                                &tool_set.namespace,
+                                tool_set.tool_namespace(tool),
                                 &tool.fn_name,
                                 &tool.name
                             );
@@ -258,6 +270,53 @@ impl App {
         }
     }
 
+    /// Remembers the code & input of successful executions
+    pub(super) fn track_execution(&mut self, entry: &LogEntry) {
+        let Some(code) = entry
+            .fields
+            .extra
+            .get("executed_code")
+            .and_then(|v| v.as_str())
+        else {
+            return;
+        };
+        let input = entry
+            .fields
+            .extra
+            .get("executed_input")
+            .and_then(|v| v.as_str())
+            .and_then(|v| serde_json::from_str::<serde_json::Value>(v).ok())
+            .filter(|v| !v.is_null());
+
+        self.last_execution = Some(ExecuteInput {
+            code: code.to_string(),
+            input,
+            ..Default::default()
+        });
+    }
+
+    /// Saves the last successful execution as a new skill `skill_<n>` in the config file,
+    /// reloading the server with the skill. Returns the name of the skill.
+    pub(super) fn promote_last_execution(&self, config_path: &Utf8PathBuf) -> Result<String> {
+        let Some(execution) = &self.last_execution else {
+            anyhow::bail!("No successful execution to promote to a skill yet");
+        };
+
+        let mut cfg = Config::load(config_path)?;
+        let name = (1..)
+            .map(|i| format!("skill_{i}"))
+            .find(|name| !cfg.skills.iter().any(|s| &s.name == name))
+            .unwrap_or_default();
+        let skill = Skill::from_execution(&name, None, execution, None);
+        cfg.skills.push(SkillConfig::from(&skill));
+        cfg.save()?;
+
+        tracing::info!(
+            "Promoted the last execution to the skill Skills.{name}, rename it & describe it in {config_path}"
+        );
+        Ok(name)
+    }
+
     pub(super) fn reprocess_logs_for_tool_usage(&mut self) {
         // Re-read the entire log file and process all entries for tool usage
         let Ok(file) = File::open(&self.log_file_path) else {
@@ -281,6 +340,50 @@ impl App {
         }
     }
 
+    /// Tool usage sorted by most used first, ties broken by server & tool name
+    pub(super) fn sorted_tool_usage(&self) -> Vec<&ToolUsage> {
+        let mut usage: Vec<&ToolUsage> = self.tool_usage.values().collect();
+        usage.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.server_name.cmp(&b.server_name))
+                .then_with(|| a.tool_name.cmp(&b.tool_name))
+        });
+        usage
+    }
+
+    /// Writes the accumulated tool usage to `path`, as CSV if the
+    /// file has a `.csv` extension and JSON otherwise.
+    pub(super) fn export_tool_usage(&self, path: &Utf8Path) -> Result<()> {
+        let usage = self.sorted_tool_usage();
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+
+        let contents = if is_csv {
+            tool_usage_csv(&usage)
+        } else {
+            serde_json::to_string_pretty(&usage)?
+        };
+
+        std::fs::write(path, contents).context(format!("Failed writing tool usage to {path}"))?;
+        tracing::info!("Exported usage of {} tools to {path}", usage.len());
+
+        Ok(())
+    }
+
+    /// Exports tool usage to the configured `--usage-out` path, falling back to
+    /// `pctx-usage.json` in the current directory.
+    pub(super) fn export_tool_usage_on_demand(&self) {
+        let path = self
+            .usage_out
+            .clone()
+            .unwrap_or_else(|| Utf8PathBuf::from(DEFAULT_USAGE_OUT));
+        if let Err(e) = self.export_tool_usage(&path) {
+            tracing::error!("{e:?}");
+        }
+    }
+
     pub(super) fn filtered_logs(&self) -> Vec<&LogEntry> {
         self.logs
             .iter()
@@ -677,3 +780,29 @@ impl App {
         }
     }
 }
+
+// -------- USAGE EXPORT ---------
+
+fn tool_usage_csv(usage: &[&ToolUsage]) -> String {
+    let mut csv = String::from("server_name,tool_name,count,last_used,code_snippets\n");
+    for u in usage {
+        let row = [
+            csv_field(&u.server_name),
+            csv_field(&u.tool_name),
+            u.count.to_string(),
+            u.last_used.to_rfc3339(),
+            csv_field(&u.code_snippets.join("\n")),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
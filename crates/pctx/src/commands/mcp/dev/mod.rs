@@ -59,6 +59,15 @@ pub struct DevCmd {
     /// Serve MCP over stdio instead of HTTP
     #[arg(long)]
     pub stdio: bool,
+
+    /// Export tool usage statistics to this file on exit (and when pressing `u`).
+    /// Written as CSV if the path ends in `.csv`, JSON otherwise
+    #[arg(long)]
+    pub usage_out: Option<Utf8PathBuf>,
+
+    /// Re-fetch upstream tool schemas on startup instead of using the cached schemas in `.pctx/cache`
+    #[arg(long)]
+    pub refresh: bool,
 }
 
 impl DevCmd {
@@ -75,18 +84,21 @@ impl DevCmd {
         let mut terminal = Terminal::new(backend)?;
 
         // Create app state
-        let app = Arc::new(Mutex::new(App::new(
-            self.host.clone(),
-            self.port,
-            self.log_file.clone(),
-        )));
+        let mut app = App::new(self.host.clone(), self.port, self.log_file.clone());
+        app.usage_out.clone_from(&self.usage_out);
+        let app = Arc::new(Mutex::new(app));
 
         // Channel for sending messages to the UI
         let (tx, mut rx) = mpsc::unbounded_channel::<AppMessage>();
 
         // Spawn initial server task
-        let (server_handle, shutdown_tx) =
-            spawn_server_task(cfg.clone(), tx.clone(), self.host.clone(), self.port);
+        let (server_handle, shutdown_tx) = spawn_server_task(
+            cfg.clone(),
+            tx.clone(),
+            self.host.clone(),
+            self.port,
+            self.refresh,
+        );
 
         // Store server control in Arc<Mutex<>> so we can replace it on config reload
         let server_control: ServerControl =
@@ -186,6 +198,14 @@ impl DevCmd {
 
         result?;
 
+        // Export tool usage statistics collected during the session, a failed export
+        // doesn't fail the session
+        if let Some(usage_out) = &self.usage_out
+            && let Err(e) = app.lock().unwrap().export_tool_usage(usage_out)
+        {
+            tracing::warn!("Failed exporting tool usage: {e:#}");
+        }
+
         Ok(cfg)
     }
 }
@@ -317,6 +337,14 @@ fn run_ui(
                                     let _ = app.copy_server_url_to_clipboard();
                                 }
                             }
+                            KeyCode::Char('u') => {
+                                app.export_tool_usage_on_demand();
+                            }
+                            KeyCode::Char('s') => {
+                                if let Err(e) = app.promote_last_execution(config_path) {
+                                    tracing::error!("{e:?}");
+                                }
+                            }
                             KeyCode::Char('d') => {
                                 // open / close docs
                                 if app.focused_panel == FocusPanel::Documentation {
@@ -391,11 +419,13 @@ fn run_ui(
                             );
 
                             // 3. Spawn new server with new config
+                            // changed servers are re-fetched as their schemas are cached on their config
                             let (new_handle, new_shutdown_tx) = spawn_server_task(
                                 new_cfg,
                                 tx_reload.clone(),
                                 host_clone,
                                 port_clone,
+                                false,
                             );
 
                             // 4. Store new server control
@@ -441,14 +471,14 @@ fn run_ui(
 
 // Spawns the PctxMcp server task
 // Returns (server_handle, shutdown_sender)
-async fn load_code_mode_for_dev(cfg: &Config) -> Result<pctx_code_mode::CodeMode> {
+async fn load_code_mode_for_dev(cfg: &Config, refresh: bool) -> Result<pctx_code_mode::CodeMode> {
     if cfg.servers.is_empty() {
         tracing::warn!(
             "No MCP servers configured, add servers with 'pctx add <name> <url>' and PCTX Dev Mode will refresh"
         );
         Ok(pctx_code_mode::CodeMode::default())
     } else {
-        let loaded = StartCmd::load_code_mode(cfg).await?;
+        let loaded = StartCmd::load_code_mode(cfg, refresh).await?;
         if loaded.tool_sets().is_empty() {
             tracing::warn!(
                 "Failed loading all configured MCP servers, add servers with 'pctx add <name> <url>' or edit {} and PCTX Dev Mode will refresh",
@@ -464,6 +494,7 @@ fn spawn_server_task(
     tx: mpsc::UnboundedSender<AppMessage>,
     host: String,
     port: u16,
+    refresh: bool,
 ) -> (
     tokio::task::JoinHandle<()>,
     tokio::sync::oneshot::Sender<()>,
@@ -473,7 +504,7 @@ fn spawn_server_task(
     let handle = tokio::spawn(async move {
         tx.send(AppMessage::ServerStarting).ok();
 
-        let tools = match load_code_mode_for_dev(&cfg).await {
+        let tools = match load_code_mode_for_dev(&cfg, refresh).await {
             Ok(loaded) => loaded,
             Err(err) => {
                 tx.send(AppMessage::ServerFailed(format!(
@@ -711,4 +742,117 @@ mod tests {
             "Expected 2 unique code snippets"
         );
     }
+
+    fn tracked_app(log_file: Utf8PathBuf) -> App {
+        let mut app = App::new("localhost".to_string(), 8080, log_file);
+        app.tools = create_pctx_tools();
+
+        for code in [
+            "await Banking.getAccountBalance({ account_id: \"ACC-1\" });",
+            "await Banking.getAccountBalance({ account_id: \"ACC-2\" });",
+            "await Banking.freezeAccount({ account_id: \"ACC-3\" });",
+        ] {
+            app.track_tool_usage(&LogEntry {
+                timestamp: Utc::now(),
+                level: LogLevel::Info,
+                target: "pctx".into(),
+                fields: LogEntryFields {
+                    message: "Executing code".into(),
+                    extra: HashMap::from_iter([("code_from_llm".to_string(), json!(code))]),
+                },
+            });
+        }
+
+        app
+    }
+
+    #[test]
+    fn test_export_tool_usage_json() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+        let app = tracked_app(root.join("test.jsonl"));
+
+        let out = root.join("usage.json");
+        app.export_tool_usage(&out).unwrap();
+
+        let exported: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        let exported = exported.as_array().unwrap();
+        assert_eq!(exported.len(), 2);
+
+        // most used tool first
+        assert_eq!(exported[0]["tool_name"], "get_account_balance");
+        assert_eq!(exported[0]["server_name"], "banking");
+        assert_eq!(exported[0]["count"], 2);
+        assert_eq!(exported[0]["code_snippets"].as_array().unwrap().len(), 2);
+        assert!(exported[0]["last_used"].is_string());
+        assert_eq!(exported[1]["tool_name"], "freeze_account");
+        assert_eq!(exported[1]["count"], 1);
+    }
+
+    #[test]
+    fn test_export_tool_usage_csv() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+        let app = tracked_app(root.join("test.jsonl"));
+
+        let out = root.join("usage.csv");
+        app.export_tool_usage(&out).unwrap();
+
+        let exported = std::fs::read_to_string(&out).unwrap();
+        let mut lines = exported.lines();
+        assert_eq!(
+            lines.next(),
+            Some("server_name,tool_name,count,last_used,code_snippets")
+        );
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("banking,get_account_balance,2,")
+        );
+        // snippets containing quotes & commas are escaped
+        assert!(exported.contains("\"\"ACC-1\"\""));
+        assert!(exported.contains("banking,freeze_account,1,"));
+    }
+
+    #[test]
+    fn test_promote_last_execution() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+        let config_path = root.join("pctx.json");
+        std::fs::write(&config_path, r#"{ "name": "test", "servers": [] }"#).unwrap();
+
+        let mut app = App::new("localhost".to_string(), 8080, root.join("test.jsonl"));
+        assert!(app.promote_last_execution(&config_path).is_err());
+
+        app.track_execution(&LogEntry {
+            timestamp: Utc::now(),
+            level: LogLevel::Debug,
+            target: "pctx_code_mode".into(),
+            fields: LogEntryFields {
+                message: "Sandbox execution completed successfully".into(),
+                extra: HashMap::from_iter([
+                    (
+                        "executed_code".to_string(),
+                        json!("async function run(input) { return input.id; }"),
+                    ),
+                    ("executed_input".to_string(), json!(r#"{"id":"ACC-1"}"#)),
+                ]),
+            },
+        });
+        assert_eq!(app.promote_last_execution(&config_path).unwrap(), "skill_1");
+        assert_eq!(app.promote_last_execution(&config_path).unwrap(), "skill_2");
+
+        let cfg = Config::load(&config_path).unwrap();
+        assert_eq!(cfg.skills.len(), 2);
+        assert_eq!(
+            cfg.skills[0].code,
+            "async function run(input) { return input.id; }"
+        );
+        assert_eq!(
+            cfg.skills[0].input_schema.as_ref().unwrap()["properties"]["id"]["type"],
+            json!("string")
+        );
+    }
 }
@@ -572,6 +572,8 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect) {
     let fast_scroll = Span::raw("[PgUp/PgDn] Fast Scroll  ");
     let select_text = Span::raw("[Mouse] Select Text  ");
     let docs = Span::raw("[d] Docs  ");
+    let export_usage = Span::raw("[u] Export Usage  ");
+    let save_skill = Span::raw("[s] Save Skill  ");
     let filter_level = Span::raw("[f] Filter Level  ");
     let switch_panel = Span::raw("[Tab] Switch Panel  ");
     let navigate = Span::raw("[↑/↓] Navigate  ");
@@ -586,10 +588,25 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect) {
             help_text.extend([back, scroll, fast_scroll, select_text]);
         }
         FocusPanel::Logs => {
-            help_text.extend([docs, switch_panel, navigate, filter_level]);
+            help_text.extend([
+                docs,
+                switch_panel,
+                navigate,
+                filter_level,
+                export_usage,
+                save_skill,
+            ]);
         }
         FocusPanel::Tools => {
-            help_text.extend([docs, switch_panel, navigate, switch_namespace, view_details]);
+            help_text.extend([
+                docs,
+                switch_panel,
+                navigate,
+                switch_namespace,
+                view_details,
+                export_usage,
+                save_skill,
+            ]);
         }
     }
 
@@ -0,0 +1,56 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::Parser;
+use pctx_code_mode::catalog::CatalogFormat;
+use pctx_config::Config;
+use tracing::info;
+
+use super::StartCmd;
+use crate::utils::styles::{fmt_bold, fmt_success};
+
+#[derive(Debug, Clone, Parser)]
+pub struct DocsCmd {
+    /// Document format, `markdown` or `html`
+    #[arg(long, short = 'f', default_value = "markdown")]
+    pub format: CatalogFormat,
+
+    /// File to write the catalog to, printed to stdout by default
+    #[arg(long, short = 'o')]
+    pub output: Option<Utf8PathBuf>,
+
+    /// Connect to every server instead of using the cached tool schemas in `.pctx/cache`
+    #[arg(long)]
+    pub refresh: bool,
+}
+
+impl DocsCmd {
+    pub(crate) async fn handle(&self, cfg: Config) -> Result<Config> {
+        if cfg.servers.is_empty() {
+            anyhow::bail!(
+                "No upstream MCP servers configured. Add servers with 'pctx add <name> <url>'"
+            );
+        }
+
+        let code_mode = StartCmd::load_code_mode(&cfg, self.refresh).await?;
+        let catalog = code_mode.export_catalog(self.format);
+
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, &catalog)
+                    .with_context(|| format!("Failed writing tool catalog to {path}"))?;
+                info!(
+                    "{}",
+                    fmt_success(&format!(
+                        "Tool catalog written to {}",
+                        fmt_bold(path.as_str())
+                    ))
+                );
+            }
+            None => std::io::stdout().lock().write_all(catalog.as_bytes())?,
+        }
+
+        Ok(cfg)
+    }
+}
@@ -6,6 +6,7 @@ use crate::utils::{
 };
 use anyhow::Result;
 use clap::Parser;
+use pctx_code_mode::schema_cache::SchemaCache;
 use pctx_config::{
     Config,
     server::{McpConnectionError, ServerConfig},
@@ -13,8 +14,14 @@ use pctx_config::{
 use rmcp::model::InitializeResult;
 use tracing::info;
 
+use super::start::schema_cache_dir;
+
 #[derive(Debug, Clone, Parser)]
-pub struct ListCmd;
+pub struct ListCmd {
+    /// Connect to every server instead of listing the cached tool schemas in `.pctx/cache`
+    #[arg(long)]
+    pub refresh: bool,
+}
 
 impl ListCmd {
     pub(crate) async fn handle(&self, cfg: Config) -> Result<Config> {
@@ -28,12 +35,13 @@ impl ListCmd {
             return Ok(cfg);
         }
 
+        let schema_cache = SchemaCache::new(schema_cache_dir(&cfg)).with_refresh(self.refresh);
         let num_servers = cfg.servers.len();
         let mut sp = Spinner::new(format!("Listing upstream MCPs... 0/{num_servers}"));
         let mut summaries = vec![];
         for (i, server) in cfg.servers.iter().enumerate() {
             sp.update_text(format!("Listing upstream MCPs... {}/{num_servers}", i + 1));
-            summaries.push(UpstreamMcpSummary::new(server).await);
+            summaries.push(UpstreamMcpSummary::new(server, &schema_cache).await);
         }
 
         sp.stop_success("Done");
@@ -53,15 +61,28 @@ struct UpstreamMcpSummary {
     pub init_res: Option<InitializeResult>,
     pub tools: Vec<String>,
     pub transport: &'static str,
+    pub cached: bool,
 }
 impl UpstreamMcpSummary {
-    async fn new(server: &ServerConfig) -> Self {
+    async fn new(server: &ServerConfig, schema_cache: &SchemaCache) -> Self {
         let transport = if server.http().is_some() {
             "http"
         } else {
             "stdio"
         };
 
+        if let Some(tool_set) = schema_cache.load(server) {
+            return Self {
+                target: server.display_target(),
+                name: server.name.clone(),
+                error: None,
+                init_res: None,
+                tools: tool_set.tools.into_iter().map(|t| t.name).collect(),
+                transport,
+                cached: true,
+            };
+        }
+
         let (error, init_res, tools) = match server.connect().await {
             Ok(client) => {
                 let mut error = None;
@@ -90,6 +111,7 @@ impl UpstreamMcpSummary {
             init_res,
             tools,
             transport,
+            cached: false,
         }
     }
 }
@@ -102,7 +124,16 @@ impl Display for UpstreamMcpSummary {
         if let Some(e) = &self.error {
             fields.extend([fmt_error(e), target_field, transport_field]);
         } else {
-            fields.extend([fmt_success("Connected"), target_field, transport_field]);
+            let status = if self.cached {
+                format!(
+                    "{} {}",
+                    fmt_success("Cached"),
+                    fmt_dimmed("(use --refresh to test the connection)")
+                )
+            } else {
+                fmt_success("Connected")
+            };
+            fields.extend([status, target_field, transport_field]);
 
             if let Some(init_res) = &self.init_res {
                 fields.push(format!(
@@ -1,14 +1,22 @@
 pub(crate) mod add;
+pub(crate) mod auth;
 pub(crate) mod dev;
+pub(crate) mod docs;
 pub(crate) mod init;
 pub(crate) mod list;
+pub(crate) mod playground;
+pub(crate) mod prompt;
 pub(crate) mod remove;
 pub(crate) mod start;
 
 pub(crate) use add::AddCmd;
+pub(crate) use auth::AuthCommands;
 
 pub(crate) use dev::DevCmd;
+pub(crate) use docs::DocsCmd;
 pub(crate) use init::InitCmd;
 pub(crate) use list::ListCmd;
+pub(crate) use playground::PlaygroundCmd;
+pub(crate) use prompt::PromptCmd;
 pub(crate) use remove::RemoveCmd;
 pub(crate) use start::StartCmd;
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use clap::Parser;
+use pctx_config::Config;
+use pctx_mcp_server::{PctxMcpServer, RestConfig};
+use tracing::info;
+
+use super::StartCmd;
+
+#[derive(Debug, Clone, Parser)]
+pub struct PlaygroundCmd {
+    /// Port to listen on
+    #[arg(short, long, default_value = "8080")]
+    pub port: u16,
+
+    /// Host address to bind to (use 0.0.0.0 for external access)
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Don't show the server banner
+    #[arg(long)]
+    pub no_banner: bool,
+
+    /// Re-fetch upstream tool schemas instead of using the cached schemas in `.pctx/cache`
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Bearer token requests of the playground must send, entered in the page, can be repeated
+    #[arg(
+        long = "rest-token",
+        env = "PCTX_REST_TOKENS",
+        value_delimiter = ',',
+        hide_env_values = true
+    )]
+    pub rest_tokens: Vec<String>,
+}
+
+impl PlaygroundCmd {
+    pub(crate) async fn handle(&self, cfg: Config) -> Result<Config> {
+        if cfg.servers.is_empty() {
+            anyhow::bail!(
+                "No upstream MCP servers configured. Add servers with 'pctx add <name> <url>'"
+            );
+        }
+
+        let code_mode = StartCmd::load_code_mode(&cfg, self.refresh).await?;
+
+        PctxMcpServer::new(&self.host, self.port, !self.no_banner)
+            .with_rest(RestConfig {
+                tokens: self.rest_tokens.clone(),
+            })
+            .with_playground()
+            .serve(&cfg, code_mode)
+            .await?;
+
+        info!("Shutting down...");
+
+        Ok(cfg)
+    }
+}
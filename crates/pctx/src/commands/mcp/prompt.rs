@@ -0,0 +1,45 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::Parser;
+use pctx_config::Config;
+use tracing::info;
+
+use super::StartCmd;
+use crate::utils::styles::{fmt_bold, fmt_success};
+
+#[derive(Debug, Clone, Parser)]
+pub struct PromptCmd {
+    /// File to write the system prompt to, printed to stdout by default
+    #[arg(long, short = 'o')]
+    pub output: Option<Utf8PathBuf>,
+
+    /// Connect to every server instead of using the cached tool schemas in `.pctx/cache`
+    #[arg(long)]
+    pub refresh: bool,
+}
+
+impl PromptCmd {
+    pub(crate) async fn handle(&self, cfg: Config) -> Result<Config> {
+        let code_mode = StartCmd::load_code_mode(&cfg, self.refresh).await?;
+        let prompt = code_mode.system_prompt(&cfg.prompt);
+
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, &prompt)
+                    .with_context(|| format!("Failed writing system prompt to {path}"))?;
+                info!(
+                    "{}",
+                    fmt_success(&format!(
+                        "System prompt written to {}",
+                        fmt_bold(path.as_str())
+                    ))
+                );
+            }
+            None => std::io::stdout().lock().write_all(prompt.as_bytes())?,
+        }
+
+        Ok(cfg)
+    }
+}
@@ -1,10 +1,32 @@
+use std::time::Instant;
+
 use anyhow::Result;
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::Parser;
-use pctx_code_mode::CodeMode;
+use pctx_code_mode::{
+    ArtifactStore, CodeMode, Isolation, KvStore, RemoteWorkers, ResultStore,
+    model::{Script, Skill},
+    schema_cache::SchemaCache,
+};
 use pctx_config::Config;
-use tracing::info;
+use tracing::{info, warn};
+
+use pctx_mcp_server::{PctxMcpServer, RestConfig};
+
+use crate::commands::worker::parse_isolation;
+
+/// Directory of upstream tool schema snapshots, relative to the config file
+const SCHEMA_CACHE_DIR: &str = ".pctx/cache";
 
-use pctx_mcp_server::PctxMcpServer;
+/// Directory of the upstream tool schema snapshots of the config, so the cache
+/// doesn't depend on the directory pctx is run from
+pub(crate) fn schema_cache_dir(cfg: &Config) -> Utf8PathBuf {
+    let config_path = cfg.path();
+    config_path
+        .parent()
+        .unwrap_or(Utf8Path::new(""))
+        .join(SCHEMA_CACHE_DIR)
+}
 
 #[derive(Debug, Clone, Parser)]
 pub struct StartCmd {
@@ -23,18 +45,88 @@ pub struct StartCmd {
     /// Serve MCP over stdio instead of HTTP
     #[arg(long)]
     pub stdio: bool,
+
+    /// Re-fetch upstream tool schemas instead of using the cached schemas in `.pctx/cache`
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// URL of a `pctx worker` executing the code instead of this process, can be repeated
+    #[arg(
+        long = "remote-worker",
+        env = "PCTX_REMOTE_WORKERS",
+        value_delimiter = ','
+    )]
+    pub remote_workers: Vec<String>,
+
+    /// Token sent to the remote workers
+    #[arg(long, env = "PCTX_REMOTE_WORKER_TOKEN", hide_env_values = true)]
+    pub remote_worker_token: Option<String>,
+
+    /// Where code is executed: `in-process` or `process` (a child process per execution)
+    #[arg(long, default_value = "in-process", value_parser = parse_isolation)]
+    pub isolation: Isolation,
+
+    /// Maximum number of MCP tool calls per execution, further calls fail
+    #[arg(long)]
+    pub max_tool_calls: Option<u32>,
+
+    /// Also serve a REST API (`/v1/functions`, `/v1/execute`) with its OpenAPI spec at
+    /// `/v1/openapi.json`
+    #[arg(long, conflicts_with = "stdio")]
+    pub rest: bool,
+
+    /// Bearer token REST API requests must send, can be repeated
+    #[arg(
+        long = "rest-token",
+        env = "PCTX_REST_TOKENS",
+        value_delimiter = ',',
+        hide_env_values = true
+    )]
+    pub rest_tokens: Vec<String>,
 }
 
 impl StartCmd {
-    pub(crate) async fn load_code_mode(cfg: &Config) -> Result<CodeMode> {
-        // Connect to each MCP server and fetch their tool definitions in parallel
+    pub(crate) async fn load_code_mode(cfg: &Config, refresh: bool) -> Result<CodeMode> {
+        // Connect to each MCP server and fetch their tool definitions in parallel,
+        // so startup takes as long as the slowest server instead of the sum of all
+        let timeout_secs = cfg.connect_timeout_secs();
         info!(
-            "Creating code mode interface for {} upstream MCP servers (parallel)",
+            "Creating code mode interface for {} upstream MCP servers (parallel, {timeout_secs}s timeout per server)",
             cfg.servers.len()
         );
-        let code_mode = CodeMode::default().with_servers(&cfg.servers, 30).await?;
+        let started = Instant::now();
+        let mut code_mode = CodeMode::default()
+            .with_schema_cache(SchemaCache::new(schema_cache_dir(cfg)).with_refresh(refresh))
+            .with_type_check(cfg.type_check.clone())
+            .with_imports(cfg.imports.clone())
+            .with_collisions(cfg.collisions.clone())
+            .with_artifacts(ArtifactStore::new(cfg.artifacts.clone()))
+            .with_results(ResultStore::new(cfg.results.clone()))
+            .with_servers(&cfg.upstream_servers(), timeout_secs)
+            .await?;
+        if let Some(path) = &cfg.kv.path {
+            code_mode.set_kv(Some(KvStore::open(path, cfg.kv.clone())?));
+        }
+
+        // scripts are type checked once against the loaded functions
+        let config_path = cfg.path();
+        let config_dir = config_path.parent().unwrap_or(Utf8Path::new(""));
+        for script in &cfg.scripts {
+            let code = script.source(config_dir)?;
+            code_mode
+                .add_script(Script::from_config(script, code))
+                .await?;
+        }
+
+        // skills no longer matching the functions of the servers are skipped
+        for skill in &cfg.skills {
+            if let Err(e) = code_mode.add_skill(Skill::from(skill)).await {
+                warn!("Skipping skill '{}': {e}", skill.name);
+            }
+        }
 
         info!(
+            elapsed_ms = started.elapsed().as_millis(),
             "Code mode initialized with {} upstream MCP servers",
             cfg.servers.len()
         );
@@ -49,9 +141,21 @@ impl StartCmd {
             );
         }
 
-        let code_mode = StartCmd::load_code_mode(&cfg).await?;
+        let code_mode = self.code_mode(&cfg).await?;
 
-        let server = PctxMcpServer::new(&self.host, self.port, !self.no_banner);
+        let mut server = PctxMcpServer::new(&self.host, self.port, !self.no_banner);
+        if !self.stdio {
+            for name in cfg.profiles.keys() {
+                let profile_cfg = cfg.profile(name)?;
+                let profile_code_mode = self.code_mode(&profile_cfg).await?;
+                server = server.with_profile(name, profile_cfg, profile_code_mode);
+            }
+        }
+        if self.rest {
+            server = server.with_rest(RestConfig {
+                tokens: self.rest_tokens.clone(),
+            });
+        }
         if self.stdio {
             server.serve_stdio(&cfg, code_mode).await?;
         } else {
@@ -62,4 +166,29 @@ impl StartCmd {
 
         Ok(cfg)
     }
+
+    /// Code mode of the configured servers, executing code as set by the options
+    async fn code_mode(&self, cfg: &Config) -> Result<CodeMode> {
+        let mut code_mode = StartCmd::load_code_mode(cfg, self.refresh).await?;
+        code_mode.set_remote_workers(self.remote_workers());
+        code_mode.set_isolation(self.isolation.clone());
+        code_mode.set_max_tool_calls(self.max_tool_calls);
+        Ok(code_mode)
+    }
+
+    fn remote_workers(&self) -> Option<RemoteWorkers> {
+        if self.remote_workers.is_empty() {
+            return None;
+        }
+
+        info!(
+            "Executing code on {} remote workers",
+            self.remote_workers.len()
+        );
+        let workers = RemoteWorkers::new(self.remote_workers.clone());
+        Some(match &self.remote_worker_token {
+            Some(token) => workers.with_token(token),
+            None => workers,
+        })
+    }
 }
@@ -1,4 +1,6 @@
+pub(crate) mod grpc;
 pub(crate) mod mcp;
 pub(crate) mod start;
+pub(crate) mod worker;
 
 pub static USER_CANCELLED: &str = "User cancelled";
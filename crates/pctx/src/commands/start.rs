@@ -1,7 +1,14 @@
 use anyhow::Result;
 use camino::Utf8PathBuf;
 use clap::Parser;
-use pctx_session_server::{AppState, start_server};
+use std::time::Duration;
+
+use pctx_session_server::{
+    AppState, ExecutionQueueConfig, HeartbeatConfig, LocalBackend, PctxSessionBackend,
+    RedisBackend, SessionLimits, SqliteBackend, TenantLimits,
+    auth::{AuthConfig, JwtConfig},
+    start_server,
+};
 use tabled::{
     Table,
     builder::Builder,
@@ -33,14 +40,136 @@ pub struct StartCmd {
     #[arg(long, default_value = ".pctx/sessions")]
     pub session_dir: Utf8PathBuf,
 
+    /// Where sessions are stored: `memory`, `sqlite` (in the session directory) or a
+    /// `redis://` URL shared by several instances
+    #[arg(long, default_value = "memory", value_parser = parse_session_store)]
+    pub session_store: SessionStore,
+
     /// Don't show the server banner
     #[arg(long)]
     pub no_banner: bool,
+
+    /// Seconds between pings sent to WebSocket clients
+    #[arg(long, default_value = "30")]
+    pub ws_ping_interval: u64,
+
+    /// Seconds without any message or pong after which a WebSocket connection is
+    /// considered dead and its session removed
+    #[arg(long, default_value = "90")]
+    pub ws_idle_timeout: u64,
+
+    /// Seconds local tool callbacks may take, unless an execution sets its own timeout
+    #[arg(long, default_value = "30")]
+    pub callback_timeout: f64,
+
+    /// Maximum number of tools a session can register
+    #[arg(long)]
+    pub max_tools_per_session: Option<usize>,
+
+    /// Maximum number of concurrently pending callbacks per session
+    #[arg(long)]
+    pub max_pending_callbacks: Option<usize>,
+
+    /// Maximum number of concurrently running code executions, further executions are queued
+    #[arg(long)]
+    pub max_concurrent_executions: Option<usize>,
+
+    /// Maximum number of queued code executions, further executions are rejected
+    #[arg(long, default_value = "100")]
+    pub max_queued_executions: usize,
+
+    /// Token clients must send to use the REST API & WebSocket, can be repeated
+    #[arg(long = "auth-token", env = "PCTX_AUTH_TOKENS", value_delimiter = ',')]
+    pub auth_tokens: Vec<String>,
+
+    /// Token identifying a tenant as `<TENANT>=<TOKEN>`, tenants only access their own sessions
+    #[arg(long = "tenant-token", env = "PCTX_TENANT_TOKENS", value_delimiter = ',', value_parser = parse_tenant_token)]
+    pub tenant_tokens: Vec<(String, String)>,
+
+    /// Secret of HS256 signed JWTs clients may send instead of a static token, JWTs must have an `exp` claim
+    #[arg(long, env = "PCTX_JWT_SECRET", hide_env_values = true)]
+    pub jwt_secret: Option<String>,
+
+    /// Required `iss` claim of JWTs
+    #[arg(long, requires = "jwt_secret")]
+    pub jwt_issuer: Option<String>,
+
+    /// Required `aud` claim of JWTs
+    #[arg(long, requires = "jwt_secret")]
+    pub jwt_audience: Option<String>,
+
+    /// Claim of JWTs identifying the tenant (e.g. `sub`), JWTs without it are rejected
+    #[arg(long, requires = "jwt_secret")]
+    pub jwt_tenant_claim: Option<String>,
+
+    /// Maximum number of open sessions per tenant
+    #[arg(long)]
+    pub max_sessions_per_tenant: Option<usize>,
+
+    /// Maximum number of code executions per minute per tenant
+    #[arg(long)]
+    pub max_executions_per_minute: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionStore {
+    Memory,
+    Sqlite,
+    Redis(String),
+}
+
+fn parse_session_store(value: &str) -> Result<SessionStore, String> {
+    match value {
+        "memory" => Ok(SessionStore::Memory),
+        "sqlite" => Ok(SessionStore::Sqlite),
+        url if url.starts_with("redis://") || url.starts_with("rediss://") => {
+            Ok(SessionStore::Redis(url.to_string()))
+        }
+        _ => Err("expected `memory`, `sqlite` or a `redis://` URL".into()),
+    }
+}
+
+fn parse_tenant_token(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((tenant, token)) if !tenant.is_empty() && !token.is_empty() => {
+            Ok((tenant.to_string(), token.to_string()))
+        }
+        _ => Err("expected <TENANT>=<TOKEN>".into()),
+    }
 }
 
 impl StartCmd {
     pub(crate) async fn handle(&self) -> Result<()> {
-        let state = AppState::new_local();
+        match &self.session_store {
+            SessionStore::Memory => self.serve(LocalBackend::default()).await,
+            SessionStore::Sqlite => {
+                let backend = SqliteBackend::open(self.session_dir.join("sessions.db"))?;
+                self.serve(backend).await
+            }
+            SessionStore::Redis(url) => self.serve(RedisBackend::connect(url).await?).await,
+        }
+    }
+
+    async fn serve<B: PctxSessionBackend>(&self, backend: B) -> Result<()> {
+        let state = AppState::new(backend)
+            .with_auth(self.auth())
+            .with_heartbeat(HeartbeatConfig {
+                ping_interval: Duration::from_secs(self.ws_ping_interval),
+                idle_timeout: Duration::from_secs(self.ws_idle_timeout),
+            })
+            .with_limits(SessionLimits {
+                callback_timeout: Duration::from_secs_f64(self.callback_timeout),
+                max_tools: self.max_tools_per_session,
+                max_pending_executions: self.max_pending_callbacks,
+            })
+            .with_execution_queue(ExecutionQueueConfig {
+                max_concurrent: self.max_concurrent_executions,
+                max_queued: self.max_queued_executions,
+            })
+            .with_tenant_limits(TenantLimits {
+                max_sessions: self.max_sessions_per_tenant,
+                max_executions_per_minute: self.max_executions_per_minute,
+            });
 
         self.print_banner();
 
@@ -49,6 +178,27 @@ impl StartCmd {
         Ok(())
     }
 
+    fn auth(&self) -> AuthConfig {
+        let mut auth = AuthConfig::default().with_tokens(self.auth_tokens.clone());
+        for (tenant, token) in &self.tenant_tokens {
+            auth = auth.with_tenant_token(tenant, token);
+        }
+        if let Some(secret) = &self.jwt_secret {
+            let mut jwt = JwtConfig::new(secret.as_bytes());
+            if let Some(issuer) = &self.jwt_issuer {
+                jwt = jwt.with_issuer(issuer);
+            }
+            if let Some(audience) = &self.jwt_audience {
+                jwt = jwt.with_audience(audience);
+            }
+            if let Some(claim) = &self.jwt_tenant_claim {
+                jwt = jwt.with_tenant_claim(claim);
+            }
+            auth = auth.with_jwt(jwt);
+        }
+        auth
+    }
+
     fn print_banner(&self) {
         let rest_url = format!("http://{}:{}", self.host, self.port);
         let ws_url = format!("ws://{}:{}/ws", self.host, self.port);
@@ -67,6 +217,20 @@ impl StartCmd {
             builder.push_record(["Mode", "Agent"]);
             builder.push_record(["REST API", &rest_url]);
             builder.push_record(["WebSocket", &ws_url]);
+            match &self.session_store {
+                SessionStore::Memory => {}
+                SessionStore::Sqlite => {
+                    builder
+                        .push_record(["Sessions", self.session_dir.join("sessions.db").as_str()]);
+                }
+                SessionStore::Redis(_) => builder.push_record(["Sessions", "redis"]),
+            }
+            if !self.auth_tokens.is_empty()
+                || !self.tenant_tokens.is_empty()
+                || self.jwt_secret.is_some()
+            {
+                builder.push_record(["Auth", "token required"]);
+            }
             builder.push_record(["Docs", &fmt_dimmed("https://github.com/portofcontext/pctx")]);
 
             let table_width = (term_width).min(80) as usize;
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use clap::Parser;
+use pctx_code_mode::{Isolation, ProcessIsolation};
+
+use pctx_session_server::{
+    ExecutionQueue, ExecutionQueueConfig,
+    auth::AuthConfig,
+    worker::{WorkerState, start_worker},
+};
+
+#[derive(Debug, Clone, Parser)]
+pub struct WorkerCmd {
+    /// Port to listen on
+    #[arg(short, long, default_value = "8090")]
+    pub port: u16,
+
+    /// Host address to bind to (use 0.0.0.0 for external access)
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Maximum number of concurrently running code executions, further executions are queued
+    #[arg(long)]
+    pub max_concurrent_executions: Option<usize>,
+
+    /// Maximum number of queued code executions, further executions are rejected so
+    /// servers fail over to another worker
+    #[arg(long, default_value = "100")]
+    pub max_queued_executions: usize,
+
+    /// Token servers must send to execute code, can be repeated. Required unless the
+    /// worker binds to a loopback address
+    #[arg(long = "auth-token", env = "PCTX_WORKER_TOKENS", value_delimiter = ',')]
+    pub auth_tokens: Vec<String>,
+
+    /// Where code is executed: `in-process` or `process` (a child process per execution)
+    #[arg(long, default_value = "in-process", value_parser = parse_isolation)]
+    pub isolation: Isolation,
+}
+
+pub(crate) fn parse_isolation(value: &str) -> Result<Isolation, String> {
+    match value {
+        "in-process" => Ok(Isolation::InProcess),
+        "process" => ProcessIsolation::current_exe()
+            .map(Isolation::Process)
+            .map_err(|e| format!("failed locating the pctx executable: {e}")),
+        _ => Err("expected `in-process` or `process`".into()),
+    }
+}
+
+impl WorkerCmd {
+    pub(crate) async fn handle(&self) -> Result<()> {
+        let state = WorkerState {
+            auth: AuthConfig::default().with_tokens(self.auth_tokens.clone()),
+            executions: ExecutionQueue::new(ExecutionQueueConfig {
+                max_concurrent: self.max_concurrent_executions,
+                max_queued: self.max_queued_executions,
+            }),
+            isolation: self.isolation.clone(),
+            ..Default::default()
+        };
+
+        start_worker(&self.host, self.port, state).await
+    }
+}
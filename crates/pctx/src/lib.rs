@@ -6,8 +6,11 @@ use clap::{Parser, Subcommand};
 use serde_json::json;
 use std::io::{self, Write};
 
-use crate::utils::{logger::init_cli_logger, telemetry::init_telemetry};
-use pctx_config::Config;
+use crate::utils::{
+    logger::{init_cli_logger, parse_log_format},
+    telemetry::init_telemetry,
+};
+use pctx_config::{Config, logger::LoggerFormat};
 
 #[derive(Parser)]
 #[command(name = "pctx")]
@@ -39,13 +42,18 @@ pub struct Cli {
     /// Verbose logging (-v) or trace logging (-vv)
     #[arg(long, short = 'v', action = clap::ArgAction::Count, global = true)]
     pub verbose: u8,
+
+    /// Log format: `compact`, `pretty` or `json` (one JSON object per line), overrides the
+    /// `logger.format` of the config
+    #[arg(long, global = true, env = "PCTX_LOG_FORMAT", value_parser = parse_log_format)]
+    pub log_format: Option<LoggerFormat>,
 }
 
 impl Cli {
     fn cli_logger(&self) -> bool {
         !matches!(
             &self.command,
-            Commands::Mcp(McpCommands::Start(_) | McpCommands::Dev(_))
+            Commands::Mcp(McpCommands::Start(_) | McpCommands::Dev(_) | McpCommands::Playground(_))
         )
     }
 
@@ -57,22 +65,42 @@ impl Cli {
         }
     }
 
+    /// Config with the logger format of the `--log-format` flag
+    fn with_log_format(&self, mut cfg: Config) -> Config {
+        if let Some(format) = &self.log_format {
+            cfg.logger.format = format.clone();
+        }
+        cfg
+    }
+
     #[allow(clippy::missing_errors_doc)]
     pub async fn handle(&self) -> anyhow::Result<()> {
         match &self.command {
             Commands::Mcp(mcp_cmd) => self.handle_mcp(mcp_cmd).await,
             Commands::Start(start_cmd) => {
-                let cfg = Config::load(&self.config).unwrap_or_default();
+                let cfg = self.with_log_format(Config::load(&self.config).unwrap_or_default());
                 // Session server uses stdout for logs (not stdio protocol)
                 init_telemetry(&cfg, None, false).await?;
 
                 start_cmd.handle().await
             }
+            Commands::Worker(worker_cmd) => {
+                let cfg = self.with_log_format(Config::load(&self.config).unwrap_or_default());
+                init_telemetry(&cfg, None, false).await?;
+
+                worker_cmd.handle().await
+            }
+            Commands::Grpc(grpc_cmd) => {
+                let cfg = self.with_log_format(Config::load(&self.config).unwrap_or_default());
+                init_telemetry(&cfg, None, false).await?;
+
+                grpc_cmd.handle().await
+            }
         }
     }
 
     async fn handle_mcp(&self, cmd: &McpCommands) -> anyhow::Result<()> {
-        let cfg = Config::load(&self.config);
+        let cfg = Config::load(&self.config).map(|cfg| self.with_log_format(cfg));
 
         if let (McpCommands::Start(start_cmd), Err(err)) = (cmd, &cfg)
             && start_cmd.stdio
@@ -81,7 +109,12 @@ impl Cli {
         }
 
         if self.cli_logger() {
-            init_cli_logger(self.verbose, self.quiet);
+            // the flag already applies to a loaded config
+            let json = match &cfg {
+                Ok(c) => matches!(c.logger.format, LoggerFormat::Json),
+                Err(_) => matches!(self.log_format, Some(LoggerFormat::Json)),
+            };
+            init_cli_logger(self.verbose, self.quiet, json);
         } else if let Ok(c) = &cfg {
             // Use stderr for stdio mode to keep stdout clean for JSON-RPC
             let use_stderr = matches!(cmd, McpCommands::Start(start_cmd) if start_cmd.stdio);
@@ -93,8 +126,12 @@ impl Cli {
             McpCommands::List(cmd) => cmd.handle(cfg?).await?,
             McpCommands::Add(cmd) => cmd.handle(cfg?, true).await?,
             McpCommands::Remove(cmd) => cmd.handle(cfg?)?,
+            McpCommands::Auth(cmd) => cmd.handle(cfg?)?,
+            McpCommands::Docs(cmd) => cmd.handle(cfg?).await?,
+            McpCommands::Prompt(cmd) => cmd.handle(cfg?).await?,
             McpCommands::Start(cmd) => cmd.handle(cfg?).await?,
             McpCommands::Dev(cmd) => cmd.handle(cfg?).await?,
+            McpCommands::Playground(cmd) => cmd.handle(cfg?).await?,
         };
 
         Ok(())
@@ -147,6 +184,18 @@ pub enum Commands {
     )]
     Start(commands::start::StartCmd),
 
+    /// Start a worker executing code for remote servers
+    #[command(
+        long_about = "Starts a worker executing the code of servers started with `--remote-worker`, so untrusted code runs in a separate pool of processes or machines."
+    )]
+    Worker(commands::worker::WorkerCmd),
+
+    /// Start a gRPC server for code mode sessions
+    #[command(
+        long_about = "Starts a gRPC server for code mode sessions, defined by `pctx.v1.CodeMode` in crates/pctx_grpc_server/proto. Clients register tools, answer their calls over a callback stream and execute code with streamed results."
+    )]
+    Grpc(commands::grpc::GrpcCmd),
+
     /// MCP server commands (with pctx.json configuration)
     #[command(subcommand)]
     Mcp(McpCommands),
@@ -172,6 +221,25 @@ pub enum McpCommands {
     #[command(long_about = "Remove an MCP server from the configuration.")]
     Remove(commands::mcp::RemoveCmd),
 
+    /// Manage upstream MCP server credentials in the system keychain
+    #[command(
+        subcommand,
+        long_about = "Store upstream MCP server credentials in the system keychain, referenced from the configuration instead of written into it."
+    )]
+    Auth(commands::mcp::AuthCommands),
+
+    /// Generate documentation of the aggregated tool catalog
+    #[command(
+        long_about = "Render the functions of all upstream MCP servers with their descriptions, signatures and example calls as Markdown or HTML, e.g. for publishing to internal wikis."
+    )]
+    Docs(commands::mcp::DocsCmd),
+
+    /// Generate the recommended system prompt for agents using code mode
+    #[command(
+        long_about = "Render the system prompt preamble for agents using code mode with the available namespaces, code conventions and error handling guidance, customizable with the `prompt` section of the configuration."
+    )]
+    Prompt(commands::mcp::PromptCmd),
+
     /// Start the PCTX MCP server
     #[command(long_about = "Start the PCTX MCP server (exposes /mcp endpoint).")]
     Start(commands::mcp::StartCmd),
@@ -181,4 +249,10 @@ pub enum McpCommands {
         long_about = "Start the PCTX MCP server in development mode with an interactive terminal UI with data and logging."
     )]
     Dev(commands::mcp::DevCmd),
+
+    /// Start the PCTX MCP server with a browser playground
+    #[command(
+        long_about = "Start the PCTX MCP server with the REST API and a web UI at /playground showing the tool catalog and a code editor with the generated typings, executing code on the server. A web alternative to `pctx mcp dev` for demos and debugging."
+    )]
+    Playground(commands::mcp::PlaygroundCmd),
 }
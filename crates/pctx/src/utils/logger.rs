@@ -1,5 +1,8 @@
 use std::io::Write;
 
+use pctx_config::logger::LoggerFormat;
+use tracing_subscriber::EnvFilter;
+
 const WHITELISTED_CRATES: &[&str] = &[
     "pctx",
     "pctx_mcp_server",
@@ -21,7 +24,17 @@ pub(crate) fn default_env_filter(level: &str) -> String {
     filters.join(",")
 }
 
-pub(crate) fn init_cli_logger(verbose: u8, quiet: bool) {
+pub(crate) fn parse_log_format(value: &str) -> Result<LoggerFormat, String> {
+    match value {
+        "compact" => Ok(LoggerFormat::Compact),
+        "pretty" => Ok(LoggerFormat::Pretty),
+        "json" => Ok(LoggerFormat::Json),
+        _ => Err("expected `compact`, `pretty` or `json`".into()),
+    }
+}
+
+/// Logger of CLI commands writing to stderr, as JSON lines like the server logs if `json`
+pub(crate) fn init_cli_logger(verbose: u8, quiet: bool, json: bool) {
     let level_str = if quiet {
         "warn"
     } else if verbose == 0 {
@@ -32,6 +45,20 @@ pub(crate) fn init_cli_logger(verbose: u8, quiet: bool) {
         "trace"
     };
 
+    if json {
+        let env_filter = EnvFilter::try_from_default_env()
+            .unwrap_or(EnvFilter::new(default_env_filter(level_str)));
+        let init = tracing_subscriber::fmt()
+            .json()
+            .with_writer(std::io::stderr)
+            .with_env_filter(env_filter)
+            .try_init();
+        if let Err(e) = init {
+            eprintln!("pctx: Failed initializing JSON logger: {e:?}");
+        }
+        return;
+    }
+
     let mut builder = env_logger::Builder::from_env(
         env_logger::Env::default().default_filter_or(default_env_filter(level_str)),
     );
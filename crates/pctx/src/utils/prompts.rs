@@ -1,7 +1,9 @@
 use anyhow::Result;
 use indexmap::IndexMap;
 use pctx_codegen::case::Case;
-use pctx_config::auth::{AuthConfig, AuthSecret, SecretString, write_to_keychain};
+use pctx_config::auth::{
+    AuthConfig, AuthSecret, BearerAuth, HeadersAuth, SecretString, write_to_keychain,
+};
 use tracing::info;
 
 use crate::utils::styles::{fmt_dimmed, fmt_success};
@@ -22,7 +24,7 @@ pub(crate) fn prompt_auth(server_name: &str) -> Result<AuthConfig> {
             // bearer token
             let bearer_key = Case::Snake.sanitize(format!("{server_name}_bearer"));
             let token = prompt_secret("Select auth option for bearer token:", "", &bearer_key)?;
-            Ok(AuthConfig::Bearer { token })
+            Ok(AuthConfig::Bearer(BearerAuth { token }))
         }
 
         Some(1) => {
@@ -45,7 +47,7 @@ pub(crate) fn prompt_auth(server_name: &str) -> Result<AuthConfig> {
                     .prompt()?;
             }
 
-            Ok(AuthConfig::Headers { headers })
+            Ok(AuthConfig::Headers(HeadersAuth { headers }))
         }
         // Some(2) => {
         //     // OAuth2
@@ -63,12 +65,12 @@ pub(crate) fn prompt_auth(server_name: &str) -> Result<AuthConfig> {
         //         .with_help_message("comma separated scopes, leave empty if does not apply")
         //         .prompt_skippable()?;
 
-        //     Ok(AuthConfig::OAuthClientCredentials {
+        //     Ok(AuthConfig::OAuthClientCredentials(OAuthClientCredentialsAuth {
         //         client_id,
         //         client_secret,
         //         token_url: token_url.parse()?,
         //         scope,
-        //     })
+        //     }))
         // }
         _ => anyhow::bail!("Invalid selection {selection}"),
     }
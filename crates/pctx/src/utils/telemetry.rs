@@ -135,9 +135,11 @@ where
             .with_ansi(colors)
             .pretty()
             .boxed(),
+        // JSON lines stay free of color codes, fields of the current spans (e.g. `request_id`,
+        // `execution_id`) are included
         LoggerFormat::Json => tracing_subscriber::fmt::layer()
             .with_writer(make_writer)
-            .with_ansi(colors)
+            .with_ansi(false)
             .json()
             .boxed(),
     }
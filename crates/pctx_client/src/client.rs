@@ -0,0 +1,390 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use rmcp::{
+    ClientHandler, RoleClient, ServiceExt,
+    model::{
+        CallToolRequestParams, ClientCapabilities, ClientInfo, Implementation, JsonObject, Meta,
+        NumberOrString, ProgressNotificationParam, ProgressToken, ProtocolVersion, RawContent,
+    },
+    service::{NotificationContext, Peer, RunningService},
+    transport::{
+        StreamableHttpClientTransport, streamable_http_client::StreamableHttpClientTransportConfig,
+    },
+};
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::sync::mpsc;
+
+use crate::{
+    Error, Result,
+    model::{
+        CheckCodeInput, CheckCodeOutput, ExecuteInput, ExecuteOutput, ExecuteProgress,
+        GetFunctionDetailsInput, GetFunctionDetailsOutput, ListFunctionsInput, ListFunctionsOutput,
+        SearchFunctionsInput, SearchFunctionsOutput,
+    },
+};
+
+/// Connection options of a [`Client`]
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// URL of the MCP endpoint, e.g. `http://localhost:8080/mcp`
+    pub url: String,
+    /// Token sent as `Authorization: Bearer <token>`
+    pub bearer_token: Option<String>,
+    /// Additional headers sent with every request
+    pub headers: Vec<(String, String)>,
+}
+
+impl ClientOptions {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            bearer_token: None,
+            headers: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    fn http_client(&self) -> Result<reqwest::Client> {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &self.bearer_token {
+            headers.insert(AUTHORIZATION, header_value(&format!("Bearer {token}"))?);
+        }
+        for (name, value) in &self.headers {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|e| Error::InvalidRequest(format!("Invalid header name {name}: {e}")))?;
+            headers.append(name, header_value(value)?);
+        }
+
+        reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(|e| Error::Connect(e.to_string()))
+    }
+}
+
+fn header_value(value: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value)
+        .map_err(|e| Error::InvalidRequest(format!("Invalid header value: {e}")))
+}
+
+type ProgressStreams = Arc<Mutex<HashMap<String, mpsc::UnboundedSender<ExecuteEvent>>>>;
+
+/// Routes progress notifications of the server to the streams of their executions
+#[derive(Clone, Default)]
+struct ProgressRouter {
+    streams: ProgressStreams,
+}
+
+impl ClientHandler for ProgressRouter {
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            protocol_version: ProtocolVersion::default(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: "pctx_client".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                ..Default::default()
+            },
+            meta: None,
+        }
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        let ProgressToken(NumberOrString::String(token)) = &params.progress_token else {
+            return;
+        };
+        if let Some(events) = self.streams.lock().unwrap().get(token.as_ref()) {
+            let _ = events.send(ExecuteEvent::Progress(ExecuteProgress {
+                progress: params.progress,
+                total: params.total,
+                message: params.message,
+            }));
+        }
+    }
+}
+
+// progress tokens of streamed executions, unique per process
+static PROGRESS_TOKENS: AtomicU64 = AtomicU64::new(0);
+
+/// Client of a remote pctx MCP server
+///
+/// ```ignore
+/// use pctx_client::{Client, model::ExecuteInput};
+///
+/// let client = Client::connect("http://localhost:8080/mcp").await?;
+/// let listed = client.list_functions(&Default::default()).await?;
+/// let output = client
+///     .execute(&ExecuteInput::new("async function run() { return 1 + 1; }"))
+///     .await?;
+/// ```
+pub struct Client {
+    service: RunningService<RoleClient, ProgressRouter>,
+}
+
+impl Client {
+    /// Connects to the pctx MCP server at the URL
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the server can't be reached or the MCP
+    /// session can't be initialized
+    pub async fn connect(url: impl Into<String>) -> Result<Self> {
+        Self::connect_with(ClientOptions::new(url)).await
+    }
+
+    /// Connects to the pctx MCP server with the options
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a header is invalid, the server can't be
+    /// reached or the MCP session can't be initialized
+    pub async fn connect_with(options: ClientOptions) -> Result<Self> {
+        let transport = StreamableHttpClientTransport::with_client(
+            options.http_client()?,
+            StreamableHttpClientTransportConfig {
+                uri: options.url.as_str().into(),
+                ..Default::default()
+            },
+        );
+        let service = ProgressRouter::default()
+            .serve(transport)
+            .await
+            .map_err(|e| Error::Connect(e.to_string()))?;
+
+        Ok(Self { service })
+    }
+
+    /// Lists the functions available to executed code
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the call fails or its response is invalid
+    pub async fn list_functions(&self, input: &ListFunctionsInput) -> Result<ListFunctionsOutput> {
+        call_tool(
+            self.service.peer(),
+            "list_functions",
+            arguments(input)?,
+            None,
+        )
+        .await
+    }
+
+    /// Searches the functions relevant to a query
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the call fails or its response is invalid
+    pub async fn search_functions(
+        &self,
+        input: &SearchFunctionsInput,
+    ) -> Result<SearchFunctionsOutput> {
+        call_tool(
+            self.service.peer(),
+            "search_functions",
+            arguments(input)?,
+            None,
+        )
+        .await
+    }
+
+    /// Returns the TypeScript declarations & types of functions
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the call fails or its response is invalid
+    pub async fn get_function_details(
+        &self,
+        input: &GetFunctionDetailsInput,
+    ) -> Result<GetFunctionDetailsOutput> {
+        call_tool(
+            self.service.peer(),
+            "get_function_details",
+            arguments(input)?,
+            None,
+        )
+        .await
+    }
+
+    /// Type checks code without executing it, only available if the server exposes `check_code`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the call fails or its response is invalid
+    pub async fn check_code(&self, input: &CheckCodeInput) -> Result<CheckCodeOutput> {
+        call_tool(self.service.peer(), "check_code", arguments(input)?, None).await
+    }
+
+    /// Executes code, failed code is reported in the output rather than as an error
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the call fails or its response is invalid
+    pub async fn execute(&self, input: &ExecuteInput) -> Result<ExecuteOutput> {
+        call_tool(self.service.peer(), "execute", arguments(input)?, None).await
+    }
+
+    /// Executes code, streaming the progress reported by its tool calls followed by
+    /// the output. Must be called within a tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the input can't be serialized
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn execute_stream(&self, input: &ExecuteInput) -> Result<ExecuteStream> {
+        let arguments = arguments(input)?;
+        let token = format!(
+            "pctx-client-{}",
+            PROGRESS_TOKENS.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut meta = Meta::new();
+        meta.set_progress_token(ProgressToken(NumberOrString::String(token.clone().into())));
+
+        let (events, receiver) = mpsc::unbounded_channel();
+        let streams = self.service.service().streams.clone();
+        streams
+            .lock()
+            .unwrap()
+            .insert(token.clone(), events.clone());
+
+        let peer = self.service.peer().clone();
+        tokio::spawn(async move {
+            let output = call_tool(&peer, "execute", arguments, Some(meta)).await;
+            streams.lock().unwrap().remove(&token);
+            let _ = events.send(ExecuteEvent::Finished(output));
+        });
+
+        Ok(ExecuteStream { events: receiver })
+    }
+
+    /// Closes the MCP session
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the session task failed
+    pub async fn close(self) -> Result<()> {
+        self.service
+            .cancel()
+            .await
+            .map(|_| ())
+            .map_err(|e| Error::Connect(e.to_string()))
+    }
+}
+
+/// Event of an execution streamed by [`Client::execute_stream`]
+#[derive(Debug)]
+pub enum ExecuteEvent {
+    Progress(ExecuteProgress),
+    /// Output of the execution, always the last event
+    Finished(Result<ExecuteOutput>),
+}
+
+/// Events of a streamed execution
+pub struct ExecuteStream {
+    events: mpsc::UnboundedReceiver<ExecuteEvent>,
+}
+
+impl ExecuteStream {
+    /// Waits for the output of the execution, skipping its progress
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the execution failed or was dropped
+    pub async fn output(mut self) -> Result<ExecuteOutput> {
+        while let Some(event) = self.events.recv().await {
+            if let ExecuteEvent::Finished(output) = event {
+                return output;
+            }
+        }
+        Err(Error::Connect(
+            "Execution ended without an output".to_string(),
+        ))
+    }
+}
+
+impl Stream for ExecuteStream {
+    type Item = ExecuteEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+fn arguments(input: &impl Serialize) -> Result<JsonObject> {
+    match serde_json::to_value(input) {
+        Ok(serde_json::Value::Object(arguments)) => Ok(arguments),
+        Ok(other) => Err(Error::InvalidRequest(format!(
+            "Tool arguments must be an object, got {other}"
+        ))),
+        Err(e) => Err(Error::InvalidRequest(e.to_string())),
+    }
+}
+
+async fn call_tool<O: DeserializeOwned>(
+    peer: &Peer<RoleClient>,
+    tool: &str,
+    arguments: JsonObject,
+    meta: Option<Meta>,
+) -> Result<O> {
+    let result = peer
+        .call_tool(CallToolRequestParams {
+            name: tool.to_string().into(),
+            arguments: Some(arguments),
+            task: None,
+            meta,
+        })
+        .await?;
+
+    if result.is_error.unwrap_or(false) {
+        let message = result
+            .content
+            .iter()
+            .find_map(|c| match &**c {
+                RawContent::Text(text) => Some(text.text.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| "Unknown error".to_string());
+        return Err(Error::Tool {
+            tool: tool.into(),
+            message,
+        });
+    }
+
+    let structured = result
+        .structured_content
+        .ok_or_else(|| Error::InvalidResponse {
+            tool: tool.into(),
+            message: "Missing structured content".into(),
+        })?;
+    serde_json::from_value(structured).map_err(|e| Error::InvalidResponse {
+        tool: tool.into(),
+        message: e.to_string(),
+    })
+}
@@ -0,0 +1,48 @@
+//! # pctx client
+//!
+//! Typed Rust client of the pctx MCP server, for agent frameworks using a remote pctx
+//! without embedding the code mode engine. It speaks the code mode tools over
+//! streamable HTTP with typed [`model`] requests & responses:
+//!
+//! - [`Client::list_functions`] / [`Client::search_functions`] - Find the available functions
+//! - [`Client::get_function_details`] - TypeScript declarations & types of functions
+//! - [`Client::check_code`] - Type check code without executing it
+//! - [`Client::execute`] - Execute code and return its output
+//! - [`Client::execute_stream`] - Execute code, streaming the progress of its tool calls
+//!
+//! ```ignore
+//! use futures::StreamExt;
+//! use pctx_client::{Client, ClientOptions, ExecuteEvent, model::ExecuteInput};
+//!
+//! let client =
+//!     Client::connect_with(ClientOptions::new("https://pctx.internal/mcp").with_bearer_token(token))
+//!         .await?;
+//!
+//! let mut events = client.execute_stream(&ExecuteInput::new(code))?;
+//! while let Some(event) = events.next().await {
+//!     match event {
+//!         ExecuteEvent::Progress(progress) => println!("{:?}", progress.message),
+//!         ExecuteEvent::Finished(output) => println!("{:?}", output?.output),
+//!     }
+//! }
+//! ```
+
+mod client;
+pub mod model;
+
+pub use client::{Client, ClientOptions, ExecuteEvent, ExecuteStream};
+
+pub type Result<T> = std::result::Result<T, Error>;
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Connection error: {0}")]
+    Connect(String),
+    #[error("MCP Service error: {0}")]
+    Service(#[from] rmcp::ServiceError),
+    #[error("Tool `{tool}` failed: {message}")]
+    Tool { tool: String, message: String },
+    #[error("Invalid response of tool `{tool}`: {message}")]
+    InvalidResponse { tool: String, message: String },
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+}
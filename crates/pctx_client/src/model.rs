@@ -0,0 +1,341 @@
+//! Requests & responses of the pctx MCP tools
+//!
+//! These mirror the inputs and structured outputs of the `list_functions`,
+//! `search_functions`, `get_function_details`, `check_code` & `execute` tools.
+//! Responses ignore fields they don't know, so newer servers stay compatible.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+// -------------- Interfaces --------------
+
+/// Size of a code interface, tokens are estimated from the characters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceSize {
+    pub chars: usize,
+    pub tokens: usize,
+}
+
+/// How an interface was reduced to fit a token budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterfaceTrim {
+    /// Descriptions were shortened to their first sentence
+    Descriptions,
+    /// Descriptions were removed, leaving the signatures
+    Signatures,
+}
+
+/// How much of the functions' TypeScript interfaces to return
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetailLevel {
+    /// Only the names of the functions, as `Namespace.functionName` lines
+    Name,
+    /// Signatures & descriptions of the functions, referring to their types by name
+    Signature,
+    /// Signatures & descriptions of the functions with the definitions of their types
+    Full,
+}
+
+// -------------- List Functions --------------
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListFunctionsInput {
+    /// Defaults to [`DetailLevel::Signature`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<DetailLevel>,
+    /// Maximum estimated tokens of the returned code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListFunctionsOutput {
+    pub functions: Vec<ListedFunction>,
+    /// TypeScript declarations of the functions
+    pub code: String,
+    #[serde(default)]
+    pub size: InterfaceSize,
+    #[serde(default)]
+    pub trimmed: Option<InterfaceTrim>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListedFunction {
+    pub namespace: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub size: InterfaceSize,
+}
+
+impl ListedFunction {
+    /// Identifier of the function as called in code, e.g. `Namespace.functionName`
+    pub fn id(&self) -> String {
+        format!("{}.{}", self.namespace, self.name)
+    }
+}
+
+// -------------- Get Function Details --------------
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GetFunctionDetailsInput {
+    /// Functions as `Namespace.functionName`
+    pub functions: Vec<String>,
+    /// Defaults to [`DetailLevel::Full`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<DetailLevel>,
+    /// Maximum estimated tokens of the returned code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetFunctionDetailsOutput {
+    pub functions: Vec<FunctionDetails>,
+    /// TypeScript declarations of the functions & their types
+    pub code: String,
+    #[serde(default)]
+    pub size: InterfaceSize,
+    #[serde(default)]
+    pub trimmed: Option<InterfaceTrim>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FunctionDetails {
+    #[serde(flatten)]
+    pub listed: ListedFunction,
+    /// TypeScript input type of the function
+    pub input_type: String,
+    /// TypeScript output type of the function
+    pub output_type: String,
+    /// TypeScript definitions of the input & output types
+    pub types: String,
+}
+
+// -------------- Search Functions --------------
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchFunctionsInput {
+    /// Keywords describing what to do, e.g. "account balance"
+    pub query: String,
+    /// Maximum number of functions to return, defaults to `10`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchFunctionsOutput {
+    /// Matching functions, most relevant first
+    pub functions: Vec<SearchedFunction>,
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchedFunction {
+    #[serde(flatten)]
+    pub listed: ListedFunction,
+    /// Relevance of the function to the query, higher is better
+    pub score: f64,
+}
+
+// -------------- Check Code --------------
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckCodeInput {
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckCodeOutput {
+    pub success: bool,
+    pub diagnostics: Vec<CodeDiagnostic>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CodeDiagnostic {
+    pub message: String,
+    /// Line of the error in the code (1-indexed)
+    pub line: Option<usize>,
+    /// Column of the error in the code (1-indexed)
+    pub column: Option<usize>,
+    /// TypeScript diagnostic code, e.g. `2322` for type mismatches
+    pub code: Option<u32>,
+    #[serde(default)]
+    pub suggestion: Option<String>,
+}
+
+impl Display for CodeDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "Line {line}")?;
+            if let Some(column) = self.column {
+                write!(f, ", Column {column}")?;
+            }
+            write!(f, ": ")?;
+        }
+        if let Some(code) = self.code {
+            write!(f, "TS{code} ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+// -------------- Execute --------------
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExecuteInput {
+    /// TypeScript code defining `async function run()`
+    pub code: String,
+    /// Skip type checking, type errors surface as runtime errors instead
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub skip_type_check: bool,
+    /// Value passed as the argument of `run(input)`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<serde_json::Value>,
+    /// Profile whose functions the code calls
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// MCP servers connected for this execution, if the server allows ephemeral servers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<serde_json::Value>,
+    /// `JSONPath` selecting the parts of the returned value to include, e.g. `$.items[0:10].title`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projection: Option<String>,
+    /// Executions waiting for a free slot of the server start by priority
+    #[serde(skip_serializing_if = "ExecutePriority::is_interactive")]
+    pub priority: ExecutePriority,
+}
+
+/// Scheduling priority of an execution on a busy server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutePriority {
+    /// Executions a user is waiting for, e.g. agent turns
+    #[default]
+    Interactive,
+    /// Executions nobody is waiting for, e.g. scheduled batch jobs
+    Background,
+}
+
+impl ExecutePriority {
+    pub fn is_interactive(&self) -> bool {
+        *self == Self::Interactive
+    }
+}
+
+impl ExecuteInput {
+    pub fn new(code: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExecuteOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    /// Value returned by `run`
+    pub output: Option<serde_json::Value>,
+    /// Whether the code was executed without type checking
+    #[serde(default)]
+    pub unchecked: bool,
+    /// Type errors that prevented the code from executing
+    #[serde(default)]
+    pub diagnostics: Vec<CodeDiagnostic>,
+    /// Tools registered during the execution
+    #[serde(default)]
+    pub registered_tools: Vec<RegisteredTool>,
+    /// Steps recorded with `pctx.step`
+    #[serde(default)]
+    pub steps: Vec<StepRecord>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegisteredTool {
+    pub name: String,
+    pub namespace: String,
+    pub description: Option<String>,
+    pub input_schema: Option<serde_json::Value>,
+    pub output_schema: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepRecord {
+    pub name: String,
+    pub status: StepStatus,
+    /// Start of the step in milliseconds since the Unix epoch
+    pub started_at: u64,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<StepRecord>,
+}
+
+/// Progress of an execution streamed by [`Client::execute_stream`](crate::Client::execute_stream),
+/// reported by the tool calls of the executed code
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecuteProgress {
+    /// Number of progress notifications so far
+    pub progress: f64,
+    pub total: Option<f64>,
+    /// Progress message, prefixed with the upstream server, e.g. `[github] 2/10`
+    pub message: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ExecuteInput, ExecuteOutput, ListFunctionsOutput, StepStatus};
+
+    #[test]
+    fn test_execute_models() {
+        let input = ExecuteInput::new("async function run() { return 1; }");
+        assert_eq!(
+            serde_json::to_value(&input).unwrap(),
+            json!({ "code": "async function run() { return 1; }" })
+        );
+
+        let output: ExecuteOutput = serde_json::from_value(json!({
+            "success": true,
+            "stdout": "",
+            "stderr": "",
+            "output": { "n": 1 },
+            "steps": [{ "name": "load", "status": "succeeded", "startedAt": 1, "durationMs": 2 }],
+            "added_later": true
+        }))
+        .unwrap();
+        assert_eq!(output.output, Some(json!({ "n": 1 })));
+        assert_eq!(output.steps[0].status, StepStatus::Succeeded);
+    }
+
+    #[test]
+    fn test_list_functions_output() {
+        let listed: ListFunctionsOutput = serde_json::from_value(json!({
+            "functions": [{ "namespace": "Users", "name": "getUser", "description": null }],
+            "code": "declare namespace Users {}"
+        }))
+        .unwrap();
+        assert_eq!(listed.functions[0].id(), "Users.getUser");
+        assert_eq!(listed.size.tokens, 0);
+    }
+}
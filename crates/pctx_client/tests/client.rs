@@ -0,0 +1,84 @@
+use futures::StreamExt;
+use pctx_client::{
+    Client, ExecuteEvent,
+    model::{DetailLevel, ExecuteInput, GetFunctionDetailsInput, ListFunctionsInput},
+};
+use pctx_code_mode::{CodeMode, model::CallbackConfig};
+use pctx_config::Config;
+use serde_json::json;
+
+/// Serves code mode with a `Users.getUser` function, returning the MCP endpoint URL
+async fn serve() -> String {
+    let code_mode = CodeMode::default()
+        .with_callback(&CallbackConfig {
+            name: "getUser".into(),
+            namespace: "Users".into(),
+            description: Some("Gets a user by id".into()),
+            input_schema: Some(json!({
+                "type": "object",
+                "properties": { "id": { "type": "string" } },
+                "required": ["id"]
+            })),
+            output_schema: None,
+        })
+        .unwrap();
+    let router = pctx_mcp_server::router(&Config::default(), code_mode);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    format!("http://{addr}/mcp")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_list_and_get_function_details() {
+    let client = Client::connect(serve().await).await.unwrap();
+
+    let listed = client
+        .list_functions(&ListFunctionsInput::default())
+        .await
+        .unwrap();
+    assert_eq!(listed.functions.len(), 1);
+    assert_eq!(listed.functions[0].id(), "Users.getUser");
+    assert!(listed.code.contains("getUser"));
+
+    let details = client
+        .get_function_details(&GetFunctionDetailsInput {
+            functions: vec!["Users.getUser".into()],
+            detail_level: Some(DetailLevel::Full),
+            budget_tokens: None,
+        })
+        .await
+        .unwrap();
+    assert!(details.functions[0].input_type.contains("Input"));
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_execute_and_stream() {
+    let client = Client::connect(serve().await).await.unwrap();
+
+    let output = client
+        .execute(&ExecuteInput::new(
+            "async function run() { console.log('adding'); return 1 + 1; }",
+        ))
+        .await
+        .unwrap();
+    assert!(output.success, "{}", output.stderr);
+    assert_eq!(output.output, Some(json!(2)));
+    assert!(output.stdout.contains("adding"));
+
+    let mut events = client
+        .execute_stream(&ExecuteInput::new(
+            "async function run() { const n: number = 'one'; return n; }",
+        ))
+        .unwrap();
+    let Some(ExecuteEvent::Finished(output)) = events.next().await else {
+        panic!("execution without progress should only finish");
+    };
+    let output = output.unwrap();
+    assert!(!output.success);
+    assert!(output.diagnostics.iter().any(|d| d.code == Some(2322)));
+    assert!(events.next().await.is_none());
+}
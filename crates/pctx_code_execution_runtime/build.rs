@@ -36,6 +36,133 @@ async fn op_invoke_callback(
     serde_json::Value::Null
 }
 
+/// Register a tool (stub)
+#[deno_core::op2]
+fn op_register_tool(#[serde] _tool: serde_json::Value) {}
+
+/// Workspace roots (stub)
+#[deno_core::op2]
+#[serde]
+fn op_roots() -> Vec<serde_json::Value> {
+    vec![]
+}
+
+/// Load a WebAssembly module (stub)
+#[deno_core::op2]
+#[buffer]
+fn op_wasm_module(#[string] _name: String) -> Vec<u8> {
+    vec![]
+}
+
+/// Read a virtual file (stub)
+#[deno_core::op2]
+#[buffer]
+fn op_fs_read(#[string] _path: String) -> Vec<u8> {
+    vec![]
+}
+
+/// Write a virtual file (stub)
+#[deno_core::op2]
+fn op_fs_write(#[string] _path: String, #[buffer] _data: deno_core::JsBuffer) {}
+
+/// Remove a virtual file (stub)
+#[deno_core::op2(fast)]
+fn op_fs_remove(#[string] _path: String) -> bool {
+    false
+}
+
+/// Check a virtual file exists (stub)
+#[deno_core::op2(fast)]
+fn op_fs_exists(#[string] _path: String) -> bool {
+    false
+}
+
+/// List virtual files (stub)
+#[deno_core::op2]
+#[serde]
+fn op_fs_list(#[string] _dir: String) -> Vec<String> {
+    vec![]
+}
+
+/// Put a bytes artifact (stub)
+#[deno_core::op2]
+fn op_artifact_put_bytes(#[string] _name: String, #[buffer] _data: deno_core::JsBuffer) {}
+
+/// Put a JSON artifact (stub)
+#[deno_core::op2]
+fn op_artifact_put_json(#[string] _name: String, #[serde] _value: serde_json::Value) {}
+
+/// Kind of an artifact (stub)
+#[deno_core::op2]
+#[serde]
+fn op_artifact_kind(#[string] _name: String) -> Option<String> {
+    None
+}
+
+/// Get a bytes artifact (stub)
+#[deno_core::op2]
+#[buffer]
+fn op_artifact_get_bytes(#[string] _name: String) -> Vec<u8> {
+    vec![]
+}
+
+/// Get a JSON artifact (stub)
+#[deno_core::op2]
+#[serde]
+fn op_artifact_get_json(#[string] _name: String) -> serde_json::Value {
+    serde_json::Value::Null
+}
+
+/// Delete an artifact (stub)
+#[deno_core::op2(fast)]
+fn op_artifact_delete(#[string] _name: String) -> bool {
+    false
+}
+
+/// List artifacts (stub)
+#[deno_core::op2]
+#[serde]
+fn op_artifact_list() -> Vec<serde_json::Value> {
+    vec![]
+}
+
+/// Fetch part of an offloaded result (stub)
+#[deno_core::op2]
+#[serde]
+fn op_results_fetch(#[string] _handle: String, #[string] _path: String) -> serde_json::Value {
+    serde_json::Value::Null
+}
+
+/// Get a key-value entry (stub)
+#[deno_core::op2]
+#[serde]
+fn op_kv_get(#[string] _namespace: String, #[string] _key: String) -> Option<serde_json::Value> {
+    None
+}
+
+/// Set a key-value entry (stub)
+#[deno_core::op2]
+fn op_kv_set(
+    #[string] _namespace: String,
+    #[string] _key: String,
+    #[serde] _value: serde_json::Value,
+    #[serde] _ttl_secs: Option<f64>,
+) {
+}
+
+/// Delete a key-value entry (stub)
+#[deno_core::op2(fast)]
+fn op_kv_delete(#[string] _namespace: String, #[string] _key: String) -> bool {
+    false
+}
+
+/// List key-value entries (stub)
+#[deno_core::op2]
+#[serde]
+fn op_kv_list(#[string] _namespace: String, #[string] _prefix: String) -> Vec<serde_json::Value> {
+    vec![]
+}
+
 // We need to define the extension here as well for snapshot creation
 // The esm_entry_point tells deno_core to execute this module during snapshot creation
 extension!(
@@ -44,14 +171,35 @@ extension!(
         // Op declarations - these will be registered but not executed during snapshot
         op_call_mcp_tool,
         op_invoke_callback,
+        op_register_tool,
+        op_roots,
+        op_wasm_module,
+        op_fs_read,
+        op_fs_write,
+        op_fs_remove,
+        op_fs_exists,
+        op_fs_list,
+        op_artifact_put_bytes,
+        op_artifact_put_json,
+        op_artifact_kind,
+        op_artifact_get_bytes,
+        op_artifact_get_json,
+        op_artifact_delete,
+        op_artifact_list,
+        op_results_fetch,
+        op_kv_get,
+        op_kv_set,
+        op_kv_delete,
+        op_kv_list,
     ],
     esm_entry_point = "ext:pctx_runtime_snapshot/runtime.js",
-    esm = [ dir "src", "runtime.js" ],
+    esm = [ dir "src", "runtime.js", "std.js" ],
 );
 
 fn main() {
-    // Tell cargo to rerun this build script if runtime.js or build.rs changes
+    // Tell cargo to rerun this build script if the runtime JS or build.rs changes
     println!("cargo:rerun-if-changed=src/runtime.js");
+    println!("cargo:rerun-if-changed=src/std.js");
     println!("cargo:rerun-if-changed=build.rs");
 
     // Get the output directory
@@ -0,0 +1,299 @@
+//! Artifact store shared by the executions of a session, exposed as `pctx.artifacts`
+//!
+//! Multi-step agents hand large intermediate data (bytes or JSON) from one execution to
+//! the next through artifacts instead of the LLM context. The store enforces the quotas of
+//! its [`ArtifactsConfig`] and drops artifacts once their TTL passed.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use deno_core::{JsBuffer, OpState, op2};
+use pctx_config::artifacts::ArtifactsConfig;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::ArtifactError;
+
+/// Content of an artifact
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArtifactData {
+    Bytes(Vec<u8>),
+    Json(Value),
+}
+
+impl ArtifactData {
+    /// Size counted against the quotas, JSON is measured serialized
+    fn size(&self) -> usize {
+        match self {
+            Self::Bytes(bytes) => bytes.len(),
+            Self::Json(value) => serde_json::to_vec(value).map_or(0, |v| v.len()),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Bytes(_) => "bytes",
+            Self::Json(_) => "json",
+        }
+    }
+}
+
+/// Listing entry of an artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactInfo {
+    pub name: String,
+    /// `bytes` or `json`
+    pub kind: String,
+    pub size: usize,
+    /// Seconds until the artifact expires
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug)]
+struct Artifact {
+    data: ArtifactData,
+    size: usize,
+    expires_at: Instant,
+}
+
+/// Artifacts of a session, clones share their artifacts
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactStore {
+    config: ArtifactsConfig,
+    artifacts: Arc<Mutex<BTreeMap<String, Artifact>>>,
+}
+
+impl ArtifactStore {
+    pub fn new(config: ArtifactsConfig) -> Self {
+        Self {
+            config,
+            artifacts: Arc::default(),
+        }
+    }
+
+    /// Quotas & expiry of the store
+    pub fn config(&self) -> &ArtifactsConfig {
+        &self.config
+    }
+
+    /// Stores the artifact under `name`, replacing any artifact with the same name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the artifact exceeds `max_artifact_bytes` or storing it would
+    /// exceed `max_total_bytes`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn put(&self, name: &str, data: ArtifactData) -> Result<(), ArtifactError> {
+        if name.is_empty() {
+            return Err(ArtifactError::InvalidName);
+        }
+        let size = data.size();
+        if size > self.config.max_artifact_bytes {
+            return Err(ArtifactError::TooLarge(
+                name.to_string(),
+                size,
+                self.config.max_artifact_bytes,
+            ));
+        }
+
+        let now = Instant::now();
+        let mut artifacts = self.artifacts.lock().unwrap();
+        artifacts.retain(|_, a| a.expires_at > now);
+        let used: usize = artifacts
+            .iter()
+            .filter(|(n, _)| n.as_str() != name)
+            .map(|(_, a)| a.size)
+            .sum();
+        if used + size > self.config.max_total_bytes {
+            return Err(ArtifactError::QuotaExceeded(
+                name.to_string(),
+                self.config.max_total_bytes.saturating_sub(used),
+            ));
+        }
+
+        artifacts.insert(
+            name.to_string(),
+            Artifact {
+                data,
+                size,
+                expires_at: now + self.config.ttl(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns the artifact if it exists & has not expired
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn get(&self, name: &str) -> Option<ArtifactData> {
+        self.artifacts
+            .lock()
+            .unwrap()
+            .get(name)
+            .filter(|a| a.expires_at > Instant::now())
+            .map(|a| a.data.clone())
+    }
+
+    /// Removes the artifact, returns `false` if it did not exist
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn delete(&self, name: &str) -> bool {
+        self.artifacts
+            .lock()
+            .unwrap()
+            .remove(name)
+            .is_some_and(|a| a.expires_at > Instant::now())
+    }
+
+    /// Artifacts that have not expired, sorted by name
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn list(&self) -> Vec<ArtifactInfo> {
+        let now = Instant::now();
+        self.artifacts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, a)| a.expires_at > now)
+            .map(|(name, a)| ArtifactInfo {
+                name: name.clone(),
+                kind: a.data.kind().into(),
+                size: a.size,
+                expires_in_secs: (a.expires_at - now).as_secs(),
+            })
+            .collect()
+    }
+}
+
+fn store(state: &OpState) -> Result<&ArtifactStore, ArtifactError> {
+    state
+        .try_borrow::<ArtifactStore>()
+        .ok_or(ArtifactError::Disabled)
+}
+
+#[op2]
+pub(crate) fn op_artifact_put_bytes(
+    state: &mut OpState,
+    #[string] name: String,
+    #[buffer] data: JsBuffer,
+) -> Result<(), ArtifactError> {
+    store(state)?.put(&name, ArtifactData::Bytes(data.to_vec()))
+}
+
+#[op2]
+pub(crate) fn op_artifact_put_json(
+    state: &mut OpState,
+    #[string] name: String,
+    #[serde] value: Value,
+) -> Result<(), ArtifactError> {
+    store(state)?.put(&name, ArtifactData::Json(value))
+}
+
+/// Kind of the artifact, `null` if it does not exist
+#[op2]
+#[serde]
+pub(crate) fn op_artifact_kind(
+    state: &mut OpState,
+    #[string] name: String,
+) -> Result<Option<String>, ArtifactError> {
+    Ok(store(state)?.get(&name).map(|a| a.kind().to_string()))
+}
+
+#[op2]
+#[buffer]
+pub(crate) fn op_artifact_get_bytes(
+    state: &mut OpState,
+    #[string] name: String,
+) -> Result<Vec<u8>, ArtifactError> {
+    match store(state)?.get(&name) {
+        Some(ArtifactData::Bytes(bytes)) => Ok(bytes),
+        _ => Err(ArtifactError::NotFound(name)),
+    }
+}
+
+#[op2]
+#[serde]
+pub(crate) fn op_artifact_get_json(
+    state: &mut OpState,
+    #[string] name: String,
+) -> Result<Value, ArtifactError> {
+    match store(state)?.get(&name) {
+        Some(ArtifactData::Json(value)) => Ok(value),
+        _ => Err(ArtifactError::NotFound(name)),
+    }
+}
+
+#[op2(fast)]
+pub(crate) fn op_artifact_delete(
+    state: &mut OpState,
+    #[string] name: String,
+) -> Result<bool, ArtifactError> {
+    Ok(store(state)?.delete(&name))
+}
+
+#[op2]
+#[serde]
+pub(crate) fn op_artifact_list(state: &mut OpState) -> Result<Vec<ArtifactInfo>, ArtifactError> {
+    Ok(store(state)?.list())
+}
+
+#[cfg(test)]
+mod tests {
+    use pctx_config::artifacts::ArtifactsConfig;
+    use serde_json::json;
+
+    use super::{ArtifactData, ArtifactStore};
+    use crate::error::ArtifactError;
+
+    #[test]
+    fn test_quotas() {
+        let store = ArtifactStore::new(ArtifactsConfig {
+            max_artifact_bytes: 8,
+            max_total_bytes: 12,
+            ttl_secs: 60,
+        });
+
+        store.put("a", ArtifactData::Bytes(vec![0; 8])).unwrap();
+        assert!(matches!(
+            store.put("b", ArtifactData::Bytes(vec![0; 9])),
+            Err(ArtifactError::TooLarge(..))
+        ));
+        assert!(matches!(
+            store.put("b", ArtifactData::Bytes(vec![0; 5])),
+            Err(ArtifactError::QuotaExceeded(..))
+        ));
+        // replacing an artifact frees its size
+        store.put("a", ArtifactData::Json(json!([1, 2]))).unwrap();
+        store.put("b", ArtifactData::Bytes(vec![0; 5])).unwrap();
+
+        assert_eq!(store.get("a"), Some(ArtifactData::Json(json!([1, 2]))));
+        let names: Vec<_> = store.list().into_iter().map(|a| a.name).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert!(store.delete("a"));
+        assert!(store.get("a").is_none());
+    }
+
+    #[test]
+    fn test_ttl() {
+        let store = ArtifactStore::new(ArtifactsConfig {
+            ttl_secs: 0,
+            ..Default::default()
+        });
+        store.put("a", ArtifactData::Json(json!(1))).unwrap();
+        assert!(store.get("a").is_none());
+        assert!(store.list().is_empty());
+    }
+}
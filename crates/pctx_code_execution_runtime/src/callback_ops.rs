@@ -7,7 +7,10 @@ use deno_core::{OpState, op2};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::{CallbackRegistry, error::McpError};
+use crate::{
+    ArtifactStore, CallTape, CallbackRegistry, ExecutionIds, ExecutionSignal, InvocationContext,
+    MCPRegistry, ToolDefinition, ToolRegistrations, error::McpError,
+};
 
 #[op2(async)]
 #[serde]
@@ -16,10 +19,53 @@ pub(crate) async fn op_invoke_callback(
     #[string] id: String,
     #[serde] arguments: Option<serde_json::Value>,
 ) -> Result<serde_json::Value, McpError> {
-    let registry = {
+    let (registry, context, tape) = {
         let borrowed = state.borrow();
-        borrowed.borrow::<CallbackRegistry>().clone()
+        let context = InvocationContext {
+            callback_id: id.clone(),
+            ids: borrowed
+                .try_borrow::<ExecutionIds>()
+                .cloned()
+                .unwrap_or_default(),
+            roots: borrowed.borrow::<MCPRegistry>().roots(),
+            signal: borrowed
+                .try_borrow::<ExecutionSignal>()
+                .cloned()
+                .unwrap_or_default(),
+            span: tracing::Span::current(),
+            registrations: borrowed.try_borrow::<ToolRegistrations>().cloned(),
+            artifacts: borrowed.try_borrow::<ArtifactStore>().cloned(),
+        };
+        (
+            borrowed.borrow::<CallbackRegistry>().clone(),
+            context,
+            borrowed.try_borrow::<CallTape>().cloned(),
+        )
     };
 
-    registry.invoke(&id, arguments).await
+    // arguments are only copied when the call is recorded
+    let signal = context.signal.clone();
+    match tape {
+        Some(tape) => {
+            let call = registry.invoke_with_context(&id, arguments.clone(), context);
+            signal.guard(tape.run(id.clone(), arguments, call)).await
+        }
+        None => {
+            signal
+                .guard(registry.invoke_with_context(&id, arguments, context))
+                .await
+        }
+    }
+}
+
+/// Register a tool for subsequent executions
+#[op2]
+pub(crate) fn op_register_tool(
+    state: &mut OpState,
+    #[serde] tool: ToolDefinition,
+) -> Result<(), McpError> {
+    state
+        .try_borrow::<ToolRegistrations>()
+        .ok_or_else(|| McpError::Config("Tools cannot be registered in this execution".into()))?
+        .register(tool)
 }
@@ -1,13 +1,91 @@
-use serde_json::json;
+use rmcp::model::Root;
 use std::{
     collections::HashMap,
     future::Future,
     pin::Pin,
     sync::{Arc, RwLock},
 };
-use tracing::instrument;
+use tracing::{Span, instrument};
 
-use crate::error::McpError;
+use crate::{
+    ArtifactStore, ExecutionSignal, ToolDefinition, ToolRegistrations, error::McpError,
+    lazy_json::LazyJson,
+};
+
+tokio::task_local! {
+    static INVOCATION_CONTEXT: InvocationContext;
+}
+
+/// Identifiers of an execution, passed to the callbacks it invokes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionIds {
+    pub execution_id: Option<String>,
+    /// Session of the session server the code is executed in
+    pub session_id: Option<String>,
+}
+
+/// Context of the execution a callback is invoked from
+#[derive(Debug, Clone)]
+pub struct InvocationContext {
+    /// Id of the invoked callback, `namespace.name`
+    pub callback_id: String,
+    /// Identifiers of the execution
+    pub ids: ExecutionIds,
+    /// Workspace roots of the client the code is executed for, empty if it has none
+    pub roots: Vec<Root>,
+    /// Cancellation & deadline of the execution
+    pub signal: ExecutionSignal,
+    /// Span of the execution, to correlate work of the callback with the execution
+    pub span: Span,
+    /// Tools registered during the execution, `None` if the host doesn't accept registrations
+    pub registrations: Option<ToolRegistrations>,
+    /// Artifacts of the session, `None` if the execution has no artifact store
+    pub artifacts: Option<ArtifactStore>,
+}
+
+impl Default for InvocationContext {
+    fn default() -> Self {
+        Self {
+            callback_id: String::new(),
+            ids: ExecutionIds::default(),
+            roots: vec![],
+            signal: ExecutionSignal::default(),
+            span: Span::none(),
+            registrations: None,
+            artifacts: None,
+        }
+    }
+}
+
+impl InvocationContext {
+    /// Context of the invocation of the running callback, default outside of
+    /// callbacks and in tasks they spawn
+    pub fn current() -> Self {
+        INVOCATION_CONTEXT
+            .try_with(Clone::clone)
+            .unwrap_or_default()
+    }
+
+    /// Registers a tool for subsequent executions, e.g. an endpoint of an API the callback
+    /// discovered
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host doesn't accept registrations or the tool is invalid
+    pub fn register_tool(&self, tool: ToolDefinition) -> Result<(), McpError> {
+        self.registrations
+            .as_ref()
+            .ok_or_else(|| McpError::Config("Tools cannot be registered in this execution".into()))?
+            .register(tool)
+    }
+
+    /// Namespace of the invoked callback
+    pub fn namespace(&self) -> &str {
+        self.callback_id
+            .split_once('.')
+            .map_or("", |(namespace, _)| namespace)
+    }
+}
 
 pub type CallbackFn = Arc<
     dyn Fn(
@@ -108,7 +186,7 @@ impl CallbackRegistry {
     #[instrument(
         name = "invoke_callback_tool",
         skip_all,
-        fields(id=id, args = json!(args).to_string()),
+        fields(id=id, args = %LazyJson(&args)),
         ret(Display),
         err
     )]
@@ -116,13 +194,33 @@ impl CallbackRegistry {
         &self,
         id: &str,
         args: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, McpError> {
+        self.invoke_with_context(id, args, InvocationContext::default())
+            .await
+    }
+
+    /// invokes the callback with the provided args, [`InvocationContext::current`] returns
+    /// `context` while it runs
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a callback by the provided id doesn't exist
+    /// or if the callback itself fails
+    pub async fn invoke_with_context(
+        &self,
+        id: &str,
+        args: Option<serde_json::Value>,
+        context: InvocationContext,
     ) -> Result<serde_json::Value, McpError> {
         let callback = self.get(id).ok_or_else(|| {
             McpError::ToolCall(format!("Callback with id \"{id}\" does not exist"))
         })?;
 
-        callback(args).await.map_err(|e| {
-            McpError::ExecutionError(format!("Failed calling callback with id \"{id}\": {e}",))
-        })
+        INVOCATION_CONTEXT
+            .scope(context, callback(args))
+            .await
+            .map_err(|e| {
+                McpError::ExecutionError(format!("Failed calling callback with id \"{id}\": {e}",))
+            })
     }
 }
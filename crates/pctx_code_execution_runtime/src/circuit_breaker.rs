@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use pctx_config::circuit_breaker::CircuitBreakerConfig;
+// follows the paused clock of tests
+use tokio::time::Instant;
+
+use crate::error::McpError;
+
+/// State of the circuit breaker of an upstream MCP server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls are made to the server
+    Closed,
+    /// The server is failing, calls fail fast until the cooldown elapsed
+    Open,
+    /// The cooldown elapsed, a single probe call is being made to the server
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+struct Breaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+}
+
+/// Circuit breakers of upstream MCP servers keyed on server name
+///
+/// Share between executions so a dead upstream fails fast instead of every
+/// execution waiting for it to time out.
+#[derive(Debug, Clone, Default)]
+pub struct CircuitBreakers {
+    breakers: Arc<RwLock<HashMap<String, Breaker>>>,
+}
+
+impl CircuitBreakers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current circuit state of the server, `Closed` for servers never called
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn state(&self, server: &str) -> CircuitState {
+        self.breakers
+            .read()
+            .unwrap()
+            .get(server)
+            .map_or(CircuitState::Closed, |b| b.state)
+    }
+
+    /// Closes all circuits
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn reset(&self) {
+        self.breakers.write().unwrap().clear();
+    }
+
+    /// Checks whether a call to the server is allowed, half-opening the
+    /// circuit if the cooldown elapsed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the circuit is open or a probe call is in progress
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn check(&self, server: &str, cfg: &CircuitBreakerConfig) -> Result<(), McpError> {
+        if !cfg.enabled() {
+            return Ok(());
+        }
+
+        let mut breakers = self.breakers.write().unwrap();
+        let Some(breaker) = breakers.get_mut(server) else {
+            return Ok(());
+        };
+
+        if breaker.state == CircuitState::Closed {
+            return Ok(());
+        }
+
+        // let a single probe call through every cooldown period
+        let elapsed = breaker.opened_at.map(|o| o.elapsed()).unwrap_or_default();
+        if elapsed >= cfg.cooldown() {
+            breaker.state = CircuitState::HalfOpen;
+            breaker.opened_at = Some(Instant::now());
+            return Ok(());
+        }
+
+        Err(McpError::Unavailable(format!(
+            "MCP server \"{server}\" failed {} consecutive calls, calls are rejected for the next {}s",
+            breaker.consecutive_failures,
+            cfg.cooldown().saturating_sub(elapsed).as_secs().max(1)
+        )))
+    }
+
+    /// Closes the circuit after a successful call
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn record_success(&self, server: &str) {
+        self.breakers.write().unwrap().remove(server);
+    }
+
+    /// Counts a failed call, opening the circuit when the failure threshold
+    /// is reached or the probe call of a half-open circuit failed.
+    /// Returns `true` if the circuit was opened.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn record_failure(&self, server: &str, cfg: &CircuitBreakerConfig) -> bool {
+        if !cfg.enabled() {
+            return false;
+        }
+
+        let mut breakers = self.breakers.write().unwrap();
+        let breaker = breakers.entry(server.into()).or_default();
+        breaker.consecutive_failures += 1;
+
+        let open = breaker.state == CircuitState::HalfOpen
+            || breaker.consecutive_failures >= cfg.failure_threshold;
+        if open {
+            breaker.state = CircuitState::Open;
+            breaker.opened_at = Some(Instant::now());
+        }
+        open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pctx_config::circuit_breaker::CircuitBreakerConfig;
+
+    use super::{CircuitBreakers, CircuitState};
+    use crate::error::McpError;
+
+    const SERVER: &str = "flaky";
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 3,
+            cooldown_secs: 30,
+        }
+    }
+
+    /// Opens the circuit of [`SERVER`] with consecutive failures
+    fn open(breakers: &CircuitBreakers) {
+        for _ in 0..config().failure_threshold {
+            breakers.record_failure(SERVER, &config());
+        }
+        assert_eq!(breakers.state(SERVER), CircuitState::Open);
+    }
+
+    fn rejected(breakers: &CircuitBreakers) -> Option<String> {
+        match breakers.check(SERVER, &config()) {
+            Ok(()) => None,
+            Err(McpError::Unavailable(message)) => Some(message),
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_opens_after_consecutive_failures() {
+        let breakers = CircuitBreakers::new();
+        assert_eq!(breakers.state(SERVER), CircuitState::Closed);
+
+        assert!(!breakers.record_failure(SERVER, &config()));
+        assert!(!breakers.record_failure(SERVER, &config()));
+        assert_eq!(breakers.state(SERVER), CircuitState::Closed);
+        assert_eq!(rejected(&breakers), None);
+
+        // a success resets the consecutive failures
+        breakers.record_success(SERVER);
+        assert!(!breakers.record_failure(SERVER, &config()));
+        assert!(!breakers.record_failure(SERVER, &config()));
+        assert!(breakers.record_failure(SERVER, &config()));
+        assert_eq!(breakers.state(SERVER), CircuitState::Open);
+
+        let message = rejected(&breakers).expect("open circuit rejects calls");
+        assert!(message.contains("failed 3 consecutive calls"), "{message}");
+        assert!(message.contains("next 30s"), "{message}");
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        let message = rejected(&breakers).expect("open circuit rejects calls");
+        assert!(message.contains("next 20s"), "{message}");
+
+        // other servers are unaffected
+        assert!(breakers.check("other", &config()).is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_successful_probe_closes_circuit() {
+        let breakers = CircuitBreakers::new();
+        open(&breakers);
+
+        tokio::time::advance(Duration::from_secs(29)).await;
+        assert!(rejected(&breakers).is_some());
+
+        // a single probe call is let through once the cooldown elapsed
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(rejected(&breakers), None);
+        assert_eq!(breakers.state(SERVER), CircuitState::HalfOpen);
+        assert!(rejected(&breakers).is_some());
+
+        breakers.record_success(SERVER);
+        assert_eq!(breakers.state(SERVER), CircuitState::Closed);
+        assert_eq!(rejected(&breakers), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_probe_reopens_circuit() {
+        let breakers = CircuitBreakers::new();
+        open(&breakers);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(rejected(&breakers), None);
+        assert_eq!(breakers.state(SERVER), CircuitState::HalfOpen);
+
+        // a single failed probe opens the circuit for another cooldown
+        assert!(breakers.record_failure(SERVER, &config()));
+        assert_eq!(breakers.state(SERVER), CircuitState::Open);
+        tokio::time::advance(Duration::from_secs(29)).await;
+        assert!(rejected(&breakers).is_some());
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(rejected(&breakers), None);
+        assert_eq!(breakers.state(SERVER), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn test_disabled_circuit_never_opens() {
+        let breakers = CircuitBreakers::new();
+        let disabled = CircuitBreakerConfig {
+            failure_threshold: 0,
+            cooldown_secs: 30,
+        };
+
+        for _ in 0..10 {
+            assert!(!breakers.record_failure(SERVER, &disabled));
+        }
+        assert_eq!(breakers.state(SERVER), CircuitState::Closed);
+        assert!(breakers.check(SERVER, &disabled).is_ok());
+    }
+}
@@ -1,9 +1,15 @@
 //! Error types for PCTX runtime
 
+use std::borrow::Cow;
+
+use deno_error::PropertyValue;
 use pctx_config::server::McpConnectionError;
 
 /// Error type for MCP operations
-#[derive(Debug, thiserror::Error)]
+///
+/// Serializable so forwarded tool calls (see [`crate::ToolCallForwarder`]) keep the kind
+/// of their errors
+#[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
 pub enum McpError {
     /// Server configuration error (e.g., duplicate name)
     #[error("MCP configuration error: {0}")]
@@ -11,12 +17,103 @@ pub enum McpError {
     /// Server connection error
     #[error("MCP connection error: {0}")]
     Connection(String),
+    /// Server is unavailable because its circuit breaker is open
+    #[error("MCP server unavailable: {0}")]
+    Unavailable(String),
     /// Tool call error (HTTP, parsing, etc.)
     #[error("MCP tool call error: {0}")]
     ToolCall(String),
+    /// The tool reported an error, either a JSON-RPC error or a result flagged `isError`
+    #[error("{message}")]
+    Tool {
+        message: String,
+        /// JSON-RPC error code, `None` for results flagged `isError`
+        code: Option<i32>,
+        /// JSON-RPC error data, or the content of results flagged `isError`
+        data: Option<serde_json::Value>,
+    },
+    /// The call did not complete in time
+    #[error("Call timed out: {0}")]
+    Timeout(String),
     /// Local tool execution error
     #[error("Local tool execution error: {0}")]
     ExecutionError(String),
+    /// The execution was cancelled or its deadline passed while the call was pending
+    #[error("Call cancelled: {0}")]
+    Cancelled(String),
+    /// The call was rejected by policy, e.g. the user did not approve it
+    #[error("Call denied: {0}")]
+    Denied(String),
+    /// A rate limit or the call budget of the execution was exceeded, the call was not made
+    #[error("Tool call limit exceeded: {0}")]
+    LimitExceeded(String),
+    /// Error of a call replayed from a recording, as it was recorded
+    #[error("{0}")]
+    Replayed(String),
+}
+
+impl McpError {
+    /// JSON-RPC code of invalid tool arguments
+    const INVALID_PARAMS: i32 = -32602;
+
+    /// Name of the error class thrown into the sandbox, declared in `std.d.ts`
+    ///
+    /// Errors that don't fit the taxonomy are thrown as the base class `ToolCallError`
+    pub fn js_class(&self) -> &'static str {
+        match self {
+            Self::Connection(_) | Self::Unavailable(_) => "NetworkError",
+            Self::Tool {
+                code: Some(Self::INVALID_PARAMS),
+                ..
+            } => "ValidationError",
+            Self::Tool { .. } | Self::ToolCall(_) | Self::ExecutionError(_) => "ToolError",
+            Self::Denied(_) => "PermissionDenied",
+            Self::Timeout(_) => "Timeout",
+            Self::Config(_) | Self::Cancelled(_) | Self::LimitExceeded(_) | Self::Replayed(_) => {
+                "ToolCallError"
+            }
+        }
+    }
+}
+
+// Thrown as plain errors carrying their class, code & data, which `runtime.js`
+// converts to instances of the error classes
+impl deno_error::JsErrorClass for McpError {
+    fn get_class(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Error")
+    }
+
+    fn get_message(&self) -> Cow<'static, str> {
+        Cow::Owned(self.to_string())
+    }
+
+    fn get_additional_properties(
+        &self,
+    ) -> Box<dyn Iterator<Item = (Cow<'static, str>, PropertyValue)>> {
+        let mut properties = vec![(
+            Cow::Borrowed("pctxClass"),
+            PropertyValue::String(Cow::Borrowed(self.js_class())),
+        )];
+        if let Self::Tool { code, data, .. } = self {
+            if let Some(code) = code {
+                properties.push((
+                    Cow::Borrowed("pctxCode"),
+                    PropertyValue::Number(f64::from(*code)),
+                ));
+            }
+            if let Some(data) = data {
+                properties.push((
+                    Cow::Borrowed("pctxData"),
+                    PropertyValue::String(Cow::Owned(data.to_string())),
+                ));
+            }
+        }
+        Box::new(properties.into_iter())
+    }
+
+    fn get_ref(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
 }
 
 impl From<McpConnectionError> for McpError {
@@ -25,5 +122,100 @@ impl From<McpConnectionError> for McpError {
     }
 }
 
+/// Error type for WebAssembly module operations
+#[derive(Debug, thiserror::Error)]
+pub enum WasmError {
+    /// No module with the name was supplied to the execution
+    #[error("WebAssembly module not found: {0}, available modules: {1:?}")]
+    NotFound(String, Vec<String>),
+}
+
+/// Error type for `pctx.fs` operations
+#[derive(Debug, thiserror::Error)]
+pub enum FsError {
+    /// The execution has no virtual filesystem
+    #[error("pctx.fs is not enabled for this execution")]
+    Disabled,
+    /// The path is not a valid virtual path, e.g. it escapes the root with `..`
+    #[error("Invalid path: {0}")]
+    InvalidPath(String),
+    /// The path is outside of the scopes the execution may access
+    #[error("Permission denied: no {0} access to {1}")]
+    PermissionDenied(&'static str, String),
+    /// No file exists at the path
+    #[error("File not found: {0}")]
+    NotFound(String),
+    /// Error of the storage of the filesystem
+    #[error("Filesystem error: {0}")]
+    Io(String),
+}
+
+/// Error type for `pctx.artifacts` operations
+#[derive(Debug, thiserror::Error)]
+pub enum ArtifactError {
+    /// The execution has no artifact store
+    #[error("pctx.artifacts is not enabled for this execution")]
+    Disabled,
+    /// Artifact names must not be empty
+    #[error("Artifact names must not be empty")]
+    InvalidName,
+    /// The artifact is larger than `max_artifact_bytes`
+    #[error("Artifact '{0}' has {1} bytes, artifacts may have at most {2} bytes")]
+    TooLarge(String, usize, usize),
+    /// Storing the artifact would exceed `max_total_bytes`
+    #[error("Storing artifact '{0}' exceeds the artifact quota, {1} bytes are left")]
+    QuotaExceeded(String, usize),
+    /// No artifact of the kind exists with the name, or it expired
+    #[error("Artifact not found: {0}")]
+    NotFound(String),
+}
+
+/// Error type for `pctx.results` operations
+#[derive(Debug, thiserror::Error)]
+pub enum ResultError {
+    /// The execution has no result store
+    #[error("pctx.results is not enabled for this execution")]
+    Disabled,
+    /// No offloaded output exists with the handle, or it was dropped for newer outputs
+    #[error("Result not found: {0}")]
+    NotFound(String),
+    /// The path is not a supported `JSONPath`
+    #[error(transparent)]
+    InvalidPath(#[from] crate::json_path::JsonPathError),
+}
+
+/// Error type for `pctx.kv` operations
+#[derive(Debug, thiserror::Error)]
+pub enum KvError {
+    /// The execution has no key-value store
+    #[error("pctx.kv is not enabled for this execution")]
+    Disabled,
+    /// Keys & namespaces must not be empty
+    #[error("Keys and namespaces must not be empty")]
+    InvalidKey,
+    /// The TTL is negative or not a number
+    #[error("Invalid TTL: {0} seconds")]
+    InvalidTtl(f64),
+    /// The value is larger than `max_value_bytes`
+    #[error("Value of '{0}' has {1} bytes, values may have at most {2} bytes")]
+    TooLarge(String, usize, usize),
+    /// Setting the value would exceed the quota of the namespace
+    #[error("Setting the value exceeds the quota of namespace '{0}', {1} bytes are left")]
+    QuotaExceeded(String, usize),
+    /// Error of the database of the store
+    #[error("Key-value store error: {0}")]
+    Storage(String),
+}
+
+impl From<rusqlite::Error> for KvError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Storage(e.to_string())
+    }
+}
+
 // Use the shared macro for JsErrorClass implementation
-crate::impl_js_error_class!(McpError);
+crate::impl_js_error_class!(WasmError);
+crate::impl_js_error_class!(FsError);
+crate::impl_js_error_class!(ArtifactError);
+crate::impl_js_error_class!(ResultError);
+crate::impl_js_error_class!(KvError);
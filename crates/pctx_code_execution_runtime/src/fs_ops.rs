@@ -0,0 +1,326 @@
+//! Deno ops of the virtual filesystem exposed as `pctx.fs`
+//!
+//! Executed code never sees the real disk: paths are virtual (`/inputs/data.csv`) and are
+//! resolved against an in-memory store or a temporary directory owned by the [`VirtualFs`].
+//! Every access of executed code is checked against the read & write scopes of the filesystem.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use deno_core::{JsBuffer, OpState, op2};
+
+use crate::error::FsError;
+
+/// Virtual filesystem executed code can stage files in with `pctx.fs`
+///
+/// Clones share their files, so files written by one execution can be read by later
+/// executions using a clone of the same filesystem. Code can only read paths within a
+/// read or write scope and only write paths within a write scope, there are none by default.
+#[derive(Debug, Clone)]
+pub struct VirtualFs {
+    storage: Storage,
+    read: Vec<String>,
+    write: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Storage {
+    Memory(Arc<Mutex<BTreeMap<String, Vec<u8>>>>),
+    Dir(Arc<tempfile::TempDir>),
+}
+
+impl VirtualFs {
+    /// Filesystem keeping its files in memory
+    pub fn in_memory() -> Self {
+        Self {
+            storage: Storage::Memory(Arc::default()),
+            read: vec![],
+            write: vec![],
+        }
+    }
+
+    /// Filesystem keeping its files in a temporary directory, removed once the last
+    /// clone is dropped
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary directory cannot be created
+    pub fn temp_dir() -> io::Result<Self> {
+        Ok(Self {
+            storage: Storage::Dir(Arc::new(tempfile::tempdir()?)),
+            read: vec![],
+            write: vec![],
+        })
+    }
+
+    /// Allows executed code to read files within `path`, e.g. `/inputs`
+    #[must_use]
+    pub fn allow_read(mut self, path: impl Into<String>) -> Self {
+        self.read.push(path.into());
+        self
+    }
+
+    /// Allows executed code to read & write files within `path`, e.g. `/scratch`
+    #[must_use]
+    pub fn allow_write(mut self, path: impl Into<String>) -> Self {
+        self.write.push(path.into());
+        self
+    }
+
+    /// Reads the file at `path`, regardless of the scopes of executed code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is invalid or the file does not exist
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        let path = normalize(path)?;
+        match &self.storage {
+            Storage::Memory(files) => files
+                .lock()
+                .unwrap()
+                .get(&path)
+                .cloned()
+                .ok_or(FsError::NotFound(path)),
+            Storage::Dir(dir) => fs::read(host_path(dir.path(), &path)).map_err(|e| {
+                if e.kind() == io::ErrorKind::NotFound {
+                    FsError::NotFound(path)
+                } else {
+                    FsError::Io(e.to_string())
+                }
+            }),
+        }
+    }
+
+    /// Writes the file at `path`, replacing it if it exists, regardless of the scopes of
+    /// executed code
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is invalid or the file cannot be written
+    pub fn write(&self, path: &str, data: impl Into<Vec<u8>>) -> Result<(), FsError> {
+        let path = normalize(path)?;
+        match &self.storage {
+            Storage::Memory(files) => {
+                files.lock().unwrap().insert(path, data.into());
+                Ok(())
+            }
+            Storage::Dir(dir) => {
+                let file = host_path(dir.path(), &path);
+                if let Some(parent) = file.parent() {
+                    fs::create_dir_all(parent).map_err(|e| FsError::Io(e.to_string()))?;
+                }
+                fs::write(file, data.into()).map_err(|e| FsError::Io(e.to_string()))
+            }
+        }
+    }
+
+    /// Removes the file at `path`, returns `false` if it did not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is invalid or the file cannot be removed
+    pub fn remove(&self, path: &str) -> Result<bool, FsError> {
+        let path = normalize(path)?;
+        match &self.storage {
+            Storage::Memory(files) => Ok(files.lock().unwrap().remove(&path).is_some()),
+            Storage::Dir(dir) => match fs::remove_file(host_path(dir.path(), &path)) {
+                Ok(()) => Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(FsError::Io(e.to_string())),
+            },
+        }
+    }
+
+    /// Paths of all files within the directory `dir`, sorted
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is invalid or the directory cannot be read
+    pub fn list(&self, dir: &str) -> Result<Vec<String>, FsError> {
+        let dir = normalize(dir)?;
+        let mut paths = match &self.storage {
+            Storage::Memory(files) => files.lock().unwrap().keys().cloned().collect(),
+            Storage::Dir(root) => {
+                let mut paths = vec![];
+                collect_files(root.path(), root.path(), &mut paths)
+                    .map_err(|e| FsError::Io(e.to_string()))?;
+                paths
+            }
+        };
+        paths.retain(|p| within(p, &dir));
+        paths.sort_unstable();
+        Ok(paths)
+    }
+
+    /// Normalized `path` if executed code may access it
+    fn check(&self, path: &str, write: bool) -> Result<String, FsError> {
+        let path = normalize(path)?;
+        if self.permits(&path, write) {
+            Ok(path)
+        } else {
+            let access = if write { "write" } else { "read" };
+            Err(FsError::PermissionDenied(access, path))
+        }
+    }
+
+    fn permits(&self, path: &str, write: bool) -> bool {
+        let readable = if write { None } else { Some(&self.read) };
+        self.write
+            .iter()
+            .chain(readable.into_iter().flatten())
+            .any(|scope| normalize(scope).is_ok_and(|scope| within(path, &scope)))
+    }
+}
+
+/// Absolute form of a virtual path, `.` & `..` segments resolved
+fn normalize(path: &str) -> Result<String, FsError> {
+    let mut segments: Vec<&str> = vec![];
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                if segments.pop().is_none() {
+                    return Err(FsError::InvalidPath(path.to_string()));
+                }
+            }
+            segment if segment.contains('\\') || segment.contains('\0') => {
+                return Err(FsError::InvalidPath(path.to_string()));
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Ok(format!("/{}", segments.join("/")))
+}
+
+/// Returns `true` if the normalized `path` is `dir` or within it
+fn within(path: &str, dir: &str) -> bool {
+    dir == "/"
+        || path == dir
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn host_path(root: &Path, path: &str) -> PathBuf {
+    root.join(path.trim_start_matches('/'))
+}
+
+fn collect_files(root: &Path, dir: &Path, paths: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &entry.path(), paths)?;
+        } else if let Ok(relative) = entry.path().strip_prefix(root) {
+            paths.push(format!(
+                "/{}",
+                relative.to_string_lossy().replace('\\', "/")
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn virtual_fs(state: &OpState) -> Result<&VirtualFs, FsError> {
+    state.try_borrow::<VirtualFs>().ok_or(FsError::Disabled)
+}
+
+#[op2]
+#[buffer]
+pub(crate) fn op_fs_read(state: &mut OpState, #[string] path: String) -> Result<Vec<u8>, FsError> {
+    let fs = virtual_fs(state)?;
+    fs.read(&fs.check(&path, false)?)
+}
+
+#[op2]
+pub(crate) fn op_fs_write(
+    state: &mut OpState,
+    #[string] path: String,
+    #[buffer] data: JsBuffer,
+) -> Result<(), FsError> {
+    let fs = virtual_fs(state)?;
+    fs.write(&fs.check(&path, true)?, &*data)
+}
+
+#[op2(fast)]
+pub(crate) fn op_fs_remove(state: &mut OpState, #[string] path: String) -> Result<bool, FsError> {
+    let fs = virtual_fs(state)?;
+    fs.remove(&fs.check(&path, true)?)
+}
+
+#[op2(fast)]
+pub(crate) fn op_fs_exists(state: &mut OpState, #[string] path: String) -> Result<bool, FsError> {
+    let fs = virtual_fs(state)?;
+    match fs.read(&fs.check(&path, false)?) {
+        Ok(_) => Ok(true),
+        Err(FsError::NotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Files within the directory readable by executed code
+#[op2]
+#[serde]
+pub(crate) fn op_fs_list(
+    state: &mut OpState,
+    #[string] dir: String,
+) -> Result<Vec<String>, FsError> {
+    let fs = virtual_fs(state)?;
+    let dir = normalize(&dir)?;
+    let mut paths = fs.list(&dir)?;
+    paths.retain(|p| fs.permits(p, false));
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VirtualFs, normalize};
+    use crate::error::FsError;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("/a/./b/../c.txt").unwrap(), "/a/c.txt");
+        assert_eq!(normalize("a//b").unwrap(), "/a/b");
+        assert_eq!(normalize("").unwrap(), "/");
+        assert!(matches!(
+            normalize("/../etc/passwd"),
+            Err(FsError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_scopes() {
+        let fs = VirtualFs::in_memory()
+            .allow_read("/inputs")
+            .allow_write("/scratch/");
+
+        assert_eq!(
+            fs.check("/inputs/data.csv", false).unwrap(),
+            "/inputs/data.csv"
+        );
+        assert!(matches!(
+            fs.check("/inputs/data.csv", true),
+            Err(FsError::PermissionDenied("write", _))
+        ));
+        assert!(fs.check("/scratch/out.json", false).is_ok());
+        assert!(fs.check("/scratch/../inputs/x", true).is_err());
+        assert!(fs.check("/inputs-other/x", false).is_err());
+    }
+
+    #[test]
+    fn test_temp_dir_storage() {
+        let fs = VirtualFs::temp_dir().unwrap();
+        fs.write("/a/b.txt", "hello").unwrap();
+        fs.write("/c.txt", "world").unwrap();
+
+        assert_eq!(fs.read("/a/b.txt").unwrap(), b"hello");
+        assert_eq!(fs.list("/").unwrap(), ["/a/b.txt", "/c.txt"]);
+        assert_eq!(fs.list("/a").unwrap(), ["/a/b.txt"]);
+        assert!(fs.remove("/c.txt").unwrap());
+        assert!(!fs.remove("/c.txt").unwrap());
+        assert!(matches!(fs.read("/c.txt"), Err(FsError::NotFound(_))));
+    }
+}
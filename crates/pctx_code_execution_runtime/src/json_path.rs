@@ -0,0 +1,216 @@
+//! Subset of `JSONPath` selecting parts of large JSON values
+//!
+//! Supported are the root `$`, member access (`.name`, `['name']`), array indices including
+//! negative ones (`[0]`, `[-1]`), slices (`[10:20]`, `[:5]`) and wildcards (`.*`, `[*]`).
+
+use serde_json::Value;
+
+/// Error parsing a `JSONPath` expression
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid JSONPath '{path}': {reason}")]
+pub struct JsonPathError {
+    path: String,
+    reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Wildcard,
+}
+
+/// Parsed `JSONPath` expression, e.g. `$.items[0:10].name`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    /// Parses the expression, the leading `$` is optional
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expression is not part of the supported subset
+    pub fn parse(path: &str) -> Result<Self, JsonPathError> {
+        let error = |reason: &str| JsonPathError {
+            path: path.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let mut rest = path.trim();
+        rest = rest.strip_prefix('$').unwrap_or(rest);
+        let mut segments = vec![];
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                let (name, after) = after.split_at(end);
+                segments.push(match name {
+                    "" => return Err(error("empty member name")),
+                    "*" => Segment::Wildcard,
+                    name => Segment::Key(name.to_string()),
+                });
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let end = bracket_end(after).ok_or_else(|| error("unclosed '['"))?;
+                segments.push(parse_bracket(after[..end].trim()).ok_or_else(|| {
+                    error("expected a quoted name, an index, a slice or '*' in brackets")
+                })?);
+                rest = &after[end + 1..];
+            } else if segments.is_empty() {
+                // `items[0]` is read as `$.items[0]`
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                segments.push(Segment::Key(rest[..end].to_string()));
+                rest = &rest[end..];
+            } else {
+                return Err(error("expected '.' or '['"));
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// Returns `true` if the path selects at most one value, i.e. it has no wildcards or slices
+    pub fn is_definite(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|s| matches!(s, Segment::Key(_) | Segment::Index(_)))
+    }
+
+    /// Values selected by the path
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut selected = vec![value];
+        for segment in &self.segments {
+            selected = selected
+                .into_iter()
+                .flat_map(|value| select_segment(segment, value))
+                .collect();
+        }
+        selected
+    }
+
+    /// The selected value of a definite path (`null` if nothing matches), otherwise the
+    /// array of all selected values
+    pub fn query(&self, value: &Value) -> Value {
+        let selected = self.select(value);
+        if self.is_definite() {
+            selected.first().map_or(Value::Null, |v| (*v).clone())
+        } else {
+            Value::Array(selected.into_iter().cloned().collect())
+        }
+    }
+}
+
+/// Index of the `]` closing a bracket, skipping quoted names
+fn bracket_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, ']') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_bracket(inner: &str) -> Option<Segment> {
+    if inner == "*" {
+        return Some(Segment::Wildcard);
+    }
+    for quote in ['\'', '"'] {
+        if let Some(name) = inner
+            .strip_prefix(quote)
+            .and_then(|s| s.strip_suffix(quote))
+        {
+            return Some(Segment::Key(name.to_string()));
+        }
+    }
+    if let Some((start, end)) = inner.split_once(':') {
+        let bound = |s: &str| -> Option<Option<i64>> {
+            let s = s.trim();
+            if s.is_empty() {
+                Some(None)
+            } else {
+                s.parse().ok().map(Some)
+            }
+        };
+        return Some(Segment::Slice(bound(start)?, bound(end)?));
+    }
+    inner.parse().ok().map(Segment::Index)
+}
+
+/// Resolves a possibly negative index against the length of an array
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        len.checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+    } else {
+        usize::try_from(index).ok()?
+    };
+    (index < len).then_some(index)
+}
+
+fn select_segment<'a>(segment: &Segment, value: &'a Value) -> Vec<&'a Value> {
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+        (Segment::Index(index), Value::Array(items)) => resolve_index(*index, items.len())
+            .and_then(|i| items.get(i))
+            .into_iter()
+            .collect(),
+        (Segment::Slice(start, end), Value::Array(items)) => {
+            let len = items.len();
+            let clamp = |bound: i64| {
+                let len = i64::try_from(len).unwrap_or(i64::MAX);
+                let bound = if bound < 0 { len + bound } else { bound };
+                usize::try_from(bound.clamp(0, len)).unwrap_or(0)
+            };
+            let start = start.map_or(0, clamp);
+            let end = end.map_or(len, clamp);
+            items
+                .get(start..end.max(start))
+                .unwrap_or_default()
+                .iter()
+                .collect()
+        }
+        (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+        (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::JsonPath;
+
+    #[test]
+    fn test_query() {
+        let value = json!({
+            "items": [
+                { "name": "a", "tags": ["x"] },
+                { "name": "b", "tags": [] },
+                { "name": "c", "tags": ["y", "z"] }
+            ],
+            "total count": 3
+        });
+        let query = |path: &str| JsonPath::parse(path).unwrap().query(&value);
+
+        assert_eq!(query("$"), value);
+        assert_eq!(query("$.items[0].name"), json!("a"));
+        assert_eq!(query("items[-1].tags[1]"), json!("z"));
+        assert_eq!(query("$['total count']"), json!(3));
+        assert_eq!(query("$.items[1:].name"), json!(["b", "c"]));
+        assert_eq!(query("$.items[:2].name"), json!(["a", "b"]));
+        assert_eq!(query("$.items[*].tags[*]"), json!(["x", "y", "z"]));
+        assert_eq!(query("$.missing"), json!(null));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(JsonPath::parse("$.items[0").is_err());
+        assert!(JsonPath::parse("$..items").is_err());
+        assert!(JsonPath::parse("$.items[?(@.a)]").is_err());
+    }
+}
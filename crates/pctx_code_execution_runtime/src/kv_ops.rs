@@ -0,0 +1,376 @@
+//! Persistent key-value store exposed as `pctx.kv`
+//!
+//! Agents remember state across executions (and server restarts) in the entries of a
+//! SQLite database. Entries are grouped in namespaces with their own size quota & default
+//! TTL from the [`KvConfig`], expired entries are never returned and are dropped on writes.
+
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use deno_core::{OpState, op2};
+use pctx_config::kv::KvConfig;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::KvError;
+
+/// Namespace of entries set without one
+pub const DEFAULT_KV_NAMESPACE: &str = "default";
+
+/// Entry of a namespace listing
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KvEntry {
+    pub key: String,
+    pub value: Value,
+    /// Seconds until the entry expires, `None` if it does not expire
+    pub expires_in_secs: Option<u64>,
+}
+
+/// Key-value store backed by a SQLite database, clones share the database connection
+#[derive(Debug, Clone)]
+pub struct KvStore {
+    config: KvConfig,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl KvStore {
+    /// Opens the store in the SQLite database at `path`, creating it if it does not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or initialized
+    pub fn open(path: impl AsRef<Path>, config: KvConfig) -> Result<Self, KvError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).map_err(|e| KvError::Storage(e.to_string()))?;
+        }
+        Self::init(Connection::open(path)?, config)
+    }
+
+    /// Store keeping its entries in memory, lost once the last clone is dropped
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be initialized
+    pub fn in_memory(config: KvConfig) -> Result<Self, KvError> {
+        Self::init(Connection::open_in_memory()?, config)
+    }
+
+    fn init(conn: Connection, config: KvConfig) -> Result<Self, KvError> {
+        // stores of several profiles may share the database
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS kv (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                expires_at INTEGER,
+                PRIMARY KEY (namespace, key)
+            ) WITHOUT ROWID;",
+        )?;
+        Ok(Self {
+            config,
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Quotas & TTLs of the store
+    pub fn config(&self) -> &KvConfig {
+        &self.config
+    }
+
+    /// Value of the key, `None` if it does not exist or expired
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn get(&self, namespace: &str, key: &str) -> Result<Option<Value>, KvError> {
+        let value: Option<String> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT value FROM kv WHERE namespace = ?1 AND key = ?2
+                 AND (expires_at IS NULL OR expires_at > ?3)",
+                params![namespace, key, now_millis()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        value
+            .map(|v| serde_json::from_str(&v).map_err(|e| KvError::Storage(e.to_string())))
+            .transpose()
+    }
+
+    /// Sets the value of the key, expiring after `ttl` or the TTL of the namespace
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is empty, the value exceeds `max_value_bytes`, setting it
+    /// would exceed the quota of the namespace or the database cannot be written
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn set(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), KvError> {
+        if namespace.is_empty() || key.is_empty() {
+            return Err(KvError::InvalidKey);
+        }
+        let value = serde_json::to_string(value).map_err(|e| KvError::Storage(e.to_string()))?;
+        if value.len() > self.config.max_value_bytes {
+            return Err(KvError::TooLarge(
+                key.to_string(),
+                value.len(),
+                self.config.max_value_bytes,
+            ));
+        }
+
+        let now = now_millis();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM kv WHERE namespace = ?1 AND expires_at <= ?2",
+            params![namespace, now],
+        )?;
+        let used: i64 = conn.query_row(
+            "SELECT COALESCE(SUM(LENGTH(CAST(key AS BLOB)) + LENGTH(CAST(value AS BLOB))), 0)
+             FROM kv WHERE namespace = ?1 AND key != ?2",
+            params![namespace, key],
+            |row| row.get(0),
+        )?;
+        let used = usize::try_from(used).unwrap_or_default();
+        let max_bytes = self.config.max_bytes(namespace);
+        if used + key.len() + value.len() > max_bytes {
+            return Err(KvError::QuotaExceeded(
+                namespace.to_string(),
+                max_bytes.saturating_sub(used),
+            ));
+        }
+
+        let expires_at = ttl
+            .or_else(|| self.config.ttl(namespace))
+            .map(|ttl| now.saturating_add(i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX)));
+        conn.execute(
+            "INSERT OR REPLACE INTO kv (namespace, key, value, expires_at) VALUES (?1, ?2, ?3, ?4)",
+            params![namespace, key, value, expires_at],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes the key, returns `false` if it did not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be written
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn delete(&self, namespace: &str, key: &str) -> Result<bool, KvError> {
+        let deleted = self.conn.lock().unwrap().execute(
+            "DELETE FROM kv WHERE namespace = ?1 AND key = ?2
+             AND (expires_at IS NULL OR expires_at > ?3)",
+            params![namespace, key, now_millis()],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Entries of the namespace whose keys start with `prefix`, sorted by key
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<KvEntry>, KvError> {
+        let now = now_millis();
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT key, value, expires_at FROM kv WHERE namespace = ?1
+             AND substr(key, 1, length(?2)) = ?2
+             AND (expires_at IS NULL OR expires_at > ?3) ORDER BY key",
+        )?;
+        let rows = stmt.query_map(params![namespace, prefix, now], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+            ))
+        })?;
+
+        rows.map(|row| -> Result<KvEntry, KvError> {
+            let (key, value, expires_at) = row?;
+            Ok(KvEntry {
+                key,
+                value: serde_json::from_str(&value).map_err(|e| KvError::Storage(e.to_string()))?,
+                expires_in_secs: expires_at
+                    .map(|at| u64::try_from((at - now) / 1000).unwrap_or_default()),
+            })
+        })
+        .collect()
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX))
+}
+
+fn store(state: &OpState) -> Result<&KvStore, KvError> {
+    state.try_borrow::<KvStore>().ok_or(KvError::Disabled)
+}
+
+#[op2]
+#[serde]
+pub(crate) fn op_kv_get(
+    state: &mut OpState,
+    #[string] namespace: String,
+    #[string] key: String,
+) -> Result<Option<Value>, KvError> {
+    store(state)?.get(&namespace, &key)
+}
+
+#[op2]
+pub(crate) fn op_kv_set(
+    state: &mut OpState,
+    #[string] namespace: String,
+    #[string] key: String,
+    #[serde] value: Value,
+    #[serde] ttl_secs: Option<f64>,
+) -> Result<(), KvError> {
+    let ttl = ttl_secs
+        .map(|secs| Duration::try_from_secs_f64(secs).map_err(|_| KvError::InvalidTtl(secs)))
+        .transpose()?;
+    store(state)?.set(&namespace, &key, &value, ttl)
+}
+
+#[op2(fast)]
+pub(crate) fn op_kv_delete(
+    state: &mut OpState,
+    #[string] namespace: String,
+    #[string] key: String,
+) -> Result<bool, KvError> {
+    store(state)?.delete(&namespace, &key)
+}
+
+#[op2]
+#[serde]
+pub(crate) fn op_kv_list(
+    state: &mut OpState,
+    #[string] namespace: String,
+    #[string] prefix: String,
+) -> Result<Vec<KvEntry>, KvError> {
+    store(state)?.list(&namespace, &prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pctx_config::kv::{KvConfig, KvNamespaceConfig};
+    use serde_json::json;
+
+    use super::KvStore;
+    use crate::error::KvError;
+
+    #[test]
+    fn test_namespaces_and_quotas() {
+        let store = KvStore::in_memory(KvConfig {
+            max_value_bytes: 16,
+            max_namespace_bytes: 24,
+            namespaces: [(
+                "small".to_string(),
+                KvNamespaceConfig {
+                    max_bytes: Some(8),
+                    ttl_secs: None,
+                },
+            )]
+            .into(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        store.set("a", "user:1", &json!({ "n": 1 }), None).unwrap();
+        store.set("a", "user:2", &json!([2]), None).unwrap();
+        store.set("b", "user:1", &json!("b"), None).unwrap();
+        assert_eq!(store.get("a", "user:1").unwrap(), Some(json!({ "n": 1 })));
+        assert_eq!(store.get("b", "user:1").unwrap(), Some(json!("b")));
+        assert_eq!(store.get("b", "user:2").unwrap(), None);
+
+        assert!(matches!(
+            store.set("a", "big", &json!("x".repeat(20)), None),
+            Err(KvError::TooLarge(..))
+        ));
+        assert!(matches!(
+            store.set("a", "user:3", &json!("xx"), None),
+            Err(KvError::QuotaExceeded(..))
+        ));
+        assert!(matches!(
+            store.set("small", "k", &json!("12345678"), None),
+            Err(KvError::QuotaExceeded(..))
+        ));
+        // replacing a value frees its size
+        store.set("a", "user:2", &json!(0), None).unwrap();
+
+        let keys: Vec<_> = store
+            .list("a", "user:")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(keys, ["user:1", "user:2"]);
+        assert!(store.delete("a", "user:1").unwrap());
+        assert!(!store.delete("a", "user:1").unwrap());
+    }
+
+    #[test]
+    fn test_ttl() {
+        let store = KvStore::in_memory(KvConfig {
+            ttl_secs: Some(0),
+            ..Default::default()
+        })
+        .unwrap();
+
+        store.set("a", "expired", &json!(1), None).unwrap();
+        store
+            .set("a", "kept", &json!(2), Some(Duration::from_secs(60)))
+            .unwrap();
+        assert_eq!(store.get("a", "expired").unwrap(), None);
+        let entries = store.list("a", "").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "kept");
+        assert!(entries[0].expires_in_secs.is_some_and(|s| s <= 60));
+    }
+
+    #[test]
+    fn test_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state/kv.sqlite");
+
+        let store = KvStore::open(&path, KvConfig::default()).unwrap();
+        store.set("a", "k", &json!({ "done": true }), None).unwrap();
+        drop(store);
+
+        let store = KvStore::open(&path, KvConfig::default()).unwrap();
+        assert_eq!(store.get("a", "k").unwrap(), Some(json!({ "done": true })));
+    }
+}
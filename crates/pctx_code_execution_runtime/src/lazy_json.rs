@@ -0,0 +1,51 @@
+//! JSON formatting of tool arguments & results for tracing fields
+//!
+//! Spans of tool calls record their arguments as JSON. Building a `serde_json::Value`
+//! and a `String` up front for every call copies large arguments twice, even if no
+//! subscriber ever formats the field. [`LazyJson`] serializes straight into the
+//! formatter when the field is recorded.
+
+use std::{fmt, io};
+
+use serde::Serialize;
+
+/// Displays the value as compact JSON, serialized only when it is formatted
+pub(crate) struct LazyJson<'a, T: ?Sized>(pub(crate) &'a T);
+
+impl<T: Serialize + ?Sized> fmt::Display for LazyJson<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        serde_json::to_writer(FormatterWriter(f), self.0).map_err(|_| fmt::Error)
+    }
+}
+
+// serde_json writes complete UTF-8 sequences, split only at ASCII escapes & punctuation
+struct FormatterWriter<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl io::Write for FormatterWriter<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = std::str::from_utf8(buf).map_err(io::Error::other)?;
+        self.0.write_str(text).map_err(io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::model::JsonObject;
+    use serde_json::json;
+
+    use super::LazyJson;
+
+    #[test]
+    fn test_lazy_json() {
+        let args: Option<JsonObject> = json!({ "name": "ünïcödé \"quoted\"", "n": [1, 2.5] })
+            .as_object()
+            .cloned();
+        assert_eq!(LazyJson(&args).to_string(), json!(args).to_string());
+        assert_eq!(LazyJson(&None::<JsonObject>).to_string(), "null");
+    }
+}
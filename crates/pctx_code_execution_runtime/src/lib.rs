@@ -8,6 +8,13 @@
 //! - **MCP Client API**: Register and call MCP tools from JavaScript
 //! - **Network Fetch**: Host-permission-based fetch with security controls
 //! - **Console Capturing**: Automatic stdout/stderr capture for testing and logging
+//! - **Standard Library**: Curated helpers exposed as `pctx.std` (see [`STD_DECLARATIONS`])
+//! - **WebAssembly**: Host supplied modules instantiated with `pctx.wasm.load(name)` (see [`WasmModules`])
+//! - **Artifacts**: Bytes & JSON handed between the executions of a session with `pctx.artifacts`, with quotas & TTL (see [`ArtifactStore`])
+//! - **Result Offloading**: Outputs too large to return are replaced by a handle, paged into with `pctx.results.fetch(handle, path)` (see [`ResultStore`])
+//! - **Key-Value Store**: Opt-in `pctx.kv` persisting JSON values across executions in SQLite, with per-namespace quotas & TTLs (see [`KvStore`])
+//! - **Virtual Filesystem**: Opt-in `pctx.fs` staging files in memory or a temporary directory, scoped by path (see [`VirtualFs`])
+//! - **Roots**: Workspace roots of the client listed with `pctx.roots.list()` & passed to callbacks (see [`InvocationContext`])
 //!
 //! The runtime is designed to be embedded in Deno-based JavaScript execution environments,
 //! providing a secure sandbox with controlled access to external services.
@@ -17,6 +24,15 @@
 //! - **MCP Integration**: Full Model Context Protocol client with server registry
 //! - **Permission System**: Host-based network access controls for fetch operations
 //! - **Output Capturing**: Automatic console.log/error capture to buffers
+//! - **Binary Results**: Image, audio & resource content of MCP tools as typed content blocks
+//! - **Result Caching**: Opt-in per-tool caching of idempotent MCP tool results
+//! - **Circuit Breaking**: Calls to failing upstream MCP servers fail fast until they recover
+//! - **Call Limits**: Per-tool rate limits (see [`RateLimiters`]) & per-execution tool call budgets
+//! - **Tool Registration**: Code & callbacks register tools for subsequent executions (see [`ToolRegistrations`])
+//! - **Recording & Replay**: MCP tool calls & callbacks recorded and replayed without calling the tools (see [`CallTape`])
+//! - **Output Schema Inference**: Opt-in recording of the shapes of MCP tool results
+//! - **Process Supervision**: Long-lived stdio MCP server processes, restarted on crash & shut down when idle
+//! - **Cancellation**: Pending MCP tool calls & callbacks are dropped when the execution is cancelled or times out (see [`ExecutionSignal`])
 //! - **V8 Snapshot**: Pre-compiled runtime for instant startup
 //! - **Type Safety**: Full TypeScript type definitions included
 //!
@@ -46,7 +62,7 @@
 //!
 //! - Network access is controlled via `AllowedHosts` whitelist
 //! - Each runtime instance has its own isolated MCP registry
-//! - No file system access is provided by default
+//! - No file system access is provided by default, `pctx.fs` only reaches the files of its [`VirtualFs`]
 //!
 //! ## Performance
 //!
@@ -54,15 +70,54 @@
 //! - **Memory**: ~2MB base runtime overhead
 //! - **Operations**: Rust ops provide native performance
 
+mod artifact_ops;
 mod callback_ops;
 mod callback_registry;
+mod circuit_breaker;
 mod error;
+mod fs_ops;
 mod js_error_impl;
+pub mod json_path;
+mod kv_ops;
+mod lazy_json;
+mod limits;
 pub mod mcp_ops;
 mod mcp_registry;
+mod output_schemas;
+mod recording;
+mod result_ops;
+mod sessions;
+mod signal;
+mod stdio_supervisor;
+#[cfg(test)]
+mod test_server;
+mod tool_cache;
+mod tool_registrations;
+mod wasm_ops;
 
-pub use callback_registry::{CallbackFn, CallbackRegistry};
-pub use mcp_registry::MCPRegistry;
+pub use artifact_ops::{ArtifactData, ArtifactInfo, ArtifactStore};
+pub use callback_registry::{CallbackFn, CallbackRegistry, ExecutionIds, InvocationContext};
+pub use circuit_breaker::{CircuitBreakers, CircuitState};
+pub use error::{ArtifactError, FsError, KvError, McpError, ResultError};
+pub use fs_ops::VirtualFs;
+pub use kv_ops::{DEFAULT_KV_NAMESPACE, KvEntry, KvStore};
+pub use limits::RateLimiters;
+pub use mcp_registry::{MCPRegistry, ToolCallForwarder};
+pub use output_schemas::{OutputSchemas, infer_schema, merge_schemas};
+pub use recording::{CallTape, RecordedCall, RecordedOutcome};
+pub use result_ops::ResultStore;
+pub use signal::ExecutionSignal;
+pub use stdio_supervisor::StdioSupervisor;
+pub use tool_cache::ToolCache;
+pub use tool_registrations::{ToolDefinition, ToolRegistrations};
+pub use wasm_ops::WasmModules;
+
+/// TypeScript declarations of the `pctx.std` helpers and the `pctx.wasm`, `pctx.fs`,
+/// `pctx.artifacts`, `pctx.results`, `pctx.kv` & `pctx.roots` APIs
+/// available in every execution
+///
+/// Included in scripts so they type check, and in the function listings shown to LLMs.
+pub const STD_DECLARATIONS: &str = include_str!("std.d.ts");
 
 /// Pre-compiled V8 snapshot containing the PCTX runtime
 ///
@@ -85,15 +140,37 @@ deno_core::extension!(
     ops = [
         mcp_ops::op_call_mcp_tool,
         callback_ops::op_invoke_callback,
+        callback_ops::op_register_tool,
+        mcp_ops::op_roots,
+        wasm_ops::op_wasm_module,
+        fs_ops::op_fs_read,
+        fs_ops::op_fs_write,
+        fs_ops::op_fs_remove,
+        fs_ops::op_fs_exists,
+        fs_ops::op_fs_list,
+        artifact_ops::op_artifact_put_bytes,
+        artifact_ops::op_artifact_put_json,
+        artifact_ops::op_artifact_kind,
+        artifact_ops::op_artifact_get_bytes,
+        artifact_ops::op_artifact_get_json,
+        artifact_ops::op_artifact_delete,
+        artifact_ops::op_artifact_list,
+        result_ops::op_results_fetch,
+        kv_ops::op_kv_get,
+        kv_ops::op_kv_set,
+        kv_ops::op_kv_delete,
+        kv_ops::op_kv_list,
     ],
     esm_entry_point = "ext:pctx_runtime_snapshot/runtime.js",
-    esm = [ dir "src", "runtime.js" ],
+    esm = [ dir "src", "runtime.js", "std.js" ],
     options = {
         registry: MCPRegistry,
         callback_registry: CallbackRegistry,
+        wasm_modules: WasmModules,
     },
     state = |state, options| {
         state.put(options.registry);
         state.put(options.callback_registry);
+        state.put(options.wasm_modules);
     },
 );
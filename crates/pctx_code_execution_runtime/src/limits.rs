@@ -0,0 +1,148 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::error::McpError;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Rate limits of upstream MCP tools configured with `rate_limit_per_minute`,
+/// keyed on server & tool name
+///
+/// Share between executions so the limit applies to all calls of a tool instead of
+/// the calls of a single execution.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiters {
+    calls: Arc<Mutex<HashMap<String, VecDeque<Instant>>>>,
+}
+
+impl RateLimiters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a call of the tool if less than `per_minute` calls were made during
+    /// the last minute
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tool reached its rate limit
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn acquire(&self, server: &str, tool: &str, per_minute: u32) -> Result<(), McpError> {
+        let now = Instant::now();
+        let mut calls = self.calls.lock().unwrap();
+        let window = calls.entry(format!("{server}.{tool}")).or_default();
+        while window
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW)
+        {
+            window.pop_front();
+        }
+
+        if window.len() >= per_minute as usize {
+            let retry_after = window.front().map_or(Duration::ZERO, |t| {
+                RATE_WINDOW.saturating_sub(now.duration_since(*t))
+            });
+            return Err(McpError::LimitExceeded(format!(
+                "{server}.{tool} is rate limited to {per_minute} calls per minute, retry in {}s",
+                retry_after.as_secs() + 1
+            )));
+        }
+
+        window.push_back(now);
+        Ok(())
+    }
+}
+
+/// Tool calls of a single execution, limited by a budget of total calls and the
+/// `max_calls_per_execution` of each tool
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallBudget {
+    max_total: Option<u32>,
+    spent: Arc<Mutex<(u32, HashMap<String, u32>)>>,
+}
+
+impl CallBudget {
+    pub(crate) fn new(max_total: Option<u32>) -> Self {
+        Self {
+            max_total,
+            spent: Arc::default(),
+        }
+    }
+
+    /// Spends a call of the tool from the budget
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the execution or the tool ran out of calls
+    pub(crate) fn spend(
+        &self,
+        server: &str,
+        tool: &str,
+        max_for_tool: Option<u32>,
+    ) -> Result<(), McpError> {
+        let mut spent = self.spent.lock().unwrap();
+        let (total, per_tool) = &mut *spent;
+        let id = format!("{server}.{tool}");
+
+        if let Some(max) = self.max_total
+            && *total >= max
+        {
+            return Err(McpError::LimitExceeded(format!(
+                "execution budget of {max} tool calls is spent, calling {id} was not attempted"
+            )));
+        }
+        let tool_calls = per_tool.entry(id.clone()).or_default();
+        if let Some(max) = max_for_tool
+            && *tool_calls >= max
+        {
+            return Err(McpError::LimitExceeded(format!(
+                "{id} can be called at most {max} times per execution"
+            )));
+        }
+
+        *total += 1;
+        *tool_calls += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CallBudget, RateLimiters};
+    use crate::error::McpError;
+
+    #[test]
+    fn test_rate_limit() {
+        let limiters = RateLimiters::new();
+        assert!(limiters.acquire("github", "search", 2).is_ok());
+        assert!(limiters.acquire("github", "search", 2).is_ok());
+        assert!(matches!(
+            limiters.acquire("github", "search", 2),
+            Err(McpError::LimitExceeded(_))
+        ));
+        // limits are per tool
+        assert!(limiters.acquire("github", "get_issue", 2).is_ok());
+    }
+
+    #[test]
+    fn test_call_budget() {
+        let budget = CallBudget::new(Some(3));
+        assert!(budget.spend("github", "search", Some(1)).is_ok());
+        assert!(matches!(
+            budget.spend("github", "search", Some(1)),
+            Err(McpError::LimitExceeded(_))
+        ));
+        assert!(budget.spend("github", "get_issue", None).is_ok());
+        assert!(budget.spend("github", "get_issue", None).is_ok());
+        assert!(matches!(
+            budget.spend("github", "get_issue", None),
+            Err(McpError::LimitExceeded(_))
+        ));
+    }
+}
@@ -4,12 +4,13 @@
 
 use deno_core::OpState;
 use deno_core::op2;
-use rmcp::model::JsonObject;
+use rmcp::model::{JsonObject, Root};
 use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::error::McpError;
 use crate::mcp_registry::MCPRegistry;
+use crate::{CallTape, ExecutionSignal};
 
 /// Call an MCP tool (async op)
 #[op2(async)]
@@ -20,9 +21,39 @@ pub(crate) async fn op_call_mcp_tool(
     #[string] tool_name: String,
     #[serde] args: Option<JsonObject>,
 ) -> Result<serde_json::Value, McpError> {
-    let registry = {
+    let (registry, signal, tape) = {
         let borrowed = state.borrow();
-        borrowed.borrow::<MCPRegistry>().clone()
+        (
+            borrowed.borrow::<MCPRegistry>().clone(),
+            borrowed
+                .try_borrow::<ExecutionSignal>()
+                .cloned()
+                .unwrap_or_default(),
+            borrowed.try_borrow::<CallTape>().cloned(),
+        )
     };
-    crate::mcp_registry::call_mcp_tool(&registry, &server_name, &tool_name, args).await
+
+    // arguments are only copied when the call is recorded
+    match tape {
+        Some(tape) => {
+            let arguments = args.clone().map(serde_json::Value::Object);
+            let call =
+                crate::mcp_registry::call_mcp_tool(&registry, &server_name, &tool_name, args);
+            signal
+                .guard(tape.run(format!("{server_name}.{tool_name}"), arguments, call))
+                .await
+        }
+        None => {
+            let call =
+                crate::mcp_registry::call_mcp_tool(&registry, &server_name, &tool_name, args);
+            signal.guard(call).await
+        }
+    }
+}
+
+/// Workspace roots of the client the code is executed for
+#[op2]
+#[serde]
+pub(crate) fn op_roots(state: &mut OpState) -> Vec<Root> {
+    state.borrow::<MCPRegistry>().roots()
 }
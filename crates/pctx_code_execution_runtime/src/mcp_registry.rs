@@ -1,22 +1,197 @@
+use crate::circuit_breaker::CircuitBreakers;
 use crate::error::McpError;
-use pctx_config::server::ServerConfig;
-use rmcp::model::{CallToolRequestParams, JsonObject, RawContent};
+use crate::lazy_json::LazyJson;
+use crate::limits::{CallBudget, RateLimiters};
+use crate::output_schemas::OutputSchemas;
+use crate::sessions::Sessions;
+use crate::stdio_supervisor::StdioSupervisor;
+use crate::tool_cache::{CacheKey, ToolCache};
+use opentelemetry::{KeyValue, metrics::Counter};
+use pctx_config::client::{ApprovalRequest, ClientHandlers};
+use pctx_config::retry::RetryConfig;
+use pctx_config::server::{McpConnectionError, ServerConfig};
+use rmcp::ServiceError;
+use rmcp::model::{
+    CallToolRequestParams, CallToolResult, JsonObject, Meta, NumberOrString, ProgressToken,
+    RawContent, Root,
+};
 use serde_json::json;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use tracing::{info, instrument, warn};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+use tracing::{Span, info, instrument, warn};
+
+/// Retries of failed upstream tool calls, counted by server & tool
+static TOOL_RETRIES: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    opentelemetry::global::meter("pctx")
+        .u64_counter("pctx.mcp.tool_retries")
+        .with_description("Retries of failed upstream MCP tool calls")
+        .build()
+});
+
+/// Makes the MCP tool calls of a registry in another process, called with the server &
+/// tool name and the arguments of the call
+///
+/// Isolated execution processes forward their calls to the parent process, which holds
+/// the rate limits, caches, circuit breakers & client handlers shared between executions.
+pub type ToolCallForwarder = Arc<
+    dyn Fn(
+            String,
+            String,
+            Option<JsonObject>,
+        ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, McpError>> + Send>>
+        + Send
+        + Sync,
+>;
 
 /// Singleton registry for MCP server configurations
 #[derive(Clone)]
 pub struct MCPRegistry {
     configs: Arc<RwLock<HashMap<String, ServerConfig>>>,
+
+    // makes the tool calls of the registry instead of calling upstream servers
+    forwarder: Option<ToolCallForwarder>,
+
+    // results of cacheable tools for the lifetime of the registry
+    execution_cache: ToolCache,
+    // results of cacheable tools with a TTL, shared between registries
+    shared_cache: Option<ToolCache>,
+    // circuit breakers of the upstream servers
+    circuit_breakers: CircuitBreakers,
+    // rate limits of tools, shared between registries
+    rate_limiters: RateLimiters,
+    // tool calls made by the execution
+    budget: CallBudget,
+    // long-lived processes of stdio servers, spawned per registry if unset
+    stdio_supervisor: Option<StdioSupervisor>,
+    // sessions with upstream servers, shared by the calls of the registry
+    sessions: Sessions,
+    // inferred result schemas of servers with `infer_output_schemas`
+    output_schemas: Option<OutputSchemas>,
+    // handlers of requests upstream servers send during tool calls
+    client_handlers: ClientHandlers,
 }
 
 impl MCPRegistry {
     pub fn new() -> Self {
         Self {
             configs: Arc::new(RwLock::new(HashMap::new())),
+            forwarder: None,
+            execution_cache: ToolCache::new(),
+            shared_cache: None,
+            circuit_breakers: CircuitBreakers::new(),
+            rate_limiters: RateLimiters::new(),
+            budget: CallBudget::default(),
+            stdio_supervisor: None,
+            sessions: Sessions::default(),
+            output_schemas: None,
+            client_handlers: ClientHandlers::default(),
+        }
+    }
+
+    /// Call stdio servers through supervised long-lived processes instead
+    /// of spawning a process for every tool call
+    #[must_use]
+    pub fn with_stdio_supervisor(mut self, supervisor: StdioSupervisor) -> Self {
+        self.stdio_supervisor = Some(supervisor);
+        self
+    }
+
+    /// Use circuit breakers shared with other registries, so calls to
+    /// failing upstream servers fail fast across executions
+    #[must_use]
+    pub fn with_circuit_breakers(mut self, breakers: CircuitBreakers) -> Self {
+        self.circuit_breakers = breakers;
+        self
+    }
+
+    /// Use rate limits shared with other registries, so tools configured with
+    /// `rate_limit_per_minute` are limited across executions
+    #[must_use]
+    pub fn with_rate_limiters(mut self, limiters: RateLimiters) -> Self {
+        self.rate_limiters = limiters;
+        self
+    }
+
+    /// Limit the total number of tool calls made through this registry
+    #[must_use]
+    pub fn with_max_tool_calls(mut self, max: u32) -> Self {
+        self.budget = CallBudget::new(Some(max));
+        self
+    }
+
+    /// Use a cache shared with other registries for results of tools
+    /// configured with a cache `ttl_secs`
+    #[must_use]
+    pub fn with_shared_cache(mut self, cache: ToolCache) -> Self {
+        self.shared_cache = Some(cache);
+        self
+    }
+
+    /// Record the shapes of results of servers configured with
+    /// `infer_output_schemas` to the provided schemas
+    #[must_use]
+    pub fn with_output_schemas(mut self, schemas: OutputSchemas) -> Self {
+        self.output_schemas = Some(schemas);
+        self
+    }
+
+    /// Handle requests upstream servers send during tool calls (e.g. sampling)
+    /// with the provided handlers
+    #[must_use]
+    pub fn with_client_handlers(mut self, handlers: ClientHandlers) -> Self {
+        self.client_handlers = handlers;
+        self
+    }
+
+    /// Forward all tool calls to `forwarder` instead of calling the upstream servers,
+    /// approvals, limits & caches are then applied by the forwarder
+    #[must_use]
+    pub fn with_forwarder(mut self, forwarder: ToolCallForwarder) -> Self {
+        self.forwarder = Some(forwarder);
+        self
+    }
+
+    /// Workspace roots of the client the code is executed for, empty if it has none
+    pub fn roots(&self) -> Vec<Root> {
+        self.client_handlers.roots.clone().unwrap_or_default()
+    }
+
+    /// Calls the tool of a registered server like executed code does, applying the
+    /// approvals, limits, caches & retries of the registry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server is not registered or the call fails
+    pub async fn call_tool(
+        &self,
+        server_name: &str,
+        tool_name: &str,
+        args: Option<JsonObject>,
+    ) -> Result<serde_json::Value, McpError> {
+        call_mcp_tool(self, server_name, tool_name, args).await
+    }
+
+    /// Closes the sessions tool calls opened with upstream servers, supervised stdio
+    /// processes keep running
+    pub async fn close_sessions(&self) {
+        self.sessions.close().await;
+    }
+
+    fn cached_result(&self, key: &CacheKey) -> Option<serde_json::Value> {
+        self.execution_cache
+            .get(key)
+            .or_else(|| self.shared_cache.as_ref()?.get(key))
+    }
+
+    fn cache_result(&self, key: CacheKey, value: &serde_json::Value, ttl_secs: Option<u64>) {
+        if let (Some(ttl), Some(shared)) = (ttl_secs, &self.shared_cache) {
+            shared.insert(key.clone(), value.clone(), Some(Duration::from_secs(ttl)));
         }
+        self.execution_cache.insert(key, value.clone(), None);
     }
 
     /// Register an MCP server configuration
@@ -88,10 +263,17 @@ impl Default for MCPRegistry {
 }
 
 /// Call an MCP tool on a registered server
+///
+/// Failed attempts are retried according to the server's retry policy,
+/// the number of retries is recorded on the `invoke_mcp_tool` span.
 #[instrument(
     name = "invoke_mcp_tool",
     skip_all,
-    fields(id=format!("{server_name}.{tool_name}"), args = json!(args).to_string()),
+    fields(
+        id=format!("{server_name}.{tool_name}"),
+        args = %LazyJson(&args),
+        retries = tracing::field::Empty,
+    ),
     ret(Display),
     err
 )]
@@ -101,6 +283,10 @@ pub(crate) async fn call_mcp_tool(
     tool_name: &str,
     args: Option<JsonObject>,
 ) -> Result<serde_json::Value, McpError> {
+    if let Some(forwarder) = &registry.forwarder {
+        return forwarder(server_name.into(), tool_name.into(), args).await;
+    }
+
     // Get the server config from registry
     let mcp_cfg = registry.get(server_name).ok_or_else(|| {
         McpError::ToolCall(format!(
@@ -108,56 +294,418 @@ pub(crate) async fn call_mcp_tool(
         ))
     })?;
 
-    let client = match mcp_cfg.connect().await {
-        Ok(client) => client,
-        Err(err) => {
-            warn!(
-                server = %server_name,
-                error = %err,
-                "Could not connect to MCP: initialization failure"
-            );
-            return Err(McpError::Connection(err.to_string()));
+    // Tools requiring approval wait for the user's decision before anything else
+    if mcp_cfg.tool_requires_approval(tool_name) {
+        let request = ApprovalRequest {
+            server: server_name.into(),
+            tool: tool_name.into(),
+            arguments: args.clone(),
+        };
+        let approved = registry
+            .client_handlers
+            .request_approval(&request)
+            .await
+            .map_err(|e| {
+                McpError::Denied(format!(
+                    "Approval of {server_name}.{tool_name} could not be requested: {e}"
+                ))
+            })?;
+        if !approved {
+            warn!(server = %server_name, tool = %tool_name, "Tool call was not approved");
+            return Err(McpError::Denied(format!(
+                "Call of {server_name}.{tool_name} was not approved"
+            )));
+        }
+    }
+
+    // Calls beyond the budget of the execution are rejected, even if cached
+    let tool_cfg = mcp_cfg.tool_config(tool_name);
+    registry.budget.spend(
+        server_name,
+        tool_name,
+        tool_cfg.and_then(|t| t.max_calls_per_execution),
+    )?;
+
+    // Serve cacheable tools from cache when called with identical arguments
+    let cache_cfg = mcp_cfg.tool_cache(tool_name).cloned();
+    let cache_key = cache_cfg
+        .as_ref()
+        .map(|_| CacheKey::new(server_name, tool_name, args.as_ref()));
+    if let Some(cached) = cache_key.as_ref().and_then(|k| registry.cached_result(k)) {
+        info!(cached = true, result = %LazyJson(&cached), "Tool result");
+        return Ok(cached);
+    }
+
+    // Rate limits protect the upstream server, cached results don't count towards them
+    if let Some(per_minute) = tool_cfg.and_then(|t| t.rate_limit_per_minute) {
+        registry
+            .rate_limiters
+            .acquire(server_name, tool_name, per_minute)?;
+    }
+
+    // Fail fast if the server has been failing
+    let breaker = mcp_cfg.circuit_breaker.clone().unwrap_or_default();
+    registry.circuit_breakers.check(server_name, &breaker)?;
+
+    let retry = mcp_cfg.retry.clone().unwrap_or_default();
+    let idempotent = mcp_cfg.tool_idempotent(tool_name);
+    let mut retries = 0;
+    let tool_result = loop {
+        match call_tool_attempt(registry, &mcp_cfg, tool_name, args.clone()).await {
+            Ok(res) => {
+                registry.circuit_breakers.record_success(server_name);
+                break res;
+            }
+            Err(err) if retries < retry.max_retries && err.is_retryable(&retry, idempotent) => {
+                let backoff = retry.backoff(retries);
+                retries += 1;
+                TOOL_RETRIES.add(
+                    1,
+                    &[
+                        KeyValue::new("server", server_name.to_string()),
+                        KeyValue::new("tool", tool_name.to_string()),
+                    ],
+                );
+                warn!(
+                    server = %server_name,
+                    tool = %tool_name,
+                    error = %err,
+                    retry = retries,
+                    backoff_ms = backoff.as_millis(),
+                    "Retrying failed MCP tool call"
+                );
+                tokio::time::sleep(backoff).await;
+            }
+            Err(err) => {
+                Span::current().record("retries", retries);
+                if err.is_upstream_failure() {
+                    if registry
+                        .circuit_breakers
+                        .record_failure(server_name, &breaker)
+                    {
+                        warn!(
+                            server = %server_name,
+                            cooldown_secs = breaker.cooldown_secs,
+                            "Circuit breaker opened for MCP server"
+                        );
+                    }
+                } else {
+                    // the server responded, so it is available
+                    registry.circuit_breakers.record_success(server_name);
+                }
+                return Err(err.into_mcp_error(server_name, tool_name));
+            }
         }
     };
-    let tool_result = client
-        .call_tool(CallToolRequestParams {
-            name: tool_name.to_string().into(),
-            arguments: args,
-            task: None,
-            meta: None,
-        })
-        .await
-        .map_err(|e| {
-            McpError::ToolCall(format!(
-                "Tool call \"{server_name}.{tool_name}\" failed: {e}"
-            ))
-        })?;
-    let _ = client.cancel().await;
+    Span::current().record("retries", retries);
 
     // Check if the tool call resulted in an error
     if tool_result.is_error.unwrap_or(false) {
-        return Err(McpError::ToolCall(format!(
-            "Tool call \"{server_name}.{tool_name}\" failed"
-        )));
+        let reason = tool_result.content.iter().find_map(|c| match &**c {
+            RawContent::Text(text) => Some(text.text.clone()),
+            _ => None,
+        });
+        return Err(McpError::Tool {
+            message: match reason {
+                Some(reason) => format!("Tool call \"{server_name}.{tool_name}\" failed: {reason}"),
+                None => format!("Tool call \"{server_name}.{tool_name}\" failed"),
+            },
+            code: None,
+            data: Some(
+                tool_result
+                    .structured_content
+                    .unwrap_or_else(|| json!(tool_result.content)),
+            ),
+        });
     }
 
     // Prefer structuredContent if available, otherwise use content array
     let has_structured = tool_result.structured_content.is_some();
     let val = if let Some(structured) = tool_result.structured_content {
         structured
+    } else if tool_result
+        .content
+        .iter()
+        .any(|c| !matches!(&**c, RawContent::Text(_)))
+    {
+        // Image, audio & resource content is passed as typed content blocks
+        serde_json::Value::Array(
+            tool_result
+                .content
+                .iter()
+                .map(|c| content_block(json!(c)))
+                .collect(),
+        )
     } else if let Some(RawContent::Text(text_content)) = tool_result.content.first().map(|a| &**a) {
         // Try to parse as JSON, fallback to string value
         serde_json::from_str(&text_content.text)
-            .or_else(|_| Ok(serde_json::Value::String(text_content.text.clone())))
-            .map_err(|e: serde_json::Error| {
-                McpError::ToolCall(format!("Failed to parse content: {e}"))
-            })?
+            .unwrap_or_else(|_| serde_json::Value::String(text_content.text.clone()))
     } else {
         // Return the whole content array as JSON
         json!(tool_result.content)
     };
 
-    info!(structured_content = has_structured, result =? &val, "Tool result");
+    info!(structured_content = has_structured, result = %LazyJson(&val), "Tool result");
+
+    if mcp_cfg.infer_output_schemas
+        && let Some(schemas) = &registry.output_schemas
+    {
+        schemas.record(server_name, tool_name, &val);
+    }
+
+    if let (Some(key), Some(cfg)) = (cache_key, cache_cfg) {
+        registry.cache_result(key, &val, cfg.ttl_secs);
+    }
 
     Ok(val)
 }
+
+/// Flattens embedded resources of a content block, so binary data is always in `data`
+/// with its content type in `mimeType` (e.g. `{"type": "resource", "uri": ..., "mimeType": ..., "data": ...}`)
+fn content_block(block: serde_json::Value) -> serde_json::Value {
+    let serde_json::Value::Object(mut block) = block else {
+        return block;
+    };
+
+    if block.get("type").and_then(serde_json::Value::as_str) == Some("resource")
+        && let Some(serde_json::Value::Object(resource)) = block.remove("resource")
+    {
+        for (key, value) in resource {
+            let key = if key == "blob" { "data".into() } else { key };
+            block.insert(key, value);
+        }
+    }
+
+    serde_json::Value::Object(block)
+}
+
+/// Failure of a single tool call attempt
+#[derive(Debug, thiserror::Error)]
+enum AttemptError {
+    #[error(transparent)]
+    Connection(#[from] McpConnectionError),
+    #[error(transparent)]
+    Call(#[from] ServiceError),
+}
+
+impl AttemptError {
+    /// Network errors, transport failures & timeouts, meaning the upstream
+    /// server could not be reached or did not respond
+    fn is_upstream_failure(&self) -> bool {
+        matches!(
+            self,
+            AttemptError::Connection(McpConnectionError::Failed(_))
+                | AttemptError::Call(
+                    ServiceError::TransportSend(_)
+                        | ServiceError::TransportClosed
+                        | ServiceError::Timeout { .. },
+                )
+        )
+    }
+
+    /// Connection failures are always retryable as the call was not sent, JSON-RPC errors
+    /// only if their code is configured in the retry policy. Transport failures & timeouts
+    /// only if the tool is idempotent, as the upstream server may have received the call.
+    fn is_retryable(&self, retry: &RetryConfig, idempotent: bool) -> bool {
+        match self {
+            AttemptError::Connection(McpConnectionError::Failed(_)) => true,
+            AttemptError::Call(ServiceError::McpError(e)) => retry.retries_code(e.code.0),
+            _ => idempotent && self.is_upstream_failure(),
+        }
+    }
+
+    fn into_mcp_error(self, server_name: &str, tool_name: &str) -> McpError {
+        match self {
+            AttemptError::Connection(err) => {
+                warn!(
+                    server = %server_name,
+                    error = %err,
+                    "Could not connect to MCP: initialization failure"
+                );
+                McpError::Connection(err.to_string())
+            }
+            AttemptError::Call(ServiceError::McpError(e)) => McpError::Tool {
+                message: format!(
+                    "Tool call \"{server_name}.{tool_name}\" failed: {}",
+                    e.message
+                ),
+                code: Some(e.code.0),
+                data: e.data,
+            },
+            AttemptError::Call(e @ ServiceError::Timeout { .. }) => McpError::Timeout(format!(
+                "Tool call \"{server_name}.{tool_name}\" failed: {e}"
+            )),
+            AttemptError::Call(
+                e @ (ServiceError::TransportSend(_) | ServiceError::TransportClosed),
+            ) => McpError::Connection(format!(
+                "Tool call \"{server_name}.{tool_name}\" failed: {e}"
+            )),
+            AttemptError::Call(e) => McpError::ToolCall(format!(
+                "Tool call \"{server_name}.{tool_name}\" failed: {e}"
+            )),
+        }
+    }
+}
+
+// progress tokens of tool calls, unique per process
+static PROGRESS_TOKENS: AtomicU64 = AtomicU64::new(0);
+
+async fn call_tool_attempt(
+    registry: &MCPRegistry,
+    mcp_cfg: &ServerConfig,
+    tool_name: &str,
+    args: Option<JsonObject>,
+) -> Result<CallToolResult, AttemptError> {
+    let mut params = CallToolRequestParams {
+        name: tool_name.to_string().into(),
+        arguments: args,
+        task: None,
+        meta: None,
+    };
+    // upstream servers only report progress of calls with a progress token
+    if registry.client_handlers.progress.is_some() {
+        let token = PROGRESS_TOKENS.fetch_add(1, Ordering::Relaxed);
+        let mut meta = Meta::new();
+        meta.set_progress_token(ProgressToken(NumberOrString::String(
+            format!("pctx-{token}").into(),
+        )));
+        params.meta = Some(meta);
+    }
+
+    // supervised stdio processes are kept running between calls
+    if let (Some(supervisor), Some(_)) = (&registry.stdio_supervisor, mcp_cfg.stdio()) {
+        let peer = supervisor.peer(mcp_cfg, &registry.client_handlers).await?;
+        return Ok(peer.call_tool(params).await?);
+    }
+
+    // concurrent calls share the session of the registry, a failed session is
+    // dropped so the next attempt reconnects
+    let peer = registry
+        .sessions
+        .peer(mcp_cfg, &registry.client_handlers)
+        .await?;
+    let tool_result = peer.call_tool(params).await.map_err(AttemptError::from);
+    if let Err(err) = &tool_result
+        && err.is_upstream_failure()
+    {
+        registry.sessions.evict(&mcp_cfg.name);
+    }
+
+    tool_result
+}
+
+#[cfg(test)]
+mod tests {
+    use pctx_config::server::ServerConfig;
+    use serde_json::{Value, json};
+
+    use super::{MCPRegistry, call_mcp_tool, content_block};
+    use crate::{
+        error::McpError,
+        test_server::{Reply, TestServer},
+    };
+
+    fn retry() -> Value {
+        json!({ "max_retries": 3, "initial_backoff_ms": 10, "retry_on_codes": [-32603] })
+    }
+
+    fn registry_of(cfg: ServerConfig) -> MCPRegistry {
+        let registry = MCPRegistry::new();
+        registry.add(cfg).unwrap();
+        registry
+    }
+
+    fn flaky(server: &TestServer, tools: &Value) -> ServerConfig {
+        server.config("flaky", &json!({ "retry": retry(), "tools": tools }))
+    }
+
+    #[test]
+    fn test_content_block() {
+        let image = json!({"type": "image", "data": "aGk=", "mimeType": "image/png"});
+        assert_eq!(content_block(image.clone()), image);
+
+        assert_eq!(
+            content_block(json!({
+                "type": "resource",
+                "resource": {"uri": "file:///a.pdf", "mimeType": "application/pdf", "blob": "aGk="}
+            })),
+            json!({"type": "resource", "uri": "file:///a.pdf", "mimeType": "application/pdf", "data": "aGk="})
+        );
+        assert_eq!(
+            content_block(json!({
+                "type": "resource",
+                "resource": {"uri": "file:///a.txt", "mimeType": "text/plain", "text": "hi"}
+            })),
+            json!({"type": "resource", "uri": "file:///a.txt", "mimeType": "text/plain", "text": "hi"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_idempotent_tool_after_transport_failures() {
+        let server = TestServer::new(&[Reply::Exit, Reply::Exit, Reply::Ok(json!("up"))]);
+        let registry = registry_of(flaky(&server, &json!({ "status": { "idempotent": true } })));
+
+        let result = call_mcp_tool(&registry, "flaky", "status", None).await;
+        assert_eq!(result.unwrap(), json!("up"));
+        assert_eq!(server.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_resend_non_idempotent_calls() {
+        let server = TestServer::new(&[Reply::Exit, Reply::Ok(json!("created"))]);
+        let registry = registry_of(flaky(&server, &json!({})));
+
+        let err = call_mcp_tool(&registry, "flaky", "create", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::Connection(_)), "{err}");
+        assert_eq!(server.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_configured_error_codes() {
+        let server =
+            TestServer::new(&[Reply::RpcError(-32603, "busy"), Reply::Ok(json!("created"))]);
+        let registry = registry_of(flaky(&server, &json!({})));
+        let result = call_mcp_tool(&registry, "flaky", "create", None).await;
+        assert_eq!(result.unwrap(), json!("created"));
+        assert_eq!(server.calls(), 2);
+
+        let server = TestServer::new(&[Reply::RpcError(-32602, "invalid params")]);
+        let registry = registry_of(flaky(&server, &json!({})));
+        let err = call_mcp_tool(&registry, "flaky", "create", None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                McpError::Tool {
+                    code: Some(-32602),
+                    ..
+                }
+            ),
+            "{err}"
+        );
+        assert_eq!(server.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_connection_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let starts = dir.path().join("starts");
+        let cfg = serde_json::from_value(json!({
+            "name": "down",
+            "command": "sh",
+            "args": ["-c", r#"echo started >> "$STARTS""#],
+            "env": { "STARTS": starts },
+            "retry": retry(),
+        }))
+        .unwrap();
+
+        let err = call_mcp_tool(&registry_of(cfg), "down", "create", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::Connection(_)), "{err}");
+        assert_eq!(std::fs::read_to_string(starts).unwrap().lines().count(), 4);
+    }
+}
@@ -0,0 +1,333 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use serde_json::{Map, Value, json};
+
+/// JSON schemas of MCP tool results inferred from real responses
+///
+/// Only results of servers configured with `infer_output_schemas` are recorded. Every
+/// recorded result refines the tool's schema, so it describes all results seen so far:
+/// object properties missing from some results become optional and differing types unions.
+#[derive(Debug, Clone, Default)]
+pub struct OutputSchemas {
+    schemas: Arc<RwLock<HashMap<(String, String), Value>>>,
+    changed: Arc<AtomicBool>,
+}
+
+impl OutputSchemas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refines the inferred schema of the tool with a result
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn record(&self, server: &str, tool: &str, result: &Value) {
+        let inferred = infer_schema(result);
+        let mut schemas = self.schemas.write().unwrap();
+        let key = (server.to_string(), tool.to_string());
+        let refined = match schemas.get(&key) {
+            Some(schema) => merge_schemas(schema, &inferred),
+            None => inferred,
+        };
+
+        if schemas.get(&key) != Some(&refined) {
+            schemas.insert(key, refined);
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Merges a previously inferred schema of the tool, e.g. loaded from disk
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn seed(&self, server: &str, tool: &str, schema: &Value) {
+        let mut schemas = self.schemas.write().unwrap();
+        let key = (server.to_string(), tool.to_string());
+        let merged = match schemas.get(&key) {
+            Some(existing) => merge_schemas(existing, schema),
+            None => schema.clone(),
+        };
+        schemas.insert(key, merged);
+    }
+
+    /// Returns the inferred schema of the tool, if any result was recorded
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn get(&self, server: &str, tool: &str) -> Option<Value> {
+        let schemas = self.schemas.read().unwrap();
+        schemas
+            .get(&(server.to_string(), tool.to_string()))
+            .cloned()
+    }
+
+    /// Inferred schemas of the server's tools keyed by tool name
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn server_schemas(&self, server: &str) -> HashMap<String, Value> {
+        let schemas = self.schemas.read().unwrap();
+        schemas
+            .iter()
+            .filter(|((s, _), _)| s == server)
+            .map(|((_, tool), schema)| (tool.clone(), schema.clone()))
+            .collect()
+    }
+
+    /// Returns `true` if a schema was refined since the last call
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// JSON schema describing the shape of a value
+pub fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({"type": "null"}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(n) if n.is_f64() => json!({"type": "number"}),
+        Value::Number(_) => json!({"type": "integer"}),
+        Value::String(_) => json!({"type": "string"}),
+        Value::Array(items) => {
+            let mut schema = json!({"type": "array"});
+            if let Some(items) = items
+                .iter()
+                .map(infer_schema)
+                .reduce(|a, b| merge_schemas(&a, &b))
+            {
+                schema["items"] = items;
+            }
+            schema
+        }
+        Value::Object(obj) => {
+            let properties: Map<String, Value> = obj
+                .iter()
+                .map(|(k, v)| (k.clone(), infer_schema(v)))
+                .collect();
+            let required: Vec<&String> = obj.keys().collect();
+            json!({"type": "object", "properties": properties, "required": required})
+        }
+    }
+}
+
+/// Schema describing the values of both schemas
+pub fn merge_schemas(a: &Value, b: &Value) -> Value {
+    if a == b {
+        return a.clone();
+    }
+    if let Some(variants) = a.get("anyOf").and_then(Value::as_array) {
+        return merge_into_any_of(variants, b);
+    }
+    if let Some(variants) = b.get("anyOf").and_then(Value::as_array) {
+        return merge_into_any_of(variants, a);
+    }
+
+    let nullable = is_nullable(a) || is_nullable(b);
+    let merged = match (kind(a), kind(b)) {
+        (Some("null"), _) => return with_nullable(b, true),
+        (_, Some("null")) => return with_nullable(a, true),
+        (Some("object"), Some("object")) => merge_objects(a, b),
+        (Some("array"), Some("array")) => {
+            let mut schema = json!({"type": "array"});
+            match (a.get("items"), b.get("items")) {
+                (Some(a), Some(b)) => schema["items"] = merge_schemas(a, b),
+                (Some(items), None) | (None, Some(items)) => schema["items"] = items.clone(),
+                (None, None) => {}
+            }
+            schema
+        }
+        (Some(x), Some(y)) if x == y => json!({"type": x}),
+        (Some("integer" | "number"), Some("integer" | "number")) => json!({"type": "number"}),
+        _ => return json!({"anyOf": [a, b]}),
+    };
+
+    with_nullable(&merged, nullable)
+}
+
+fn merge_objects(a: &Value, b: &Value) -> Value {
+    let empty = Map::new();
+    let props_a = a
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let props_b = b
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let mut properties = props_a.clone();
+    for (key, schema) in props_b {
+        let merged = match properties.get(key) {
+            Some(existing) => merge_schemas(existing, schema),
+            None => schema.clone(),
+        };
+        properties.insert(key.clone(), merged);
+    }
+
+    // properties are only required if every result had them
+    let required_b = required(b);
+    let required: Vec<&str> = required(a)
+        .into_iter()
+        .filter(|key| required_b.contains(key))
+        .collect();
+
+    json!({"type": "object", "properties": properties, "required": required})
+}
+
+fn merge_into_any_of(variants: &[Value], schema: &Value) -> Value {
+    let mut variants = variants.to_vec();
+    if let Some(variant) = variants.iter_mut().find(|v| compatible(v, schema)) {
+        *variant = merge_schemas(variant, schema);
+    } else {
+        variants.push(schema.clone());
+    }
+    json!({"anyOf": variants})
+}
+
+/// Whether the schemas merge into a single schema instead of a union
+fn compatible(a: &Value, b: &Value) -> bool {
+    match (kind(a), kind(b)) {
+        (Some("integer" | "number"), Some("integer" | "number")) => true,
+        (Some(x), Some(y)) => x == y,
+        _ => false,
+    }
+}
+
+fn required(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Type of the schema besides `null`
+fn kind(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(t) => Some(t),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .or(Some("null")),
+        _ => None,
+    }
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    match schema.get("type") {
+        Some(Value::Array(types)) => types.iter().any(|t| t == "null"),
+        _ => false,
+    }
+}
+
+fn with_nullable(schema: &Value, nullable: bool) -> Value {
+    let mut schema = schema.clone();
+    if let Some(kind) = kind(&schema).map(String::from)
+        && kind != "null"
+    {
+        schema["type"] = if nullable {
+            json!([kind, "null"])
+        } else {
+            json!(kind)
+        };
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{OutputSchemas, infer_schema, merge_schemas};
+
+    #[test]
+    fn test_infer_schema() {
+        assert_eq!(
+            infer_schema(&json!({"id": 1, "tags": ["a"], "score": 0.5})),
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "score": {"type": "number"}
+                },
+                "required": ["id", "tags", "score"]
+            })
+        );
+        assert_eq!(infer_schema(&json!([])), json!({"type": "array"}));
+    }
+
+    #[test]
+    fn test_merge_schemas() {
+        let merged = merge_schemas(
+            &infer_schema(&json!({"id": 1, "name": "a", "parent": null})),
+            &infer_schema(&json!({"id": 2.5, "parent": {"id": 1}})),
+        );
+        assert_eq!(
+            merged,
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": {"type": "number"},
+                    "name": {"type": "string"},
+                    "parent": {
+                        "type": ["object", "null"],
+                        "properties": {"id": {"type": "integer"}},
+                        "required": ["id"]
+                    }
+                },
+                "required": ["id", "parent"]
+            })
+        );
+
+        let union = merge_schemas(&json!({"type": "string"}), &json!({"type": "boolean"}));
+        assert_eq!(
+            union,
+            json!({"anyOf": [{"type": "string"}, {"type": "boolean"}]})
+        );
+        assert_eq!(
+            merge_schemas(&union, &json!({"type": "string"})),
+            union,
+            "merging a known variant is stable"
+        );
+    }
+
+    #[test]
+    fn test_output_schemas_record() {
+        let schemas = OutputSchemas::new();
+        assert!(schemas.get("github", "get_me").is_none());
+
+        schemas.record("github", "get_me", &json!({"login": "octocat"}));
+        assert!(schemas.take_changed());
+        schemas.record("github", "get_me", &json!({"login": "hubot"}));
+        assert!(
+            !schemas.take_changed(),
+            "same shape does not refine the schema"
+        );
+
+        schemas.record(
+            "github",
+            "get_me",
+            &json!({"login": "hubot", "bio": "robot"}),
+        );
+        assert!(schemas.take_changed());
+        assert_eq!(
+            schemas.get("github", "get_me").unwrap()["required"],
+            json!(["login"])
+        );
+        assert_eq!(schemas.server_schemas("github").len(), 1);
+        assert!(schemas.server_schemas("other").is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::McpError;
+
+/// MCP tool call or callback invocation made by an execution, with its result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// `server.tool` of MCP tools, the id of callbacks
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub outcome: RecordedOutcome,
+}
+
+/// Result of a recorded call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    Output(serde_json::Value),
+    Error(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TapeMode {
+    Record,
+    Replay,
+}
+
+/// Tool calls of an execution, either recorded while they are made or replayed
+/// instead of calling the tools
+///
+/// Replayed calls are matched on the tool & arguments, in the order they were
+/// recorded, so concurrent calls replay regardless of the order they are made in.
+#[derive(Debug, Clone)]
+pub struct CallTape {
+    mode: TapeMode,
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+}
+
+impl CallTape {
+    /// Tape recording the calls of an execution
+    pub fn recording() -> Self {
+        Self {
+            mode: TapeMode::Record,
+            calls: Arc::default(),
+        }
+    }
+
+    /// Tape answering the calls of an execution with the recorded `calls`
+    pub fn replaying(calls: Vec<RecordedCall>) -> Self {
+        Self {
+            mode: TapeMode::Replay,
+            calls: Arc::new(Mutex::new(calls)),
+        }
+    }
+
+    /// Returns `true` if calls are answered from the tape
+    pub fn is_replaying(&self) -> bool {
+        self.mode == TapeMode::Replay
+    }
+
+    /// Recorded calls, or the calls not replayed yet when replaying
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Makes the call with `call` and records it, or answers it from the tape without
+    /// making it when replaying
+    pub(crate) async fn run(
+        &self,
+        tool: String,
+        arguments: Option<serde_json::Value>,
+        call: impl Future<Output = Result<serde_json::Value, McpError>>,
+    ) -> Result<serde_json::Value, McpError> {
+        if self.is_replaying() {
+            let recorded = {
+                let mut calls = self.calls.lock().unwrap();
+                calls
+                    .iter()
+                    .position(|c| c.tool == tool && c.arguments == arguments)
+                    .map(|i| calls.remove(i))
+            };
+            return match recorded.map(|c| c.outcome) {
+                Some(RecordedOutcome::Output(output)) => Ok(output),
+                Some(RecordedOutcome::Error(error)) => Err(McpError::Replayed(error)),
+                None => Err(McpError::ToolCall(format!(
+                    "No recorded result for call of \"{tool}\" with these arguments"
+                ))),
+            };
+        }
+
+        let res = call.await;
+        let outcome = match &res {
+            Ok(output) => RecordedOutcome::Output(output.clone()),
+            Err(e) => RecordedOutcome::Error(e.to_string()),
+        };
+        self.calls.lock().unwrap().push(RecordedCall {
+            tool,
+            arguments,
+            outcome,
+        });
+        res
+    }
+}
@@ -0,0 +1,178 @@
+//! Store of execution outputs too large to return, exposed as `pctx.results`
+//!
+//! Offloaded outputs are replaced by a handle & a summary of their shape, so they never
+//! reach the LLM in full. Later executions select the parts they need with
+//! `pctx.results.fetch(handle, path)`, `path` being a [`JsonPath`].
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use deno_core::{OpState, op2};
+use pctx_config::results::ResultsConfig;
+use serde_json::{Value, json};
+
+use crate::{error::ResultError, infer_schema, json_path::JsonPath};
+
+/// Offloaded outputs, clones share their outputs
+#[derive(Debug, Clone, Default)]
+pub struct ResultStore {
+    config: ResultsConfig,
+    next_id: Arc<AtomicU64>,
+    results: Arc<Mutex<VecDeque<(String, Value)>>>,
+}
+
+impl ResultStore {
+    pub fn new(config: ResultsConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Offloading threshold & number of kept outputs
+    pub fn config(&self) -> &ResultsConfig {
+        &self.config
+    }
+
+    /// Replaces `output` with a handle & summary if it is larger than the offloading
+    /// threshold, keeping the output in the store
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn offload(&self, output: Value) -> Value {
+        let Some(threshold) = self.config.offload_threshold_bytes else {
+            return output;
+        };
+        let size = serde_json::to_vec(&output).map_or(0, |v| v.len());
+        if size <= threshold || self.config.max_results == 0 {
+            return output;
+        }
+
+        let handle = format!(
+            "result_{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let summary = summary(&handle, size, &output);
+
+        let mut results = self.results.lock().unwrap();
+        while results.len() >= self.config.max_results {
+            results.pop_front();
+        }
+        results.push_back((handle, output));
+        summary
+    }
+
+    /// Offloaded output of the handle, `None` if it does not exist or was dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn get(&self, handle: &str) -> Option<Value> {
+        self.results
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(h, _)| h == handle)
+            .map(|(_, v)| v.clone())
+    }
+
+    /// Part of the offloaded output selected by the `JSONPath` `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the handle does not exist or the path is invalid
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn fetch(&self, handle: &str, path: &str) -> Result<Value, ResultError> {
+        let path = JsonPath::parse(path)?;
+        let results = self.results.lock().unwrap();
+        let (_, output) = results
+            .iter()
+            .find(|(h, _)| h == handle)
+            .ok_or_else(|| ResultError::NotFound(handle.to_string()))?;
+        Ok(path.query(output))
+    }
+}
+
+/// Value returned instead of an offloaded output
+fn summary(handle: &str, size: usize, output: &Value) -> Value {
+    let mut summary = json!({
+        "offloaded": true,
+        "handle": handle,
+        "bytes": size,
+        "schema": infer_schema(output),
+        "hint": format!(
+            "The output was too large to return. Select the parts you need in a later execution with `pctx.results.fetch(\"{handle}\", \"$.path[0:10]\")`"
+        ),
+    });
+    match output {
+        Value::Array(items) => summary["length"] = json!(items.len()),
+        Value::Object(map) => summary["keys"] = json!(map.keys().collect::<Vec<_>>()),
+        _ => {}
+    }
+    summary
+}
+
+#[op2]
+#[serde]
+pub(crate) fn op_results_fetch(
+    state: &mut OpState,
+    #[string] handle: String,
+    #[string] path: String,
+) -> Result<Value, ResultError> {
+    state
+        .try_borrow::<ResultStore>()
+        .ok_or(ResultError::Disabled)?
+        .fetch(&handle, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use pctx_config::results::ResultsConfig;
+    use serde_json::json;
+
+    use super::ResultStore;
+    use crate::error::ResultError;
+
+    #[test]
+    fn test_offload() {
+        let store = ResultStore::new(ResultsConfig {
+            offload_threshold_bytes: Some(32),
+            max_results: 1,
+        });
+
+        assert_eq!(store.offload(json!([1, 2])), json!([1, 2]));
+
+        let output = json!({ "items": (0..20).map(|i| json!({ "id": i })).collect::<Vec<_>>() });
+        let summary = store.offload(output.clone());
+        assert_eq!(summary["handle"], json!("result_1"));
+        assert_eq!(summary["keys"], json!(["items"]));
+        assert_eq!(store.get("result_1"), Some(output.clone()));
+        assert_eq!(
+            store.fetch("result_1", "$.items[18:].id").unwrap(),
+            json!([18, 19])
+        );
+
+        // the oldest outputs are dropped
+        store.offload(output);
+        assert!(matches!(
+            store.fetch("result_1", "$"),
+            Err(ResultError::NotFound(_))
+        ));
+        assert!(store.get("result_2").is_some());
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let output = json!({ "items": vec![0; 10_000] });
+        assert_eq!(ResultStore::default().offload(output.clone()), output);
+    }
+}
@@ -1,5 +1,7 @@
 // PCTX Runtime - MCP Client and Console Capturing
 
+import { std } from "ext:pctx_runtime_snapshot/std.js";
+
 const core = Deno.core;
 const ops = core.ops;
 
@@ -37,38 +39,228 @@ function formatConsoleArgs(...args) {
     .join(" ");
 }
 
+// Serializes a console argument to a JSON compatible value
+function serializeConsoleArg(arg) {
+  if (arg === undefined) return null;
+  if (typeof arg === "bigint") return arg.toString();
+  if (arg instanceof Error) {
+    return { name: arg.name, message: arg.message, stack: arg.stack };
+  }
+  try {
+    return JSON.parse(JSON.stringify(arg)) ?? null;
+  } catch {
+    return String(arg);
+  }
+}
+
+// Position of the executed module in a stack trace, defaults to the console caller
+function consoleCallSite(stack = new Error().stack) {
+  const match = (stack ?? "").match(/file:\/\/\/execute\.js:(\d+):(\d+)/);
+  return match
+    ? { line: Number(match[1]), column: Number(match[2]) }
+    : { line: null, column: null };
+}
+
+// Renders console.table data as a text table
+function formatTable(data, columns) {
+  if (data === null || typeof data !== "object") {
+    return formatConsoleArgs(data);
+  }
+
+  const rows = Object.entries(data);
+  const keys = columns ?? [
+    ...new Set(
+      rows.flatMap(([, row]) =>
+        row !== null && typeof row === "object" ? Object.keys(row) : []
+      ),
+    ),
+  ];
+  const hasValues = rows.some(([, row]) =>
+    row === null || typeof row !== "object"
+  );
+  const header = ["(index)", ...keys, ...(hasValues ? ["Values"] : [])];
+  const cell = (value) =>
+    value === undefined ? "" : formatConsoleArgs(value);
+  const body = rows.map(([index, row]) => {
+    const isObject = row !== null && typeof row === "object";
+    return [
+      index,
+      ...keys.map((key) => cell(isObject ? row[key] : undefined)),
+      ...(hasValues ? [isObject ? "" : cell(row)] : []),
+    ];
+  });
+
+  const widths = header.map((h, i) =>
+    Math.max(h.length, ...body.map((row) => row[i].length))
+  );
+  const line = (cells) =>
+    `| ${cells.map((c, i) => c.padEnd(widths[i])).join(" | ")} |`;
+  const separator = `|${widths.map((w) => "-".repeat(w + 2)).join("|")}|`;
+  return [line(header), separator, ...body.map(line)].join("\n");
+}
+
 // Set up console output capturing
 globalThis.__stdout = [];
 globalThis.__stderr = [];
+// Structured console records: level, timestamp, call site & serialized args
+globalThis.__console = [];
 
-// Override console.log to capture stdout
-console.log = (...args) => {
-  globalThis.__stdout.push(formatConsoleArgs(...args));
-};
+function record(level, args, callSite = consoleCallSite()) {
+  globalThis.__console.push({
+    level,
+    timestamp: Date.now(),
+    ...callSite,
+    args: args.map(serializeConsoleArg),
+  });
+}
 
-// Override console.error to capture stderr
-console.error = (...args) => {
-  globalThis.__stderr.push(formatConsoleArgs(...args));
-};
+function capture(level, stream, args) {
+  stream.push(formatConsoleArgs(...args));
+  record(level, args);
+}
 
-// console.warn goes to stderr
-console.warn = (...args) => {
-  globalThis.__stderr.push(formatConsoleArgs(...args));
-};
+// console.log, console.info and console.debug go to stdout
+console.log = (...args) => capture("log", globalThis.__stdout, args);
+console.info = (...args) => capture("info", globalThis.__stdout, args);
+console.debug = (...args) => capture("debug", globalThis.__stdout, args);
 
-// console.info and console.debug go to stdout
-console.info = (...args) => {
-  globalThis.__stdout.push(formatConsoleArgs(...args));
+// console.table renders a text table to stdout
+console.table = (data, columns) => {
+  globalThis.__stdout.push(formatTable(data, columns));
+  record("table", columns === undefined ? [data] : [data, columns]);
 };
 
-console.debug = (...args) => {
-  globalThis.__stdout.push(formatConsoleArgs(...args));
-};
+// console.error and console.warn go to stderr
+console.error = (...args) => capture("error", globalThis.__stderr, args);
+console.warn = (...args) => capture("warn", globalThis.__stderr, args);
+
+// Unhandled promise rejections and uncaught errors (e.g. thrown in timer
+// callbacks) are reported to stderr like `window.onerror` instead of
+// aborting the execution
+function captureUncaught(level, prefix, error) {
+  const message = error instanceof Error
+    ? (error.stack ?? `${error.name}: ${error.message}`)
+    : formatConsoleArgs(error);
+  globalThis.__stderr.push(`${prefix} ${message}`);
+  record(
+    level,
+    [error],
+    error instanceof Error ? consoleCallSite(error.stack) : consoleCallSite(),
+  );
+}
+
+if (typeof core.setUnhandledPromiseRejectionHandler === "function") {
+  core.setUnhandledPromiseRejectionHandler((_promise, reason) => {
+    captureUncaught("unhandled_rejection", "Uncaught (in promise)", reason);
+    return true;
+  });
+}
+
+if (typeof core.setReportExceptionCallback === "function") {
+  core.setReportExceptionCallback((error) => {
+    captureUncaught("uncaught_error", "Uncaught", error);
+  });
+}
 
 // ============================================================================
 // MCP & Callback Operations
 // ============================================================================
 
+const CONTENT_TYPES = new Set([
+  "text",
+  "image",
+  "audio",
+  "resource",
+  "resource_link",
+]);
+
+/**
+ * Content block of an MCP tool result, see `pctx.mcp.Content` in std.d.ts
+ *
+ * Accessors live on the prototype, so the block serializes to the same
+ * JSON (including its `type` and `mimeType`) when returned from the script.
+ */
+class McpContent {
+  constructor(block) {
+    Object.assign(this, block);
+  }
+
+  /** Base64 of the content, text is encoded as UTF-8 */
+  base64() {
+    return this.data ?? std.base64.encode(this.text ?? "");
+  }
+
+  /** Bytes of the content, text is encoded as UTF-8 */
+  bytes() {
+    return this.data !== undefined
+      ? std.base64.decodeBytes(this.data)
+      : core.encode(this.text ?? "");
+  }
+}
+
+// Errors of failed tool calls, see `ToolCallError` in std.d.ts
+class ToolCallError extends Error {
+  constructor(message, { tool, code, data, cause } = {}) {
+    super(message, { cause });
+    this.name = new.target.name;
+    this.tool = tool;
+    if (code !== undefined) this.code = code;
+    if (data !== undefined) this.data = data;
+  }
+}
+class NetworkError extends ToolCallError {}
+class ToolError extends ToolCallError {}
+class ValidationError extends ToolError {}
+class PermissionDenied extends ToolCallError {}
+class Timeout extends ToolCallError {}
+
+const TOOL_CALL_ERRORS = {
+  ToolCallError,
+  NetworkError,
+  ToolError,
+  ValidationError,
+  PermissionDenied,
+  Timeout,
+};
+
+/**
+ * Converts an error thrown by a tool call op to its error class, ops
+ * report the class, code & data as properties of plain errors
+ * @param {unknown} error
+ * @param {string} tool
+ * @returns {unknown}
+ */
+function toolCallError(error, tool) {
+  const ErrorClass = TOOL_CALL_ERRORS[error?.pctxClass];
+  if (!ErrorClass) {
+    return error;
+  }
+  let data;
+  if (error.pctxData !== undefined) {
+    try {
+      data = JSON.parse(error.pctxData);
+    } catch {
+      data = error.pctxData;
+    }
+  }
+  return new ErrorClass(error.message, {
+    tool,
+    code: error.pctxCode,
+    data,
+    cause: error,
+  });
+}
+
+// Tool results with image, audio or resource content are arrays of content blocks
+function wrapContent(result) {
+  const isContent = Array.isArray(result) &&
+    result.length > 0 &&
+    result.every((b) => CONTENT_TYPES.has(b?.type)) &&
+    result.some((b) => b.type !== "text");
+
+  return isContent ? result.map((b) => new McpContent(b)) : result;
+}
+
 /**
  * Call an MCP tool
  * @template T
@@ -79,11 +271,17 @@ console.debug = (...args) => {
  * @returns {Promise<T>} The tool's response
  */
 export async function callMCPTool(call) {
-  return await ops.op_call_mcp_tool(
-    call.serverName,
-    call.toolName,
-    call.arguments,
-  );
+  let result;
+  try {
+    result = await ops.op_call_mcp_tool(
+      call.serverName,
+      call.toolName,
+      call.arguments,
+    );
+  } catch (e) {
+    throw toolCallError(e, `${call.serverName}.${call.toolName}`);
+  }
+  return wrapContent(result);
 }
 
 /**
@@ -95,9 +293,245 @@ export async function callMCPTool(call) {
  * @returns {Promise<T>} The tool's response
  */
 export async function invokeCallback(call) {
-  return await ops.op_invoke_callback(call.id, call.arguments);
+  try {
+    return await ops.op_invoke_callback(call.id, call.arguments);
+  } catch (e) {
+    throw toolCallError(e, call.id);
+  }
 }
 
 // Make APIs available globally for convenience (matching original behavior)
 globalThis.callMCPTool = callMCPTool;
 globalThis.invokeCallback = invokeCallback;
+Object.assign(globalThis, TOOL_CALL_ERRORS);
+
+// ============================================================================
+// WEBASSEMBLY MODULES
+// ============================================================================
+
+// Compiled host supplied modules, instantiated again on every load
+const wasmModules = new Map();
+
+/**
+ * Instantiates a WebAssembly module supplied by the host
+ * @param {string} name - Name of the module
+ * @param {object} [imports] - Imports of the module instance
+ * @returns {Promise<WebAssembly.Instance>}
+ */
+async function loadWasm(name, imports = {}) {
+  let module = wasmModules.get(name);
+  if (!module) {
+    module = await WebAssembly.compile(ops.op_wasm_module(name));
+    wasmModules.set(name, module);
+  }
+  return await WebAssembly.instantiate(module, imports);
+}
+
+const wasm = Object.freeze({ load: loadWasm });
+
+// ============================================================================
+// VIRTUAL FILESYSTEM
+// ============================================================================
+
+/**
+ * Reads the bytes of a virtual file
+ * @param {string} path - Virtual path, e.g. `/inputs/data.csv`
+ * @returns {Uint8Array}
+ */
+function readFile(path) {
+  return new Uint8Array(ops.op_fs_read(path));
+}
+
+/**
+ * Writes a virtual file, strings are encoded as UTF-8
+ * @param {string} path - Virtual path, e.g. `/scratch/out.json`
+ * @param {string | Uint8Array} data
+ */
+function writeFile(path, data) {
+  ops.op_fs_write(path, typeof data === "string" ? core.encode(data) : data);
+}
+
+const fs = Object.freeze({
+  readFile,
+  readText: (path) => core.decode(readFile(path)),
+  writeFile,
+  writeText: writeFile,
+  remove: (path) => ops.op_fs_remove(path),
+  exists: (path) => ops.op_fs_exists(path),
+  list: (dir = "/") => ops.op_fs_list(dir),
+});
+
+// ============================================================================
+// ARTIFACTS
+// ============================================================================
+
+/**
+ * Stores an artifact for later executions of the session
+ * @param {string} name
+ * @param {Uint8Array | any} value - Bytes, or any JSON serializable value
+ */
+function putArtifact(name, value) {
+  if (value instanceof Uint8Array) {
+    ops.op_artifact_put_bytes(name, value);
+  } else {
+    ops.op_artifact_put_json(name, value);
+  }
+}
+
+/**
+ * Returns an artifact stored by this or an earlier execution of the session
+ * @param {string} name
+ * @returns {Uint8Array | any | undefined} undefined if it does not exist or expired
+ */
+function getArtifact(name) {
+  const kind = ops.op_artifact_kind(name);
+  if (kind === null) return undefined;
+  return kind === "bytes"
+    ? new Uint8Array(ops.op_artifact_get_bytes(name))
+    : ops.op_artifact_get_json(name);
+}
+
+const artifacts = Object.freeze({
+  put: putArtifact,
+  get: getArtifact,
+  remove: (name) => ops.op_artifact_delete(name),
+  list: () => ops.op_artifact_list(),
+});
+
+// ============================================================================
+// OFFLOADED RESULTS
+// ============================================================================
+
+/**
+ * Selects part of an output offloaded by an earlier execution
+ * @param {string} handle - Handle of the offloaded output, e.g. `result_1`
+ * @param {string} [path] - JSONPath of the part, e.g. `$.items[0:10]`, defaults to the whole output
+ * @returns {any}
+ */
+function fetchResult(handle, path = "$") {
+  return ops.op_results_fetch(handle, path);
+}
+
+const results = Object.freeze({ fetch: fetchResult });
+
+// ============================================================================
+// KEY-VALUE STORE
+// ============================================================================
+
+/**
+ * Returns the value of a key, persisted across executions
+ * @param {string} key
+ * @param {{namespace?: string}} [options]
+ * @returns {any} undefined if the key does not exist or expired
+ */
+function getKv(key, options = {}) {
+  return ops.op_kv_get(options.namespace ?? "default", key) ?? undefined;
+}
+
+/**
+ * Sets the value of a key
+ * @param {string} key
+ * @param {any} value - Any JSON serializable value
+ * @param {{namespace?: string, ttlSecs?: number}} [options]
+ */
+function setKv(key, value, options = {}) {
+  ops.op_kv_set(options.namespace ?? "default", key, value, options.ttlSecs ?? null);
+}
+
+/**
+ * Lists the entries of a namespace whose keys start with the prefix
+ * @param {{namespace?: string, prefix?: string}} [options]
+ * @returns {Array<{key: string, value: any, expiresInSecs: number | null}>}
+ */
+function listKv(options = {}) {
+  return ops.op_kv_list(options.namespace ?? "default", options.prefix ?? "");
+}
+
+const kv = Object.freeze({
+  get: getKv,
+  set: setKv,
+  remove: (key, options = {}) => ops.op_kv_delete(options.namespace ?? "default", key),
+  list: listKv,
+});
+
+// ============================================================================
+// ROOTS
+// ============================================================================
+
+/**
+ * Lists the workspace roots of the client the code is executed for
+ * @returns {Array<{uri: string, name?: string}>}
+ */
+function listRoots() {
+  return ops.op_roots();
+}
+
+const roots = Object.freeze({ list: listRoots });
+
+// ============================================================================
+// TOOL REGISTRATION
+// ============================================================================
+
+/**
+ * Registers a tool available to subsequent executions, calls of the tool invoke
+ * the callback `namespace.name`
+ * @param {{namespace: string, name: string, description?: string, inputSchema?: object, outputSchema?: object}} tool
+ */
+function registerTool(tool) {
+  ops.op_register_tool(tool);
+}
+
+const tools = Object.freeze({ register: registerTool });
+
+// ============================================================================
+// STEPS
+// ============================================================================
+
+// Tree of the steps of the execution, captured with the console output
+globalThis.__steps = [];
+
+/**
+ * Runs `fn` as a step recorded in `steps` with its status, duration & result or error.
+ * `fn` receives a context whose `step` records nested steps
+ * @param {Array<object>} steps
+ * @param {string} name
+ * @param {(step: {step: Function}) => any} fn
+ * @returns {Promise<any>}
+ */
+async function runStep(steps, name, fn) {
+  if (typeof name !== "string" || typeof fn !== "function") {
+    throw new TypeError("pctx.step expects a name and a function");
+  }
+
+  const step = { name, status: "running", startedAt: Date.now(), steps: [] };
+  steps.push(step);
+  const context = Object.freeze({
+    step: (name, fn) => runStep(step.steps, name, fn),
+  });
+  try {
+    const result = await fn(context);
+    step.status = "succeeded";
+    step.result = serializeConsoleArg(result);
+    return result;
+  } catch (e) {
+    step.status = "failed";
+    step.error = e instanceof Error ? e.message : String(e);
+    throw e;
+  } finally {
+    step.durationMs = Date.now() - step.startedAt;
+  }
+}
+
+// Standard library helpers, WebAssembly modules, virtual filesystem, artifacts, offloaded
+// results, key-value store, roots, tool registration & steps, see std.d.ts
+globalThis.pctx = Object.freeze({
+  std,
+  wasm,
+  fs,
+  artifacts,
+  results,
+  kv,
+  roots,
+  tools,
+  step: (name, fn) => runStep(globalThis.__steps, name, fn),
+});
@@ -0,0 +1,117 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use pctx_config::{
+    client::{ClientHandlers, McpClient},
+    server::{McpConnectionError, ServerConfig},
+};
+use rmcp::{RoleClient, service::Peer};
+use tokio::sync::OnceCell;
+
+/// Sessions with upstream servers, shared by the tool calls of an execution
+///
+/// The first call to a server connects, concurrent calls wait for that connection
+/// and are then sent over the same session instead of each connecting on its own,
+/// so `Promise.all` over calls to one server takes as long as the slowest call.
+#[derive(Clone, Default)]
+pub(crate) struct Sessions {
+    clients: Arc<Mutex<HashMap<String, Arc<OnceCell<McpClient>>>>>,
+}
+
+impl Sessions {
+    /// Peer of the session with the server, connecting if there is none yet
+    /// or the transport of the previous one closed
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) async fn peer(
+        &self,
+        cfg: &ServerConfig,
+        handlers: &ClientHandlers,
+    ) -> Result<Peer<RoleClient>, McpConnectionError> {
+        let session = {
+            let mut clients = self.clients.lock().unwrap();
+            let session = clients.entry(cfg.name.clone()).or_default();
+            if session
+                .get()
+                .is_some_and(|c| c.peer().is_transport_closed())
+            {
+                *session = Arc::default();
+            }
+            session.clone()
+        };
+
+        let client = session
+            .get_or_try_init(|| cfg.connect_with(handlers.clone()))
+            .await?;
+        Ok(client.peer().clone())
+    }
+
+    /// Drops the session with the server, the next call reconnects
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn evict(&self, server: &str) {
+        self.clients.lock().unwrap().remove(server);
+    }
+
+    /// Closes the sessions with all servers
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) async fn close(&self) {
+        let sessions: Vec<_> = self.clients.lock().unwrap().drain().collect();
+        for (_, session) in sessions {
+            // sessions still used by a pending call are closed once it is dropped
+            if let Some(client) = Arc::into_inner(session).and_then(OnceCell::into_inner) {
+                let _ = client.cancel().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+    use tokio::task::JoinSet;
+
+    use crate::{
+        MCPRegistry,
+        mcp_registry::call_mcp_tool,
+        test_server::{Reply, TestServer},
+    };
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_session() {
+        let latency = Duration::from_millis(500);
+        let server = TestServer::new(&[Reply::Ok(json!("done"))]).with_delay(latency);
+        let registry = MCPRegistry::new();
+        registry.add(server.config("slow", &json!({}))).unwrap();
+
+        let started = Instant::now();
+        let mut calls = JoinSet::new();
+        for _ in 0..10 {
+            let registry = registry.clone();
+            calls.spawn(async move { call_mcp_tool(&registry, "slow", "wait", None).await });
+        }
+        while let Some(result) = calls.join_next().await {
+            assert_eq!(result.unwrap().unwrap(), json!("done"));
+        }
+        let elapsed = started.elapsed();
+
+        // the calls wait for the slowest one instead of each other
+        assert!(elapsed >= latency, "{elapsed:?}");
+        assert!(elapsed < latency * 3, "{elapsed:?}");
+        assert_eq!(server.calls(), 10);
+        assert_eq!(server.starts(), 1);
+
+        registry.close_sessions().await;
+    }
+}
@@ -0,0 +1,97 @@
+use std::{future::Future, time::Instant};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::error::McpError;
+
+/// Cancellation & deadline of an execution
+///
+/// Pending MCP tool calls and callbacks of an execution are dropped once it is cancelled
+/// or its deadline passes, cancelling their upstream requests. Callbacks can observe it
+/// through [`InvocationContext::current`](crate::InvocationContext::current).
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionSignal {
+    token: CancellationToken,
+    deadline: Option<Instant>,
+}
+
+impl ExecutionSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signal cancelled along with `token`
+    pub fn from_token(token: CancellationToken) -> Self {
+        Self {
+            token,
+            deadline: None,
+        }
+    }
+
+    /// Expire at `deadline`, or at the current deadline if it is earlier
+    #[must_use]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(self.deadline.map_or(deadline, |d| d.min(deadline)));
+        self
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Cancels the execution
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns `true` if the deadline passed
+    pub fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|d| d <= Instant::now())
+    }
+
+    /// Returns `true` if the execution was cancelled or its deadline passed
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled() || self.is_expired()
+    }
+
+    /// Completes once the execution is cancelled or its deadline passes
+    pub async fn cancelled(&self) {
+        match self.deadline {
+            Some(deadline) => {
+                tokio::select! {
+                    () = self.token.cancelled() => {}
+                    () = tokio::time::sleep_until(deadline.into()) => {}
+                }
+            }
+            None => self.token.cancelled().await,
+        }
+    }
+
+    /// Runs `fut` until it completes or the execution is cancelled, dropping it if cancelled
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `fut`, or an error if the execution was cancelled
+    pub async fn guard<T>(
+        &self,
+        fut: impl Future<Output = Result<T, McpError>>,
+    ) -> Result<T, McpError> {
+        tokio::select! {
+            res = fut => res,
+            () = self.cancelled() => Err(if self.is_expired() {
+                McpError::Timeout(self.reason().into())
+            } else {
+                McpError::Cancelled(self.reason().into())
+            }),
+        }
+    }
+
+    /// Why the execution stopped, if it was cancelled or its deadline passed
+    pub fn reason(&self) -> &'static str {
+        if self.is_expired() {
+            "execution deadline exceeded"
+        } else {
+            "execution cancelled"
+        }
+    }
+}
@@ -0,0 +1,220 @@
+/** Standard library helpers, available in every execution without imports */
+declare namespace pctx.std {
+  /** Resolves after `ms` milliseconds */
+  function sleep(ms: number): Promise<void>;
+  /** Calls `fn` until it resolves, retrying failures with exponential backoff (defaults: 3 retries, 200ms delay, factor 2) */
+  function retry<T>(fn: () => Promise<T>, options?: { retries?: number; delayMs?: number; factor?: number }): Promise<T>;
+  /** Splits an array into chunks of `size` items */
+  function chunk<T>(items: T[], size: number): T[][];
+  namespace base64 {
+    /** Encodes a string (as UTF-8) or bytes to base64 */
+    function encode(data: string | Uint8Array): string;
+    /** Decodes base64 to a UTF-8 string */
+    function decode(data: string): string;
+    /** Decodes base64 to bytes */
+    function decodeBytes(data: string): Uint8Array;
+  }
+  namespace csv {
+    /** Parses CSV into objects keyed by the header row */
+    function parse(text: string, options?: { header?: true; delimiter?: string }): Record<string, string>[];
+    /** Parses CSV into arrays of values */
+    function parse(text: string, options: { header: false; delimiter?: string }): string[][];
+    /** Serializes objects (columns from the keys of the first row) or arrays of values to CSV */
+    function stringify(rows: Record<string, unknown>[] | unknown[][], options?: { delimiter?: string }): string;
+  }
+  namespace json {
+    /** Parses JSON, returning `fallback` instead of throwing on invalid JSON */
+    function tryParse<T = any>(text: string, fallback?: T): T;
+  }
+  namespace date {
+    /** Formats a date in UTC with the tokens YYYY, MM, DD, HH, mm, ss and SSS, e.g. "YYYY-MM-DD HH:mm" */
+    function format(date: Date | string | number, pattern?: string): string;
+    /** Adds days to a date */
+    function addDays(date: Date | string | number, days: number): Date;
+  }
+}
+
+/** Results of MCP tools */
+declare namespace pctx.mcp {
+  /**
+   * Content block of a tool result. Tools returning image, audio or resource content without
+   * structured content return an array of content blocks instead of the parsed text.
+   */
+  interface Content {
+    type: "text" | "image" | "audio" | "resource" | "resource_link";
+    /** Content type of `data` or `text`, e.g. "image/png" */
+    mimeType?: string;
+    /** Text of text blocks and text resources */
+    text?: string;
+    /** Base64 encoded data of image, audio and binary resource blocks */
+    data?: string;
+    /** URI of resource and resource link blocks */
+    uri?: string;
+    /** Name of resource link blocks */
+    name?: string;
+    /** Base64 of the content, text is encoded as UTF-8 */
+    base64(): string;
+    /** Bytes of the content, text is encoded as UTF-8 */
+    bytes(): Uint8Array;
+  }
+}
+
+/** Workspace roots of the client, e.g. the directories open in the editor */
+declare namespace pctx.roots {
+  interface Root {
+    /** `file://` URI of the root */
+    uri: string;
+    name?: string;
+  }
+  /** Lists the roots of the client, empty if it has none */
+  function list(): Root[];
+}
+
+/** Registration of tools available to subsequent executions */
+declare namespace pctx.tools {
+  interface Definition {
+    namespace: string;
+    name: string;
+    description?: string;
+    /** JSON schema of the arguments */
+    inputSchema?: Record<string, unknown>;
+    /** JSON schema of the result */
+    outputSchema?: Record<string, unknown>;
+  }
+  /** Registers a tool, it can be called as `namespace.name` in subsequent executions */
+  function register(tool: Definition): void;
+}
+
+/**
+ * Virtual filesystem to stage files between tool calls, paths like `/scratch/out.json` never
+ * reach the real disk. Only available if enabled by the host, which scopes the paths
+ * code may read & write
+ */
+declare namespace pctx.fs {
+  /** Reads the bytes of a file */
+  function readFile(path: string): Uint8Array;
+  /** Reads a UTF-8 text file */
+  function readText(path: string): string;
+  /** Writes a file, replacing it if it exists, strings are encoded as UTF-8 */
+  function writeFile(path: string, data: string | Uint8Array): void;
+  /** Writes a UTF-8 text file, replacing it if it exists */
+  function writeText(path: string, text: string): void;
+  /** Removes a file, returns `false` if it did not exist */
+  function remove(path: string): boolean;
+  /** Returns `true` if the file exists */
+  function exists(path: string): boolean;
+  /** Paths of the readable files within the directory, recursively, defaults to `/` */
+  function list(dir?: string): string[];
+}
+
+/**
+ * Artifacts handed between the executions of a session: store large intermediate data
+ * here for later executions instead of returning it. Artifacts expire and are subject to
+ * size quotas set by the host
+ */
+declare namespace pctx.artifacts {
+  interface Info {
+    name: string;
+    kind: "bytes" | "json";
+    /** Size in bytes, JSON artifacts are measured serialized */
+    size: number;
+    expiresInSecs: number;
+  }
+  /** Stores bytes or a JSON serializable value, replacing the artifact with the same name */
+  function put(name: string, value: unknown): void;
+  /** Returns the artifact, `undefined` if it does not exist or expired */
+  function get<T = unknown>(name: string): T | undefined;
+  /** Removes the artifact, returns `false` if it did not exist */
+  function remove(name: string): boolean;
+  /** Artifacts of the session, sorted by name */
+  function list(): Info[];
+}
+
+declare namespace pctx {
+  /** Context of a running step */
+  interface Step {
+    /** Runs `fn` as a step nested in this step */
+    step<T>(name: string, fn: (step: Step) => Promise<T> | T): Promise<T>;
+  }
+  /**
+   * Runs `fn` as a named step of the execution. The status, duration & result or error of
+   * every step are returned next to the output, so partial failures show which steps
+   * completed. Errors thrown by `fn` are rethrown after they are recorded
+   */
+  function step<T>(name: string, fn: (step: Step) => Promise<T> | T): Promise<T>;
+}
+
+/** Outputs of earlier executions too large to return, replaced by a `handle` */
+declare namespace pctx.results {
+  /**
+   * Selects part of an offloaded output with a JSONPath: members (`$.a.b`, `$['a b']`),
+   * indices (`[0]`, `[-1]`), slices (`[10:20]`) and wildcards (`[*]`, `.*`).
+   * Paths with slices or wildcards return an array of the matches
+   */
+  function fetch<T = unknown>(handle: string, path?: string): T;
+}
+
+/**
+ * Key-value store persisted across executions, to remember state without an external
+ * database. Entries are grouped in namespaces (`"default"` if none is passed), each with
+ * a size quota & default TTL set by the host
+ */
+declare namespace pctx.kv {
+  interface Entry<T = unknown> {
+    key: string;
+    value: T;
+    /** Seconds until the entry expires, `null` if it does not expire */
+    expiresInSecs: number | null;
+  }
+  /** Returns the value of the key, `undefined` if it does not exist or expired */
+  function get<T = unknown>(key: string, options?: { namespace?: string }): T | undefined;
+  /** Sets the key to a JSON serializable value, expiring after `ttlSecs` or the namespace's TTL */
+  function set(key: string, value: unknown, options?: { namespace?: string; ttlSecs?: number }): void;
+  /** Removes the key, returns `false` if it did not exist */
+  function remove(key: string, options?: { namespace?: string }): boolean;
+  /** Entries whose keys start with `prefix`, sorted by key */
+  function list<T = unknown>(options?: { namespace?: string; prefix?: string }): Entry<T>[];
+}
+
+/** WebAssembly modules supplied by the host */
+declare namespace pctx.wasm {
+  /** Instantiated WebAssembly module, `exports` holds the exported functions & memories */
+  interface Instance {
+    readonly exports: Record<string, any>;
+  }
+  /** Instantiates the module with the given name, the imports are the only host functions it can call */
+  function load(name: string, imports?: Record<string, Record<string, unknown>>): Promise<Instance>;
+}
+
+/**
+ * Base class of the errors thrown by failed tool calls, e.g. when a call exceeds a rate limit.
+ * Subclasses classify why the call failed:
+ *
+ * ```ts
+ * try {
+ *   await Crm.getUser({ id });
+ * } catch (e) {
+ *   if (e instanceof NetworkError || e instanceof Timeout) { ... } // retry later
+ *   else if (e instanceof ValidationError) { ... } // fix the arguments
+ *   else throw e;
+ * }
+ * ```
+ */
+declare class ToolCallError extends Error {
+  /** Called tool, e.g. "crm.getUser" */
+  readonly tool: string;
+  /** JSON-RPC error code reported by the tool */
+  readonly code?: number;
+  /** JSON-RPC error data, or the content of results the tool flagged as errors */
+  readonly data?: unknown;
+}
+/** The server of the tool could not be reached, or is unavailable after failing repeatedly */
+declare class NetworkError extends ToolCallError {}
+/** The tool reported an error */
+declare class ToolError extends ToolCallError {}
+/** The tool rejected its arguments */
+declare class ValidationError extends ToolError {}
+/** The call was not permitted, e.g. the user did not approve it */
+declare class PermissionDenied extends ToolCallError {}
+/** The call did not complete in time */
+declare class Timeout extends ToolCallError {}
@@ -0,0 +1,262 @@
+// PCTX Runtime - Standard library helpers exposed as `pctx.std`
+//
+// Declarations of these helpers live in std.d.ts, keep both in sync.
+
+const core = Deno.core;
+
+// ============================================================================
+// ASYNC
+// ============================================================================
+
+/**
+ * Resolves after `ms` milliseconds
+ * @param {number} ms
+ * @returns {Promise<void>}
+ */
+function sleep(ms) {
+  return new Promise((resolve) => {
+    if (typeof globalThis.setTimeout === "function") {
+      globalThis.setTimeout(resolve, ms);
+    } else {
+      core.queueUserTimer(0, false, ms, resolve);
+    }
+  });
+}
+
+/**
+ * Calls `fn` until it resolves, retrying failures with exponential backoff
+ * @template T
+ * @param {() => Promise<T>} fn
+ * @param {{retries?: number, delayMs?: number, factor?: number}} [options]
+ * @returns {Promise<T>}
+ */
+async function retry(fn, options = {}) {
+  const { retries = 3, delayMs = 200, factor = 2 } = options;
+  let delay = delayMs;
+  for (let attempt = 0; ; attempt++) {
+    try {
+      return await fn();
+    } catch (error) {
+      if (attempt >= retries) throw error;
+      await sleep(delay);
+      delay *= factor;
+    }
+  }
+}
+
+// ============================================================================
+// COLLECTIONS
+// ============================================================================
+
+/**
+ * Splits an array into chunks of `size` items
+ * @template T
+ * @param {T[]} items
+ * @param {number} size
+ * @returns {T[][]}
+ */
+function chunk(items, size) {
+  if (!(size > 0)) throw new RangeError("chunk size must be greater than 0");
+  const chunks = [];
+  for (let i = 0; i < items.length; i += size) {
+    chunks.push(items.slice(i, i + size));
+  }
+  return chunks;
+}
+
+// ============================================================================
+// BASE64
+// ============================================================================
+
+const BASE64_ALPHABET =
+  "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/**
+ * Encodes a string (as UTF-8) or bytes to base64
+ * @param {string | Uint8Array} data
+ * @returns {string}
+ */
+function base64Encode(data) {
+  const bytes = typeof data === "string" ? core.encode(data) : data;
+  let out = "";
+  for (let i = 0; i < bytes.length; i += 3) {
+    const [a, b, c] = [bytes[i], bytes[i + 1], bytes[i + 2]];
+    const n = (a << 16) | ((b ?? 0) << 8) | (c ?? 0);
+    out += BASE64_ALPHABET[(n >> 18) & 63] + BASE64_ALPHABET[(n >> 12) & 63];
+    out += b === undefined ? "=" : BASE64_ALPHABET[(n >> 6) & 63];
+    out += c === undefined ? "=" : BASE64_ALPHABET[n & 63];
+  }
+  return out;
+}
+
+/**
+ * Decodes base64 (standard or URL-safe alphabet) to bytes
+ * @param {string} data
+ * @returns {Uint8Array}
+ */
+function base64DecodeBytes(data) {
+  const clean = data.replace(/[\s=]/g, "").replace(/-/g, "+").replace(/_/g, "/");
+  const bytes = [];
+  let buffer = 0;
+  let bits = 0;
+  for (const char of clean) {
+    const value = BASE64_ALPHABET.indexOf(char);
+    if (value === -1) throw new SyntaxError(`Invalid base64 character '${char}'`);
+    buffer = (buffer << 6) | value;
+    bits += 6;
+    if (bits >= 8) {
+      bits -= 8;
+      bytes.push((buffer >> bits) & 255);
+    }
+  }
+  return new Uint8Array(bytes);
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+/**
+ * Parses CSV text (RFC 4180 quoting) into objects keyed by the header row,
+ * or arrays of values with `header: false`
+ * @param {string} text
+ * @param {{header?: boolean, delimiter?: string}} [options]
+ */
+function csvParse(text, options = {}) {
+  const { header = true, delimiter = "," } = options;
+  const rows = [];
+  let row = [];
+  let field = "";
+  let quoted = false;
+
+  for (let i = 0; i < text.length; i++) {
+    const char = text[i];
+    if (quoted) {
+      if (char === '"' && text[i + 1] === '"') {
+        field += '"';
+        i++;
+      } else if (char === '"') {
+        quoted = false;
+      } else {
+        field += char;
+      }
+    } else if (char === '"') {
+      quoted = true;
+    } else if (char === delimiter) {
+      row.push(field);
+      field = "";
+    } else if (char === "\n" || char === "\r") {
+      if (char === "\r" && text[i + 1] === "\n") i++;
+      row.push(field);
+      rows.push(row);
+      row = [];
+      field = "";
+    } else {
+      field += char;
+    }
+  }
+  if (field !== "" || row.length > 0) {
+    row.push(field);
+    rows.push(row);
+  }
+
+  if (!header) return rows;
+  const [columns = [], ...records] = rows;
+  return records.map((values) =>
+    Object.fromEntries(columns.map((column, i) => [column, values[i] ?? ""]))
+  );
+}
+
+/**
+ * Serializes objects (columns from the keys of the first row) or arrays of values to CSV
+ * @param {Array<Record<string, unknown> | unknown[]>} rows
+ * @param {{delimiter?: string}} [options]
+ * @returns {string}
+ */
+function csvStringify(rows, options = {}) {
+  const { delimiter = "," } = options;
+  const escape = (value) => {
+    if (value === null || value === undefined) return "";
+    const str = typeof value === "object" ? JSON.stringify(value) : String(value);
+    return /["\r\n]/.test(str) || str.includes(delimiter)
+      ? `"${str.replace(/"/g, '""')}"`
+      : str;
+  };
+  const line = (values) => values.map(escape).join(delimiter);
+
+  if (rows.length === 0) return "";
+  if (Array.isArray(rows[0])) return rows.map(line).join("\n");
+
+  const columns = Object.keys(rows[0]);
+  return [line(columns), ...rows.map((r) => line(columns.map((c) => r[c])))]
+    .join("\n");
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+/**
+ * Parses JSON, returning `fallback` instead of throwing on invalid JSON
+ * @param {string} text
+ * @param {unknown} [fallback]
+ */
+function jsonTryParse(text, fallback = undefined) {
+  try {
+    return JSON.parse(text);
+  } catch {
+    return fallback;
+  }
+}
+
+// ============================================================================
+// DATES
+// ============================================================================
+
+/**
+ * Formats a date in UTC with the tokens YYYY, MM, DD, HH, mm, ss and SSS
+ * @param {Date | string | number} date
+ * @param {string} [pattern]
+ * @returns {string}
+ */
+function dateFormat(date, pattern = "YYYY-MM-DDTHH:mm:ss.SSSZ") {
+  const d = new Date(date);
+  if (Number.isNaN(d.getTime())) throw new RangeError(`Invalid date '${date}'`);
+  const pad = (n, width = 2) => String(n).padStart(width, "0");
+  const tokens = {
+    YYYY: pad(d.getUTCFullYear(), 4),
+    MM: pad(d.getUTCMonth() + 1),
+    DD: pad(d.getUTCDate()),
+    HH: pad(d.getUTCHours()),
+    mm: pad(d.getUTCMinutes()),
+    ss: pad(d.getUTCSeconds()),
+    SSS: pad(d.getUTCMilliseconds(), 3),
+  };
+  return pattern.replace(/YYYY|MM|DD|HH|mm|ss|SSS/g, (token) => tokens[token]);
+}
+
+/**
+ * Adds days to a date
+ * @param {Date | string | number} date
+ * @param {number} days
+ * @returns {Date}
+ */
+function dateAddDays(date, days) {
+  const d = new Date(date);
+  d.setUTCDate(d.getUTCDate() + days);
+  return d;
+}
+
+export const std = Object.freeze({
+  sleep,
+  retry,
+  chunk,
+  base64: Object.freeze({
+    encode: base64Encode,
+    decode: (data) => core.decode(base64DecodeBytes(data)),
+    decodeBytes: base64DecodeBytes,
+  }),
+  csv: Object.freeze({ parse: csvParse, stringify: csvStringify }),
+  json: Object.freeze({ tryParse: jsonTryParse }),
+  date: Object.freeze({ format: dateFormat, addDays: dateAddDays }),
+});
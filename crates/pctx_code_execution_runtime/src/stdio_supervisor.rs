@@ -0,0 +1,382 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use pctx_config::{
+    client::{ClientHandlers, McpClient},
+    server::{McpConnectionError, ServerConfig},
+};
+use rmcp::{RoleClient, service::Peer};
+use tokio::runtime::Handle;
+use tracing::{info, warn};
+
+type Client = McpClient;
+
+/// Supervised processes are keyed by server name and the owner of the handlers
+type ProcessKey = (String, Option<String>);
+
+/// Supervisor of long-lived stdio MCP server processes
+///
+/// Processes are spawned on the first tool call and reused by later calls and
+/// executions. Crashed processes are restarted with backoff on the next call,
+/// idle processes are shut down after the server's `idle_timeout_secs`.
+///
+/// Each [`ClientHandlers::owner`] gets processes of its own, so the requests a
+/// server sends back reach the client whose call it is handling.
+///
+/// Processes are spawned on the tokio runtime the supervisor was created on,
+/// so they outlive the short-lived runtimes executions may run on.
+#[derive(Clone)]
+pub struct StdioSupervisor {
+    handle: Option<Handle>,
+    processes: Arc<Mutex<HashMap<ProcessKey, Arc<tokio::sync::Mutex<Process>>>>>,
+}
+
+#[derive(Default)]
+struct Process {
+    client: Option<Client>,
+    // incremented on every spawn so idle watchers of previous processes stop
+    generation: u64,
+    last_used: Option<Instant>,
+    crashes: u32,
+    crashed_at: Option<Instant>,
+}
+
+impl Default for StdioSupervisor {
+    fn default() -> Self {
+        Self {
+            handle: Handle::try_current().ok(),
+            processes: Arc::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for StdioSupervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StdioSupervisor")
+            .field("servers", &self.servers())
+            .finish_non_exhaustive()
+    }
+}
+
+impl StdioSupervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names of the servers with a supervised process
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn servers(&self) -> Vec<String> {
+        let processes = self.processes.lock().unwrap();
+        let servers: BTreeSet<_> = processes.keys().map(|(server, _)| server.clone()).collect();
+        servers.into_iter().collect()
+    }
+
+    /// Shuts down all supervised processes
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub async fn shutdown(&self) {
+        let processes: Vec<_> = self.processes.lock().unwrap().drain().collect();
+        for ((server, _), process) in processes {
+            if let Some(client) = process.lock().await.client.take() {
+                info!(server = %server, "Shutting down stdio MCP server");
+                let _ = client.cancel().await;
+            }
+        }
+    }
+
+    /// Shuts down the supervised processes of the server, if any
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub async fn stop(&self, server: &str) {
+        let processes: Vec<_> = {
+            let mut processes = self.processes.lock().unwrap();
+            let keys: Vec<_> = processes
+                .keys()
+                .filter(|(s, _)| s == server)
+                .cloned()
+                .collect();
+            keys.iter().filter_map(|k| processes.remove(k)).collect()
+        };
+        for process in processes {
+            if let Some(client) = process.lock().await.client.take() {
+                info!(server = %server, "Shutting down stdio MCP server");
+                let _ = client.cancel().await;
+            }
+        }
+    }
+
+    /// Returns a peer of the server's running process of the handlers' owner, spawning
+    /// it if it is not running and restarting it with backoff if it crashed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server is not a stdio server or the process
+    /// could not be spawned and initialized
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) async fn peer(
+        &self,
+        cfg: &ServerConfig,
+        handlers: &ClientHandlers,
+    ) -> Result<Peer<RoleClient>, McpConnectionError> {
+        let Some(stdio) = cfg.stdio() else {
+            return Err(McpConnectionError::Failed(format!(
+                "MCP server \"{}\" is not a stdio server",
+                cfg.name
+            )));
+        };
+
+        let process = self
+            .processes
+            .lock()
+            .unwrap()
+            .entry((cfg.name.clone(), handlers.owner.clone()))
+            .or_default()
+            .clone();
+        let mut guard = process.lock().await;
+        guard.last_used = Some(Instant::now());
+
+        if let Some(client) = &guard.client {
+            if !client.peer().is_transport_closed() {
+                // the process survived since the last restart, requests it sends back
+                // go to the handlers of the current call, which have the same owner
+                client.service().set_handlers(handlers.clone());
+                let peer = client.peer().clone();
+                guard.crashes = 0;
+                return Ok(peer);
+            }
+
+            warn!(server = %cfg.name, "Stdio MCP server process exited unexpectedly");
+            guard.client = None;
+            guard.crashes += 1;
+            guard.crashed_at = Some(Instant::now());
+        }
+
+        if let Some(crashed_at) = guard.crashed_at {
+            let remaining = stdio
+                .restart_backoff(guard.crashes)
+                .saturating_sub(crashed_at.elapsed());
+            if !remaining.is_zero() {
+                tokio::time::sleep(remaining).await;
+            }
+            info!(server = %cfg.name, crashes = guard.crashes, "Restarting stdio MCP server");
+        }
+
+        let client = match self.spawn(cfg, handlers).await {
+            Ok(client) => client,
+            Err(err) => {
+                guard.crashes += 1;
+                guard.crashed_at = Some(Instant::now());
+                return Err(err);
+            }
+        };
+        let peer = client.peer().clone();
+        guard.client = Some(client);
+        guard.generation += 1;
+
+        if let Some(idle_timeout) = stdio.idle_timeout() {
+            self.watch_idle(
+                cfg.name.clone(),
+                process.clone(),
+                guard.generation,
+                idle_timeout,
+            );
+        }
+
+        Ok(peer)
+    }
+
+    async fn spawn(
+        &self,
+        cfg: &ServerConfig,
+        handlers: &ClientHandlers,
+    ) -> Result<Client, McpConnectionError> {
+        let Some(handle) = &self.handle else {
+            return cfg.connect_with(handlers.clone()).await;
+        };
+
+        let (cfg, handlers) = (cfg.clone(), handlers.clone());
+        handle
+            .spawn(async move { cfg.connect_with(handlers).await })
+            .await
+            .map_err(|e| McpConnectionError::Failed(e.to_string()))?
+    }
+
+    /// Shuts the process down once it has been idle for `idle_timeout`
+    fn watch_idle(
+        &self,
+        server: String,
+        process: Arc<tokio::sync::Mutex<Process>>,
+        generation: u64,
+        idle_timeout: Duration,
+    ) {
+        let watcher = async move {
+            let mut wait = idle_timeout;
+            loop {
+                tokio::time::sleep(wait).await;
+
+                let mut guard = process.lock().await;
+                if guard.generation != generation || guard.client.is_none() {
+                    return;
+                }
+
+                let idle = guard.last_used.map(|l| l.elapsed()).unwrap_or_default();
+                if idle < idle_timeout {
+                    wait = idle_timeout.saturating_sub(idle);
+                    continue;
+                }
+
+                if let Some(client) = guard.client.take() {
+                    info!(server = %server, idle_secs = idle.as_secs(), "Shutting down idle stdio MCP server");
+                    let _ = client.cancel().await;
+                }
+                return;
+            }
+        };
+
+        match &self.handle {
+            Some(handle) => {
+                handle.spawn(watcher);
+            }
+            None => {
+                tokio::spawn(watcher);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    use pctx_config::{client::ClientHandlers, server::ServerConfig};
+    use serde_json::json;
+
+    use super::StdioSupervisor;
+
+    /// Stdio MCP server answering the initialization, appending a line to `starts`
+    /// when started and exiting right after the handshake if `crash` is set
+    fn server(starts: &Path, crash: bool, idle_timeout_secs: u64) -> ServerConfig {
+        let script = format!(
+            r#"echo started >> "$STARTS"
+read -r line
+id=$(printf '%s' "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+printf '{{"jsonrpc":"2.0","id":%s,"result":{{"protocolVersion":"2025-03-26","capabilities":{{}},"serverInfo":{{"name":"test","version":"0.1.0"}}}}}}\n' "$id"
+read -r initialized
+{}"#,
+            if crash {
+                "exit 1"
+            } else {
+                "while read -r line; do :; done"
+            }
+        );
+        serde_json::from_value(json!({
+            "name": "test",
+            "command": "sh",
+            "args": ["-c", script],
+            "env": { "STARTS": starts },
+            "idle_timeout_secs": idle_timeout_secs,
+            "restart_backoff_ms": 300
+        }))
+        .unwrap()
+    }
+
+    fn starts(path: &Path) -> usize {
+        std::fs::read_to_string(path).map_or(0, |s| s.lines().count())
+    }
+
+    async fn wait_until(mut condition: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "condition not met in time");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_processes_are_reused_per_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("starts");
+        let cfg = server(&path, false, 0);
+        let supervisor = StdioSupervisor::new();
+
+        let first = ClientHandlers::default().with_owner("first");
+        supervisor.peer(&cfg, &first).await.unwrap();
+        supervisor.peer(&cfg, &first).await.unwrap();
+        assert_eq!(starts(&path), 1);
+
+        let second = ClientHandlers::default().with_owner("second");
+        supervisor.peer(&cfg, &second).await.unwrap();
+        assert_eq!(starts(&path), 2);
+        assert_eq!(supervisor.servers(), vec!["test".to_string()]);
+
+        supervisor.stop("test").await;
+        assert!(supervisor.servers().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_crashed_process_restarts_with_backoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("starts");
+        let cfg = server(&path, true, 0);
+        let supervisor = StdioSupervisor::new();
+        let handlers = ClientHandlers::default();
+
+        let peer = supervisor.peer(&cfg, &handlers).await.unwrap();
+        wait_until(|| peer.is_transport_closed()).await;
+
+        // the first restart waits `restart_backoff_ms`
+        let started = Instant::now();
+        let peer = supervisor.peer(&cfg, &handlers).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_eq!(starts(&path), 2);
+        wait_until(|| peer.is_transport_closed()).await;
+
+        // the backoff doubles with every consecutive crash
+        let started = Instant::now();
+        supervisor.peer(&cfg, &handlers).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(600));
+        assert_eq!(starts(&path), 3);
+
+        supervisor.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_idle_process_is_shut_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("starts");
+        let cfg = server(&path, false, 1);
+        let supervisor = StdioSupervisor::new();
+        let handlers = ClientHandlers::default();
+
+        let peer = supervisor.peer(&cfg, &handlers).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!peer.is_transport_closed());
+
+        wait_until(|| peer.is_transport_closed()).await;
+        assert_eq!(starts(&path), 1);
+
+        // the next call spawns the process again, without backoff
+        let started = Instant::now();
+        supervisor.peer(&cfg, &handlers).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(300));
+        assert_eq!(starts(&path), 2);
+
+        supervisor.shutdown().await;
+    }
+}
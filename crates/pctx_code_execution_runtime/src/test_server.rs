@@ -0,0 +1,115 @@
+//! Scripted stdio MCP server for tests of upstream tool calls
+
+use std::{path::Path, time::Duration};
+
+use pctx_config::server::ServerConfig;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+/// Reply of the test server to a `tools/call` request
+pub(crate) enum Reply {
+    /// Tool result with the JSON value as text content
+    Ok(Value),
+    /// Tool result flagged `isError`
+    ToolError(&'static str),
+    /// JSON-RPC error response
+    RpcError(i32, &'static str),
+    /// The process exits without replying, closing the transport
+    Exit,
+}
+
+/// Stdio MCP server answering its n-th `tools/call` request with the n-th reply, the last
+/// reply answers all following calls. Calls and process starts are counted across restarts
+/// of the process.
+pub(crate) struct TestServer {
+    dir: TempDir,
+}
+
+const SCRIPT: &str = r#"echo start >> "$DIR/starts"
+while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"test","version":"0.1.0"}}}\n' "$id" ;;
+    *'"tools/call"'*)
+      echo call >> "$DIR/calls"
+      n=$(wc -l < "$DIR/calls")
+      reply="$DIR/reply-$((n))"
+      [ -f "$reply" ] || reply="$DIR/reply-last"
+      if [ "$(cat "$reply")" = exit ]; then exit 1; fi
+      if [ -f "$DIR/delay" ]; then
+        (sleep "$(cat "$DIR/delay")"; printf '{"jsonrpc":"2.0","id":%s,%s}\n' "$id" "$(cat "$reply")") &
+      else
+        printf '{"jsonrpc":"2.0","id":%s,%s}\n' "$id" "$(cat "$reply")"
+      fi ;;
+  esac
+done"#;
+
+impl TestServer {
+    pub(crate) fn new(replies: &[Reply]) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        for (i, reply) in replies.iter().enumerate() {
+            write_reply(&dir.path().join(format!("reply-{}", i + 1)), reply);
+        }
+        if let Some(last) = replies.last() {
+            write_reply(&dir.path().join("reply-last"), last);
+        }
+        Self { dir }
+    }
+
+    /// Delays every reply, answering concurrent calls concurrently
+    pub(crate) fn with_delay(self, delay: Duration) -> Self {
+        std::fs::write(
+            self.dir.path().join("delay"),
+            format!("{:.3}", delay.as_secs_f64()),
+        )
+        .unwrap();
+        self
+    }
+
+    /// Configuration of the server named `name`, `extra` is merged into it (e.g. `tools`)
+    pub(crate) fn config(&self, name: &str, extra: &Value) -> ServerConfig {
+        let mut cfg = json!({
+            "name": name,
+            "command": "sh",
+            "args": ["-c", SCRIPT],
+            "env": { "DIR": self.dir.path() },
+        });
+        if let (Some(cfg), Some(extra)) = (cfg.as_object_mut(), extra.as_object()) {
+            cfg.extend(extra.clone());
+        }
+        serde_json::from_value(cfg).unwrap()
+    }
+
+    /// Number of `tools/call` requests received
+    pub(crate) fn calls(&self) -> usize {
+        self.count("calls")
+    }
+
+    /// Number of times the server process was started, i.e. of connections made to it
+    pub(crate) fn starts(&self) -> usize {
+        self.count("starts")
+    }
+
+    fn count(&self, file: &str) -> usize {
+        std::fs::read_to_string(self.dir.path().join(file)).map_or(0, |s| s.lines().count())
+    }
+}
+
+fn write_reply(path: &Path, reply: &Reply) {
+    let reply = match reply {
+        Reply::Ok(value) => format!(
+            r#""result":{}"#,
+            json!({ "content": [{ "type": "text", "text": value.to_string() }] })
+        ),
+        Reply::ToolError(message) => format!(
+            r#""result":{}"#,
+            json!({ "content": [{ "type": "text", "text": message }], "isError": true })
+        ),
+        Reply::RpcError(code, message) => {
+            format!(r#""error":{}"#, json!({ "code": code, "message": message }))
+        }
+        Reply::Exit => "exit".into(),
+    };
+    std::fs::write(path, reply).unwrap();
+}
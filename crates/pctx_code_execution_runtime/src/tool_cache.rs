@@ -0,0 +1,279 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use rmcp::model::JsonObject;
+use serde_json::Value;
+
+/// Cache of MCP tool results keyed on (server, tool, arguments hash)
+///
+/// Only tools configured with a `cache` annotation are cached. Each execution gets
+/// its own cache, a cache shared between executions can be provided for tools
+/// with a `ttl_secs`.
+#[derive(Debug, Clone, Default)]
+pub struct ToolCache {
+    entries: Arc<RwLock<HashMap<CacheKey, CacheEntry>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    server: String,
+    tool: String,
+    args_hash: u64,
+}
+
+impl CacheKey {
+    pub(crate) fn new(server: &str, tool: &str, args: Option<&JsonObject>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        if let Some(args) = args {
+            hash_object(args, &mut hasher);
+        }
+
+        Self {
+            server: server.into(),
+            tool: tool.into(),
+            args_hash: hasher.finish(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    value: Value,
+    expires_at: Option<Instant>,
+}
+
+impl CacheEntry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|e| e <= now)
+    }
+}
+
+impl ToolCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached result if it exists & has not expired
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<Value> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(key)
+            .filter(|e| !e.is_expired(Instant::now()))
+            .map(|e| e.value.clone())
+    }
+
+    /// Caches a result, expiring after `ttl` if provided. Expired entries are pruned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn insert(&self, key: CacheKey, value: Value, ttl: Option<Duration>) {
+        let now = Instant::now();
+        let mut entries = self.entries.write().unwrap();
+        entries.retain(|_, e| !e.is_expired(now));
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                expires_at: ttl.map(|ttl| now + ttl),
+            },
+        );
+    }
+
+    /// Number of cached results, including expired results not pruned yet
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Returns `true` if there are no cached results
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached results
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+}
+
+/// Hashes JSON independent of object key order
+fn hash_value(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Null => 0u8.hash(hasher),
+        Value::Bool(b) => (1u8, b).hash(hasher),
+        Value::Number(n) => (2u8, n.to_string()).hash(hasher),
+        Value::String(s) => (3u8, s).hash(hasher),
+        Value::Array(items) => {
+            (4u8, items.len()).hash(hasher);
+            for item in items {
+                hash_value(item, hasher);
+            }
+        }
+        Value::Object(obj) => {
+            5u8.hash(hasher);
+            hash_object(obj, hasher);
+        }
+    }
+}
+
+fn hash_object(obj: &JsonObject, hasher: &mut DefaultHasher) {
+    let mut keys: Vec<&String> = obj.keys().collect();
+    keys.sort();
+    keys.len().hash(hasher);
+    for key in keys {
+        key.hash(hasher);
+        hash_value(&obj[key], hasher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::{Value, json};
+
+    use super::{CacheKey, ToolCache};
+    use crate::{
+        MCPRegistry,
+        mcp_registry::call_mcp_tool,
+        test_server::{Reply, TestServer},
+    };
+
+    fn key(args: &Value) -> CacheKey {
+        CacheKey::new("server", "tool", args.as_object())
+    }
+
+    fn registry(server: &TestServer, shared: Option<&ToolCache>) -> MCPRegistry {
+        let registry = shared.map_or_else(MCPRegistry::new, |cache| {
+            MCPRegistry::new().with_shared_cache(cache.clone())
+        });
+        registry
+            .add(server.config(
+                "server",
+                &json!({ "tools": { "search": { "cache": { "ttl_secs": 60 } } } }),
+            ))
+            .unwrap();
+        registry
+    }
+
+    async fn search(registry: &MCPRegistry, args: Value) -> Result<Value, String> {
+        call_mcp_tool(registry, "server", "search", args.as_object().cloned())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_key_ignores_argument_order() {
+        assert_eq!(
+            key(&json!({ "a": 1, "b": { "c": [1, 2], "d": null } })),
+            key(&json!({ "b": { "d": null, "c": [1, 2] }, "a": 1 }))
+        );
+        assert_ne!(key(&json!({ "a": 1 })), key(&json!({ "a": 2 })));
+        assert_ne!(key(&json!({ "a": [1, 2] })), key(&json!({ "a": [2, 1] })));
+        assert_ne!(key(&json!({ "a": "1" })), key(&json!({ "a": 1 })));
+        assert_ne!(
+            CacheKey::new("server", "tool", None),
+            CacheKey::new("server", "other", None)
+        );
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let cache = ToolCache::new();
+        cache.insert(
+            key(&json!({ "a": 1 })),
+            json!("short"),
+            Some(Duration::from_millis(50)),
+        );
+        cache.insert(key(&json!({ "a": 2 })), json!("forever"), None);
+        assert_eq!(cache.get(&key(&json!({ "a": 1 }))), Some(json!("short")));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&key(&json!({ "a": 1 }))), None);
+        assert_eq!(cache.get(&key(&json!({ "a": 2 }))), Some(json!("forever")));
+
+        // expired entries are pruned on the next insert
+        cache.insert(key(&json!({ "a": 3 })), json!("new"), None);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execution_cache_hit() {
+        let server = TestServer::new(&[Reply::Ok(json!({ "hits": 1 }))]);
+
+        let execution = registry(&server, None);
+        assert_eq!(
+            search(&execution, json!({ "q": "rust", "limit": 5 })).await,
+            Ok(json!({ "hits": 1 }))
+        );
+        assert_eq!(
+            search(&execution, json!({ "limit": 5, "q": "rust" })).await,
+            Ok(json!({ "hits": 1 }))
+        );
+        assert_eq!(server.calls(), 1);
+
+        search(&execution, json!({ "q": "go" })).await.unwrap();
+        assert_eq!(server.calls(), 2);
+
+        // without a shared cache results are only reused within the execution
+        search(&registry(&server, None), json!({ "q": "rust", "limit": 5 }))
+            .await
+            .unwrap();
+        assert_eq!(server.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_shared_cache_hit_across_executions() {
+        let server = TestServer::new(&[Reply::Ok(json!({ "hits": 1 }))]);
+        let shared = ToolCache::new();
+
+        search(&registry(&server, Some(&shared)), json!({ "q": "rust" }))
+            .await
+            .unwrap();
+        assert_eq!(
+            search(&registry(&server, Some(&shared)), json!({ "q": "rust" })).await,
+            Ok(json!({ "hits": 1 }))
+        );
+        assert_eq!(server.calls(), 1);
+        assert_eq!(shared.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_not_cached() {
+        let server = TestServer::new(&[
+            Reply::ToolError("quota exceeded"),
+            Reply::RpcError(-32603, "internal error"),
+            Reply::Ok(json!({ "hits": 1 })),
+        ]);
+        let shared = ToolCache::new();
+        let execution = registry(&server, Some(&shared));
+
+        assert!(search(&execution, json!({ "q": "rust" })).await.is_err());
+        assert!(search(&execution, json!({ "q": "rust" })).await.is_err());
+        assert!(shared.is_empty());
+
+        assert_eq!(
+            search(&execution, json!({ "q": "rust" })).await,
+            Ok(json!({ "hits": 1 }))
+        );
+        search(&execution, json!({ "q": "rust" })).await.unwrap();
+        assert_eq!(server.calls(), 3);
+        assert_eq!(shared.len(), 1);
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::McpError;
+
+/// Tool registered during an execution, e.g. by a callback discovering an API
+///
+/// Calls of the tool are invoked as the callback `namespace.name`, the tool is
+/// available to subsequent executions once the host adds it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDefinition {
+    pub namespace: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+impl ToolDefinition {
+    /// Id of the callback invoked for calls of the tool
+    pub fn id(&self) -> String {
+        format!("{}.{}", self.namespace, self.name)
+    }
+}
+
+/// Tools registered during an execution, with `pctx.tools.register(...)` or by callbacks
+/// through [`InvocationContext::current`](crate::InvocationContext::current)
+#[derive(Debug, Clone, Default)]
+pub struct ToolRegistrations {
+    tools: Arc<Mutex<Vec<ToolDefinition>>>,
+}
+
+impl ToolRegistrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the tool, replacing an earlier registration with the same id
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the namespace or name is not a valid identifier
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn register(&self, tool: ToolDefinition) -> Result<(), McpError> {
+        for part in [&tool.namespace, &tool.name] {
+            let valid = part
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if !valid {
+                return Err(McpError::Config(format!(
+                    "Invalid tool registration \"{}\", namespace & name must be identifiers",
+                    tool.id()
+                )));
+            }
+        }
+
+        let mut tools = self.tools.lock().unwrap();
+        tools.retain(|t| t.id() != tool.id());
+        tools.push(tool);
+        Ok(())
+    }
+
+    /// Tools registered so far
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn tools(&self) -> Vec<ToolDefinition> {
+        self.tools.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,73 @@
+//! Deno ops for host supplied WebAssembly modules
+//!
+//! Modules are named byte blobs provided by the host, generated code can only instantiate
+//! these modules with `pctx.wasm.load(name)`. Instances only see the imports passed by the
+//! code so network and file sandboxing is unaffected.
+
+use std::{collections::HashMap, sync::Arc};
+
+use deno_core::{OpState, op2};
+
+use crate::error::WasmError;
+
+/// Named WebAssembly modules available to executed code
+#[derive(Debug, Clone, Default)]
+pub struct WasmModules {
+    modules: HashMap<String, Arc<[u8]>>,
+}
+
+impl WasmModules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the module bytes under the name used by `pctx.wasm.load(name)`
+    #[must_use]
+    pub fn with_module(mut self, name: impl Into<String>, bytes: impl Into<Arc<[u8]>>) -> Self {
+        self.insert(name, bytes);
+        self
+    }
+
+    /// Adds the module bytes under the name used by `pctx.wasm.load(name)`,
+    /// replacing any module with the same name
+    pub fn insert(&mut self, name: impl Into<String>, bytes: impl Into<Arc<[u8]>>) {
+        self.modules.insert(name.into(), bytes.into());
+    }
+
+    /// Returns the bytes of the module with the given name
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.modules.get(name).map(AsRef::as_ref)
+    }
+
+    /// Names of all modules, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.modules.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Number of modules
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Returns `true` if there are no modules
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+#[op2]
+#[buffer]
+pub(crate) fn op_wasm_module(
+    state: &mut OpState,
+    #[string] name: String,
+) -> Result<Vec<u8>, WasmError> {
+    let modules = state.borrow::<WasmModules>();
+    modules.get(&name).map(<[u8]>::to_vec).ok_or_else(|| {
+        WasmError::NotFound(
+            name,
+            modules.names().into_iter().map(String::from).collect(),
+        )
+    })
+}
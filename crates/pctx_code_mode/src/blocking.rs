@@ -0,0 +1,54 @@
+//! Executions on a runtime of their own, for hosts running on a multi-thread runtime.
+//!
+//! Deno ops use `deno_unsync`, which requires a current-thread runtime: [`execute_blocking`]
+//! runs the execution on a blocking thread with a current-thread runtime, entering the
+//! tracing span of the caller in that thread.
+
+use std::future::Future;
+
+use crate::model::{ExecuteInput, ExecuteOutput};
+use crate::{CallbackRegistry, CodeMode, Error, Result};
+
+/// Executes an [`ExecuteInput`] with [`CodeMode::execute_input`] on a current-thread runtime of
+/// its own
+///
+/// # Errors
+///
+/// Returns an error if the runtime can not be created, the blocking task panics or the
+/// execution fails
+pub async fn execute_blocking(
+    code_mode: CodeMode,
+    input: ExecuteInput,
+    callback_registry: Option<CallbackRegistry>,
+) -> Result<ExecuteOutput> {
+    run_blocking(move || async move { code_mode.execute_input(&input, callback_registry).await })
+        .await
+}
+
+/// Runs the future created by `task` on a current-thread runtime of its own, e.g. type checks
+/// or executions of saved scripts
+///
+/// # Errors
+///
+/// Returns an error if the runtime can not be created, the blocking task panics or the
+/// future fails
+pub async fn run_blocking<F, Fut, T>(task: F) -> Result<T>
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T>>,
+    T: Send + 'static,
+{
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        let _guard = span.enter();
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| Error::Message(format!("Failed to create runtime: {e}")))?;
+
+        rt.block_on(task())
+    })
+    .await
+    .map_err(|e| Error::Message(format!("Task join failed: {e}")))?
+}
@@ -0,0 +1,90 @@
+use pctx_codegen::Tool;
+
+use crate::model::InterfaceTrim;
+
+// rough average of characters per token of typescript declarations
+const CHARS_PER_TOKEN: usize = 4;
+// descriptions are shortened to their first sentence, at most this many characters
+const SHORT_DESCRIPTION_CHARS: usize = 120;
+
+/// Estimated number of tokens of the text
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Renders an interface, reducing the tool descriptions step by step until its estimated
+/// tokens fit the budget. Returns the smallest rendering if none fits.
+pub(crate) fn fit(
+    budget_tokens: Option<usize>,
+    render: impl Fn(Option<InterfaceTrim>) -> String,
+) -> (String, Option<InterfaceTrim>) {
+    let code = render(None);
+    let Some(budget) = budget_tokens else {
+        return (code, None);
+    };
+    if estimate_tokens(&code) <= budget {
+        return (code, None);
+    }
+
+    let code = render(Some(InterfaceTrim::Descriptions));
+    if estimate_tokens(&code) <= budget {
+        return (code, Some(InterfaceTrim::Descriptions));
+    }
+    (
+        render(Some(InterfaceTrim::Signatures)),
+        Some(InterfaceTrim::Signatures),
+    )
+}
+
+/// Tool with its description reduced by the trim
+pub(crate) fn trim_tool(tool: &Tool, trim: Option<InterfaceTrim>) -> Tool {
+    let mut tool = tool.clone();
+    match trim {
+        None => {}
+        Some(InterfaceTrim::Descriptions) => {
+            tool.description = tool.description.as_deref().map(short_description);
+        }
+        Some(InterfaceTrim::Signatures) => tool.description = None,
+    }
+    tool
+}
+
+/// First sentence of the description, cut at a word boundary if it is long
+fn short_description(description: &str) -> String {
+    let first_line = description.trim().lines().next().unwrap_or_default();
+    let sentence = first_line
+        .find(". ")
+        .map_or(first_line, |end| &first_line[..=end]);
+    if sentence.chars().count() <= SHORT_DESCRIPTION_CHARS {
+        return sentence.to_string();
+    }
+
+    let cut: String = sentence.chars().take(SHORT_DESCRIPTION_CHARS).collect();
+    let cut = cut
+        .rsplit_once(' ')
+        .map_or(cut.as_str(), |(words, _)| words);
+    format!("{}...", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate_tokens, short_description};
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn test_short_description() {
+        assert_eq!(
+            short_description("Lists files. Supports paging.\nMore details"),
+            "Lists files."
+        );
+        let long = "word ".repeat(40);
+        let short = short_description(&long);
+        assert!(short.ends_with("word..."), "{short}");
+        assert!(short.chars().count() <= 123);
+    }
+}
@@ -0,0 +1,269 @@
+//! Documentation of the aggregated tool catalog for publishing, e.g. to internal wikis,
+//! rendered from the same tool sets as the TypeScript interfaces.
+//!
+//! ```ignore
+//! use pctx_code_mode::catalog::CatalogFormat;
+//!
+//! let markdown = code_mode.export_catalog(CatalogFormat::Markdown);
+//! ```
+
+use std::{fmt::Write, str::FromStr};
+
+use pctx_codegen::{Tool, ToolSet};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Document format of the tool catalog
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogFormat {
+    #[default]
+    Markdown,
+    /// Standalone HTML page
+    Html,
+}
+
+impl FromStr for CatalogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            other => Err(format!(
+                "Unknown catalog format '{other}', expected `markdown` or `html`"
+            )),
+        }
+    }
+}
+
+/// Documented function of the catalog
+struct Entry {
+    id: String,
+    description: Option<String>,
+    deprecated: Option<String>,
+    signature: String,
+    example: String,
+}
+
+impl Entry {
+    fn new(tool_set: &ToolSet, tool: &Tool) -> Self {
+        let id = format!("{}.{}", tool_set.tool_namespace(tool), tool.fn_name);
+        let signature = pctx_codegen::format::format_d_ts(&format!(
+            "declare function {}(input: {}): Promise<{}>;",
+            tool.fn_name, tool.input_signature, tool.output_signature
+        ));
+        let example = format!(
+            "const result = await {id}({});",
+            example_value(
+                &serde_json::to_value(&tool.input_schema).unwrap_or_default(),
+                0
+            )
+        );
+
+        Self {
+            id,
+            description: tool.description.clone(),
+            deprecated: tool.deprecated.clone(),
+            signature: signature.trim().to_string(),
+            example,
+        }
+    }
+}
+
+/// Renders the listed tools of the tool sets as a catalog document
+pub(crate) fn render(tool_sets: &[ToolSet], format: CatalogFormat) -> String {
+    let namespaces: Vec<(&ToolSet, Vec<Entry>)> = tool_sets
+        .iter()
+        .filter_map(|s| {
+            let entries: Vec<Entry> = s.visible_tools().map(|t| Entry::new(s, t)).collect();
+            (!entries.is_empty()).then_some((s, entries))
+        })
+        .collect();
+
+    match format {
+        CatalogFormat::Markdown => markdown(&namespaces),
+        CatalogFormat::Html => html(&namespaces),
+    }
+}
+
+fn markdown(namespaces: &[(&ToolSet, Vec<Entry>)]) -> String {
+    let mut doc = String::from("# Tool Catalog\n\n");
+    for (tool_set, entries) in namespaces {
+        let _ = writeln!(
+            doc,
+            "- [{}](#{}) ({} functions)",
+            tool_set.namespace,
+            anchor(&tool_set.namespace),
+            entries.len()
+        );
+    }
+
+    for (tool_set, entries) in namespaces {
+        let _ = write!(doc, "\n## {}\n\n", tool_set.namespace);
+        if !tool_set.description.is_empty() {
+            let _ = write!(doc, "{}\n\n", tool_set.description.trim());
+        }
+        for entry in entries {
+            let _ = write!(doc, "### `{}`\n\n", entry.id);
+            if let Some(reason) = &entry.deprecated {
+                let _ = write!(doc, "> **Deprecated** {reason}\n\n");
+            }
+            if let Some(description) = &entry.description {
+                let _ = write!(doc, "{}\n\n", description.trim());
+            }
+            let _ = write!(
+                doc,
+                "```typescript\n{}\n```\n\nExample:\n\n```typescript\n{}\n```\n\n",
+                entry.signature, entry.example
+            );
+        }
+    }
+    format!("{}\n", doc.trim_end())
+}
+
+fn html(namespaces: &[(&ToolSet, Vec<Entry>)]) -> String {
+    let mut body = String::from("<h1>Tool Catalog</h1>\n<ul>\n");
+    for (tool_set, entries) in namespaces {
+        let _ = writeln!(
+            body,
+            "<li><a href=\"#{}\">{}</a> ({} functions)</li>",
+            anchor(&tool_set.namespace),
+            escape(&tool_set.namespace),
+            entries.len()
+        );
+    }
+    body.push_str("</ul>\n");
+
+    for (tool_set, entries) in namespaces {
+        let _ = writeln!(
+            body,
+            "<h2 id=\"{}\">{}</h2>",
+            anchor(&tool_set.namespace),
+            escape(&tool_set.namespace)
+        );
+        if !tool_set.description.is_empty() {
+            let _ = writeln!(body, "<p>{}</p>", escape(tool_set.description.trim()));
+        }
+        for entry in entries {
+            let _ = writeln!(
+                body,
+                "<h3 id=\"{}\"><code>{}</code></h3>",
+                anchor(&entry.id),
+                escape(&entry.id)
+            );
+            if let Some(reason) = &entry.deprecated {
+                let _ = writeln!(
+                    body,
+                    "<p><strong>Deprecated</strong> {}</p>",
+                    escape(reason)
+                );
+            }
+            if let Some(description) = &entry.description {
+                let _ = writeln!(body, "<p>{}</p>", escape(description.trim()));
+            }
+            let _ = writeln!(
+                body,
+                "<pre><code>{}</code></pre>\n<p>Example:</p>\n<pre><code>{}</code></pre>",
+                escape(&entry.signature),
+                escape(&entry.example)
+            );
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Tool Catalog</title>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
+
+/// Anchor of a heading as generated by GitHub, e.g. `Google.Drive` -> `googledrive`
+fn anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Placeholder value of a JSON schema for examples, objects have their required properties
+fn example_value(schema: &Value, depth: usize) -> String {
+    if let Some(example) = schema
+        .get("examples")
+        .and_then(|e| e.get(0))
+        .or_else(|| schema.get("default"))
+    {
+        return example.to_string();
+    }
+    if let Some(first) = schema.get("enum").and_then(|e| e.get(0)) {
+        return first.to_string();
+    }
+
+    let kind = match schema.get("type") {
+        Some(Value::Array(kinds)) => kinds.iter().find(|k| *k != "null").cloned(),
+        kind => kind.cloned(),
+    };
+    match kind.as_ref().and_then(Value::as_str) {
+        Some("string") => "\"...\"".into(),
+        Some("integer" | "number") => "0".into(),
+        Some("boolean") => "false".into(),
+        Some("array") => "[]".into(),
+        Some("object") if depth < 3 => {
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|r| r.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let fields: Vec<String> = required
+                .iter()
+                .filter_map(|name| {
+                    let property = schema.get("properties")?.get(name)?;
+                    Some(format!("{name}: {}", example_value(property, depth + 1)))
+                })
+                .collect();
+            if fields.is_empty() {
+                "{}".into()
+            } else {
+                format!("{{ {} }}", fields.join(", "))
+            }
+        }
+        _ => "{}".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{anchor, escape, example_value};
+
+    #[test]
+    fn test_example_value() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "limit": { "type": "integer" },
+                "status": { "enum": ["open", "closed"] },
+                "verbose": { "type": "boolean" }
+            },
+            "required": ["id", "limit", "status"]
+        });
+        assert_eq!(
+            example_value(&schema, 0),
+            "{ id: \"...\", limit: 0, status: \"open\" }"
+        );
+    }
+
+    #[test]
+    fn test_anchor_and_escape() {
+        assert_eq!(anchor("Google.Drive"), "googledrive");
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
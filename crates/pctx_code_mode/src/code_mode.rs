@@ -1,23 +1,71 @@
 use std::{
     collections::{HashMap, HashSet},
-    time::Duration,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
-use pctx_code_execution_runtime::CallbackRegistry;
-use pctx_codegen::{Tool, ToolSet};
-use pctx_config::server::ServerConfig;
-use serde::{Deserialize, Serialize};
+use pctx_code_execution_runtime::{
+    ArtifactStore, CallTape, CallbackRegistry, CircuitBreakers, ExecutionIds, KvStore,
+    OutputSchemas, RateLimiters, ResultStore, STD_DECLARATIONS, StdioSupervisor, ToolCache,
+    ToolRegistrations, VirtualFs, WasmModules, json_path::JsonPath,
+};
+use pctx_codegen::{Tool, ToolSet, case::CasePolicy};
+use pctx_config::{
+    client::{
+        ApprovalHandler, ClientHandlers, ElicitationHandler, ProgressHandler, Root, SamplingHandler,
+    },
+    collisions::CollisionConfig,
+    imports::ImportsConfig,
+    prompt::PromptConfig,
+    server::{NameCase, ServerConfig},
+    type_check::TypeCheckConfig,
+};
+use pctx_executor::{CheckCache, Determinism, Isolation, RemoteWorkers};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::json;
 use tracing::{debug, info, instrument, warn};
 
 use crate::{
-    Error, Result,
+    Error, Result, budget,
+    catalog::{self, CatalogFormat},
+    collisions,
+    discovery::{Embedder, ToolIndex},
+    mock::MockToolSet,
     model::{
-        CallbackConfig, ExecuteOutput, FunctionDetails, GetFunctionDetailsInput,
-        GetFunctionDetailsOutput, ListFunctionsOutput, ListedFunction,
+        CallbackConfig, CheckCodeOutput, DetailLevel, ExecuteInput, ExecuteOutput, ExecutionBundle,
+        FunctionDetails, GetFunctionDetailsInput, GetFunctionDetailsOutput, InterfaceSize,
+        InterfaceTrim, ListFunctionsInput, ListFunctionsOutput, ListedFunction, RepairAttempt,
+        RepairHistory, Script, SearchFunctionsInput, SearchFunctionsOutput, SearchedFunction,
+        Skill,
     },
+    prompt,
+    schema_cache::SchemaCache,
+    search,
+    tool_specs::{ToolSpec, ToolSpecFormat},
+    typed::typed_callback,
 };
 
+/// Version of the snapshot format, bumped on incompatible changes
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Tool set of the skills, generating the `Skills` namespace
+const SKILLS_TOOL_SET: &str = "skills";
+
+#[derive(Serialize)]
+struct CodeModeSnapshotRef<'a> {
+    version: u32,
+    code_mode: &'a CodeMode,
+}
+
+#[derive(Deserialize)]
+struct CodeModeSnapshot {
+    version: u32,
+    code_mode: CodeMode,
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct CodeMode {
     // Codegen interfaces
@@ -26,6 +74,96 @@ pub struct CodeMode {
     // configurations
     servers: Vec<ServerConfig>,
     callbacks: Vec<CallbackConfig>,
+    #[serde(default)]
+    scripts: Vec<Script>,
+    #[serde(default)]
+    skills: Vec<Skill>,
+    #[serde(default)]
+    type_check: TypeCheckConfig,
+    #[serde(default)]
+    imports: ImportsConfig,
+    #[serde(default)]
+    collisions: CollisionConfig,
+
+    // optional embeddings index for semantic tool discovery
+    #[serde(skip)]
+    tool_index: Option<ToolIndex>,
+
+    // results of cacheable MCP tools shared between executions
+    #[serde(skip)]
+    tool_cache: ToolCache,
+
+    // circuit breakers of upstream MCP servers shared between executions
+    #[serde(skip)]
+    circuit_breakers: CircuitBreakers,
+
+    // rate limits of MCP tools shared between executions
+    #[serde(skip)]
+    rate_limiters: RateLimiters,
+
+    // long-lived stdio MCP server processes shared between executions
+    #[serde(skip)]
+    stdio_supervisor: StdioSupervisor,
+
+    // type check results & transpiled code shared between executions
+    #[serde(skip)]
+    check_cache: CheckCache,
+
+    // WebAssembly modules executed code can load with `pctx.wasm.load(name)`
+    #[serde(skip)]
+    wasm_modules: WasmModules,
+
+    // artifacts executions of the session hand over with `pctx.artifacts`
+    #[serde(skip)]
+    artifacts: ArtifactStore,
+
+    // outputs too large to return, fetched from with `pctx.results.fetch(handle, path)`
+    #[serde(skip)]
+    results: ResultStore,
+
+    // optional virtual filesystem executed code can access with `pctx.fs`
+    #[serde(skip)]
+    fs: Option<VirtualFs>,
+
+    // optional persistent key-value store executed code can access with `pctx.kv`
+    #[serde(skip)]
+    kv: Option<KvStore>,
+
+    // optional on-disk snapshots of upstream tool schemas
+    #[serde(skip)]
+    schema_cache: Option<SchemaCache>,
+
+    // output schemas inferred from the results of tools without one
+    #[serde(skip)]
+    output_schemas: OutputSchemas,
+
+    // handlers of requests upstream servers send during tool calls
+    #[serde(skip)]
+    client_handlers: ClientHandlers,
+
+    // optional workers executing the code instead of this process
+    #[serde(skip)]
+    remote_workers: Option<RemoteWorkers>,
+
+    // where code is executed when not on remote workers
+    #[serde(skip)]
+    isolation: Isolation,
+
+    // time after which executions are stopped & their pending tool calls cancelled
+    #[serde(skip)]
+    execution_timeout: Option<Duration>,
+
+    // MCP tool calls an execution may make
+    #[serde(skip)]
+    max_tool_calls: Option<u32>,
+
+    // seeded randomness & frozen clock of executions
+    #[serde(skip)]
+    determinism: Option<Determinism>,
+
+    // identifiers of executions passed to the callbacks they invoke
+    #[serde(skip)]
+    execution_ids: ExecutionIds,
 }
 
 impl CodeMode {
@@ -58,6 +196,125 @@ impl CodeMode {
         Ok(self)
     }
 
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Result<Self> {
+        self.set_embedder(embedder)?;
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn with_schema_cache(mut self, schema_cache: SchemaCache) -> Self {
+        self.set_schema_cache(Some(schema_cache));
+        self
+    }
+
+    #[must_use]
+    pub fn with_type_check(mut self, type_check: TypeCheckConfig) -> Self {
+        self.set_type_check(type_check);
+        self
+    }
+
+    #[must_use]
+    pub fn with_imports(mut self, imports: ImportsConfig) -> Self {
+        self.set_imports(imports);
+        self
+    }
+
+    #[must_use]
+    pub fn with_collisions(mut self, collisions: CollisionConfig) -> Self {
+        self.set_collisions(collisions);
+        self
+    }
+
+    #[must_use]
+    pub fn with_sampling_handler(mut self, handler: Arc<dyn SamplingHandler>) -> Self {
+        self.set_sampling_handler(Some(handler));
+        self
+    }
+
+    #[must_use]
+    pub fn with_elicitation_handler(mut self, handler: Arc<dyn ElicitationHandler>) -> Self {
+        self.set_elicitation_handler(Some(handler));
+        self
+    }
+
+    #[must_use]
+    pub fn with_progress_handler(mut self, handler: Arc<dyn ProgressHandler>) -> Self {
+        self.set_progress_handler(Some(handler));
+        self
+    }
+
+    #[must_use]
+    pub fn with_approval_handler(mut self, handler: Arc<dyn ApprovalHandler>) -> Self {
+        self.set_approval_handler(Some(handler));
+        self
+    }
+
+    #[must_use]
+    pub fn with_roots(mut self, roots: Vec<Root>) -> Self {
+        self.set_roots(Some(roots));
+        self
+    }
+
+    #[must_use]
+    pub fn with_wasm_modules(mut self, modules: WasmModules) -> Self {
+        self.set_wasm_modules(modules);
+        self
+    }
+
+    #[must_use]
+    pub fn with_artifacts(mut self, store: ArtifactStore) -> Self {
+        self.set_artifacts(store);
+        self
+    }
+
+    #[must_use]
+    pub fn with_results(mut self, store: ResultStore) -> Self {
+        self.set_results(store);
+        self
+    }
+
+    #[must_use]
+    pub fn with_fs(mut self, fs: VirtualFs) -> Self {
+        self.set_fs(Some(fs));
+        self
+    }
+
+    #[must_use]
+    pub fn with_kv(mut self, kv: KvStore) -> Self {
+        self.set_kv(Some(kv));
+        self
+    }
+
+    #[must_use]
+    pub fn with_remote_workers(mut self, workers: RemoteWorkers) -> Self {
+        self.set_remote_workers(Some(workers));
+        self
+    }
+
+    #[must_use]
+    pub fn with_isolation(mut self, isolation: Isolation) -> Self {
+        self.set_isolation(isolation);
+        self
+    }
+
+    #[must_use]
+    pub fn with_execution_timeout(mut self, timeout: Duration) -> Self {
+        self.set_execution_timeout(Some(timeout));
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_tool_calls(mut self, max: u32) -> Self {
+        self.set_max_tool_calls(Some(max));
+        self
+    }
+
+    #[must_use]
+    pub fn with_determinism(mut self, determinism: Determinism) -> Self {
+        self.set_determinism(Some(determinism));
+        self
+    }
+
     // --------------- Registrations functions ---------------
 
     pub async fn add_server(&mut self, server: &ServerConfig) -> Result<()> {
@@ -76,11 +333,31 @@ impl CodeMode {
         for server in servers {
             servers_to_add.push(server.clone());
             let server = server.clone();
+            let schema_cache = self.schema_cache.clone();
             let task = tokio::spawn(async move {
+                // servers with a schema snapshot are only connected on the first tool call
+                if let Some(tool_set) = schema_cache.as_ref().and_then(|c| c.load(&server)) {
+                    debug!(server = %server.name, "Loaded MCP server tools from schema snapshot");
+                    return Ok(tool_set);
+                }
+
+                let started = Instant::now();
                 let result = tokio::time::timeout(timeout, Self::server_to_toolset(&server)).await;
 
                 match result {
-                    Ok(Ok(tool_set)) => Ok(tool_set),
+                    Ok(Ok(tool_set)) => {
+                        debug!(
+                            server = %server.name,
+                            elapsed_ms = started.elapsed().as_millis(),
+                            "Loaded MCP server tools"
+                        );
+                        if let Some(cache) = &schema_cache
+                            && let Err(e) = cache.store(&server, &tool_set)
+                        {
+                            warn!(server = %server.name, error = %e, "Failed storing schema snapshot");
+                        }
+                        Ok(tool_set)
+                    }
                     Ok(Err(e)) => Err(e),
                     Err(_) => Err(Error::Message(format!(
                         "Registration timed out after {}s for MCP server {} ({})",
@@ -103,17 +380,66 @@ impl CodeMode {
             })??);
         }
 
-        // check for ToolSet conflicts & add to self
-        for tool_set in tool_sets {
-            self.add_tool_set(tool_set)?;
+        // apply namespaces, grouping & tool annotations, check for ToolSet conflicts & add to self
+        for (server, tool_set) in servers_to_add.iter().zip(tool_sets) {
+            self.add_tool_set(configure_tools(server, tool_set)?)?;
+
+            // seed output schemas inferred in previous runs
+            if server.infer_output_schemas
+                && let Some(cache) = &self.schema_cache
+            {
+                for (tool, schema) in cache.load_outputs(server) {
+                    self.output_schemas.seed(&server.name, &tool, &schema);
+                }
+            }
         }
 
         // add server configs
         self.servers.extend(servers_to_add);
 
+        self.refine_output_types()?;
+
         Ok(())
     }
 
+    /// Regenerates the return types of tools without an output schema on servers
+    /// configured with `infer_output_schemas`, from the shapes of their results so far.
+    /// Returns the number of tools with refined return types.
+    pub fn refine_output_types(&mut self) -> Result<usize> {
+        let mut refined = 0;
+        for server in self.servers.iter().filter(|s| s.infer_output_schemas) {
+            let Some(tool_set) = self.tool_sets.iter_mut().find(|t| t.name == server.name) else {
+                continue;
+            };
+
+            for (name, schema) in self.output_schemas.server_schemas(&server.name) {
+                let Some(idx) = tool_set.tools.iter().position(|t| {
+                    t.name == name && (t.output_schema.is_none() || t.output_inferred)
+                }) else {
+                    continue;
+                };
+                let tool = &tool_set.tools[idx];
+                if tool.output_schema.as_ref().map(|o| json!(o)).as_ref() == Some(&schema) {
+                    continue;
+                }
+
+                let output =
+                    serde_json::from_value::<pctx_codegen::RootSchema>(schema).map_err(|e| {
+                        Error::Message(format!(
+                            "Failed parsing inferred output schema of tool `{name}`: {e}"
+                        ))
+                    })?;
+                tool_set.tools[idx] = tool.clone().with_inferred_output(output)?;
+                refined += 1;
+            }
+        }
+
+        if refined > 0 {
+            debug!(refined, "Refined inferred tool return types");
+        }
+        Ok(refined)
+    }
+
     async fn server_to_toolset(server: &ServerConfig) -> Result<ToolSet> {
         // Connect to the MCP server (this is the slow operation)
         debug!(
@@ -195,22 +521,52 @@ impl CodeMode {
         Ok(())
     }
 
+    /// Adds a callback tool with the schemas of its `In` arguments & `Out` result, and
+    /// registers `f` as its callback in `registry`, which must be passed to executions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tool already exists or the callback is already registered
+    pub fn add_typed_callback<In, Out, F, Fut>(
+        &mut self,
+        registry: &CallbackRegistry,
+        namespace: &str,
+        name: &str,
+        description: Option<&str>,
+        f: F,
+    ) -> Result<CallbackConfig>
+    where
+        In: DeserializeOwned + JsonSchema + Send + 'static,
+        Out: Serialize + JsonSchema + 'static,
+        F: Fn(In) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Out, String>> + Send + 'static,
+    {
+        let config = CallbackConfig::typed::<In, Out>(namespace, name, description);
+        self.add_callback(&config)?;
+        registry
+            .add(&config.id(), typed_callback(f))
+            .map_err(|e| Error::Message(e.to_string()))?;
+        Ok(config)
+    }
+
+    /// Adds the tools of a [`MockToolSet`] and registers their canned responses in
+    /// `registry`, which must be passed to executions
+    pub fn add_mock_tools(
+        &mut self,
+        mock: &MockToolSet,
+        registry: &CallbackRegistry,
+    ) -> Result<()> {
+        self.add_callbacks(&mock.callbacks())?;
+        mock.register(registry)
+    }
+
     // Generates a Tool and add it to the correct Toolset from the given callback config
     pub fn add_callback(&mut self, callback: &CallbackConfig) -> Result<()> {
         debug!(callback =? callback.id(), "Adding callback tool {}", callback.id());
 
         // find the correct toolset & check for clashes
-        let idx = self
-            .tool_sets
-            .iter()
-            .position(|s| s.name == callback.namespace)
-            .unwrap_or_else(|| {
-                let idx = self.tool_sets.len();
-                self.tool_sets
-                    .push(ToolSet::new(&callback.namespace, "", vec![]));
-                idx
-            });
-        let tool_set = &mut self.tool_sets[idx];
+        let idx = self.tool_set_index(&callback.namespace, "")?;
+        let tool_set = &self.tool_sets[idx];
 
         if tool_set.tools.iter().any(|t| t.name == callback.name) {
             return Err(Error::Message(format!(
@@ -220,251 +576,1899 @@ impl CodeMode {
         }
 
         // convert callback config into tool
-        let input_schema = if let Some(i) = &callback.input_schema {
-            serde_json::from_value::<pctx_codegen::RootSchema>(json!(i)).map_err(|e| {
-                Error::Message(format!(
-                    "Failed parsing inputSchema as json schema for tool `{}`: {e}",
-                    &callback.name
-                ))
-            })?
-        } else {
-            // TODO: better empty input schema support
-            serde_json::from_value::<pctx_codegen::RootSchema>(json!({})).unwrap()
-        };
-        let output_schema = if let Some(o) = &callback.output_schema {
-            Some(
-                serde_json::from_value::<pctx_codegen::RootSchema>(json!(o)).map_err(|e| {
-                    Error::Message(format!(
-                        "Failed parsing outputSchema as json schema for tool `{}`: {e}",
-                        &callback.name
-                    ))
-                })?,
-            )
-        } else {
-            None
-        };
-        let tool = Tool::new_callback(
+        let tool = callback_tool(
             &callback.name,
             callback.description.clone(),
-            input_schema,
-            output_schema,
+            callback.input_schema.as_ref(),
+            callback.output_schema.as_ref(),
         )?;
 
+        let tool = collisions::resolve_tool(&self.tool_sets, tool_set, tool, &self.collisions)?;
+
         // add tool & it's configuration
-        tool_set.tools.push(tool);
+        self.tool_sets[idx].tools.push(tool);
         self.callbacks.push(callback.clone());
 
-        Ok(())
+        self.reindex()
     }
 
-    pub fn add_tool_set(&mut self, tool_set: ToolSet) -> Result<()> {
-        if self.tool_sets.iter().any(|t| t.name == tool_set.name) {
+    /// Type checks the script against the current functions & adds it, replacing the
+    /// script with the same name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not a valid tool name or the script has type errors
+    pub async fn add_script(&mut self, script: Script) -> Result<()> {
+        let valid_name = !script.name.is_empty()
+            && script.name.len() <= 128
+            && script
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid_name {
             return Err(Error::Message(format!(
-                "CodeMode already has ToolSet with name: {}",
-                tool_set.name
+                "Invalid script name `{}`, names may only contain letters, digits, '_', '-' and '.'",
+                script.name
             )));
         }
 
-        self.tool_sets.push(tool_set);
+        let check = self.check(&script.code).await?;
+        if !check.success {
+            let diagnostics: Vec<String> =
+                check.diagnostics.iter().map(ToString::to_string).collect();
+            return Err(Error::Message(format!(
+                "Script `{}` has type errors:\n{}",
+                script.name,
+                diagnostics.join("\n")
+            )));
+        }
 
+        debug!(script = %script.name, "Adding script");
+        self.scripts.retain(|s| s.name != script.name);
+        self.scripts.push(script);
         Ok(())
     }
 
-    // --------------- Accessor functions ---------------
+    /// Type checks the skill against the current functions & adds it as
+    /// `Skills.<name>(input)`, replacing the skill with the same name. The skill is left
+    /// unchanged if the new one is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not a valid identifier, the code imports modules
+    /// or exports a default, or the skill has type errors
+    pub async fn add_skill(&mut self, skill: Skill) -> Result<()> {
+        let valid_name = skill
+            .name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && skill.name.len() <= 128
+            && skill
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(Error::Message(format!(
+                "Invalid skill name `{}`, names may only contain letters, digits and '_'",
+                skill.name
+            )));
+        }
+        // the code is inlined into the skill's function
+        let imports = skill
+            .code
+            .lines()
+            .any(|line| line.trim_start().starts_with("import "));
+        if imports || has_default_export(&skill.code) {
+            return Err(Error::Message(format!(
+                "Skill `{}` may neither import modules nor export a default",
+                skill.name
+            )));
+        }
 
-    /// Returns an immutable reference to the registered ToolSets
-    pub fn tool_sets(&self) -> &[pctx_codegen::ToolSet] {
-        &self.tool_sets
-    }
+        let tool_sets = self.tool_sets.clone();
+        let skills = self.skills.clone();
+        if self.skills.iter().any(|s| s.name == skill.name) {
+            self.remove_skill(&skill.name)?;
+        }
 
-    /// Returns an immutable reference to the registered server configurations
-    pub fn servers(&self) -> &[ServerConfig] {
-        &self.servers
-    }
+        let name = skill.name.clone();
+        let added = match self.insert_skill(skill) {
+            Ok(()) => self.check("").await,
+            Err(e) => Err(e),
+        };
+        let error = match added {
+            Ok(check) if check.success => None,
+            Ok(check) => {
+                let diagnostics: Vec<String> =
+                    check.diagnostics.iter().map(ToString::to_string).collect();
+                Some(Error::Message(format!(
+                    "Skill `{name}` has type errors:\n{}",
+                    diagnostics.join("\n")
+                )))
+            }
+            Err(e) => Some(e),
+        };
+        if let Some(e) = error {
+            self.tool_sets = tool_sets;
+            self.skills = skills;
+            self.reindex()?;
+            return Err(e);
+        }
 
-    /// Returns an immutable reference to the registered callback configurations
-    pub fn callbacks(&self) -> &[CallbackConfig] {
-        &self.callbacks
+        debug!(skill = %name, "Added skill");
+        Ok(())
     }
 
-    pub fn allowed_hosts(&self) -> HashSet<String> {
-        self.servers
-            .iter()
-            .filter_map(|s| {
-                let http_cfg = s.http()?;
-                let host = http_cfg.url.host()?;
-                let allowed = if let Some(port) = http_cfg.url.port() {
-                    format!("{host}:{port}")
-                } else {
-                    let default_port = if http_cfg.url.scheme() == "https" {
-                        443
-                    } else {
-                        80
-                    };
-                    format!("{host}:{default_port}")
-                };
-                Some(allowed)
-            })
-            .collect()
+    /// Promotes a successful execution to a skill parameterized by the execution's
+    /// `input`, see [`Skill::from_execution`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the execution failed or the skill cannot be added
+    /// (see [`CodeMode::add_skill`])
+    pub async fn promote_execution(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        input: &ExecuteInput,
+        output: &ExecuteOutput,
+    ) -> Result<Skill> {
+        if !output.success {
+            return Err(Error::Message(
+                "Only successful executions can be promoted to skills".into(),
+            ));
+        }
+
+        let skill = Skill::from_execution(
+            name,
+            description.map(String::from),
+            input,
+            output.output.as_ref(),
+        );
+        self.add_skill(skill.clone()).await?;
+        Ok(skill)
     }
 
-    // --------------- Code-Mode Tools ---------------
+    fn insert_skill(&mut self, skill: Skill) -> Result<()> {
+        let tool = callback_tool(
+            &skill.name,
+            skill.description.clone(),
+            skill.input_schema.as_ref(),
+            skill.output_schema.as_ref(),
+        )?;
+        let idx = self.tool_set_index(
+            SKILLS_TOOL_SET,
+            "Functions learned from successful executions",
+        )?;
+        let tool = collisions::resolve_tool(
+            &self.tool_sets,
+            &self.tool_sets[idx],
+            tool,
+            &self.collisions,
+        )?;
 
-    /// Returns internal tool sets as minimal code interfaces
-    pub fn list_functions(&self) -> ListFunctionsOutput {
-        let mut namespaces = vec![];
-        let mut functions = vec![];
+        self.tool_sets[idx].tools.push(tool);
+        self.skills.push(skill);
+        self.reindex()
+    }
 
-        for tool_set in &self.tool_sets {
+    /// Removes the skill & its function
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no skill with the name was added
+    pub fn remove_skill(&mut self, name: &str) -> Result<Skill> {
+        let idx = self
+            .skills
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| Error::Message(format!("CodeMode has no skill `{name}`")))?;
+        let skill = self.skills.remove(idx);
+
+        if let Some(idx) = self
+            .tool_sets
+            .iter()
+            .position(|s| s.name == SKILLS_TOOL_SET)
+        {
+            let tool_set = &mut self.tool_sets[idx];
+            tool_set.tools.retain(|t| t.name != skill.name);
             if tool_set.tools.is_empty() {
-                // skip sets with no tools
-                continue;
+                self.tool_sets.remove(idx);
             }
-
-            namespaces.push(tool_set.namespace_interface(false));
-
-            functions.extend(tool_set.tools.iter().map(|t| ListedFunction {
-                namespace: tool_set.namespace.clone(),
-                name: t.fn_name.clone(),
-                description: t.description.clone(),
-            }));
         }
 
-        ListFunctionsOutput {
-            code: pctx_codegen::format::format_d_ts(&namespaces.join("\n\n")),
-            functions,
-        }
+        self.reindex()?;
+        Ok(skill)
     }
 
-    /// Gets the full typed interface for the requested functions
-    pub fn get_function_details(&self, input: GetFunctionDetailsInput) -> GetFunctionDetailsOutput {
-        // sort by mod
-        let mut by_mod: HashMap<String, HashSet<String>> = HashMap::default();
-        for fn_id in &input.functions {
-            by_mod
-                .entry(fn_id.mod_name.clone())
-                .or_default()
-                .insert(fn_id.fn_name.clone());
+    /// Index of the tool set with the name, adding an empty tool set if none exists
+    fn tool_set_index(&mut self, name: &str, description: &str) -> Result<usize> {
+        if let Some(idx) = self.tool_sets.iter().position(|s| s.name == name) {
+            return Ok(idx);
         }
+        let tool_set = collisions::resolve_tool_set(
+            &self.tool_sets,
+            ToolSet::new(name, description, vec![]),
+            &self.collisions,
+        )?;
+        self.tool_sets.push(tool_set);
+        Ok(self.tool_sets.len() - 1)
+    }
 
-        let mut namespaces = vec![];
-        let mut functions = vec![];
-
-        for tool_set in &self.tool_sets {
-            if let Some(fn_names) = by_mod.get(&tool_set.namespace) {
-                // filter tools based on requested fn names
-                let tools: Vec<&pctx_codegen::Tool> = tool_set
-                    .tools
-                    .iter()
-                    .filter(|t| fn_names.contains(&t.fn_name))
-                    .collect();
-
-                if !tools.is_empty() {
-                    // code definition
-                    let fn_details: Vec<String> =
-                        tools.iter().map(|t| t.fn_signature(true)).collect();
-                    namespaces.push(tool_set.wrap_with_namespace(&fn_details.join("\n\n")));
-
-                    // struct output
-                    functions.extend(tools.iter().map(|t| FunctionDetails {
-                        listed: ListedFunction {
-                            namespace: tool_set.namespace.clone(),
-                            name: t.fn_name.clone(),
-                            description: t.description.clone(),
-                        },
-                        input_type: t.input_signature.clone(),
-                        output_type: t.output_signature.clone(),
-                        types: t.types.clone(),
-                    }));
-                }
-            }
+    pub fn add_tool_set(&mut self, tool_set: ToolSet) -> Result<()> {
+        if self.tool_sets.iter().any(|t| t.name == tool_set.name) {
+            return Err(Error::Message(format!(
+                "CodeMode already has ToolSet with name: {}",
+                tool_set.name
+            )));
         }
 
-        let code = if namespaces.is_empty() {
-            "// No namespaces/functions match the request".to_string()
-        } else {
-            pctx_codegen::format::format_d_ts(&namespaces.join("\n\n"))
-        };
+        let tool_set = collisions::resolve_tool_set(&self.tool_sets, tool_set, &self.collisions)?;
+        self.tool_sets.push(tool_set);
 
-        GetFunctionDetailsOutput { code, functions }
+        self.reindex()
     }
 
-    #[instrument(skip(self, callback_registry), ret(Display), err)]
-    pub async fn execute(
-        &self,
-        code: &str,
-        callback_registry: Option<CallbackRegistry>,
-    ) -> Result<ExecuteOutput> {
-        let registry = callback_registry.unwrap_or_default();
-        // Format for logging only
-        let formatted_code = pctx_codegen::format::format_ts(code);
+    /// Removes the MCP server and its tools, shutting down its supervised stdio process.
+    /// Callback tools added to the server's namespace are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no server with the name was added
+    pub async fn remove_server(&mut self, name: &str) -> Result<ServerConfig> {
+        let idx = self
+            .servers
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| Error::Message(format!("CodeMode has no MCP server `{name}`")))?;
+        let server = self.servers.remove(idx);
+
+        if let Some(idx) = self.tool_sets.iter().position(|s| s.name == name) {
+            let callbacks: HashSet<&str> = self
+                .callbacks
+                .iter()
+                .filter(|c| c.namespace == name)
+                .map(|c| c.name.as_str())
+                .collect();
+            let tool_set = &mut self.tool_sets[idx];
+            tool_set
+                .tools
+                .retain(|t| callbacks.contains(t.name.as_str()));
+            if tool_set.tools.is_empty() {
+                self.tool_sets.remove(idx);
+            }
+        }
+        self.stdio_supervisor.stop(name).await;
 
-        debug!(
-            code_from_llm = %code,
-            formatted_code = %formatted_code,
-            code_length = code.len(),
-            callbacks =? registry.ids(),
-            "Received code to execute"
-        );
+        self.reindex()?;
+        Ok(server)
+    }
 
-        // confirm all configured callbacks in the CodeMode interface have
-        // registered callback functions
-        let missing_ids: Vec<String> = self
+    /// Removes the callback tool with the id `namespace.name`, its callback function can be
+    /// removed from the registry with [`CallbackRegistry::remove`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no callback with the id was added
+    pub fn remove_callback(&mut self, id: &str) -> Result<CallbackConfig> {
+        let idx = self
             .callbacks
             .iter()
-            .filter_map(|c| {
-                if registry.has(&c.id()) {
-                    None
-                } else {
-                    Some(c.id())
-                }
-            })
-            .collect();
-        if !missing_ids.is_empty() {
-            return Err(Error::Message(format!(
-                "Missing configured callbacks in registry with ids: {missing_ids:?}"
-            )));
-        }
+            .position(|c| c.id() == id)
+            .ok_or_else(|| Error::Message(format!("CodeMode has no callback `{id}`")))?;
+        let callback = self.callbacks.remove(idx);
 
-        // generate the full script to be executed
-        let namespaces: Vec<String> = self
+        if let Some(idx) = self
             .tool_sets
             .iter()
-            .filter_map(|s| {
-                if s.tools.is_empty() {
-                    None
-                } else {
-                    Some(s.namespace())
-                }
-            })
-            .collect();
+            .position(|s| s.name == callback.namespace)
+        {
+            let tool_set = &mut self.tool_sets[idx];
+            tool_set.tools.retain(|t| t.name != callback.name);
+            if tool_set.tools.is_empty() {
+                self.tool_sets.remove(idx);
+            }
+        }
 
-        // Put LLM code at the top, then namespaces below
-        let to_execute = format!(
-            "{code}\n\n{namespaces}\n\nexport default await run();\n",
-            namespaces = namespaces.join("\n\n"),
-        );
+        self.reindex()?;
+        Ok(callback)
+    }
 
-        debug!(to_execute = %to_execute, "Executing code in sandbox");
+    /// Replaces the callback tool with the same id, e.g. to change its description or schemas.
+    /// The callback is left unchanged if the updated configuration is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no callback with the id was added or the configuration is invalid
+    pub fn update_callback(&mut self, callback: &CallbackConfig) -> Result<()> {
+        let tool_sets = self.tool_sets.clone();
+        let callbacks = self.callbacks.clone();
+
+        self.remove_callback(&callback.id())?;
+        if let Err(e) = self.add_callback(callback) {
+            self.tool_sets = tool_sets;
+            self.callbacks = callbacks;
+            self.reindex()?;
+            return Err(e);
+        }
+        Ok(())
+    }
 
-        let options = pctx_executor::ExecuteOptions::new()
-            .with_allowed_hosts(self.allowed_hosts().into_iter().collect())
-            .with_servers(self.servers.clone())
-            .with_callbacks(registry);
+    /// Replaces the ToolSet with the same name, returning the replaced ToolSet.
+    /// The ToolSet is added if no ToolSet has its name, callbacks of the namespace without
+    /// a tool in the new ToolSet are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ToolSet's namespace or functions collide with other ToolSets
+    pub fn replace_tool_set(&mut self, tool_set: ToolSet) -> Result<Option<ToolSet>> {
+        let Some(idx) = self.tool_sets.iter().position(|s| s.name == tool_set.name) else {
+            self.add_tool_set(tool_set)?;
+            return Ok(None);
+        };
 
-        let execution_res = pctx_executor::execute(&to_execute, options).await?;
+        let others: Vec<ToolSet> = self
+            .tool_sets
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != idx)
+            .map(|(_, s)| s.clone())
+            .collect();
+        let tool_set = collisions::resolve_tool_set(&others, tool_set, &self.collisions)?;
+        let replaced = std::mem::replace(&mut self.tool_sets[idx], tool_set);
+        let tool_set = &self.tool_sets[idx];
+        self.callbacks.retain(|c| {
+            c.namespace != tool_set.name || tool_set.tools.iter().any(|t| t.name == c.name)
+        });
+
+        self.reindex()?;
+        Ok(Some(replaced))
+    }
+
+    /// Enables semantic tool discovery, embedding all current and future tools
+    /// with the provided embedder. Powers [`CodeMode::find_tools`] & [`CodeMode::search_functions`].
+    pub fn set_embedder(&mut self, embedder: Arc<dyn Embedder>) -> Result<()> {
+        let mut index = ToolIndex::new(embedder);
+        index.rebuild(&self.tool_sets)?;
+        self.tool_index = Some(index);
+
+        Ok(())
+    }
+
+    /// Sets the directory of tool schema snapshots used to load servers
+    /// without connecting to them
+    pub fn set_schema_cache(&mut self, schema_cache: Option<SchemaCache>) {
+        self.schema_cache = schema_cache;
+    }
+
+    /// Sets the TypeScript compiler options used to type check code
+    pub fn set_type_check(&mut self, type_check: TypeCheckConfig) {
+        self.type_check = type_check;
+    }
+
+    /// Sets the `npm:`, `jsr:` and `https:` module imports allowed in executed code
+    pub fn set_imports(&mut self, imports: ImportsConfig) {
+        self.imports = imports;
+    }
+
+    /// Sets how namespaces or function names produced by more than one registration are resolved
+    pub fn set_collisions(&mut self, collisions: CollisionConfig) {
+        self.collisions = collisions;
+    }
+
+    /// Sets the handler of `sampling/createMessage` requests upstream servers send during
+    /// tool calls, servers are only offered the sampling capability if a handler is set
+    pub fn set_sampling_handler(&mut self, handler: Option<Arc<dyn SamplingHandler>>) {
+        self.client_handlers.sampling = handler;
+    }
+
+    /// Sets the handler of `elicitation/create` requests upstream servers send during
+    /// tool calls, servers are only offered the elicitation capability if a handler is set
+    pub fn set_elicitation_handler(&mut self, handler: Option<Arc<dyn ElicitationHandler>>) {
+        self.client_handlers.elicitation = handler;
+    }
+
+    /// Sets the handler of progress notifications upstream servers send during tool
+    /// calls, progress is only requested from servers if a handler is set
+    pub fn set_progress_handler(&mut self, handler: Option<Arc<dyn ProgressHandler>>) {
+        self.client_handlers.progress = handler;
+    }
+
+    /// Sets the handler approving calls of tools configured with `requires_approval`,
+    /// the elicitation handler asks the user if unset and calls are denied without either
+    pub fn set_approval_handler(&mut self, handler: Option<Arc<dyn ApprovalHandler>>) {
+        self.client_handlers.approval = handler;
+    }
+
+    /// Sets the workspace roots listed by `pctx.roots.list()`, passed to callbacks and
+    /// returned to upstream servers, which are only offered the roots capability if set
+    pub fn set_roots(&mut self, roots: Option<Vec<Root>>) {
+        self.client_handlers.roots = roots;
+    }
+
+    /// Sets the client the handlers reach (e.g. its MCP session id), supervised stdio
+    /// server processes are only shared between executions of the same owner
+    pub fn set_handlers_owner(&mut self, owner: Option<String>) {
+        self.client_handlers.owner = owner;
+    }
+
+    /// Sets the WebAssembly modules executed code can load with `pctx.wasm.load(name)`
+    pub fn set_wasm_modules(&mut self, modules: WasmModules) {
+        self.wasm_modules = modules;
+    }
+
+    /// Sets the store of the artifacts executions put & get with `pctx.artifacts`,
+    /// executions on remote workers or in child processes have no artifacts
+    pub fn set_artifacts(&mut self, store: ArtifactStore) {
+        self.artifacts = store;
+    }
+
+    /// Sets the store outputs larger than its offloading threshold are kept in, the
+    /// output is replaced by a handle executed code fetches parts of with `pctx.results`
+    pub fn set_results(&mut self, store: ResultStore) {
+        self.results = store;
+    }
+
+    /// Sets the virtual filesystem executed code can access with `pctx.fs`, files are
+    /// shared between executions
+    pub fn set_fs(&mut self, fs: Option<VirtualFs>) {
+        self.fs = fs;
+    }
+
+    /// Sets the key-value store executed code remembers state in with `pctx.kv`, only
+    /// executions in this process can access it
+    pub fn set_kv(&mut self, kv: Option<KvStore>) {
+        self.kv = kv;
+    }
+
+    /// Sets the workers executing code instead of this process, executions with
+    /// callbacks or WebAssembly modules then fail
+    pub fn set_remote_workers(&mut self, workers: Option<RemoteWorkers>) {
+        self.remote_workers = workers;
+    }
+
+    /// Sets where code is executed, e.g. in a child process per execution, executions
+    /// with callbacks or WebAssembly modules then fail
+    pub fn set_isolation(&mut self, isolation: Isolation) {
+        self.isolation = isolation;
+    }
+
+    /// Sets the time after which executions are stopped, cancelling their pending MCP
+    /// tool calls & callbacks
+    pub fn set_execution_timeout(&mut self, timeout: Option<Duration>) {
+        self.execution_timeout = timeout;
+    }
+
+    /// Sets the number of MCP tool calls an execution may make, further calls fail
+    pub fn set_max_tool_calls(&mut self, max: Option<u32>) {
+        self.max_tool_calls = max;
+    }
+
+    /// Sets the identifiers of executions passed to callbacks with the
+    /// [`InvocationContext`](pctx_code_execution_runtime::InvocationContext)
+    pub fn set_execution_ids(&mut self, ids: ExecutionIds) {
+        self.execution_ids = ids;
+    }
+
+    /// Sets the seed of `Math.random` & `crypto.getRandomValues` and the frozen time of
+    /// `Date` in executions, so executions produce identical outputs
+    pub fn set_determinism(&mut self, determinism: Option<Determinism>) {
+        self.determinism = determinism;
+    }
+
+    fn reindex(&mut self) -> Result<()> {
+        if let Some(index) = &mut self.tool_index {
+            index.rebuild(&self.tool_sets)?;
+        }
+        Ok(())
+    }
+
+    /// Persists the inferred output schemas if results refined them
+    fn store_output_schemas(&self) {
+        let Some(cache) = &self.schema_cache else {
+            return;
+        };
+        if !self.output_schemas.take_changed() {
+            return;
+        }
+
+        for server in self.servers.iter().filter(|s| s.infer_output_schemas) {
+            let outputs = self.output_schemas.server_schemas(&server.name);
+            if outputs.is_empty() {
+                continue;
+            }
+            if let Err(e) = cache.store_outputs(server, outputs) {
+                warn!(server = %server.name, error = %e, "Failed storing inferred output schemas");
+            }
+        }
+    }
+
+    // --------------- Accessor functions ---------------
+
+    /// Returns an immutable reference to the registered ToolSets
+    pub fn tool_sets(&self) -> &[pctx_codegen::ToolSet] {
+        &self.tool_sets
+    }
+
+    /// Returns an immutable reference to the registered server configurations
+    pub fn servers(&self) -> &[ServerConfig] {
+        &self.servers
+    }
+
+    /// Returns an immutable reference to the registered callback configurations
+    pub fn callbacks(&self) -> &[CallbackConfig] {
+        &self.callbacks
+    }
+
+    /// Returns the TypeScript compiler options used to type check code
+    pub fn type_check(&self) -> &TypeCheckConfig {
+        &self.type_check
+    }
+
+    /// Returns the module imports allowed in executed code
+    pub fn imports(&self) -> &ImportsConfig {
+        &self.imports
+    }
+
+    /// Returns how colliding namespaces or function names are resolved
+    pub fn collisions(&self) -> &CollisionConfig {
+        &self.collisions
+    }
+
+    /// Returns the handlers of requests upstream servers send during tool calls
+    pub fn client_handlers(&self) -> &ClientHandlers {
+        &self.client_handlers
+    }
+
+    /// Returns the WebAssembly modules executed code can load
+    pub fn wasm_modules(&self) -> &WasmModules {
+        &self.wasm_modules
+    }
+
+    /// Returns the store of the artifacts of executions
+    pub fn artifacts(&self) -> &ArtifactStore {
+        &self.artifacts
+    }
+
+    /// Returns the store of offloaded outputs
+    pub fn results(&self) -> &ResultStore {
+        &self.results
+    }
+
+    /// Returns the saved scripts executed with [`CodeMode::execute_script`]
+    pub fn scripts(&self) -> &[Script] {
+        &self.scripts
+    }
+
+    /// Skills callable by executed code as `Skills.<name>(input)`
+    pub fn skills(&self) -> &[Skill] {
+        &self.skills
+    }
+
+    /// Returns the virtual filesystem executed code can access
+    pub fn fs(&self) -> Option<&VirtualFs> {
+        self.fs.as_ref()
+    }
+
+    /// Returns the key-value store executed code can access
+    pub fn kv(&self) -> Option<&KvStore> {
+        self.kv.as_ref()
+    }
+
+    /// Returns the workers executing code instead of this process
+    pub fn remote_workers(&self) -> Option<&RemoteWorkers> {
+        self.remote_workers.as_ref()
+    }
+
+    /// Returns where code is executed
+    pub fn isolation(&self) -> &Isolation {
+        &self.isolation
+    }
+
+    /// Returns the time after which executions are stopped
+    pub fn execution_timeout(&self) -> Option<Duration> {
+        self.execution_timeout
+    }
+
+    /// Returns the number of MCP tool calls an execution may make
+    pub fn max_tool_calls(&self) -> Option<u32> {
+        self.max_tool_calls
+    }
+
+    /// Returns the identifiers of executions passed to callbacks
+    pub fn execution_ids(&self) -> &ExecutionIds {
+        &self.execution_ids
+    }
+
+    /// Returns the seeded randomness & frozen clock of executions
+    pub fn determinism(&self) -> Option<Determinism> {
+        self.determinism
+    }
+
+    /// Returns the cache of MCP tool results shared between executions
+    pub fn tool_cache(&self) -> &ToolCache {
+        &self.tool_cache
+    }
+
+    /// Returns the circuit breakers of upstream MCP servers shared between executions
+    pub fn circuit_breakers(&self) -> &CircuitBreakers {
+        &self.circuit_breakers
+    }
+
+    /// Returns the rate limits of MCP tools shared between executions
+    pub fn rate_limiters(&self) -> &RateLimiters {
+        &self.rate_limiters
+    }
+
+    /// Returns the cache of type check results & transpiled code shared between executions
+    pub fn check_cache(&self) -> &CheckCache {
+        &self.check_cache
+    }
+
+    /// Returns the output schemas inferred from tool results shared between executions
+    pub fn output_schemas(&self) -> &OutputSchemas {
+        &self.output_schemas
+    }
+
+    /// Returns the tool schema snapshot cache, if set
+    pub fn schema_cache(&self) -> Option<&SchemaCache> {
+        self.schema_cache.as_ref()
+    }
+
+    /// Returns the supervisor of stdio MCP server processes shared between executions
+    pub fn stdio_supervisor(&self) -> &StdioSupervisor {
+        &self.stdio_supervisor
+    }
+
+    /// Returns the semantic tool discovery index, if an embedder is set
+    pub fn tool_index(&self) -> Option<&ToolIndex> {
+        self.tool_index.as_ref()
+    }
+
+    pub fn allowed_hosts(&self) -> HashSet<String> {
+        self.servers
+            .iter()
+            .filter_map(|s| {
+                let http_cfg = s.http()?;
+                let host = http_cfg.url.host()?;
+                let allowed = if let Some(port) = http_cfg.url.port() {
+                    format!("{host}:{port}")
+                } else {
+                    let default_port = if http_cfg.url.scheme() == "https" {
+                        443
+                    } else {
+                        80
+                    };
+                    format!("{host}:{default_port}")
+                };
+                Some(allowed)
+            })
+            .collect()
+    }
+
+    // --------------- Snapshots ---------------
+
+    /// Writes the resolved tool environment to a JSON snapshot: the servers, ToolSets,
+    /// callback configurations, type check, imports & collision configurations.
+    /// Callback functions, handlers, caches and the semantic index are not persisted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot cannot be written
+    pub fn save_snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let snapshot = CodeModeSnapshotRef {
+            version: SNAPSHOT_VERSION,
+            code_mode: self,
+        };
+        let contents = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| Error::Message(format!("Failed serializing CodeMode snapshot: {e}")))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                Error::Message(format!("Failed creating {}: {e}", parent.display()))
+            })?;
+        }
+        std::fs::write(path, contents).map_err(|e| {
+            Error::Message(format!(
+                "Failed writing CodeMode snapshot {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Restores a CodeMode from a snapshot written with [`CodeMode::save_snapshot`] without
+    /// connecting to upstream servers, which are connected on their first tool call.
+    /// Callback functions must be registered again, and handlers & the embedder set again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot cannot be read or was written by an incompatible version
+    pub fn load_snapshot(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            Error::Message(format!(
+                "Failed reading CodeMode snapshot {}: {e}",
+                path.display()
+            ))
+        })?;
+        let snapshot: CodeModeSnapshot = serde_json::from_str(&contents).map_err(|e| {
+            Error::Message(format!("Invalid CodeMode snapshot {}: {e}", path.display()))
+        })?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(Error::Message(format!(
+                "CodeMode snapshot {} has version {}, expected {SNAPSHOT_VERSION}",
+                path.display(),
+                snapshot.version
+            )));
+        }
+        Ok(snapshot.code_mode)
+    }
+
+    /// Writes the TypeScript interfaces executed code sees to `out_dir`: a `<Namespace>.d.ts`
+    /// file per namespace with listed functions and `pctx.d.ts` with the standard library.
+    /// Returns the paths of the written files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or a file cannot be written
+    pub fn emit_types(&self, out_dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let out_dir = out_dir.as_ref();
+        std::fs::create_dir_all(out_dir)
+            .map_err(|e| Error::Message(format!("Failed creating {}: {e}", out_dir.display())))?;
+
+        let mut files = vec![(out_dir.join("pctx.d.ts"), STD_DECLARATIONS.to_string())];
+        for tool_set in &self.tool_sets {
+            if tool_set.visible_tools().next().is_none() {
+                continue;
+            }
+            files.push((
+                out_dir.join(format!("{}.d.ts", tool_set.namespace)),
+                pctx_codegen::format::format_d_ts(&tool_set.namespace_interface(true)),
+            ));
+        }
+
+        for (path, contents) in &files {
+            std::fs::write(path, contents)
+                .map_err(|e| Error::Message(format!("Failed writing {}: {e}", path.display())))?;
+        }
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// Renders the `list_functions`, `get_function_details` & `execute` tools of code mode
+    /// as tool definitions of an LLM provider API. With `include_tools` the listed tools of
+    /// all tool sets follow, named `Namespace_fnName`.
+    pub fn export_tool_specs(
+        &self,
+        format: ToolSpecFormat,
+        include_tools: bool,
+    ) -> Vec<serde_json::Value> {
+        let mut specs = ToolSpec::meta_tools();
+        if include_tools {
+            for tool_set in &self.tool_sets {
+                specs.extend(
+                    tool_set
+                        .visible_tools()
+                        .map(|t| ToolSpec::for_tool(tool_set, t)),
+                );
+            }
+        }
+        specs.iter().map(|s| s.render(format)).collect()
+    }
+
+    /// Renders the listed tools of all tool sets as a catalog document with their
+    /// descriptions, signatures & example calls, for publishing outside of code mode
+    pub fn export_catalog(&self, format: CatalogFormat) -> String {
+        catalog::render(&self.tool_sets, format)
+    }
+
+    /// Renders the system prompt preamble recommended to agents using code mode, listing
+    /// the available namespaces, code conventions & error handling guidance. The
+    /// template & additional instructions are taken from the config.
+    pub fn system_prompt(&self, config: &PromptConfig) -> String {
+        prompt::render(&self.tool_sets, config)
+    }
+
+    // --------------- Code-Mode Tools ---------------
+
+    /// Returns internal tool sets as minimal code interfaces, hidden tools are not listed
+    pub fn list_functions(&self) -> ListFunctionsOutput {
+        self.list_functions_with(&ListFunctionsInput::default())
+    }
+
+    /// Returns internal tool sets as code interfaces of the input's detail level, shortening
+    /// or removing descriptions to fit its token budget
+    pub fn list_functions_with(&self, input: &ListFunctionsInput) -> ListFunctionsOutput {
+        // skip sets with no listed tools
+        let listed: Vec<(&ToolSet, Vec<&Tool>)> = self
+            .tool_sets
+            .iter()
+            .map(|s| (s, s.visible_tools().collect::<Vec<_>>()))
+            .filter(|(_, tools)| !tools.is_empty())
+            .collect();
+
+        let (code, trimmed) = budget::fit(input.budget_tokens, |trim| {
+            let interface = interface(&listed, input.detail_level, trim);
+            if input.detail_level == DetailLevel::Name {
+                interface
+            } else {
+                pctx_codegen::format::format_d_ts(&format!("{STD_DECLARATIONS}\n{interface}"))
+            }
+        });
+
+        let functions = listed
+            .iter()
+            .flat_map(|(s, tools)| tools.iter().map(move |t| (*s, *t)))
+            .map(|(tool_set, t)| ListedFunction {
+                namespace: tool_set.tool_namespace(t),
+                name: t.fn_name.clone(),
+                description: t.description.clone(),
+                size: InterfaceSize::of(&declaration(tool_set, t, input.detail_level, trimmed)),
+            })
+            .collect();
+
+        ListFunctionsOutput {
+            size: InterfaceSize::of(&code),
+            code,
+            functions,
+            trimmed,
+        }
+    }
+
+    /// Gets the typed interface for the requested functions, in full unless the input
+    /// requests another detail level
+    pub fn get_function_details(&self, input: GetFunctionDetailsInput) -> GetFunctionDetailsOutput {
+        // sort by mod
+        let mut by_mod: HashMap<String, HashSet<String>> = HashMap::default();
+        for fn_id in &input.functions {
+            by_mod
+                .entry(fn_id.mod_name.clone())
+                .or_default()
+                .insert(fn_id.fn_name.clone());
+        }
+
+        let mut matched: Vec<(&ToolSet, Vec<&Tool>)> = vec![];
+        for tool_set in &self.tool_sets {
+            // filter tools based on requested namespaces & fn names
+            let tools: Vec<&pctx_codegen::Tool> = tool_set
+                .tools
+                .iter()
+                .filter(|t| {
+                    by_mod
+                        .get(&tool_set.tool_namespace(t))
+                        .is_some_and(|fn_names| fn_names.contains(&t.fn_name))
+                })
+                .collect();
+
+            if !tools.is_empty() {
+                matched.push((tool_set, tools));
+            }
+        }
+
+        let (code, trimmed) = if matched.is_empty() {
+            (
+                "// No namespaces/functions match the request".to_string(),
+                None,
+            )
+        } else {
+            budget::fit(input.budget_tokens, |trim| {
+                let interface = interface(&matched, input.detail_level, trim);
+                if input.detail_level == DetailLevel::Name {
+                    interface
+                } else {
+                    pctx_codegen::format::format_d_ts(&interface)
+                }
+            })
+        };
+
+        let functions = matched
+            .iter()
+            .flat_map(|(s, tools)| tools.iter().map(move |t| (*s, *t)))
+            .map(|(tool_set, t)| FunctionDetails {
+                listed: ListedFunction {
+                    namespace: tool_set.tool_namespace(t),
+                    name: t.fn_name.clone(),
+                    description: t.description.clone(),
+                    size: InterfaceSize::of(&declaration(tool_set, t, input.detail_level, trimmed)),
+                },
+                input_type: t.input_signature.clone(),
+                output_type: t.output_signature.clone(),
+                types: t.types.clone(),
+            })
+            .collect();
+
+        GetFunctionDetailsOutput {
+            size: InterfaceSize::of(&code),
+            code,
+            functions,
+            trimmed,
+        }
+    }
+
+    /// Finds the `limit` functions most relevant to the query, most relevant first.
+    ///
+    /// Uses the semantic index when an embedder is set (see [`CodeMode::set_embedder`]),
+    /// falling back to keyword search over function names, descriptions and parameter names.
+    pub fn find_tools(&self, query: &str, limit: usize) -> Vec<SearchedFunction> {
+        self.rank_tools(query, limit)
+            .into_iter()
+            .map(|(score, tool_set, tool)| searched_function(score, tool_set, tool))
+            .collect()
+    }
+
+    fn rank_tools(&self, query: &str, limit: usize) -> Vec<(f64, &ToolSet, &Tool)> {
+        if let Some(index) = &self.tool_index {
+            match index.find(query, limit) {
+                Ok(found) if !found.is_empty() => {
+                    return found
+                        .into_iter()
+                        .filter_map(|(set_name, tool_name, score)| {
+                            let tool_set = self.tool_sets.iter().find(|s| s.name == set_name)?;
+                            let tool = tool_set.tools.iter().find(|t| t.name == tool_name)?;
+                            Some((score, tool_set, tool))
+                        })
+                        .collect();
+                }
+                Ok(_) => {
+                    debug!("No semantic matches for `{query}`, falling back to keyword search")
+                }
+                Err(e) => warn!("Semantic tool search failed, falling back to keyword search: {e}"),
+            }
+        }
+
+        let terms = search::terms(query);
+        let mut matches: Vec<(f64, &ToolSet, &Tool)> = self
+            .tool_sets
+            .iter()
+            .flat_map(|s| s.visible_tools().map(move |t| (s, t)))
+            .filter_map(|(s, t)| {
+                let score = search::score_tool(&terms, s, t);
+                (score > 0.0).then_some((score, s, t))
+            })
+            .collect();
+        // stable sort keeps registration order for equal scores
+        matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+        matches.truncate(limit);
+
+        matches
+    }
+
+    /// Searches for the top `limit` functions relevant to the query (see [`CodeMode::find_tools`]),
+    /// returning them with minimal code interfaces
+    pub fn search_functions(&self, input: SearchFunctionsInput) -> SearchFunctionsOutput {
+        let matches = self.rank_tools(&input.query, input.limit);
+
+        // group matched tools by namespace, ordered by best match
+        let mut grouped: Vec<(&ToolSet, Vec<&Tool>)> = vec![];
+        for (_, tool_set, tool) in &matches {
+            if let Some((_, tools)) = grouped.iter_mut().find(|(s, _)| s.name == tool_set.name) {
+                tools.push(*tool);
+            } else {
+                grouped.push((*tool_set, vec![*tool]));
+            }
+        }
+
+        let code = if grouped.is_empty() {
+            "// No functions match the query".to_string()
+        } else {
+            let namespaces: Vec<String> = grouped
+                .iter()
+                .map(|(s, tools)| s.namespace_with(tools, |t| t.fn_signature(false)))
+                .collect();
+            pctx_codegen::format::format_d_ts(&namespaces.join("\n\n"))
+        };
+
+        let functions = matches
+            .into_iter()
+            .map(|(score, tool_set, tool)| searched_function(score, tool_set, tool))
+            .collect();
+
+        SearchFunctionsOutput { functions, code }
+    }
+
+    /// Type checks code against the registered functions without executing it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the type checker fails internally, type errors are
+    /// reported in the output's diagnostics
+    #[instrument(skip(self), ret(Display), err)]
+    pub async fn check(&self, code: &str) -> Result<CheckCodeOutput> {
+        let check_res = pctx_executor::check(
+            &self.script(code, None),
+            &self.type_check,
+            Some(&self.check_cache),
+        )
+        .await?;
+
+        debug!(
+            success = check_res.success,
+            diagnostics = check_res.diagnostics.len(),
+            "Type checked code"
+        );
+
+        Ok(CheckCodeOutput {
+            success: check_res.success,
+            diagnostics: check_res.diagnostics.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Full script to execute: the code, the namespaces and the call of `run`, whose
+    /// awaited return value becomes the output unless the code exports its own default
+    fn script(&self, code: &str, input: Option<&serde_json::Value>) -> String {
+        let namespaces: Vec<String> = self
+            .tool_sets
+            .iter()
+            .filter_map(|s| {
+                if s.tools.is_empty() {
+                    None
+                } else {
+                    let tools: Vec<&Tool> = s.tools.iter().collect();
+                    Some(s.namespace_with(&tools, |t| self.fn_impl(s, t)))
+                }
+            })
+            .collect();
+
+        // Put LLM code at the top so diagnostic line numbers match, then namespaces below
+        format!(
+            "{code}\n\n{namespaces}\n\n{STD_DECLARATIONS}\n{entrypoint}",
+            namespaces = namespaces.join("\n\n"),
+            entrypoint = run_entrypoint(code, input),
+        )
+    }
+
+    /// Implementation of the tool's function, skills inline their code
+    fn fn_impl(&self, tool_set: &ToolSet, tool: &Tool) -> String {
+        let skill = self
+            .skills
+            .iter()
+            .find(|s| tool_set.name == SKILLS_TOOL_SET && s.name == tool.name);
+        match skill {
+            // `run` may not declare the input, the output is typed by the signature
+            Some(skill) => format!(
+                "{fn_sig} {{\n{code}\n  return await (run as (input: any) => any)(input);\n}}",
+                fn_sig = tool.fn_signature(true),
+                code = skill.code,
+            ),
+            None => tool.fn_impl(&tool_set.name),
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        code: &str,
+        callback_registry: Option<CallbackRegistry>,
+    ) -> Result<ExecuteOutput> {
+        let input = ExecuteInput {
+            code: code.to_string(),
+            ..Default::default()
+        };
+        self.run(&input, callback_registry, None, None).await
+    }
+
+    /// Executes code without type checking it first, saving the type checking latency
+    ///
+    /// Type errors surface as runtime errors, the output is marked as `unchecked`.
+    pub async fn execute_unchecked(
+        &self,
+        code: &str,
+        callback_registry: Option<CallbackRegistry>,
+    ) -> Result<ExecuteOutput> {
+        let input = ExecuteInput {
+            code: code.to_string(),
+            skip_type_check: true,
+            ..Default::default()
+        };
+        self.run(&input, callback_registry, None, None).await
+    }
+
+    /// Executes the code of an [`ExecuteInput`], passing its `input` as the argument of
+    /// `run(input)`, skipping type checking if requested & selecting the parts of the
+    /// output matching its `projection`
+    pub async fn execute_input(
+        &self,
+        input: &ExecuteInput,
+        callback_registry: Option<CallbackRegistry>,
+    ) -> Result<ExecuteOutput> {
+        self.run(input, callback_registry, None, None).await
+    }
+
+    /// Executes code, passing failed attempts to `repair` for fixed code until an attempt
+    /// succeeds, `max_attempts` were made or `repair` gives up by returning `None`
+    ///
+    /// `repair` receives the failed attempt, whose [`RepairAttempt::feedback`] has the type
+    /// errors or the runtime error of the code, and is typically an LLM call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an execution fails to run, failing code is recorded as an attempt
+    pub async fn execute_with_repair<F, Fut>(
+        &self,
+        code: &str,
+        max_attempts: usize,
+        mut repair: F,
+        callback_registry: Option<CallbackRegistry>,
+    ) -> Result<RepairHistory>
+    where
+        F: FnMut(RepairAttempt) -> Fut,
+        Fut: Future<Output = Option<String>>,
+    {
+        let mut attempts = vec![];
+        let mut code = code.to_string();
+        loop {
+            let output = self.execute(&code, callback_registry.clone()).await?;
+            let attempt = RepairAttempt { code, output };
+            if attempt.output.success || attempts.len() + 1 >= max_attempts {
+                attempts.push(attempt);
+                break;
+            }
+
+            debug!(
+                attempt = attempts.len() + 1,
+                diagnostics = attempt.output.diagnostics.len(),
+                "Repairing failed execution"
+            );
+            let repaired = repair(attempt.clone()).await;
+            attempts.push(attempt);
+            match repaired {
+                Some(repaired) => code = repaired,
+                None => break,
+            }
+        }
+        Ok(RepairHistory { attempts })
+    }
+
+    /// Executes the saved script with `input` as the argument of `run(input)`, without
+    /// type checking it again
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no script with the name was added or the execution fails
+    pub async fn execute_script(
+        &self,
+        name: &str,
+        input: Option<serde_json::Value>,
+        callback_registry: Option<CallbackRegistry>,
+    ) -> Result<ExecuteOutput> {
+        let script = self
+            .scripts
+            .iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| Error::Message(format!("Script `{name}` not found")))?;
+        let input = ExecuteInput {
+            code: script.code.clone(),
+            input,
+            skip_type_check: true,
+            ..Default::default()
+        };
+        let mut output = self.execute_input(&input, callback_registry).await?;
+        // the script was type checked when it was added
+        output.unchecked = false;
+        Ok(output)
+    }
+
+    /// Executes the code of an [`ExecuteInput`] like [`CodeMode::execute_input`],
+    /// recording its MCP tool calls & callback invocations into a replayable bundle
+    ///
+    /// The execution is deterministic so replays produce the same output, with the
+    /// current time frozen if no [`Determinism`] is set.
+    pub async fn record(
+        &self,
+        input: &ExecuteInput,
+        callback_registry: Option<CallbackRegistry>,
+    ) -> Result<ExecutionBundle> {
+        let tape = CallTape::recording();
+        let determinism = self.determinism.unwrap_or_else(Determinism::from_clock);
+        let output = self
+            .run(
+                input,
+                callback_registry,
+                Some(tape.clone()),
+                Some(determinism),
+            )
+            .await?;
+
+        Ok(ExecutionBundle {
+            input: input.clone(),
+            calls: tape.calls(),
+            determinism: Some(determinism),
+            output,
+        })
+    }
+
+    /// Re-runs a recorded execution, answering its MCP tool calls & callback invocations
+    /// with the recorded results instead of calling the tools
+    ///
+    /// Calls that were not recorded fail, e.g. when code branches differently.
+    pub async fn replay(&self, bundle: &ExecutionBundle) -> Result<ExecuteOutput> {
+        self.run(
+            &bundle.input,
+            None,
+            Some(CallTape::replaying(bundle.calls.clone())),
+            bundle.determinism,
+        )
+        .await
+    }
+
+    #[instrument(skip(self, callback_registry, tape, determinism), ret(Display), err)]
+    async fn run(
+        &self,
+        input: &ExecuteInput,
+        callback_registry: Option<CallbackRegistry>,
+        tape: Option<CallTape>,
+        determinism: Option<Determinism>,
+    ) -> Result<ExecuteOutput> {
+        let code = &input.code;
+        let projection = input
+            .projection
+            .as_deref()
+            .map(JsonPath::parse)
+            .transpose()?;
+        let registry = callback_registry.unwrap_or_default();
+        // Format for logging only
+        let formatted_code = pctx_codegen::format::format_ts(code);
+
+        debug!(
+            code_from_llm = %code,
+            formatted_code = %formatted_code,
+            code_length = code.len(),
+            callbacks =? registry.ids(),
+            "Received code to execute"
+        );
+
+        // confirm all configured callbacks in the CodeMode interface have
+        // registered callback functions
+        let missing_ids: Vec<String> = self
+            .callbacks
+            .iter()
+            .filter_map(|c| {
+                if registry.has(&c.id()) {
+                    None
+                } else {
+                    Some(c.id())
+                }
+            })
+            .collect();
+        // replayed callbacks are answered from the tape
+        let replaying = tape.as_ref().is_some_and(CallTape::is_replaying);
+        if !missing_ids.is_empty() && !replaying {
+            return Err(Error::Message(format!(
+                "Missing configured callbacks in registry with ids: {missing_ids:?}"
+            )));
+        }
+
+        // generate the full script to be executed
+        let to_execute = self.script(code, input.input.as_ref());
+
+        debug!(to_execute = %to_execute, "Executing code in sandbox");
+
+        let mut options = pctx_executor::ExecuteOptions::new()
+            .with_allowed_hosts(self.allowed_hosts().into_iter().collect())
+            .with_servers(self.servers.clone())
+            .with_callbacks(registry)
+            .with_tool_cache(self.tool_cache.clone())
+            .with_circuit_breakers(self.circuit_breakers.clone())
+            .with_rate_limiters(self.rate_limiters.clone())
+            .with_stdio_supervisor(self.stdio_supervisor.clone())
+            .with_output_schemas(self.output_schemas.clone())
+            .with_client_handlers(self.client_handlers.clone())
+            .with_type_check(self.type_check.clone())
+            .with_skip_type_check(input.skip_type_check)
+            .with_check_cache(self.check_cache.clone())
+            .with_imports(self.imports.clone())
+            .with_wasm_modules(self.wasm_modules.clone())
+            .with_isolation(self.isolation.clone())
+            .with_ids(self.execution_ids.clone());
+        if let Some(workers) = &self.remote_workers {
+            options = options.with_remote_workers(workers.clone());
+        }
+        if let Some(fs) = &self.fs {
+            options = options.with_fs(fs.clone());
+        }
+        if let Some(timeout) = self.execution_timeout {
+            options = options.with_timeout(timeout);
+        }
+        if let Some(max) = self.max_tool_calls {
+            options = options.with_max_tool_calls(max);
+        }
+        if let Some(tape) = tape {
+            options = options.with_tape(tape);
+        }
+        if let Some(determinism) = determinism.or(self.determinism) {
+            options = options.with_determinism(determinism);
+        }
+        // registered tools, artifacts & the key-value store are kept in this process only
+        let registrations = (self.remote_workers.is_none()
+            && matches!(self.isolation, Isolation::InProcess))
+        .then(ToolRegistrations::new);
+        if let Some(registrations) = &registrations {
+            options = options
+                .with_tool_registrations(registrations.clone())
+                .with_artifacts(self.artifacts.clone())
+                .with_results(self.results.clone());
+            if let Some(kv) = &self.kv {
+                options = options.with_kv(kv.clone());
+            }
+        }
+
+        let execution_res = pctx_executor::execute(&to_execute, options).await?;
+        self.store_output_schemas();
 
         if execution_res.success {
-            debug!("Sandbox execution completed successfully");
+            // the executed code & input are logged to promote executions to skills
+            debug!(
+                executed_code = %code,
+                executed_input = %json!(input.input),
+                "Sandbox execution completed successfully"
+            );
         } else {
             warn!("Sandbox execution failed: {:?}", execution_res.stderr);
         }
 
+        // the projection is applied first, so only projections that are still large are offloaded
+        let output = match (execution_res.output, projection) {
+            (Some(output), Some(projection)) => Some(projection.query(&output)),
+            (output, _) => output,
+        };
         Ok(ExecuteOutput {
             success: execution_res.success,
             stdout: execution_res.stdout,
             stderr: execution_res.stderr,
-            output: execution_res.output,
+            // outputs are offloaded where later executions can fetch them
+            output: match output {
+                Some(output) if registrations.is_some() => Some(self.results.offload(output)),
+                output => output,
+            },
+            unchecked: !execution_res.type_checked,
+            diagnostics: execution_res
+                .diagnostics
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            registered_tools: registrations
+                .map(|r| r.tools().into_iter().map(CallbackConfig::from).collect())
+                .unwrap_or_default(),
+            steps: execution_res.steps,
         })
     }
 }
+
+/// Calls `run` with the JSON input and default exports its awaited return value.
+///
+/// Code exporting its own default is left as is, code without a `run` function outputs
+/// `undefined`.
+fn run_entrypoint(code: &str, input: Option<&serde_json::Value>) -> String {
+    if has_default_export(code) {
+        return String::new();
+    }
+
+    let args = input.map_or_else(String::new, |input| {
+        // parsing keeps the input `any` typed, the string literal is valid JS
+        let json = serde_json::to_string(input).unwrap_or_else(|_| "null".into());
+        format!(
+            "JSON.parse({})",
+            serde_json::to_string(&json).unwrap_or_else(|_| "\"null\"".into())
+        )
+    });
+    format!("export default typeof run === \"function\" ? await run({args}) : undefined;\n")
+}
+
+fn has_default_export(code: &str) -> bool {
+    code.lines()
+        .any(|line| line.trim_start().starts_with("export default"))
+}
+
+/// Callback tool generated from the JSON schemas of its input & output
+fn callback_tool(
+    name: &str,
+    description: Option<String>,
+    input_schema: Option<&serde_json::Value>,
+    output_schema: Option<&serde_json::Value>,
+) -> Result<Tool> {
+    let input_schema = if let Some(i) = input_schema {
+        serde_json::from_value::<pctx_codegen::RootSchema>(json!(i)).map_err(|e| {
+            Error::Message(format!(
+                "Failed parsing inputSchema as json schema for tool `{name}`: {e}"
+            ))
+        })?
+    } else {
+        // TODO: better empty input schema support
+        serde_json::from_value::<pctx_codegen::RootSchema>(json!({})).unwrap()
+    };
+    let output_schema = if let Some(o) = output_schema {
+        Some(
+            serde_json::from_value::<pctx_codegen::RootSchema>(json!(o)).map_err(|e| {
+                Error::Message(format!(
+                    "Failed parsing outputSchema as json schema for tool `{name}`: {e}"
+                ))
+            })?,
+        )
+    } else {
+        None
+    };
+    Ok(Tool::new_callback(
+        name,
+        description,
+        input_schema,
+        output_schema,
+    )?)
+}
+
+/// Applies the server's namespace, case conversion, grouping rule & tool annotations
+/// to the tools it lists
+fn configure_tools(server: &ServerConfig, mut tool_set: ToolSet) -> Result<ToolSet> {
+    if let Some(namespace) = &server.namespace {
+        tool_set = tool_set.with_namespace(namespace);
+    }
+
+    let case = match server.case {
+        Some(NameCase::Preserve) => CasePolicy::Preserve,
+        Some(NameCase::SnakeCase) => CasePolicy::Snake,
+        Some(NameCase::CamelCase) | None => CasePolicy::Camel,
+    };
+    let mut tools = vec![];
+    for tool in tool_set.tools {
+        let mut tool = tool.with_case(case)?;
+        if let Some((category, fn_name)) = server.tool_category(&tool.name) {
+            tool = tool.with_category(&category, &fn_name);
+        }
+        if let Some(config) = server.tools.get(&tool.name) {
+            if let Some(reason) = config.deprecated.as_ref().and_then(|d| d.reason()) {
+                tool = tool.with_deprecated(reason);
+            }
+            tool = tool.with_hidden(config.hidden);
+        }
+        tools.push(tool);
+    }
+    tool_set.tools = tools;
+
+    Ok(tool_set)
+}
+
+/// Declarations of the tools at the detail level, `Namespace.fnName` lines for
+/// [`DetailLevel::Name`], otherwise namespaces of TypeScript declarations
+fn interface(
+    tool_sets: &[(&ToolSet, Vec<&Tool>)],
+    detail_level: DetailLevel,
+    trim: Option<InterfaceTrim>,
+) -> String {
+    let separator = if detail_level == DetailLevel::Name {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    tool_sets
+        .iter()
+        .map(|(tool_set, tools)| match detail_level {
+            DetailLevel::Name => tools
+                .iter()
+                .map(|t| declaration(tool_set, t, detail_level, trim))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            DetailLevel::Signature | DetailLevel::Full => {
+                tool_set.namespace_with(tools, |t| declaration(tool_set, t, detail_level, trim))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+/// Declaration of a single tool at the detail level
+fn declaration(
+    tool_set: &ToolSet,
+    tool: &Tool,
+    detail_level: DetailLevel,
+    trim: Option<InterfaceTrim>,
+) -> String {
+    match detail_level {
+        DetailLevel::Name => format!("{}.{}", tool_set.tool_namespace(tool), tool.fn_name),
+        DetailLevel::Signature => budget::trim_tool(tool, trim).fn_signature(false),
+        DetailLevel::Full => budget::trim_tool(tool, trim).fn_signature(true),
+    }
+}
+
+fn searched_function(score: f64, tool_set: &ToolSet, tool: &Tool) -> SearchedFunction {
+    SearchedFunction {
+        listed: ListedFunction {
+            namespace: tool_set.tool_namespace(tool),
+            name: tool.fn_name.clone(),
+            description: tool.description.clone(),
+            size: InterfaceSize::of(&tool.fn_signature(false)),
+        },
+        score,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use pctx_codegen::{Tool, ToolSet};
+    use pctx_config::{prompt::PromptConfig, server::ServerConfig};
+    use serde_json::json;
+
+    use super::CodeMode;
+    use crate::{
+        catalog::CatalogFormat,
+        model::{
+            CallbackConfig, DetailLevel, ExecuteInput, FunctionId, GetFunctionDetailsInput,
+            InterfaceSize, InterfaceTrim, ListFunctionsInput, Skill,
+        },
+        schema_cache::SchemaCache,
+        tool_specs::ToolSpecFormat,
+    };
+
+    fn callback(namespace: &str, name: &str, description: &str) -> CallbackConfig {
+        CallbackConfig {
+            name: name.into(),
+            namespace: namespace.into(),
+            description: Some(description.into()),
+            input_schema: Some(json!({ "type": "object", "properties": {} })),
+            output_schema: None,
+        }
+    }
+
+    #[test]
+    fn test_update_and_remove_callbacks() {
+        let mut code_mode = CodeMode::default()
+            .with_callbacks(&[
+                callback("Users", "getUser", "Gets a user"),
+                callback("Users", "listUsers", "Lists users"),
+            ])
+            .unwrap();
+
+        code_mode
+            .update_callback(&callback("Users", "getUser", "Gets a user by id"))
+            .unwrap();
+        assert_eq!(code_mode.callbacks().len(), 2);
+        let tools = &code_mode.tool_sets()[0].tools;
+        assert!(
+            tools
+                .iter()
+                .any(|t| t.description.as_deref() == Some("Gets a user by id"))
+        );
+
+        // invalid updates leave the callback unchanged
+        let mut invalid = callback("Users", "getUser", "Invalid");
+        invalid.input_schema = Some(json!("not a schema"));
+        assert!(code_mode.update_callback(&invalid).is_err());
+        assert_eq!(code_mode.callbacks().len(), 2);
+        assert_eq!(code_mode.tool_sets()[0].tools.len(), 2);
+
+        code_mode.remove_callback("Users.getUser").unwrap();
+        code_mode.remove_callback("Users.listUsers").unwrap();
+        assert!(code_mode.callbacks().is_empty());
+        assert!(code_mode.tool_sets().is_empty());
+        assert!(code_mode.remove_callback("Users.getUser").is_err());
+    }
+
+    #[test]
+    fn test_list_functions_budget() {
+        let details = format!("Gets a user. {}", "It has many details. ".repeat(50));
+        let code_mode = CodeMode::default()
+            .with_callbacks(&[callback("Users", "getUser", &details)])
+            .unwrap();
+
+        let full = code_mode.list_functions();
+        assert!(full.trimmed.is_none());
+        assert_eq!(full.size, InterfaceSize::of(&full.code));
+        assert!(full.functions[0].size.tokens > 250);
+
+        let listed = code_mode.list_functions_with(&ListFunctionsInput {
+            budget_tokens: Some(full.size.tokens - 100),
+            ..Default::default()
+        });
+        assert_eq!(listed.trimmed, Some(InterfaceTrim::Descriptions));
+        assert!(listed.code.contains("Gets a user."));
+        assert!(!listed.code.contains("many details"));
+        assert!(listed.size.tokens <= full.size.tokens - 100);
+
+        let listed = code_mode.list_functions_with(&ListFunctionsInput {
+            budget_tokens: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(listed.trimmed, Some(InterfaceTrim::Signatures));
+        assert!(!listed.code.contains("Gets a user"));
+        assert!(listed.code.contains("getUser"));
+        // descriptions stay available in the structured output
+        assert_eq!(listed.functions[0].description.as_deref(), Some(&*details));
+    }
+
+    #[test]
+    fn test_detail_levels() {
+        let mut user = callback("Users", "getUser", "Gets a user");
+        user.input_schema = Some(json!({
+            "type": "object",
+            "properties": { "id": { "type": "string" } },
+            "required": ["id"]
+        }));
+        let code_mode = CodeMode::default().with_callbacks(&[user]).unwrap();
+        let list = |detail_level| {
+            code_mode
+                .list_functions_with(&ListFunctionsInput {
+                    detail_level,
+                    ..Default::default()
+                })
+                .code
+        };
+
+        assert_eq!(list(DetailLevel::Name), "Users.getUser");
+        let signature = list(DetailLevel::Signature);
+        assert_eq!(signature, code_mode.list_functions().code);
+        assert!(signature.contains("Gets a user"));
+        let full = list(DetailLevel::Full);
+        assert!(full.len() > signature.len());
+        assert!(full.contains("id: string"), "{full}");
+
+        let details = |detail_level| {
+            code_mode.get_function_details(GetFunctionDetailsInput {
+                functions: vec![FunctionId {
+                    mod_name: "Users".into(),
+                    fn_name: "getUser".into(),
+                }],
+                detail_level,
+                budget_tokens: None,
+            })
+        };
+        let name = details(DetailLevel::Name);
+        assert_eq!(name.code, "Users.getUser");
+        assert_eq!(
+            name.functions[0].listed.size,
+            InterfaceSize::of("Users.getUser")
+        );
+        assert!(details(DetailLevel::Full).code.contains("id: string"));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("code_mode.json");
+        let code_mode = CodeMode::default()
+            .with_callback(&callback("Users", "getUser", "Gets a user"))
+            .unwrap();
+        code_mode.save_snapshot(&path).unwrap();
+
+        let restored = CodeMode::load_snapshot(&path).unwrap();
+        assert_eq!(restored.callbacks().len(), 1);
+        assert_eq!(
+            restored.list_functions().code,
+            code_mode.list_functions().code
+        );
+
+        std::fs::write(&path, r#"{ "version": 0, "code_mode": {} }"#).unwrap();
+        assert!(CodeMode::load_snapshot(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_servers_load_from_schema_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SchemaCache::new(dir.path());
+        // the command doesn't exist, so the server's tools can only come from its snapshot
+        let server = ServerConfig::new_stdio(
+            "docker".into(),
+            "pctx-test-missing-command".into(),
+            vec![],
+            BTreeMap::new(),
+        );
+        let tool = Tool::new_mcp(
+            "ps",
+            Some("Lists containers".into()),
+            serde_json::from_value(json!({ "type": "object", "properties": {} })).unwrap(),
+            None,
+        )
+        .unwrap();
+        cache
+            .store(&server, &ToolSet::new("docker", "Docker tools", vec![tool]))
+            .unwrap();
+
+        let code_mode = CodeMode::default()
+            .with_schema_cache(cache.clone())
+            .with_servers([&server], 5)
+            .await
+            .unwrap();
+        let functions = code_mode.list_functions().functions;
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "ps");
+        assert_eq!(
+            functions[0].description.as_deref(),
+            Some("Lists containers")
+        );
+
+        // refreshing connects to the server instead of using its snapshot
+        let refreshed = CodeMode::default()
+            .with_schema_cache(cache.with_refresh(true))
+            .with_servers([&server], 5)
+            .await;
+        assert!(refreshed.is_err());
+    }
+
+    #[test]
+    fn test_emit_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let code_mode = CodeMode::default()
+            .with_callback(&callback("Users", "getUser", "Gets a user"))
+            .unwrap();
+
+        let files = code_mode.emit_types(dir.path()).unwrap();
+        assert_eq!(
+            files,
+            vec![dir.path().join("pctx.d.ts"), dir.path().join("Users.d.ts")]
+        );
+        let users = std::fs::read_to_string(dir.path().join("Users.d.ts")).unwrap();
+        assert!(users.contains("namespace Users"));
+        assert!(users.contains("getUser"));
+    }
+
+    #[test]
+    fn test_export_catalog() {
+        let code_mode = CodeMode::default()
+            .with_callbacks(&[
+                callback("Users", "getUser", "Gets a user"),
+                callback("Users", "listUsers", "Lists <all> users"),
+            ])
+            .unwrap();
+
+        let markdown = code_mode.export_catalog(CatalogFormat::Markdown);
+        assert!(markdown.starts_with("# Tool Catalog\n\n- [Users](#users) (2 functions)"));
+        assert!(markdown.contains("### `Users.getUser`\n\nGets a user\n"));
+        assert!(markdown.contains("const result = await Users.listUsers({});"));
+
+        let html = code_mode.export_catalog(CatalogFormat::Html);
+        assert!(html.contains("<h2 id=\"users\">Users</h2>"));
+        assert!(html.contains("<p>Lists &lt;all&gt; users</p>"));
+    }
+
+    #[test]
+    fn test_system_prompt() {
+        let code_mode = CodeMode::default()
+            .with_callbacks(&[
+                callback("Users", "getUser", "Gets a user"),
+                callback("Users", "listUsers", "Lists users"),
+            ])
+            .unwrap();
+
+        let prompt = code_mode.system_prompt(&PromptConfig::default());
+        assert!(prompt.contains("## Available Namespaces\n\n- `Users` (2 functions)"));
+        assert!(prompt.contains("`async function run()`"));
+        assert!(prompt.contains("`ValidationError`"));
+        assert!(!prompt.contains("Additional Instructions"));
+
+        let prompt = code_mode.system_prompt(&PromptConfig {
+            template: Some("Namespaces:\n{{namespaces}}\n\n{{instructions}}".into()),
+            instructions: vec!["Never delete users".into()],
+        });
+        assert_eq!(
+            prompt,
+            "Namespaces:\n- `Users` (2 functions)\n\n## Additional Instructions\n\n- Never delete users\n"
+        );
+    }
+
+    #[test]
+    fn test_export_tool_specs() {
+        let code_mode = CodeMode::default()
+            .with_callback(&callback("Users", "getUser", "Gets a user"))
+            .unwrap();
+
+        let meta = code_mode.export_tool_specs(ToolSpecFormat::Anthropic, false);
+        assert_eq!(meta.len(), 3);
+
+        let all = code_mode.export_tool_specs(ToolSpecFormat::OpenAi, true);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[3]["function"]["name"], "Users_getUser");
+        assert_eq!(all[3]["function"]["description"], "Gets a user");
+        assert_eq!(all[3]["function"]["parameters"]["type"], "object");
+    }
+
+    #[tokio::test]
+    async fn test_promote_execution() {
+        let mut code_mode = CodeMode::default();
+        let input = ExecuteInput {
+            code: "async function run(input) { return { total: input.items.reduce((a, b) => a + b, 0) }; }".into(),
+            input: Some(json!({ "items": [1, 2] })),
+            ..Default::default()
+        };
+        let output = code_mode.execute_input(&input, None).await.unwrap();
+        assert!(output.success, "{}", output.stderr);
+
+        let skill = code_mode
+            .promote_execution("sumItems", Some("Sums the items"), &input, &output)
+            .await
+            .unwrap();
+        assert_eq!(
+            skill.input_schema.unwrap()["properties"]["items"]["type"],
+            json!("array")
+        );
+        assert!(code_mode.list_functions().code.contains("namespace Skills"));
+
+        let output = code_mode
+            .execute(
+                "async function run() { const { total } = await Skills.sumItems({ items: [3, 4] }); return total; }",
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(output.output, Some(json!(7)), "{}", output.stderr);
+
+        // invalid skills leave the skills unchanged
+        let invalid = |code: &str| Skill {
+            name: "sumItems".into(),
+            description: None,
+            code: code.into(),
+            input_schema: None,
+            output_schema: None,
+        };
+        assert!(
+            code_mode
+                .add_skill(invalid(
+                    "async function run() { const n: number = \"one\"; return n; }"
+                ))
+                .await
+                .is_err()
+        );
+        assert!(
+            code_mode
+                .add_skill(invalid("import x from \"npm:x\";\nasync function run() {}"))
+                .await
+                .is_err()
+        );
+        assert_eq!(code_mode.skills().len(), 1);
+        assert_eq!(
+            code_mode.skills()[0].description.as_deref(),
+            Some("Sums the items")
+        );
+
+        let mut failed = output.clone();
+        failed.success = false;
+        assert!(
+            code_mode
+                .promote_execution("other", None, &input, &failed)
+                .await
+                .is_err()
+        );
+
+        code_mode.remove_skill("sumItems").unwrap();
+        assert!(code_mode.tool_sets().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_repair() {
+        let code_mode = CodeMode::default();
+        let mut feedback = vec![];
+        let history = code_mode
+            .execute_with_repair(
+                "async function run() { const n: number = \"one\"; return n; }",
+                3,
+                |attempt| {
+                    feedback.push(attempt.feedback());
+                    let code = if feedback.len() == 1 {
+                        "async function run() { throw new Error(\"still broken\"); }"
+                    } else {
+                        "async function run() { return 1; }"
+                    };
+                    async move { Some(code.to_string()) }
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(history.success());
+        assert_eq!(history.attempts.len(), 3);
+        assert_eq!(history.last().unwrap().output.output, Some(json!(1)));
+        assert!(feedback[0].contains("type checking"), "{}", feedback[0]);
+        assert!(feedback[1].contains("still broken"), "{}", feedback[1]);
+
+        // the repair function gives up
+        let history = code_mode
+            .execute_with_repair(
+                "async function run() { throw new Error(\"broken\"); }",
+                3,
+                |_| async { None },
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!history.success());
+        assert_eq!(history.attempts.len(), 1);
+    }
+}
@@ -0,0 +1,252 @@
+//! Detection of namespaces & function names produced by more than one registration,
+//! resolved with the configured [`CollisionStrategy`] instead of shadowing each other.
+
+use std::collections::HashMap;
+
+use pctx_codegen::{Tool, ToolSet, case::Case};
+use pctx_config::collisions::{CollisionConfig, CollisionStrategy};
+
+use crate::{Error, Result};
+
+/// Resolves collisions of a tool set's namespace & functions with the registered tool sets
+pub(crate) fn resolve_tool_set(
+    registered: &[ToolSet],
+    mut tool_set: ToolSet,
+    config: &CollisionConfig,
+) -> Result<ToolSet> {
+    if let Some(other) = registered
+        .iter()
+        .find(|s| s.namespace == tool_set.namespace)
+    {
+        let collision = format!(
+            "Namespace `{}` of `{}` collides with `{}`",
+            &tool_set.namespace, &tool_set.name, &other.name
+        );
+        let namespace = match config.strategy {
+            CollisionStrategy::Error => None,
+            CollisionStrategy::Suffix => {
+                Some(format!("{}_{}", &tool_set.namespace, &tool_set.name))
+            }
+            CollisionStrategy::Rename => config.rename.get(&tool_set.name).cloned(),
+        };
+        let Some(namespace) = namespace else {
+            return Err(unresolved(&collision, config.strategy, &tool_set.name));
+        };
+
+        tool_set = tool_set.with_namespace(&namespace);
+        if registered.iter().any(|s| s.namespace == tool_set.namespace) {
+            return Err(Error::Message(format!(
+                "{collision}, renamed namespace `{}` collides as well",
+                &tool_set.namespace
+            )));
+        }
+    }
+
+    let mut taken = function_paths(registered);
+    for tool in &mut tool_set.tools {
+        resolve_function(
+            &mut taken,
+            &tool_set.name,
+            &tool_set.namespace,
+            tool,
+            config,
+        )?;
+    }
+
+    Ok(tool_set)
+}
+
+/// Resolves collisions of a tool about to be added to a registered tool set
+pub(crate) fn resolve_tool(
+    registered: &[ToolSet],
+    tool_set: &ToolSet,
+    mut tool: Tool,
+    config: &CollisionConfig,
+) -> Result<Tool> {
+    let mut taken = function_paths(registered);
+    resolve_function(
+        &mut taken,
+        &tool_set.name,
+        &tool_set.namespace,
+        &mut tool,
+        config,
+    )?;
+
+    Ok(tool)
+}
+
+fn resolve_function(
+    taken: &mut HashMap<String, String>,
+    set_name: &str,
+    namespace: &str,
+    tool: &mut Tool,
+    config: &CollisionConfig,
+) -> Result<()> {
+    let id = format!("{set_name}.{}", &tool.name);
+    let path = function_path(namespace, tool);
+
+    if let Some(other) = taken.get(&path) {
+        let collision = format!("Function `{path}` of `{id}` collides with `{other}`");
+        let fn_name = match config.strategy {
+            CollisionStrategy::Error => None,
+            CollisionStrategy::Suffix => Some(format!("{}_{set_name}", &tool.fn_name)),
+            CollisionStrategy::Rename => config.rename.get(&id).cloned(),
+        };
+        let Some(fn_name) = fn_name else {
+            return Err(unresolved(&collision, config.strategy, &id));
+        };
+
+        tool.fn_name = Case::Camel.sanitize(fn_name);
+        if taken.contains_key(&function_path(namespace, tool)) {
+            return Err(Error::Message(format!(
+                "{collision}, renamed function `{}` collides as well",
+                &tool.fn_name
+            )));
+        }
+    }
+
+    taken.insert(function_path(namespace, tool), id);
+    Ok(())
+}
+
+/// Full paths of all registered functions (e.g. `Google.Drive.listFiles`) mapped to
+/// the `<tool set>.<tool>` they call
+fn function_paths(registered: &[ToolSet]) -> HashMap<String, String> {
+    registered
+        .iter()
+        .flat_map(|s| {
+            s.tools.iter().map(|t| {
+                (
+                    function_path(&s.namespace, t),
+                    format!("{}.{}", &s.name, &t.name),
+                )
+            })
+        })
+        .collect()
+}
+
+fn function_path(namespace: &str, tool: &Tool) -> String {
+    match &tool.category {
+        Some(category) => format!("{namespace}.{category}.{}", &tool.fn_name),
+        None => format!("{namespace}.{}", &tool.fn_name),
+    }
+}
+
+fn unresolved(collision: &str, strategy: CollisionStrategy, key: &str) -> Error {
+    let hint = match strategy {
+        CollisionStrategy::Rename => format!("add a `{key}` entry to the collisions `rename` map"),
+        _ => "configure the `suffix` or `rename` collision strategy".into(),
+    };
+    Error::Message(format!("{collision}, {hint}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pctx_codegen::{Tool, ToolSet};
+    use pctx_config::collisions::{CollisionConfig, CollisionStrategy};
+    use serde_json::json;
+
+    use super::{resolve_tool, resolve_tool_set};
+
+    fn tool(name: &str) -> Tool {
+        Tool::new_mcp(
+            name,
+            None,
+            serde_json::from_value(json!({"type": "object", "properties": {}})).unwrap(),
+            None,
+        )
+        .unwrap()
+    }
+
+    fn config(strategy: CollisionStrategy, rename: &[(&str, &str)]) -> CollisionConfig {
+        CollisionConfig {
+            strategy,
+            rename: rename
+                .iter()
+                .map(|(k, v)| ((*k).into(), (*v).into()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_namespace_collisions() {
+        let registered = vec![ToolSet::new("my_api", "", vec![tool("get_data")])];
+        let colliding = || ToolSet::new("MyApi", "", vec![tool("list_items")]);
+
+        let err = resolve_tool_set(
+            &registered,
+            colliding(),
+            &config(CollisionStrategy::Error, &[]),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Namespace `MyApi` of `MyApi` collides with `my_api`")
+        );
+
+        let suffixed = resolve_tool_set(
+            &registered,
+            colliding(),
+            &config(CollisionStrategy::Suffix, &[]),
+        )
+        .unwrap();
+        assert_eq!(suffixed.namespace, "MyApiMyApi");
+
+        let renamed = resolve_tool_set(
+            &registered,
+            colliding(),
+            &config(CollisionStrategy::Rename, &[("MyApi", "Items")]),
+        )
+        .unwrap();
+        assert_eq!(renamed.namespace, "Items");
+
+        assert!(
+            resolve_tool_set(
+                &registered,
+                colliding(),
+                &config(CollisionStrategy::Rename, &[("other", "Items")]),
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_function_collisions() {
+        // `get_data` & `getData` both generate `getData`
+        let tool_set = || ToolSet::new("api", "", vec![tool("get_data"), tool("getData")]);
+
+        let err =
+            resolve_tool_set(&[], tool_set(), &config(CollisionStrategy::Error, &[])).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Function `Api.getData` of `api.getData` collides with `api.get_data`")
+        );
+
+        let suffixed =
+            resolve_tool_set(&[], tool_set(), &config(CollisionStrategy::Suffix, &[])).unwrap();
+        assert_eq!(suffixed.tools[0].fn_name, "getData");
+        assert_eq!(suffixed.tools[1].fn_name, "getDataApi");
+
+        let renamed = resolve_tool_set(
+            &[],
+            tool_set(),
+            &config(CollisionStrategy::Rename, &[("api.getData", "fetch_data")]),
+        )
+        .unwrap();
+        assert_eq!(renamed.tools[1].fn_name, "fetchData");
+        // calls still dispatch on the original tool name
+        assert_eq!(renamed.tools[1].name, "getData");
+
+        // tools added to a registered tool set, e.g. callbacks
+        let registered = vec![ToolSet::new("api", "", vec![tool("get_data")])];
+        assert!(
+            resolve_tool(
+                &registered,
+                &registered[0],
+                tool("getData"),
+                &config(CollisionStrategy::Error, &[]),
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Embeddings-based tool discovery.
+//!
+//! A [`ToolIndex`] embeds a text document per registered tool (namespace, name, description
+//! and parameter names) with a pluggable [`Embedder`] and ranks tools by cosine similarity
+//! to a query. [`TfIdfEmbedder`] is a dependency-free local default; plug in a real
+//! embedding model to match synonyms like "make ticket" and "create issue".
+
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+use pctx_codegen::{Tool, ToolSet};
+
+use crate::{Result, search};
+
+/// Computes vector embeddings for tool documents and search queries.
+///
+/// Documents are embedded whenever the tools registered on a [`CodeMode`](crate::CodeMode)
+/// change, implementations calling remote APIs should cache embeddings accordingly.
+pub trait Embedder: Send + Sync + Debug {
+    /// Returns an embedder fitted to the tool documents of an index (e.g. with the
+    /// vocabulary of the documents), which embeds the documents & the queries searching
+    /// them. The index keeps the fitted embedder, so it never changes for a built index.
+    ///
+    /// Embedders without fitted state return `None` and embed with themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the embedder could not be fitted
+    fn fit(&self, _documents: &[String]) -> Result<Option<Arc<dyn Embedder>>> {
+        Ok(None)
+    }
+
+    /// Embeds the tool documents, returning one vector per document in the same order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the embeddings could not be computed
+    fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Embeds a search query into the same vector space as the documents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the embedding could not be computed
+    fn embed_query(&self, query: &str) -> Result<Vec<f32>>;
+}
+
+/// Local TF-IDF embedder, [fitting](Embedder::fit) the vocabulary to the tool documents.
+#[derive(Debug, Default)]
+pub struct TfIdfEmbedder {
+    vocabulary: Vocabulary,
+}
+
+#[derive(Debug, Default)]
+struct Vocabulary {
+    indices: HashMap<String, usize>,
+    idf: Vec<f32>,
+}
+
+impl Vocabulary {
+    fn fit(documents: &[Vec<String>]) -> Self {
+        let mut indices = HashMap::new();
+        let mut doc_freq: Vec<usize> = vec![];
+        for doc in documents {
+            let mut seen = vec![false; doc_freq.len()];
+            for term in doc {
+                let idx = *indices.entry(term.clone()).or_insert_with(|| {
+                    doc_freq.push(0);
+                    seen.push(false);
+                    doc_freq.len() - 1
+                });
+                if !seen[idx] {
+                    seen[idx] = true;
+                    doc_freq[idx] += 1;
+                }
+            }
+        }
+
+        // smoothed idf, matching the common sklearn formulation
+        let n = documents.len() as f32;
+        let idf = doc_freq
+            .iter()
+            .map(|df| ((1.0 + n) / (1.0 + *df as f32)).ln() + 1.0)
+            .collect();
+
+        Self { indices, idf }
+    }
+
+    fn vectorize(&self, terms: &[String]) -> Vec<f32> {
+        let mut counts: HashMap<usize, f32> = HashMap::new();
+        for term in terms {
+            if let Some(idx) = self.indices.get(term) {
+                *counts.entry(*idx).or_default() += 1.0;
+            }
+        }
+
+        let mut vector = vec![0.0; self.idf.len()];
+        for (idx, tf) in counts {
+            // sublinear term frequency
+            vector[idx] = (1.0 + tf.ln()) * self.idf[idx];
+        }
+        vector
+    }
+}
+
+impl TfIdfEmbedder {
+    fn terms(text: &str) -> Vec<String> {
+        search::terms(text)
+            .into_iter()
+            .map(|t| {
+                // naive plural folding so "issues" matches "issue"
+                if t.len() > 3 && t.ends_with('s') && !t.ends_with("ss") {
+                    t[..t.len() - 1].to_string()
+                } else {
+                    t
+                }
+            })
+            .collect()
+    }
+}
+
+impl Embedder for TfIdfEmbedder {
+    fn fit(&self, documents: &[String]) -> Result<Option<Arc<dyn Embedder>>> {
+        let tokenized: Vec<Vec<String>> = documents.iter().map(|d| Self::terms(d)).collect();
+        Ok(Some(Arc::new(Self {
+            vocabulary: Vocabulary::fit(&tokenized),
+        })))
+    }
+
+    fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(documents
+            .iter()
+            .map(|d| self.vocabulary.vectorize(&Self::terms(d)))
+            .collect())
+    }
+
+    fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
+        Ok(self.vocabulary.vectorize(&Self::terms(query)))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    tool_set: String,
+    tool: String,
+    vector: Vec<f32>,
+}
+
+/// Embeddings of all tools registered on a [`CodeMode`](crate::CodeMode)
+///
+/// The embedder fitted to the tools is part of the index, clones of an index are
+/// searched with the embedder they were built with.
+#[derive(Debug, Clone)]
+pub struct ToolIndex {
+    embedder: Arc<dyn Embedder>,
+    fitted: Option<Arc<dyn Embedder>>,
+    entries: Vec<IndexEntry>,
+}
+
+impl ToolIndex {
+    /// Creates an empty index that embeds with the provided embedder
+    pub fn new(embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            embedder,
+            fitted: None,
+            entries: vec![],
+        }
+    }
+
+    fn fitted_embedder(&self) -> &dyn Embedder {
+        self.fitted.as_deref().unwrap_or(self.embedder.as_ref())
+    }
+
+    /// Re-embeds all visible tools of the provided tool sets, replacing the current entries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the embedder fails or returns the wrong number of embeddings
+    pub fn rebuild(&mut self, tool_sets: &[ToolSet]) -> Result<()> {
+        let mut keys = vec![];
+        let mut documents = vec![];
+        for tool_set in tool_sets {
+            for tool in tool_set.visible_tools() {
+                keys.push((tool_set.name.clone(), tool.name.clone()));
+                documents.push(tool_document(tool_set, tool));
+            }
+        }
+
+        let fitted = self.embedder.fit(&documents)?;
+        let vectors = fitted
+            .as_deref()
+            .unwrap_or(self.embedder.as_ref())
+            .embed_documents(&documents)?;
+        if vectors.len() != documents.len() {
+            return Err(crate::Error::Message(format!(
+                "Embedder returned {} embeddings for {} tools",
+                vectors.len(),
+                documents.len()
+            )));
+        }
+
+        self.entries = keys
+            .into_iter()
+            .zip(vectors)
+            .map(|((tool_set, tool), vector)| IndexEntry {
+                tool_set,
+                tool,
+                vector,
+            })
+            .collect();
+        self.fitted = fitted;
+
+        Ok(())
+    }
+
+    /// Returns `(tool set name, tool name, similarity)` of the `limit` most similar
+    /// tools to the query, most similar first. Tools with no similarity are omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the embedder fails embedding the query, or embeds it with other
+    /// dimensions than the tools
+    pub fn find(&self, query: &str, limit: usize) -> Result<Vec<(&str, &str, f64)>> {
+        let query = self.fitted_embedder().embed_query(query)?;
+        if let Some(entry) = self.entries.iter().find(|e| e.vector.len() != query.len()) {
+            return Err(crate::Error::Message(format!(
+                "Embedder returned a query embedding of {} dimensions for tools of {} dimensions",
+                query.len(),
+                entry.vector.len()
+            )));
+        }
+
+        let mut scored: Vec<(&str, &str, f64)> = self
+            .entries
+            .iter()
+            .filter_map(|e| {
+                let similarity = cosine_similarity(&query, &e.vector);
+                (similarity > 0.0).then_some((e.tool_set.as_str(), e.tool.as_str(), similarity))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+        scored.truncate(limit);
+
+        Ok(scored)
+    }
+}
+
+/// Text representation of a tool used for embedding
+fn tool_document(tool_set: &ToolSet, tool: &Tool) -> String {
+    format!(
+        "{namespace} {name} {fn_name} {description} {params}",
+        namespace = &tool_set.name,
+        name = &tool.name,
+        fn_name = &tool.fn_name,
+        description = tool.description.as_deref().unwrap_or_default(),
+        params = search::param_names(tool).join(" "),
+    )
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    assert_eq!(a.len(), b.len(), "Embeddings of different dimensions");
+    let (mut dot, mut norm_a, mut norm_b) = (0.0_f64, 0.0_f64, 0.0_f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(*x), f64::from(*y));
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a.sqrt() * norm_b.sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use pctx_codegen::{Tool, ToolSet};
+    use serde_json::json;
+
+    use super::{TfIdfEmbedder, ToolIndex};
+
+    fn tool(name: &str, description: &str) -> Tool {
+        Tool::new_mcp(
+            name,
+            Some(description.into()),
+            serde_json::from_value(json!({"type": "object", "properties": {}})).unwrap(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tfidf_index_ranks_by_similarity() {
+        let tool_sets = vec![
+            ToolSet::new(
+                "github",
+                "",
+                vec![
+                    tool("create_issue", "Opens a new issue in a repository"),
+                    tool("list_pull_requests", "Lists pull requests of a repository"),
+                ],
+            ),
+            ToolSet::new(
+                "weather",
+                "",
+                vec![tool("get_forecast", "Weather forecast for a city")],
+            ),
+        ];
+
+        let mut index = ToolIndex::new(Arc::new(TfIdfEmbedder::default()));
+        index.rebuild(&tool_sets).unwrap();
+
+        let found = index.find("open new issues", 10).unwrap();
+        assert_eq!(found[0].0, "github");
+        assert_eq!(found[0].1, "create_issue");
+        assert!(found.iter().all(|(set, _, _)| *set != "weather"));
+
+        assert!(index.find("unrelated", 10).unwrap().is_empty());
+        assert_eq!(index.find("repository", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tfidf_index_clones_keep_their_vocabulary() {
+        let mut index = ToolIndex::new(Arc::new(TfIdfEmbedder::default()));
+        index
+            .rebuild(&[ToolSet::new(
+                "github",
+                "",
+                vec![
+                    tool("create_issue", "Opens a new issue in a repository"),
+                    tool("list_pull_requests", "Lists pull requests of a repository"),
+                ],
+            )])
+            .unwrap();
+
+        let mut other = index.clone();
+        other
+            .rebuild(&[ToolSet::new(
+                "weather",
+                "",
+                vec![tool("get_forecast", "Weather forecast for a city")],
+            )])
+            .unwrap();
+
+        let found = index.find("open new issues", 10).unwrap();
+        assert_eq!(found[0].1, "create_issue");
+        assert!(index.find("weather forecast", 10).unwrap().is_empty());
+
+        let found = other.find("weather forecast", 10).unwrap();
+        assert_eq!(found[0].1, "get_forecast");
+        assert!(other.find("open new issues", 10).unwrap().is_empty());
+    }
+}
@@ -72,22 +72,110 @@
 //! **Builder methods** (chainable):
 //! - [`CodeMode::with_server`] / [`CodeMode::with_servers`] - Add MCP servers
 //! - [`CodeMode::with_callback`] / [`CodeMode::with_callbacks`] - Add callback tools
+//! - [`CodeMode::with_embedder`] - Enable semantic tool discovery
+//! - [`CodeMode::with_schema_cache`] - Load servers from tool schema snapshots
+//! - [`CodeMode::with_type_check`] - Configure TypeScript type checking strictness
+//! - [`CodeMode::with_imports`] - Allow `npm:`, `jsr:` and `https:` module imports
+//! - [`CodeMode::with_collisions`] - Configure how namespace & function name collisions are resolved
+//! - [`CodeMode::with_sampling_handler`] - Handle sampling requests of upstream servers with a [`SamplingHandler`](pctx_config::client::SamplingHandler)
+//! - [`CodeMode::with_elicitation_handler`] - Handle elicitation requests of upstream servers with an [`ElicitationHandler`](pctx_config::client::ElicitationHandler)
+//! - [`CodeMode::with_progress_handler`] - Receive progress of upstream tool calls with a [`ProgressHandler`](pctx_config::client::ProgressHandler)
+//! - [`CodeMode::with_approval_handler`] - Approve calls of tools configured with `requires_approval` with an [`ApprovalHandler`](pctx_config::client::ApprovalHandler)
+//! - [`CodeMode::with_roots`] - Workspace roots for `pctx.roots.list()`, callbacks and upstream servers
+//! - [`CodeMode::with_wasm_modules`] - Supply WebAssembly modules loadable with `pctx.wasm.load(name)`
+//! - [`CodeMode::with_fs`] - Enable the virtual filesystem `pctx.fs`, scoped by path
+//! - [`CodeMode::with_kv`] - Enable the persistent key-value store `pctx.kv`
+//! - [`CodeMode::with_artifacts`] - Set the store of the artifacts executions hand over with `pctx.artifacts`
+//! - [`CodeMode::with_results`] - Offload outputs too large to return, fetched with `pctx.results.fetch(handle, path)`
+//! - [`CodeMode::with_remote_workers`] - Execute code on [`RemoteWorkers`] instead of this process
+//! - [`CodeMode::with_isolation`] - Execute code in a child process per execution with [`Isolation::Process`]
+//! - [`CodeMode::with_execution_timeout`] - Stop executions running too long, cancelling their pending tool calls
+//! - [`CodeMode::with_max_tool_calls`] - Limit the number of MCP tool calls per execution
+//! - [`CodeMode::with_determinism`] - Seed `Math.random` and freeze `Date` for identical outputs
 //!
 //! **Registration methods** (mutable):
 //! - [`CodeMode::add_server`] / [`CodeMode::add_servers`] - Add MCP servers
 //! - [`CodeMode::add_callback`] / [`CodeMode::add_callbacks`] - Add callback tools
+//! - [`CodeMode::add_script`] - Type check & add a saved [`Script`](model::Script) executed as a tool of its own
+//! - [`CodeMode::add_skill`] / [`CodeMode::promote_execution`] - Add a [`Skill`](model::Skill) callable as `Skills.<name>(input)`, e.g. promoted from a successful execution
+//! - [`CodeMode::add_typed_callback`] - Add a callback tool with schemas derived from its Rust argument & result types
+//! - [`CodeMode::add_mock_tools`] - Add a [`MockToolSet`](mock::MockToolSet) answering with canned responses in tests
 //! - [`CodeMode::add_tool_set`] - Add a pre-built ToolSet directly
+//! - [`CodeMode::remove_server`] / [`CodeMode::remove_callback`] / [`CodeMode::remove_skill`] - Remove MCP servers, callback tools & skills
+//! - [`CodeMode::update_callback`] - Replace the configuration of a callback tool
+//! - [`CodeMode::replace_tool_set`] - Replace a ToolSet with the same name
+//! - [`CodeMode::set_embedder`] - Enable semantic tool discovery with an [`Embedder`](discovery::Embedder)
+//! - [`CodeMode::set_schema_cache`] - Set the [`SchemaCache`](schema_cache::SchemaCache) of tool schema snapshots
+//! - [`CodeMode::set_type_check`] - Set the TypeScript compiler options used to type check code
+//! - [`CodeMode::set_imports`] - Set the module imports allowed in executed code
+//! - [`CodeMode::set_collisions`] - Set how namespace & function name collisions are resolved
+//! - [`CodeMode::set_sampling_handler`] - Set the handler of sampling requests of upstream servers
+//! - [`CodeMode::set_elicitation_handler`] - Set the handler of elicitation requests of upstream servers
+//! - [`CodeMode::set_progress_handler`] - Set the handler of progress of upstream tool calls
+//! - [`CodeMode::set_roots`] - Set the workspace roots
+//! - [`CodeMode::set_handlers_owner`] - Set the client the handlers of upstream requests reach
+//! - [`CodeMode::set_wasm_modules`] - Set the WebAssembly modules available to executed code
+//! - [`CodeMode::set_fs`] - Set the virtual filesystem available to executed code
+//! - [`CodeMode::set_kv`] - Set the key-value store available to executed code
+//! - [`CodeMode::set_artifacts`] - Set the artifact store of executions
+//! - [`CodeMode::set_results`] - Set the store of offloaded outputs
+//! - [`CodeMode::set_remote_workers`] - Set the workers executing code
+//! - [`CodeMode::set_isolation`] - Set where code is executed
+//! - [`CodeMode::set_execution_timeout`] - Set the time after which executions are stopped
+//! - [`CodeMode::set_max_tool_calls`] - Set the number of MCP tool calls per execution
+//! - [`CodeMode::save_snapshot`] / [`CodeMode::load_snapshot`] - Persist & restore the resolved tool environment without reconnecting to upstream servers
+//! - [`CodeMode::emit_types`] - Write the TypeScript declarations executed code sees to disk
+//! - [`CodeMode::export_tool_specs`] - Render the code mode tools as OpenAI, Anthropic or Gemini tool definitions
+//! - [`CodeMode::export_catalog`] - Render the listed functions as a Markdown or HTML [`catalog`] document
+//! - [`CodeMode::system_prompt`] - Render the recommended system prompt preamble for agents using code mode
+//! - [`CodeMode::refine_output_types`] - Regenerate return types inferred from tool results
 //!
 //! **Accessor methods**:
 //! - [`CodeMode::tool_sets`] - Get registered ToolSets
 //! - [`CodeMode::servers`] - Get registered server configurations
 //! - [`CodeMode::callbacks`] - Get registered callback configurations
+//! - [`CodeMode::scripts`] - Get the saved scripts
+//! - [`CodeMode::skills`] - Get the skills callable by executed code
 //! - [`CodeMode::allowed_hosts`] - Get allowed network hosts
+//! - [`CodeMode::tool_index`] - Get the semantic tool discovery index
+//! - [`CodeMode::tool_cache`] - Get the cache of MCP tool results shared between executions
+//! - [`CodeMode::circuit_breakers`] - Get the circuit breakers of upstream MCP servers
+//! - [`CodeMode::rate_limiters`] - Get the rate limits of MCP tools
+//! - [`CodeMode::stdio_supervisor`] - Get the supervisor of stdio MCP server processes
+//! - [`CodeMode::schema_cache`] - Get the tool schema snapshot cache
+//! - [`CodeMode::output_schemas`] - Get the output schemas inferred from tool results
+//! - [`CodeMode::check_cache`] - Get the cache of type check results & transpiled code
+//! - [`CodeMode::type_check`] - Get the TypeScript compiler options
+//! - [`CodeMode::imports`] - Get the module imports allowed in executed code
+//! - [`CodeMode::collisions`] - Get how namespace & function name collisions are resolved
+//! - [`CodeMode::client_handlers`] - Get the handlers of requests upstream servers send during tool calls
+//! - [`CodeMode::wasm_modules`] - Get the WebAssembly modules available to executed code
+//! - [`CodeMode::fs`] - Get the virtual filesystem available to executed code
+//! - [`CodeMode::kv`] - Get the key-value store available to executed code
+//! - [`CodeMode::artifacts`] - Get the artifact store of executions
+//! - [`CodeMode::results`] - Get the store of offloaded outputs
+//! - [`CodeMode::remote_workers`] - Get the workers executing code
+//! - [`CodeMode::isolation`] - Get where code is executed
+//! - [`CodeMode::execution_timeout`] - Get the time after which executions are stopped
+//! - [`CodeMode::max_tool_calls`] - Get the number of MCP tool calls per execution
 //!
 //! **Execution methods**:
 //! - [`CodeMode::list_functions`] - List all available functions with minimal interfaces
+//! - [`CodeMode::list_functions_with`] - List all available functions, fitting the interfaces to a token budget
+//! - [`CodeMode::find_tools`] - Find the functions most relevant to a query
+//! - [`CodeMode::search_functions`] - Search for relevant functions with minimal interfaces
 //! - [`CodeMode::get_function_details`] - Get full typed interfaces for specific functions
+//! - [`CodeMode::check`] - Type check TypeScript code without executing it
 //! - [`CodeMode::execute`] - Execute TypeScript code in the sandbox
+//! - [`CodeMode::execute_unchecked`] - Execute TypeScript code without type checking it
+//! - [`CodeMode::execute_input`] - Execute an [`ExecuteInput`](model::ExecuteInput), passing its `input` to `run(input)`
+//! - [`CodeMode::execute_script`] - Execute a saved script with an input, without type checking it again
+//! - [`CodeMode::execute_with_repair`] - Execute code, passing failed attempts to a repair function (e.g. an LLM) for fixed code, returning the [`RepairHistory`](model::RepairHistory)
+//! - [`CodeMode::record`] - Execute an [`ExecuteInput`](model::ExecuteInput), recording its tool calls into an [`ExecutionBundle`](model::ExecutionBundle)
+//! - [`CodeMode::replay`] - Re-run an [`ExecutionBundle`](model::ExecutionBundle) against its recorded tool call results
+//!
+//! Deno requires a current-thread runtime, hosts on a multi-thread runtime execute code with
+//! [`execute_blocking`] (or run other futures, e.g. type checks, with [`run_blocking`]).
 //!
 //! ### Tools and ToolSets
 //!
@@ -100,6 +188,20 @@
 //!
 //! [`CallbackFn`] are Rust async functions that execute when TypeScript code calls callback tools.
 //! Register them in a [`CallbackRegistry`] and pass it to [`CodeMode::execute`].
+//! While a callback runs, [`InvocationContext::current`] returns the context of the execution,
+//! e.g. the workspace roots set with [`CodeMode::with_roots`], the [`ExecutionIds`] set with
+//! [`CodeMode::set_execution_ids`] and the tracing span of the execution. Callbacks pending when the
+//! execution is cancelled or times out are dropped, its [`ExecutionSignal`] lets long
+//! running callbacks stop their own work.
+//!
+//! ### Registering Tools During Executions
+//!
+//! Code registers tools with `pctx.tools.register(...)` and callbacks with
+//! [`InvocationContext::register_tool`], e.g. a "discover API" callback materializing the
+//! endpoints it just learned about. Registered tools are returned in
+//! [`ExecuteOutput::registered_tools`](model::ExecuteOutput::registered_tools), add them with
+//! [`CodeMode::add_callbacks`] and register their [`CallbackFn`]s so subsequent executions can
+//! call them with generated typings. Only executions in this process register tools.
 //!
 //! ## Examples
 //!
@@ -154,11 +256,24 @@
 //! - No subprocess spawning
 //! - Isolated V8 context per execution
 
+mod blocking;
+mod budget;
+pub mod catalog;
 mod code_mode;
+mod collisions;
+pub mod discovery;
+pub mod mock;
 pub mod model;
+mod prompt;
+pub mod schema_cache;
+mod search;
+pub mod tool_specs;
+mod typed;
 
 // Core execution API
+pub use blocking::{execute_blocking, run_blocking};
 pub use code_mode::CodeMode;
+pub use typed::typed_callback;
 
 // Re-export config, runtime and codegen crates
 pub use pctx_code_execution_runtime as runtime;
@@ -166,8 +281,17 @@ pub use pctx_codegen as codegen;
 pub use pctx_config as config;
 
 // Re-export commonly used types for backwards compatibility
-pub use pctx_code_execution_runtime::{CallbackFn, CallbackRegistry};
+pub use pctx_code_execution_runtime::{
+    ArtifactData, ArtifactStore, CallTape, CallbackFn, CallbackRegistry, CircuitBreakers,
+    CircuitState, ExecutionIds, ExecutionSignal, InvocationContext, KvStore, RateLimiters,
+    RecordedCall, RecordedOutcome, ResultStore, StdioSupervisor, ToolCache, ToolDefinition,
+    ToolRegistrations, VirtualFs, WasmModules,
+};
 pub use pctx_codegen::{RootSchema, Tool, ToolSet, case};
+pub use pctx_executor::{
+    CheckCache, Determinism, Isolation, ProcessIsolation, RemoteRequest, RemoteWorkers, StepRecord,
+    StepStatus, isolation,
+};
 
 pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug, thiserror::Error)]
@@ -180,6 +304,8 @@ pub enum Error {
     Codegen(#[from] pctx_codegen::CodegenError),
     #[error("Execution error: {0:?}")]
     Execution(#[from] pctx_executor::DenoExecutorError),
+    #[error("Projection error: {0}")]
+    Projection(#[from] pctx_code_execution_runtime::json_path::JsonPathError),
     #[error("Error: {0}")]
     Message(String),
 }
@@ -0,0 +1,305 @@
+//! Mock tools for tests
+//!
+//! A [`MockToolSet`] registers tools answering with canned responses in place of an
+//! upstream MCP server, so integration tests & CI run without real servers. Fixture
+//! files describe the tools & their responses:
+//!
+//! ```json
+//! {
+//!   "namespace": "Weather",
+//!   "tools": [
+//!     {
+//!       "name": "getForecast",
+//!       "input_schema": { "type": "object", "properties": { "city": { "type": "string" } } },
+//!       "responses": [
+//!         { "when": { "city": "Atlantis" }, "error": "Unknown city" },
+//!         { "output": { "city": "{{city}}", "summary": "Sunny in {{city}}" } }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! A call is answered by the first response whose `when` arguments all match the call's
+//! arguments. `{{path}}` placeholders in outputs are replaced with the argument at the
+//! dotted path, a string consisting of a single placeholder takes the argument's value.
+//! Responses can also be scripted in Rust with [`MockToolSet::with_handler`].
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use pctx_code_execution_runtime::{CallbackFn, CallbackRegistry};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Error, Result, model::CallbackConfig};
+
+/// Tool responding to calls computed in Rust
+pub type MockHandler =
+    Arc<dyn Fn(Option<Value>) -> std::result::Result<Value, String> + Send + Sync>;
+
+/// Mock tool & its canned responses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub input_schema: Option<Value>,
+    #[serde(default)]
+    pub output_schema: Option<Value>,
+    #[serde(default)]
+    pub responses: Vec<MockResponse>,
+}
+
+/// Canned response of a mock tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockResponse {
+    /// Arguments the call must have for this response, any call matches if unset
+    #[serde(default)]
+    pub when: Option<serde_json::Map<String, Value>>,
+    /// Output returned, with `{{path}}` placeholders replaced by arguments
+    #[serde(default)]
+    pub output: Option<Value>,
+    /// Error the call fails with instead of returning an output
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl MockResponse {
+    fn matches(&self, args: Option<&Value>) -> bool {
+        self.when.as_ref().is_none_or(|when| {
+            when.iter()
+                .all(|(key, expected)| args.and_then(|a| a.get(key)) == Some(expected))
+        })
+    }
+}
+
+/// Namespace of mock tools registered as callbacks in place of an upstream MCP server
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct MockToolSet {
+    pub namespace: String,
+    #[serde(default)]
+    pub tools: Vec<MockTool>,
+    #[serde(skip)]
+    handlers: HashMap<String, MockHandler>,
+    #[serde(skip)]
+    calls: Arc<Mutex<Vec<(String, Option<Value>)>>>,
+}
+
+impl std::fmt::Debug for MockToolSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockToolSet")
+            .field("namespace", &self.namespace)
+            .field("tools", &self.tools)
+            .field("handlers", &self.handlers.keys())
+            .finish_non_exhaustive()
+    }
+}
+
+impl MockToolSet {
+    pub fn new(namespace: &str) -> Self {
+        Self {
+            namespace: namespace.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Loads the mock tools of a JSON fixture file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid fixture
+    pub fn from_fixture(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::Message(format!("Failed reading fixture {}: {e}", path.display()))
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| Error::Message(format!("Invalid fixture {}: {e}", path.display())))
+    }
+
+    #[must_use]
+    pub fn with_tool(mut self, tool: MockTool) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    /// Computes the responses of the tool with `handler` instead of its canned responses
+    #[must_use]
+    pub fn with_handler(mut self, tool: &str, handler: MockHandler) -> Self {
+        self.handlers.insert(tool.into(), handler);
+        self
+    }
+
+    /// Calls made to the mock tools as `(tool name, arguments)`, in the order they were made
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn calls(&self) -> Vec<(String, Option<Value>)> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Callback configurations of the mock tools
+    pub fn callbacks(&self) -> Vec<CallbackConfig> {
+        self.tools
+            .iter()
+            .map(|t| CallbackConfig {
+                name: t.name.clone(),
+                namespace: self.namespace.clone(),
+                description: t.description.clone(),
+                input_schema: t.input_schema.clone(),
+                output_schema: t.output_schema.clone(),
+            })
+            .collect()
+    }
+
+    /// Registers the mock tools' responses in `registry`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a callback with the same id is already registered
+    pub fn register(&self, registry: &CallbackRegistry) -> Result<()> {
+        for tool in &self.tools {
+            let callback = self.callback(tool);
+            registry
+                .add(&format!("{}.{}", self.namespace, tool.name), callback)
+                .map_err(|e| Error::Message(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn callback(&self, tool: &MockTool) -> CallbackFn {
+        let name = tool.name.clone();
+        let responses = tool.responses.clone();
+        let handler = self.handlers.get(&tool.name).cloned();
+        let calls = self.calls.clone();
+
+        Arc::new(move |args: Option<Value>| {
+            calls.lock().unwrap().push((name.clone(), args.clone()));
+            let res = match &handler {
+                Some(handler) => handler(args),
+                None => respond(&name, &responses, args.as_ref()),
+            };
+            Box::pin(async move { res })
+        })
+    }
+}
+
+fn respond(
+    tool: &str,
+    responses: &[MockResponse],
+    args: Option<&Value>,
+) -> std::result::Result<Value, String> {
+    let response = responses
+        .iter()
+        .find(|r| r.matches(args))
+        .ok_or_else(|| format!("No mock response of {tool} matches the arguments"))?;
+
+    if let Some(error) = &response.error {
+        return Err(error.clone());
+    }
+    let output = response.output.clone().unwrap_or(Value::Null);
+    Ok(render(&output, args.unwrap_or(&Value::Null)))
+}
+
+/// Replaces `{{path}}` placeholders in the strings of `template` with arguments
+fn render(template: &Value, args: &Value) -> Value {
+    match template {
+        Value::String(s) => {
+            if let Some(path) = s
+                .strip_prefix("{{")
+                .and_then(|s| s.strip_suffix("}}"))
+                .filter(|p| !p.contains("{{"))
+            {
+                return lookup(args, path.trim()).cloned().unwrap_or(Value::Null);
+            }
+
+            let mut rendered = String::new();
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("{{")
+                && let Some(len) = rest[start..].find("}}")
+            {
+                rendered.push_str(&rest[..start]);
+                match lookup(args, rest[start + 2..start + len].trim()) {
+                    Some(Value::String(v)) => rendered.push_str(v),
+                    Some(v) => rendered.push_str(&v.to_string()),
+                    None => {}
+                }
+                rest = &rest[start + len + 2..];
+            }
+            rendered.push_str(rest);
+            Value::String(rendered)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| render(v, args)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render(v, args)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn lookup<'a>(args: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(args, |value, key| match value {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{MockToolSet, render};
+
+    #[test]
+    fn test_render() {
+        let args = json!({ "city": "Paris", "days": 3, "tags": ["a", "b"] });
+        assert_eq!(
+            render(
+                &json!({ "days": "{{days}}", "summary": "{{ city }} for {{days}} days, {{tags.1}}" }),
+                &args
+            ),
+            json!({ "days": 3, "summary": "Paris for 3 days, b" })
+        );
+        assert_eq!(render(&json!("{{missing}}"), &args), json!(null));
+    }
+
+    #[tokio::test]
+    async fn test_fixture_responses() {
+        let mock: MockToolSet = serde_json::from_value(json!({
+            "namespace": "Weather",
+            "tools": [{
+                "name": "getForecast",
+                "responses": [
+                    { "when": { "city": "Atlantis" }, "error": "Unknown city" },
+                    { "output": { "summary": "Sunny in {{city}}" } }
+                ]
+            }]
+        }))
+        .unwrap();
+        let registry = pctx_code_execution_runtime::CallbackRegistry::default();
+        mock.register(&registry).unwrap();
+
+        let res = registry
+            .invoke("Weather.getForecast", Some(json!({ "city": "Paris" })))
+            .await
+            .unwrap();
+        assert_eq!(res, json!({ "summary": "Sunny in Paris" }));
+        assert!(
+            registry
+                .invoke("Weather.getForecast", Some(json!({ "city": "Atlantis" })))
+                .await
+                .is_err()
+        );
+        assert_eq!(mock.calls().len(), 2);
+    }
+}
@@ -1,17 +1,82 @@
 use std::fmt::Display;
 
+use pctx_code_execution_runtime::{ToolDefinition, infer_schema};
+use pctx_config::{script::ScriptConfig, server::ServerConfig, skill::SkillConfig};
+use pctx_executor::{StepRecord, StepStatus};
 use schemars::{JsonSchema, json_schema};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::json;
 use utoipa::ToSchema;
 
+// -------------- Interface Size --------------
+
+/// Size of a code interface returned to the LLM, tokens are estimated from the characters
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema,
+)]
+pub struct InterfaceSize {
+    pub chars: usize,
+    pub tokens: usize,
+}
+impl InterfaceSize {
+    pub fn of(code: &str) -> Self {
+        Self {
+            chars: code.chars().count(),
+            tokens: crate::budget::estimate_tokens(code),
+        }
+    }
+}
+
+/// How an interface was reduced to fit a token budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InterfaceTrim {
+    /// Descriptions were shortened to their first sentence
+    Descriptions,
+    /// Descriptions were removed, leaving the signatures
+    Signatures,
+}
+
+/// How much of the functions' TypeScript interfaces to return
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DetailLevel {
+    /// Only the names of the functions, as `Namespace.functionName` lines
+    Name,
+    /// Signatures & descriptions of the functions, referring to their types by name
+    #[default]
+    Signature,
+    /// Signatures & descriptions of the functions with the definitions of their types
+    Full,
+}
+
 // -------------- List Functions --------------
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, ToSchema)]
+#[serde(default)]
+pub struct ListFunctionsInput {
+    /// How much of the interfaces to return: `name`, `signature` (default) or `full`.
+    pub detail_level: DetailLevel,
+    /// Maximum estimated tokens of the returned code, descriptions are shortened or
+    /// removed to fit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<usize>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema, ToSchema)]
 pub struct ListFunctionsOutput {
     /// Available functions
     pub functions: Vec<ListedFunction>,
 
     pub code: String,
+    /// Size of `code`
+    #[serde(default)]
+    pub size: InterfaceSize,
+    /// How `code` was reduced to fit the token budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trimmed: Option<InterfaceTrim>,
 }
 #[derive(Debug, Serialize, Deserialize, JsonSchema, ToSchema)]
 pub struct ListedFunction {
@@ -21,6 +86,9 @@ pub struct ListedFunction {
     pub name: String,
     /// Function description
     pub description: Option<String>,
+    /// Size of the function's declaration in `code`
+    #[serde(default)]
+    pub size: InterfaceSize,
 }
 
 // -------------- Get Function Details --------------
@@ -30,6 +98,17 @@ pub struct GetFunctionDetailsInput {
     /// List of functions to get details of.
     #[schema(value_type = Vec<String>)]
     pub functions: Vec<FunctionId>,
+    /// How much of the interfaces to return: `name`, `signature` or `full` (default).
+    #[serde(default = "full_detail_level")]
+    pub detail_level: DetailLevel,
+    /// Maximum estimated tokens of the returned code, descriptions are shortened or
+    /// removed to fit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<usize>,
+}
+
+fn full_detail_level() -> DetailLevel {
+    DetailLevel::Full
 }
 
 #[derive(Debug, Clone, Default)]
@@ -52,7 +131,7 @@ impl JsonSchema for FunctionId {
     fn json_schema(_gen: &mut schemars::SchemaGenerator) -> schemars::Schema {
         json_schema!({
             "type": "string",
-            "description": "Function representation in the form should be in the form '<namespace>.<function name>'. e.g. If there is a function `getData` within the `DataApi` namespace the value provided in this field is DataApi.getData"
+            "description": "Function representation in the form should be in the form '<namespace>.<function name>'. e.g. If there is a function `getData` within the `DataApi` namespace the value provided in this field is DataApi.getData. Nested namespaces are included in full, e.g. Google.Drive.listFiles"
         })
     }
 }
@@ -72,18 +151,17 @@ impl<'de> Deserialize<'de> for FunctionId {
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let parts: Vec<&str> = s.splitn(2, '.').collect();
-
-        if parts.len() != 2 {
+        // namespaces can be nested (e.g. `Google.Drive.listFiles`), the function is the last part
+        let Some((mod_name, fn_name)) = s.rsplit_once('.') else {
             return Err(serde::de::Error::custom(format!(
                 "Expected format '<mod_name>.<fn_name>', got '{}'",
                 s
             )));
-        }
+        };
 
         Ok(FunctionId {
-            mod_name: parts[0].to_string(),
-            fn_name: parts[1].to_string(),
+            mod_name: mod_name.to_string(),
+            fn_name: fn_name.to_string(),
         })
     }
 }
@@ -93,6 +171,12 @@ pub struct GetFunctionDetailsOutput {
     pub functions: Vec<FunctionDetails>,
 
     pub code: String,
+    /// Size of `code`
+    #[serde(default)]
+    pub size: InterfaceSize,
+    /// How `code` was reduced to fit the token budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trimmed: Option<InterfaceTrim>,
 }
 #[derive(Debug, Serialize, Deserialize, JsonSchema, ToSchema)]
 pub struct FunctionDetails {
@@ -107,10 +191,42 @@ pub struct FunctionDetails {
     pub types: String,
 }
 
+// -------------- Search Functions --------------
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct SearchFunctionsInput {
+    /// Keywords describing what you want to do, matched against function
+    /// names, descriptions and parameter names. e.g. "account balance"
+    pub query: String,
+    /// Maximum number of functions to return
+    #[serde(default = "default_search_limit")]
+    pub limit: usize,
+}
+
+fn default_search_limit() -> usize {
+    10
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct SearchFunctionsOutput {
+    /// Matching functions, most relevant first
+    pub functions: Vec<SearchedFunction>,
+
+    pub code: String,
+}
+#[derive(Debug, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct SearchedFunction {
+    #[serde(flatten)]
+    pub listed: ListedFunction,
+
+    /// Relevance of the function to the query, higher is better
+    pub score: f64,
+}
+
 // -------------- Execute --------------
 
 #[allow(clippy::doc_markdown)]
-#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, ToSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema, ToSchema)]
 #[serde(default)]
 pub struct ExecuteInput {
     /// Typescript code to execute.
@@ -125,6 +241,47 @@ pub struct ExecuteInput {
     /// The sandbox automatically calls run() and exports the result.
     ///
     pub code: String,
+    /// Skip type checking to reduce latency, type errors surface as runtime errors instead.
+    pub skip_type_check: bool,
+    /// JSON value passed as the argument of ``run(input)``.
+    #[schema(value_type = Object)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<serde_json::Value>,
+    /// Profile of the server whose functions the code calls, defaults to all configured servers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Additional MCP servers connected for this execution, if the server allows ephemeral servers.
+    #[schemars(with = "Vec<serde_json::Value>")]
+    #[schema(value_type = Vec<Object>)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<ServerConfig>,
+    /// JSONPath selecting the parts of the returned value to include in the output, e.g.
+    /// ``$.items[0:10].title``, to avoid returning large values in full.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projection: Option<String>,
+    /// ``interactive`` (default) for executions a user waits for, ``background`` for batch jobs.
+    /// Waiting interactive executions start before background executions.
+    #[serde(skip_serializing_if = "ExecutePriority::is_interactive")]
+    pub priority: ExecutePriority,
+}
+
+/// Scheduling priority of an execution waiting for a free execution slot
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutePriority {
+    /// Executions a user is waiting for, e.g. agent turns
+    #[default]
+    Interactive,
+    /// Executions nobody is waiting for, e.g. scheduled batch jobs
+    Background,
+}
+
+impl ExecutePriority {
+    pub fn is_interactive(&self) -> bool {
+        *self == Self::Interactive
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, ToSchema)]
@@ -138,12 +295,27 @@ pub struct ExecuteOutput {
     /// Value returned by executed function
     #[schema(value_type = Object)]
     pub output: Option<serde_json::Value>,
+    /// Whether the code was executed without type checking
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unchecked: bool,
+    /// Type errors that prevented the code from executing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<CodeDiagnostic>,
+    /// Tools registered during the execution, add them with `add_callbacks` so
+    /// subsequent executions can call them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registered_tools: Vec<CallbackConfig>,
+    /// Tree of the steps recorded with `pctx.step`, with their status, duration & result
+    #[schemars(with = "Vec<serde_json::Value>")]
+    #[schema(value_type = Vec<Object>)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<StepRecord>,
 }
 impl ExecuteOutput {
     pub fn markdown(&self) -> String {
         format!(
-            "Code Executed Successfully: {success}
-
+            "Code Executed Successfully: {success}{unchecked}
+{steps}
 # Return Value
 ```json
 {return_val}
@@ -156,22 +328,204 @@ impl ExecuteOutput {
 {stderr}
 ",
             success = self.success,
+            unchecked = if self.unchecked {
+                "\nType Checked: false (skipped, type errors surface as runtime errors)"
+            } else {
+                ""
+            },
             return_val = serde_json::to_string_pretty(&self.output)
                 .unwrap_or(json!(&self.output).to_string()),
+            steps = if self.steps.is_empty() {
+                String::new()
+            } else {
+                let mut lines = vec![];
+                step_lines(&self.steps, 0, &mut lines);
+                format!("\n# Steps\n{}\n", lines.join("\n"))
+            },
             stdout = &self.stdout,
             stderr = &self.stderr,
         )
     }
 }
+
+/// Markdown list of the step tree, nested steps indented below their parent
+fn step_lines(steps: &[StepRecord], depth: usize, lines: &mut Vec<String>) {
+    for step in steps {
+        let status = match step.status {
+            StepStatus::Running => "running",
+            StepStatus::Succeeded => "succeeded",
+            StepStatus::Failed => "failed",
+        };
+        let duration = step
+            .duration_ms
+            .map_or_else(String::new, |ms| format!(" ({ms} ms)"));
+        let error = step
+            .error
+            .as_ref()
+            .map_or_else(String::new, |e| format!(": {e}"));
+        lines.push(format!(
+            "{indent}- [{status}] {name}{duration}{error}",
+            indent = "  ".repeat(depth),
+            name = step.name,
+        ));
+        step_lines(&step.steps, depth + 1, lines);
+    }
+}
 impl Display for ExecuteOutput {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", json!(&self))
     }
 }
 
+/// Recorded execution, replayable with [`CodeMode::replay`](crate::CodeMode::replay)
+/// against the recorded tool call results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionBundle {
+    pub input: ExecuteInput,
+    /// MCP tool calls & callback invocations in the order they completed
+    pub calls: Vec<pctx_code_execution_runtime::RecordedCall>,
+    /// Seeded randomness & frozen clock the execution was recorded with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub determinism: Option<pctx_executor::Determinism>,
+    /// Output of the recorded execution
+    pub output: ExecuteOutput,
+}
+
+// -------------- Check Code --------------
+
+#[derive(Debug, Default, Serialize, Deserialize, schemars::JsonSchema, ToSchema)]
+#[serde(default)]
+pub struct CheckCodeInput {
+    /// Typescript code to type check, in the same format as the code passed to execute.
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, ToSchema)]
+pub struct CheckCodeOutput {
+    /// Whether the code passed type checking
+    pub success: bool,
+    /// Type errors found in the code
+    pub diagnostics: Vec<CodeDiagnostic>,
+}
+impl CheckCodeOutput {
+    pub fn markdown(&self) -> String {
+        if self.success {
+            return "Code Type Checked Successfully: true".into();
+        }
+
+        let diagnostics = self
+            .diagnostics
+            .iter()
+            .map(|d| format!("- {d}"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!("Code Type Checked Successfully: false\n\n# Diagnostics\n{diagnostics}\n")
+    }
+}
+impl Display for CheckCodeOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", json!(&self))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, ToSchema)]
+pub struct CodeDiagnostic {
+    /// Error message
+    pub message: String,
+    /// Line of the error in the checked code (1-indexed)
+    pub line: Option<usize>,
+    /// Column of the error in the checked code (1-indexed)
+    pub column: Option<usize>,
+    /// TypeScript diagnostic code (e.g., 2322 for type mismatch)
+    pub code: Option<u32>,
+    /// Suggested fix (e.g. "did you mean 'title'?")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+impl From<pctx_executor::Diagnostic> for CodeDiagnostic {
+    fn from(d: pctx_executor::Diagnostic) -> Self {
+        Self {
+            message: d
+                .message
+                .replace("file:///check.ts:", "")
+                .trim()
+                .to_string(),
+            line: d.line,
+            column: d.column,
+            code: d.code,
+            suggestion: d.suggestion,
+        }
+    }
+}
+impl Display for CodeDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "Line {line}")?;
+            if let Some(column) = self.column {
+                write!(f, ", Column {column}")?;
+            }
+            write!(f, ": ")?;
+        }
+        if let Some(code) = self.code {
+            write!(f, "TS{code} ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (suggestion: {suggestion})")?;
+        }
+        Ok(())
+    }
+}
+
+// -------------- Repair --------------
+
+/// Attempt of [`CodeMode::execute_with_repair`](crate::CodeMode::execute_with_repair)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairAttempt {
+    /// Code executed in the attempt
+    pub code: String,
+    pub output: ExecuteOutput,
+}
+impl RepairAttempt {
+    /// Why the attempt failed, for the repair function to fix: the type errors of the
+    /// code if it did not type check, otherwise its standard error
+    pub fn feedback(&self) -> String {
+        if self.output.diagnostics.is_empty() {
+            format!("The code failed at runtime:\n{}", self.output.stderr.trim())
+        } else {
+            let diagnostics = self
+                .output
+                .diagnostics
+                .iter()
+                .map(|d| format!("- {d}"))
+                .collect::<Vec<String>>()
+                .join("\n");
+            format!("The code failed type checking:\n{diagnostics}")
+        }
+    }
+}
+
+/// Attempts of [`CodeMode::execute_with_repair`](crate::CodeMode::execute_with_repair),
+/// in the order they were executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairHistory {
+    pub attempts: Vec<RepairAttempt>,
+}
+impl RepairHistory {
+    /// Whether the last attempt succeeded
+    pub fn success(&self) -> bool {
+        self.last().is_some_and(|a| a.output.success)
+    }
+
+    /// The last attempt, whose output is the result of the execution
+    pub fn last(&self) -> Option<&RepairAttempt> {
+        self.attempts.last()
+    }
+}
+
 // -------------- Callbacks --------------
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, ToSchema)]
 pub struct CallbackConfig {
     pub name: String,
     pub namespace: String,
@@ -184,3 +538,99 @@ impl CallbackConfig {
         format!("{}.{}", &self.namespace, &self.name)
     }
 }
+impl From<ToolDefinition> for CallbackConfig {
+    fn from(tool: ToolDefinition) -> Self {
+        Self {
+            name: tool.name,
+            namespace: tool.namespace,
+            description: tool.description,
+            input_schema: tool.input_schema,
+            output_schema: tool.output_schema,
+        }
+    }
+}
+
+// -------------- Scripts --------------
+
+/// Saved script executed as a tool of its own, type checked once when it is added
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct Script {
+    pub name: String,
+    pub description: Option<String>,
+    /// Code defining ``async function run(input)``
+    pub code: String,
+    /// JSON schema of the input, any object is accepted if unset
+    pub input_schema: Option<serde_json::Value>,
+}
+impl Script {
+    /// Script of the configuration, `code` being its source (see [`ScriptConfig::source`])
+    pub fn from_config(config: &ScriptConfig, code: String) -> Self {
+        Self {
+            name: config.name.clone(),
+            description: config.description.clone(),
+            code,
+            input_schema: config.input_schema.clone(),
+        }
+    }
+}
+
+// -------------- Skills --------------
+
+/// Function learned from a successful execution, callable by executed code as
+/// `Skills.<name>(input)`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct Skill {
+    pub name: String,
+    pub description: Option<String>,
+    /// Code defining ``async function run(input)``, inlined into the function
+    pub code: String,
+    /// JSON schema of the input, any value is accepted if unset
+    pub input_schema: Option<serde_json::Value>,
+    /// JSON schema of the output, the function returns `any` if unset
+    pub output_schema: Option<serde_json::Value>,
+}
+impl Skill {
+    /// Skill parameterized by the `input` of the execution, its types are inferred from
+    /// the execution's input & output
+    ///
+    /// No output type is inferred if the output was projected or offloaded, as it is not
+    /// the value returned by `run`.
+    pub fn from_execution(
+        name: &str,
+        description: Option<String>,
+        input: &ExecuteInput,
+        output: Option<&serde_json::Value>,
+    ) -> Self {
+        let returned = output
+            .filter(|o| input.projection.is_none() && o.get("offloaded") != Some(&json!(true)));
+        Self {
+            name: name.into(),
+            description,
+            code: input.code.clone(),
+            input_schema: input.input.as_ref().map(infer_schema),
+            output_schema: returned.map(infer_schema),
+        }
+    }
+}
+impl From<&SkillConfig> for Skill {
+    fn from(config: &SkillConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            description: config.description.clone(),
+            code: config.code.clone(),
+            input_schema: config.input_schema.clone(),
+            output_schema: config.output_schema.clone(),
+        }
+    }
+}
+impl From<&Skill> for SkillConfig {
+    fn from(skill: &Skill) -> Self {
+        Self {
+            name: skill.name.clone(),
+            description: skill.description.clone(),
+            code: skill.code.clone(),
+            input_schema: skill.input_schema.clone(),
+            output_schema: skill.output_schema.clone(),
+        }
+    }
+}
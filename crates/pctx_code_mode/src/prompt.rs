@@ -0,0 +1,74 @@
+use pctx_codegen::ToolSet;
+use pctx_config::prompt::PromptConfig;
+
+const DEFAULT_TEMPLATE: &str = "You can call functions by writing TypeScript code that is executed in a sandbox with the `execute` tool.
+
+## Available Namespaces
+
+{{namespaces}}
+
+## Conventions
+
+{{conventions}}
+
+## Error Handling
+
+{{error_handling}}
+
+{{instructions}}";
+
+const CONVENTIONS: &str = "- Find functions with `list_functions` or `search_functions`, then read their types with `get_function_details` before writing code
+- Code must define `async function run()` returning the result, it is called automatically
+- Call functions with their namespace, e.g. `await Namespace.functionName({ ... })`, results are parsed objects so don't `JSON.parse` them
+- Only the listed functions and the `pctx` helpers are available, there is no `fetch`, filesystem or other Node/Deno API
+- Filter & aggregate data inside `run` and only return what you need, variables don't persist between executions";
+
+const ERROR_HANDLING: &str = "- Failed function calls throw subclasses of `ToolCallError`: retry `NetworkError` & `Timeout`, fix the arguments of `ValidationError` and don't retry `PermissionDenied`
+- Catch the errors of independent calls separately so one failure doesn't lose the other results, and report failures in the returned value
+- If an execution fails, read the type errors or the runtime error of its output, fix the code and execute it again";
+
+/// System prompt preamble of the tool sets, rendered from the template of the config
+pub(crate) fn render(tool_sets: &[ToolSet], config: &PromptConfig) -> String {
+    let namespaces: Vec<String> = tool_sets
+        .iter()
+        .filter_map(|s| {
+            let count = s.visible_tools().count();
+            if count == 0 {
+                return None;
+            }
+            let summary = s.description.trim().lines().next().unwrap_or_default();
+            let functions = if count == 1 { "function" } else { "functions" };
+            Some(if summary.is_empty() {
+                format!("- `{}` ({count} {functions})", s.namespace)
+            } else {
+                format!("- `{}` ({count} {functions}): {summary}", s.namespace)
+            })
+        })
+        .collect();
+    let namespaces = if namespaces.is_empty() {
+        "No functions are available yet.".to_string()
+    } else {
+        namespaces.join("\n")
+    };
+
+    let instructions = if config.instructions.is_empty() {
+        String::new()
+    } else {
+        let list: Vec<String> = config
+            .instructions
+            .iter()
+            .map(|i| format!("- {}", i.trim()))
+            .collect();
+        format!("## Additional Instructions\n\n{}", list.join("\n"))
+    };
+
+    let prompt = config
+        .template
+        .as_deref()
+        .unwrap_or(DEFAULT_TEMPLATE)
+        .replace("{{namespaces}}", &namespaces)
+        .replace("{{conventions}}", CONVENTIONS)
+        .replace("{{error_handling}}", ERROR_HANDLING)
+        .replace("{{instructions}}", &instructions);
+    format!("{}\n", prompt.trim())
+}
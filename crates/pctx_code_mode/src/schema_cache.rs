@@ -0,0 +1,291 @@
+//! On-disk snapshots of upstream MCP server tool schemas.
+//!
+//! A snapshot is keyed on the server name and invalidated when the server's
+//! transport configuration changes, so restarts don't need to re-fetch the
+//! schemas of every upstream server. The configuration hash is a SHA-256 of
+//! its canonical JSON, so snapshots stay valid across builds of pctx.
+//!
+//! Output schemas inferred from the results of servers configured with
+//! `infer_output_schemas` are stored next to the snapshots, and kept when
+//! the snapshots are refreshed.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use pctx_codegen::{ToolSet, case::Case};
+use pctx_config::server::ServerConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::{Error, Result};
+
+/// Directory of tool schema snapshots, one JSON file per upstream server
+#[derive(Debug, Clone)]
+pub struct SchemaCache {
+    dir: PathBuf,
+    refresh: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    config_hash: String,
+    tool_set: ToolSet,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OutputsSnapshot {
+    config_hash: String,
+    outputs: BTreeMap<String, Value>,
+}
+
+impl SchemaCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            refresh: false,
+        }
+    }
+
+    /// Ignore existing snapshots, so schemas are re-fetched and the snapshots
+    /// replaced. Snapshots of `lazy` servers are still used.
+    #[must_use]
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Loads the tool set snapshot of the server, `None` if there is no snapshot,
+    /// the server's configuration changed since the snapshot was taken or
+    /// the cache is refreshing
+    pub fn load(&self, server: &ServerConfig) -> Option<ToolSet> {
+        if self.refresh && !server.lazy {
+            return None;
+        }
+
+        let path = self.snapshot_path(server);
+        let contents = fs::read_to_string(&path).ok()?;
+        let snapshot: Snapshot = match serde_json::from_str(&contents) {
+            Ok(s) => s,
+            Err(e) => {
+                debug!(path = %path.display(), error = %e, "Ignoring invalid schema snapshot");
+                return None;
+            }
+        };
+
+        (snapshot.config_hash == config_hash(server)).then_some(snapshot.tool_set)
+    }
+
+    /// Writes a snapshot of the server's tool set, replacing any previous snapshot
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory or snapshot file could not be written
+    pub fn store(&self, server: &ServerConfig, tool_set: &ToolSet) -> Result<()> {
+        self.create_dir()?;
+
+        let snapshot = Snapshot {
+            config_hash: config_hash(server),
+            tool_set: tool_set.clone(),
+        };
+        let contents = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| Error::Message(format!("Failed serializing schema snapshot: {e}")))?;
+
+        let path = self.snapshot_path(server);
+        fs::write(&path, contents).map_err(|e| {
+            Error::Message(format!(
+                "Failed writing schema snapshot {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Loads the inferred output schemas of the server's tools keyed by tool name,
+    /// empty if none were stored or the server's configuration changed since
+    pub fn load_outputs(&self, server: &ServerConfig) -> HashMap<String, Value> {
+        let path = self.outputs_path(server);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return HashMap::new();
+        };
+        match serde_json::from_str::<OutputsSnapshot>(&contents) {
+            Ok(s) if s.config_hash == config_hash(server) => s.outputs.into_iter().collect(),
+            Ok(_) => HashMap::new(),
+            Err(e) => {
+                debug!(path = %path.display(), error = %e, "Ignoring invalid output schemas");
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Writes the inferred output schemas of the server's tools, replacing any previous ones
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory or output schemas file could not be written
+    pub fn store_outputs(
+        &self,
+        server: &ServerConfig,
+        outputs: HashMap<String, Value>,
+    ) -> Result<()> {
+        self.create_dir()?;
+
+        let snapshot = OutputsSnapshot {
+            config_hash: config_hash(server),
+            outputs: outputs.into_iter().collect(),
+        };
+        let contents = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| Error::Message(format!("Failed serializing output schemas: {e}")))?;
+
+        let path = self.outputs_path(server);
+        fs::write(&path, contents).map_err(|e| {
+            Error::Message(format!(
+                "Failed writing output schemas {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    fn create_dir(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            Error::Message(format!(
+                "Failed creating schema cache directory {}: {e}",
+                self.dir.display()
+            ))
+        })
+    }
+
+    fn snapshot_path(&self, server: &ServerConfig) -> PathBuf {
+        self.dir.join(format!("{}.json", file_stem(&server.name)))
+    }
+
+    fn outputs_path(&self, server: &ServerConfig) -> PathBuf {
+        self.dir
+            .join(format!("{}.outputs.json", file_stem(&server.name)))
+    }
+}
+
+/// File name of the server's snapshots: the sanitized name stays readable and the
+/// hash of the exact name keeps names sanitized alike (e.g. `a-b` & `a_b`) apart
+fn file_stem(server: &str) -> String {
+    let hash = format!("{:x}", Sha256::digest(server.as_bytes()));
+    format!("{}-{}", Case::Snake.sanitize(server), &hash[..12])
+}
+
+/// Hash of the server configuration that determines its tool schemas
+fn config_hash(server: &ServerConfig) -> String {
+    let config = json!({ "name": server.name, "transport": server.transport });
+    format!("{:x}", Sha256::digest(canonical(config).to_string()))
+}
+
+/// Value with the keys of all objects sorted
+fn canonical(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, canonical(v)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonical).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use pctx_codegen::ToolSet;
+    use pctx_config::server::ServerConfig;
+    use serde_json::json;
+
+    use super::{SchemaCache, canonical, config_hash};
+
+    #[test]
+    fn test_schema_cache_roundtrip_and_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SchemaCache::new(dir.path().join("cache"));
+        let server =
+            ServerConfig::new_stdio("docker".into(), "docker".into(), vec![], BTreeMap::new());
+        let tool_set = ToolSet::new("docker", "Docker tools", vec![]);
+
+        assert!(cache.load(&server).is_none());
+        cache.store(&server, &tool_set).unwrap();
+        let loaded = cache.load(&server).unwrap();
+        assert_eq!(loaded.name, "docker");
+        assert_eq!(loaded.description, "Docker tools");
+
+        // changing the transport invalidates the snapshot
+        let changed = ServerConfig::new_stdio(
+            "docker".into(),
+            "docker".into(),
+            vec!["run".into()],
+            BTreeMap::new(),
+        );
+        assert!(cache.load(&changed).is_none());
+
+        // refreshing ignores snapshots of all but lazy servers
+        let cache = cache.with_refresh(true);
+        assert!(cache.load(&server).is_none());
+        let mut lazy = server.clone();
+        lazy.lazy = true;
+        cache.store(&lazy, &tool_set).unwrap();
+        assert!(cache.load(&lazy).is_some());
+    }
+
+    #[test]
+    fn test_schema_cache_file_names_and_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SchemaCache::new(dir.path());
+        let dashed = ServerConfig::new_stdio("a-b".into(), "a".into(), vec![], BTreeMap::new());
+        let underscored =
+            ServerConfig::new_stdio("a_b".into(), "b".into(), vec![], BTreeMap::new());
+
+        // names sanitized to the same identifier don't share a snapshot
+        cache
+            .store(&dashed, &ToolSet::new("a-b", "dashed", vec![]))
+            .unwrap();
+        cache
+            .store(&underscored, &ToolSet::new("a_b", "underscored", vec![]))
+            .unwrap();
+        assert_eq!(cache.load(&dashed).unwrap().description, "dashed");
+        assert_eq!(cache.load(&underscored).unwrap().description, "underscored");
+
+        // the hash doesn't depend on the key order or the build of pctx
+        assert_eq!(
+            canonical(json!({"b": 1, "a": {"d": [{"f": 1, "e": 2}], "c": 3}})).to_string(),
+            r#"{"a":{"c":3,"d":[{"e":2,"f":1}]},"b":1}"#
+        );
+        assert_eq!(config_hash(&dashed), config_hash(&dashed.clone()));
+        assert_eq!(config_hash(&dashed).len(), 64);
+        assert_ne!(config_hash(&dashed), config_hash(&underscored));
+    }
+
+    #[test]
+    fn test_schema_cache_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SchemaCache::new(dir.path()).with_refresh(true);
+        let server =
+            ServerConfig::new_stdio("docker".into(), "docker".into(), vec![], BTreeMap::new());
+
+        assert!(cache.load_outputs(&server).is_empty());
+        let outputs = HashMap::from([("ps".to_string(), json!({"type": "array"}))]);
+        cache.store_outputs(&server, outputs.clone()).unwrap();
+
+        // inferred schemas are kept when refreshing snapshots
+        assert_eq!(cache.load_outputs(&server), outputs);
+        assert!(cache.load(&server).is_none());
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use pctx_codegen::{Tool, ToolSet, case::Case};
+
+// relative weights of where a query term matched
+const NAME_WEIGHT: f64 = 3.0;
+const PARTIAL_NAME_WEIGHT: f64 = 1.5;
+const PARAM_WEIGHT: f64 = 2.0;
+const DESCRIPTION_WEIGHT: f64 = 1.0;
+const NAMESPACE_WEIGHT: f64 = 1.0;
+
+/// Splits text into lowercase word terms, breaking on whitespace,
+/// punctuation and case boundaries (e.g. `getAccountBalance` -> `get`, `account`, `balance`)
+pub(crate) fn terms(text: &str) -> Vec<String> {
+    Case::Snake
+        .sanitize(text)
+        .split('_')
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Keyword relevance of a tool for the given query terms, `0.0` means no match.
+pub(crate) fn score_tool(query: &[String], tool_set: &ToolSet, tool: &Tool) -> f64 {
+    let name_terms: HashSet<String> = terms(&tool.name)
+        .into_iter()
+        .chain(terms(&tool.fn_name))
+        .collect();
+    let joined_name = tool.fn_name.to_lowercase();
+    let param_terms: HashSet<String> = param_names(tool).iter().flat_map(|p| terms(p)).collect();
+    let description_terms: HashSet<String> = tool
+        .description
+        .as_deref()
+        .map(terms)
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let namespace_terms: HashSet<String> = terms(&tool_set.name).into_iter().collect();
+
+    let mut score = 0.0;
+    for term in query {
+        if name_terms.contains(term) {
+            score += NAME_WEIGHT;
+        } else if joined_name.contains(term.as_str()) {
+            score += PARTIAL_NAME_WEIGHT;
+        }
+        if param_terms.contains(term) {
+            score += PARAM_WEIGHT;
+        }
+        if description_terms.contains(term) {
+            score += DESCRIPTION_WEIGHT;
+        }
+        if namespace_terms.contains(term) {
+            score += NAMESPACE_WEIGHT;
+        }
+    }
+
+    score
+}
+
+/// Top level property names of the tool's input schema
+pub(crate) fn param_names(tool: &Tool) -> Vec<String> {
+    tool.input_schema
+        .schema
+        .object
+        .as_ref()
+        .map(|o| o.properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use pctx_codegen::{Tool, ToolSet};
+    use serde_json::json;
+
+    use super::{score_tool, terms};
+
+    fn tool(name: &str, description: &str, properties: &serde_json::Value) -> Tool {
+        Tool::new_mcp(
+            name,
+            Some(description.into()),
+            serde_json::from_value(json!({"type": "object", "properties": properties})).unwrap(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_terms_split_case_and_punctuation() {
+        assert_eq!(
+            terms("getAccountBalance, for_user"),
+            vec!["get", "account", "balance", "for", "user"]
+        );
+    }
+
+    #[test]
+    fn test_score_tool_prefers_name_matches() {
+        let balance = tool(
+            "get_account_balance",
+            "Retrieves the balance for an account",
+            &json!({"account_id": {"type": "string"}}),
+        );
+        let freeze = tool(
+            "freeze_account",
+            "Freezes an account so the balance can not change",
+            &json!({"account_id": {"type": "string"}}),
+        );
+        let weather = tool(
+            "get_forecast",
+            "Weather forecast for a city",
+            &json!({"city": {"type": "string"}}),
+        );
+        let tool_set = ToolSet::new("banking", "", vec![]);
+        let query = terms("account balance");
+
+        let balance_score = score_tool(&query, &tool_set, &balance);
+        let freeze_score = score_tool(&query, &tool_set, &freeze);
+
+        assert!(balance_score > freeze_score);
+        assert!(freeze_score > 0.0);
+        assert!(score_tool(&query, &tool_set, &weather) == 0.0);
+        assert!(score_tool(&terms("city"), &tool_set, &weather) > 0.0);
+    }
+}
@@ -0,0 +1,370 @@
+//! Tool definitions of code mode for the function calling APIs of LLM providers, so
+//! agent frameworks can register pctx without hand-writing the schemas.
+//!
+//! ```ignore
+//! use pctx_code_mode::tool_specs::ToolSpecFormat;
+//!
+//! let tools = code_mode.export_tool_specs(ToolSpecFormat::Anthropic, false);
+//! ```
+
+use std::str::FromStr;
+
+use pctx_codegen::{Tool, ToolSet};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+/// Maximum length of function names accepted by the provider APIs
+const MAX_NAME_LEN: usize = 64;
+
+/// Depth after which `$ref`s of recursive schemas are no longer inlined for Gemini
+const MAX_REF_DEPTH: usize = 8;
+
+const LIST_FUNCTIONS_DESCRIPTION: &str =
+    "ALWAYS USE THIS TOOL FIRST to list all available functions organized by namespace.
+
+WORKFLOW:
+1. Start here - Call this tool to see what functions are available
+2. Then call get_function_details() for specific functions you need to understand
+3. Finally call execute() to run your TypeScript code
+
+This returns function signatures without full details.";
+
+const GET_FUNCTION_DETAILS_DESCRIPTION: &str = "Get detailed information about specific functions you want to use.
+
+WHEN TO USE: After calling list_functions(), use this to learn about parameter types, return values, and usage for specific functions.
+
+REQUIRED FORMAT: Functions must be specified as 'namespace.functionName' (e.g., 'Namespace.apiPostSearch')
+
+Only request details for functions you actually plan to use in your code.";
+
+const EXECUTE_DESCRIPTION: &str = "Execute TypeScript code that calls namespaced functions. USE THIS LAST after list_functions() and get_function_details().
+
+REQUIRED CODE STRUCTURE:
+async function run() {
+    // Call Namespace.functionName() - MUST include namespace prefix
+    // Filter the data here and only return what you need
+    return result;
+}
+
+IMPORTANT RULES:
+- Functions MUST be called as 'Namespace.functionName' (e.g., 'Notion.apiPostSearch')
+- Only functions from list_functions() are available - no fetch(), fs, or other Node/Deno APIs
+- Results of functions are parsed objects, don't call JSON.parse() on them
+- Variables don't persist between execute() calls - return or log anything you need later";
+
+fn detail_level_parameter() -> Value {
+    json!({
+        "type": "string",
+        "enum": ["name", "signature", "full"],
+        "description": "How much of the interfaces to return: only names, signatures with descriptions, or signatures with descriptions and type definitions"
+    })
+}
+
+fn budget_tokens_parameter() -> Value {
+    json!({
+        "type": "integer",
+        "description": "Maximum estimated tokens of the returned code, descriptions are shortened or removed to fit"
+    })
+}
+
+/// Tool definition format of an LLM provider API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolSpecFormat {
+    /// OpenAI function calling, `{"type": "function", "function": {"name", "description", "parameters"}}`
+    OpenAi,
+    /// Anthropic tool use, `{"name", "description", "input_schema"}`
+    Anthropic,
+    /// Gemini `FunctionDeclaration`s, `{"name", "description", "parameters"}` with the
+    /// parameters restricted to Gemini's subset of OpenAPI schemas
+    Gemini,
+}
+
+impl FromStr for ToolSpecFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "openai" => Ok(Self::OpenAi),
+            "anthropic" => Ok(Self::Anthropic),
+            "gemini" => Ok(Self::Gemini),
+            other => Err(format!(
+                "Unknown tool spec format '{other}', expected `openai`, `anthropic` or `gemini`"
+            )),
+        }
+    }
+}
+
+/// Provider independent definition of a tool
+#[derive(Debug, Clone)]
+pub(crate) struct ToolSpec {
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+impl ToolSpec {
+    /// The `list_functions`, `get_function_details` & `execute` tools of code mode
+    pub(crate) fn meta_tools() -> Vec<Self> {
+        vec![
+            Self {
+                name: "list_functions".into(),
+                description: LIST_FUNCTIONS_DESCRIPTION.into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "detail_level": detail_level_parameter(),
+                        "budget_tokens": budget_tokens_parameter()
+                    }
+                }),
+            },
+            Self {
+                name: "get_function_details".into(),
+                description: GET_FUNCTION_DETAILS_DESCRIPTION.into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "functions": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Functions to get details of, as 'Namespace.functionName'"
+                        },
+                        "detail_level": detail_level_parameter(),
+                        "budget_tokens": budget_tokens_parameter()
+                    },
+                    "required": ["functions"]
+                }),
+            },
+            Self {
+                name: "execute".into(),
+                description: EXECUTE_DESCRIPTION.into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "code": {
+                            "type": "string",
+                            "description": "TypeScript code defining `async function run()`, its return value is the result of the execution"
+                        }
+                    },
+                    "required": ["code"]
+                }),
+            },
+        ]
+    }
+
+    /// Tool of a tool set, named `Namespace_fnName`
+    pub(crate) fn for_tool(tool_set: &ToolSet, tool: &Tool) -> Self {
+        let name: String = format!("{}_{}", tool_set.tool_namespace(tool), tool.fn_name)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .take(MAX_NAME_LEN)
+            .collect();
+
+        let mut parameters = serde_json::to_value(&tool.input_schema)
+            .unwrap_or_else(|_| json!({ "type": "object", "properties": {} }));
+        if let Some(obj) = parameters.as_object_mut() {
+            obj.remove("$schema");
+            obj.remove("title");
+        }
+
+        Self {
+            name,
+            description: tool.description.clone().unwrap_or_default(),
+            parameters,
+        }
+    }
+
+    pub(crate) fn render(&self, format: ToolSpecFormat) -> Value {
+        match format {
+            ToolSpecFormat::OpenAi => json!({
+                "type": "function",
+                "function": {
+                    "name": self.name,
+                    "description": self.description,
+                    "parameters": self.parameters,
+                }
+            }),
+            ToolSpecFormat::Anthropic => json!({
+                "name": self.name,
+                "description": self.description,
+                "input_schema": self.parameters,
+            }),
+            ToolSpecFormat::Gemini => {
+                let mut declaration = json!({
+                    "name": self.name,
+                    "description": self.description,
+                });
+                let defs = definitions(&self.parameters);
+                let parameters = gemini_schema(&self.parameters, &defs, 0);
+                // functions without parameters omit them
+                if parameters
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .is_some_and(|p| !p.is_empty())
+                {
+                    declaration["parameters"] = parameters;
+                }
+                declaration
+            }
+        }
+    }
+}
+
+/// Definitions `$ref`s of the schema point to
+fn definitions(schema: &Value) -> Map<String, Value> {
+    let mut defs = Map::new();
+    for key in ["definitions", "$defs"] {
+        if let Some(Value::Object(d)) = schema.get(key) {
+            defs.extend(d.clone());
+        }
+    }
+    defs
+}
+
+/// Converts a JSON schema into Gemini's subset of OpenAPI schemas: `$ref`s are inlined,
+/// nullable type unions become `nullable` and unsupported keywords are dropped
+fn gemini_schema(schema: &Value, defs: &Map<String, Value>, depth: usize) -> Value {
+    let Value::Object(obj) = schema else {
+        // `true` schemas accept any value
+        return json!({});
+    };
+
+    if let Some(Value::String(reference)) = obj.get("$ref") {
+        let name = reference.rsplit('/').next().unwrap_or_default();
+        return match defs.get(name) {
+            Some(def) if depth < MAX_REF_DEPTH => gemini_schema(def, defs, depth + 1),
+            _ => json!({}),
+        };
+    }
+
+    let mut out = Map::new();
+    for (key, value) in obj {
+        match key.as_str() {
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(k, v)| (k.clone(), gemini_schema(v, defs, depth)))
+                    .collect();
+                out.insert(key.clone(), Value::Object(properties));
+            }
+            "items" => {
+                out.insert(key.clone(), gemini_schema(value, defs, depth));
+            }
+            "anyOf" | "oneOf" => {
+                let variants: Vec<Value> = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|v| v.get("type") != Some(&json!("null")))
+                    .map(|v| gemini_schema(v, defs, depth))
+                    .collect();
+                if variants.len() < value.as_array().map_or(0, Vec::len) {
+                    out.insert("nullable".into(), Value::Bool(true));
+                }
+                out.insert("anyOf".into(), Value::Array(variants));
+            }
+            "type" => {
+                if let Value::Array(types) = value {
+                    if types.iter().any(|t| t == "null") {
+                        out.insert("nullable".into(), Value::Bool(true));
+                    }
+                    if let Some(t) = types.iter().find(|t| *t != "null") {
+                        out.insert(key.clone(), t.clone());
+                    }
+                } else {
+                    out.insert(key.clone(), value.clone());
+                }
+            }
+            "description" | "enum" | "format" | "required" | "nullable" | "minimum" | "maximum"
+            | "minItems" | "maxItems" | "minLength" | "maxLength" | "pattern" => {
+                out.insert(key.clone(), value.clone());
+            }
+            // `$schema`, `definitions`, `additionalProperties`, `title`, `default`, ...
+            _ => {}
+        }
+    }
+    Value::Object(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ToolSpec, ToolSpecFormat, definitions, gemini_schema};
+
+    #[test]
+    fn test_render_meta_tools() {
+        let tools = ToolSpec::meta_tools();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["list_functions", "get_function_details", "execute"]);
+
+        let execute = &tools[2];
+        let openai = execute.render(ToolSpecFormat::OpenAi);
+        assert_eq!(openai["type"], "function");
+        assert_eq!(openai["function"]["name"], "execute");
+        assert_eq!(
+            openai["function"]["parameters"]["required"],
+            json!(["code"])
+        );
+
+        let anthropic = execute.render(ToolSpecFormat::Anthropic);
+        assert_eq!(anthropic["name"], "execute");
+        assert_eq!(anthropic["input_schema"]["required"], json!(["code"]));
+
+        // functions without parameters omit them for Gemini
+        let gemini = tools[0].render(ToolSpecFormat::Gemini);
+        assert_eq!(gemini["name"], "list_functions");
+        assert!(gemini.get("parameters").is_none());
+    }
+
+    #[test]
+    fn test_gemini_schema() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Input",
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "user": { "$ref": "#/definitions/User" },
+                "limit": { "type": ["integer", "null"], "default": 10 }
+            },
+            "required": ["user"],
+            "definitions": {
+                "User": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } }
+                }
+            }
+        });
+
+        assert_eq!(
+            gemini_schema(&schema, &definitions(&schema), 0),
+            json!({
+                "type": "object",
+                "properties": {
+                    "user": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" } }
+                    },
+                    "limit": { "type": "integer", "nullable": true }
+                },
+                "required": ["user"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("OpenAI".parse(), Ok(ToolSpecFormat::OpenAi));
+        assert_eq!("gemini".parse(), Ok(ToolSpecFormat::Gemini));
+        assert!("bedrock".parse::<ToolSpecFormat>().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Callbacks typed with Rust structs
+//!
+//! The input & output schemas of typed callbacks are derived from their argument and
+//! result types with `schemars`, and arguments are deserialized before the callback
+//! is called:
+//!
+//! ```rust,no_run
+//! use pctx_code_mode::{CallbackRegistry, CodeMode};
+//! use schemars::JsonSchema;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, JsonSchema)]
+//! struct GreetInput {
+//!     name: String,
+//! }
+//!
+//! #[derive(Serialize, JsonSchema)]
+//! struct GreetOutput {
+//!     message: String,
+//! }
+//!
+//! # fn example() -> pctx_code_mode::Result<()> {
+//! let mut code_mode = CodeMode::default();
+//! let registry = CallbackRegistry::default();
+//! code_mode.add_typed_callback(
+//!     &registry,
+//!     "Greeter",
+//!     "greet",
+//!     Some("Greets a person by name"),
+//!     |input: GreetInput| async move {
+//!         Ok(GreetOutput {
+//!             message: format!("Hello, {}!", input.name),
+//!         })
+//!     },
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{future::Future, sync::Arc};
+
+use pctx_code_execution_runtime::CallbackFn;
+use schemars::JsonSchema;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::model::CallbackConfig;
+
+impl CallbackConfig {
+    /// Configuration of a callback with the schemas of its `In` arguments & `Out` result
+    pub fn typed<In: JsonSchema, Out: JsonSchema>(
+        namespace: &str,
+        name: &str,
+        description: Option<&str>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            namespace: namespace.into(),
+            description: description.map(Into::into),
+            input_schema: Some(schema_of::<In>()),
+            output_schema: Some(schema_of::<Out>()),
+        }
+    }
+}
+
+/// Callback deserializing its arguments into `In` and serializing its `Out` result
+pub fn typed_callback<In, Out, F, Fut>(f: F) -> CallbackFn
+where
+    In: DeserializeOwned + Send + 'static,
+    Out: Serialize + 'static,
+    F: Fn(In) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Out, String>> + Send + 'static,
+{
+    let f = Arc::new(f);
+    Arc::new(move |args: Option<serde_json::Value>| {
+        let f = f.clone();
+        Box::pin(async move {
+            let input: In = serde_json::from_value(args.unwrap_or_else(|| serde_json::json!({})))
+                .map_err(|e| format!("Invalid arguments: {e}"))?;
+            let output = f(input).await?;
+            serde_json::to_value(output).map_err(|e| format!("Invalid result: {e}"))
+        })
+    })
+}
+
+fn schema_of<T: JsonSchema>() -> serde_json::Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default();
+    // the tool's own description & name are used instead
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("$schema");
+        obj.remove("title");
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::typed_callback;
+    use crate::model::CallbackConfig;
+
+    #[derive(Deserialize, JsonSchema)]
+    struct AddInput {
+        a: i64,
+        b: i64,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    struct AddOutput {
+        sum: i64,
+    }
+
+    #[tokio::test]
+    async fn test_typed_callback() {
+        let config = CallbackConfig::typed::<AddInput, AddOutput>("MyMath", "add", None);
+        let input = config.input_schema.unwrap();
+        assert_eq!(input["type"], json!("object"));
+        assert_eq!(input["required"], json!(["a", "b"]));
+        assert!(input.get("title").is_none());
+
+        let callback = typed_callback(|input: AddInput| async move {
+            Ok(AddOutput {
+                sum: input.a + input.b,
+            })
+        });
+        assert_eq!(
+            callback(Some(json!({ "a": 1, "b": 2 }))).await.unwrap(),
+            json!({ "sum": 3 })
+        );
+        assert!(callback(Some(json!({ "a": "one" }))).await.is_err());
+    }
+}
@@ -1,7 +1,58 @@
 use heck::{
     ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutySnakeCase, ToSnakeCase, ToTitleCase,
 };
-use unicode_ident::is_xid_continue;
+use serde::{Deserialize, Serialize};
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+/// Words that can not name a generated function
+const RESERVED_WORDS: &[&str] = &[
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
 
 #[derive(Debug)]
 pub enum Case {
@@ -45,9 +96,65 @@ impl Case {
     }
 }
 
+/// Case conversion of tool names into generated function names
+///
+/// Type names are always the PascalCase function name followed by `Input`/`Output`.
+/// Generated functions call tools by their original name, so any policy round-trips.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CasePolicy {
+    /// Keeps tool names as is, replacing characters not allowed in identifiers with `_`
+    #[serde(rename = "preserve")]
+    Preserve,
+    /// `get_HTTP_response` becomes `getHttpResponse`
+    #[default]
+    #[serde(rename = "camelCase")]
+    Camel,
+    /// `getHTTPResponse` becomes `get_http_response`
+    #[serde(rename = "snake_case")]
+    Snake,
+}
+
+impl CasePolicy {
+    /// Function name of the tool, always a valid TypeScript identifier
+    pub fn fn_name(self, name: &str) -> String {
+        let converted = match self {
+            CasePolicy::Preserve => name
+                .chars()
+                .map(|c| {
+                    if is_xid_continue(c) || c == '$' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect(),
+            CasePolicy::Camel => Case::Camel.sanitize(name),
+            CasePolicy::Snake => Case::Snake.sanitize(name),
+        };
+
+        identifier(converted)
+    }
+}
+
+/// Escapes names that are not valid identifiers by themselves: names starting with a
+/// digit are prefixed with `_` and reserved words are suffixed with `_`
+pub fn identifier(name: String) -> String {
+    if name
+        .chars()
+        .next()
+        .is_some_and(|c| !is_xid_start(c) && c != '_' && c != '$')
+    {
+        format!("_{name}")
+    } else if RESERVED_WORDS.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::Case;
+    use super::{Case, CasePolicy};
 
     #[test]
     fn test_trailing_underscore() {
@@ -60,4 +167,48 @@ mod test {
         let input = "_ident";
         assert_eq!(Case::Camel.sanitize(input), "_ident");
     }
+
+    #[test]
+    fn test_case_policy() {
+        let cases = [
+            (
+                "get_HTTP_response",
+                "get_HTTP_response",
+                "getHttpResponse",
+                "get_http_response",
+            ),
+            (
+                "getHTTPResponse",
+                "getHTTPResponse",
+                "getHttpResponse",
+                "get_http_response",
+            ),
+            (
+                "list-files.v2",
+                "list_files_v2",
+                "listFilesV2",
+                "list_files_v2",
+            ),
+            (
+                "s3_get_object",
+                "s3_get_object",
+                "s3GetObject",
+                "s3_get_object",
+            ),
+            ("2fa_verify", "_2fa_verify", "_2faVerify", "_2fa_verify"),
+            ("delete", "delete_", "delete_", "delete_"),
+        ];
+
+        for (name, preserve, camel, snake) in cases {
+            assert_eq!(CasePolicy::Preserve.fn_name(name), preserve, "{name}");
+            assert_eq!(CasePolicy::Camel.fn_name(name), camel, "{name}");
+            assert_eq!(CasePolicy::Snake.fn_name(name), snake, "{name}");
+        }
+
+        // conversions are stable
+        for policy in [CasePolicy::Preserve, CasePolicy::Camel, CasePolicy::Snake] {
+            let converted = policy.fn_name("getHTTPResponse_v2");
+            assert_eq!(policy.fn_name(&converted), converted);
+        }
+    }
 }
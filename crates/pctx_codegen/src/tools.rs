@@ -3,7 +3,12 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tracing::debug;
 
-use crate::{CodegenResult, case::Case, generate_docstring, typegen::generate_types_new};
+use crate::{
+    CodegenResult,
+    case::{Case, CasePolicy},
+    generate_docstring,
+    typegen::generate_types_new,
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ToolSet {
@@ -23,19 +28,74 @@ impl ToolSet {
         }
     }
 
-    pub fn namespace_interface(&self, include_types: bool) -> String {
-        let fns: Vec<String> = self
-            .tools
+    /// Replaces the namespace, dotted namespaces (e.g. `Google.Workspace`) are nested
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        let namespace = namespace_path(namespace);
+        if !namespace.is_empty() {
+            self.namespace = namespace;
+        }
+        self
+    }
+
+    /// Finds the tool generating the function, the function name is mapped back to the
+    /// original tool name regardless of the tool's [`CasePolicy`]
+    pub fn find_fn(&self, namespace: &str, fn_name: &str) -> Option<&Tool> {
+        self.tools
             .iter()
-            .map(|t| t.fn_signature(include_types))
-            .collect();
+            .find(|t| t.fn_name == fn_name && self.tool_namespace(t) == namespace)
+    }
 
-        self.wrap_with_namespace(&fns.join("\n\n"))
+    /// Full namespace of the tool, including its category (e.g. `Google.Drive`)
+    pub fn tool_namespace(&self, tool: &Tool) -> String {
+        match &tool.category {
+            Some(category) => format!("{}.{category}", &self.namespace),
+            None => self.namespace.clone(),
+        }
+    }
+
+    /// Tools listed to agents, hidden tools are callable but not listed
+    pub fn visible_tools(&self) -> impl Iterator<Item = &Tool> {
+        self.tools.iter().filter(|t| !t.hidden)
+    }
+
+    /// Interface of the visible tools
+    pub fn namespace_interface(&self, include_types: bool) -> String {
+        let tools: Vec<&Tool> = self.visible_tools().collect();
+        self.namespace_with(&tools, |t| t.fn_signature(include_types))
     }
 
     pub fn namespace(&self) -> String {
-        let fns: Vec<String> = self.tools.iter().map(|t| t.fn_impl(&self.name)).collect();
-        self.wrap_with_namespace(&fns.join("\n\n"))
+        let tools: Vec<&Tool> = self.tools.iter().collect();
+        self.namespace_with(&tools, |t| t.fn_impl(&self.name))
+    }
+
+    /// Wraps the rendered tools with the namespace, nesting categorized tools
+    /// in `export namespace <Category>` blocks
+    pub fn namespace_with(&self, tools: &[&Tool], render: impl Fn(&Tool) -> String) -> String {
+        let mut content: Vec<String> = vec![];
+        let mut categories: Vec<(&str, Vec<String>)> = vec![];
+        for tool in tools {
+            match &tool.category {
+                Some(category) => {
+                    if let Some((_, fns)) = categories.iter_mut().find(|(c, _)| c == category) {
+                        fns.push(render(tool));
+                    } else {
+                        categories.push((category, vec![render(tool)]));
+                    }
+                }
+                None => content.push(render(tool)),
+            }
+        }
+
+        content.extend(categories.into_iter().map(|(category, fns)| {
+            format!(
+                "export namespace {category} {{\n  {fns}\n}}",
+                fns = fns.join("\n\n")
+            )
+        }));
+
+        self.wrap_with_namespace(&content.join("\n\n"))
     }
 
     pub fn wrap_with_namespace(&self, content: &str) -> String {
@@ -63,6 +123,26 @@ pub struct Tool {
     pub types: String,
 
     pub variant: ToolVariant,
+
+    /// Nested namespace of the tool within its tool set (e.g. `Drive`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    /// Case conversion of the tool name into the function name
+    #[serde(default)]
+    pub case: CasePolicy,
+
+    /// Deprecation note of the tool, empty if deprecated without a reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+
+    /// Hidden tools are callable but not listed to agents
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+
+    /// The output schema was inferred from results instead of provided by the tool
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub output_inferred: bool,
 }
 
 impl Tool {
@@ -72,7 +152,14 @@ impl Tool {
         input: RootSchema,
         output: Option<RootSchema>,
     ) -> CodegenResult<Self> {
-        Self::_new(name, description, input, output, ToolVariant::Mcp)
+        Self::_new(
+            name,
+            description,
+            input,
+            output,
+            ToolVariant::Mcp,
+            CasePolicy::default(),
+        )
     }
 
     pub fn new_callback(
@@ -81,7 +168,14 @@ impl Tool {
         input: RootSchema,
         output: Option<RootSchema>,
     ) -> CodegenResult<Self> {
-        Self::_new(name, description, input, output, ToolVariant::Callback)
+        Self::_new(
+            name,
+            description,
+            input,
+            output,
+            ToolVariant::Callback,
+            CasePolicy::default(),
+        )
     }
 
     fn _new(
@@ -90,8 +184,9 @@ impl Tool {
         input: RootSchema,
         output: Option<RootSchema>,
         variant: ToolVariant,
+        case: CasePolicy,
     ) -> CodegenResult<Self> {
-        let fn_name = Case::Camel.sanitize(name);
+        let fn_name = case.fn_name(name);
         debug!(
             variant =? variant,
             "Generating Typescript interface for tool: '{name}' -> function {fn_name}",
@@ -118,11 +213,111 @@ impl Tool {
             output_signature,
             types: type_defs,
             variant,
+            category: None,
+            case,
+            deprecated: None,
+            hidden: false,
+            output_inferred: false,
+        })
+    }
+
+    /// Marks the tool deprecated, adding a JSDoc `@deprecated` note with the reason
+    #[must_use]
+    pub fn with_deprecated(mut self, reason: &str) -> Self {
+        self.deprecated = Some(reason.into());
+        self
+    }
+
+    /// Hides the tool from listings, it can still be called
+    #[must_use]
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Regenerates the function & type names with the case conversion policy,
+    /// call before [`Tool::with_category`] as the category is reset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the types could not be generated
+    pub fn with_case(self, case: CasePolicy) -> CodegenResult<Self> {
+        if self.case == case {
+            return Ok(self);
+        }
+
+        let (deprecated, hidden, output_inferred) =
+            (self.deprecated.clone(), self.hidden, self.output_inferred);
+        let tool = Self::_new(
+            &self.name,
+            self.description,
+            self.input_schema,
+            self.output_schema,
+            self.variant,
+            case,
+        )?;
+        Ok(Self {
+            deprecated,
+            hidden,
+            output_inferred,
+            ..tool
+        })
+    }
+
+    /// Regenerates the output types with a schema inferred from the tool's results,
+    /// keeping the function name, category & annotations
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the types could not be generated
+    pub fn with_inferred_output(self, output: RootSchema) -> CodegenResult<Self> {
+        let (category, fn_name, deprecated, hidden) = (
+            self.category.clone(),
+            self.fn_name.clone(),
+            self.deprecated.clone(),
+            self.hidden,
+        );
+        let tool = Self::_new(
+            &self.name,
+            self.description,
+            self.input_schema,
+            Some(output),
+            self.variant,
+            self.case,
+        )?;
+        Ok(Self {
+            category,
+            fn_name,
+            deprecated,
+            hidden,
+            output_inferred: true,
+            ..tool
         })
     }
 
+    /// Moves the tool into a nested namespace of its tool set, naming the function after
+    /// `fn_name` instead of the full tool name (e.g. `list_files` in `Drive` rather than
+    /// `drive_list_files`). Calls still dispatch on the original tool name.
+    #[must_use]
+    pub fn with_category(mut self, category: &str, fn_name: &str) -> Self {
+        let category = namespace_path(category);
+        let fn_name = self.case.fn_name(fn_name);
+
+        if !category.is_empty() && !fn_name.is_empty() {
+            self.category = Some(category);
+            self.fn_name = fn_name;
+        }
+        self
+    }
+
     pub fn fn_signature(&self, include_types: bool) -> String {
-        let docstring_content = self.description.clone().unwrap_or_default();
+        let mut docstring_content = self.description.clone().unwrap_or_default();
+        if let Some(reason) = &self.deprecated {
+            if !docstring_content.is_empty() {
+                docstring_content.push_str("\n\n");
+            }
+            docstring_content.push_str(format!("@deprecated {reason}").trim_end());
+        }
 
         let types = if include_types && !self.types.is_empty() {
             format!("{}\n\n", &self.types)
@@ -173,9 +368,165 @@ impl Tool {
     }
 }
 
+/// PascalCases each segment of a dotted namespace, dropping empty segments
+fn namespace_path(namespace: &str) -> String {
+    namespace
+        .split('.')
+        .map(|segment| Case::Pascal.sanitize(segment))
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ToolVariant {
     Mcp,
     Callback,
 }
+
+#[cfg(test)]
+mod test {
+    use pctx_type_check_runtime::type_check;
+    use serde_json::json;
+
+    use super::{Tool, ToolSet};
+    use crate::case::CasePolicy;
+
+    fn tool(name: &str) -> Tool {
+        Tool::new_mcp(
+            name,
+            None,
+            serde_json::from_value(json!({"type": "object", "properties": {}})).unwrap(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_case_policy_round_trip() {
+        let tool_set = ToolSet::new(
+            "api",
+            "",
+            vec![
+                tool("getHTTPResponse")
+                    .with_case(CasePolicy::Snake)
+                    .unwrap(),
+                tool("list-items.v2")
+                    .with_case(CasePolicy::Preserve)
+                    .unwrap(),
+                tool("delete"),
+            ],
+        );
+
+        assert_eq!(tool_set.tools[0].fn_name, "get_http_response");
+        assert_eq!(tool_set.tools[1].fn_name, "list_items_v2");
+        assert_eq!(tool_set.tools[2].fn_name, "delete_");
+
+        // function names map back to the original tool names
+        assert_eq!(
+            tool_set.find_fn("Api", "get_http_response").unwrap().name,
+            "getHTTPResponse"
+        );
+        assert_eq!(
+            tool_set.find_fn("Api", "list_items_v2").unwrap().name,
+            "list-items.v2"
+        );
+        assert!(tool_set.find_fn("Api", "getHttpResponse").is_none());
+
+        let code = tool_set.namespace();
+        assert!(code.contains("export async function get_http_response("));
+        assert!(code.contains(r#"toolName: "getHTTPResponse""#));
+        assert!(code.contains(r#"toolName: "delete""#));
+    }
+
+    #[test]
+    fn test_deprecated_and_hidden_tools() {
+        let tool_set = ToolSet::new(
+            "github",
+            "",
+            vec![
+                tool("search_code").with_deprecated("Use searchCodeV2 instead"),
+                tool("get_issue").with_deprecated(""),
+                tool("debug_dump").with_hidden(true),
+            ],
+        );
+
+        let listed = tool_set.namespace_interface(false);
+        assert!(listed.contains("* @deprecated Use searchCodeV2 instead\n"));
+        assert!(listed.contains("* @deprecated\n"));
+        assert!(!listed.contains("debugDump"));
+
+        // hidden tools are still callable
+        assert!(
+            tool_set
+                .namespace()
+                .contains("export async function debugDump(")
+        );
+        assert_eq!(tool_set.visible_tools().count(), 2);
+    }
+
+    #[test]
+    fn test_inferred_output() {
+        let listed = tool("drive_list_files")
+            .with_category("drive", "list_files")
+            .with_inferred_output(
+                serde_json::from_value(json!({
+                    "type": "object",
+                    "properties": {"files": {"type": "array", "items": {"type": "string"}}},
+                    "required": ["files"]
+                }))
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert!(listed.output_inferred);
+        assert_eq!(listed.fn_name, "listFiles");
+        assert_eq!(listed.category.as_deref(), Some("Drive"));
+        assert_ne!(listed.output_signature, "any");
+        assert!(listed.types.contains("files: string[]"), "{}", listed.types);
+    }
+
+    #[tokio::test]
+    async fn test_nested_namespaces() {
+        let tool_set = ToolSet::new(
+            "google",
+            "",
+            vec![
+                tool("drive_list_files").with_category("drive", "list_files"),
+                tool("gmail_send").with_category("gmail", "send"),
+                tool("search"),
+            ],
+        )
+        .with_namespace("google.workspace");
+
+        assert_eq!(tool_set.namespace, "Google.Workspace");
+        assert_eq!(
+            tool_set.tool_namespace(&tool_set.tools[0]),
+            "Google.Workspace.Drive"
+        );
+        assert_eq!(tool_set.tools[0].fn_name, "listFiles");
+        assert_eq!(
+            tool_set.tool_namespace(&tool_set.tools[2]),
+            "Google.Workspace"
+        );
+
+        let code = format!(
+            "declare function callMCPTool<T>(call: {{ serverName: string; toolName: string; arguments: any }}): Promise<T>;
+
+{namespace}
+
+await Google.Workspace.Drive.listFiles({{}});
+await Google.Workspace.Gmail.send({{}});
+await Google.Workspace.search({{}});
+export {{}};",
+            namespace = tool_set.namespace()
+        );
+        let check_res = type_check(&code).await.expect("failed typecheck");
+        assert!(check_res.success, "{code}\n{check_res:?}");
+
+        // calls dispatch on the server & original tool names
+        assert!(code.contains(r#"serverName: "google""#));
+        assert!(code.contains(r#"toolName: "drive_list_files""#));
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     CodegenResult, SchemaDefinitions, generate_docstring,
     schema_type::{ObjectSchemaType, SchemaType},
-    utils::get_description,
+    utils::get_docs,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,7 +32,7 @@ impl ObjectSchemaData {
             let prop_data = ObjectPropertyData {
                 name: prop_name.clone(),
                 sig: prop_st.type_signature(required, defs)?,
-                doc_string: get_description(&prop_schema.clone().into_object(), defs)?
+                doc_string: get_docs(&prop_schema.clone().into_object(), defs)?
                     .map(|desc| generate_docstring(&desc)),
                 required,
                 nullable: prop_st.is_nullable(),
@@ -53,8 +53,7 @@ impl ObjectSchemaData {
 
         Ok(Self {
             name: obj_st.type_name.clone(),
-            doc_string: get_description(&obj_st.schema_obj, defs)?
-                .map(|desc| generate_docstring(&desc)),
+            doc_string: get_docs(&obj_st.schema_obj, defs)?.map(|desc| generate_docstring(&desc)),
             properties,
             additional_props_sig,
         })
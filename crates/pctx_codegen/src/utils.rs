@@ -46,6 +46,58 @@ pub fn get_description(
     }
 }
 
+/// JSDoc content of a schema: the description, the documented values of enums
+/// (`oneOf`/`anyOf` of `const` values with descriptions), then `@default` & `@example` tags.
+/// In the case of a ref, the referenced schema's docs are used for missing parts.
+pub fn get_docs(obj: &SchemaObject, defs: &SchemaDefinitions) -> CodegenResult<Option<String>> {
+    let followed = if let SchemaType::Reference(ref_type) = SchemaType::from(obj) {
+        Some(ref_type.follow(defs)?.into_object())
+    } else {
+        None
+    };
+    let schemas: Vec<&SchemaObject> = std::iter::once(obj).chain(followed.as_ref()).collect();
+
+    let mut lines = vec![];
+    if let Some(desc) = get_description(obj, defs)? {
+        lines.push(desc);
+    }
+
+    if let Some(subschemas) = schemas.iter().find_map(|s| s.subschemas.as_ref()) {
+        let variants = subschemas
+            .one_of
+            .iter()
+            .chain(subschemas.any_of.iter())
+            .flatten();
+        for variant in variants {
+            let Schema::Object(variant) = variant else {
+                continue;
+            };
+            let value = variant.const_value.as_ref().or(variant
+                .enum_values
+                .as_ref()
+                .filter(|values| values.len() == 1)
+                .and_then(|values| values.first()));
+            let desc = variant
+                .metadata
+                .as_ref()
+                .and_then(|m| m.description.as_ref());
+            if let (Some(value), Some(desc)) = (value, desc) {
+                lines.push(format!("- `{value}`: {desc}"));
+            }
+        }
+    }
+
+    let metadata = || schemas.iter().filter_map(|s| s.metadata.as_ref());
+    if let Some(default) = metadata().find_map(|m| m.default.as_ref()) {
+        lines.push(format!("@default {default}"));
+    }
+    if let Some(examples) = metadata().map(|m| &m.examples).find(|e| !e.is_empty()) {
+        lines.extend(examples.iter().map(|example| format!("@example {example}")));
+    }
+
+    Ok((!lines.is_empty()).then(|| lines.join("\n")))
+}
+
 /// Iterates through the provided schema, assigning unique type names recursively
 pub fn assign_type_names(schema: Schema, type_name: &str) -> Schema {
     match SchemaType::from(&schema) {
@@ -169,3 +221,52 @@ fn rebuild_instance_type(typ: InstanceType, nullable: bool) -> SingleOrVec<Insta
         SingleOrVec::Single(Box::new(typ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use schemars::schema::{RootSchema, Schema};
+    use serde_json::json;
+
+    use super::get_docs;
+
+    #[test]
+    fn test_get_docs() {
+        let root: RootSchema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "order": {
+                    "description": "Sort order of the results",
+                    "oneOf": [
+                        { "const": "asc", "description": "Oldest first" },
+                        { "const": "desc", "description": "Newest first" }
+                    ],
+                    "default": "desc"
+                },
+                "query": {
+                    "type": "string",
+                    "examples": ["repo:pctx is:open", "label:bug"]
+                },
+                "limit": { "type": "integer" }
+            }
+        }))
+        .unwrap();
+        let props = &root.schema.object.as_ref().unwrap().properties;
+        let docs = |prop: &str| {
+            let Schema::Object(obj) = &props[prop] else {
+                panic!("expected schema object");
+            };
+            get_docs(obj, &IndexMap::new()).unwrap()
+        };
+
+        assert_eq!(
+            docs("order").unwrap(),
+            "Sort order of the results\n- `\"asc\"`: Oldest first\n- `\"desc\"`: Newest first\n@default \"desc\""
+        );
+        assert_eq!(
+            docs("query").unwrap(),
+            "@example \"repo:pctx is:open\"\n@example \"label:bug\""
+        );
+        assert_eq!(docs("limit"), None);
+    }
+}
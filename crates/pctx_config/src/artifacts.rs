@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Quotas & expiry of the artifacts executions store with `pctx.artifacts`
+///
+/// Artifacts are kept per session, executions put intermediate data there for later
+/// executions instead of returning it to the LLM.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArtifactsConfig {
+    /// Maximum size of a single artifact in bytes, JSON artifacts are measured serialized
+    #[serde(default = "crate::defaults::default_artifacts_max_artifact_bytes")]
+    pub max_artifact_bytes: usize,
+    /// Maximum size of all artifacts of a session in bytes
+    #[serde(default = "crate::defaults::default_artifacts_max_total_bytes")]
+    pub max_total_bytes: usize,
+    /// Seconds after which an artifact expires, counted from when it was put
+    #[serde(default = "crate::defaults::default_artifacts_ttl_secs")]
+    pub ttl_secs: u64,
+}
+
+impl Default for ArtifactsConfig {
+    fn default() -> Self {
+        Self {
+            max_artifact_bytes: crate::defaults::default_artifacts_max_artifact_bytes(),
+            max_total_bytes: crate::defaults::default_artifacts_max_total_bytes(),
+            ttl_secs: crate::defaults::default_artifacts_ttl_secs(),
+        }
+    }
+}
+
+impl ArtifactsConfig {
+    /// Time an artifact is kept after it was put
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+}
@@ -1,31 +1,272 @@
-use std::{fmt::Display, process::Stdio, str::FromStr};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    process::Stdio,
+    str::FromStr,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use subtle::ConstantTimeEq;
 use tokio::process::Command;
 use tracing::debug;
 
+/// Provides the HTTP headers authenticating requests to an MCP server
+#[async_trait]
+pub trait AuthProvider: Debug + Send + Sync {
+    /// Resolves the headers to send, obtaining a new token if the cached one expired
+    ///
+    /// # Errors
+    ///
+    /// This will error if secrets can not be resolved or a token can not be obtained
+    async fn headers(&self) -> Result<Vec<(String, String)>>;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum AuthConfig {
     /// Bearer token
-    Bearer { token: SecretString },
+    Bearer(BearerAuth),
     /// Custom headers
     #[serde(alias = "custom")] // "custom" alias for backwards compat.
-    Headers {
-        headers: IndexMap<String, SecretString>,
-    },
-    // TODO: support OAuth client credentials flow?
-    // /// OAuth 2.1 Client Credentials Flow (machine-to-machine)
-    // #[serde(rename = "oauth_client_credentials")]
-    // OAuthClientCredentials {
-    //     client_id: SecretString,
-    //     client_secret: SecretString,
-    //     token_url: url::Url,
-    //     #[serde(skip_serializing_if = "Option::is_none")]
-    //     scope: Option<String>,
-    // },
+    Headers(HeadersAuth),
+    /// HTTP basic auth
+    Basic(BasicAuth),
+    /// OAuth 2.1 Client Credentials Flow (machine-to-machine)
+    #[serde(rename = "oauth_client_credentials")]
+    OAuthClientCredentials(OAuthClientCredentialsAuth),
+    /// Bearer token printed by an external command
+    Exec(ExecAuth),
+}
+
+impl AuthConfig {
+    /// Returns the provider resolving the headers of this auth configuration
+    pub fn provider(&self) -> &dyn AuthProvider {
+        match self {
+            AuthConfig::Bearer(p) => p,
+            AuthConfig::Headers(p) => p,
+            AuthConfig::Basic(p) => p,
+            AuthConfig::OAuthClientCredentials(p) => p,
+            AuthConfig::Exec(p) => p,
+        }
+    }
+
+    /// Name of the auth type as written in the `type` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AuthConfig::Bearer(_) => "bearer",
+            AuthConfig::Headers(_) => "headers",
+            AuthConfig::Basic(_) => "basic",
+            AuthConfig::OAuthClientCredentials(_) => "oauth_client_credentials",
+            AuthConfig::Exec(_) => "exec",
+        }
+    }
+
+    /// Secret strings of this auth configuration
+    pub fn secrets(&self) -> Vec<&SecretString> {
+        match self {
+            AuthConfig::Bearer(p) => vec![&p.token],
+            AuthConfig::Headers(p) => p.headers.values().collect(),
+            AuthConfig::Basic(p) => vec![&p.username, &p.password],
+            AuthConfig::OAuthClientCredentials(p) => vec![&p.client_id, &p.client_secret],
+            AuthConfig::Exec(_) => vec![],
+        }
+    }
+
+    /// Keychain keys referenced by the secrets of this auth configuration
+    pub fn keychain_keys(&self) -> Vec<String> {
+        self.secrets()
+            .into_iter()
+            .flat_map(SecretString::keychain_keys)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for AuthConfig {
+    async fn headers(&self) -> Result<Vec<(String, String)>> {
+        self.provider().headers().await
+    }
+}
+
+/// Static bearer token, `Bearer ` prefix is added automatically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BearerAuth {
+    pub token: SecretString,
+}
+
+#[async_trait]
+impl AuthProvider for BearerAuth {
+    async fn headers(&self) -> Result<Vec<(String, String)>> {
+        Ok(vec![(
+            "Authorization".into(),
+            format!("Bearer {}", self.token.resolve().await?),
+        )])
+    }
+}
+
+/// Header templates, values may embed secrets like `"Token ${env:API_TOKEN}"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadersAuth {
+    pub headers: IndexMap<String, SecretString>,
+}
+
+#[async_trait]
+impl AuthProvider for HeadersAuth {
+    async fn headers(&self) -> Result<Vec<(String, String)>> {
+        let mut headers = Vec::with_capacity(self.headers.len());
+        for (name, val) in &self.headers {
+            headers.push((name.clone(), val.resolve().await?));
+        }
+        Ok(headers)
+    }
+}
+
+/// HTTP basic auth
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BasicAuth {
+    pub username: SecretString,
+    pub password: SecretString,
+}
+
+#[async_trait]
+impl AuthProvider for BasicAuth {
+    async fn headers(&self) -> Result<Vec<(String, String)>> {
+        use base64::{Engine as _, engine::general_purpose};
+
+        let credentials = format!(
+            "{}:{}",
+            self.username.resolve().await?,
+            self.password.resolve().await?
+        );
+        Ok(vec![(
+            "Authorization".into(),
+            format!("Basic {}", general_purpose::STANDARD.encode(credentials)),
+        )])
+    }
+}
+
+/// OAuth 2.1 client credentials flow, the access token is cached until it expires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientCredentialsAuth {
+    pub client_id: SecretString,
+    pub client_secret: SecretString,
+    pub token_url: url::Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+#[async_trait]
+impl AuthProvider for OAuthClientCredentialsAuth {
+    async fn headers(&self) -> Result<Vec<(String, String)>> {
+        let key = format!("oauth:{}:{}", self.token_url, self.client_id);
+        let token = cached_token(key, || async {
+            let client_id = self.client_id.resolve().await?;
+            let client_secret = self.client_secret.resolve().await?;
+            let mut form = vec![
+                ("grant_type", "client_credentials"),
+                ("client_id", &client_id),
+                ("client_secret", &client_secret),
+            ];
+            if let Some(scope) = &self.scope {
+                form.push(("scope", scope));
+            }
+
+            let res: TokenResponse = reqwest::Client::new()
+                .post(self.token_url.clone())
+                .form(&form)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .with_context(|| format!("OAuth token request to {} failed", self.token_url))?
+                .json()
+                .await
+                .with_context(|| format!("Invalid OAuth token response from {}", self.token_url))?;
+
+            let ttl = res
+                .expires_in
+                .unwrap_or_else(crate::defaults::default_oauth_token_ttl_secs);
+            Ok((res.access_token, Duration::from_secs(ttl)))
+        })
+        .await?;
+
+        Ok(vec![("Authorization".into(), format!("Bearer {token}"))])
+    }
+}
+
+/// Bearer token printed by an external command (e.g. `gcloud auth print-access-token`),
+/// the command is run again once the token is older than `refresh_secs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecAuth {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_secs: Option<u64>,
+}
+
+#[async_trait]
+impl AuthProvider for ExecAuth {
+    async fn headers(&self) -> Result<Vec<(String, String)>> {
+        let refresh_secs = self
+            .refresh_secs
+            .unwrap_or_else(crate::defaults::default_exec_auth_refresh_secs);
+        let token = cached_token(format!("exec:{}", self.command), || async {
+            let token = AuthSecret::Command(self.command.clone()).resolve().await?;
+            Ok((token, Duration::from_secs(refresh_secs)))
+        })
+        .await?;
+
+        Ok(vec![("Authorization".into(), format!("Bearer {token}"))])
+    }
+}
+
+/// Tokens are refreshed this long before they expire
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+struct CachedToken {
+    token: String,
+    expires_at: Instant,
+}
+
+/// Tokens of refreshing providers shared by all connections
+static TOKEN_CACHE: LazyLock<Mutex<HashMap<String, CachedToken>>> = LazyLock::new(Mutex::default);
+
+/// Returns the cached token for the key, or fetches a `(token, ttl)` if there is none or it expired
+async fn cached_token<F, Fut>(key: String, fetch: F) -> Result<String>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<(String, Duration)>>,
+{
+    let cached = TOKEN_CACHE
+        .lock()
+        .unwrap()
+        .get(&key)
+        .filter(|c| c.expires_at > Instant::now())
+        .map(|c| c.token.clone());
+    if let Some(token) = cached {
+        return Ok(token);
+    }
+
+    let (token, ttl) = fetch().await?;
+    debug!(provider = %key, ttl_secs = ttl.as_secs(), "Obtained auth token");
+    TOKEN_CACHE.lock().unwrap().insert(
+        key,
+        CachedToken {
+            token: token.clone(),
+            expires_at: Instant::now() + ttl.saturating_sub(TOKEN_EXPIRY_MARGIN),
+        },
+    );
+
+    Ok(token)
 }
 
 /// A string that may contain 0 or more embedded secrets
@@ -337,6 +578,21 @@ pub fn write_to_keychain(key: &str, val: &str) -> Result<()> {
     Ok(())
 }
 
+/// Checks if a value is stored in the system keychain
+///
+/// # Errors
+/// This function fails if keyring is unable to access the local system's keychain
+pub fn keychain_contains(key: &str) -> Result<bool> {
+    let entry: keyring::Entry =
+        keyring::Entry::new("pctx", key).context("Failed to create keychain entry")?;
+
+    match entry.get_password() {
+        Ok(_) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => anyhow::bail!(e),
+    }
+}
+
 /// Removes a value stored in the system keychain as a password
 ///
 /// # Errors
@@ -356,6 +612,16 @@ pub fn remove_from_keychain(key: &str) -> Result<()> {
     Ok(())
 }
 
+/// Returns `true` if the `token` sent by a client is one of the `accepted` tokens
+///
+/// Tokens are compared in constant time, so response times do not leak how much of a
+/// token was guessed correctly.
+pub fn token_accepted<'a>(accepted: impl IntoIterator<Item = &'a str>, token: &str) -> bool {
+    accepted.into_iter().fold(false, |found, t| {
+        found | bool::from(t.as_bytes().ct_eq(token.as_bytes()))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -591,4 +857,86 @@ mod tests {
         // Clean up
         entry.delete_credential().unwrap();
     }
+
+    #[tokio::test]
+    async fn test_auth_providers() {
+        let cfg: AuthConfig = serde_json::from_value(serde_json::json!({
+            "type": "basic",
+            "username": "ada",
+            "password": "secret"
+        }))
+        .unwrap();
+        assert_eq!(
+            cfg.headers().await.unwrap(),
+            vec![("Authorization".into(), "Basic YWRhOnNlY3JldA==".into())]
+        );
+
+        let cfg: AuthConfig = serde_json::from_value(serde_json::json!({
+            "type": "custom",
+            "headers": { "x-api-key": "key-123" }
+        }))
+        .unwrap();
+        assert_eq!(
+            cfg.headers().await.unwrap(),
+            vec![("x-api-key".into(), "key-123".into())]
+        );
+
+        let cfg: AuthConfig = serde_json::from_value(serde_json::json!({
+            "type": "exec",
+            "command": "echo exec-token"
+        }))
+        .unwrap();
+        assert_eq!(
+            cfg.headers().await.unwrap(),
+            vec![("Authorization".into(), "Bearer exec-token".into())]
+        );
+
+        let cfg: AuthConfig = serde_json::from_value(serde_json::json!({
+            "type": "oauth_client_credentials",
+            "client_id": "client",
+            "client_secret": "${env:OAUTH_SECRET}",
+            "token_url": "https://auth.example.com/token",
+            "scope": "mcp"
+        }))
+        .unwrap();
+        assert!(matches!(cfg, AuthConfig::OAuthClientCredentials(_)));
+    }
+
+    #[tokio::test]
+    async fn test_cached_token_refresh() {
+        let key = "test:cached_token_refresh".to_string();
+        let first = cached_token(key.clone(), || async {
+            Ok(("first".into(), Duration::from_mins(10)))
+        })
+        .await
+        .unwrap();
+        let cached = cached_token(key.clone(), || async {
+            Ok(("second".into(), Duration::from_mins(10)))
+        })
+        .await
+        .unwrap();
+        assert_eq!((first.as_str(), cached.as_str()), ("first", "first"));
+
+        // tokens expiring within the margin are refreshed on the next use
+        let key = "test:cached_token_expired".to_string();
+        cached_token(key.clone(), || async {
+            Ok(("old".into(), TOKEN_EXPIRY_MARGIN))
+        })
+        .await
+        .unwrap();
+        let refreshed = cached_token(key, || async {
+            Ok(("new".into(), Duration::from_mins(10)))
+        })
+        .await
+        .unwrap();
+        assert_eq!(refreshed, "new");
+    }
+
+    #[test]
+    fn test_token_accepted() {
+        assert!(token_accepted(["first", "second"], "second"));
+        assert!(!token_accepted(["first", "second"], "sec"));
+        assert!(!token_accepted(["first", "second"], "second!"));
+        assert!(!token_accepted([], ""));
+    }
 }
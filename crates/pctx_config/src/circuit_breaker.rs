@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Circuit breaker policy for upstream MCP servers
+///
+/// After `failure_threshold` consecutive failed tool calls (connection errors,
+/// transport failures or timeouts) the circuit opens and calls to the server
+/// fail fast. After `cooldown_secs` a single probe call is let through, closing
+/// the circuit again if it succeeds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures before the circuit opens, `0` disables the circuit breaker
+    #[serde(default = "crate::defaults::default_circuit_breaker_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Seconds the circuit stays open before a probe call is allowed
+    #[serde(default = "crate::defaults::default_circuit_breaker_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: crate::defaults::default_circuit_breaker_failure_threshold(),
+            cooldown_secs: crate::defaults::default_circuit_breaker_cooldown_secs(),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn enabled(&self) -> bool {
+        self.failure_threshold > 0
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CircuitBreakerConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let cfg: CircuitBreakerConfig = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(cfg, CircuitBreakerConfig::default());
+        assert!(cfg.enabled());
+
+        let disabled: CircuitBreakerConfig =
+            serde_json::from_value(json!({ "failure_threshold": 0 })).unwrap();
+        assert!(!disabled.enabled());
+        assert_eq!(disabled.cooldown_secs, 30);
+    }
+}
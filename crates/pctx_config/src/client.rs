@@ -0,0 +1,379 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rmcp::{
+    ClientHandler, ErrorData, RoleClient,
+    model::{
+        ClientCapabilities, ClientInfo, CreateElicitationRequestParams, CreateElicitationResult,
+        CreateMessageRequestMethod, CreateMessageRequestParams, CreateMessageResult,
+        ElicitationAction, ElicitationCapability, ElicitationCreateRequestMethod, Implementation,
+        JsonObject, ListRootsRequestMethod, ListRootsResult, ProgressNotificationParam,
+        ProtocolVersion, RootsCapabilities,
+    },
+    service::{NotificationContext, RequestContext, RunningService},
+};
+use serde::Serialize;
+use tracing::debug;
+
+pub use rmcp::model::Root;
+
+/// Connected MCP client of an upstream server
+pub type McpClient = RunningService<RoleClient, PctxClient>;
+
+/// Handles `sampling/createMessage` requests, i.e. LLM completions upstream
+/// servers request from pctx during a tool call
+#[async_trait]
+pub trait SamplingHandler: Debug + Send + Sync {
+    /// Creates the message requested by the upstream server
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the completion failed or was declined, it is
+    /// returned to the upstream server
+    async fn create_message(
+        &self,
+        server: &str,
+        params: CreateMessageRequestParams,
+    ) -> Result<CreateMessageResult>;
+}
+
+/// Handles `elicitation/create` requests, i.e. user input upstream servers
+/// request during a tool call (e.g. a confirmation)
+#[async_trait]
+pub trait ElicitationHandler: Debug + Send + Sync {
+    /// Asks the user for the input requested by the upstream server
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user could not be asked, it is returned to the
+    /// upstream server. Declining or cancelling is a successful result.
+    async fn create_elicitation(
+        &self,
+        server: &str,
+        params: CreateElicitationRequestParams,
+    ) -> Result<CreateElicitationResult>;
+}
+
+/// Handles `notifications/progress` upstream servers send during long-running tool calls
+#[async_trait]
+pub trait ProgressHandler: Debug + Send + Sync {
+    /// Receives a progress update of a tool call, `params.progress_token` is the token
+    /// pctx sent with the call
+    async fn on_progress(&self, server: &str, params: ProgressNotificationParam);
+}
+
+/// Tool call of a tool configured with `requires_approval`, awaiting the user's decision
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    pub server: String,
+    pub tool: String,
+    pub arguments: Option<JsonObject>,
+}
+
+/// Decides whether calls of tools configured with `requires_approval` may proceed,
+/// e.g. by prompting the user
+#[async_trait]
+pub trait ApprovalHandler: Debug + Send + Sync {
+    /// Returns `true` if the call is allowed, the execution waits for the decision
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user could not be asked, the call is rejected
+    async fn approve(&self, request: &ApprovalRequest) -> Result<bool>;
+}
+
+/// Handlers of requests upstream servers send to pctx
+#[derive(Debug, Clone, Default)]
+pub struct ClientHandlers {
+    pub sampling: Option<Arc<dyn SamplingHandler>>,
+    pub elicitation: Option<Arc<dyn ElicitationHandler>>,
+    /// Progress of tool calls is only requested if set
+    pub progress: Option<Arc<dyn ProgressHandler>>,
+    /// Workspace roots returned for `roots/list` requests, the roots capability is
+    /// only offered if set
+    pub roots: Option<Vec<Root>>,
+    /// Approves calls of tools configured with `requires_approval`, the elicitation
+    /// handler asks the user if unset
+    pub approval: Option<Arc<dyn ApprovalHandler>>,
+    /// Client the handlers reach (e.g. its MCP session id), long-lived connections are
+    /// only shared by handlers of the same owner so requests reach the calling client
+    pub owner: Option<String>,
+}
+
+impl ClientHandlers {
+    #[must_use]
+    pub fn with_sampling(mut self, handler: Arc<dyn SamplingHandler>) -> Self {
+        self.sampling = Some(handler);
+        self
+    }
+
+    #[must_use]
+    pub fn with_elicitation(mut self, handler: Arc<dyn ElicitationHandler>) -> Self {
+        self.elicitation = Some(handler);
+        self
+    }
+
+    #[must_use]
+    pub fn with_progress(mut self, handler: Arc<dyn ProgressHandler>) -> Self {
+        self.progress = Some(handler);
+        self
+    }
+
+    #[must_use]
+    pub fn with_roots(mut self, roots: Vec<Root>) -> Self {
+        self.roots = Some(roots);
+        self
+    }
+
+    #[must_use]
+    pub fn with_approval(mut self, handler: Arc<dyn ApprovalHandler>) -> Self {
+        self.approval = Some(handler);
+        self
+    }
+
+    #[must_use]
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Asks for approval of a tool call with the approval handler, or the elicitation
+    /// handler if no approval handler is set. Calls are denied if neither is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the handler failed to ask for approval
+    pub async fn request_approval(&self, request: &ApprovalRequest) -> Result<bool> {
+        if let Some(handler) = &self.approval {
+            return handler.approve(request).await;
+        }
+        let Some(handler) = &self.elicitation else {
+            debug!(
+                server = %request.server,
+                tool = %request.tool,
+                "No handler to approve tool call, denying"
+            );
+            return Ok(false);
+        };
+
+        let arguments = serde_json::to_string(&request.arguments.clone().unwrap_or_default())?;
+        let params: CreateElicitationRequestParams = serde_json::from_value(serde_json::json!({
+            "message": format!(
+                "Allow calling `{}` with arguments {arguments}?",
+                request.tool
+            ),
+            "requestedSchema": { "type": "object", "properties": {} },
+        }))?;
+        let result = handler.create_elicitation(&request.server, params).await?;
+
+        Ok(matches!(result.action, ElicitationAction::Accept))
+    }
+}
+
+/// MCP client handler of the connection to an upstream server, only
+/// advertising the capabilities pctx has handlers for
+///
+/// The handlers can be replaced on long-lived connections, the capabilities
+/// are negotiated once when connecting.
+#[derive(Debug, Clone)]
+pub struct PctxClient {
+    server: String,
+    handlers: Arc<RwLock<ClientHandlers>>,
+}
+
+impl PctxClient {
+    pub fn new(server: &str, handlers: ClientHandlers) -> Self {
+        Self {
+            server: server.into(),
+            handlers: Arc::new(RwLock::new(handlers)),
+        }
+    }
+
+    /// Replaces the handlers of requests of the upstream server
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn set_handlers(&self, handlers: ClientHandlers) {
+        *self.handlers.write().unwrap() = handlers;
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn handlers(&self) -> ClientHandlers {
+        self.handlers.read().unwrap().clone()
+    }
+}
+
+impl ClientHandler for PctxClient {
+    fn get_info(&self) -> ClientInfo {
+        let handlers = self.handlers();
+        let mut capabilities = ClientCapabilities::default();
+        if handlers.sampling.is_some() {
+            capabilities.sampling = Some(JsonObject::default());
+        }
+        if handlers.elicitation.is_some() {
+            capabilities.elicitation = Some(ElicitationCapability::default());
+        }
+        if handlers.roots.is_some() {
+            capabilities.roots = Some(RootsCapabilities::default());
+        }
+
+        ClientInfo {
+            protocol_version: ProtocolVersion::default(),
+            capabilities,
+            client_info: Implementation {
+                name: "pctx-client".to_string(),
+                version: option_env!("CARGO_PKG_VERSION")
+                    .unwrap_or("0.1.0")
+                    .to_string(),
+                ..Default::default()
+            },
+            meta: None,
+        }
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        let Some(handler) = self.handlers().sampling else {
+            return Err(ErrorData::method_not_found::<CreateMessageRequestMethod>());
+        };
+
+        debug!(server = %self.server, "Handling sampling request of MCP server");
+        handler
+            .create_message(&self.server, params)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))
+    }
+
+    async fn create_elicitation(
+        &self,
+        params: CreateElicitationRequestParams,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateElicitationResult, ErrorData> {
+        let Some(handler) = self.handlers().elicitation else {
+            return Err(ErrorData::method_not_found::<ElicitationCreateRequestMethod>());
+        };
+
+        debug!(server = %self.server, "Handling elicitation request of MCP server");
+        handler
+            .create_elicitation(&self.server, params)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("{e:#}"), None))
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        let Some(roots) = self.handlers().roots else {
+            return Err(ErrorData::method_not_found::<ListRootsRequestMethod>());
+        };
+
+        Ok(ListRootsResult { roots })
+    }
+
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        if let Some(handler) = self.handlers().progress {
+            handler.on_progress(&self.server, params).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use rmcp::{
+        ClientHandler,
+        model::{CreateMessageRequestParams, CreateMessageResult},
+    };
+
+    use super::{ApprovalHandler, ApprovalRequest, ClientHandlers, PctxClient, SamplingHandler};
+
+    #[derive(Debug)]
+    struct Declining;
+
+    #[async_trait]
+    impl SamplingHandler for Declining {
+        async fn create_message(
+            &self,
+            _server: &str,
+            _params: CreateMessageRequestParams,
+        ) -> anyhow::Result<CreateMessageResult> {
+            anyhow::bail!("declined")
+        }
+    }
+
+    #[derive(Debug)]
+    struct AllowReads;
+
+    #[async_trait]
+    impl ApprovalHandler for AllowReads {
+        async fn approve(&self, request: &ApprovalRequest) -> anyhow::Result<bool> {
+            Ok(request.tool.starts_with("read_"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_approval() {
+        let request = |tool: &str| ApprovalRequest {
+            server: "upstream".into(),
+            tool: tool.into(),
+            arguments: None,
+        };
+
+        // denied without any handler to ask
+        let handlers = ClientHandlers::default();
+        assert!(
+            !handlers
+                .request_approval(&request("read_file"))
+                .await
+                .unwrap()
+        );
+
+        let handlers = ClientHandlers::default().with_approval(Arc::new(AllowReads));
+        assert!(
+            handlers
+                .request_approval(&request("read_file"))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !handlers
+                .request_approval(&request("delete_file"))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sampling_capability() {
+        let client = PctxClient::new("upstream", ClientHandlers::default());
+        assert!(client.get_info().capabilities.sampling.is_none());
+
+        let client = PctxClient::new(
+            "upstream",
+            ClientHandlers::default().with_sampling(Arc::new(Declining)),
+        );
+        assert!(client.get_info().capabilities.sampling.is_some());
+        assert!(client.get_info().capabilities.elicitation.is_none());
+        assert!(client.get_info().capabilities.roots.is_none());
+
+        let client = PctxClient::new("upstream", ClientHandlers::default().with_roots(vec![]));
+        assert!(client.get_info().capabilities.roots.is_some());
+    }
+}
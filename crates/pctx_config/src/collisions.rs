@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Resolution of namespaces or function names produced by more than one registration
+///
+/// Collisions are detected when servers & callbacks are registered, the later
+/// registration is resolved according to the `strategy`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollisionConfig {
+    #[serde(default)]
+    pub strategy: CollisionStrategy,
+    /// New namespaces keyed by server name (or callback namespace) & new function
+    /// names keyed by `<server name>.<tool name>`, used by the `rename` strategy
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rename: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionStrategy {
+    /// Fail the registration
+    #[default]
+    Error,
+    /// Suffix the colliding namespace or function name with the server name
+    Suffix,
+    /// Rename according to the `rename` map, failing collisions it does not cover
+    Rename,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CollisionConfig, CollisionStrategy};
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_collision_config() {
+        let cfg: CollisionConfig = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(cfg, CollisionConfig::default());
+        assert_eq!(cfg.strategy, CollisionStrategy::Error);
+
+        let cfg: CollisionConfig = serde_json::from_value(json!({
+            "strategy": "rename",
+            "rename": { "github_enterprise": "GithubEnterprise", "github.get_me": "whoAmI" }
+        }))
+        .unwrap();
+        assert_eq!(cfg.strategy, CollisionStrategy::Rename);
+        assert_eq!(cfg.rename["github.get_me"], "whoAmI");
+
+        assert!(serde_json::from_value::<CollisionConfig>(json!({"strategy": "ignore"})).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Limits of concurrent code executions of the MCP server
+///
+/// At most `max_executions` run at once, further executions wait in a queue of at
+/// most `queue_depth` executions. Queued executions are started round robin across
+/// clients (MCP sessions), so a client sending many executions can't starve others.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConcurrencyConfig {
+    /// Executions running at once, `0` disables the limit
+    #[serde(default = "crate::defaults::default_max_executions")]
+    pub max_executions: usize,
+    /// Executions waiting for a slot, further executions are rejected
+    #[serde(default = "crate::defaults::default_execution_queue_depth")]
+    pub queue_depth: usize,
+    /// Executions a single client runs at once, defaults to `max_executions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_per_client: Option<usize>,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            max_executions: crate::defaults::default_max_executions(),
+            queue_depth: crate::defaults::default_execution_queue_depth(),
+            max_per_client: None,
+        }
+    }
+}
+
+impl ConcurrencyConfig {
+    pub fn enabled(&self) -> bool {
+        self.max_executions > 0
+    }
+
+    /// Executions a single client runs at once, at least one and at most `max_executions`
+    pub fn max_per_client(&self) -> usize {
+        self.max_per_client
+            .unwrap_or(self.max_executions)
+            .clamp(1, self.max_executions.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConcurrencyConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let cfg: ConcurrencyConfig = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(cfg, ConcurrencyConfig::default());
+        assert!(cfg.enabled());
+        assert_eq!(cfg.max_per_client(), cfg.max_executions);
+
+        let cfg: ConcurrencyConfig =
+            serde_json::from_value(json!({ "max_executions": 4, "max_per_client": 10 })).unwrap();
+        assert_eq!(cfg.max_per_client(), 4);
+        assert_eq!(cfg.queue_depth, 64);
+
+        let disabled: ConcurrencyConfig =
+            serde_json::from_value(json!({ "max_executions": 0 })).unwrap();
+        assert!(!disabled.enabled());
+    }
+}
@@ -17,3 +17,97 @@ pub(crate) fn default_sampling_rate() -> f64 {
 pub(crate) fn default_timeout_ms() -> u64 {
     10000
 }
+
+pub(crate) fn default_retry_initial_backoff_ms() -> u64 {
+    200
+}
+
+pub(crate) fn default_retry_max_backoff_ms() -> u64 {
+    5000
+}
+
+pub(crate) fn default_circuit_breaker_failure_threshold() -> u32 {
+    5
+}
+
+pub(crate) fn default_circuit_breaker_cooldown_secs() -> u64 {
+    30
+}
+
+pub(crate) fn default_max_executions() -> usize {
+    16
+}
+
+pub(crate) fn default_execution_queue_depth() -> usize {
+    64
+}
+
+pub(crate) fn default_pool_max_idle() -> usize {
+    8
+}
+
+pub(crate) fn default_pool_idle_timeout_secs() -> u64 {
+    90
+}
+
+pub(crate) fn default_pool_keep_alive_secs() -> u64 {
+    60
+}
+
+pub(crate) fn default_stdio_idle_timeout_secs() -> u64 {
+    300
+}
+
+pub(crate) fn default_stdio_restart_backoff_ms() -> u64 {
+    500
+}
+
+pub(crate) const STDIO_MAX_RESTART_BACKOFF_MS: u64 = 30_000;
+
+pub(crate) fn default_exec_auth_refresh_secs() -> u64 {
+    300
+}
+
+pub(crate) fn default_oauth_token_ttl_secs() -> u64 {
+    3600
+}
+
+pub(crate) fn default_grouping_separator() -> String {
+    "_".into()
+}
+
+pub(crate) fn default_connect_timeout_secs() -> u64 {
+    30
+}
+
+pub(crate) fn default_type_check_target() -> String {
+    "ES2020".into()
+}
+
+pub(crate) fn default_imports_registry() -> String {
+    "https://esm.sh".into()
+}
+
+pub(crate) fn default_artifacts_max_artifact_bytes() -> usize {
+    8 * 1024 * 1024
+}
+
+pub(crate) fn default_artifacts_max_total_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+pub(crate) fn default_artifacts_ttl_secs() -> u64 {
+    3600
+}
+
+pub(crate) fn default_results_max_results() -> usize {
+    16
+}
+
+pub(crate) fn default_kv_max_value_bytes() -> usize {
+    1024 * 1024
+}
+
+pub(crate) fn default_kv_max_namespace_bytes() -> usize {
+    16 * 1024 * 1024
+}
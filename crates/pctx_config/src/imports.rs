@@ -0,0 +1,208 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Module imports allowed in executed code
+///
+/// `npm:` and `jsr:` specifiers are loaded as ES modules from the `registry`
+/// (an esm.sh compatible CDN), `https:` specifiers are loaded directly. Imports are
+/// only allowed if they match an `allow` pattern, including the imports of loaded
+/// modules: modules of the registry are mapped back to the package they serve, so an
+/// allowed package may import its own modules but not other packages.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportsConfig {
+    /// Allowed import specifiers, e.g. `npm:lodash`, `jsr:@std/path` or `https://deno.land/std/*`.
+    /// A pattern matches any version & subpath of the package, a trailing `*` matches any suffix.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Directory to cache loaded modules in, modules are fetched on every execution if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<Utf8PathBuf>,
+    /// CDN serving `npm:` and `jsr:` packages as ES modules
+    #[serde(default = "crate::defaults::default_imports_registry")]
+    pub registry: String,
+}
+
+impl Default for ImportsConfig {
+    fn default() -> Self {
+        Self {
+            allow: vec![],
+            cache_dir: None,
+            registry: crate::defaults::default_imports_registry(),
+        }
+    }
+}
+
+impl ImportsConfig {
+    pub fn enabled(&self) -> bool {
+        !self.allow.is_empty()
+    }
+
+    /// Whether the import specifier matches an `allow` pattern
+    ///
+    /// `npm:` and `jsr:` specifiers with query strings, fragments, escapes or `.`/`..`
+    /// segments are never allowed, other specifiers are matched as normalized URLs.
+    pub fn allows(&self, specifier: &str) -> bool {
+        let normalized = if let Some(url) = self.registry_url(specifier) {
+            let package = specifier
+                .split_once(':')
+                .map_or(specifier, |(_, package)| package);
+            if !is_clean_package(package) {
+                return false;
+            }
+            // the package the registry serves at the URL, e.g. `npm:lodash/../evil` -> `npm:evil`
+            match Url::parse(&url)
+                .ok()
+                .and_then(|u| self.registry_specifier(&u))
+            {
+                Some(normalized) => normalized,
+                None => return false,
+            }
+        } else {
+            match Url::parse(specifier) {
+                Ok(url) => url.to_string(),
+                Err(_) => return false,
+            }
+        };
+
+        self.allow
+            .iter()
+            .any(|pattern| matches_pattern(pattern, &normalized))
+    }
+
+    /// Whether the module at `url` may be loaded, e.g. when imported by another module
+    ///
+    /// URLs of the `registry` are allowed if the package they serve is allowed, other
+    /// URLs if they match an `https:` pattern.
+    pub fn allows_url(&self, url: &Url) -> bool {
+        if url.scheme() != "https" {
+            return false;
+        }
+        if self.allows(url.as_str()) {
+            return true;
+        }
+
+        url.query().is_none()
+            && url.fragment().is_none()
+            && self
+                .registry_specifier(url)
+                .is_some_and(|specifier| self.allows(&specifier))
+    }
+
+    /// `npm:` or `jsr:` specifier of the package module the registry serves at `url`,
+    /// `None` for URLs outside of the registry
+    fn registry_specifier(&self, url: &Url) -> Option<String> {
+        let registry = Url::parse(&self.registry).ok()?;
+        if url.origin() != registry.origin() {
+            return None;
+        }
+        let path = url
+            .path()
+            .strip_prefix(registry.path().trim_end_matches('/'))?
+            .strip_prefix('/')?;
+        if path.is_empty() {
+            return None;
+        }
+
+        Some(match path.strip_prefix("jsr/") {
+            Some(package) => format!("jsr:{package}"),
+            None => format!("npm:{path}"),
+        })
+    }
+
+    /// URL an `npm:` or `jsr:` specifier is loaded from, `None` for other specifiers
+    pub fn registry_url(&self, specifier: &str) -> Option<String> {
+        let registry = self.registry.trim_end_matches('/');
+        if let Some(package) = specifier.strip_prefix("npm:") {
+            Some(format!("{registry}/{}", package.trim_start_matches('/')))
+        } else {
+            specifier
+                .strip_prefix("jsr:")
+                .map(|package| format!("{registry}/jsr/{}", package.trim_start_matches('/')))
+        }
+    }
+}
+
+/// Whether the package of an `npm:` or `jsr:` specifier only names a package, version
+/// & subpath, without segments escaping it or query parameters changing what is served
+fn is_clean_package(package: &str) -> bool {
+    !package.contains(['?', '#', '%', '\\'])
+        && package
+            .trim_start_matches('/')
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."))
+}
+
+fn matches_pattern(pattern: &str, specifier: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        specifier.starts_with(prefix)
+    } else {
+        specifier
+            .strip_prefix(pattern)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['@', '/']))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImportsConfig;
+    use serde_json::json;
+    use url::Url;
+
+    #[test]
+    fn test_allows() {
+        let cfg: ImportsConfig = serde_json::from_value(json!({
+            "allow": ["npm:lodash", "jsr:@std/path", "https://deno.land/std/*"]
+        }))
+        .unwrap();
+        assert!(cfg.enabled());
+
+        assert!(cfg.allows("npm:lodash"));
+        assert!(cfg.allows("npm:lodash@4.17.21"));
+        assert!(cfg.allows("npm:lodash/fp"));
+        assert!(!cfg.allows("npm:lodash-es"));
+        assert!(cfg.allows("jsr:@std/path@1"));
+        assert!(!cfg.allows("jsr:@std/fs"));
+        assert!(cfg.allows("https://deno.land/std/path/mod.ts"));
+        assert!(!cfg.allows("https://example.com/mod.js"));
+
+        // specifiers escaping the allowed package
+        assert!(!cfg.allows("npm:lodash/../evil-pkg"));
+        assert!(!cfg.allows("npm:lodash/%2e%2e/evil-pkg"));
+        assert!(!cfg.allows("npm:lodash/./fp"));
+        assert!(!cfg.allows("npm:lodash//evil-pkg"));
+        assert!(!cfg.allows("npm:lodash?alias=lodash:evil-pkg"));
+        assert!(!cfg.allows("npm:lodash#evil"));
+        assert!(!cfg.allows("jsr:@std/path/../../@evil/pkg"));
+        assert!(!cfg.allows("https://deno.land/std/../x/evil/mod.ts"));
+
+        // imports of loaded modules are limited to the allowed packages
+        let url = |u: &str| Url::parse(u).unwrap();
+        assert!(cfg.allows_url(&url("https://esm.sh/lodash@4.17.21/es2022/lodash.mjs")));
+        assert!(cfg.allows_url(&url("https://esm.sh/jsr/@std/path@1/mod.js")));
+        assert!(!cfg.allows_url(&url("https://esm.sh/evil-pkg@1/es2022/evil-pkg.mjs")));
+        assert!(!cfg.allows_url(&url("https://esm.sh/lodash/../evil-pkg")));
+        assert!(!cfg.allows_url(&url("https://esm.sh/lodash@4?alias=lodash:evil-pkg")));
+        assert!(!cfg.allows_url(&url("https://esm.sh/")));
+        assert!(cfg.allows_url(&url("https://deno.land/std/path/mod.ts")));
+        assert!(!cfg.allows_url(&url("https://deno.land/x/evil/mod.ts")));
+        assert!(!cfg.allows_url(&url("http://deno.land/std/path/mod.ts")));
+
+        assert!(!ImportsConfig::default().enabled());
+    }
+
+    #[test]
+    fn test_registry_url() {
+        let cfg = ImportsConfig::default();
+        assert_eq!(
+            cfg.registry_url("npm:lodash@4").as_deref(),
+            Some("https://esm.sh/lodash@4")
+        );
+        assert_eq!(
+            cfg.registry_url("jsr:@std/path").as_deref(),
+            Some("https://esm.sh/jsr/@std/path")
+        );
+        assert_eq!(cfg.registry_url("https://deno.land/x/mod.ts"), None);
+    }
+}
@@ -0,0 +1,73 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// Persistent key-value store executions remember state in with `pctx.kv`
+///
+/// Entries are kept in a `SQLite` database & grouped in namespaces, every namespace has its
+/// own size quota. `pctx.kv` is disabled unless `path` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KvConfig {
+    /// `SQLite` database of the store, created if it does not exist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<Utf8PathBuf>,
+    /// Maximum size of a single value in bytes, measured serialized
+    #[serde(default = "crate::defaults::default_kv_max_value_bytes")]
+    pub max_value_bytes: usize,
+    /// Maximum size of the keys & values of a namespace in bytes
+    #[serde(default = "crate::defaults::default_kv_max_namespace_bytes")]
+    pub max_namespace_bytes: usize,
+    /// Seconds after which entries expire unless set with their own TTL, entries are kept
+    /// until deleted if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+    /// Quotas & TTLs of individual namespaces, keyed by namespace
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespaces: BTreeMap<String, KvNamespaceConfig>,
+}
+
+/// Overrides of the quota & TTL of a namespace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KvNamespaceConfig {
+    /// Maximum size of the keys & values of the namespace in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<usize>,
+    /// Seconds after which entries of the namespace expire unless set with their own TTL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+}
+
+impl Default for KvConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_value_bytes: crate::defaults::default_kv_max_value_bytes(),
+            max_namespace_bytes: crate::defaults::default_kv_max_namespace_bytes(),
+            ttl_secs: None,
+            namespaces: BTreeMap::new(),
+        }
+    }
+}
+
+impl KvConfig {
+    /// Maximum size of the keys & values of the namespace
+    pub fn max_bytes(&self, namespace: &str) -> usize {
+        self.namespaces
+            .get(namespace)
+            .and_then(|n| n.max_bytes)
+            .unwrap_or(self.max_namespace_bytes)
+    }
+
+    /// Time entries of the namespace are kept when set without their own TTL, `None` if
+    /// they do not expire
+    pub fn ttl(&self, namespace: &str) -> Option<Duration> {
+        self.namespaces
+            .get(namespace)
+            .and_then(|n| n.ttl_secs)
+            .or(self.ttl_secs)
+            .map(Duration::from_secs)
+    }
+}
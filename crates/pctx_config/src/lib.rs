@@ -2,16 +2,38 @@ use anyhow::{Context, Result};
 use camino::Utf8PathBuf;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::fs;
+use std::{collections::BTreeMap, fs};
 use tracing::debug;
 
-use crate::{logger::LoggerConfig, server::ServerConfig, telemetry::TelemetryConfig};
+use crate::{
+    artifacts::ArtifactsConfig, circuit_breaker::CircuitBreakerConfig, collisions::CollisionConfig,
+    concurrency::ConcurrencyConfig, imports::ImportsConfig, kv::KvConfig, logger::LoggerConfig,
+    profile::ProfileConfig, prompt::PromptConfig, results::ResultsConfig, retry::RetryConfig,
+    script::ScriptConfig, server::ServerConfig, skill::SkillConfig, telemetry::TelemetryConfig,
+    type_check::TypeCheckConfig,
+};
 
+pub mod artifacts;
 pub mod auth;
+pub mod circuit_breaker;
+pub mod client;
+pub mod collisions;
+pub mod concurrency;
 pub(crate) mod defaults;
+pub mod imports;
+pub mod kv;
 pub mod logger;
+pub mod pool;
+pub mod profile;
+pub mod prompt;
+pub mod results;
+pub mod retry;
+pub mod script;
 pub mod server;
+pub mod skill;
 pub mod telemetry;
+pub mod tls;
+pub mod type_check;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -33,6 +55,78 @@ pub struct Config {
     #[serde(default)]
     pub servers: Vec<ServerConfig>,
 
+    /// Saved scripts, each exposed as an MCP tool of its own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<ScriptConfig>,
+
+    /// Functions learned from successful executions, callable by executed code as
+    /// `Skills.<name>(input)`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<SkillConfig>,
+
+    /// Named subsets of the servers, each served at `/mcp/{profile}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+
+    /// Default retry policy for upstream tool calls, servers can override it
+    #[serde(default, skip_serializing_if = "is_default_retry")]
+    pub retry: RetryConfig,
+
+    /// Timeout in seconds for connecting to an upstream server and listing its tools.
+    /// Servers are connected concurrently, so this bounds the total startup time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Default circuit breaker policy for upstream servers, servers can override it
+    #[serde(default, skip_serializing_if = "is_default_circuit_breaker")]
+    pub circuit_breaker: CircuitBreakerConfig,
+
+    /// Expose the `check_code` tool, letting agents type check code before executing it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub check_code_tool: bool,
+
+    /// Keep MCP sessions over streamable HTTP, every client session gets its own service
+    /// and server initiated requests (elicitation, roots) reach the client
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stateful_mode: bool,
+
+    /// Allow executions to pass MCP servers connected for the request in addition to the
+    /// configured servers
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ephemeral_servers: bool,
+
+    /// Quotas & expiry of the artifacts stored by executions with `pctx.artifacts`
+    #[serde(default, skip_serializing_if = "is_default_artifacts")]
+    pub artifacts: ArtifactsConfig,
+
+    /// Offloading of execution outputs too large to return, see `pctx.results.fetch`
+    #[serde(default, skip_serializing_if = "is_default_results")]
+    pub results: ResultsConfig,
+
+    /// Persistent key-value store executions remember state in with `pctx.kv`
+    #[serde(default, skip_serializing_if = "is_default_kv")]
+    pub kv: KvConfig,
+
+    /// TypeScript compiler options used to type check code before executing it
+    #[serde(default, skip_serializing_if = "is_default_type_check")]
+    pub type_check: TypeCheckConfig,
+
+    /// `npm:`, `jsr:` and `https:` module imports allowed in executed code
+    #[serde(default, skip_serializing_if = "is_default_imports")]
+    pub imports: ImportsConfig,
+
+    /// Limits of concurrent code executions & their fair scheduling across clients
+    #[serde(default, skip_serializing_if = "is_default_concurrency")]
+    pub concurrency: ConcurrencyConfig,
+
+    /// Resolution of namespaces or function names produced by more than one server
+    #[serde(default, skip_serializing_if = "is_default_collisions")]
+    pub collisions: CollisionConfig,
+
+    /// System prompt preamble generated for agents, see `pctx mcp prompt`
+    #[serde(default, skip_serializing_if = "is_default_prompt")]
+    pub prompt: PromptConfig,
+
     /// MCP server logger configuration
     #[serde(default)]
     pub logger: LoggerConfig,
@@ -46,6 +140,46 @@ fn default_version() -> String {
     "0.1.0".into()
 }
 
+fn is_default_retry(retry: &RetryConfig) -> bool {
+    *retry == RetryConfig::default()
+}
+
+fn is_default_circuit_breaker(circuit_breaker: &CircuitBreakerConfig) -> bool {
+    *circuit_breaker == CircuitBreakerConfig::default()
+}
+
+fn is_default_concurrency(concurrency: &ConcurrencyConfig) -> bool {
+    *concurrency == ConcurrencyConfig::default()
+}
+
+fn is_default_artifacts(artifacts: &ArtifactsConfig) -> bool {
+    *artifacts == ArtifactsConfig::default()
+}
+
+fn is_default_results(results: &ResultsConfig) -> bool {
+    *results == ResultsConfig::default()
+}
+
+fn is_default_kv(kv: &KvConfig) -> bool {
+    *kv == KvConfig::default()
+}
+
+fn is_default_prompt(prompt: &PromptConfig) -> bool {
+    *prompt == PromptConfig::default()
+}
+
+fn is_default_type_check(type_check: &TypeCheckConfig) -> bool {
+    *type_check == TypeCheckConfig::default()
+}
+
+fn is_default_imports(imports: &ImportsConfig) -> bool {
+    *imports == ImportsConfig::default()
+}
+
+fn is_default_collisions(collisions: &CollisionConfig) -> bool {
+    *collisions == CollisionConfig::default()
+}
+
 impl Config {
     #[must_use]
     pub fn with_path(mut self, path: &Utf8PathBuf) -> Self {
@@ -128,9 +262,33 @@ impl Config {
             .context(format!("Server '{name}' not found"))?;
 
         self.servers.remove(index);
+        for profile in self.profiles.values_mut() {
+            profile.servers.retain(|s| s != name);
+        }
         Ok(())
     }
 
+    /// Timeout in seconds for connecting to each upstream server, defaults to 30 seconds
+    pub fn connect_timeout_secs(&self) -> u64 {
+        self.connect_timeout_secs
+            .unwrap_or_else(crate::defaults::default_connect_timeout_secs)
+    }
+
+    /// Upstream server configurations with config-wide defaults applied
+    /// (e.g. the retry & circuit breaker policies) for servers that don't override them
+    pub fn upstream_servers(&self) -> Vec<ServerConfig> {
+        self.servers
+            .iter()
+            .cloned()
+            .map(|mut s| {
+                s.retry.get_or_insert_with(|| self.retry.clone());
+                s.circuit_breaker
+                    .get_or_insert_with(|| self.circuit_breaker.clone());
+                s
+            })
+            .collect()
+    }
+
     pub fn get_server(&self, name: &str) -> Option<&ServerConfig> {
         self.servers.iter().find(|s| s.name == name)
     }
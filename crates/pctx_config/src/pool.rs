@@ -0,0 +1,175 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use http::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+use crate::tls::TlsConfig;
+
+/// Connection pool of the HTTP client connecting to an MCP server
+///
+/// The client is shared by all connections to the server, so TCP connections and
+/// TLS sessions are reused across tool calls and executions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PoolConfig {
+    /// Idle connections kept open to the server, defaults to `8`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_idle: Option<usize>,
+    /// Seconds idle connections are kept open, defaults to `90`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
+    /// Interval of keep-alive probes of open connections in seconds, defaults to `60`, `0` disables them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_alive_secs: Option<u64>,
+    /// Only speak HTTP/2, also over plain HTTP. HTTPS servers negotiate HTTP/2 by default.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub http2_only: bool,
+}
+
+impl PoolConfig {
+    pub fn max_idle(&self) -> usize {
+        self.max_idle
+            .unwrap_or_else(crate::defaults::default_pool_max_idle)
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.idle_timeout_secs
+                .unwrap_or_else(crate::defaults::default_pool_idle_timeout_secs),
+        )
+    }
+
+    /// Interval of keep-alive probes, `None` if they are disabled
+    pub fn keep_alive(&self) -> Option<Duration> {
+        match self
+            .keep_alive_secs
+            .unwrap_or_else(crate::defaults::default_pool_keep_alive_secs)
+        {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Applies the pool options to the HTTP client builder
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        builder = builder
+            .pool_max_idle_per_host(self.max_idle())
+            .pool_idle_timeout(self.idle_timeout())
+            .tcp_keepalive(self.keep_alive());
+        if let Some(interval) = self.keep_alive() {
+            builder = builder
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_while_idle(true);
+        }
+        if self.http2_only {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder
+    }
+}
+
+struct PooledClient {
+    fingerprint: u64,
+    client: reqwest::Client,
+}
+
+/// HTTP clients of MCP servers shared by all connections, keyed by server name & URL
+static HTTP_CLIENTS: LazyLock<Mutex<HashMap<String, PooledClient>>> = LazyLock::new(Mutex::default);
+
+/// Returns the pooled HTTP client of the server, building it if there is none yet or
+/// its headers, TLS or pool options changed (e.g. after an auth token was refreshed)
+///
+/// # Errors
+///
+/// This will error if the TLS options can not be applied or the client can not be built
+///
+/// # Panics
+///
+/// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+pub(crate) fn http_client(
+    key: &str,
+    headers: HeaderMap,
+    tls: Option<&TlsConfig>,
+    pool: Option<&PoolConfig>,
+) -> Result<reqwest::Client> {
+    let fingerprint = fingerprint(&headers, tls, pool);
+    if let Some(pooled) = HTTP_CLIENTS.lock().unwrap().get(key)
+        && pooled.fingerprint == fingerprint
+    {
+        return Ok(pooled.client.clone());
+    }
+
+    let mut builder = reqwest::Client::builder().default_headers(headers);
+    if let Some(tls) = tls {
+        builder = tls.apply(builder)?;
+    }
+    let client = pool.cloned().unwrap_or_default().apply(builder).build()?;
+    HTTP_CLIENTS.lock().unwrap().insert(
+        key.into(),
+        PooledClient {
+            fingerprint,
+            client: client.clone(),
+        },
+    );
+    Ok(client)
+}
+
+fn fingerprint(headers: &HeaderMap, tls: Option<&TlsConfig>, pool: Option<&PoolConfig>) -> u64 {
+    let mut pairs: Vec<(&str, &[u8])> = headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_bytes()))
+        .collect();
+    pairs.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    pairs.hash(&mut hasher);
+    tls.hash(&mut hasher);
+    pool.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::{HeaderMap, HeaderValue};
+    use serde_json::json;
+
+    use super::{PoolConfig, fingerprint};
+
+    #[test]
+    fn test_pool_config() {
+        let cfg: PoolConfig = serde_json::from_value(json!({
+            "max_idle": 2,
+            "keep_alive_secs": 0,
+            "http2_only": true
+        }))
+        .unwrap();
+        assert_eq!(cfg.max_idle(), 2);
+        assert_eq!(cfg.idle_timeout(), Duration::from_secs(90));
+        assert_eq!(cfg.keep_alive(), None);
+        assert!(serde_json::from_value::<PoolConfig>(json!({ "size": 2 })).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer a"));
+        let before = fingerprint(&headers, None, None);
+        assert_eq!(before, fingerprint(&headers.clone(), None, None));
+
+        assert_ne!(
+            before,
+            fingerprint(&headers, None, Some(&PoolConfig::default()))
+        );
+
+        headers.insert("authorization", HeaderValue::from_static("Bearer b"));
+        assert_ne!(before, fingerprint(&headers, None, None));
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::Config;
+
+/// Named subset of the configured servers, served at `/mcp/{profile}` next to `/mcp`
+///
+/// Each profile gets its own code mode, so agents using a profile only see the
+/// functions of its servers and executed code can only reach the hosts of its servers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Description of the profile's MCP server, defaults to the root `description`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Names of the root `servers` the profile exposes
+    #[serde(default)]
+    pub servers: Vec<String>,
+    /// Names of the root `scripts` the profile exposes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<String>,
+    /// Expose the `check_code` tool, defaults to the root `check_code_tool`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_code_tool: Option<bool>,
+}
+
+impl Config {
+    /// Configuration of the named profile: the root configuration restricted to the
+    /// profile's servers & scripts, named `<name>-<profile>`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile does not exist or names servers or scripts that are
+    /// not configured
+    pub fn profile(&self, name: &str) -> Result<Self> {
+        let profile = self
+            .profiles
+            .get(name)
+            .context(format!("Profile '{name}' not found"))?;
+
+        let servers = profile
+            .servers
+            .iter()
+            .map(|server| {
+                self.get_server(server)
+                    .cloned()
+                    .context(format!("Server '{server}' of profile '{name}' not found"))
+            })
+            .collect::<Result<_>>()?;
+        if let Some(script) = profile
+            .scripts
+            .iter()
+            .find(|s| !self.scripts.iter().any(|script| script.name == **s))
+        {
+            anyhow::bail!("Script '{script}' of profile '{name}' not found");
+        }
+
+        let mut cfg = self.clone();
+        cfg.name = format!("{}-{name}", self.name);
+        cfg.description = profile
+            .description
+            .clone()
+            .or_else(|| self.description.clone());
+        cfg.servers = servers;
+        cfg.scripts
+            .retain(|script| profile.scripts.contains(&script.name));
+        cfg.check_code_tool = profile.check_code_tool.unwrap_or(self.check_code_tool);
+        cfg.profiles.clear();
+        Ok(cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::Config;
+
+    #[test]
+    fn test_profile() {
+        let cfg: Config = serde_json::from_value(json!({
+            "name": "pctx",
+            "description": "All tools",
+            "servers": [
+                { "name": "github", "url": "https://api.githubcopilot.com/mcp/" },
+                { "name": "notion", "url": "https://mcp.notion.com/mcp" }
+            ],
+            "scripts": [
+                { "name": "open_tickets", "code": "async function run() { return []; }" },
+                { "name": "create_repo", "code": "async function run() { return null; }" }
+            ],
+            "profiles": {
+                "support": { "servers": ["notion"], "scripts": ["open_tickets"], "check_code_tool": true },
+                "broken": { "servers": ["slack"] },
+                "unknown_script": { "servers": ["notion"], "scripts": ["deploy"] }
+            }
+        }))
+        .unwrap();
+
+        let support = cfg.profile("support").unwrap();
+        assert_eq!(support.name, "pctx-support");
+        assert_eq!(support.description.as_deref(), Some("All tools"));
+        assert_eq!(
+            support
+                .servers
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>(),
+            ["notion"]
+        );
+        assert_eq!(support.scripts.len(), 1);
+        assert_eq!(support.scripts[0].name, "open_tickets");
+        assert!(support.check_code_tool);
+        assert!(support.profiles.is_empty());
+
+        let err = cfg.profile("broken").unwrap_err().to_string();
+        assert!(err.contains("Server 'slack'"), "{err}");
+        let err = cfg.profile("unknown_script").unwrap_err().to_string();
+        assert!(err.contains("Script 'deploy'"), "{err}");
+        assert!(cfg.profile("missing").is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// System prompt preamble recommended to agents using code mode, see `pctx mcp prompt`
+///
+/// The built-in preamble lists the available namespaces and explains the conventions of
+/// executed code & how to handle failed tool calls.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptConfig {
+    /// Template replacing the built-in preamble, `{{namespaces}}`, `{{conventions}}`,
+    /// `{{error_handling}}` & `{{instructions}}` are replaced by their sections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Additional instructions of the host application, listed after the built-in sections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructions: Vec<String>,
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// Offloading of large execution outputs
+///
+/// Outputs larger than `offload_threshold_bytes` are kept by the server and replaced by a
+/// handle & a summary of their shape, later executions page into them with
+/// `pctx.results.fetch(handle, path)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResultsConfig {
+    /// Serialized size in bytes above which outputs are offloaded, outputs are always
+    /// returned in full if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offload_threshold_bytes: Option<usize>,
+    /// Number of offloaded outputs kept, the oldest are dropped first
+    #[serde(default = "crate::defaults::default_results_max_results")]
+    pub max_results: usize,
+}
+
+impl Default for ResultsConfig {
+    fn default() -> Self {
+        Self {
+            offload_threshold_bytes: None,
+            max_results: crate::defaults::default_results_max_results(),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Retry policy for failed upstream MCP tool calls
+///
+/// Connection failures are retried, as well as JSON-RPC errors returned by the upstream
+/// with a code in `retry_on_codes`. Transport errors and timeouts are only retried for
+/// tools configured as `idempotent`, as the upstream may have received the call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    /// Maximum number of retries after the first failed attempt
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds, doubled for every following retry
+    #[serde(default = "crate::defaults::default_retry_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Upper bound of the delay between retries in milliseconds
+    #[serde(default = "crate::defaults::default_retry_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    /// JSON-RPC error codes returned by the upstream server that should be retried
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_on_codes: Vec<i32>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff_ms: crate::defaults::default_retry_initial_backoff_ms(),
+            max_backoff_ms: crate::defaults::default_retry_max_backoff_ms(),
+            retry_on_codes: vec![],
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay before the given retry (starting at 0) using exponential backoff
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(2_u64.saturating_pow(retry))
+            .min(self.max_backoff_ms);
+        Duration::from_millis(delay)
+    }
+
+    /// Whether a JSON-RPC error code returned by the upstream should be retried
+    pub fn retries_code(&self, code: i32) -> bool {
+        self.retry_on_codes.contains(&code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let retry = RetryConfig {
+            max_retries: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 500,
+            retry_on_codes: vec![],
+        };
+
+        assert_eq!(retry.backoff(0), Duration::from_millis(100));
+        assert_eq!(retry.backoff(1), Duration::from_millis(200));
+        assert_eq!(retry.backoff(2), Duration::from_millis(400));
+        assert_eq!(retry.backoff(3), Duration::from_millis(500));
+        assert_eq!(retry.backoff(64), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let retry: RetryConfig =
+            serde_json::from_value(json!({ "max_retries": 3, "retry_on_codes": [-32603] }))
+                .unwrap();
+
+        assert_eq!(retry.max_retries, 3);
+        assert_eq!(retry.initial_backoff_ms, 200);
+        assert_eq!(retry.max_backoff_ms, 5000);
+        assert!(retry.retries_code(-32603));
+        assert!(!retry.retries_code(-32602));
+    }
+}
@@ -0,0 +1,54 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Saved TypeScript snippet exposed as an MCP tool of its own
+///
+/// The code defines `async function run(input)` like code passed to `execute`, the
+/// arguments of a call of the tool are its `input`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptConfig {
+    /// Name of the MCP tool
+    pub name: String,
+    /// Description of the MCP tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Code of the script, either `code` or `file` is required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// File containing the code of the script, relative to the config file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<Utf8PathBuf>,
+    /// JSON schema of the input of the script, any object is accepted if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<Value>,
+}
+
+impl ScriptConfig {
+    /// Code of the script, reading `file` relative to `dir`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither or both of `code` and `file` are set, or the file
+    /// cannot be read
+    pub fn source(&self, dir: &Utf8Path) -> Result<String> {
+        match (&self.code, &self.file) {
+            (Some(code), None) => Ok(code.clone()),
+            (None, Some(file)) => {
+                let path = dir.join(file);
+                fs::read_to_string(&path).context(format!(
+                    "Failed reading code of script '{}': {path}",
+                    self.name
+                ))
+            }
+            _ => anyhow::bail!(
+                "Script '{}' must set exactly one of `code` and `file`",
+                self.name
+            ),
+        }
+    }
+}
@@ -1,10 +1,7 @@
 use http::{HeaderMap, HeaderName, HeaderValue};
 use rmcp::{
-    RoleClient, ServiceExt,
-    model::{
-        ClientCapabilities, ClientInfo, Implementation, InitializeRequestParams, ProtocolVersion,
-    },
-    service::{ClientInitializeError, RunningService},
+    ServiceExt,
+    service::ClientInitializeError,
     transport::{
         StreamableHttpClientTransport,
         child_process::{ConfigureCommandExt, TokioChildProcess},
@@ -13,24 +10,72 @@ use rmcp::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::process::Stdio;
 use std::str::FromStr;
-use tokio::process::Command;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{ChildStderr, Command};
+use tracing::info;
 
 pub use rmcp::ServiceError;
 
-use super::auth::AuthConfig;
+use super::auth::{AuthConfig, AuthProvider};
+use crate::{
+    circuit_breaker::CircuitBreakerConfig,
+    client::{ClientHandlers, McpClient, PctxClient},
+    pool::PoolConfig,
+    retry::RetryConfig,
+    tls::TlsConfig,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub name: String,
     #[serde(flatten)]
     pub transport: ServerTransport,
+    /// Per-tool configuration keyed by the upstream tool name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, ToolConfig>,
+    /// Retry policy for tool calls, overrides the config-wide retry policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    /// Circuit breaker policy, overrides the config-wide circuit breaker policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Load tools from a schema snapshot on startup and only connect on the first tool call
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lazy: bool,
+    /// Namespace of the server's functions, dotted for nested namespaces (e.g. `Google.Workspace`).
+    /// Defaults to the `PascalCase` server name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Groups tools into nested category namespaces by their name prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grouping: Option<GroupingConfig>,
+    /// Case conversion of tool names into function names, defaults to `camelCase`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<NameCase>,
+    /// Infer return types of tools without an output schema from the results of calls
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub infer_output_schemas: bool,
+}
+
+/// Case conversion of tool names into function names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NameCase {
+    /// Keep tool names as is, replacing characters not allowed in identifiers with `_`
+    #[serde(rename = "preserve")]
+    Preserve,
+    #[serde(rename = "camelCase")]
+    CamelCase,
+    #[serde(rename = "snake_case")]
+    SnakeCase,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ServerTransport {
-    Http(HttpServerConfig),
+    Http(Box<HttpServerConfig>),
     Stdio(StdioServerConfig),
 }
 
@@ -40,6 +85,12 @@ pub struct HttpServerConfig {
     pub url: url::Url,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auth: Option<AuthConfig>,
+    /// Custom root CAs, mutual TLS and certificate verification of the connection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+    /// Connection pool of the HTTP client shared by all connections to the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool: Option<PoolConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,13 +101,135 @@ pub struct StdioServerConfig {
     pub args: Vec<String>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub env: BTreeMap<String, String>,
+    /// Seconds a supervised process may be idle before it is shut down, `0` keeps it running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
+    /// Delay before restarting a crashed process in milliseconds, doubled for every consecutive crash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_backoff_ms: Option<u64>,
+}
+
+impl StdioServerConfig {
+    /// Idle period after which a supervised process is shut down, `None` if it is kept running
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        match self
+            .idle_timeout_secs
+            .unwrap_or_else(crate::defaults::default_stdio_idle_timeout_secs)
+        {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Delay before restarting a process that crashed `crashes` consecutive times
+    pub fn restart_backoff(&self, crashes: u32) -> Duration {
+        let initial = self
+            .restart_backoff_ms
+            .unwrap_or_else(crate::defaults::default_stdio_restart_backoff_ms);
+        let delay = initial
+            .saturating_mul(2_u64.saturating_pow(crashes.saturating_sub(1)))
+            .min(crate::defaults::STDIO_MAX_RESTART_BACKOFF_MS);
+        Duration::from_millis(delay)
+    }
+}
+
+/// Configuration of a single upstream tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolConfig {
+    /// Cache results of identical calls to this tool, only enable for idempotent tools
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<ToolCacheConfig>,
+    /// Nested namespace of this tool within the server namespace (e.g. `Drive`),
+    /// takes precedence over the server's grouping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Marks the tool deprecated in the generated interfaces, `true` or the reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<Deprecation>,
+    /// Excludes the tool from function listings & search, it can still be called
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// Calls of this tool must be approved by the user before they are sent upstream
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub requires_approval: bool,
+    /// Maximum calls of this tool per minute across all executions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_per_minute: Option<u32>,
+    /// Maximum calls of this tool within a single execution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_calls_per_execution: Option<u32>,
+    /// Calls of this tool can safely be sent again, so calls that timed out or lost
+    /// their connection are retried even though the upstream may have received them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub idempotent: bool,
+}
+
+/// Deprecation of a tool, either `true` or the reason shown to agents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Deprecation {
+    Flag(bool),
+    Reason(String),
+}
+
+impl Deprecation {
+    /// Reason the tool is deprecated for, empty if none is given.
+    /// `None` if the tool is not deprecated.
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Deprecation::Flag(true) => Some(""),
+            Deprecation::Flag(false) => None,
+            Deprecation::Reason(reason) => Some(reason),
+        }
+    }
+}
+
+/// Rule grouping the tools of a server into nested category namespaces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupingConfig {
+    /// Separator between the category & the function name of tool names,
+    /// e.g. with `_` the tool `drive_list_files` becomes `Drive.listFiles`
+    #[serde(default = "crate::defaults::default_grouping_separator")]
+    pub separator: String,
+}
+
+impl Default for GroupingConfig {
+    fn default() -> Self {
+        Self {
+            separator: crate::defaults::default_grouping_separator(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolCacheConfig {
+    /// Reuse cached results across executions for this many seconds.
+    /// When unset results are only reused within a single execution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
 }
 
 impl ServerConfig {
     pub fn new(name: String, url: url::Url) -> Self {
         Self {
             name,
-            transport: ServerTransport::Http(HttpServerConfig { url, auth: None }),
+            transport: ServerTransport::Http(Box::new(HttpServerConfig {
+                url,
+                auth: None,
+                tls: None,
+                pool: None,
+            })),
+            tools: BTreeMap::new(),
+            retry: None,
+            circuit_breaker: None,
+            lazy: false,
+            namespace: None,
+            grouping: None,
+            case: None,
+            infer_output_schemas: false,
         }
     }
 
@@ -68,20 +241,34 @@ impl ServerConfig {
     ) -> Self {
         Self {
             name,
-            transport: ServerTransport::Stdio(StdioServerConfig { command, args, env }),
+            transport: ServerTransport::Stdio(StdioServerConfig {
+                command,
+                args,
+                env,
+                idle_timeout_secs: None,
+                restart_backoff_ms: None,
+            }),
+            tools: BTreeMap::new(),
+            retry: None,
+            circuit_breaker: None,
+            lazy: false,
+            namespace: None,
+            grouping: None,
+            case: None,
+            infer_output_schemas: false,
         }
     }
 
     pub fn http(&self) -> Option<&HttpServerConfig> {
         match &self.transport {
-            ServerTransport::Http(cfg) => Some(cfg),
+            ServerTransport::Http(cfg) => Some(cfg.as_ref()),
             ServerTransport::Stdio(_) => None,
         }
     }
 
     pub fn http_mut(&mut self) -> Option<&mut HttpServerConfig> {
         match &mut self.transport {
-            ServerTransport::Http(cfg) => Some(cfg),
+            ServerTransport::Http(cfg) => Some(cfg.as_mut()),
             ServerTransport::Stdio(_) => None,
         }
     }
@@ -99,6 +286,43 @@ impl ServerConfig {
         }
     }
 
+    /// Returns the cache configuration of the tool if its results are cacheable
+    pub fn tool_cache(&self, tool_name: &str) -> Option<&ToolCacheConfig> {
+        self.tools.get(tool_name)?.cache.as_ref()
+    }
+
+    /// Returns the configuration of the tool, if any
+    pub fn tool_config(&self, tool_name: &str) -> Option<&ToolConfig> {
+        self.tools.get(tool_name)
+    }
+
+    /// Returns `true` if calls of the tool must be approved by the user
+    pub fn tool_requires_approval(&self, tool_name: &str) -> bool {
+        self.tools
+            .get(tool_name)
+            .is_some_and(|t| t.requires_approval)
+    }
+
+    /// Returns `true` if calls of the tool may be sent again after timeouts & transport failures
+    pub fn tool_idempotent(&self, tool_name: &str) -> bool {
+        self.tools.get(tool_name).is_some_and(|t| t.idempotent)
+    }
+
+    /// Returns the `(category, function name)` of a tool grouped into a nested namespace,
+    /// `None` if the tool belongs to the server namespace itself
+    pub fn tool_category(&self, tool_name: &str) -> Option<(String, String)> {
+        if let Some(category) = self.tools.get(tool_name).and_then(|t| t.category.as_ref()) {
+            return Some((category.clone(), tool_name.into()));
+        }
+
+        let grouping = self.grouping.as_ref()?;
+        if grouping.separator.is_empty() {
+            return None;
+        }
+        let (category, fn_name) = tool_name.split_once(&grouping.separator)?;
+        (!category.is_empty() && !fn_name.is_empty()).then(|| (category.into(), fn_name.into()))
+    }
+
     pub fn display_target(&self) -> String {
         match &self.transport {
             ServerTransport::Http(cfg) => cfg.url.to_string(),
@@ -118,61 +342,52 @@ impl ServerConfig {
     ///
     /// This function will return an error if unable to connect and send the
     /// initialization request
-    pub async fn connect(
+    pub async fn connect(&self) -> Result<McpClient, McpConnectionError> {
+        self.connect_with(ClientHandlers::default()).await
+    }
+
+    /// Connects to the MCP server, handling requests the server sends back
+    /// (e.g. sampling) with the provided handlers
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if unable to connect and send the
+    /// initialization request
+    pub async fn connect_with(
         &self,
-    ) -> Result<RunningService<RoleClient, InitializeRequestParams>, McpConnectionError> {
-        let init_request = ClientInfo {
-            protocol_version: ProtocolVersion::default(),
-            capabilities: ClientCapabilities::default(),
-            client_info: Implementation {
-                name: "pctx-client".to_string(),
-                version: option_env!("CARGO_PKG_VERSION")
-                    .unwrap_or("0.1.0")
-                    .to_string(),
-                ..Default::default()
-            },
-            meta: None,
-        };
+        handlers: ClientHandlers,
+    ) -> Result<McpClient, McpConnectionError> {
+        let init_request = PctxClient::new(&self.name, handlers);
 
         match &self.transport {
             ServerTransport::Http(http_cfg) => {
                 let mut default_headers = HeaderMap::new();
 
-                // Add auth to http client
+                // Add auth to http client, refreshing providers resolve a new
+                // token on connect once the cached one expired
                 if let Some(a) = &http_cfg.auth {
-                    match a {
-                        AuthConfig::Bearer { token } => {
-                            let resolved = token
-                                .resolve()
-                                .await
-                                .map_err(|e| McpConnectionError::Failed(e.to_string()))?;
-                            default_headers.append(
-                                http::header::AUTHORIZATION,
-                                HeaderValue::from_str(&format!("Bearer {resolved}"))
-                                    .map_err(|e| McpConnectionError::Failed(e.to_string()))?,
-                            );
-                        }
-                        AuthConfig::Headers { headers } => {
-                            for (name, val) in headers {
-                                let resolved = val
-                                    .resolve()
-                                    .await
-                                    .map_err(|e| McpConnectionError::Failed(e.to_string()))?;
-                                default_headers.append(
-                                    HeaderName::from_str(name)
-                                        .map_err(|e| McpConnectionError::Failed(e.to_string()))?,
-                                    HeaderValue::from_str(&resolved)
-                                        .map_err(|e| McpConnectionError::Failed(e.to_string()))?,
-                                );
-                            }
-                        }
+                    let headers = a
+                        .headers()
+                        .await
+                        .map_err(|e| McpConnectionError::Failed(format!("{e:#}")))?;
+                    for (name, val) in headers {
+                        default_headers.append(
+                            HeaderName::from_str(&name)
+                                .map_err(|e| McpConnectionError::Failed(e.to_string()))?,
+                            HeaderValue::from_str(&val)
+                                .map_err(|e| McpConnectionError::Failed(e.to_string()))?,
+                        );
                     }
                 }
 
-                let reqwest_client = reqwest::Client::builder()
-                    .default_headers(default_headers)
-                    .build()
-                    .map_err(|e| McpConnectionError::Failed(e.to_string()))?;
+                // connections & TLS sessions are reused across connects of the server
+                let reqwest_client = crate::pool::http_client(
+                    &format!("{}@{}", self.name, http_cfg.url),
+                    default_headers,
+                    http_cfg.tls.as_ref(),
+                    http_cfg.pool.as_ref(),
+                )
+                .map_err(|e| McpConnectionError::Failed(format!("{e:#}")))?;
 
                 let transport = StreamableHttpClientTransport::with_client(
                     reqwest_client,
@@ -217,15 +432,21 @@ impl ServerConfig {
                     (stdio_cfg.command.clone(), stdio_cfg.args.clone())
                 };
 
-                let transport =
-                    TokioChildProcess::new(Command::new(&cmd).configure(|cmd_builder| {
+                let (transport, stderr) =
+                    TokioChildProcess::builder(Command::new(&cmd).configure(|cmd_builder| {
                         cmd_builder.args(&args);
                         if !stdio_cfg.env.is_empty() {
                             cmd_builder.envs(&stdio_cfg.env);
                         }
                     }))
+                    .stderr(Stdio::piped())
+                    .spawn()
                     .map_err(|e| McpConnectionError::Failed(e.to_string()))?;
 
+                if let Some(stderr) = stderr {
+                    tokio::spawn(log_stderr(self.name.clone(), stderr));
+                }
+
                 init_request
                     .serve(transport)
                     .await
@@ -235,6 +456,14 @@ impl ServerConfig {
     }
 }
 
+/// Routes the stderr output of a stdio MCP server process into the logs
+async fn log_stderr(server: String, stderr: ChildStderr) {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        info!(target: "pctx::stdio", server = %server, "{line}");
+    }
+}
+
 /// Simplified error types for MCP server connection failures
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum McpConnectionError {
@@ -248,7 +477,9 @@ pub enum McpConnectionError {
 
 #[cfg(test)]
 mod tests {
-    use super::ServerConfig;
+    use std::{collections::BTreeMap, time::Duration};
+
+    use super::{NameCase, ServerConfig};
     use serde_json::json;
 
     #[test]
@@ -281,4 +512,122 @@ mod tests {
             Some("development")
         );
     }
+
+    #[test]
+    fn test_deserialize_tool_cache_config() {
+        let payload = json!({
+            "name": "weather",
+            "url": "http://localhost:8080/mcp",
+            "tools": {
+                "get_forecast": { "cache": { "ttl_secs": 60 } },
+                "get_alerts": { "cache": {} },
+                "set_location": {}
+            }
+        });
+        let cfg: ServerConfig = serde_json::from_value(payload).unwrap();
+        assert!(cfg.http().is_some());
+        assert_eq!(cfg.tool_cache("get_forecast").unwrap().ttl_secs, Some(60));
+        assert_eq!(cfg.tool_cache("get_alerts").unwrap().ttl_secs, None);
+        assert!(cfg.tool_cache("set_location").is_none());
+        assert!(cfg.tool_cache("unknown").is_none());
+
+        // empty tool configs are not serialized
+        let cfg = ServerConfig::new("http".into(), "http://localhost:8080/mcp".parse().unwrap());
+        assert!(serde_json::to_value(&cfg).unwrap().get("tools").is_none());
+    }
+
+    #[test]
+    fn test_tool_category() {
+        let payload = json!({
+            "name": "google",
+            "url": "http://localhost:8080/mcp",
+            "namespace": "Google",
+            "grouping": {},
+            "tools": {
+                "send_message": { "category": "Gmail" }
+            }
+        });
+        let cfg: ServerConfig = serde_json::from_value(payload).unwrap();
+        assert_eq!(cfg.namespace.as_deref(), Some("Google"));
+        assert_eq!(cfg.case, None);
+        assert_eq!(
+            cfg.tool_category("drive_list_files"),
+            Some(("drive".into(), "list_files".into()))
+        );
+        assert_eq!(
+            cfg.tool_category("send_message"),
+            Some(("Gmail".into(), "send_message".into()))
+        );
+        assert_eq!(cfg.tool_category("search"), None);
+        assert_eq!(cfg.tool_category("_private"), None);
+
+        // without a grouping only explicit categories apply
+        let cfg = ServerConfig::new("http".into(), "http://localhost:8080/mcp".parse().unwrap());
+        assert_eq!(cfg.tool_category("drive_list_files"), None);
+    }
+
+    #[test]
+    fn test_deserialize_tool_annotations() {
+        let payload = json!({
+            "name": "github",
+            "url": "http://localhost:8080/mcp",
+            "tools": {
+                "search_code": { "deprecated": "Use search_code_v2 instead" },
+                "get_issue": { "deprecated": true },
+                "list_issues": { "deprecated": false },
+                "debug_dump": { "hidden": true }
+            }
+        });
+        let cfg: ServerConfig = serde_json::from_value(payload).unwrap();
+        let reason = |tool: &str| {
+            cfg.tools
+                .get(tool)
+                .and_then(|t| t.deprecated.as_ref())
+                .and_then(|d| d.reason())
+        };
+        assert_eq!(reason("search_code"), Some("Use search_code_v2 instead"));
+        assert_eq!(reason("get_issue"), Some(""));
+        assert_eq!(reason("list_issues"), None);
+        assert_eq!(reason("debug_dump"), None);
+        assert!(cfg.tools["debug_dump"].hidden);
+        assert!(!cfg.tools["search_code"].hidden);
+    }
+
+    #[test]
+    fn test_deserialize_name_case() {
+        for (case, expected) in [
+            ("preserve", NameCase::Preserve),
+            ("camelCase", NameCase::CamelCase),
+            ("snake_case", NameCase::SnakeCase),
+        ] {
+            let cfg: ServerConfig = serde_json::from_value(json!({
+                "name": "api",
+                "url": "http://localhost:8080/mcp",
+                "case": case
+            }))
+            .unwrap();
+            assert_eq!(cfg.case, Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_stdio_lifecycle_config() {
+        let payload = json!({
+            "name": "stdio",
+            "command": "node ./server.js",
+            "idle_timeout_secs": 0,
+            "restart_backoff_ms": 100
+        });
+        let cfg: ServerConfig = serde_json::from_value(payload).unwrap();
+        let stdio = cfg.stdio().expect("expected stdio config");
+        assert_eq!(stdio.idle_timeout(), None);
+        assert_eq!(stdio.restart_backoff(1), Duration::from_millis(100));
+        assert_eq!(stdio.restart_backoff(3), Duration::from_millis(400));
+        assert_eq!(stdio.restart_backoff(30), Duration::from_secs(30));
+
+        let cfg = ServerConfig::new_stdio("stdio".into(), "node".into(), vec![], BTreeMap::new());
+        let stdio = cfg.stdio().unwrap();
+        assert_eq!(stdio.idle_timeout(), Some(Duration::from_mins(5)));
+        assert_eq!(stdio.restart_backoff(1), Duration::from_millis(500));
+    }
 }
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Function learned from a successful execution, callable by executed code as
+/// `Skills.<name>(input)`
+///
+/// The code defines `async function run(input)` like code passed to `execute`, the
+/// argument of a call of the function is its `input`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SkillConfig {
+    /// Name of the function, letters, digits & `_`
+    pub name: String,
+    /// Description of the function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Code of the skill
+    pub code: String,
+    /// JSON schema of the input, any value is accepted if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<Value>,
+    /// JSON schema of the output, the function returns `any` if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+}
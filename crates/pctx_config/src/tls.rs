@@ -0,0 +1,109 @@
+use anyhow::{Context, Result, bail};
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// TLS options of the HTTP client connecting to an MCP server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM bundle of root CA certificates trusted in addition to the system roots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<Utf8PathBuf>,
+    /// PEM client certificate (chain) presented for mutual TLS, requires `client_key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<Utf8PathBuf>,
+    /// PEM private key of the client certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<Utf8PathBuf>,
+    /// Accept any server certificate, only use this for local development
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure_skip_verify: bool,
+}
+
+impl TlsConfig {
+    /// Applies the TLS options to the HTTP client builder
+    ///
+    /// # Errors
+    ///
+    /// This will error if a certificate or key can not be read or parsed, or
+    /// if only one of `client_cert` and `client_key` is set
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder> {
+        if let Some(ca_cert) = &self.ca_cert {
+            let pem = std::fs::read(ca_cert)
+                .with_context(|| format!("Failed reading CA certificate {ca_cert}"))?;
+            let certs = reqwest::Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA certificate {ca_cert}"))?;
+            if certs.is_empty() {
+                bail!("No certificates found in CA certificate {ca_cert}");
+            }
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => {
+                let mut pem = std::fs::read(cert)
+                    .with_context(|| format!("Failed reading client certificate {cert}"))?;
+                pem.push(b'\n');
+                pem.extend(
+                    std::fs::read(key)
+                        .with_context(|| format!("Failed reading client key {key}"))?,
+                );
+                let identity = reqwest::Identity::from_pem(&pem)
+                    .with_context(|| format!("Invalid client certificate {cert} or key {key}"))?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => bail!("TLS `client_cert` and `client_key` must be set together"),
+        }
+
+        if self.insecure_skip_verify {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::TlsConfig;
+
+    #[test]
+    fn test_tls_config() {
+        let cfg: TlsConfig = serde_json::from_value(json!({
+            "ca_cert": "./certs/internal-ca.pem",
+            "insecure_skip_verify": true
+        }))
+        .unwrap();
+        assert_eq!(
+            cfg.ca_cert.as_deref(),
+            Some(camino::Utf8Path::new("./certs/internal-ca.pem"))
+        );
+        assert!(cfg.insecure_skip_verify);
+
+        // defaults apply cleanly
+        assert!(
+            TlsConfig::default()
+                .apply(reqwest::Client::builder())
+                .is_ok()
+        );
+
+        let cert_only = TlsConfig {
+            client_cert: Some("client.pem".into()),
+            ..Default::default()
+        };
+        let err = cert_only.apply(reqwest::Client::builder()).unwrap_err();
+        assert!(err.to_string().contains("must be set together"));
+
+        let missing_ca = TlsConfig {
+            ca_cert: Some("/does/not/exist.pem".into()),
+            ..Default::default()
+        };
+        let err = missing_ca.apply(reqwest::Client::builder()).unwrap_err();
+        assert!(err.to_string().contains("Failed reading CA certificate"));
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// TypeScript compiler options used to type check code before executing it
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypeCheckConfig {
+    /// Enables all strict type checking flags
+    #[serde(default = "crate::defaults::default_true")]
+    pub strict: bool,
+    /// Report expressions & declarations with an implied `any` type, defaults to `strict`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_implicit_any: Option<bool>,
+    /// ECMAScript target version (e.g. `ES2020`, `ES2022`, `ESNext`)
+    #[serde(default = "crate::defaults::default_type_check_target")]
+    pub target: String,
+    /// Global names declared as `any`, for code referencing globals the checker does not know
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_globals: Vec<String>,
+    /// Report type errors as warnings and execute the code anyway
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub warn_only: bool,
+}
+
+impl Default for TypeCheckConfig {
+    fn default() -> Self {
+        Self {
+            strict: true,
+            no_implicit_any: None,
+            target: crate::defaults::default_type_check_target(),
+            allowed_globals: vec![],
+            warn_only: false,
+        }
+    }
+}
+
+impl TypeCheckConfig {
+    pub fn no_implicit_any(&self) -> bool {
+        self.no_implicit_any.unwrap_or(self.strict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeCheckConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let cfg: TypeCheckConfig = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(cfg, TypeCheckConfig::default());
+        assert!(cfg.no_implicit_any());
+
+        let loose: TypeCheckConfig = serde_json::from_value(json!({
+            "strict": false,
+            "allowed_globals": ["process"],
+            "warn_only": true
+        }))
+        .unwrap();
+        assert!(!loose.no_implicit_any());
+        assert_eq!(loose.target, "ES2020");
+        assert_eq!(loose.allowed_globals, vec!["process"]);
+    }
+}
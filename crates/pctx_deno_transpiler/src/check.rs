@@ -0,0 +1,74 @@
+use deno_ast::{MediaType, ModuleSpecifier, ParseDiagnostic, ParseParams, diagnostics::Diagnostic};
+use serde::Serialize;
+
+/// Syntax error of checked code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyntaxDiagnostic {
+    /// Error message
+    pub message: String,
+    /// Line of the error (1-indexed)
+    pub line: usize,
+    /// Column of the error (1-indexed)
+    pub column: usize,
+}
+
+impl From<&ParseDiagnostic> for SyntaxDiagnostic {
+    fn from(diagnostic: &ParseDiagnostic) -> Self {
+        let position = diagnostic.display_position();
+        Self {
+            message: diagnostic.message().to_string(),
+            line: position.line_number,
+            column: position.column_number,
+        }
+    }
+}
+
+/// Checks the syntax of TypeScript code without type checking it
+///
+/// This is the part of checking code that runs without the Deno runtime, e.g. in
+/// browsers to validate code before sending it to a server. Type errors are only
+/// found by the server's type checker.
+///
+/// # Examples
+/// ```
+/// use pctx_deno_transpiler::check_syntax;
+///
+/// assert!(check_syntax("async function run() { return 1; }").is_empty());
+/// assert_eq!(check_syntax("const x = ;")[0].line, 1);
+/// ```
+pub fn check_syntax(code: &str) -> Vec<SyntaxDiagnostic> {
+    let Ok(specifier) = ModuleSpecifier::parse("file:///check.ts") else {
+        return vec![];
+    };
+
+    match deno_ast::parse_module(ParseParams {
+        specifier,
+        text: code.into(),
+        media_type: MediaType::TypeScript,
+        capture_tokens: false,
+        scope_analysis: false,
+        maybe_syntax: None,
+    }) {
+        Ok(parsed) => parsed
+            .diagnostics()
+            .iter()
+            .map(SyntaxDiagnostic::from)
+            .collect(),
+        Err(diagnostic) => vec![SyntaxDiagnostic::from(&diagnostic)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_syntax;
+
+    #[test]
+    fn test_check_syntax() {
+        let code = "async function run() {\n  const n: number = 1;\n  return n;\n}";
+        assert!(check_syntax(code).is_empty());
+
+        let diagnostics = check_syntax("async function run() {\n  return {;\n}");
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].line, 2);
+    }
+}
@@ -1,3 +1,16 @@
+//! TypeScript transpilation of pctx, stripping types from executed code with `deno_ast`.
+//!
+//! - [`transpile`] / [`transpile_with_source_map`] - Transpile TypeScript to JavaScript
+//! - [`check_syntax`] - Syntax errors of TypeScript code, without type checking it
+//!
+//! The crate does not depend on the Deno runtime and builds for `wasm32-unknown-unknown`.
+//! `pctx_deno_transpiler_wasm` exports `transpile` & `checkSyntax` to JavaScript, so browser
+//! based playgrounds can validate code before sending it to a server.
+
+mod check;
+
+pub use check::{SyntaxDiagnostic, check_syntax};
+
 use deno_ast::{
     EmitOptions, MediaType, ModuleSpecifier, ParseParams, TranspileModuleOptions, TranspileOptions,
 };
@@ -48,6 +61,34 @@ pub type Result<T> = std::result::Result<T, TranspileError>;
 /// assert!(!js_code.contains(": number")); // Type annotations removed
 /// ```
 pub fn transpile(code: &str, specifier: Option<&str>) -> Result<String> {
+    emit(code, specifier, deno_ast::SourceMapOption::None).map(|t| t.code)
+}
+
+/// JavaScript transpiled from TypeScript, with the source map back to the TypeScript
+#[derive(Debug, Clone)]
+pub struct Transpiled {
+    /// The transpiled JavaScript code
+    pub code: String,
+    /// JSON source map mapping the JavaScript back to the TypeScript code
+    pub source_map: Option<String>,
+}
+
+/// Transpile TypeScript code to JavaScript, also emitting a source map
+///
+/// See [`transpile`], the source map allows mapping runtime positions (e.g. of stack traces)
+/// back to the TypeScript code.
+///
+/// # Errors
+/// Returns the same errors as [`transpile`]
+pub fn transpile_with_source_map(code: &str, specifier: Option<&str>) -> Result<Transpiled> {
+    emit(code, specifier, deno_ast::SourceMapOption::Separate)
+}
+
+fn emit(
+    code: &str,
+    specifier: Option<&str>,
+    source_map: deno_ast::SourceMapOption,
+) -> Result<Transpiled> {
     let specifier = ModuleSpecifier::parse(specifier.unwrap_or("file:///execute.ts"))
         .map_err(|e| TranspileError::InvalidSpecifier(e.to_string()))?;
 
@@ -66,19 +107,31 @@ pub fn transpile(code: &str, specifier: Option<&str>) -> Result<String> {
             &TranspileOptions::default(),
             &TranspileModuleOptions::default(),
             &EmitOptions {
-                source_map: deno_ast::SourceMapOption::None,
+                source_map,
                 ..Default::default()
             },
         )
         .map_err(|e| TranspileError::TranspileError(e.to_string()))?;
 
-    Ok(transpiled.into_source().text)
+    let source = transpiled.into_source();
+    Ok(Transpiled {
+        code: source.text,
+        source_map: source.source_map,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_transpile_with_source_map() {
+        let code = "const x: number = 42;\nconsole.log(x);";
+        let result = transpile_with_source_map(code, None).unwrap();
+        assert!(result.code.contains("console.log(x)"));
+        assert!(result.source_map.is_some_and(|m| m.contains("mappings")));
+    }
+
     #[test]
     fn test_transpile_simple_typescript() {
         let code = r"const x: number = 42;";
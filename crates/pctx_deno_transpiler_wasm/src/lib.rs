@@ -0,0 +1,38 @@
+//! JavaScript bindings of [`pctx_deno_transpiler`] for browsers.
+//!
+//! The crate is a separate `cdylib` so native consumers of the transpiler only build an rlib:
+//!
+//! ```sh
+//! wasm-pack build crates/pctx_deno_transpiler_wasm --target web
+//! ```
+//!
+//! ```js
+//! import init, { transpile, checkSyntax } from "./pkg/pctx_deno_transpiler_wasm.js";
+//!
+//! await init();
+//! const diagnostics = checkSyntax(code); // [{ message, line, column }]
+//! const js = transpile(code);
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+/// Transpiles TypeScript code to JavaScript, throwing if it can not be parsed
+///
+/// # Errors
+///
+/// Returns the parse or transpile error as a JavaScript error
+#[wasm_bindgen(js_name = transpile)]
+pub fn transpile_js(code: &str) -> Result<String, JsError> {
+    pctx_deno_transpiler::transpile(code, None).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Syntax errors of TypeScript code as `{ message, line, column }` objects
+///
+/// # Errors
+///
+/// Returns an error if the diagnostics can not be converted to JavaScript values
+#[wasm_bindgen(js_name = checkSyntax)]
+pub fn check_syntax_js(code: &str) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(&pctx_deno_transpiler::check_syntax(code))
+        .map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, RwLock},
+};
+
+use pctx_config::type_check::TypeCheckConfig;
+use pctx_deno_transpiler::Transpiled;
+
+use crate::CheckResult;
+
+/// Maximum number of type check results & transpiled scripts kept, oldest are evicted first
+const MAX_ENTRIES: usize = 256;
+
+/// Cache of type check results and transpiled JavaScript keyed on a hash of the code
+///
+/// The code hashed is the full script, including the generated declarations of the
+/// registered functions, so changing the registered tools invalidates the cached results.
+/// Type check results are additionally keyed on the type check options. Agents often
+/// re-submit identical code after transient failures, which then skips type checking
+/// and transpilation.
+#[derive(Debug, Clone, Default)]
+pub struct CheckCache {
+    checks: Arc<RwLock<Entries<CheckResult>>>,
+    transpiled: Arc<RwLock<Entries<Transpiled>>>,
+}
+
+#[derive(Debug)]
+struct Entries<T> {
+    values: HashMap<u64, T>,
+    order: VecDeque<u64>,
+}
+
+impl<T> Default for Entries<T> {
+    fn default() -> Self {
+        Self {
+            values: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: Clone> Entries<T> {
+    fn get(&self, key: u64) -> Option<T> {
+        self.values.get(&key).cloned()
+    }
+
+    fn insert(&mut self, key: u64, value: T) {
+        if self.values.insert(key, value).is_none() {
+            self.order.push_back(key);
+            if self.order.len() > MAX_ENTRIES
+                && let Some(oldest) = self.order.pop_front()
+            {
+                self.values.remove(&oldest);
+            }
+        }
+    }
+}
+
+impl CheckCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached type check result of the code with the given options
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn check(&self, code: &str, type_check: &TypeCheckConfig) -> Option<CheckResult> {
+        self.checks.read().unwrap().get(check_key(code, type_check))
+    }
+
+    /// Caches the type check result of the code with the given options
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn insert_check(&self, code: &str, type_check: &TypeCheckConfig, res: CheckResult) {
+        self.checks
+            .write()
+            .unwrap()
+            .insert(check_key(code, type_check), res);
+    }
+
+    /// Returns the cached JavaScript (and source map) transpiled from the code
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn transpiled(&self, code: &str) -> Option<Transpiled> {
+        self.transpiled.read().unwrap().get(code_key(code))
+    }
+
+    /// Caches the JavaScript (and source map) transpiled from the code
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) fn insert_transpiled(&self, code: &str, transpiled: Transpiled) {
+        self.transpiled
+            .write()
+            .unwrap()
+            .insert(code_key(code), transpiled);
+    }
+
+    /// Number of cached type check results
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn len(&self) -> usize {
+        self.checks.read().unwrap().values.len()
+    }
+
+    /// Returns `true` if there are no cached type check results
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached type check results & transpiled scripts
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn clear(&self) {
+        *self.checks.write().unwrap() = Entries::default();
+        *self.transpiled.write().unwrap() = Entries::default();
+    }
+}
+
+fn code_key(code: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    code.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn check_key(code: &str, type_check: &TypeCheckConfig) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    code.hash(&mut hasher);
+    type_check.hash(&mut hasher);
+    hasher.finish()
+}
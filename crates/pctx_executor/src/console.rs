@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Level of a console call, or the kind of uncaught error reported to the console
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleLevel {
+    Log,
+    Info,
+    Debug,
+    Table,
+    Warn,
+    Error,
+    /// A promise rejected without a rejection handler
+    UnhandledRejection,
+    /// An error thrown outside of the module evaluation, e.g. in a timer callback
+    UncaughtError,
+}
+
+/// A console call made by the executed code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsoleRecord {
+    pub level: ConsoleLevel,
+    /// Time of the call in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Line of the call in the executed TypeScript code (1-indexed)
+    pub line: Option<usize>,
+    /// Column of the call in the executed TypeScript code (1-indexed)
+    pub column: Option<usize>,
+    /// Arguments of the call serialized as JSON
+    pub args: Vec<serde_json::Value>,
+}
+
+/// Parses the console records captured by the runtime, mapping the call sites in the
+/// transpiled JavaScript back to the TypeScript code with the source map
+pub(crate) fn records(captured: serde_json::Value, source_map: Option<&str>) -> Vec<ConsoleRecord> {
+    let mut records: Vec<ConsoleRecord> = match serde_json::from_value(captured) {
+        Ok(records) => records,
+        Err(e) => {
+            debug!(runtime = "execution", error = %e, "Failed parsing console records");
+            return vec![];
+        }
+    };
+
+    let source_map = source_map.and_then(|m| sourcemap::SourceMap::from_slice(m.as_bytes()).ok());
+    for record in &mut records {
+        let (Some(line), Some(column)) = (record.line, record.column) else {
+            continue;
+        };
+        let mapped = source_map.as_ref().and_then(|m| {
+            m.lookup_token(
+                u32::try_from(line.saturating_sub(1)).ok()?,
+                u32::try_from(column.saturating_sub(1)).ok()?,
+            )
+        });
+        // positions that can not be mapped do not point into the TypeScript code
+        (record.line, record.column) = match mapped {
+            Some(token) => (
+                Some(token.get_src_line() as usize + 1),
+                Some(token.get_src_col() as usize + 1),
+            ),
+            None => (None, None),
+        };
+    }
+
+    records
+}
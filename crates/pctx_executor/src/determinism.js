@@ -0,0 +1,42 @@
+// Replaces the sources of randomness & time of the sandbox with a seeded PRNG
+// and a frozen clock, called with the seed & the frozen time in milliseconds
+((seed, now) => {
+  // mulberry32
+  let state = seed >>> 0;
+  const next = () => {
+    state = (state + 0x6d2b79f5) >>> 0;
+    let t = state;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+
+  Math.random = next;
+
+  if (globalThis.crypto?.getRandomValues) {
+    globalThis.crypto.getRandomValues = (array) => {
+      const bytes = new Uint8Array(array.buffer, array.byteOffset, array.byteLength);
+      for (let i = 0; i < bytes.length; i++) {
+        bytes[i] = Math.floor(next() * 256);
+      }
+      return array;
+    };
+  }
+
+  const RealDate = Date;
+  function FrozenDate(...args) {
+    if (!new.target) {
+      return new RealDate(now).toString();
+    }
+    return args.length === 0 ? new RealDate(now) : new RealDate(...args);
+  }
+  FrozenDate.prototype = RealDate.prototype;
+  FrozenDate.now = () => now;
+  FrozenDate.parse = RealDate.parse;
+  FrozenDate.UTC = RealDate.UTC;
+  globalThis.Date = FrozenDate;
+
+  if (globalThis.performance?.now) {
+    globalThis.performance.now = () => 0;
+  }
+})
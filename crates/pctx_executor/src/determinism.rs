@@ -0,0 +1,42 @@
+//! Deterministic executions
+//!
+//! `Math.random`, `crypto.getRandomValues` and `Date` of deterministic executions are
+//! replaced before the code runs, so executions with the same [`Determinism`] produce
+//! identical outputs, e.g. in test suites or when replaying recordings.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+static DETERMINISM_JS: &str = include_str!("determinism.js");
+
+/// Seed of the random numbers & frozen time of a deterministic execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Determinism {
+    /// Seed of `Math.random` & `crypto.getRandomValues`
+    pub seed: u32,
+    /// Milliseconds since the Unix epoch returned by `Date.now()` & `new Date()`
+    pub now_ms: i64,
+}
+
+impl Determinism {
+    pub fn new(seed: u32, now_ms: i64) -> Self {
+        Self { seed, now_ms }
+    }
+
+    /// Freezes the current time, seeding the random numbers from it
+    pub fn from_clock() -> Self {
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            seed: elapsed.subsec_nanos(),
+            now_ms: i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX),
+        }
+    }
+
+    /// Script replacing the sources of randomness & time of the runtime
+    pub(crate) fn script(self) -> String {
+        format!("{DETERMINISM_JS}({}, {});", self.seed, self.now_ms)
+    }
+}
@@ -0,0 +1,364 @@
+//! Isolation of executions in short-lived child processes
+//!
+//! With [`Isolation::Process`] every execution runs in a child process, so a V8 crash,
+//! runaway memory or a native bug only takes down the child. The child is started with
+//! the [`CHILD_ENV`] environment variable set. Binaries using process isolation must call
+//! [`run_child`] at startup when [`is_child`] returns `true`.
+//!
+//! Parent & child exchange JSON lines: the parent writes the [`RemoteRequest`] to the
+//! child's stdin, the child writes `{"call": {"id", "server", "tool", "arguments"}}` lines
+//! for the MCP tool calls of the code and finally `{"result": ExecuteResult}` to stdout.
+//! The parent makes the calls with its own registry, so the rate limits, caches, circuit
+//! breakers & client handlers (approvals, sampling, elicitation, progress) shared between
+//! executions apply, and answers with `{"id", "result": {"Ok": value} | {"Err": error}}`
+//! lines on stdin.
+//!
+//! On Linux the child sets `no_new_privs` before executing, then runs the hardening
+//! hook passed to [`run_child`], e.g. to install seccomp filters or a landlock ruleset.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Write},
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use futures::{StreamExt, stream::FuturesUnordered};
+use pctx_code_execution_runtime::{MCPRegistry, McpError, ToolCallForwarder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    sync::oneshot,
+};
+use tracing::{debug, warn};
+
+use crate::{DenoExecutorError, ExecuteResult, ExecutionError, RemoteRequest, Result};
+
+/// Environment variable marking a process as an execution child process
+pub static CHILD_ENV: &str = "PCTX_EXECUTOR_CHILD";
+
+/// Hook hardening the child process before it executes code
+pub type HardeningFn = Arc<dyn Fn() -> io::Result<()> + Send + Sync>;
+
+/// Where executions run
+#[derive(Debug, Clone, Default)]
+pub enum Isolation {
+    /// In the calling process
+    #[default]
+    InProcess,
+    /// In a short-lived child process per execution
+    Process(ProcessIsolation),
+}
+
+/// Line written by the child process
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ChildMessage {
+    /// MCP tool call of the executed code, answered with a [`CallReply`]
+    Call {
+        id: u64,
+        server: String,
+        tool: String,
+        arguments: Option<Map<String, Value>>,
+    },
+    /// Result of the execution, the last line of the child
+    Result(ExecuteResult),
+}
+
+/// Line written by the parent process answering a tool call of the child
+#[derive(Debug, Serialize, Deserialize)]
+struct CallReply {
+    id: u64,
+    result: std::result::Result<Value, McpError>,
+}
+
+/// Child processes executing code
+#[derive(Debug, Clone)]
+pub struct ProcessIsolation {
+    program: PathBuf,
+    args: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+impl ProcessIsolation {
+    /// Children running the current executable
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path of the current executable cannot be determined
+    pub fn current_exe() -> io::Result<Self> {
+        Ok(Self::new(std::env::current_exe()?))
+    }
+
+    /// Children running `program`, which must call [`run_child`] at startup
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: vec![],
+            timeout: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Kill children running longer than `timeout`
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Executes the request in a new child process, making the MCP tool calls of the
+    /// child with `registry`
+    ///
+    /// Crashes and timeouts of the child are returned as failed executions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the child cannot be started or returns an invalid result
+    pub async fn execute(
+        &self,
+        request: &RemoteRequest,
+        registry: MCPRegistry,
+    ) -> Result<ExecuteResult> {
+        let mut body = serde_json::to_vec(request)
+            .map_err(|e| DenoExecutorError::IsolationError(format!("Invalid request: {e}")))?;
+        body.push(b'\n');
+
+        let mut child = tokio::process::Command::new(&self.program)
+            .args(&self.args)
+            .env(CHILD_ENV, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                DenoExecutorError::IsolationError(format!("Failed starting child process: {e}"))
+            })?;
+        debug!(pid = ?child.id(), "Executing code in child process");
+
+        let (Some(mut stdin), Some(stdout), Some(mut stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(DenoExecutorError::IsolationError(
+                "Child process has no stdio".into(),
+            ));
+        };
+        stdin.write_all(&body).await.map_err(|e| {
+            DenoExecutorError::IsolationError(format!("Failed sending code to child: {e}"))
+        })?;
+        let stderr = tokio::spawn(async move {
+            let mut buf = vec![];
+            let _ = stderr.read_to_end(&mut buf).await;
+            String::from_utf8_lossy(&buf).into_owned()
+        });
+
+        let exchange = async {
+            let mut lines = BufReader::new(stdout).lines();
+            let mut calls = FuturesUnordered::new();
+            let outcome = loop {
+                tokio::select! {
+                    Some(reply) = calls.next(), if !calls.is_empty() => {
+                        let mut line = serde_json::to_vec(&reply).unwrap_or_default();
+                        line.push(b'\n');
+                        if let Err(e) = stdin.write_all(&line).await {
+                            debug!("Failed answering tool call of child process: {e}");
+                        }
+                    }
+                    line = lines.next_line() => match line {
+                        Ok(Some(line)) => match serde_json::from_str(&line) {
+                            Ok(ChildMessage::Call { id, server, tool, arguments }) => {
+                                let registry = registry.clone();
+                                calls.push(async move {
+                                    let result = registry.call_tool(&server, &tool, arguments).await;
+                                    CallReply { id, result }
+                                });
+                            }
+                            Ok(ChildMessage::Result(result)) => break Ok(Some(result)),
+                            Err(e) => break Err(format!("Invalid message from child process: {e}")),
+                        },
+                        Ok(None) => break Ok(None),
+                        Err(e) => break Err(format!("Failed reading from child process: {e}")),
+                    }
+                }
+            };
+            drop(stdin);
+            (outcome, child.wait().await)
+        };
+        let (outcome, status) = match self.timeout {
+            Some(timeout) => {
+                let Ok(exchange) = tokio::time::timeout(timeout, exchange).await else {
+                    warn!("Child process timed out after {timeout:?}");
+                    return Ok(failed(format!(
+                        "Execution timed out after {}s",
+                        timeout.as_secs_f64()
+                    )));
+                };
+                exchange
+            }
+            None => exchange.await,
+        };
+        let status = status
+            .map_err(|e| DenoExecutorError::IsolationError(format!("Child process failed: {e}")))?;
+
+        if !status.success() {
+            let stderr = stderr.await.unwrap_or_default();
+            warn!(%status, %stderr, "Child process crashed");
+            return Ok(failed(format!(
+                "Execution process crashed ({status}): {}",
+                stderr.trim()
+            )));
+        }
+
+        match outcome {
+            Ok(Some(result)) => Ok(result),
+            Ok(None) => Err(DenoExecutorError::IsolationError(
+                "Child process exited without a result".into(),
+            )),
+            Err(e) => Err(DenoExecutorError::IsolationError(e)),
+        }
+    }
+}
+
+impl fmt::Display for Isolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Isolation::InProcess => write!(f, "in-process"),
+            Isolation::Process(_) => write!(f, "process"),
+        }
+    }
+}
+
+pub(crate) fn failed(message: String) -> ExecuteResult {
+    ExecuteResult {
+        success: false,
+        diagnostics: vec![],
+        runtime_error: Some(ExecutionError {
+            message: message.clone(),
+            stack: None,
+        }),
+        output: None,
+        stdout: String::new(),
+        stderr: message,
+        console: vec![],
+        steps: vec![],
+        type_checked: false,
+    }
+}
+
+/// Returns `true` if this process was started to run an execution
+pub fn is_child() -> bool {
+    std::env::var_os(CHILD_ENV).is_some()
+}
+
+/// Runs the execution of a child process: reads the request from stdin, executes it
+/// forwarding its MCP tool calls to the parent and writes the result to stdout
+///
+/// Must be called outside of a tokio runtime, nothing else may write to stdout.
+///
+/// # Errors
+///
+/// Returns an error if hardening fails, the request is invalid or the execution fails
+///
+/// # Panics
+///
+/// Panics if the lock of the pending tool calls is poisoned
+pub fn run_child(hardening: Option<HardeningFn>) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    set_no_new_privs()?;
+    if let Some(harden) = hardening {
+        harden()?;
+    }
+
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let request: RemoteRequest = serde_json::from_str(&line)?;
+
+    // the parent answers tool calls on stdin while the code executes
+    let pending: Arc<Mutex<HashMap<u64, oneshot::Sender<CallReply>>>> = Arc::default();
+    std::thread::spawn({
+        let pending = pending.clone();
+        move || {
+            for line in io::stdin().lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str::<CallReply>(&line) {
+                    Ok(reply) => {
+                        if let Some(tx) = pending.lock().unwrap().remove(&reply.id) {
+                            let _ = tx.send(reply);
+                        }
+                    }
+                    Err(e) => warn!("Invalid reply from parent process: {e}"),
+                }
+            }
+            // fails the calls still waiting for a reply
+            pending.lock().unwrap().clear();
+        }
+    });
+
+    let next_id = Arc::new(AtomicU64::new(0));
+    let forwarder: ToolCallForwarder = Arc::new(move |server, tool, arguments| {
+        let id = next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        pending.lock().unwrap().insert(id, tx);
+        let sent = write_line(&ChildMessage::Call {
+            id,
+            server,
+            tool,
+            arguments,
+        });
+        Box::pin(async move {
+            sent.map_err(|e| {
+                McpError::ToolCall(format!("Failed forwarding call to parent process: {e}"))
+            })?;
+            let reply = rx.await.map_err(|_| {
+                McpError::ToolCall("Parent process stopped answering tool calls".into())
+            })?;
+            reply.result
+        })
+    });
+
+    let mut options = request.options();
+    options.tool_call_forwarder = Some(forwarder);
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let result = rt
+        .block_on(crate::execute(&request.code, options))
+        .map_err(io::Error::other)?;
+
+    write_line(&ChildMessage::Result(result))
+}
+
+fn write_line(message: &ChildMessage) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(&line)?;
+    stdout.flush()
+}
+
+/// Prevents the child and its descendants from gaining privileges, required by
+/// unprivileged seccomp filters & landlock rulesets
+#[cfg(target_os = "linux")]
+fn set_no_new_privs() -> io::Result<()> {
+    // SAFETY: prctl with PR_SET_NO_NEW_PRIVS only sets a flag of the calling process
+    let res = unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
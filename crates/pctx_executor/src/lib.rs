@@ -3,13 +3,38 @@ use deno_core::ModuleCodeString;
 use deno_core::RuntimeOptions;
 use deno_core::anyhow;
 use deno_core::error::CoreError;
-use pctx_code_execution_runtime::CallbackRegistry;
-pub use pctx_type_check_runtime::{CheckResult, Diagnostic, is_relevant_error, type_check};
+use pctx_code_execution_runtime::{
+    ArtifactStore, CallTape, CallbackRegistry, CircuitBreakers, ExecutionIds, ExecutionSignal,
+    KvStore, MCPRegistry, McpError, OutputSchemas, RateLimiters, ResultStore, StdioSupervisor,
+    ToolCache, ToolCallForwarder, ToolRegistrations, VirtualFs, WasmModules,
+};
+use pctx_config::{client::ClientHandlers, imports::ImportsConfig, type_check::TypeCheckConfig};
+pub use pctx_type_check_runtime::{
+    CheckResult, Diagnostic, TypeCheckOptions, is_relevant_error, type_check,
+    type_check_with_options,
+};
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tracing::{debug, warn};
 
+mod check_cache;
+mod console;
+pub mod determinism;
+pub mod isolation;
+mod module_loader;
+pub mod remote;
+mod steps;
+mod watchdog;
+
+pub use check_cache::CheckCache;
+pub use console::{ConsoleLevel, ConsoleRecord};
+pub use determinism::Determinism;
+pub use isolation::{Isolation, ProcessIsolation};
+pub use remote::{RemoteRequest, RemoteWorkers};
+pub use steps::{StepRecord, StepStatus};
+
 pub type Result<T> = std::result::Result<T, DenoExecutorError>;
 
 #[derive(Clone, Default)]
@@ -17,6 +42,31 @@ pub struct ExecuteOptions {
     pub allowed_hosts: Option<Vec<String>>,
     pub servers: Vec<pctx_config::server::ServerConfig>,
     pub callback_registry: CallbackRegistry,
+    pub tool_cache: Option<ToolCache>,
+    pub circuit_breakers: Option<CircuitBreakers>,
+    pub rate_limiters: Option<RateLimiters>,
+    pub max_tool_calls: Option<u32>,
+    pub stdio_supervisor: Option<StdioSupervisor>,
+    pub output_schemas: Option<OutputSchemas>,
+    pub client_handlers: ClientHandlers,
+    pub tool_call_forwarder: Option<ToolCallForwarder>,
+    pub type_check: TypeCheckConfig,
+    pub skip_type_check: bool,
+    pub check_cache: Option<CheckCache>,
+    pub imports: ImportsConfig,
+    pub wasm_modules: WasmModules,
+    pub fs: Option<VirtualFs>,
+    pub artifacts: Option<ArtifactStore>,
+    pub results: Option<ResultStore>,
+    pub kv: Option<KvStore>,
+    pub remote_workers: Option<RemoteWorkers>,
+    pub isolation: Isolation,
+    pub signal: Option<ExecutionSignal>,
+    pub timeout: Option<Duration>,
+    pub tape: Option<CallTape>,
+    pub determinism: Option<Determinism>,
+    pub ids: ExecutionIds,
+    pub tool_registrations: Option<ToolRegistrations>,
 }
 
 impl std::fmt::Debug for ExecuteOptions {
@@ -25,6 +75,40 @@ impl std::fmt::Debug for ExecuteOptions {
             .field("allowed_hosts", &self.allowed_hosts)
             .field("servers", &self.servers)
             .field("callback_registry", &self.callback_registry.ids())
+            .field("tool_cache", &self.tool_cache.as_ref().map(ToolCache::len))
+            .field("circuit_breakers", &self.circuit_breakers)
+            .field("rate_limiters", &self.rate_limiters)
+            .field("max_tool_calls", &self.max_tool_calls)
+            .field("stdio_supervisor", &self.stdio_supervisor)
+            .field("output_schemas", &self.output_schemas)
+            .field("client_handlers", &self.client_handlers)
+            .field("tool_call_forwarder", &self.tool_call_forwarder.is_some())
+            .field("type_check", &self.type_check)
+            .field("skip_type_check", &self.skip_type_check)
+            .field(
+                "check_cache",
+                &self.check_cache.as_ref().map(CheckCache::len),
+            )
+            .field("imports", &self.imports)
+            .field("wasm_modules", &self.wasm_modules.names())
+            .field("fs", &self.fs)
+            .field(
+                "artifacts",
+                &self.artifacts.as_ref().map(ArtifactStore::config),
+            )
+            .field("results", &self.results.as_ref().map(ResultStore::config))
+            .field("kv", &self.kv.as_ref().map(KvStore::config))
+            .field(
+                "remote_workers",
+                &self.remote_workers.as_ref().map(RemoteWorkers::urls),
+            )
+            .field("isolation", &self.isolation)
+            .field("signal", &self.signal)
+            .field("timeout", &self.timeout)
+            .field("tape", &self.tape)
+            .field("determinism", &self.determinism)
+            .field("ids", &self.ids)
+            .field("tool_registrations", &self.tool_registrations)
             .finish()
     }
 }
@@ -58,6 +142,194 @@ impl ExecuteOptions {
         self.callback_registry = registry;
         self
     }
+
+    /// Set the cache shared between executions for MCP tools
+    /// configured with a cache `ttl_secs`
+    #[must_use]
+    pub fn with_tool_cache(mut self, cache: ToolCache) -> Self {
+        self.tool_cache = Some(cache);
+        self
+    }
+
+    /// Set the circuit breakers of upstream MCP servers shared between executions
+    #[must_use]
+    pub fn with_circuit_breakers(mut self, breakers: CircuitBreakers) -> Self {
+        self.circuit_breakers = Some(breakers);
+        self
+    }
+
+    /// Set the rate limits of MCP tools shared between executions
+    #[must_use]
+    pub fn with_rate_limiters(mut self, limiters: RateLimiters) -> Self {
+        self.rate_limiters = Some(limiters);
+        self
+    }
+
+    /// Limit the number of MCP tool calls of the execution, further calls fail
+    #[must_use]
+    pub fn with_max_tool_calls(mut self, max: u32) -> Self {
+        self.max_tool_calls = Some(max);
+        self
+    }
+
+    /// Set the supervisor of long-lived stdio MCP server processes shared between executions
+    #[must_use]
+    pub fn with_stdio_supervisor(mut self, supervisor: StdioSupervisor) -> Self {
+        self.stdio_supervisor = Some(supervisor);
+        self
+    }
+
+    /// Set the schemas refined with the results of MCP servers
+    /// configured with `infer_output_schemas`
+    #[must_use]
+    pub fn with_output_schemas(mut self, schemas: OutputSchemas) -> Self {
+        self.output_schemas = Some(schemas);
+        self
+    }
+
+    /// Set the handlers of requests upstream MCP servers send during tool calls,
+    /// e.g. sampling requests
+    #[must_use]
+    pub fn with_client_handlers(mut self, handlers: ClientHandlers) -> Self {
+        self.client_handlers = handlers;
+        self
+    }
+
+    /// Make the MCP tool calls of the execution with `forwarder`, e.g. in the process the
+    /// execution was isolated from
+    #[must_use]
+    pub fn with_tool_call_forwarder(mut self, forwarder: ToolCallForwarder) -> Self {
+        self.tool_call_forwarder = Some(forwarder);
+        self
+    }
+
+    /// Set the TypeScript compiler options used to type check the code
+    #[must_use]
+    pub fn with_type_check(mut self, type_check: TypeCheckConfig) -> Self {
+        self.type_check = type_check;
+        self
+    }
+
+    /// Skip type checking, executing the code directly
+    ///
+    /// Saves the type checking latency, type errors then surface as runtime errors.
+    /// The result is marked with `type_checked: false`.
+    #[must_use]
+    pub fn with_skip_type_check(mut self, skip: bool) -> Self {
+        self.skip_type_check = skip;
+        self
+    }
+
+    /// Set the cache of type check results & transpiled code shared between executions
+    #[must_use]
+    pub fn with_check_cache(mut self, cache: CheckCache) -> Self {
+        self.check_cache = Some(cache);
+        self
+    }
+
+    /// Set the `npm:`, `jsr:` and `https:` module imports allowed in the executed code
+    #[must_use]
+    pub fn with_imports(mut self, imports: ImportsConfig) -> Self {
+        self.imports = imports;
+        self
+    }
+
+    /// Set the WebAssembly modules the executed code can load with `pctx.wasm.load(name)`
+    #[must_use]
+    pub fn with_wasm_modules(mut self, modules: WasmModules) -> Self {
+        self.wasm_modules = modules;
+        self
+    }
+
+    /// Enable `pctx.fs`, the executed code can access the files of `fs` within its scopes
+    #[must_use]
+    pub fn with_fs(mut self, fs: VirtualFs) -> Self {
+        self.fs = Some(fs);
+        self
+    }
+
+    /// Enable `pctx.artifacts`, the executed code & its callbacks can put and get the
+    /// artifacts of `store`
+    #[must_use]
+    pub fn with_artifacts(mut self, store: ArtifactStore) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+
+    /// Enable `pctx.results`, the executed code can fetch parts of the outputs offloaded to
+    /// `store` by earlier executions
+    #[must_use]
+    pub fn with_results(mut self, store: ResultStore) -> Self {
+        self.results = Some(store);
+        self
+    }
+
+    /// Enable `pctx.kv`, the executed code can get and set the entries of `store`
+    #[must_use]
+    pub fn with_kv(mut self, store: KvStore) -> Self {
+        self.kv = Some(store);
+        self
+    }
+
+    /// Execute the code on remote workers instead of this process
+    #[must_use]
+    pub fn with_remote_workers(mut self, workers: RemoteWorkers) -> Self {
+        self.remote_workers = Some(workers);
+        self
+    }
+
+    /// Set where the code is executed, e.g. in a child process per execution
+    #[must_use]
+    pub fn with_isolation(mut self, isolation: Isolation) -> Self {
+        self.isolation = isolation;
+        self
+    }
+
+    /// Set the signal cancelling the execution, pending MCP tool calls & callbacks are
+    /// dropped and running code is terminated once it is cancelled
+    #[must_use]
+    pub fn with_signal(mut self, signal: ExecutionSignal) -> Self {
+        self.signal = Some(signal);
+        self
+    }
+
+    /// Cancel the execution once it ran longer than `timeout`, including type checking.
+    /// The code is not executed if the deadline passes while it is type checked.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Record the MCP tool calls & callbacks of the execution to the tape, or answer
+    /// them from a replaying tape without calling the tools
+    #[must_use]
+    pub fn with_tape(mut self, tape: CallTape) -> Self {
+        self.tape = Some(tape);
+        self
+    }
+
+    /// Set the identifiers of the execution passed to the callbacks it invokes
+    #[must_use]
+    pub fn with_ids(mut self, ids: ExecutionIds) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Accept tools registered by the execution's code & callbacks, collected in `registrations`
+    #[must_use]
+    pub fn with_tool_registrations(mut self, registrations: ToolRegistrations) -> Self {
+        self.tool_registrations = Some(registrations);
+        self
+    }
+
+    /// Seed `Math.random` & `crypto.getRandomValues` and freeze `Date`, so the execution
+    /// produces identical outputs every time
+    #[must_use]
+    pub fn with_determinism(mut self, determinism: Determinism) -> Self {
+        self.determinism = Some(determinism);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +350,16 @@ pub struct ExecuteResult {
 
     /// Standard error from execution
     pub stderr: String,
+
+    /// Structured console calls, `stdout` and `stderr` join their formatted arguments
+    pub console: Vec<ConsoleRecord>,
+
+    /// Tree of the steps recorded with `pctx.step`, including the steps of failed executions
+    #[serde(default)]
+    pub steps: Vec<StepRecord>,
+
+    /// Whether the code was type checked before execution
+    pub type_checked: bool,
 }
 
 #[derive(Debug, Error)]
@@ -90,6 +372,12 @@ pub enum DenoExecutorError {
 
     #[error("Type check error: {0}")]
     TypeCheckError(#[from] pctx_type_check_runtime::TypeCheckError),
+
+    #[error("Remote execution error: {0}")]
+    RemoteError(String),
+
+    #[error("Isolated execution error: {0}")]
+    IsolationError(String),
 }
 
 /// Execute TypeScript code with type checking and runtime execution
@@ -121,15 +409,55 @@ pub enum DenoExecutorError {
 /// # Ok(())
 /// # }
 /// ```
-pub async fn execute(code: &str, options: ExecuteOptions) -> Result<ExecuteResult> {
+pub async fn execute(code: &str, mut options: ExecuteOptions) -> Result<ExecuteResult> {
+    if let Some(workers) = &options.remote_workers {
+        remote::check_worker_options(&options)?;
+        return workers.execute(&RemoteRequest::new(code, &options)?).await;
+    }
+    if let Isolation::Process(process) = &options.isolation {
+        let request = RemoteRequest::new(code, &options)?;
+        let registry = match mcp_registry(&options) {
+            Ok(registry) => registry,
+            Err(e) => return Ok(isolation::failed(format!("MCP registration failed: {e}"))),
+        };
+        return process.execute(&request, registry).await;
+    }
+
+    // the deadline starts before type checking, which counts towards the timeout
+    if let Some(timeout) = options.timeout.take() {
+        let signal = options.signal.take().unwrap_or_default();
+        options.signal = Some(signal.with_deadline(Instant::now() + timeout));
+    }
+
     debug!(
         code_length = code.len(),
         "Code submitted for typecheck & execution"
     );
-    let check_result = run_type_check(code).await?;
+    let check_result = if options.skip_type_check {
+        debug!(runtime = "type_check", "Type check skipped");
+        CheckResult {
+            success: true,
+            diagnostics: vec![],
+        }
+    } else {
+        let check = run_type_check(code, &options.type_check, options.check_cache.as_ref());
+        match &options.signal {
+            Some(signal) => tokio::select! {
+                result = check => result?,
+                () = signal.cancelled() => return Ok(stopped(signal, false)),
+            },
+            None => check.await?,
+        }
+    };
+    let type_checked = !options.skip_type_check;
+
+    // the compiler runs synchronously, the deadline may have passed meanwhile
+    if let Some(signal) = options.signal.as_ref().filter(|s| s.is_cancelled()) {
+        return Ok(stopped(signal, type_checked));
+    }
 
     // Check if we have diagnostics
-    if !check_result.diagnostics.is_empty() {
+    if !check_result.diagnostics.is_empty() && !options.type_check.warn_only {
         // Format diagnostics as rich stderr output
         let stderr = format_diagnostics(&check_result.diagnostics);
 
@@ -147,10 +475,25 @@ pub async fn execute(code: &str, options: ExecuteOptions) -> Result<ExecuteResul
             output: None,
             stdout: String::new(),
             stderr,
+            console: vec![],
+            steps: vec![],
+            type_checked,
         });
     }
 
-    debug!(runtime = "type_check", "Type check passed");
+    let warnings = if check_result.diagnostics.is_empty() {
+        debug!(runtime = "type_check", "Type check passed");
+        String::new()
+    } else {
+        let warnings = format_diagnostics(&check_result.diagnostics);
+        warn!(
+            runtime = "type_check",
+            diagnostic = %warnings,
+            diagnostic_count = check_result.diagnostics.len(),
+            "Type check failed with diagnostics, executing anyway (warn_only)"
+        );
+        format!("Type check warnings:\n{warnings}\n\n")
+    };
 
     let exec_result = execute_code(code, options)
         .await
@@ -162,23 +505,88 @@ pub async fn execute(code: &str, options: ExecuteOptions) -> Result<ExecuteResul
         String::new()
     };
 
+    let stderr = if exec_result.stderr.is_empty() {
+        stderr
+    } else {
+        exec_result.stderr
+    };
+
     Ok(ExecuteResult {
         success: exec_result.success,
-        diagnostics: Vec::new(), // No type-check diagnostics if we reach execution
+        // Only warn_only type-check diagnostics if we reach execution
+        diagnostics: check_result.diagnostics,
         runtime_error: exec_result.error,
         output: exec_result.output,
         stdout: exec_result.stdout,
-        stderr: if exec_result.stderr.is_empty() {
-            stderr
-        } else {
-            exec_result.stderr
-        },
+        stderr: format!("{warnings}{stderr}"),
+        console: exec_result.console,
+        steps: exec_result.steps,
+        type_checked,
     })
 }
 
-#[tracing::instrument(fields(runtime = "type_check"))]
-async fn run_type_check(code: &str) -> Result<CheckResult> {
-    let mut check_result = type_check(code).await?;
+/// Result of an execution cancelled or past its deadline before its code ran
+fn stopped(signal: &ExecutionSignal, type_checked: bool) -> ExecuteResult {
+    warn!(
+        reason = signal.reason(),
+        "Execution cancelled before running"
+    );
+    let message = format!("Execution stopped: {}", signal.reason());
+    ExecuteResult {
+        success: false,
+        diagnostics: vec![],
+        runtime_error: Some(ExecutionError {
+            message: message.clone(),
+            stack: None,
+        }),
+        output: None,
+        stdout: String::new(),
+        stderr: message,
+        console: vec![],
+        steps: vec![],
+        type_checked,
+    }
+}
+
+/// Type check TypeScript code without executing it
+///
+/// Only diagnostics relevant to the code are returned (see [`is_relevant_error`]),
+/// the code passed type checking if there are none. With `warn_only` diagnostics
+/// are reported as warnings and the code always passes.
+///
+/// # Errors
+/// * Returns error only if internal tooling fails (not for type errors)
+pub async fn check(
+    code: &str,
+    type_check: &TypeCheckConfig,
+    cache: Option<&CheckCache>,
+) -> Result<CheckResult> {
+    let mut check_result = run_type_check(code, type_check, cache).await?;
+    check_result.success = !check_result
+        .diagnostics
+        .iter()
+        .any(|d| d.severity == "error");
+    Ok(check_result)
+}
+
+#[tracing::instrument(skip(type_check, cache), fields(runtime = "type_check"))]
+async fn run_type_check(
+    code: &str,
+    type_check: &TypeCheckConfig,
+    cache: Option<&CheckCache>,
+) -> Result<CheckResult> {
+    if let Some(cached) = cache.and_then(|c| c.check(code, type_check)) {
+        debug!(runtime = "type_check", "Type check cache hit");
+        return Ok(cached);
+    }
+
+    let options = TypeCheckOptions {
+        strict: type_check.strict,
+        no_implicit_any: type_check.no_implicit_any(),
+        target: type_check.target.clone(),
+        allowed_globals: type_check.allowed_globals.clone(),
+    };
+    let mut check_result = type_check_with_options(code, &options).await?;
 
     if !check_result.success && !check_result.diagnostics.is_empty() {
         // filter for only relevant diagnostics
@@ -189,11 +597,21 @@ async fn run_type_check(code: &str) -> Result<CheckResult> {
             .collect();
     }
 
+    if type_check.warn_only {
+        for diagnostic in &mut check_result.diagnostics {
+            diagnostic.severity = "warning".into();
+        }
+    }
+
+    if let Some(cache) = cache {
+        cache.insert_check(code, type_check, check_result.clone());
+    }
+
     Ok(check_result)
 }
 
 /// Format diagnostics as rich stderr output with line numbers, columns, and error codes
-fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+pub fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
     diagnostics
         .iter()
         .map(|d| {
@@ -221,10 +639,15 @@ fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
                 .to_string();
 
             // Build the formatted error
-            if parts.is_empty() {
+            let formatted = if parts.is_empty() {
                 cleaned_message
             } else {
                 format!("{}: {}", parts.join(", "), cleaned_message)
+            };
+
+            match &d.suggestion {
+                Some(suggestion) => format!("{formatted}\n  Suggestion: {suggestion}"),
+                None => formatted,
             }
         })
         .collect::<Vec<_>>()
@@ -245,6 +668,41 @@ struct InternalExecuteResult {
     pub error: Option<ExecutionError>,
     pub stdout: String,
     pub stderr: String,
+    pub console: Vec<ConsoleRecord>,
+    pub steps: Vec<StepRecord>,
+}
+
+/// MCP registry making the tool calls of an execution, with the servers and the state
+/// shared between executions of the options
+fn mcp_registry(options: &ExecuteOptions) -> std::result::Result<MCPRegistry, McpError> {
+    let mut registry = MCPRegistry::new();
+    if let Some(cache) = &options.tool_cache {
+        registry = registry.with_shared_cache(cache.clone());
+    }
+    if let Some(breakers) = &options.circuit_breakers {
+        registry = registry.with_circuit_breakers(breakers.clone());
+    }
+    if let Some(limiters) = &options.rate_limiters {
+        registry = registry.with_rate_limiters(limiters.clone());
+    }
+    if let Some(max) = options.max_tool_calls {
+        registry = registry.with_max_tool_calls(max);
+    }
+    if let Some(supervisor) = &options.stdio_supervisor {
+        registry = registry.with_stdio_supervisor(supervisor.clone());
+    }
+    if let Some(schemas) = &options.output_schemas {
+        registry = registry.with_output_schemas(schemas.clone());
+    }
+    if let Some(forwarder) = &options.tool_call_forwarder {
+        registry = registry.with_forwarder(forwarder.clone());
+    }
+    registry = registry.with_client_handlers(options.client_handlers.clone());
+
+    for config in &options.servers {
+        registry.add(config.clone())?;
+    }
+    Ok(registry)
 }
 
 /// Execute TypeScript/JavaScript code with `pctx_runtime`
@@ -270,15 +728,29 @@ async fn execute_code(
 ) -> anyhow::Result<InternalExecuteResult> {
     debug!("Starting code execution");
 
-    // Transpile TypeScript to JavaScript
-    let js_code = match pctx_deno_transpiler::transpile(code, None) {
-        Ok(js) => {
+    // Transpile TypeScript to JavaScript, unless the same code was transpiled before
+    let transpiled = if let Some(transpiled) = options
+        .check_cache
+        .as_ref()
+        .and_then(|c| c.transpiled(code))
+    {
+        debug!(runtime = "execution", "Transpiled code cache hit");
+        Ok(transpiled)
+    } else {
+        pctx_deno_transpiler::transpile_with_source_map(code, None).inspect(|transpiled| {
+            if let Some(cache) = &options.check_cache {
+                cache.insert_transpiled(code, transpiled.clone());
+            }
+        })
+    };
+    let (js_code, source_map) = match transpiled {
+        Ok(transpiled) => {
             debug!(
                 runtime = "execution",
-                transpiled_code_length = js.len(),
+                transpiled_code_length = transpiled.code.len(),
                 "Code transpiled successfully"
             );
-            js
+            (transpiled.code, transpiled.source_map)
         }
         Err(e) => {
             warn!(runtime = "execution", error = %e, "Transpilation failed");
@@ -291,15 +763,15 @@ async fn execute_code(
                 }),
                 stdout: String::new(),
                 stderr: String::new(),
+                console: vec![],
+                steps: vec![],
             });
         }
     };
 
-    // Create MCP registry and populate it with provided configs
-    let mcp_registry = pctx_code_execution_runtime::MCPRegistry::new();
-
-    for config in options.servers {
-        if let Err(e) = mcp_registry.add(config) {
+    let mcp_registry = match mcp_registry(&options) {
+        Ok(registry) => registry,
+        Err(e) => {
             warn!(runtime = "execution", error = %e, "Failed to register MCP server");
             return Ok(InternalExecuteResult {
                 success: false,
@@ -310,26 +782,63 @@ async fn execute_code(
                 }),
                 stdout: String::new(),
                 stderr: String::new(),
+                console: vec![],
+                steps: vec![],
             });
         }
-    }
+    };
 
     // Build extensions list
+    let sessions = mcp_registry.clone();
     let extensions = vec![pctx_code_execution_runtime::pctx_runtime_snapshot::init(
         mcp_registry,
         options.callback_registry,
+        options.wasm_modules,
     )];
 
     // Create JsRuntime from `pctx_runtime` snapshot and extension
     // The snapshot contains the ESM code pre-compiled, and init() registers both ops and ESM
     // Deno handles the deduplication when loading from snapshot
+    // Imports are resolved by the loader, only allowed remote modules can be loaded
     let mut js_runtime = JsRuntime::new(RuntimeOptions {
-        module_loader: Some(Rc::new(deno_core::FsModuleLoader)),
+        module_loader: Some(Rc::new(module_loader::ImportsModuleLoader::new(
+            options.imports,
+        ))),
         startup_snapshot: Some(pctx_code_execution_runtime::RUNTIME_SNAPSHOT),
         extensions,
         ..Default::default()
     });
 
+    if let Some(tape) = options.tape {
+        js_runtime.op_state().borrow_mut().put(tape);
+    }
+    js_runtime.op_state().borrow_mut().put(options.ids);
+    if let Some(fs) = options.fs {
+        js_runtime.op_state().borrow_mut().put(fs);
+    }
+    if let Some(artifacts) = options.artifacts {
+        js_runtime.op_state().borrow_mut().put(artifacts);
+    }
+    if let Some(results) = options.results {
+        js_runtime.op_state().borrow_mut().put(results);
+    }
+    if let Some(kv) = options.kv {
+        js_runtime.op_state().borrow_mut().put(kv);
+    }
+    if let Some(registrations) = options.tool_registrations {
+        js_runtime.op_state().borrow_mut().put(registrations);
+    }
+    if let Some(determinism) = options.determinism {
+        js_runtime.execute_script("<determinism>", determinism.script())?;
+    }
+
+    // Terminate running code & drop pending ops once the execution is cancelled
+    let signal = options.signal;
+    let _watchdog = signal.clone().map(|signal| {
+        js_runtime.op_state().borrow_mut().put(signal.clone());
+        watchdog::Watchdog::start(signal, js_runtime.v8_isolate().thread_safe_handle())
+    });
+
     // Create the main module specifier
     let main_module = deno_core::resolve_url("file:///execute.js")?;
 
@@ -354,6 +863,8 @@ async fn execute_code(
                 }),
                 stdout: String::new(),
                 stderr: String::new(),
+                console: vec![],
+                steps: vec![],
             });
         }
     };
@@ -372,19 +883,42 @@ async fn execute_code(
     // Drive both futures together - wait for BOTH to complete
     let (eval_result, event_loop_result) = futures::join!(eval_future, event_loop_future);
     debug!("Eval and event loop futures resolved");
+    sessions.close_sessions().await;
+
+    let Some(signal) = signal.filter(ExecutionSignal::is_cancelled) else {
+        return process_execution_results(
+            &mut js_runtime,
+            mod_id,
+            source_map.as_deref(),
+            eval_result.err(),
+            event_loop_result.err(),
+        );
+    };
 
-    process_execution_results(
+    // allow capturing the console output of the terminated execution
+    warn!(reason = signal.reason(), "Execution cancelled");
+    js_runtime.v8_isolate().cancel_terminate_execution();
+    let mut result = process_execution_results(
         &mut js_runtime,
         mod_id,
+        source_map.as_deref(),
         eval_result.err(),
         event_loop_result.err(),
-    )
+    )?;
+    result.success = false;
+    result.output = None;
+    result.error = Some(ExecutionError {
+        message: format!("Execution stopped: {}", signal.reason()),
+        stack: None,
+    });
+    Ok(result)
 }
 
 #[tracing::instrument(skip_all)]
 fn process_execution_results(
     js_runtime: &mut JsRuntime,
     mod_id: usize,
+    source_map: Option<&str>,
     eval_err: Option<CoreError>,
     event_loop_err: Option<CoreError>,
 ) -> anyhow::Result<InternalExecuteResult> {
@@ -410,7 +944,9 @@ fn process_execution_results(
     let capture_script = r"
         ({
             stdout: globalThis.__stdout || [],
-            stderr: globalThis.__stderr || []
+            stderr: globalThis.__stderr || [],
+            console: globalThis.__console || [],
+            steps: globalThis.__steps || []
         })
     ";
 
@@ -456,6 +992,16 @@ fn process_execution_results(
         })
         .unwrap_or_default();
 
+    let console = console_output
+        .as_ref()
+        .map(|v| console::records(v["console"].clone(), source_map))
+        .unwrap_or_default();
+
+    let steps = console_output
+        .as_ref()
+        .map(|v| steps::records(v["steps"].clone()))
+        .unwrap_or_default();
+
     // Extract default export from module namespace
     let output: Option<serde_json::Value> = module_namespace.and_then(|module_namespace| {
         let namespace = deno_core::v8::Local::new(scope, module_namespace);
@@ -489,6 +1035,8 @@ fn process_execution_results(
         error,
         stdout: stdout_str,
         stderr: stderr_str,
+        console,
+        steps,
     })
 }
 
@@ -0,0 +1,190 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    time::Duration,
+};
+
+use camino::Utf8Path;
+use deno_core::{
+    ModuleLoadOptions, ModuleLoadReferrer, ModuleLoadResponse, ModuleLoader, ModuleSource,
+    ModuleSourceCode, ModuleSpecifier, ModuleType, ResolutionKind, error::ModuleLoaderError,
+    resolve_import,
+};
+use deno_error::JsErrorBox;
+use pctx_config::imports::ImportsConfig;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Timeout of fetching a single remote module
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Module loader resolving `npm:`, `jsr:` and `https:` imports allowed by the [`ImportsConfig`]
+///
+/// Any other import (including local files) is rejected with an error explaining how to
+/// allow it, instead of failing with a file system error.
+pub(crate) struct ImportsModuleLoader {
+    imports: ImportsConfig,
+    client: reqwest::Client,
+}
+
+impl ImportsModuleLoader {
+    pub(crate) fn new(imports: ImportsConfig) -> Self {
+        Self {
+            imports,
+            client: reqwest::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn not_allowed(&self, specifier: &str) -> ModuleLoaderError {
+        let message = if self.imports.enabled() {
+            format!(
+                "Import \"{specifier}\" is not allowed, allowed imports are: {}",
+                self.imports.allow.join(", ")
+            )
+        } else {
+            format!(
+                "Import \"{specifier}\" is not allowed, module imports are disabled. Add the module to `imports.allow` in the pctx config to enable it"
+            )
+        };
+        JsErrorBox::generic(message)
+    }
+}
+
+impl ModuleLoader for ImportsModuleLoader {
+    fn resolve(
+        &self,
+        specifier: &str,
+        referrer: &str,
+        _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, ModuleLoaderError> {
+        if let Some(url) = self.imports.registry_url(specifier) {
+            if !self.imports.allows(specifier) {
+                return Err(self.not_allowed(specifier));
+            }
+            return ModuleSpecifier::parse(&url).map_err(|e| JsErrorBox::generic(e.to_string()));
+        }
+
+        // imports of loaded modules must be allowed too, modules of the registry are
+        // allowed if they belong to an allowed package
+        let resolved =
+            resolve_import(specifier, referrer).map_err(|e| JsErrorBox::generic(e.to_string()))?;
+        if self.imports.allows_url(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(self.not_allowed(specifier))
+        }
+    }
+
+    fn load(
+        &self,
+        module_specifier: &ModuleSpecifier,
+        _maybe_referrer: Option<&ModuleLoadReferrer>,
+        _options: ModuleLoadOptions,
+    ) -> ModuleLoadResponse {
+        let specifier = module_specifier.clone();
+        let client = self.client.clone();
+        let imports = self.imports.clone();
+        let cache_dir = self.imports.cache_dir.clone();
+
+        ModuleLoadResponse::Async(Box::pin(async move {
+            let module = match cache_dir
+                .as_deref()
+                .and_then(|d| CachedModule::read(d, &specifier))
+            {
+                Some(cached) => {
+                    debug!(runtime = "execution", specifier = %specifier, "Module cache hit");
+                    cached
+                }
+                None => {
+                    let fetched = CachedModule::fetch(&client, &specifier)
+                        .await
+                        .map_err(JsErrorBox::generic)?;
+                    if let Some(dir) = cache_dir.as_deref() {
+                        fetched.write(dir, &specifier);
+                    }
+                    fetched
+                }
+            };
+
+            let found = ModuleSpecifier::parse(&module.url).unwrap_or_else(|_| specifier.clone());
+            // the imports of redirected modules resolve relative to where they were found
+            if found != specifier && !imports.allows_url(&found) {
+                return Err(JsErrorBox::generic(format!(
+                    "Import \"{specifier}\" redirected to \"{found}\", which is not allowed"
+                )));
+            }
+            Ok(ModuleSource::new_with_redirect(
+                ModuleType::JavaScript,
+                ModuleSourceCode::String(module.code.into()),
+                &specifier,
+                &found,
+                None,
+            ))
+        }))
+    }
+}
+
+/// Remote module, stored as JSON in the cache directory
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedModule {
+    /// URL the module was found at after redirects
+    url: String,
+    /// JavaScript code of the module
+    code: String,
+}
+
+impl CachedModule {
+    async fn fetch(client: &reqwest::Client, specifier: &ModuleSpecifier) -> Result<Self, String> {
+        debug!(runtime = "execution", specifier = %specifier, "Fetching module");
+        let res = client
+            .get(specifier.as_str())
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| format!("Failed fetching module \"{specifier}\": {e}"))?;
+        let url = res.url().to_string();
+        // modules served as TypeScript (e.g. from deno.land) need transpiling
+        let is_typescript = [".ts", ".mts", ".tsx"]
+            .iter()
+            .any(|ext| res.url().path().ends_with(ext));
+        let code = res
+            .text()
+            .await
+            .map_err(|e| format!("Failed reading module \"{specifier}\": {e}"))?;
+
+        let code = if is_typescript {
+            pctx_deno_transpiler::transpile(&code, Some(&url))
+                .map_err(|e| format!("Failed transpiling module \"{specifier}\": {e}"))?
+        } else {
+            code
+        };
+
+        Ok(Self { url, code })
+    }
+
+    fn path(cache_dir: &Utf8Path, specifier: &ModuleSpecifier) -> camino::Utf8PathBuf {
+        let mut hasher = DefaultHasher::new();
+        specifier.as_str().hash(&mut hasher);
+        cache_dir.join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    fn read(cache_dir: &Utf8Path, specifier: &ModuleSpecifier) -> Option<Self> {
+        let contents = std::fs::read_to_string(Self::path(cache_dir, specifier)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Caches the module, failures are only logged as the module is loaded regardless
+    fn write(&self, cache_dir: &Utf8Path, specifier: &ModuleSpecifier) {
+        let res = std::fs::create_dir_all(cache_dir).and_then(|()| {
+            std::fs::write(
+                Self::path(cache_dir, specifier),
+                serde_json::to_string(self).unwrap_or_default(),
+            )
+        });
+        if let Err(e) = res {
+            debug!(runtime = "execution", error = %e, "Failed caching module {specifier}");
+        }
+    }
+}
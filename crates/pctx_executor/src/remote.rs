@@ -0,0 +1,283 @@
+//! Execution of code on remote workers
+//!
+//! Workers are `pctx worker` processes (or any service implementing `POST /execute`)
+//! receiving a [`RemoteRequest`] and responding with the [`ExecuteResult`]. Executions
+//! are distributed round-robin over the workers, failing over to the next worker if
+//! one is unreachable.
+//!
+//! Workers make the MCP tool calls themselves, so state shared between executions only
+//! lives as long as a worker: circuit breakers & cached results are kept per worker.
+//! Executions calling tools that are rate limited or require approval cannot run on
+//! workers, and upstream servers cannot send sampling or elicitation requests or
+//! progress to the client.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use pctx_config::{
+    client::Root, imports::ImportsConfig, server::ServerConfig, type_check::TypeCheckConfig,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{DenoExecutorError, Determinism, ExecuteOptions, ExecuteResult, Result};
+
+/// Path of the execution endpoint of workers
+pub static EXECUTE_PATH: &str = "/execute";
+
+/// Execution sent to a remote worker
+///
+/// Only contains the options that can be serialized, executions with local callbacks
+/// or WebAssembly modules cannot run remotely or in child processes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteRequest {
+    pub code: String,
+    #[serde(default)]
+    pub allowed_hosts: Option<Vec<String>>,
+    #[serde(default)]
+    pub servers: Vec<ServerConfig>,
+    #[serde(default)]
+    pub type_check: TypeCheckConfig,
+    #[serde(default)]
+    pub skip_type_check: bool,
+    #[serde(default)]
+    pub imports: ImportsConfig,
+    #[serde(default)]
+    pub timeout_secs: Option<f64>,
+    #[serde(default)]
+    pub max_tool_calls: Option<u32>,
+    #[serde(default)]
+    pub determinism: Option<Determinism>,
+    #[serde(default)]
+    pub roots: Option<Vec<Root>>,
+}
+
+impl RemoteRequest {
+    /// Request executing `code` with `options`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options have local callbacks, WebAssembly modules, a virtual
+    /// filesystem, an artifact or result store, a tape or accept tool registrations
+    pub fn new(code: &str, options: &ExecuteOptions) -> Result<Self> {
+        if !options.callback_registry.ids().is_empty() {
+            return Err(DenoExecutorError::RemoteError(
+                "Executions with local callbacks cannot run outside of this process".into(),
+            ));
+        }
+        if options.tool_registrations.is_some() {
+            return Err(DenoExecutorError::RemoteError(
+                "Executions registering tools cannot run outside of this process".into(),
+            ));
+        }
+        if options.tape.is_some() {
+            return Err(DenoExecutorError::RemoteError(
+                "Recorded or replayed executions cannot run outside of this process".into(),
+            ));
+        }
+        if !options.wasm_modules.names().is_empty() {
+            return Err(DenoExecutorError::RemoteError(
+                "Executions with WebAssembly modules cannot run outside of this process".into(),
+            ));
+        }
+        if options.artifacts.is_some() || options.results.is_some() {
+            return Err(DenoExecutorError::RemoteError(
+                "Executions with artifacts or offloaded results cannot run outside of this process"
+                    .into(),
+            ));
+        }
+        if options.kv.is_some() {
+            return Err(DenoExecutorError::RemoteError(
+                "Executions with a key-value store cannot run outside of this process".into(),
+            ));
+        }
+        if options.fs.is_some() {
+            return Err(DenoExecutorError::RemoteError(
+                "Executions with a virtual filesystem cannot run outside of this process".into(),
+            ));
+        }
+
+        Ok(Self {
+            code: code.to_string(),
+            allowed_hosts: options.allowed_hosts.clone(),
+            servers: options.servers.clone(),
+            type_check: options.type_check.clone(),
+            skip_type_check: options.skip_type_check,
+            imports: options.imports.clone(),
+            timeout_secs: options.timeout.map(|t| t.as_secs_f64()),
+            max_tool_calls: options.max_tool_calls,
+            determinism: options.determinism,
+            roots: options.client_handlers.roots.clone(),
+        })
+    }
+
+    /// Options executing the request on this process
+    pub fn options(&self) -> ExecuteOptions {
+        let mut options = ExecuteOptions::new()
+            .with_servers(self.servers.clone())
+            .with_type_check(self.type_check.clone())
+            .with_skip_type_check(self.skip_type_check)
+            .with_imports(self.imports.clone());
+        options.allowed_hosts.clone_from(&self.allowed_hosts);
+        options.max_tool_calls = self.max_tool_calls;
+        options.determinism = self.determinism;
+        options.client_handlers.roots.clone_from(&self.roots);
+        if let Some(timeout) = self.timeout_secs {
+            options = options.with_timeout(Duration::from_secs_f64(timeout));
+        }
+        options
+    }
+}
+
+/// Rejects executions depending on state a remote worker cannot share with this process
+///
+/// # Errors
+///
+/// Returns an error if a server has tools that are rate limited or require approval
+pub(crate) fn check_worker_options(options: &ExecuteOptions) -> Result<()> {
+    for server in &options.servers {
+        for (name, tool) in &server.tools {
+            if tool.rate_limit_per_minute.is_some() {
+                return Err(DenoExecutorError::RemoteError(format!(
+                    "Tool `{name}` of MCP server `{}` is rate limited and cannot be called on remote workers",
+                    server.name
+                )));
+            }
+            if tool.requires_approval {
+                return Err(DenoExecutorError::RemoteError(format!(
+                    "Tool `{name}` of MCP server `{}` requires approval and cannot be called on remote workers",
+                    server.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pool of remote workers executions are distributed over
+#[derive(Debug, Clone)]
+pub struct RemoteWorkers {
+    urls: Arc<Vec<String>>,
+    next: Arc<AtomicUsize>,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl RemoteWorkers {
+    /// Workers reachable at the base `urls`, e.g. `http://worker-1:8090`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `urls` is empty
+    pub fn new(urls: Vec<String>) -> Self {
+        assert!(!urls.is_empty(), "at least one remote worker is required");
+        Self {
+            urls: Arc::new(
+                urls.into_iter()
+                    .map(|u| u.trim_end_matches('/').to_string())
+                    .collect(),
+            ),
+            next: Arc::default(),
+            token: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send `token` as `Authorization: Bearer <token>` to the workers
+    #[must_use]
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Timeout of executions on a worker, including type checking
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("failed to build remote worker client");
+        self
+    }
+
+    /// Base URLs of the workers
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Executes the request on the next worker, trying the other workers in turn if
+    /// workers are unreachable
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no worker could be reached or a worker failed the request
+    pub async fn execute(&self, request: &RemoteRequest) -> Result<ExecuteResult> {
+        let body = serde_json::to_vec(request)
+            .map_err(|e| DenoExecutorError::RemoteError(format!("Invalid request: {e}")))?;
+
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for i in 0..self.urls.len() {
+            let url = &self.urls[(start + i) % self.urls.len()];
+            let mut req = self
+                .client
+                .post(format!("{url}{EXECUTE_PATH}"))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            if let Some(token) = &self.token {
+                req = req.bearer_auth(token);
+            }
+
+            debug!(worker = %url, "Executing code on remote worker");
+            let res = match req.send().await {
+                Ok(res) => res,
+                Err(e) if e.is_connect() => {
+                    warn!(worker = %url, "Remote worker unreachable: {e}");
+                    last_error = Some(e.to_string());
+                    continue;
+                }
+                Err(e) => {
+                    return Err(DenoExecutorError::RemoteError(format!(
+                        "Remote worker {url} failed: {e}"
+                    )));
+                }
+            };
+
+            let status = res.status();
+            let bytes = res.bytes().await.map_err(|e| {
+                DenoExecutorError::RemoteError(format!("Remote worker {url} failed: {e}"))
+            })?;
+            if status == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+                warn!(worker = %url, "Remote worker busy");
+                last_error = Some(format!("{url} is busy"));
+                continue;
+            }
+            if !status.is_success() {
+                return Err(DenoExecutorError::RemoteError(format!(
+                    "Remote worker {url} responded with {status}: {}",
+                    String::from_utf8_lossy(&bytes)
+                )));
+            }
+
+            return serde_json::from_slice(&bytes).map_err(|e| {
+                DenoExecutorError::RemoteError(format!(
+                    "Invalid result from remote worker {url}: {e}"
+                ))
+            });
+        }
+
+        Err(DenoExecutorError::RemoteError(format!(
+            "No remote worker available: {}",
+            last_error.unwrap_or_default()
+        )))
+    }
+}
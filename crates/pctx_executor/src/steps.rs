@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Status of a step recorded with `pctx.step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    /// The step had not completed when the execution ended, e.g. it was stopped
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// A step of the executed code, recorded with `pctx.step(name, fn)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepRecord {
+    pub name: String,
+    pub status: StepStatus,
+    /// Start of the step in milliseconds since the Unix epoch
+    pub started_at: u64,
+    /// Duration of the step in milliseconds, `None` while it is running
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Value returned by the step serialized as JSON
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    /// Message of the error thrown by the step
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Steps nested in the step, in the order they started
+    #[serde(default)]
+    pub steps: Vec<StepRecord>,
+}
+
+/// Parses the step tree captured by the runtime
+pub(crate) fn records(captured: serde_json::Value) -> Vec<StepRecord> {
+    serde_json::from_value(captured).unwrap_or_else(|e| {
+        debug!(runtime = "execution", error = %e, "Failed parsing step records");
+        vec![]
+    })
+}
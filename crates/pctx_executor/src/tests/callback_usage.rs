@@ -1,6 +1,15 @@
-use std::sync::Arc;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use pctx_code_execution_runtime::CallbackRegistry;
+use pctx_code_execution_runtime::{
+    CallTape, CallbackRegistry, ExecutionIds, InvocationContext, RecordedOutcome, ToolDefinition,
+    ToolRegistrations,
+};
 use serde::Deserialize;
 use serde_json::json;
 
@@ -117,3 +126,358 @@ export default await test();
     );
     assert!(result.diagnostics.is_empty(), "Should have no type errors");
 }
+
+#[serial]
+#[tokio::test]
+async fn test_concurrent_callbacks() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let registry = CallbackRegistry::default();
+    let (current, max) = (in_flight.clone(), max_in_flight.clone());
+    registry
+        .add(
+            "Slow.fetch",
+            Arc::new(move |args: Option<serde_json::Value>| {
+                let (current, max) = (current.clone(), max.clone());
+                Box::pin(async move {
+                    let running = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(running, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+                    Ok(args.unwrap_or_default()["i"].clone())
+                })
+            }),
+        )
+        .expect("callback registration should succeed");
+
+    let code = r#"
+const ids = Array.from({ length: 10 }, (_, i) => i);
+export default await Promise.all(
+  ids.map((i) => invokeCallback({ id: "Slow.fetch", arguments: { i } })),
+);
+"#;
+    let started = Instant::now();
+    let result = execute(
+        code,
+        ExecuteOptions::new()
+            .with_callbacks(registry)
+            .with_skip_type_check(true),
+    )
+    .await
+    .expect("execution should succeed");
+
+    assert!(result.success, "{}", result.stderr);
+    assert_eq!(result.output, Some(json!([0, 1, 2, 3, 4, 5, 6, 7, 8, 9])));
+    // the fan-out takes about as long as one call, not the sum of all 10
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 10);
+    assert!(
+        started.elapsed() < Duration::from_millis(1500),
+        "{:?}",
+        started.elapsed()
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_timeout_cancels_pending_callbacks() {
+    struct Dropped(Arc<AtomicBool>);
+    impl Drop for Dropped {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let registry = CallbackRegistry::default();
+    let flag = cancelled.clone();
+    registry
+        .add(
+            "Slow.wait",
+            Arc::new(move |_| {
+                let guard = Dropped(flag.clone());
+                Box::pin(async move {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    drop(guard);
+                    Ok(json!(null))
+                })
+            }),
+        )
+        .expect("callback registration should succeed");
+
+    let code = r#"
+export default await invokeCallback({ id: "Slow.wait", arguments: {} });
+"#;
+    let started = Instant::now();
+    let result = execute(
+        code,
+        ExecuteOptions::new()
+            .with_callbacks(registry)
+            .with_skip_type_check(true)
+            .with_timeout(Duration::from_millis(200)),
+    )
+    .await
+    .expect("execution should complete");
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(!result.success);
+    assert!(
+        result.stderr.contains("deadline exceeded"),
+        "{}",
+        result.stderr
+    );
+    assert!(
+        cancelled.load(Ordering::SeqCst),
+        "pending callback should be dropped"
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_timeout_terminates_busy_code() {
+    let result = execute(
+        "while (true) {}",
+        ExecuteOptions::new()
+            .with_skip_type_check(true)
+            .with_timeout(Duration::from_millis(200)),
+    )
+    .await
+    .expect("execution should complete");
+
+    assert!(!result.success);
+    assert!(
+        result.stderr.contains("deadline exceeded"),
+        "{}",
+        result.stderr
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_record_and_replay_callbacks() {
+    let registry = CallbackRegistry::default();
+    registry
+        .add(
+            "Dice.roll",
+            Arc::new(move |_| Box::pin(async move { Ok(json!(4)) })),
+        )
+        .expect("callback registration should succeed");
+
+    let code = r#"
+const a = await invokeCallback({ id: "Dice.roll", arguments: { sides: 6 } });
+const b = await invokeCallback({ id: "Dice.roll", arguments: { sides: 20 } }).catch((e) => e.message);
+export default [a, b];
+"#;
+    let tape = CallTape::recording();
+    let recorded = execute(
+        code,
+        ExecuteOptions::new()
+            .with_callbacks(registry)
+            .with_skip_type_check(true)
+            .with_tape(tape.clone()),
+    )
+    .await
+    .expect("execution should succeed");
+    assert_eq!(recorded.output, Some(json!([4, 4])));
+
+    let calls = tape.calls();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].tool, "Dice.roll");
+    assert_eq!(calls[0].arguments, Some(json!({ "sides": 6 })));
+    assert_eq!(calls[0].outcome, RecordedOutcome::Output(json!(4)));
+
+    // replayed without the callback registered
+    let replayed = execute(
+        code,
+        ExecuteOptions::new()
+            .with_skip_type_check(true)
+            .with_tape(CallTape::replaying(calls)),
+    )
+    .await
+    .expect("execution should succeed");
+    assert_eq!(replayed.output, recorded.output);
+
+    // calls that were not recorded fail
+    let unrecorded = execute(
+        code,
+        ExecuteOptions::new()
+            .with_skip_type_check(true)
+            .with_tape(CallTape::replaying(vec![])),
+    )
+    .await
+    .expect("execution should complete");
+    assert!(!unrecorded.success);
+    assert!(
+        unrecorded.stderr.contains("No recorded result"),
+        "{}",
+        unrecorded.stderr
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_callback_invocation_context() {
+    let registry = CallbackRegistry::default();
+    registry
+        .add(
+            "Audit.whoami",
+            Arc::new(move |_| {
+                Box::pin(async move {
+                    let context = InvocationContext::current();
+                    Ok(json!({
+                        "callback": context.callback_id,
+                        "namespace": context.namespace(),
+                        "execution": context.ids.execution_id,
+                        "session": context.ids.session_id,
+                    }))
+                })
+            }),
+        )
+        .expect("callback registration should succeed");
+
+    let result = execute(
+        r#"export default await invokeCallback({ id: "Audit.whoami" });"#,
+        ExecuteOptions::new()
+            .with_callbacks(registry)
+            .with_skip_type_check(true)
+            .with_ids(ExecutionIds {
+                execution_id: Some("exec-1".into()),
+                session_id: Some("session-1".into()),
+            }),
+    )
+    .await
+    .expect("execution should succeed");
+
+    assert_eq!(
+        result.output,
+        Some(json!({
+            "callback": "Audit.whoami",
+            "namespace": "Audit",
+            "execution": "exec-1",
+            "session": "session-1",
+        }))
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_register_tools() {
+    let registry = CallbackRegistry::default();
+    registry
+        .add(
+            "Api.discover",
+            Arc::new(move |_| {
+                Box::pin(async move {
+                    InvocationContext::current()
+                        .register_tool(ToolDefinition {
+                            namespace: "Api".into(),
+                            name: "getUser".into(),
+                            description: Some("Gets a user".into()),
+                            input_schema: None,
+                            output_schema: None,
+                        })
+                        .map_err(|e| e.to_string())?;
+                    Ok(json!(null))
+                })
+            }),
+        )
+        .expect("callback registration should succeed");
+
+    let registrations = ToolRegistrations::new();
+    let result = execute(
+        r#"
+await invokeCallback({ id: "Api.discover" });
+pctx.tools.register({ namespace: "Api", name: "listUsers" });
+"#,
+        ExecuteOptions::new()
+            .with_callbacks(registry)
+            .with_skip_type_check(true)
+            .with_tool_registrations(registrations.clone()),
+    )
+    .await
+    .expect("execution should succeed");
+    assert!(
+        result.success,
+        "execution should succeed: {}",
+        result.stderr
+    );
+
+    let ids: Vec<String> = registrations
+        .tools()
+        .iter()
+        .map(ToolDefinition::id)
+        .collect();
+    assert_eq!(ids, vec!["Api.getUser", "Api.listUsers"]);
+
+    // without registrations accepted, registering fails
+    let result = execute(
+        r#"pctx.tools.register({ namespace: "Api", name: "listUsers" });"#,
+        ExecuteOptions::new().with_skip_type_check(true),
+    )
+    .await
+    .expect("execution should run");
+    assert!(!result.success);
+}
+
+#[serial]
+#[tokio::test]
+async fn test_failed_calls_throw_typed_errors() {
+    let registry = CallbackRegistry::default();
+    registry
+        .add(
+            "Billing.charge",
+            Arc::new(move |_args: Option<serde_json::Value>| {
+                Box::pin(async move { Err("card declined".to_string()) })
+            }),
+        )
+        .expect("callback registration should succeed");
+
+    let code = r#"
+async function describe(call: () => Promise<unknown>) {
+    try {
+        await call();
+        return null;
+    } catch (e) {
+        return {
+            name: e.name,
+            tool: e.tool,
+            toolCallError: e instanceof ToolCallError,
+            toolError: e instanceof ToolError,
+            networkError: e instanceof NetworkError,
+            error: e instanceof Error,
+        };
+    }
+}
+
+export default [
+    await describe(() => invokeCallback({ id: "Billing.charge", arguments: {} })),
+    await describe(() => callMCPTool({ serverName: "missing", toolName: "list" })),
+];
+"#;
+
+    let result = execute(code, ExecuteOptions::new().with_callbacks(registry))
+        .await
+        .expect("execution should succeed");
+
+    assert!(result.success, "{}", result.stderr);
+    assert_eq!(
+        result.output,
+        Some(json!([
+            {
+                "name": "ToolError",
+                "tool": "Billing.charge",
+                "toolCallError": true,
+                "toolError": true,
+                "networkError": false,
+                "error": true,
+            },
+            {
+                "name": "ToolError",
+                "tool": "missing.list",
+                "toolCallError": true,
+                "toolError": true,
+                "networkError": false,
+                "error": true,
+            },
+        ]))
+    );
+}
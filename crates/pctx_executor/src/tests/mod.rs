@@ -18,6 +18,9 @@ mod callback_usage;
 mod default_export_capture;
 mod diagnostic_filtering;
 mod mcp_client_usage;
+mod module_imports;
 mod output_capture;
+#[cfg(unix)]
+mod process_isolation;
 mod runtime_execution;
 mod type_checking;
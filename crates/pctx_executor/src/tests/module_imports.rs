@@ -0,0 +1,68 @@
+use super::serial;
+use crate::{ExecuteOptions, execute};
+use pctx_config::imports::ImportsConfig;
+
+#[tokio::test]
+#[serial]
+async fn test_import_disabled_by_default() {
+    let code = r#"
+import _ from "npm:lodash";
+export default _.chunk([1, 2, 3], 2);
+"#;
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(!result.success, "Imports should be disabled");
+    let error = result.runtime_error.expect("Should have runtime error");
+    assert!(
+        error.message.contains("imports.allow"),
+        "Error should explain how to allow the import, got: {}",
+        error.message
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_import_not_in_allowlist() {
+    let code = r#"
+import { z } from "npm:zod";
+export default typeof z;
+"#;
+    let imports = ImportsConfig {
+        allow: vec!["npm:lodash".into()],
+        ..Default::default()
+    };
+
+    let result = execute(code, ExecuteOptions::new().with_imports(imports))
+        .await
+        .expect("execution should succeed");
+    assert!(!result.success, "zod is not allowed");
+    let error = result.runtime_error.expect("Should have runtime error");
+    assert!(
+        error.message.contains("\"npm:zod\" is not allowed")
+            && error.message.contains("npm:lodash"),
+        "Error should list the allowed imports, got: {}",
+        error.message
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_local_import_rejected() {
+    let code = r#"
+import { secret } from "./secret.js";
+export default secret;
+"#;
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(!result.success);
+    let error = result.runtime_error.expect("Should have runtime error");
+    assert!(
+        error.message.contains("is not allowed"),
+        "Local imports should be rejected, got: {}",
+        error.message
+    );
+}
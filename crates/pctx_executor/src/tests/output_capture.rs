@@ -1,5 +1,5 @@
 use super::serial;
-use crate::{ExecuteOptions, execute};
+use crate::{ConsoleLevel, ExecuteOptions, StepStatus, execute};
 
 #[serial]
 #[tokio::test]
@@ -206,3 +206,131 @@ export default "done";
         result.stdout
     );
 }
+
+#[serial]
+#[tokio::test]
+async fn test_execute_console_records() {
+    let code = r#"
+interface Item {
+    id: number;
+}
+const item: Item = { id: 1 };
+console.log("item", item);
+console.warn("careful");
+export default "result";
+"#;
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "Code should execute successfully");
+    assert_eq!(result.console.len(), 2, "got: {:?}", result.console);
+
+    let log = &result.console[0];
+    assert_eq!(log.level, ConsoleLevel::Log);
+    assert_eq!(
+        log.args,
+        vec![serde_json::json!("item"), serde_json::json!({"id": 1})]
+    );
+    assert_eq!(
+        log.line,
+        Some(6),
+        "call site should map to the TypeScript line"
+    );
+    assert!(log.timestamp > 0);
+
+    let warn = &result.console[1];
+    assert_eq!(warn.level, ConsoleLevel::Warn);
+    assert_eq!(warn.line, Some(7));
+    assert!(result.stderr.contains("careful"));
+}
+
+#[serial]
+#[tokio::test]
+async fn test_execute_captures_table_and_unhandled_rejections() {
+    let code = r#"
+console.info("info");
+console.debug("debug");
+console.table([{ a: 1, b: "x" }, { a: 2 }]);
+Promise.reject(new Error("nobody listens"));
+await pctx.std.sleep(10);
+export default "result";
+"#;
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(
+        result.success,
+        "Unhandled rejections should not abort execution"
+    );
+    assert_eq!(result.output, Some(serde_json::json!("result")));
+
+    let levels: Vec<ConsoleLevel> = result.console.iter().map(|r| r.level).collect();
+    assert_eq!(
+        levels,
+        vec![
+            ConsoleLevel::Info,
+            ConsoleLevel::Debug,
+            ConsoleLevel::Table,
+            ConsoleLevel::UnhandledRejection,
+        ]
+    );
+    assert!(
+        result.stdout.contains("| (index) | a | b |"),
+        "stdout should contain the table, got: {}",
+        result.stdout
+    );
+    assert!(
+        result.stderr.contains("Uncaught (in promise)") && result.stderr.contains("nobody listens"),
+        "stderr should contain the rejection reason, got: {}",
+        result.stderr
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_execute_step_records() {
+    let code = r#"
+const users = await pctx.step("fetch users", async (step) => {
+    await step.step("page 1", () => [1, 2]);
+    return [1, 2];
+});
+try {
+    await pctx.step("notify", async () => {
+        throw new Error("slack is down");
+    });
+} catch {}
+export default users.length;
+"#;
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "{}", result.stderr);
+    assert_eq!(result.steps.len(), 2, "got: {:?}", result.steps);
+
+    let fetch = &result.steps[0];
+    assert_eq!(fetch.name, "fetch users");
+    assert_eq!(fetch.status, StepStatus::Succeeded);
+    assert_eq!(fetch.result, Some(serde_json::json!([1, 2])));
+    assert!(fetch.duration_ms.is_some());
+    assert_eq!(fetch.steps.len(), 1);
+    assert_eq!(fetch.steps[0].name, "page 1");
+
+    let notify = &result.steps[1];
+    assert_eq!(notify.status, StepStatus::Failed);
+    assert_eq!(notify.error.as_deref(), Some("slack is down"));
+
+    // steps of failed executions are returned too
+    let code = r#"
+await pctx.step("fetch", () => 1);
+await pctx.step("process", () => { throw new Error("invalid"); });
+"#;
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should complete");
+    assert!(!result.success);
+    let statuses: Vec<StepStatus> = result.steps.iter().map(|s| s.status).collect();
+    assert_eq!(statuses, [StepStatus::Succeeded, StepStatus::Failed]);
+}
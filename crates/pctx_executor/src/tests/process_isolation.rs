@@ -0,0 +1,139 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use deno_core::anyhow;
+use pctx_code_execution_runtime::RateLimiters;
+use pctx_config::{
+    client::{ApprovalHandler, ApprovalRequest, ClientHandlers},
+    server::{ServerConfig, ToolConfig},
+};
+use serde_json::json;
+
+use super::serial;
+use crate::{ExecuteOptions, Isolation, ProcessIsolation, execute};
+
+fn shell(script: &str) -> ExecuteOptions {
+    ExecuteOptions::new().with_isolation(Isolation::Process(
+        ProcessIsolation::new("sh").with_args(vec!["-c".into(), script.into()]),
+    ))
+}
+
+#[tokio::test]
+#[serial]
+async fn test_process_isolation_result() {
+    let result = json!({
+        "success": true,
+        "diagnostics": [],
+        "runtime_error": null,
+        "output": 2,
+        "stdout": "",
+        "stderr": "",
+        "console": [],
+        "type_checked": true
+    });
+    let options = shell(&format!("read -r _; printf '%s' '{{\"result\":{result}}}'"));
+
+    let result = execute("export default 1 + 1;", options)
+        .await
+        .expect("execution should succeed");
+    assert!(result.success);
+    assert_eq!(result.output, Some(json!(2)));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_process_isolation_crash() {
+    let result = execute(
+        "export default 1;",
+        shell("read -r _; echo 'out of memory' >&2; exit 3"),
+    )
+    .await
+    .expect("crashes should be failed executions");
+    assert!(!result.success);
+    assert!(result.stderr.contains("crashed"), "{}", result.stderr);
+    assert!(result.stderr.contains("out of memory"), "{}", result.stderr);
+
+    let options = ExecuteOptions::new().with_isolation(Isolation::Process(
+        ProcessIsolation::new("sh")
+            .with_args(vec!["-c".into(), "sleep 5".into()])
+            .with_timeout(Duration::from_millis(100)),
+    ));
+    let result = execute("export default 1;", options)
+        .await
+        .expect("timeouts should be failed executions");
+    assert!(!result.success);
+    assert!(result.stderr.contains("timed out"), "{}", result.stderr);
+}
+
+#[derive(Debug, Default)]
+struct DenyAll {
+    requests: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl ApprovalHandler for DenyAll {
+    async fn approve(&self, request: &ApprovalRequest) -> anyhow::Result<bool> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(format!("{}.{}", request.server, request.tool));
+        Ok(false)
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_process_isolation_forwards_tool_calls() {
+    let mut server =
+        ServerConfig::new("upstream".into(), "http://127.0.0.1:1/mcp".parse().unwrap());
+    server.tools.insert(
+        "search".into(),
+        ToolConfig {
+            rate_limit_per_minute: Some(1),
+            ..Default::default()
+        },
+    );
+    server.tools.insert(
+        "delete".into(),
+        ToolConfig {
+            requires_approval: true,
+            ..Default::default()
+        },
+    );
+
+    // the limit was reached by an earlier execution
+    let limiters = RateLimiters::new();
+    limiters.acquire("upstream", "search", 1).unwrap();
+    let approvals = Arc::new(DenyAll::default());
+
+    // the child calls both tools & returns the replies of the parent as output
+    let script = r#"read -r _
+echo '{"call":{"id":0,"server":"upstream","tool":"search","arguments":null}}'
+read -r limited
+echo '{"call":{"id":1,"server":"upstream","tool":"delete","arguments":{"path":"/"}}}'
+read -r denied
+printf '{"result":{"success":true,"diagnostics":[],"runtime_error":null,"output":[%s,%s],"stdout":"","stderr":"","console":[],"type_checked":true}}\n' "$limited" "$denied""#;
+    let options = shell(script)
+        .with_servers(vec![server])
+        .with_rate_limiters(limiters)
+        .with_client_handlers(ClientHandlers::default().with_approval(approvals.clone()));
+
+    let result = execute("export default 1;", options)
+        .await
+        .expect("execution should succeed");
+    let output = result.output.expect("output should be set");
+
+    assert_eq!(output[0]["id"], json!(0));
+    let limited = output[0]["result"]["Err"]["LimitExceeded"]
+        .as_str()
+        .unwrap();
+    assert!(limited.contains("rate limited"), "{limited}");
+
+    assert_eq!(output[1]["id"], json!(1));
+    let denied = output[1]["result"]["Err"]["Denied"].as_str().unwrap();
+    assert!(denied.contains("not approved"), "{denied}");
+    assert_eq!(*approvals.requests.lock().unwrap(), vec!["upstream.delete"]);
+}
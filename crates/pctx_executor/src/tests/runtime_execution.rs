@@ -1,5 +1,10 @@
+use pctx_code_execution_runtime::{
+    ArtifactData, ArtifactStore, KvStore, ResultStore, VirtualFs, WasmModules,
+};
+use pctx_config::{client::ClientHandlers, kv::KvConfig, results::ResultsConfig};
+
 use super::serial;
-use crate::{ExecuteOptions, execute};
+use crate::{Determinism, ExecuteOptions, execute};
 
 #[tokio::test]
 #[serial]
@@ -81,3 +86,329 @@ export default await test();
             .starts_with("ReferenceError: fetch is not defined")
     )
 }
+
+#[tokio::test]
+#[serial]
+async fn test_std_helpers() {
+    let code = r#"
+const { std } = pctx;
+let attempts = 0;
+const retried = await std.retry(async () => {
+  attempts++;
+  if (attempts < 2) throw new Error("transient");
+  return attempts;
+}, { delayMs: 1 });
+await std.sleep(1);
+
+export default {
+  chunks: std.chunk([1, 2, 3, 4, 5], 2),
+  encoded: std.base64.encode("héllo"),
+  decoded: std.base64.decode(std.base64.encode("héllo")),
+  rows: std.csv.parse('name,note\nada,"says ""hi"", twice"\n'),
+  csv: std.csv.stringify([{ a: 1, b: "x,y" }]),
+  json: std.json.tryParse("{oops", null),
+  date: std.date.format(Date.UTC(2024, 0, 2, 3, 4, 5), "YYYY-MM-DD HH:mm:ss"),
+  retried,
+};
+"#;
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "std helpers should run: {}", result.stderr);
+    assert_eq!(
+        result.output,
+        Some(serde_json::json!({
+            "chunks": [[1, 2], [3, 4], [5]],
+            "encoded": "aMOpbGxv",
+            "decoded": "héllo",
+            "rows": [{ "name": "ada", "note": "says \"hi\", twice" }],
+            "csv": "a,b\n1,\"x,y\"",
+            "json": null,
+            "date": "2024-01-02 03:04:05",
+            "retried": 2,
+        }))
+    );
+}
+
+// (module (func (export "add") (param i32 i32) (result i32) local.get 0 local.get 1 i32.add))
+const ADD_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01,
+    0x7f, 0x03, 0x02, 0x01, 0x00, 0x07, 0x07, 0x01, 0x03, 0x61, 0x64, 0x64, 0x00, 0x00, 0x0a, 0x09,
+    0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a, 0x0b,
+];
+
+#[serial]
+#[tokio::test]
+async fn test_wasm_modules() {
+    let code = r#"
+const math = await pctx.wasm.load("math");
+let missing = "";
+try {
+  await pctx.wasm.load("codec");
+} catch (e) {
+  missing = String(e);
+}
+export default { sum: math.exports.add(2, 3), missing };
+"#;
+
+    let options =
+        ExecuteOptions::new().with_wasm_modules(WasmModules::new().with_module("math", ADD_WASM));
+    let result = execute(code, options)
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "wasm module should load: {}", result.stderr);
+
+    let output = result.output.expect("should have output");
+    assert_eq!(output["sum"], serde_json::json!(5));
+    assert!(
+        output["missing"]
+            .as_str()
+            .is_some_and(|e| e.contains("WebAssembly module not found: codec")),
+        "got: {output}"
+    );
+}
+
+#[tokio::test]
+async fn test_virtual_fs() {
+    let code = r#"
+pctx.fs.writeText("/scratch/out.txt", pctx.fs.readText("/inputs/in.txt").toUpperCase());
+let denied = "";
+try {
+  pctx.fs.writeText("/inputs/in.txt", "overwritten");
+} catch (e) {
+  denied = String(e);
+}
+export default { files: pctx.fs.list(), exists: pctx.fs.exists("/scratch/missing"), denied };
+"#;
+
+    let fs = VirtualFs::in_memory()
+        .allow_read("/inputs")
+        .allow_write("/scratch");
+    fs.write("/inputs/in.txt", "hello").unwrap();
+    fs.write("/private/key", "secret").unwrap();
+
+    let result = execute(code, ExecuteOptions::new().with_fs(fs.clone()))
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "fs should be usable: {}", result.stderr);
+
+    let output = result.output.expect("should have output");
+    assert_eq!(
+        output["files"],
+        serde_json::json!(["/inputs/in.txt", "/scratch/out.txt"])
+    );
+    assert_eq!(output["exists"], serde_json::json!(false));
+    assert!(
+        output["denied"]
+            .as_str()
+            .is_some_and(|e| e.contains("Permission denied: no write access to /inputs/in.txt")),
+        "got: {output}"
+    );
+    assert_eq!(fs.read("/scratch/out.txt").unwrap(), b"HELLO");
+
+    // without a filesystem `pctx.fs` fails
+    let result = execute("export default pctx.fs.list();", ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(!result.success);
+    assert!(
+        result.stderr.contains("pctx.fs is not enabled"),
+        "{}",
+        result.stderr
+    );
+}
+
+#[tokio::test]
+async fn test_artifacts() {
+    let store = ArtifactStore::default();
+
+    let put = r#"
+pctx.artifacts.put("rows", [{ id: 1 }, { id: 2 }]);
+pctx.artifacts.put("raw", new Uint8Array([1, 2, 3]));
+export default pctx.artifacts.list().map((a) => `${a.name}:${a.kind}:${a.size}`);
+"#;
+    let result = execute(put, ExecuteOptions::new().with_artifacts(store.clone()))
+        .await
+        .expect("execution should succeed");
+    assert!(
+        result.success,
+        "artifacts should be stored: {}",
+        result.stderr
+    );
+    assert_eq!(
+        result.output,
+        Some(serde_json::json!(["raw:bytes:3", "rows:json:19"]))
+    );
+
+    // a later execution with the same store gets the artifacts
+    let get = r#"
+const rows = pctx.artifacts.get("rows");
+const raw = pctx.artifacts.get("raw");
+export default { ids: rows.map((r) => r.id), raw: Array.from(raw), missing: pctx.artifacts.get("missing") ?? null };
+"#;
+    let result = execute(get, ExecuteOptions::new().with_artifacts(store.clone()))
+        .await
+        .expect("execution should succeed");
+    assert!(
+        result.success,
+        "artifacts should be read: {}",
+        result.stderr
+    );
+    assert_eq!(
+        result.output,
+        Some(serde_json::json!({ "ids": [1, 2], "raw": [1, 2, 3], "missing": null }))
+    );
+    assert_eq!(store.get("raw"), Some(ArtifactData::Bytes(vec![1, 2, 3])));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_kv() {
+    let store = KvStore::in_memory(KvConfig::default()).unwrap();
+
+    let set = r#"
+pctx.kv.set("visits", 1);
+pctx.kv.set("visits", 2, { namespace: "other" });
+pctx.kv.set("session", { step: "fetch" }, { ttlSecs: 60 });
+export default pctx.kv.get("visits");
+"#;
+    let result = execute(set, ExecuteOptions::new().with_kv(store.clone()))
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "entries should be set: {}", result.stderr);
+    assert_eq!(result.output, Some(serde_json::json!(1)));
+
+    // a later execution with the same store sees the entries
+    let get = r#"
+export default {
+  keys: pctx.kv.list().map((e) => e.key),
+  other: pctx.kv.get("visits", { namespace: "other" }),
+  removed: pctx.kv.remove("visits"),
+  missing: pctx.kv.get("visits") ?? null,
+};
+"#;
+    let result = execute(get, ExecuteOptions::new().with_kv(store.clone()))
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "entries should be read: {}", result.stderr);
+    assert_eq!(
+        result.output,
+        Some(serde_json::json!({
+            "keys": ["session", "visits"],
+            "other": 2,
+            "removed": true,
+            "missing": null
+        }))
+    );
+    assert_eq!(
+        store.get("default", "session").unwrap(),
+        Some(serde_json::json!({ "step": "fetch" }))
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_fetch_offloaded_result() {
+    let store = ResultStore::new(ResultsConfig {
+        offload_threshold_bytes: Some(64),
+        ..Default::default()
+    });
+    let summary = store.offload(serde_json::json!({
+        "items": (0..50).map(|i| serde_json::json!({ "id": i })).collect::<Vec<_>>()
+    }));
+    assert_eq!(summary["handle"], serde_json::json!("result_1"));
+
+    let code = r#"
+export default {
+  ids: pctx.results.fetch("result_1", "$.items[-2:].id"),
+  first: pctx.results.fetch("result_1", "items[0]"),
+};
+"#;
+    let result = execute(code, ExecuteOptions::new().with_results(store))
+        .await
+        .expect("execution should succeed");
+    assert!(
+        result.success,
+        "result should be fetched: {}",
+        result.stderr
+    );
+    assert_eq!(
+        result.output,
+        Some(serde_json::json!({ "ids": [48, 49], "first": { "id": 0 } }))
+    );
+
+    // without a store the results are unavailable
+    let result = execute(
+        r#"export default pctx.results.fetch("result_1");"#,
+        ExecuteOptions::new(),
+    )
+    .await
+    .expect("execution should succeed");
+    assert!(!result.success, "fetching without a store should fail");
+}
+
+#[serial]
+#[tokio::test]
+async fn test_roots() {
+    let code = r"
+export default pctx.roots.list();
+";
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "roots should list: {}", result.stderr);
+    assert_eq!(result.output, Some(serde_json::json!([])));
+
+    let root = serde_json::from_value(
+        serde_json::json!({"uri": "file:///workspace", "name": "workspace"}),
+    )
+    .unwrap();
+    let options = ExecuteOptions::new()
+        .with_client_handlers(ClientHandlers::default().with_roots(vec![root]));
+    let result = execute(code, options)
+        .await
+        .expect("execution should succeed");
+    assert_eq!(
+        result.output,
+        Some(serde_json::json!([{"uri": "file:///workspace", "name": "workspace"}]))
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_deterministic_execution() {
+    let code = r"
+export default {
+    random: [Math.random(), Math.random()],
+    now: Date.now(),
+    date: new Date().toISOString(),
+    parsed: new Date(0).toISOString(),
+};
+";
+    let run = |determinism| async move {
+        execute(
+            code,
+            ExecuteOptions::new()
+                .with_skip_type_check(true)
+                .with_determinism(determinism),
+        )
+        .await
+        .expect("execution should succeed")
+        .output
+        .expect("execution should have an output")
+    };
+
+    let first = run(Determinism::new(42, 1_700_000_000_000)).await;
+    assert_eq!(first, run(Determinism::new(42, 1_700_000_000_000)).await);
+    assert_eq!(first["now"], serde_json::json!(1_700_000_000_000_i64));
+    assert_eq!(first["date"], serde_json::json!("2023-11-14T22:13:20.000Z"));
+    assert_eq!(
+        first["parsed"],
+        serde_json::json!("1970-01-01T00:00:00.000Z")
+    );
+
+    let reseeded = run(Determinism::new(7, 1_700_000_000_000)).await;
+    assert_ne!(first["random"], reseeded["random"]);
+}
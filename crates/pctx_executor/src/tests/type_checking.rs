@@ -1,5 +1,9 @@
+use std::time::Duration;
+
 use super::serial;
 use crate::{ExecuteOptions, execute};
+use pctx_code_execution_runtime::ExecutionSignal;
+use pctx_config::type_check::TypeCheckConfig;
 
 #[serial]
 #[tokio::test]
@@ -302,3 +306,184 @@ async function run() {
         result.stderr
     );
 }
+
+#[serial]
+#[tokio::test]
+async fn test_check_without_execution() {
+    let valid = r#"const x: number = 1;
+console.log(x);"#;
+    let result = crate::check(valid, &TypeCheckConfig::default(), None)
+        .await
+        .expect("check should succeed");
+    assert!(
+        result.success,
+        "Valid code should pass: {:?}",
+        result.diagnostics
+    );
+    assert!(result.diagnostics.is_empty());
+
+    let invalid = r#"const x: number = "string";"#;
+    let result = crate::check(invalid, &TypeCheckConfig::default(), None)
+        .await
+        .expect("check should succeed");
+    assert!(!result.success, "Type mismatch should fail type checking");
+    assert_eq!(result.diagnostics[0].line, Some(1));
+}
+
+#[serial]
+#[tokio::test]
+async fn test_execute_warn_only_type_errors() {
+    let code = r#"const x: number = "string";
+export default x;"#;
+    let options = ExecuteOptions::new().with_type_check(TypeCheckConfig {
+        warn_only: true,
+        ..Default::default()
+    });
+
+    let result = execute(code, options)
+        .await
+        .expect("execution should succeed");
+    assert!(
+        result.success,
+        "warn_only should execute despite type errors"
+    );
+    assert_eq!(result.output, Some(serde_json::json!("string")));
+    assert!(!result.diagnostics.is_empty());
+    assert!(result.diagnostics.iter().all(|d| d.severity == "warning"));
+    assert!(
+        result.stderr.contains("Type check warnings"),
+        "stderr should contain the type check warnings, got: {}",
+        result.stderr
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_check_non_strict() {
+    let code = r"let name: string = undefined;";
+    let strict = crate::check(code, &TypeCheckConfig::default(), None)
+        .await
+        .expect("check should succeed");
+    assert!(!strict.success, "strict mode should reject undefined");
+
+    let loose = TypeCheckConfig {
+        strict: false,
+        ..Default::default()
+    };
+    let result = crate::check(code, &loose, None)
+        .await
+        .expect("check should succeed");
+    assert!(result.success, "{:?}", result.diagnostics);
+}
+
+#[serial]
+#[tokio::test]
+async fn test_execute_skip_type_check() {
+    let code = r#"const x: number = "string";
+export default x;"#;
+
+    let result = execute(code, ExecuteOptions::new().with_skip_type_check(true))
+        .await
+        .expect("execution should succeed");
+    assert!(result.success, "Type errors should not be checked");
+    assert!(!result.type_checked);
+    assert!(result.diagnostics.is_empty());
+    assert_eq!(result.output, Some(serde_json::json!("string")));
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(!result.success);
+    assert!(result.type_checked);
+}
+
+#[serial]
+#[tokio::test]
+async fn test_check_cache() {
+    let cache = crate::CheckCache::new();
+    let code = r#"const x: number = "string";"#;
+    let options = || ExecuteOptions::new().with_check_cache(cache.clone());
+
+    let first = execute(code, options())
+        .await
+        .expect("execution should succeed");
+    assert!(!first.success);
+    assert_eq!(cache.len(), 1);
+
+    let second = execute(code, options())
+        .await
+        .expect("execution should succeed");
+    assert_eq!(first.diagnostics, second.diagnostics);
+    assert_eq!(cache.len(), 1, "identical code should hit the cache");
+
+    let loose = TypeCheckConfig {
+        strict: false,
+        ..Default::default()
+    };
+    crate::check(code, &loose, Some(&cache))
+        .await
+        .expect("check should succeed");
+    assert_eq!(cache.len(), 2, "type check options are part of the key");
+
+    let valid = "export default 1 + 1;";
+    let result = execute(valid, options())
+        .await
+        .expect("execution should succeed");
+    assert!(result.success);
+    let result = execute(valid, options())
+        .await
+        .expect("execution should succeed");
+    assert_eq!(result.output, Some(serde_json::json!(2)));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[serial]
+#[tokio::test]
+async fn test_execute_type_error_suggestion() {
+    let code = r#"function createIssue(input: { title: string }) {
+  return input.title;
+}
+export default createIssue({ titel: "bug" });"#;
+
+    let result = execute(code, ExecuteOptions::new())
+        .await
+        .expect("execution should succeed");
+    assert!(!result.success);
+    assert!(
+        result.stderr.contains("Suggestion: did you mean 'title'?"),
+        "stderr should contain the suggested fix, got: {}",
+        result.stderr
+    );
+}
+
+#[serial]
+#[tokio::test]
+async fn test_timeout_includes_type_checking() {
+    // type checking alone takes longer than the timeout
+    let result = execute(
+        "export default 1;",
+        ExecuteOptions::new().with_timeout(Duration::from_millis(1)),
+    )
+    .await
+    .expect("execution should complete");
+    assert!(!result.success);
+    assert!(result.output.is_none());
+    assert!(
+        result.stderr.contains("deadline exceeded"),
+        "{}",
+        result.stderr
+    );
+
+    let signal = ExecutionSignal::new();
+    signal.cancel();
+    let result = execute(
+        "export default 1;",
+        ExecuteOptions::new().with_signal(signal),
+    )
+    .await
+    .expect("execution should complete");
+    assert!(!result.success);
+    assert!(result.stderr.contains("cancelled"), "{}", result.stderr);
+}
@@ -0,0 +1,42 @@
+use deno_core::v8::IsolateHandle;
+use pctx_code_execution_runtime::ExecutionSignal;
+use tokio_util::sync::CancellationToken;
+
+/// Terminates the V8 isolate of an execution once its signal is cancelled or its
+/// deadline passes, including code stuck in synchronous loops
+///
+/// Runs on its own thread, as the execution's thread is blocked while JavaScript runs.
+pub(crate) struct Watchdog {
+    done: CancellationToken,
+}
+
+impl Watchdog {
+    pub(crate) fn start(signal: ExecutionSignal, isolate: IsolateHandle) -> Self {
+        let done = CancellationToken::new();
+        let finished = done.clone();
+        std::thread::spawn(move || {
+            let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+            else {
+                return;
+            };
+            rt.block_on(async {
+                tokio::select! {
+                    () = signal.cancelled() => {
+                        isolate.terminate_execution();
+                    }
+                    () = finished.cancelled() => {}
+                }
+            });
+        });
+
+        Self { done }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.done.cancel();
+    }
+}
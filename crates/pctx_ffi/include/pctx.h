@@ -0,0 +1,157 @@
+#ifndef PCTX_H
+#define PCTX_H
+
+/* Generated with cbindgen from crates/pctx_ffi, do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Status of functions without a result
+ */
+typedef enum PctxStatus {
+  PCTX_STATUS_OK = 0,
+  PCTX_STATUS_ERROR = 1,
+} PctxStatus;
+
+/**
+ * Result of a callback call, set with [`pctx_call_result_set_output`] or
+ * [`pctx_call_result_set_error`]
+ */
+typedef struct PctxCallResult PctxCallResult;
+
+/**
+ * Tools & callbacks executed code can call
+ */
+typedef struct PctxCodeMode PctxCodeMode;
+
+/**
+ * Callback called with its `user_data`, the JSON arguments of the call and the result
+ * to set
+ */
+typedef void (*PctxCallback)(void *user_data, const char *args_json, struct PctxCallResult *result);
+
+/**
+ * Message of the last failure on the calling thread, `NULL` if none failed yet
+ *
+ * The string is owned by the library and valid until the next failure on this thread.
+ */
+const char *pctx_last_error(void);
+
+/**
+ * Frees a string returned by the library
+ *
+ * # Safety
+ *
+ * `s` must be null or a string returned by the library not freed yet
+ */
+void pctx_string_free(char *s);
+
+/**
+ * Creates a code mode without tools, freed with [`pctx_code_mode_free`], `NULL` on failure
+ */
+struct PctxCodeMode *pctx_code_mode_new(void);
+
+/**
+ * Frees a code mode
+ *
+ * # Safety
+ *
+ * `code_mode` must be null or a pointer returned by [`pctx_code_mode_new`] not freed yet
+ */
+void pctx_code_mode_free(struct PctxCodeMode *code_mode);
+
+/**
+ * Connects to the MCP server of the JSON `server_json` configuration and adds its tools
+ *
+ * # Safety
+ *
+ * `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+ * `server_json` a nul-terminated string
+ */
+enum PctxStatus pctx_code_mode_add_server(struct PctxCodeMode *code_mode, const char *server_json);
+
+/**
+ * Adds the callback of the JSON `callback_json` configuration, calling `callback` with
+ * `user_data` when executed code calls it
+ *
+ * # Safety
+ *
+ * `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+ * `callback_json` a nul-terminated string. `callback` & `user_data` must be usable from
+ * any thread until the code mode is freed
+ */
+enum PctxStatus pctx_code_mode_add_callback(struct PctxCodeMode *code_mode,
+                                            const char *callback_json,
+                                            PctxCallback callback,
+                                            void *user_data);
+
+/**
+ * Sets the JSON output of a callback call
+ *
+ * # Safety
+ *
+ * `result` must be the pointer the callback was called with and `output_json` a
+ * nul-terminated string
+ */
+void pctx_call_result_set_output(struct PctxCallResult *result, const char *output_json);
+
+/**
+ * Fails a callback call with the error `message`
+ *
+ * # Safety
+ *
+ * `result` must be the pointer the callback was called with and `message` a
+ * nul-terminated string
+ */
+void pctx_call_result_set_error(struct PctxCallResult *result, const char *message);
+
+/**
+ * Functions executed code can call, as the JSON of a `ListFunctionsOutput`
+ *
+ * # Safety
+ *
+ * `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet
+ */
+char *pctx_code_mode_list_functions(const struct PctxCodeMode *code_mode);
+
+/**
+ * Details of the functions of the JSON `GetFunctionDetailsInput`, as the JSON of a
+ * `GetFunctionDetailsOutput`
+ *
+ * # Safety
+ *
+ * `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+ * `input_json` a nul-terminated string
+ */
+char *pctx_code_mode_get_function_details(const struct PctxCodeMode *code_mode,
+                                          const char *input_json);
+
+/**
+ * Executes the code of the JSON `ExecuteInput` on the calling thread, returning the
+ * JSON of the `ExecuteOutput`
+ *
+ * # Safety
+ *
+ * `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+ * `input_json` a nul-terminated string
+ */
+char *pctx_code_mode_execute(const struct PctxCodeMode *code_mode, const char *input_json);
+
+/**
+ * Executes the code of the JSON `ExecuteInput` like [`pctx_code_mode_execute`], stopping
+ * the execution & cancelling its pending calls after `timeout_ms` milliseconds
+ *
+ * # Safety
+ *
+ * `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+ * `input_json` a nul-terminated string
+ */
+char *pctx_code_mode_execute_with_timeout(const struct PctxCodeMode *code_mode,
+                                          const char *input_json,
+                                          uint64_t timeout_ms);
+
+#endif  /* PCTX_H */
@@ -0,0 +1,593 @@
+//! # pctx FFI
+//!
+//! Stable C API over [`CodeMode`], so bindings for Go, C#, Java, Swift, ... wrap a single
+//! native library instead of each shipping their own addon. The header is generated with
+//! cbindgen into `include/pctx.h`.
+//!
+//! ## Conventions
+//!
+//! - Configurations & inputs are passed as JSON strings in the same format as the Rust
+//!   types they deserialize into ([`ServerConfig`], [`CallbackConfig`], [`ExecuteInput`], ...)
+//! - Functions without a result return a [`PctxStatus`], functions returning JSON return
+//!   `NULL` on failure. The message of the last failure on the calling thread is returned by
+//!   [`pctx_last_error`]
+//! - Panics never unwind into the caller, they fail the function like any other error
+//! - Strings returned by the library are owned by the caller and freed with
+//!   [`pctx_string_free`], strings passed to the library remain owned by the caller
+//! - Executions run on the calling thread, callbacks are called from other threads while
+//!   an execution is running. Functions taking a `const PctxCodeMode *` can be called
+//!   concurrently, the others must not run concurrently with any function of the code mode
+//!
+//! ## Callbacks
+//!
+//! Callbacks are [`PctxCallback`] function pointers receiving the `user_data` they were
+//! registered with and the JSON arguments of the call. They answer the call with
+//! [`pctx_call_result_set_output`] or [`pctx_call_result_set_error`], which copy the given
+//! strings so the callback keeps ownership of them.
+//!
+//! ```c
+//! void greet(void *user_data, const char *args_json, PctxCallResult *result) {
+//!     pctx_call_result_set_output(result, "\"Hello!\"");
+//! }
+//!
+//! PctxCodeMode *code_mode = pctx_code_mode_new();
+//! if (pctx_code_mode_add_callback(code_mode, "{\"namespace\": \"Greeter\", \"name\": \"greet\"}", greet, NULL) != PCTX_STATUS_OK) {
+//!     fprintf(stderr, "%s\n", pctx_last_error());
+//! }
+//! char *output = pctx_code_mode_execute(code_mode, "{\"code\": \"async function run() { return await Greeter.greet({}); }\"}");
+//! if (output == NULL) {
+//!     fprintf(stderr, "%s\n", pctx_last_error());
+//! }
+//! pctx_string_free(output);
+//! pctx_code_mode_free(code_mode);
+//! ```
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{CStr, CString, c_char, c_void},
+    future::Future,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::Arc,
+    time::Duration,
+};
+
+use pctx_code_mode::{
+    CallbackRegistry, CodeMode,
+    config::server::ServerConfig,
+    model::{CallbackConfig, ExecuteInput, GetFunctionDetailsInput},
+};
+use serde_json::Value;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Status of functions without a result
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PctxStatus {
+    Ok = 0,
+    Error = 1,
+}
+
+/// Tools & callbacks executed code can call
+pub struct PctxCodeMode {
+    code_mode: CodeMode,
+    registry: CallbackRegistry,
+}
+
+/// Result of a callback call, set with [`pctx_call_result_set_output`] or
+/// [`pctx_call_result_set_error`]
+#[derive(Default)]
+pub struct PctxCallResult {
+    result: Option<Result<String, String>>,
+}
+
+/// Callback called with its `user_data`, the JSON arguments of the call and the result
+/// to set
+pub type PctxCallback =
+    extern "C" fn(user_data: *mut c_void, args_json: *const c_char, result: *mut PctxCallResult);
+
+/// Callback with its `user_data`, the caller guarantees both can be used from any thread
+struct ForeignCallback {
+    callback: PctxCallback,
+    user_data: *mut c_void,
+}
+
+unsafe impl Send for ForeignCallback {}
+unsafe impl Sync for ForeignCallback {}
+
+impl ForeignCallback {
+    fn call(&self, args: Option<Value>) -> Result<Value, String> {
+        let args = CString::new(args.unwrap_or(Value::Null).to_string())
+            .map_err(|e| format!("Invalid arguments: {e}"))?;
+        let mut result = PctxCallResult::default();
+        (self.callback)(self.user_data, args.as_ptr(), &raw mut result);
+
+        match result.result {
+            Some(Ok(output)) => serde_json::from_str(&output)
+                .map_err(|e| format!("Callback returned invalid JSON: {e}")),
+            Some(Err(error)) => Err(error),
+            None => Err("Callback returned without setting a result".into()),
+        }
+    }
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, failing with the panic message if it panics instead of unwinding across
+/// the FFI boundary
+fn guarded<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| Err(panic_message(&*panic)))
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    let message = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+    format!("pctx panicked: {message}")
+}
+
+fn status(f: impl FnOnce() -> Result<(), String>) -> PctxStatus {
+    match guarded(f) {
+        Ok(()) => PctxStatus::Ok,
+        Err(e) => {
+            set_last_error(&e);
+            PctxStatus::Error
+        }
+    }
+}
+
+fn json_result(f: impl FnOnce() -> Result<String, String>) -> *mut c_char {
+    match guarded(f).and_then(|s| CString::new(s).map_err(|e| e.to_string())) {
+        Ok(s) => s.into_raw(),
+        Err(e) => {
+            set_last_error(&e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a valid nul-terminated string outliving `'a`
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{name} is null"));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|e| format!("{name} is not valid UTF-8: {e}"))
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a valid nul-terminated string
+unsafe fn json_arg<T: serde::de::DeserializeOwned>(
+    ptr: *const c_char,
+    name: &str,
+) -> Result<T, String> {
+    let json = unsafe { str_arg(ptr, name) }?;
+    serde_json::from_str(json).map_err(|e| format!("Invalid {name}: {e}"))
+}
+
+/// # Safety
+///
+/// `code_mode` must be null or a pointer returned by [`pctx_code_mode_new`] not freed yet
+unsafe fn code_mode_arg<'a>(code_mode: *mut PctxCodeMode) -> Result<&'a mut PctxCodeMode, String> {
+    unsafe { code_mode.as_mut() }.ok_or_else(|| "code_mode is null".into())
+}
+
+/// # Safety
+///
+/// `code_mode` must be null or a pointer returned by [`pctx_code_mode_new`] not freed yet
+unsafe fn code_mode_ref<'a>(code_mode: *const PctxCodeMode) -> Result<&'a PctxCodeMode, String> {
+    unsafe { code_mode.as_ref() }.ok_or_else(|| "code_mode is null".into())
+}
+
+/// Runs `f` to completion on a runtime of the calling thread, like the servers do for
+/// executions as Deno ops require a current-thread runtime
+fn block_on<F: Future>(f: F) -> Result<F::Output, String> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to create runtime: {e}"))?;
+    Ok(rt.block_on(f))
+}
+
+/// Message of the last failure on the calling thread, `NULL` if none failed yet
+///
+/// The string is owned by the library and valid until the next failure on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn pctx_last_error() -> *const c_char {
+    catch_unwind(|| {
+        LAST_ERROR.with(|e| e.borrow().as_ref().map_or(std::ptr::null(), |e| e.as_ptr()))
+    })
+    .unwrap_or(std::ptr::null())
+}
+
+/// Frees a string returned by the library
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by the library not freed yet
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_string_free(s: *mut c_char) {
+    status(|| {
+        if !s.is_null() {
+            drop(unsafe { CString::from_raw(s) });
+        }
+        Ok(())
+    });
+}
+
+/// Creates a code mode without tools, freed with [`pctx_code_mode_free`], `NULL` on failure
+#[unsafe(no_mangle)]
+pub extern "C" fn pctx_code_mode_new() -> *mut PctxCodeMode {
+    let code_mode = guarded(|| {
+        Ok(PctxCodeMode {
+            code_mode: CodeMode::default(),
+            registry: CallbackRegistry::default(),
+        })
+    });
+    match code_mode {
+        Ok(code_mode) => Box::into_raw(Box::new(code_mode)),
+        Err(e) => {
+            set_last_error(&e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a code mode
+///
+/// # Safety
+///
+/// `code_mode` must be null or a pointer returned by [`pctx_code_mode_new`] not freed yet
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_code_mode_free(code_mode: *mut PctxCodeMode) {
+    status(|| {
+        if !code_mode.is_null() {
+            drop(unsafe { Box::from_raw(code_mode) });
+        }
+        Ok(())
+    });
+}
+
+/// Connects to the MCP server of the JSON `server_json` configuration and adds its tools
+///
+/// # Safety
+///
+/// `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+/// `server_json` a nul-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_code_mode_add_server(
+    code_mode: *mut PctxCodeMode,
+    server_json: *const c_char,
+) -> PctxStatus {
+    status(|| unsafe { add_server(code_mode, server_json) })
+}
+
+unsafe fn add_server(
+    code_mode: *mut PctxCodeMode,
+    server_json: *const c_char,
+) -> Result<(), String> {
+    let code_mode = unsafe { code_mode_arg(code_mode) }?;
+    let server: ServerConfig = unsafe { json_arg(server_json, "server_json") }?;
+    block_on(code_mode.code_mode.add_server(&server))?.map_err(|e| e.to_string())
+}
+
+/// Adds the callback of the JSON `callback_json` configuration, calling `callback` with
+/// `user_data` when executed code calls it
+///
+/// # Safety
+///
+/// `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+/// `callback_json` a nul-terminated string. `callback` & `user_data` must be usable from
+/// any thread until the code mode is freed
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_code_mode_add_callback(
+    code_mode: *mut PctxCodeMode,
+    callback_json: *const c_char,
+    callback: PctxCallback,
+    user_data: *mut c_void,
+) -> PctxStatus {
+    status(|| unsafe { add_callback(code_mode, callback_json, callback, user_data) })
+}
+
+unsafe fn add_callback(
+    code_mode: *mut PctxCodeMode,
+    callback_json: *const c_char,
+    callback: PctxCallback,
+    user_data: *mut c_void,
+) -> Result<(), String> {
+    let code_mode = unsafe { code_mode_arg(code_mode) }?;
+    let config: CallbackConfig = unsafe { json_arg(callback_json, "callback_json") }?;
+    code_mode
+        .code_mode
+        .add_callback(&config)
+        .map_err(|e| e.to_string())?;
+
+    let foreign = Arc::new(ForeignCallback {
+        callback,
+        user_data,
+    });
+    code_mode
+        .registry
+        .add(
+            &config.id(),
+            Arc::new(move |args: Option<Value>| {
+                let foreign = foreign.clone();
+                Box::pin(async move {
+                    tokio::task::spawn_blocking(move || foreign.call(args))
+                        .await
+                        .map_err(|e| format!("Callback failed: {e}"))?
+                })
+            }),
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Sets the JSON output of a callback call
+///
+/// # Safety
+///
+/// `result` must be the pointer the callback was called with and `output_json` a
+/// nul-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_call_result_set_output(
+    result: *mut PctxCallResult,
+    output_json: *const c_char,
+) {
+    status(|| {
+        if let Some(result) = unsafe { result.as_mut() } {
+            result.result = Some(unsafe { str_arg(output_json, "output_json") }.map(Into::into));
+        }
+        Ok(())
+    });
+}
+
+/// Fails a callback call with the error `message`
+///
+/// # Safety
+///
+/// `result` must be the pointer the callback was called with and `message` a
+/// nul-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_call_result_set_error(
+    result: *mut PctxCallResult,
+    message: *const c_char,
+) {
+    status(|| {
+        if let Some(result) = unsafe { result.as_mut() } {
+            result.result = Some(Err(
+                unsafe { str_arg(message, "message") }.map_or_else(|e| e, Into::into)
+            ));
+        }
+        Ok(())
+    });
+}
+
+/// Functions executed code can call, as the JSON of a `ListFunctionsOutput`
+///
+/// # Safety
+///
+/// `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_code_mode_list_functions(
+    code_mode: *const PctxCodeMode,
+) -> *mut c_char {
+    json_result(|| unsafe { list_functions(code_mode) })
+}
+
+unsafe fn list_functions(code_mode: *const PctxCodeMode) -> Result<String, String> {
+    let code_mode = unsafe { code_mode_ref(code_mode) }?;
+    serde_json::to_string(&code_mode.code_mode.list_functions()).map_err(|e| e.to_string())
+}
+
+/// Details of the functions of the JSON `GetFunctionDetailsInput`, as the JSON of a
+/// `GetFunctionDetailsOutput`
+///
+/// # Safety
+///
+/// `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+/// `input_json` a nul-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_code_mode_get_function_details(
+    code_mode: *const PctxCodeMode,
+    input_json: *const c_char,
+) -> *mut c_char {
+    json_result(|| unsafe { get_function_details(code_mode, input_json) })
+}
+
+unsafe fn get_function_details(
+    code_mode: *const PctxCodeMode,
+    input_json: *const c_char,
+) -> Result<String, String> {
+    let code_mode = unsafe { code_mode_ref(code_mode) }?;
+    let input: GetFunctionDetailsInput = unsafe { json_arg(input_json, "input_json") }?;
+    serde_json::to_string(&code_mode.code_mode.get_function_details(input))
+        .map_err(|e| e.to_string())
+}
+
+/// Executes the code of the JSON `ExecuteInput` on the calling thread, returning the
+/// JSON of the `ExecuteOutput`
+///
+/// # Safety
+///
+/// `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+/// `input_json` a nul-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_code_mode_execute(
+    code_mode: *const PctxCodeMode,
+    input_json: *const c_char,
+) -> *mut c_char {
+    json_result(|| unsafe { execute(code_mode, input_json, None) })
+}
+
+/// Executes the code of the JSON `ExecuteInput` like [`pctx_code_mode_execute`], stopping
+/// the execution & cancelling its pending calls after `timeout_ms` milliseconds
+///
+/// # Safety
+///
+/// `code_mode` must be a pointer returned by [`pctx_code_mode_new`] not freed yet and
+/// `input_json` a nul-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pctx_code_mode_execute_with_timeout(
+    code_mode: *const PctxCodeMode,
+    input_json: *const c_char,
+    timeout_ms: u64,
+) -> *mut c_char {
+    json_result(|| unsafe {
+        execute(
+            code_mode,
+            input_json,
+            Some(Duration::from_millis(timeout_ms)),
+        )
+    })
+}
+
+unsafe fn execute(
+    code_mode: *const PctxCodeMode,
+    input_json: *const c_char,
+    timeout: Option<Duration>,
+) -> Result<String, String> {
+    let code_mode = unsafe { code_mode_ref(code_mode) }?;
+    let input: ExecuteInput = unsafe { json_arg(input_json, "input_json") }?;
+    let output = if let Some(timeout) = timeout {
+        let mut timed = code_mode.code_mode.clone();
+        timed.set_execution_timeout(Some(timeout));
+        block_on(timed.execute_input(&input, Some(code_mode.registry.clone())))?
+    } else {
+        block_on(
+            code_mode
+                .code_mode
+                .execute_input(&input, Some(code_mode.registry.clone())),
+        )?
+    }
+    .map_err(|e| e.to_string())?;
+    serde_json::to_string(&output).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    extern "C" fn add(
+        _user_data: *mut c_void,
+        args_json: *const c_char,
+        result: *mut PctxCallResult,
+    ) {
+        let args: Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(args_json) }.to_str().unwrap()).unwrap();
+        let sum =
+            CString::new((args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap()).to_string())
+                .unwrap();
+        unsafe { pctx_call_result_set_output(result, sum.as_ptr()) };
+    }
+
+    fn take_json(s: *mut c_char) -> Value {
+        assert!(!s.is_null());
+        let value = serde_json::from_str(unsafe { CStr::from_ptr(s) }.to_str().unwrap()).unwrap();
+        unsafe { pctx_string_free(s) };
+        value
+    }
+
+    #[test]
+    fn test_ffi_callbacks() {
+        let code_mode = pctx_code_mode_new();
+        let callback = CString::new(
+            json!({
+                "namespace": "MyMath",
+                "name": "add",
+                "description": "Adds two numbers",
+                "input_schema": {
+                    "type": "object",
+                    "properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+                    "required": ["a", "b"]
+                },
+                "output_schema": { "type": "number" }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let status = unsafe {
+            pctx_code_mode_add_callback(code_mode, callback.as_ptr(), add, std::ptr::null_mut())
+        };
+        assert_eq!(status, PctxStatus::Ok);
+
+        let listed = take_json(unsafe { pctx_code_mode_list_functions(code_mode) });
+        assert_eq!(listed["functions"][0]["name"], json!("add"));
+
+        let input = CString::new(
+            json!({ "code": "async function run() { return await MyMath.add({ a: 1, b: 2 }); }" })
+                .to_string(),
+        )
+        .unwrap();
+        let output = take_json(unsafe { pctx_code_mode_execute(code_mode, input.as_ptr()) });
+        assert_eq!(output["success"], json!(true));
+        assert_eq!(output["output"], json!(3));
+
+        // invalid inputs fail with the last error set
+        let invalid = CString::new("{").unwrap();
+        assert!(unsafe { pctx_code_mode_execute(code_mode, invalid.as_ptr()) }.is_null());
+        assert!(!pctx_last_error().is_null());
+
+        unsafe { pctx_code_mode_free(code_mode) };
+    }
+
+    fn last_error() -> String {
+        let error = pctx_last_error();
+        assert!(!error.is_null());
+        unsafe { CStr::from_ptr(error) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_ffi_invalid_arguments() {
+        let code_mode = pctx_code_mode_new();
+
+        assert!(unsafe { pctx_code_mode_list_functions(std::ptr::null()) }.is_null());
+        assert_eq!(last_error(), "code_mode is null");
+        assert!(unsafe { pctx_code_mode_execute(code_mode, std::ptr::null()) }.is_null());
+        assert_eq!(last_error(), "input_json is null");
+        let status = unsafe { pctx_code_mode_add_server(code_mode, std::ptr::null()) };
+        assert_eq!(status, PctxStatus::Error);
+        assert_eq!(last_error(), "server_json is null");
+
+        let invalid = CString::new(vec![b'{', 0xff, b'}']).unwrap();
+        let status = unsafe { pctx_code_mode_add_server(code_mode, invalid.as_ptr()) };
+        assert_eq!(status, PctxStatus::Error);
+        assert!(last_error().starts_with("server_json is not valid UTF-8"));
+        assert!(
+            unsafe { pctx_code_mode_get_function_details(code_mode, invalid.as_ptr()) }.is_null()
+        );
+        assert!(last_error().starts_with("input_json is not valid UTF-8"));
+
+        // freeing null pointers is a no-op
+        unsafe {
+            pctx_string_free(std::ptr::null_mut());
+            pctx_code_mode_free(std::ptr::null_mut());
+            pctx_code_mode_free(code_mode);
+        }
+    }
+
+    #[test]
+    fn test_ffi_panics_fail_with_last_error() {
+        assert_eq!(status(|| panic!("boom")), PctxStatus::Error);
+        assert_eq!(last_error(), "pctx panicked: boom");
+
+        assert!(json_result(|| panic!("invalid {}", "state")).is_null());
+        assert_eq!(last_error(), "pctx panicked: invalid state");
+    }
+}
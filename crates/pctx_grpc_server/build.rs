@@ -0,0 +1,9 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // use the vendored protoc so building doesn't require protobuf to be installed
+    if std::env::var_os("PROTOC").is_none() {
+        // SAFETY: build scripts are single threaded
+        unsafe { std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?) };
+    }
+    tonic_prost_build::configure().compile_protos(&["proto/pctx/v1/pctx.proto"], &["proto"])?;
+    Ok(())
+}
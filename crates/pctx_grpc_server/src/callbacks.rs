@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use pctx_code_mode::model::CallbackConfig;
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tonic::Status;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::proto::{CallbackCall, CallbackResult, callback_result::Outcome};
+
+pub(crate) type CallSender = mpsc::UnboundedSender<Result<CallbackCall, Status>>;
+
+type PendingCall = (Uuid, oneshot::Sender<Result<Value, String>>);
+
+/// `Callbacks` streams attached to sessions and the calls awaiting their results
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallbackStreams {
+    streams: Arc<Mutex<HashMap<Uuid, CallSender>>>,
+    pending: Arc<Mutex<HashMap<String, PendingCall>>>,
+}
+
+impl CallbackStreams {
+    /// Sends the calls of the session's callbacks to `sender`, replacing the stream
+    /// previously attached
+    pub(crate) fn attach(&self, session_id: Uuid, sender: CallSender) {
+        self.streams.lock().unwrap().insert(session_id, sender);
+    }
+
+    /// Detaches the stream of `sender` from the session, failing its pending calls
+    pub(crate) fn detach(&self, session_id: Uuid, sender: &CallSender) {
+        let mut streams = self.streams.lock().unwrap();
+        if streams
+            .get(&session_id)
+            .is_some_and(|s| s.same_channel(sender))
+        {
+            streams.remove(&session_id);
+            self.fail_pending(session_id, "Callback stream of the session was closed");
+        }
+    }
+
+    /// Detaches any stream from the session, e.g. when it is closed
+    pub(crate) fn remove(&self, session_id: Uuid) {
+        self.streams.lock().unwrap().remove(&session_id);
+        self.fail_pending(session_id, "Session was closed");
+    }
+
+    fn fail_pending(&self, session_id: Uuid, reason: &str) {
+        let mut pending = self.pending.lock().unwrap();
+        let ids: Vec<String> = pending
+            .iter()
+            .filter(|(_, (s, _))| *s == session_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ids {
+            if let Some((_, tx)) = pending.remove(&id) {
+                let _ = tx.send(Err(reason.to_string()));
+            }
+        }
+    }
+
+    /// Answers a pending call with the result the client sent
+    pub(crate) fn resolve(&self, result: CallbackResult) {
+        let Some((_, tx)) = self.pending.lock().unwrap().remove(&result.call_id) else {
+            debug!(call_id = %result.call_id, "Ignoring result of unknown callback call");
+            return;
+        };
+        let res = match result.outcome {
+            Some(Outcome::OutputJson(json)) => serde_json::from_str(&json)
+                .map_err(|e| format!("Callback returned invalid JSON: {e}")),
+            Some(Outcome::Error(error)) => Err(error),
+            None => Ok(Value::Null),
+        };
+        if tx.send(res).is_err() {
+            warn!(call_id = %result.call_id, "Callback call ended before its result arrived");
+        }
+    }
+
+    /// Calls the callback on the stream attached to the session, waiting at most
+    /// `timeout` for its result
+    pub(crate) async fn call(
+        &self,
+        session_id: Uuid,
+        callback: &CallbackConfig,
+        args: Option<Value>,
+        timeout: Duration,
+    ) -> Result<Value, String> {
+        let stream = self
+            .streams
+            .lock()
+            .unwrap()
+            .get(&session_id)
+            .cloned()
+            .ok_or_else(|| {
+                format!(
+                    "No callback stream is attached to session {session_id}, cannot call {}",
+                    callback.id()
+                )
+            })?;
+
+        let call_id = Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(call_id.clone(), (session_id, tx));
+
+        let call = CallbackCall {
+            call_id: call_id.clone(),
+            namespace: callback.namespace.clone(),
+            name: callback.name.clone(),
+            args_json: args.map(|a| a.to_string()),
+        };
+        if stream.send(Ok(call)).is_err() {
+            self.pending.lock().unwrap().remove(&call_id);
+            return Err("Callback stream of the session was closed".into());
+        }
+
+        let res = tokio::time::timeout(timeout, rx).await;
+        self.pending.lock().unwrap().remove(&call_id);
+        match res {
+            Ok(Ok(res)) => res,
+            Ok(Err(_)) => Err("Callback stream closed before answering".into()),
+            Err(_) => Err(format!(
+                "{} timed out after {}s",
+                callback.id(),
+                timeout.as_secs_f64()
+            )),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! # pctx gRPC server
+//!
+//! Code mode sessions over gRPC, for internal services preferring gRPC over the MCP &
+//! WebSocket protocols. The service is defined in `proto/pctx/v1/pctx.proto`:
+//!
+//! - `CreateSession` / `CloseSession` manage code mode sessions
+//! - `RegisterTools` registers tools the client answers on its `Callbacks` stream
+//! - `ListFunctions` / `GetFunctionDetails` return the functions of a session & their
+//!   TypeScript declarations
+//! - `Execute` runs code, streaming the progress of tool calls followed by the console
+//!   output & result of the execution
+//!
+//! Sessions, authentication, tenants & execution limits are shared with the session
+//! server through its [`AppState`], tokens are sent in the `authorization` metadata.
+
+mod callbacks;
+mod service;
+
+/// Types & client/server stubs generated from `pctx.proto`
+#[allow(clippy::pedantic, unreachable_pub)]
+pub mod proto {
+    tonic::include_proto!("pctx.v1");
+}
+
+use anyhow::Result;
+use pctx_session_server::{AppState, PctxSessionBackend};
+use tokio_stream::wrappers::TcpListenerStream;
+use tracing::info;
+
+pub use service::CodeModeService;
+
+/// gRPC service of code mode sessions
+pub fn service<B: PctxSessionBackend>(
+    state: AppState<B>,
+) -> proto::code_mode_server::CodeModeServer<CodeModeService<B>> {
+    proto::code_mode_server::CodeModeServer::new(CodeModeService::new(state))
+}
+
+/// Start the gRPC server
+///
+/// # Errors
+///
+/// This function will return an error if the server fails binding to the provided host/port
+pub async fn start_grpc_server<B: PctxSessionBackend>(
+    host: &str,
+    port: u16,
+    state: AppState<B>,
+) -> Result<()> {
+    let addr = format!("{host}:{port}");
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+    info!("pctx gRPC server listening on {addr}");
+
+    tonic::transport::Server::builder()
+        .add_service(service(state))
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Shutdown signal received, cleaning up...");
+        })
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,472 @@
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures::Stream;
+use pctx_code_mode::{
+    CallbackRegistry, CodeMode, ExecutionIds,
+    config::client::ProgressHandler,
+    execute_blocking,
+    model::{CallbackConfig, ExecuteInput, ExecuteOutput, GetFunctionDetailsInput, ListedFunction},
+};
+use pctx_session_server::{Admission, AppState, CreateSessionError, PctxSessionBackend, Tenant};
+use rmcp::model::ProgressNotificationParam;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    callbacks::CallbackStreams,
+    proto::{
+        self, CallbackCall, CallbackClientMessage, CloseSessionRequest, CloseSessionResponse,
+        ConsoleOutput, CreateSessionRequest, CreateSessionResponse, ExecuteEvent, ExecuteRequest,
+        ExecuteResult, GetFunctionDetailsRequest, GetFunctionDetailsResponse, ListFunctionsRequest,
+        ListFunctionsResponse, RegisterToolsRequest, RegisterToolsResponse, ToolProgress,
+        callback_client_message::Message, code_mode_server, execute_event::Event,
+    },
+};
+
+type EventSender = mpsc::UnboundedSender<Result<ExecuteEvent, Status>>;
+
+/// Implementation of the `pctx.v1.CodeMode` service over the sessions of an [`AppState`]
+#[derive(Clone)]
+pub struct CodeModeService<B: PctxSessionBackend> {
+    state: AppState<B>,
+    callbacks: CallbackStreams,
+}
+
+impl<B: PctxSessionBackend> CodeModeService<B> {
+    pub fn new(state: AppState<B>) -> Self {
+        Self {
+            state,
+            callbacks: CallbackStreams::default(),
+        }
+    }
+
+    fn tenant<T>(&self, request: &Request<T>) -> Result<Tenant, Status> {
+        self.state
+            .auth
+            .authenticate(&request.metadata().clone().into_headers())
+            .map_err(|e| {
+                warn!("Rejecting unauthenticated gRPC request: {e}");
+                Status::unauthenticated(e.to_string())
+            })
+    }
+
+    /// Parses the session id, hiding the sessions of other tenants as if they did not exist
+    async fn session(&self, tenant: &Tenant, session_id: &str) -> Result<Uuid, Status> {
+        let session_id = Uuid::parse_str(session_id)
+            .map_err(|_| Status::invalid_argument(format!("Invalid session id {session_id}")))?;
+        if !self
+            .state
+            .can_access(session_id, tenant)
+            .await
+            .map_err(internal)?
+        {
+            return Err(session_not_found(session_id));
+        }
+        Ok(session_id)
+    }
+
+    async fn code_mode(&self, session_id: Uuid) -> Result<CodeMode, Status> {
+        self.state
+            .backend
+            .get(session_id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| session_not_found(session_id))
+    }
+
+    fn callback_registry(
+        &self,
+        session_id: Uuid,
+        code_mode: &CodeMode,
+    ) -> Result<CallbackRegistry, Status> {
+        let registry = CallbackRegistry::default();
+        let timeout = self.state.limits.callback_timeout;
+        for config in code_mode.callbacks() {
+            let callbacks = self.callbacks.clone();
+            let cfg = config.clone();
+            registry
+                .add(
+                    &config.id(),
+                    Arc::new(move |args: Option<serde_json::Value>| {
+                        let callbacks = callbacks.clone();
+                        let cfg = cfg.clone();
+                        Box::pin(
+                            async move { callbacks.call(session_id, &cfg, args, timeout).await },
+                        )
+                    }),
+                )
+                .map_err(internal)?;
+        }
+        Ok(registry)
+    }
+}
+
+#[async_trait]
+impl<B: PctxSessionBackend> code_mode_server::CodeMode for CodeModeService<B> {
+    type CallbacksStream = Pin<Box<dyn Stream<Item = Result<CallbackCall, Status>> + Send>>;
+    type ExecuteStream = Pin<Box<dyn Stream<Item = Result<ExecuteEvent, Status>> + Send>>;
+
+    async fn create_session(
+        &self,
+        request: Request<CreateSessionRequest>,
+    ) -> Result<Response<CreateSessionResponse>, Status> {
+        let tenant = self.tenant(&request)?;
+        info!(tenant =? tenant, "Creating new CodeMode session");
+
+        let session_id = self
+            .state
+            .create_session(&tenant)
+            .await
+            .map_err(|e| match e {
+                CreateSessionError::Tenant(e) => Status::resource_exhausted(e.to_string()),
+                CreateSessionError::Backend(e) => internal(e),
+            })?;
+
+        Ok(Response::new(CreateSessionResponse {
+            session_id: session_id.to_string(),
+        }))
+    }
+
+    async fn close_session(
+        &self,
+        request: Request<CloseSessionRequest>,
+    ) -> Result<Response<CloseSessionResponse>, Status> {
+        let tenant = self.tenant(&request)?;
+        let session_id = self.session(&tenant, &request.get_ref().session_id).await?;
+        info!(session_id =? session_id, "Closing CodeMode session");
+
+        let closed = self
+            .state
+            .backend
+            .delete(session_id)
+            .await
+            .map_err(internal)?;
+        self.state.session_locks.remove(session_id);
+        self.callbacks.remove(session_id);
+        if !closed {
+            return Err(session_not_found(session_id));
+        }
+
+        Ok(Response::new(CloseSessionResponse { closed }))
+    }
+
+    async fn register_tools(
+        &self,
+        request: Request<RegisterToolsRequest>,
+    ) -> Result<Response<RegisterToolsResponse>, Status> {
+        let tenant = self.tenant(&request)?;
+        let request = request.into_inner();
+        let session_id = self.session(&tenant, &request.session_id).await?;
+
+        let tools = request
+            .tools
+            .into_iter()
+            .map(|tool| {
+                let schema = |json: Option<String>, kind: &str| {
+                    json.map(|j| serde_json::from_str(&j))
+                        .transpose()
+                        .map_err(|e| {
+                            Status::invalid_argument(format!(
+                                "Invalid {kind} schema of {}.{}: {e}",
+                                tool.namespace, tool.name
+                            ))
+                        })
+                };
+                Ok(CallbackConfig {
+                    input_schema: schema(tool.input_schema_json.clone(), "input")?,
+                    output_schema: schema(tool.output_schema_json.clone(), "output")?,
+                    name: tool.name,
+                    namespace: tool.namespace,
+                    description: tool.description,
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+        info!(
+            session_id =? session_id,
+            tools =? tools.iter().map(CallbackConfig::id).collect::<Vec<_>>(),
+            "Registering tools...",
+        );
+
+        let _lock = self.state.session_locks.lock(session_id).await;
+        let mut code_mode = self.code_mode(session_id).await?;
+        if let Some(max) = self.state.limits.max_tools
+            && code_mode.callbacks().len() + tools.len() > max
+        {
+            return Err(Status::resource_exhausted(format!(
+                "Registering {} tools exceeds the limit of {max} tools per session",
+                tools.len()
+            )));
+        }
+        code_mode
+            .add_callbacks(&tools)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        self.state
+            .backend
+            .update(session_id, code_mode)
+            .await
+            .map_err(internal)?;
+
+        Ok(Response::new(RegisterToolsResponse {
+            registered: tools.len() as u32,
+        }))
+    }
+
+    async fn callbacks(
+        &self,
+        request: Request<Streaming<CallbackClientMessage>>,
+    ) -> Result<Response<Self::CallbacksStream>, Status> {
+        let tenant = self.tenant(&request)?;
+        let mut inbound = request.into_inner();
+        let Some(Message::AttachSessionId(session_id)) =
+            inbound.message().await?.and_then(|m| m.message)
+        else {
+            return Err(Status::invalid_argument(
+                "The first message must attach the stream to a session",
+            ));
+        };
+        let session_id = self.session(&tenant, &session_id).await?;
+        info!(session_id =? session_id, "Attaching callback stream");
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.callbacks.attach(session_id, tx.clone());
+        let callbacks = self.callbacks.clone();
+        tokio::spawn(async move {
+            loop {
+                match inbound.message().await {
+                    Ok(Some(CallbackClientMessage {
+                        message: Some(Message::Result(result)),
+                    })) => callbacks.resolve(result),
+                    Ok(Some(_)) => warn!(session_id =? session_id, "Ignoring callback message"),
+                    Ok(None) => break,
+                    Err(e) => {
+                        debug!(session_id =? session_id, "Callback stream failed: {e}");
+                        break;
+                    }
+                }
+            }
+            info!(session_id =? session_id, "Callback stream closed");
+            callbacks.detach(session_id, &tx);
+        });
+
+        Ok(Response::new(Box::pin(UnboundedReceiverStream::new(rx))))
+    }
+
+    async fn list_functions(
+        &self,
+        request: Request<ListFunctionsRequest>,
+    ) -> Result<Response<ListFunctionsResponse>, Status> {
+        let tenant = self.tenant(&request)?;
+        let session_id = self.session(&tenant, &request.get_ref().session_id).await?;
+        let listed = self.code_mode(session_id).await?.list_functions();
+
+        Ok(Response::new(ListFunctionsResponse {
+            functions: listed.functions.into_iter().map(function).collect(),
+            code: listed.code,
+        }))
+    }
+
+    async fn get_function_details(
+        &self,
+        request: Request<GetFunctionDetailsRequest>,
+    ) -> Result<Response<GetFunctionDetailsResponse>, Status> {
+        let tenant = self.tenant(&request)?;
+        let request = request.into_inner();
+        let session_id = self.session(&tenant, &request.session_id).await?;
+        let input: GetFunctionDetailsInput =
+            serde_json::from_value(json!({ "functions": request.functions }))
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let details = self
+            .code_mode(session_id)
+            .await?
+            .get_function_details(input);
+
+        Ok(Response::new(GetFunctionDetailsResponse {
+            functions: details
+                .functions
+                .into_iter()
+                .map(|d| proto::FunctionDetails {
+                    function: Some(function(d.listed)),
+                    input_type: d.input_type,
+                    output_type: d.output_type,
+                    types: d.types,
+                })
+                .collect(),
+            code: details.code,
+        }))
+    }
+
+    async fn execute(
+        &self,
+        request: Request<ExecuteRequest>,
+    ) -> Result<Response<Self::ExecuteStream>, Status> {
+        let tenant = self.tenant(&request)?;
+        let request = request.into_inner();
+        let session_id = self.session(&tenant, &request.session_id).await?;
+        let input = ExecuteInput {
+            code: request.code,
+            skip_type_check: request.skip_type_check,
+            input: request
+                .input_json
+                .map(|j| serde_json::from_str(&j))
+                .transpose()
+                .map_err(|e| Status::invalid_argument(format!("Invalid input: {e}")))?,
+            ..Default::default()
+        };
+        let timeout = request
+            .timeout_secs
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+
+        let code_mode = self.code_mode(session_id).await?;
+        self.state
+            .tenants
+            .record_execution(&tenant)
+            .map_err(|e| Status::resource_exhausted(e.to_string()))?;
+        let admission = self
+            .state
+            .executions
+            .admit()
+            .map_err(|e| Status::resource_exhausted(e.to_string()))?;
+        let registry = self.callback_registry(session_id, &code_mode)?;
+
+        let execution_id = Uuid::new_v4();
+        info!(session_id =? session_id, execution_id =? execution_id, "Executing code");
+        let (tx, rx) = mpsc::unbounded_channel();
+        let state = self.state.clone();
+
+        tokio::spawn(async move {
+            let _permit = match admission {
+                Admission::Ready(permit) => permit,
+                Admission::Queued(queued) => queued.acquire().await,
+            };
+            let mut exec_code_mode = code_mode.clone();
+            exec_code_mode
+                .set_progress_handler(Some(Arc::new(GrpcProgress { sender: tx.clone() })));
+            exec_code_mode.set_execution_timeout(timeout);
+            exec_code_mode.set_execution_ids(ExecutionIds {
+                execution_id: Some(execution_id.to_string()),
+                session_id: Some(session_id.to_string()),
+            });
+
+            let res = execute_blocking(exec_code_mode, input.clone(), Some(registry))
+                .await
+                .map_err(|e| anyhow!("{e}"));
+
+            match &res {
+                Ok(output) => send_output(&tx, output),
+                Err(e) => {
+                    let _ = tx.send(Err(Status::internal(format!("Execution failed: {e}"))));
+                }
+            }
+
+            if let Ok(output) = &res
+                && !output.registered_tools.is_empty()
+                && let Err(e) =
+                    add_registered_tools(&state, session_id, &output.registered_tools).await
+            {
+                error!("Failed adding registered tools to session: {e}");
+            }
+            if let Err(e) = state
+                .backend
+                .post_execution(session_id, execution_id, code_mode, input, res)
+                .await
+            {
+                error!("Failed to post_execution hook: {e}");
+            }
+        });
+
+        Ok(Response::new(Box::pin(UnboundedReceiverStream::new(rx))))
+    }
+}
+
+fn send_output(tx: &EventSender, output: &ExecuteOutput) {
+    let console = Event::Console(ConsoleOutput {
+        stdout: output.stdout.clone(),
+        stderr: output.stderr.clone(),
+    });
+    let result = Event::Result(ExecuteResult {
+        success: output.success,
+        output_json: output.output.as_ref().map(ToString::to_string),
+        unchecked: output.unchecked,
+        diagnostics: output
+            .diagnostics
+            .iter()
+            .map(|d| proto::Diagnostic {
+                message: d.message.clone(),
+                line: d.line.map(|l| l as u32),
+                column: d.column.map(|c| c as u32),
+                code: d.code,
+                suggestion: d.suggestion.clone(),
+            })
+            .collect(),
+    });
+    for event in [console, result] {
+        if tx.send(Ok(ExecuteEvent { event: Some(event) })).is_err() {
+            debug!("Execute stream closed before the execution completed");
+            return;
+        }
+    }
+}
+
+/// Adds the tools registered by an execution to its session, their calls in subsequent
+/// executions are sent to the callback stream like any registered tool
+async fn add_registered_tools<B: PctxSessionBackend>(
+    state: &AppState<B>,
+    session_id: Uuid,
+    tools: &[CallbackConfig],
+) -> anyhow::Result<()> {
+    let _lock = state.session_locks.lock(session_id).await;
+    let Some(mut code_mode) = state.backend.get(session_id).await? else {
+        return Ok(());
+    };
+    let existing: Vec<String> = code_mode
+        .callbacks()
+        .iter()
+        .map(CallbackConfig::id)
+        .collect();
+    code_mode.add_callbacks(tools.iter().filter(|t| !existing.contains(&t.id())))?;
+    state.backend.update(session_id, code_mode).await
+}
+
+fn function(listed: ListedFunction) -> proto::Function {
+    proto::Function {
+        namespace: listed.namespace,
+        name: listed.name,
+        description: listed.description,
+    }
+}
+
+fn session_not_found(session_id: Uuid) -> Status {
+    Status::not_found(format!("Code mode session {session_id} does not exist"))
+}
+
+fn internal(e: impl std::fmt::Display) -> Status {
+    error!("Server Error: {e}");
+    Status::internal("Internal error")
+}
+
+/// Sends the progress of upstream tool calls on the execute stream
+#[derive(Debug)]
+struct GrpcProgress {
+    sender: EventSender,
+}
+
+#[async_trait]
+impl ProgressHandler for GrpcProgress {
+    async fn on_progress(&self, server: &str, params: ProgressNotificationParam) {
+        let event = Event::Progress(ToolProgress {
+            server: server.to_string(),
+            progress: params.progress,
+            total: params.total,
+            message: params.message,
+        });
+        if let Err(e) = self.sender.send(Ok(ExecuteEvent { event: Some(event) })) {
+            debug!("Failed to send progress event: {e}");
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use pctx_grpc_server::proto::{
+    CallbackClientMessage, CallbackResult, CreateSessionRequest, ExecuteRequest,
+    ListFunctionsRequest, RegisterToolsRequest, Tool, callback_client_message::Message,
+    callback_result::Outcome, code_mode_client::CodeModeClient, execute_event::Event,
+};
+use pctx_session_server::AppState;
+use serde_json::{Value, json};
+use serial_test::serial;
+use tokio::sync::mpsc;
+use tokio_stream::{
+    StreamExt,
+    wrappers::{TcpListenerStream, UnboundedReceiverStream},
+};
+use tonic::transport::Channel;
+
+async fn connect() -> CodeModeClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(pctx_grpc_server::service(AppState::new_local()))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    CodeModeClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn test_execute_with_callbacks() {
+    let mut client = connect().await;
+    let session_id = client
+        .create_session(CreateSessionRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .session_id;
+
+    let registered = client
+        .register_tools(RegisterToolsRequest {
+            session_id: session_id.clone(),
+            tools: vec![Tool {
+                namespace: "MyMath".into(),
+                name: "add".into(),
+                description: Some("Adds two numbers".into()),
+                input_schema_json: Some(
+                    json!({
+                        "type": "object",
+                        "properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+                        "required": ["a", "b"]
+                    })
+                    .to_string(),
+                ),
+                output_schema_json: Some(json!({ "type": "number" }).to_string()),
+            }],
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(registered.registered, 1);
+
+    let listed = client
+        .list_functions(ListFunctionsRequest {
+            session_id: session_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(listed.functions[0].name, "add");
+
+    // answer the calls of the tool on the callback stream
+    let (tx, rx) = mpsc::unbounded_channel();
+    tx.send(CallbackClientMessage {
+        message: Some(Message::AttachSessionId(session_id.clone())),
+    })
+    .unwrap();
+    let mut calls = client
+        .callbacks(UnboundedReceiverStream::new(rx))
+        .await
+        .unwrap()
+        .into_inner();
+    tokio::spawn(async move {
+        while let Some(Ok(call)) = calls.next().await {
+            let args: Value = serde_json::from_str(&call.args_json.unwrap()).unwrap();
+            let sum = args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap();
+            tx.send(CallbackClientMessage {
+                message: Some(Message::Result(CallbackResult {
+                    call_id: call.call_id,
+                    outcome: Some(Outcome::OutputJson(sum.to_string())),
+                })),
+            })
+            .unwrap();
+        }
+    });
+
+    let mut events = client
+        .execute(ExecuteRequest {
+            session_id,
+            code: r#"async function run() {
+                console.log("adding");
+                return await MyMath.add({ a: 1, b: 2 });
+            }"#
+            .into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut console = None;
+    let mut result = None;
+    while let Some(event) = events.next().await {
+        match event.unwrap().event {
+            Some(Event::Console(c)) => console = Some(c),
+            Some(Event::Result(r)) => result = Some(r),
+            _ => {}
+        }
+    }
+    assert_eq!(console.unwrap().stdout.trim(), "adding");
+    let result = result.unwrap();
+    assert!(result.success);
+    assert_eq!(result.output_json.as_deref(), Some("3"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn test_unknown_session() {
+    let mut client = connect().await;
+    let err = client
+        .list_functions(ListFunctionsRequest {
+            session_id: uuid::Uuid::new_v4().to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use pctx_config::client::ElicitationHandler;
+use rmcp::{
+    RoleServer,
+    model::{CreateElicitationRequestParams, CreateElicitationResult},
+    service::Peer,
+};
+use tracing::info;
+
+/// Forwards elicitation requests of upstream servers to the client of the pctx MCP server
+pub(crate) struct PeerElicitation {
+    peer: Peer<RoleServer>,
+}
+
+impl PeerElicitation {
+    /// Forwarding handler if the client supports elicitation
+    pub(crate) fn for_peer(peer: Peer<RoleServer>) -> Option<Self> {
+        let supported = peer
+            .peer_info()
+            .is_some_and(|info| info.capabilities.elicitation.is_some());
+
+        supported.then_some(Self { peer })
+    }
+}
+
+impl std::fmt::Debug for PeerElicitation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PeerElicitation").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ElicitationHandler for PeerElicitation {
+    async fn create_elicitation(
+        &self,
+        server: &str,
+        mut params: CreateElicitationRequestParams,
+    ) -> anyhow::Result<CreateElicitationResult> {
+        info!(server = %server, "Forwarding elicitation request of upstream MCP server");
+
+        // the client can't tell which upstream server is asking otherwise
+        params.message = format!("[{server}] {}", params.message);
+        Ok(self.peer.create_elicitation(params).await?)
+    }
+}
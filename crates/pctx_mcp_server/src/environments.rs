@@ -0,0 +1,200 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use axum::http::HeaderMap;
+use pctx_code_mode::{CodeMode, config::server::ServerConfig, model::ExecuteInput};
+use tracing::{debug, info};
+
+/// Header selecting the profile of an execution, if `ExecuteInput::profile` is unset
+pub(crate) const PROFILE_HEADER: &str = "x-pctx-profile";
+
+/// Number of environments with ephemeral servers kept connected for later executions
+const EPHEMERAL_CACHE_SIZE: usize = 32;
+
+/// Code modes executions can run in: all configured servers, a profile, or either extended
+/// with ephemeral servers passed in the request
+#[derive(Clone)]
+pub(crate) struct Environments {
+    default: CodeMode,
+    profiles: Arc<HashMap<String, CodeMode>>,
+    ephemeral: Option<Arc<Mutex<EphemeralCache>>>,
+    connect_timeout_secs: u64,
+}
+
+#[derive(Default)]
+struct EphemeralCache {
+    order: VecDeque<String>,
+    code_modes: HashMap<String, CodeMode>,
+}
+
+impl Environments {
+    /// Environments of a single code mode, without profiles or ephemeral servers
+    pub(crate) fn new(default: CodeMode) -> Self {
+        Self {
+            default,
+            profiles: Arc::default(),
+            ephemeral: None,
+            connect_timeout_secs: 0,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn with_profiles(mut self, profiles: HashMap<String, CodeMode>) -> Self {
+        self.profiles = Arc::new(profiles);
+        self
+    }
+
+    /// Allows requests to pass servers connected for their executions
+    #[must_use]
+    pub(crate) fn with_ephemeral_servers(mut self, connect_timeout_secs: u64) -> Self {
+        self.ephemeral = Some(Arc::default());
+        self.connect_timeout_secs = connect_timeout_secs;
+        self
+    }
+
+    pub(crate) fn default_code_mode(&self) -> &CodeMode {
+        &self.default
+    }
+
+    /// Code mode the input is executed in, selecting the profile of the input or of the
+    /// [`PROFILE_HEADER`] and connecting the ephemeral servers of the input
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile does not exist, ephemeral servers are not allowed or
+    /// connecting to them fails
+    pub(crate) async fn resolve(
+        &self,
+        input: &ExecuteInput,
+        headers: Option<&HeaderMap>,
+    ) -> Result<CodeMode, String> {
+        let profile = input.profile.as_deref().or_else(|| {
+            headers
+                .and_then(|h| h.get(PROFILE_HEADER))
+                .and_then(|v| v.to_str().ok())
+        });
+        let base = match profile {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .ok_or_else(|| format!("Profile '{name}' not found"))?,
+            None => &self.default,
+        };
+
+        if input.servers.is_empty() {
+            return Ok(base.clone());
+        }
+        let Some(cache) = &self.ephemeral else {
+            return Err("This server does not allow ephemeral servers".into());
+        };
+
+        let key = format!(
+            "{}:{}",
+            profile.unwrap_or_default(),
+            serde_json::to_string(&input.servers).map_err(|e| e.to_string())?
+        );
+        if let Some(code_mode) = cache.lock().unwrap().code_modes.get(&key) {
+            debug!("Reusing environment with ephemeral servers");
+            return Ok(code_mode.clone());
+        }
+
+        info!(
+            "Connecting {} ephemeral MCP servers for execution",
+            input.servers.len()
+        );
+        let mut code_mode = base.clone();
+        code_mode
+            .add_servers(&input.servers, self.connect_timeout_secs)
+            .await
+            .map_err(|e| format!("Failed connecting ephemeral servers: {e}"))?;
+
+        let mut cache = cache.lock().unwrap();
+        if cache.order.len() >= EPHEMERAL_CACHE_SIZE
+            && let Some(oldest) = cache.order.pop_front()
+        {
+            cache.code_modes.remove(&oldest);
+        }
+        cache.order.push_back(key.clone());
+        cache.code_modes.insert(key, code_mode.clone());
+
+        Ok(code_mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue};
+    use pctx_code_mode::{CodeMode, model::ExecuteInput};
+    use serde_json::json;
+
+    use super::{Environments, PROFILE_HEADER};
+
+    fn code_mode(namespace: &str) -> CodeMode {
+        CodeMode::default()
+            .with_callback(
+                &serde_json::from_value(json!({
+                    "namespace": namespace,
+                    "name": "ping",
+                    "input_schema": { "type": "object", "properties": {} }
+                }))
+                .unwrap(),
+            )
+            .unwrap()
+    }
+
+    fn namespaces(code_mode: &CodeMode) -> Vec<String> {
+        code_mode
+            .tool_sets()
+            .iter()
+            .map(|s| s.namespace.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_profile() {
+        let envs = Environments::new(code_mode("All"))
+            .with_profiles([("support".to_string(), code_mode("Support"))].into());
+
+        let default = envs.resolve(&ExecuteInput::default(), None).await.unwrap();
+        assert_eq!(namespaces(&default), ["All"]);
+
+        let input = ExecuteInput {
+            profile: Some("support".into()),
+            ..Default::default()
+        };
+        let support = envs.resolve(&input, None).await.unwrap();
+        assert_eq!(namespaces(&support), ["Support"]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(PROFILE_HEADER, HeaderValue::from_static("support"));
+        let support = envs
+            .resolve(&ExecuteInput::default(), Some(&headers))
+            .await
+            .unwrap();
+        assert_eq!(namespaces(&support), ["Support"]);
+
+        headers.insert(PROFILE_HEADER, HeaderValue::from_static("missing"));
+        assert!(
+            envs.resolve(&ExecuteInput::default(), Some(&headers))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ephemeral_servers_require_opt_in() {
+        let input: ExecuteInput = serde_json::from_value(json!({
+            "code": "async function run() {}",
+            "servers": [{ "name": "extra", "url": "http://127.0.0.1:1/mcp" }]
+        }))
+        .unwrap();
+
+        let err = Environments::new(code_mode("All"))
+            .resolve(&input, None)
+            .await
+            .unwrap_err();
+        assert!(err.contains("ephemeral"), "{err}");
+    }
+}
@@ -1,6 +1,14 @@
+mod elicitation;
+mod environments;
 mod extractors;
+mod limiter;
+mod playground;
+mod progress;
+mod rest;
+mod roots;
 mod server;
 mod service;
 mod utils;
 
-pub use server::PctxMcpServer;
+pub use rest::{RestApiDoc, RestConfig};
+pub use server::{PctxMcpServer, router};
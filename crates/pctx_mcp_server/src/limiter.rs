@@ -0,0 +1,488 @@
+//! Concurrency limit of executions with priority lanes & fair scheduling across clients
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{extract::ConnectInfo, http::Extensions};
+use opentelemetry::{KeyValue, metrics::Histogram};
+use pctx_code_mode::model::ExecutePriority;
+use pctx_config::concurrency::ConcurrencyConfig;
+use rmcp::model::ErrorCode;
+use serde_json::json;
+use tokio::sync::oneshot;
+use tracing::debug;
+
+/// Time executions waited for a slot, recorded for every limited execution
+static QUEUE_WAIT: LazyLock<Histogram<f64>> = LazyLock::new(|| {
+    opentelemetry::global::meter("pctx")
+        .f64_histogram("pctx.execution.queue_wait_ms")
+        .with_description("Time executions waited for a free execution slot in milliseconds")
+        .with_unit("ms")
+        .build()
+});
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum LimitError {
+    #[error("Too many queued executions ({0}), retry later")]
+    QueueFull(usize),
+}
+
+impl LimitError {
+    /// JSON-RPC code of executions rejected by the limiter, in the range reserved for
+    /// server errors
+    pub(crate) const CODE: ErrorCode = ErrorCode(-32001);
+
+    /// MCP error of the rejected execution, marked as retryable so clients back off
+    /// instead of treating it as a failure of the server
+    pub(crate) fn to_error_data(&self) -> rmcp::ErrorData {
+        let data = match self {
+            Self::QueueFull(queued) => json!({
+                "reason": "RESOURCE_EXHAUSTED",
+                "retryable": true,
+                "queued": queued,
+            }),
+        };
+        rmcp::ErrorData::new(Self::CODE, self.to_string(), Some(data))
+    }
+}
+
+/// Client executions are queued for: its MCP session, or the IP address it connects
+/// from without a session
+///
+/// Clients without either share a single queue, e.g. the client of a stdio server or
+/// the clients of a router served without connect info.
+pub(crate) fn client_key(session_id: Option<&str>, extensions: Option<&Extensions>) -> String {
+    if let Some(session_id) = session_id {
+        return session_id.to_string();
+    }
+    extensions
+        .and_then(|e| e.get::<ConnectInfo<SocketAddr>>())
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_default()
+}
+
+/// Limits the executions running at once, shared by all sessions of a server
+///
+/// Executions waiting for a slot are queued per client in the lane of their priority.
+/// Slots are handed to the clients of the interactive lane round robin, and to the
+/// clients of the background lane once no interactive execution is waiting.
+#[derive(Clone)]
+pub(crate) struct ExecutionLimiter {
+    config: ConcurrencyConfig,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    running: usize,
+    running_by_client: HashMap<String, usize>,
+    // waiting executions by priority, interactive first
+    lanes: [Lane; 2],
+}
+
+/// Executions of a priority waiting for a slot
+#[derive(Default)]
+struct Lane {
+    waiting: HashMap<String, VecDeque<oneshot::Sender<ExecutionPermit>>>,
+    // clients with waiting executions, in the order they are served
+    rotation: VecDeque<String>,
+}
+
+impl Lane {
+    fn queued(&self) -> usize {
+        self.waiting.values().map(VecDeque::len).sum()
+    }
+
+    /// Forgets waiting executions that were cancelled
+    fn prune(&mut self) {
+        self.waiting.retain(|_, waiting| {
+            waiting.retain(|sender| !sender.is_closed());
+            !waiting.is_empty()
+        });
+        let waiting = &self.waiting;
+        self.rotation.retain(|client| waiting.contains_key(client));
+    }
+}
+
+fn lane_index(priority: ExecutePriority) -> usize {
+    match priority {
+        ExecutePriority::Interactive => 0,
+        ExecutePriority::Background => 1,
+    }
+}
+
+impl State {
+    fn queued(&self) -> usize {
+        self.lanes.iter().map(Lane::queued).sum()
+    }
+
+    fn client_running(&self, client: &str) -> usize {
+        self.running_by_client
+            .get(client)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn start(&mut self, client: &str) {
+        self.running += 1;
+        *self.running_by_client.entry(client.into()).or_default() += 1;
+    }
+
+    fn finish(&mut self, client: &str) {
+        self.running -= 1;
+        if let Some(running) = self.running_by_client.get_mut(client) {
+            *running -= 1;
+            if *running == 0 {
+                self.running_by_client.remove(client);
+            }
+        }
+    }
+
+    fn prune(&mut self) {
+        for lane in &mut self.lanes {
+            lane.prune();
+        }
+    }
+}
+
+/// Slot of a running execution, freed when dropped
+pub(crate) struct ExecutionPermit {
+    client: String,
+    state: Option<Arc<Mutex<State>>>,
+    config: ConcurrencyConfig,
+}
+
+impl Drop for ExecutionPermit {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            let mut guard = state.lock().unwrap();
+            guard.finish(&self.client);
+            dispatch(&state, &mut guard, &self.config);
+        }
+    }
+}
+
+/// Hands free slots to the waiting executions, lane by lane & visiting the clients of
+/// a lane round robin
+fn dispatch(state: &Arc<Mutex<State>>, guard: &mut State, config: &ConcurrencyConfig) {
+    for lane in 0..guard.lanes.len() {
+        dispatch_lane(state, guard, lane, config);
+    }
+}
+
+fn dispatch_lane(
+    state: &Arc<Mutex<State>>,
+    guard: &mut State,
+    lane: usize,
+    config: &ConcurrencyConfig,
+) {
+    let mut skipped = 0;
+    while guard.running < config.max_executions && skipped < guard.lanes[lane].rotation.len() {
+        let Some(client) = guard.lanes[lane].rotation.pop_front() else {
+            break;
+        };
+        if guard.client_running(&client) >= config.max_per_client() {
+            // the client is at its share, the next client gets the slot
+            guard.lanes[lane].rotation.push_back(client);
+            skipped += 1;
+            continue;
+        }
+
+        let waiting = &mut guard.lanes[lane].waiting;
+        let Some(sender) = waiting.get_mut(&client).and_then(VecDeque::pop_front) else {
+            waiting.remove(&client);
+            continue;
+        };
+        if waiting.get(&client).is_some_and(VecDeque::is_empty) {
+            waiting.remove(&client);
+        } else {
+            guard.lanes[lane].rotation.push_back(client.clone());
+        }
+
+        guard.start(&client);
+        let permit = ExecutionPermit {
+            client: client.clone(),
+            state: Some(state.clone()),
+            config: config.clone(),
+        };
+        if let Err(mut permit) = sender.send(permit) {
+            // the execution was cancelled while waiting
+            permit.state = None;
+            guard.finish(&client);
+        }
+        skipped = 0;
+    }
+}
+
+impl ExecutionLimiter {
+    pub(crate) fn new(config: ConcurrencyConfig) -> Self {
+        Self {
+            config,
+            state: Arc::default(),
+        }
+    }
+
+    /// Waits for a free slot of the client's execution, `None` if executions are not limited.
+    /// Background executions only start immediately if no interactive execution waits.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the queue of waiting executions is full
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub(crate) async fn acquire(
+        &self,
+        client: &str,
+        priority: ExecutePriority,
+    ) -> Result<Option<ExecutionPermit>, LimitError> {
+        if !self.config.enabled() {
+            return Ok(None);
+        }
+
+        let started = Instant::now();
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            let lane = lane_index(priority);
+            let ahead: usize = state.lanes[..lane].iter().map(Lane::queued).sum();
+            if state.running < self.config.max_executions
+                && state.client_running(client) < self.config.max_per_client()
+                && ahead == 0
+            {
+                state.start(client);
+                record_wait(client, priority, Duration::ZERO);
+                return Ok(Some(ExecutionPermit {
+                    client: client.into(),
+                    state: Some(self.state.clone()),
+                    config: self.config.clone(),
+                }));
+            }
+
+            if state.queued() >= self.config.queue_depth {
+                state.prune();
+                if state.queued() >= self.config.queue_depth {
+                    return Err(LimitError::QueueFull(state.queued()));
+                }
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            let lane = &mut state.lanes[lane];
+            let waiting = lane.waiting.entry(client.into()).or_default();
+            waiting.push_back(sender);
+            if waiting.len() == 1 {
+                lane.rotation.push_back(client.into());
+            }
+            receiver
+        };
+
+        // the sender is only dropped with the limiter, which outlives the waiting executions
+        let permit = receiver.await.ok();
+        record_wait(client, priority, started.elapsed());
+        Ok(permit)
+    }
+}
+
+fn record_wait(client: &str, priority: ExecutePriority, waited: Duration) {
+    let priority = match priority {
+        ExecutePriority::Interactive => "interactive",
+        ExecutePriority::Background => "background",
+    };
+    let waited_ms = waited.as_secs_f64() * 1000.0;
+    QUEUE_WAIT.record(waited_ms, &[KeyValue::new("priority", priority)]);
+    if !waited.is_zero() {
+        debug!(
+            client,
+            priority,
+            queue_wait_ms = waited_ms,
+            "Execution waited for a slot"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pctx_code_mode::model::ExecutePriority::{self, Background, Interactive};
+    use pctx_config::concurrency::ConcurrencyConfig;
+
+    use super::{ExecutionLimiter, LimitError, client_key};
+
+    fn limiter(
+        max_executions: usize,
+        queue_depth: usize,
+        max_per_client: Option<usize>,
+    ) -> ExecutionLimiter {
+        ExecutionLimiter::new(ConcurrencyConfig {
+            max_executions,
+            queue_depth,
+            max_per_client,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_limits_and_queues() {
+        let limiter = limiter(1, 1, None);
+
+        let running = limiter.acquire("a", Interactive).await.unwrap();
+        assert!(running.is_some());
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("a", Interactive).await.unwrap().is_some() }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(
+            limiter.acquire("b", Interactive).await.err(),
+            Some(LimitError::QueueFull(1))
+        );
+
+        drop(running);
+        assert!(queued.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_serves_clients_round_robin() {
+        let limiter = limiter(1, 10, None);
+        let running = limiter.acquire("chatty", Interactive).await.unwrap();
+
+        let (order, mut started) = tokio::sync::mpsc::unbounded_channel();
+        for client in ["chatty", "chatty", "chatty", "quiet"] {
+            let limiter = limiter.clone();
+            let order = order.clone();
+            tokio::spawn(async move {
+                let permit = limiter.acquire(client, Interactive).await.unwrap();
+                order.send(client).unwrap();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                drop(permit);
+            });
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        drop(running);
+
+        let mut served = vec![];
+        for _ in 0..4 {
+            served.push(started.recv().await.unwrap());
+        }
+        assert_eq!(served, ["chatty", "quiet", "chatty", "chatty"]);
+    }
+
+    #[tokio::test]
+    async fn test_per_client_share() {
+        let limiter = limiter(2, 10, Some(1));
+        let _running = limiter.acquire("chatty", Interactive).await.unwrap();
+
+        let chatty = tokio::spawn({
+            let limiter = limiter.clone();
+            async move {
+                limiter
+                    .acquire("chatty", Interactive)
+                    .await
+                    .unwrap()
+                    .is_some()
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!chatty.is_finished(), "the client is at its share");
+
+        // the free slot goes to another client
+        assert!(
+            limiter
+                .acquire("quiet", Interactive)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        chatty.abort();
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let limiter = limiter(0, 0, None);
+        assert!(limiter.acquire("a", Interactive).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_interactive_before_background() {
+        let limiter = limiter(1, 10, None);
+        let running = limiter.acquire("batch", Background).await.unwrap();
+
+        let (order, mut started) = tokio::sync::mpsc::unbounded_channel();
+        let executions: [(&str, ExecutePriority); 3] = [
+            ("batch", Background),
+            ("batch", Background),
+            ("agent", Interactive),
+        ];
+        for (client, priority) in executions {
+            let limiter = limiter.clone();
+            let order = order.clone();
+            tokio::spawn(async move {
+                let permit = limiter.acquire(client, priority).await.unwrap();
+                order.send(priority).unwrap();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                drop(permit);
+            });
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        drop(running);
+
+        let mut served = vec![];
+        for _ in 0..3 {
+            served.push(started.recv().await.unwrap());
+        }
+        assert_eq!(served, [Interactive, Background, Background]);
+    }
+
+    #[tokio::test]
+    async fn test_prunes_cancelled_background() {
+        let limiter = limiter(1, 1, None);
+        let _running = limiter.acquire("agent", Interactive).await.unwrap();
+
+        let cancelled = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire("batch", Background).await.is_ok() }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        // the cancelled execution no longer takes up the queue
+        let queued = tokio::time::timeout(
+            Duration::from_millis(20),
+            limiter.acquire("agent", Interactive),
+        )
+        .await;
+        assert!(queued.is_err(), "the execution should wait for a slot");
+
+        let state = limiter.state.lock().unwrap();
+        assert!(state.lanes[1].waiting.is_empty());
+        assert!(state.lanes[1].rotation.is_empty());
+    }
+
+    #[test]
+    fn test_client_key() {
+        let mut extensions = axum::http::Extensions::new();
+        extensions.insert(axum::extract::ConnectInfo(std::net::SocketAddr::from((
+            [10, 0, 0, 7],
+            51234,
+        ))));
+
+        assert_eq!(client_key(Some("session"), Some(&extensions)), "session");
+        assert_eq!(client_key(None, Some(&extensions)), "10.0.0.7");
+        assert_eq!(client_key(None, None), "");
+    }
+
+    #[test]
+    fn test_queue_full_error_is_retryable() {
+        let err = LimitError::QueueFull(64).to_error_data();
+        assert_eq!(err.code, LimitError::CODE);
+        let data = err.data.unwrap();
+        assert_eq!(data["reason"], "RESOURCE_EXHAUSTED");
+        assert_eq!(data["retryable"], true);
+    }
+}
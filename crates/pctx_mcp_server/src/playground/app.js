@@ -0,0 +1,215 @@
+// pctx playground: lists the functions of the server, edits code with their typings
+// and executes it with the REST API served next to this page.
+
+const MONACO = "https://cdn.jsdelivr.net/npm/monaco-editor@0.52.2/min/vs";
+const DEFAULT_CODE = `async function run() {
+  // call functions of the catalog, e.g. await Namespace.functionName({ ... })
+  return "hello from pctx";
+}
+`;
+
+const $ = (id) => document.getElementById(id);
+const tokenInput = $("token");
+tokenInput.value = localStorage.getItem("pctx-playground-token") ?? "";
+tokenInput.addEventListener("change", () => {
+  localStorage.setItem("pctx-playground-token", tokenInput.value);
+  loadFunctions();
+});
+
+let functions = [];
+let editor = null;
+
+function headers(accept = "application/json") {
+  const headers = { "Content-Type": "application/json", Accept: accept };
+  if (tokenInput.value) {
+    headers.Authorization = `Bearer ${tokenInput.value}`;
+  }
+  return headers;
+}
+
+async function api(path, body) {
+  const res = await fetch(path, {
+    method: body === undefined ? "GET" : "POST",
+    headers: headers(),
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  const json = await res.json();
+  if (!res.ok) {
+    throw new Error(json.message ?? res.statusText);
+  }
+  return json;
+}
+
+function setStatus(message, error = false) {
+  $("status").textContent = message;
+  $("status").className = error ? "error" : "";
+}
+
+// -------------- Editor --------------
+
+/** Monaco editor if it can be loaded, otherwise a plain textarea */
+function createEditor() {
+  return new Promise((resolve) => {
+    const fallback = () => {
+      const textarea = document.createElement("textarea");
+      textarea.spellcheck = false;
+      textarea.value = DEFAULT_CODE;
+      $("editor").replaceChildren(textarea);
+      resolve({
+        getValue: () => textarea.value,
+        setValue: (value) => (textarea.value = value),
+        insert: (text) => textarea.setRangeText(text, textarea.selectionStart, textarea.selectionEnd, "end"),
+        setTypings: () => {},
+      });
+    };
+
+    const loader = document.createElement("script");
+    loader.src = `${MONACO}/loader.js`;
+    loader.onerror = fallback;
+    loader.onload = () => {
+      window.require.config({ paths: { vs: MONACO } });
+      window.require(["vs/editor/editor.main"], (monaco) => {
+        const ts = monaco.languages.typescript;
+        ts.typescriptDefaults.setCompilerOptions({
+          target: ts.ScriptTarget.ES2022,
+          lib: ["es2022"],
+          strict: true,
+          noEmit: true,
+        });
+        let typings = null;
+        const instance = monaco.editor.create($("editor"), {
+          value: DEFAULT_CODE,
+          language: "typescript",
+          theme: "vs-dark",
+          automaticLayout: true,
+          minimap: { enabled: false },
+          fontSize: 13,
+        });
+        resolve({
+          getValue: () => instance.getValue(),
+          setValue: (value) => instance.setValue(value),
+          insert: (text) => instance.executeEdits("catalog", [{ range: instance.getSelection(), text }]),
+          setTypings: (code) => {
+            typings?.dispose();
+            typings = ts.typescriptDefaults.addExtraLib(code, "file:///pctx-functions.d.ts");
+          },
+        });
+      }, fallback);
+    };
+    document.head.append(loader);
+  });
+}
+
+// -------------- Catalog --------------
+
+function renderCatalog() {
+  const filter = $("filter").value.toLowerCase();
+  const items = functions
+    .filter((f) => `${f.namespace}.${f.name} ${f.description ?? ""}`.toLowerCase().includes(filter))
+    .map((f) => {
+      const item = document.createElement("li");
+      const name = document.createElement("div");
+      name.className = "name";
+      name.textContent = `${f.namespace}.${f.name}`;
+      const description = document.createElement("div");
+      description.className = "description";
+      description.textContent = f.description ?? "";
+      item.append(name, description);
+      item.title = "Insert a call of the function";
+      item.addEventListener("click", () => editor?.insert(`await ${f.namespace}.${f.name}({})`));
+      return item;
+    });
+  $("catalog").replaceChildren(...items);
+}
+
+async function loadFunctions() {
+  setStatus("Loading functions...");
+  try {
+    const listed = await api("/v1/functions");
+    functions = listed.functions;
+    renderCatalog();
+
+    const ids = functions.map((f) => `${f.namespace}.${f.name}`);
+    const details = ids.length
+      ? await api("/v1/functions/details", { functions: ids, detail_level: "full" })
+      : { code: "" };
+    editor?.setTypings(details.code);
+    setStatus(`${functions.length} functions`);
+  } catch (e) {
+    setStatus(`Failed to load functions: ${e.message}`, true);
+  }
+}
+
+// -------------- Execute --------------
+
+function showOutput(output) {
+  const diagnostics = (output.diagnostics ?? [])
+    .map((d) => `Line ${d.line ?? "?"}: ${d.code ? `TS${d.code} ` : ""}${d.message}`)
+    .join("\n");
+  $("output").textContent = diagnostics || JSON.stringify(output.output ?? null, null, 2);
+  $("logs").textContent = [output.stdout, output.stderr].filter(Boolean).join("\n");
+  setStatus(output.success ? "Succeeded" : "Failed", !output.success);
+}
+
+/** Executes the code, reading the `progress` & `result` events of the response */
+async function execute() {
+  $("execute").disabled = true;
+  $("output").textContent = "";
+  $("logs").textContent = "";
+  setStatus("Executing...");
+
+  try {
+    const res = await fetch("/v1/execute", {
+      method: "POST",
+      headers: headers("text/event-stream"),
+      body: JSON.stringify({
+        code: editor.getValue(),
+        skip_type_check: $("skip-type-check").checked,
+      }),
+    });
+    if (!res.ok) {
+      const error = await res.json().catch(() => ({}));
+      throw new Error(error.message ?? res.statusText);
+    }
+
+    const reader = res.body.pipeThrough(new TextDecoderStream()).getReader();
+    let buffer = "";
+    for (;;) {
+      const { value, done } = await reader.read();
+      if (done) break;
+      buffer += value;
+      let end;
+      while ((end = buffer.indexOf("\n\n")) >= 0) {
+        const lines = buffer.slice(0, end).split("\n");
+        buffer = buffer.slice(end + 2);
+        const event = lines.find((l) => l.startsWith("event:"))?.slice(6).trim();
+        const data = lines
+          .filter((l) => l.startsWith("data:"))
+          .map((l) => l.slice(5).trim())
+          .join("\n");
+        if (!data) continue;
+        const payload = JSON.parse(data);
+        if (event === "progress") {
+          setStatus(`[${payload.server}] ${payload.message ?? payload.progress}`);
+        } else if (event === "result") {
+          showOutput(payload);
+        } else if (event === "error") {
+          throw new Error(payload.message);
+        }
+      }
+    }
+  } catch (e) {
+    setStatus(`Execution failed: ${e.message}`, true);
+  } finally {
+    $("execute").disabled = false;
+  }
+}
+
+$("filter").addEventListener("input", renderCatalog);
+$("reload").addEventListener("click", loadFunctions);
+$("execute").addEventListener("click", execute);
+
+createEditor().then((created) => {
+  editor = created;
+  loadFunctions();
+});
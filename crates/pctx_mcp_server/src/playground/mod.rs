@@ -0,0 +1,88 @@
+//! Browser playground of code mode, a web alternative to the `pctx mcp dev` terminal UI
+//!
+//! The page is bundled into the binary and talks to the REST API served next to it
+//! (`/v1/functions`, `/v1/functions/details`, `/v1/execute`).
+
+use axum::{
+    Router,
+    http::header,
+    response::{IntoResponse, Redirect},
+    routing::get,
+};
+
+static INDEX_HTML: &str = include_str!("index.html");
+static APP_JS: &str = include_str!("app.js");
+static STYLE_CSS: &str = include_str!("style.css");
+
+/// Router serving the playground at `/playground`
+pub(crate) fn router() -> Router {
+    Router::new()
+        .route(
+            "/playground",
+            get(|| async { Redirect::permanent("/playground/") }),
+        )
+        .route(
+            "/playground/",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+                    INDEX_HTML,
+                )
+            }),
+        )
+        .route(
+            "/playground/app.js",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "text/javascript; charset=utf-8")],
+                    APP_JS,
+                )
+                    .into_response()
+            }),
+        )
+        .route(
+            "/playground/style.css",
+            get(|| async {
+                (
+                    [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+                    STYLE_CSS,
+                )
+            }),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header},
+    };
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    use super::router;
+
+    #[tokio::test]
+    async fn test_serves_assets() {
+        let res = router()
+            .oneshot(Request::get("/playground").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+
+        for (path, content_type) in [
+            ("/playground/", "text/html; charset=utf-8"),
+            ("/playground/app.js", "text/javascript; charset=utf-8"),
+            ("/playground/style.css", "text/css; charset=utf-8"),
+        ] {
+            let res = router()
+                .oneshot(Request::get(path).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK, "{path}");
+            assert_eq!(res.headers()[header::CONTENT_TYPE], content_type);
+            let body = res.into_body().collect().await.unwrap().to_bytes();
+            assert!(!body.is_empty());
+        }
+    }
+}
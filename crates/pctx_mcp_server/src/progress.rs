@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use async_trait::async_trait;
+use pctx_config::client::ProgressHandler;
+use rmcp::{
+    RoleServer,
+    model::{ProgressNotificationParam, ProgressToken},
+    service::Peer,
+};
+use tracing::debug;
+
+/// Forwards progress of upstream tool calls as progress of the client's `execute` call
+///
+/// Upstream calls report progress on their own scales, so the forwarded progress counts
+/// the notifications (it must increase) and the upstream values go into the message.
+pub(crate) struct PeerProgress {
+    peer: Peer<RoleServer>,
+    token: ProgressToken,
+    count: AtomicU32,
+}
+
+impl PeerProgress {
+    pub(crate) fn new(peer: Peer<RoleServer>, token: ProgressToken) -> Self {
+        Self {
+            peer,
+            token,
+            count: AtomicU32::new(0),
+        }
+    }
+}
+
+impl std::fmt::Debug for PeerProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PeerProgress")
+            .field("token", &self.token)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ProgressHandler for PeerProgress {
+    async fn on_progress(&self, server: &str, params: ProgressNotificationParam) {
+        let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        let message = match (params.message, params.total) {
+            (Some(message), _) => format!("[{server}] {message}"),
+            (None, Some(total)) => format!("[{server}] {}/{total}", params.progress),
+            (None, None) => format!("[{server}] {}", params.progress),
+        };
+
+        let forwarded = ProgressNotificationParam {
+            progress_token: self.token.clone(),
+            progress: f64::from(count),
+            total: None,
+            message: Some(message),
+        };
+        if let Err(e) = self.peer.notify_progress(forwarded).await {
+            debug!(error = %e, "Failed forwarding progress of upstream MCP server");
+        }
+    }
+}
@@ -0,0 +1,526 @@
+#![allow(clippy::needless_for_each)] // Caused by #[derive(OpenApi)]
+
+//! REST facade of code mode, for platforms that can speak neither MCP nor WebSockets
+
+use std::{convert::Infallible, sync::Arc};
+
+use async_trait::async_trait;
+use axum::{
+    Json, Router,
+    extract::{Request, State},
+    http::{Extensions, HeaderMap, StatusCode, header},
+    middleware::{self, Next},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{get, post},
+};
+use pctx_code_mode::{
+    CodeMode, execute_blocking,
+    model::{
+        ExecuteInput, ExecuteOutput, GetFunctionDetailsInput, GetFunctionDetailsOutput,
+        ListFunctionsOutput,
+    },
+};
+use pctx_config::{auth::token_accepted, client::ProgressHandler};
+use rmcp::model::ProgressNotificationParam;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio_stream::{Stream, StreamExt, wrappers::UnboundedReceiverStream};
+use tracing::{Instrument, error, warn};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::{
+    environments::Environments,
+    limiter::{ExecutionLimiter, ExecutionPermit, client_key},
+    utils::execution_span,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_functions, get_function_details, execute),
+    components(schemas(RestError, ProgressEvent)),
+    tags((name = "code-mode", description = "Code mode functions and execution")),
+    info(
+        title = "pctx REST API",
+        version = "0.1.0",
+        description = "REST facade of the pctx MCP server. Requests are authenticated with `Authorization: Bearer <token>` if tokens are configured.",
+    )
+)]
+pub struct RestApiDoc;
+
+/// Configuration of the REST API served next to `/mcp`
+#[derive(Debug, Clone, Default)]
+pub struct RestConfig {
+    /// Bearer tokens accepted by the API, requests are not authenticated if empty
+    pub tokens: Vec<String>,
+}
+
+#[derive(Clone)]
+struct RestState {
+    environments: Environments,
+    tokens: Arc<Vec<String>>,
+    // shared with the MCP endpoints, so executions are limited per server
+    limiter: ExecutionLimiter,
+}
+
+/// Error returned by the REST API
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct RestError {
+    message: String,
+}
+
+/// `progress` event of a streamed execution, sent when an upstream tool call reports
+/// its progress
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct ProgressEvent {
+    /// Upstream MCP server of the tool call
+    server: String,
+    progress: f64,
+    total: Option<f64>,
+    message: Option<String>,
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (
+        status,
+        Json(RestError {
+            message: message.into(),
+        }),
+    )
+        .into_response()
+}
+
+/// Router of the REST API, `/v1/openapi.json` is served without authentication
+pub(crate) fn router(
+    environments: Environments,
+    config: RestConfig,
+    limiter: ExecutionLimiter,
+) -> Router {
+    let state = RestState {
+        environments,
+        tokens: Arc::new(config.tokens),
+        limiter,
+    };
+
+    let authenticated = Router::new()
+        .route("/v1/functions", get(list_functions))
+        .route("/v1/functions/details", post(get_function_details))
+        .route("/v1/execute", post(execute))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_auth));
+
+    Router::new()
+        .route(
+            "/v1/openapi.json",
+            get(|| async { Json(RestApiDoc::openapi()) }),
+        )
+        .merge(authenticated)
+        .with_state(state)
+}
+
+/// Middleware rejecting requests without an accepted bearer token with `401 Unauthorized`
+async fn require_auth(State(state): State<RestState>, request: Request, next: Next) -> Response {
+    if state.tokens.is_empty() {
+        return next.run(request).await;
+    }
+
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    match token {
+        Some(token) if token_accepted(state.tokens.iter().map(String::as_str), token) => {
+            next.run(request).await
+        }
+        Some(_) => {
+            warn!(uri = %request.uri(), "Rejecting request with invalid token");
+            error_response(StatusCode::UNAUTHORIZED, "Invalid bearer token")
+        }
+        None => {
+            warn!(uri = %request.uri(), "Rejecting request without token");
+            error_response(StatusCode::UNAUTHORIZED, "Missing bearer token")
+        }
+    }
+}
+
+/// List the functions executed code can call
+#[utoipa::path(
+    get,
+    path = "/v1/functions",
+    tag = "code-mode",
+    responses(
+        (status = 200, description = "Functions of the upstream MCP servers", body = ListFunctionsOutput),
+        (status = 401, description = "Missing or invalid bearer token", body = RestError)
+    )
+)]
+async fn list_functions(State(state): State<RestState>) -> Json<ListFunctionsOutput> {
+    Json(state.environments.default_code_mode().list_functions())
+}
+
+/// Get the TypeScript declarations of functions
+#[utoipa::path(
+    post,
+    path = "/v1/functions/details",
+    tag = "code-mode",
+    request_body = GetFunctionDetailsInput,
+    responses(
+        (status = 200, description = "Declarations of the requested functions", body = GetFunctionDetailsOutput),
+        (status = 401, description = "Missing or invalid bearer token", body = RestError)
+    )
+)]
+async fn get_function_details(
+    State(state): State<RestState>,
+    Json(input): Json<GetFunctionDetailsInput>,
+) -> Json<GetFunctionDetailsOutput> {
+    Json(
+        state
+            .environments
+            .default_code_mode()
+            .get_function_details(input),
+    )
+}
+
+/// Execute TypeScript code
+///
+/// Requests accepting `text/event-stream` receive `progress` events of upstream tool
+/// calls followed by a `result` event with the output, or an `error` event.
+#[utoipa::path(
+    post,
+    path = "/v1/execute",
+    tag = "code-mode",
+    request_body = ExecuteInput,
+    responses(
+        (status = 200, description = "Output of the execution", content(
+            (ExecuteOutput = "application/json"),
+            (String = "text/event-stream")
+        )),
+        (status = 400, description = "Unknown profile or ephemeral servers not allowed", body = RestError),
+        (status = 401, description = "Missing or invalid bearer token", body = RestError),
+        (status = 429, description = "Too many queued executions, retry later", body = RestError),
+        (status = 500, description = "Execution failed", body = RestError)
+    )
+)]
+async fn execute(
+    State(state): State<RestState>,
+    headers: HeaderMap,
+    extensions: Extensions,
+    Json(input): Json<ExecuteInput>,
+) -> Response {
+    let mut code_mode = match state.environments.resolve(&input, Some(&headers)).await {
+        Ok(code_mode) => code_mode,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+
+    let client = client_key(None, Some(&extensions));
+    let permit = match state.limiter.acquire(&client, input.priority).await {
+        Ok(permit) => permit,
+        Err(e) => {
+            warn!("Rejecting execution: {e}");
+            let mut res = error_response(StatusCode::TOO_MANY_REQUESTS, e.to_string());
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from_static("1"));
+            return res;
+        }
+    };
+    let span = execution_span(&mut code_mode, None);
+
+    let streamed = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/event-stream"));
+    if streamed {
+        let _entered = span.enter();
+        return execute_events(code_mode, input, permit).into_response();
+    }
+
+    let result = execute_blocking(code_mode, input, None)
+        .instrument(span)
+        .await;
+    drop(permit);
+    match result {
+        Ok(output) => Json(output).into_response(),
+        Err(e) => {
+            error!("Sandbox execution error: {e}");
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+    }
+}
+
+fn execute_events(
+    mut code_mode: CodeMode,
+    input: ExecuteInput,
+    permit: Option<ExecutionPermit>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    // the host's progress handler takes precedence over the events of this request
+    if code_mode.client_handlers().progress.is_none() {
+        code_mode.set_progress_handler(Some(Arc::new(EventProgress { events: tx.clone() })));
+        // progress of stdio servers shared with other requests would reach their events
+        code_mode.set_handlers_owner(Some(uuid::Uuid::new_v4().to_string()));
+    }
+
+    // the execution stays in the span of the request
+    tokio::spawn(
+        async move {
+            let result = execute_blocking(code_mode, input, None).await;
+            drop(permit);
+            let event = match result {
+                Ok(output) => json_event("result", &output),
+                Err(e) => {
+                    error!("Sandbox execution error: {e}");
+                    json_event(
+                        "error",
+                        &RestError {
+                            message: e.to_string(),
+                        },
+                    )
+                }
+            };
+            // the stream ends once the progress handler is dropped with the code mode
+            let _ = tx.send(event);
+        }
+        .in_current_span(),
+    );
+
+    Sse::new(UnboundedReceiverStream::new(rx).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+fn json_event(name: &str, data: &impl Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()))
+}
+
+/// Sends the progress of upstream tool calls as `progress` events
+#[derive(Debug)]
+struct EventProgress {
+    events: mpsc::UnboundedSender<Event>,
+}
+
+#[async_trait]
+impl ProgressHandler for EventProgress {
+    async fn on_progress(&self, server: &str, params: ProgressNotificationParam) {
+        let event = ProgressEvent {
+            server: server.to_string(),
+            progress: params.progress,
+            total: params.total,
+            message: params.message,
+        };
+        let _ = self.events.send(json_event("progress", &event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use pctx_code_mode::model::ExecutePriority;
+    use pctx_config::concurrency::ConcurrencyConfig;
+    use serde_json::{Value, json};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn rest(tokens: &[&str]) -> Router {
+        router(
+            Environments::new(CodeMode::default()),
+            RestConfig {
+                tokens: tokens.iter().map(ToString::to_string).collect(),
+            },
+            ExecutionLimiter::new(ConcurrencyConfig::default()),
+        )
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requires_token() {
+        let app = rest(&["secret"]);
+
+        let res = app
+            .clone()
+            .oneshot(Request::get("/v1/functions").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/v1/functions")
+                    .header(header::AUTHORIZATION, "Bearer wrong")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/v1/functions")
+                    .header(header::AUTHORIZATION, "Bearer secret")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body_json(res).await["functions"], json!([]));
+
+        // the spec is public
+        let res = app
+            .oneshot(
+                Request::get("/v1/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(body_json(res).await["paths"]["/v1/execute"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let input = json!({
+            "code": "async function run(input) { console.log('hi'); return input.a + 1; }",
+            "input": {"a": 1}
+        });
+        let request = |accept: &str| {
+            Request::post("/v1/execute")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::ACCEPT, accept)
+                .body(Body::from(input.to_string()))
+                .unwrap()
+        };
+
+        let res = rest(&[])
+            .oneshot(request("application/json"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let output = body_json(res).await;
+        assert_eq!(output["success"], json!(true));
+        assert_eq!(output["output"], json!(2));
+        assert_eq!(output["stdout"].as_str().unwrap().trim(), "hi");
+
+        let res = rest(&[])
+            .oneshot(request("text/event-stream"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("event: result"), "{body}");
+        assert!(body.contains("\"output\":2"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_execute_requires_token() {
+        let app = rest(&["secret"]);
+        let request = |token: Option<&str>| {
+            let mut request =
+                Request::post("/v1/execute").header(header::CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            request
+                .body(Body::from(
+                    json!({ "code": "async function run() { return 1; }" }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let res = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            body_json(res).await["message"],
+            json!("Missing bearer token")
+        );
+
+        let res = app.clone().oneshot(request(Some("secre"))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            body_json(res).await["message"],
+            json!("Invalid bearer token")
+        );
+
+        let res = app.oneshot(request(Some("secret"))).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body_json(res).await["output"], json!(1));
+    }
+
+    #[tokio::test]
+    async fn test_execute_projection() {
+        let input = json!({
+            "code": "async function run() { return { items: [{ id: 1, name: 'a' }, { id: 2, name: 'b' }] }; }",
+            "projection": "$.items[*].name"
+        });
+        let res = rest(&[])
+            .oneshot(
+                Request::post("/v1/execute")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(input.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body_json(res).await["output"], json!(["a", "b"]));
+    }
+
+    #[tokio::test]
+    async fn test_execute_shares_limiter() {
+        // the only slot is taken by an execution of the MCP endpoint
+        let limiter = ExecutionLimiter::new(ConcurrencyConfig {
+            max_executions: 1,
+            queue_depth: 0,
+            max_per_client: None,
+        });
+        let running = limiter
+            .acquire("mcp-session", ExecutePriority::Interactive)
+            .await
+            .unwrap();
+        let app = router(
+            Environments::new(CodeMode::default()),
+            RestConfig::default(),
+            limiter,
+        );
+        let request = |accept: &str| {
+            Request::post("/v1/execute")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::ACCEPT, accept)
+                .body(Body::from(
+                    json!({ "code": "async function run() { return 1; }" }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        for accept in ["application/json", "text/event-stream"] {
+            let res = app.clone().oneshot(request(accept)).await.unwrap();
+            assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(res.headers()[header::RETRY_AFTER], "1");
+            assert!(
+                body_json(res).await["message"]
+                    .as_str()
+                    .unwrap()
+                    .contains("retry later")
+            );
+        }
+
+        drop(running);
+        let res = app.oneshot(request("application/json")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(body_json(res).await["output"], json!(1));
+    }
+}
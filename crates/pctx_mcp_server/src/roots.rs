@@ -0,0 +1,27 @@
+use pctx_config::client::Root;
+use rmcp::{RoleServer, service::Peer};
+use tracing::{debug, warn};
+
+/// Lists the workspace roots of the client of the pctx MCP server, `None` if the client
+/// doesn't support roots or listing them failed
+///
+/// Roots are listed on every execution, so `notifications/roots/list_changed` needs no handling.
+pub(crate) async fn list_roots(peer: &Peer<RoleServer>) -> Option<Vec<Root>> {
+    let supported = peer
+        .peer_info()
+        .is_some_and(|info| info.capabilities.roots.is_some());
+    if !supported {
+        return None;
+    }
+
+    match peer.list_roots().await {
+        Ok(res) => {
+            debug!(roots = res.roots.len(), "Listed roots of MCP client");
+            Some(res.roots)
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed listing roots of MCP client");
+            None
+        }
+    }
+}
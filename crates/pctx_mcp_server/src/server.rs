@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+
 use anyhow::Result;
 use opentelemetry::{global, trace::TraceContextExt};
 use pctx_config::Config;
@@ -28,7 +30,10 @@ use tracing::{debug, info, warn};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::{
+    environments::Environments,
     extractors::HeaderExtractor,
+    limiter::ExecutionLimiter,
+    rest::{self, RestConfig},
     service::PctxMcpService,
     utils::{
         LOGO,
@@ -40,6 +45,9 @@ pub struct PctxMcpServer {
     host: String,
     port: u16,
     banner: bool,
+    rest: Option<RestConfig>,
+    playground: bool,
+    profiles: Vec<(String, Config, pctx_code_mode::CodeMode)>,
 }
 
 impl PctxMcpServer {
@@ -48,7 +56,74 @@ impl PctxMcpServer {
             host: host.into(),
             port,
             banner,
+            rest: None,
+            playground: false,
+            profiles: vec![],
+        }
+    }
+
+    /// Serves the profile's code mode at `/mcp/{name}` over HTTP, `cfg` is the
+    /// configuration of the profile (see [`Config::profile`])
+    #[must_use]
+    pub fn with_profile(
+        mut self,
+        name: &str,
+        cfg: Config,
+        code_mode: pctx_code_mode::CodeMode,
+    ) -> Self {
+        self.profiles.push((name.into(), cfg, code_mode));
+        self
+    }
+
+    /// Serves the REST API (`/v1/functions`, `/v1/execute`) next to `/mcp` over HTTP
+    #[must_use]
+    pub fn with_rest(mut self, rest: RestConfig) -> Self {
+        self.rest = Some(rest);
+        self
+    }
+
+    /// Serves the browser playground at `/playground`, which uses the REST API and
+    /// enables it without tokens if it isn't already
+    #[must_use]
+    pub fn with_playground(mut self) -> Self {
+        self.playground = true;
+        self.rest.get_or_insert_default();
+        self
+    }
+
+    /// Router of the server: `/mcp`, `/mcp/{profile}` of each profile, the REST API &
+    /// playground if enabled & the request id / tracing middleware
+    ///
+    /// The executions of all endpoints share the concurrency limit of the server.
+    pub fn router(&self, cfg: &Config, code_mode: pctx_code_mode::CodeMode) -> axum::Router {
+        let environments = configured_environments(cfg, code_mode).with_profiles(
+            self.profiles
+                .iter()
+                .map(|(name, _, code_mode)| (name.clone(), code_mode.clone()))
+                .collect(),
+        );
+        let limiter = ExecutionLimiter::new(cfg.concurrency.clone());
+        let rest_router = self
+            .rest
+            .clone()
+            .map(|rest| rest::router(environments.clone(), rest, limiter.clone()));
+
+        let mut router = mcp_router("/mcp", cfg, environments, limiter.clone());
+        for (name, profile_cfg, profile_code_mode) in &self.profiles {
+            router = router.merge(mcp_router(
+                &format!("/mcp/{name}"),
+                profile_cfg,
+                configured_environments(profile_cfg, profile_code_mode.clone()),
+                limiter.clone(),
+            ));
+        }
+        if let Some(rest_router) = rest_router {
+            router = router.merge(rest_router);
+        }
+        if self.playground {
+            router = router.merge(crate::playground::router());
         }
+        with_middleware(router)
     }
 
     /// Serves MCP server with default Ctr + C shutdown signal
@@ -87,74 +162,17 @@ impl PctxMcpServer {
     {
         self.banner_http(cfg, &code_mode);
 
-        let mcp_service = PctxMcpService::new(cfg, code_mode);
-
-        let service = StreamableHttpService::new(
-            move || Ok(mcp_service.clone()),
-            LocalSessionManager::default().into(),
-            StreamableHttpServerConfig {
-                stateful_mode: false,
-                ..Default::default()
-            },
-        );
-
-        let router = axum::Router::new().nest_service("/mcp", service).layer(
-            ServiceBuilder::new()
-                // Generate UUID if x-request-id header doesn't exist
-                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
-                // Propagate x-request-id to response headers
-                .layer(PropagateRequestIdLayer::x_request_id())
-                // Add tracing layer that includes request_id in spans
-                .layer(TraceLayer::new_for_http().make_span_with(
-                    |request: &axum::http::Request<_>| {
-                        let request_id = request
-                            .extensions()
-                            .get::<RequestId>()
-                            .map_or("unknown".to_string(), |id| {
-                                id.header_value().to_str().unwrap_or("invalid").to_string()
-                            });
-
-                        // Extract trace context from headers using OpenTelemetry propagator
-                        let parent_cx = global::get_text_map_propagator(|propagator| {
-                            propagator.extract(&HeaderExtractor(request.headers()))
-                        });
-
-                        // Check if we have a valid parent context
-                        let is_valid = parent_cx.span().span_context().is_valid();
-                        debug!(
-                            traceparent = ?request.headers().get("traceparent"),
-                            parent_valid = %is_valid,
-                            "Extracting trace context"
-                        );
-
-                        // Create span with extracted context
-                        let span = tracing::error_span!(
-                            "request",
-                            method = %request.method(),
-                            uri = %request.uri(),
-                            version = ?request.version(),
-                            request_id = %request_id,
-                        );
-
-                        // Set the parent OpenTelemetry context on the tracing span
-                        if is_valid {
-                            if let Err(e) = span.set_parent(parent_cx) {
-                                warn!(err = ?e, "Failed setting parent span context");
-                            } else {
-                                debug!("Successfully set parent span context");
-                            }
-                        }
-
-                        span
-                    },
-                )),
-        );
+        let router = self.router(cfg, code_mode);
         let tcp_listener =
             tokio::net::TcpListener::bind(format!("{}:{}", &self.host, self.port)).await?;
 
-        let _ = axum::serve(tcp_listener, router)
-            .with_graceful_shutdown(shutdown_signal)
-            .await;
+        // the address of clients identifies them for fair scheduling without a session
+        let _ = axum::serve(
+            tcp_listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown_signal)
+        .await;
 
         Ok(())
     }
@@ -195,7 +213,11 @@ impl PctxMcpServer {
     {
         self.banner_stdio(cfg, &code_mode);
 
-        let mcp_service = PctxMcpService::new(cfg, code_mode);
+        let mcp_service = PctxMcpService::new(
+            cfg,
+            configured_environments(cfg, code_mode),
+            ExecutionLimiter::new(cfg.concurrency.clone()),
+        );
         let mut shutdown_signal = Box::pin(shutdown_signal);
         let mut serve_task = tokio::spawn(mcp_service.serve(stdio()));
         let running = tokio::select! {
@@ -255,10 +277,15 @@ impl PctxMcpServer {
         builder.push_record(["Server Name", &cfg.name]);
         builder.push_record(["Server Version", &cfg.version]);
         builder.push_record([transport_label, transport_value]);
-        builder.push_record([
-            "Tools",
-            &["list_functions", "get_function_details", "execute"].join(", "),
-        ]);
+        if cfg.stateful_mode && transport_label == "Server URL" {
+            builder.push_record(["Sessions", "stateful"]);
+        }
+        let mut tools = vec!["list_functions", "search_functions", "get_function_details"];
+        if cfg.check_code_tool {
+            tools.push("check_code");
+        }
+        tools.push("execute");
+        builder.push_record(["Tools", &tools.join(", ")]);
         builder.push_record(["Docs", &fmt_dimmed("https://github.com/portofcontext/pctx")]);
 
         if !code_mode.tool_sets().is_empty() {
@@ -328,6 +355,24 @@ impl PctxMcpServer {
         }
 
         info!("PCTX listening at {mcp_url}...");
+        for (name, profile_cfg, _) in &self.profiles {
+            info!(
+                "Profile '{name}' with {} upstream MCP servers at {mcp_url}/{name}",
+                profile_cfg.servers.len()
+            );
+        }
+        if self.rest.is_some() {
+            info!(
+                "REST API at http://{}:{}/v1, OpenAPI spec at /v1/openapi.json",
+                self.host, self.port
+            );
+        }
+        if self.playground {
+            info!(
+                "Playground at http://{}:{}/playground/",
+                self.host, self.port
+            );
+        }
     }
 
     fn banner_stdio(&self, cfg: &pctx_config::Config, code_mode: &pctx_code_mode::CodeMode) {
@@ -339,10 +384,124 @@ impl PctxMcpServer {
     }
 }
 
+/// Router serving code mode at `/mcp` with the request id & tracing middleware of the pctx
+/// MCP server, to mount code mode inside an existing axum application
+///
+/// ```ignore
+/// let app = axum::Router::new()
+///     .route("/health", get(health))
+///     .merge(pctx_mcp_server::router(&cfg, code_mode));
+/// ```
+pub fn router(cfg: &Config, code_mode: pctx_code_mode::CodeMode) -> axum::Router {
+    with_middleware(mcp_router(
+        "/mcp",
+        cfg,
+        configured_environments(cfg, code_mode),
+        ExecutionLimiter::new(cfg.concurrency.clone()),
+    ))
+}
+
+/// Environments of the code mode, allowing ephemeral servers if configured
+fn configured_environments(cfg: &Config, code_mode: pctx_code_mode::CodeMode) -> Environments {
+    let environments = Environments::new(code_mode);
+    if cfg.ephemeral_servers {
+        environments.with_ephemeral_servers(cfg.connect_timeout_secs())
+    } else {
+        environments
+    }
+}
+
+fn mcp_router(
+    path: &str,
+    cfg: &Config,
+    environments: Environments,
+    limiter: ExecutionLimiter,
+) -> axum::Router {
+    let mcp_service = PctxMcpService::new(cfg, environments, limiter);
+
+    // in stateful mode the service is created once per MCP session, so nothing
+    // attached to a session (e.g. artifacts, offloaded outputs) is shared with other clients
+    let stateful = cfg.stateful_mode;
+    let service = StreamableHttpService::new(
+        move || {
+            Ok(if stateful {
+                mcp_service.for_session()
+            } else {
+                mcp_service.clone()
+            })
+        },
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig {
+            stateful_mode: cfg.stateful_mode,
+            ..Default::default()
+        },
+    );
+
+    axum::Router::new().nest_service(path, service)
+}
+
+fn with_middleware(router: axum::Router) -> axum::Router {
+    router.layer(
+        ServiceBuilder::new()
+            // Generate UUID if x-request-id header doesn't exist
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+            // Propagate x-request-id to response headers
+            .layer(PropagateRequestIdLayer::x_request_id())
+            // Add tracing layer that includes request_id in spans
+            .layer(TraceLayer::new_for_http().make_span_with(
+                |request: &axum::http::Request<_>| {
+                    let request_id = request
+                        .extensions()
+                        .get::<RequestId>()
+                        .map_or("unknown".to_string(), |id| {
+                            id.header_value().to_str().unwrap_or("invalid").to_string()
+                        });
+
+                    // Extract trace context from headers using OpenTelemetry propagator
+                    let parent_cx = global::get_text_map_propagator(|propagator| {
+                        propagator.extract(&HeaderExtractor(request.headers()))
+                    });
+
+                    // Check if we have a valid parent context
+                    let is_valid = parent_cx.span().span_context().is_valid();
+                    debug!(
+                        traceparent = ?request.headers().get("traceparent"),
+                        parent_valid = %is_valid,
+                        "Extracting trace context"
+                    );
+
+                    // Create span with extracted context
+                    let span = tracing::error_span!(
+                        "request",
+                        method = %request.method(),
+                        uri = %request.uri(),
+                        version = ?request.version(),
+                        request_id = %request_id,
+                    );
+
+                    // Set the parent OpenTelemetry context on the tracing span
+                    if is_valid {
+                        if let Err(e) = span.set_parent(parent_cx) {
+                            warn!(err = ?e, "Failed setting parent span context");
+                        } else {
+                            debug!("Successfully set parent span context");
+                        }
+                    }
+
+                    span
+                },
+            )),
+    )
+}
+
 #[cfg(test)]
 mod tests {
-    use super::PctxMcpServer;
+    use axum::{body::Body, http::Request};
     use pctx_config::Config;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use super::{PctxMcpServer, router};
 
     #[tokio::test]
     async fn test_serve_stdio_with_immediate_shutdown() {
@@ -365,6 +524,51 @@ mod tests {
     // stdio transport without actual stdin. The immediate shutdown test above covers
     // the basic shutdown mechanism.
 
+    fn initialize(path: &str) -> Request<Body> {
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": { "name": "test", "version": "0.0.0" }
+            }
+        });
+        Request::post(path)
+            .header("content-type", "application/json")
+            .header("accept", "application/json, text/event-stream")
+            .body(Body::from(initialize.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_embedded_router() {
+        let app = axum::Router::new().merge(router(
+            &Config::default(),
+            pctx_code_mode::CodeMode::default(),
+        ));
+
+        let res = app.oneshot(initialize("/mcp")).await.unwrap();
+        assert!(res.status().is_success(), "{}", res.status());
+        assert!(res.headers().contains_key("x-request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_profile_routes() {
+        let server = PctxMcpServer::new("127.0.0.1", 0, false).with_profile(
+            "support",
+            Config::default(),
+            pctx_code_mode::CodeMode::default(),
+        );
+        let app = server.router(&Config::default(), pctx_code_mode::CodeMode::default());
+
+        for path in ["/mcp", "/mcp/support"] {
+            let res = app.clone().oneshot(initialize(path)).await.unwrap();
+            assert!(res.status().is_success(), "{path}: {}", res.status());
+        }
+    }
+
     #[test]
     fn test_server_construction() {
         let server = PctxMcpServer::new("127.0.0.1", 8080, true);
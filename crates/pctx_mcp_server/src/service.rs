@@ -1,22 +1,39 @@
+use std::sync::Arc;
+
 use pctx_code_mode::{
-    CodeMode,
+    ArtifactStore, CodeMode, ResultStore, execute_blocking,
     model::{
-        ExecuteInput, ExecuteOutput, GetFunctionDetailsInput, GetFunctionDetailsOutput,
-        ListFunctionsOutput,
+        CheckCodeInput, CheckCodeOutput, ExecuteInput, ExecuteOutput, ExecutePriority,
+        GetFunctionDetailsInput, GetFunctionDetailsOutput, ListFunctionsInput, ListFunctionsOutput,
+        Script, SearchFunctionsInput, SearchFunctionsOutput,
     },
 };
 use rmcp::{
     RoleServer, ServerHandler,
-    handler::server::{router::tool::ToolRouter, tool::ToolCallContext, wrapper::Parameters},
+    handler::server::{
+        router::tool::ToolRouter,
+        tool::{ToolCallContext, cached_schema_for_type},
+        wrapper::Parameters,
+    },
     model::{
-        CallToolRequestParams, CallToolResult, Content, Implementation, ListToolsResult,
-        PaginatedRequestParams, ProtocolVersion, ServerCapabilities, ServerInfo,
+        CallToolRequestParams, CallToolResult, Content, Implementation, JsonObject,
+        ListToolsResult, PaginatedRequestParams, ProtocolVersion, ServerCapabilities, ServerInfo,
+        Tool,
     },
     service::RequestContext,
     tool, tool_router,
 };
 use serde_json::json;
-use tracing::{error, info, instrument};
+use tracing::{Instrument, error, info, instrument, warn};
+
+use crate::{
+    elicitation::PeerElicitation,
+    environments::Environments,
+    limiter::{ExecutionLimiter, ExecutionPermit, client_key},
+    progress::PeerProgress,
+    roots::list_roots,
+    utils::execution_span,
+};
 
 // Metrics removed - will be added via telemetry support later
 
@@ -28,18 +45,57 @@ pub(crate) struct PctxMcpService {
     version: String,
     description: Option<String>,
     code_mode: CodeMode,
+    environments: Environments,
+    // artifacts & offloaded outputs of the MCP session, if the service serves a single session
+    artifacts: Option<ArtifactStore>,
+    results: Option<ResultStore>,
+    // shared by all sessions, so executions are limited per server
+    limiter: ExecutionLimiter,
     tool_router: ToolRouter<PctxMcpService>,
 }
 
 #[tool_router]
 impl PctxMcpService {
-    pub(crate) fn new(cfg: &pctx_config::Config, code_mode: CodeMode) -> Self {
+    pub(crate) fn new(
+        cfg: &pctx_config::Config,
+        environments: Environments,
+        limiter: ExecutionLimiter,
+    ) -> Self {
+        let mut tool_router = Self::tool_router();
+        if !cfg.check_code_tool {
+            tool_router.remove_route("check_code");
+        }
+        for script in environments.default_code_mode().scripts() {
+            if tool_router.has_route(&script.name) {
+                warn!(
+                    "Script `{}` is not exposed, its name is taken by a built-in tool",
+                    script.name
+                );
+            }
+        }
+
         Self {
             name: cfg.name.clone(),
             version: cfg.version.clone(),
             description: cfg.description.clone(),
-            code_mode,
-            tool_router: Self::tool_router(),
+            code_mode: environments.default_code_mode().clone(),
+            environments,
+            artifacts: None,
+            results: None,
+            limiter,
+            tool_router,
+        }
+    }
+
+    /// Service of a new MCP session, keeping the artifacts & offloaded outputs of the
+    /// session's executions apart from other sessions
+    pub(crate) fn for_session(&self) -> Self {
+        Self {
+            artifacts: Some(ArtifactStore::new(
+                self.code_mode.artifacts().config().clone(),
+            )),
+            results: Some(ResultStore::new(self.code_mode.results().config().clone())),
+            ..self.clone()
         }
     }
 
@@ -52,17 +108,43 @@ impl PctxMcpService {
         2. Then call get_function_details() for specific functions you need to understand
         3. Finally call execute() to run your TypeScript code
 
-        This returns function signatures without full details.",
+        This returns function signatures without full details. Pass detail_level 'name' to only list the function names,
+        and budget_tokens to limit the size of the returned code.",
         output_schema = rmcp::handler::server::tool::schema_for_type::<ListFunctionsOutput>()
     )]
-    async fn list_functions(&self) -> McpResult<CallToolResult> {
-        let listed = self.code_mode.list_functions();
+    async fn list_functions(
+        &self,
+        Parameters(input): Parameters<ListFunctionsInput>,
+    ) -> McpResult<CallToolResult> {
+        let listed = self.code_mode.list_functions_with(&input);
         let mut res = CallToolResult::success(vec![Content::text(&listed.code)]);
         res.structured_content = Some(json!(listed));
 
         Ok(res)
     }
 
+    #[tool(
+        title = "Search Functions",
+        description = "Search for functions relevant to a task by keywords instead of listing every function.
+
+        WHEN TO USE: Prefer this over list_functions() when many functions are available. Describe what you
+        want to do (e.g. 'account balance' or 'create issue') and the most relevant functions are returned
+        with their signatures, ordered by relevance.
+
+        Then call get_function_details() for the functions you plan to use.",
+        output_schema = rmcp::handler::server::tool::schema_for_type::<SearchFunctionsOutput>()
+    )]
+    async fn search_functions(
+        &self,
+        Parameters(input): Parameters<SearchFunctionsInput>,
+    ) -> McpResult<CallToolResult> {
+        let found = self.code_mode.search_functions(input);
+        let mut res = CallToolResult::success(vec![Content::text(&found.code)]);
+        res.structured_content = Some(json!(found));
+
+        Ok(res)
+    }
+
     #[tool(
         title = "Get Function Details",
         description = "Get detailed information about specific functions you want to use.
@@ -72,7 +154,8 @@ impl PctxMcpService {
         REQUIRED FORMAT: Functions must be specified as 'namespace.functionName' (e.g., 'Namespace.apiPostSearch')
 
         This tool is lightweight and only returns details for the functions you request, avoiding unnecessary token usage.
-        Only request details for functions you actually plan to use in your code.
+        Only request details for functions you actually plan to use in your code. Pass detail_level 'signature' to leave out
+        the type definitions.
 
         NOTE ON RETURN TYPES:
         - If a function returns Promise<any>, the MCP server didn't provide an output schema
@@ -91,6 +174,36 @@ impl PctxMcpService {
         Ok(res)
     }
 
+    #[tool(
+        title = "Check Code",
+        description = "Type check TypeScript code without executing it. Accepts the same code as execute().
+
+        WHEN TO USE: Before execute() to catch wrong function names, missing parameters or type mismatches
+        without running any function calls. Fix the reported diagnostics and check again until it succeeds.",
+        output_schema = rmcp::handler::server::tool::schema_for_type::<CheckCodeOutput>()
+    )]
+    async fn check_code(
+        &self,
+        Parameters(input): Parameters<CheckCodeInput>,
+    ) -> McpResult<CallToolResult> {
+        let code_mode = self.code_mode.clone();
+        let code = input.code;
+
+        // type checking runs in Deno, requiring a current-thread runtime like execute
+        let check_output =
+            pctx_code_mode::run_blocking(move || async move { code_mode.check(&code).await })
+                .await
+                .map_err(|e| {
+                    error!("Type check error: {e}");
+                    rmcp::ErrorData::internal_error(format!("Type check failed: {e}"), None)
+                })?;
+
+        let mut res = CallToolResult::success(vec![Content::text(check_output.markdown())]);
+        res.structured_content = Some(json!(check_output));
+
+        Ok(res)
+    }
+
     #[tool(
         title = "Execute Code",
         description = "Execute TypeScript code that calls namespaced functions. USE THIS LAST after list_functions() and get_function_details().
@@ -101,6 +214,7 @@ impl PctxMcpService {
         - Only return specific fields you need (e.g., return {id: result.id, count: items.length})
         - Use console.log() for intermediate results instead of returning everything
         - Avoid returning full API responses - extract just what you need
+        - Pass a JSONPath `projection` (e.g. '$.items[0:10].title') to return only parts of the value
 
         REQUIRED CODE STRUCTURE:
         async function run() {
@@ -113,6 +227,7 @@ impl PctxMcpService {
         IMPORTANT RULES:
         - Functions MUST be called as 'Namespace.functionName' (e.g., 'Notion.apiPostSearch')
         - Only functions from list_functions() are available - no fetch(), fs, or other Node/Deno APIs
+        - Helpers in pctx.std (sleep, retry, chunk, base64, csv, json, date) are available without imports
         - Variables don't persist between execute() calls - return or log anything you need later
         - Add console.log() statements between API calls to track progress if errors occur
         - Code runs in an isolated Deno sandbox with restricted network access
@@ -129,39 +244,117 @@ impl PctxMcpService {
     async fn execute(
         &self,
         Parameters(input): Parameters<ExecuteInput>,
+        ctx: RequestContext<RoleServer>,
     ) -> McpResult<CallToolResult> {
-        // Capture current tracing context to propagate to spawned thread
-        let current_span = tracing::Span::current();
+        let _permit = self.acquire_slot(&ctx, input.priority).await?;
+        let session_id = session_id(&ctx).map(String::from);
+        let mut code_mode = self.request_code_mode(&input, ctx).await?;
+        let span = execution_span(&mut code_mode, session_id.as_deref());
+        let execution_output = execute_blocking(code_mode, input, None)
+            .instrument(span)
+            .await
+            .map_err(|e| execution_error(&e))?;
 
-        let code_mode = self.code_mode.clone();
-        let code = input.code;
+        let mut res = CallToolResult::success(vec![Content::text(execution_output.markdown())]);
+        res.structured_content = Some(json!(execution_output));
 
-        let execution_output = tokio::task::spawn_blocking(move || -> Result<_, anyhow::Error> {
-            // Enter the captured span context in the new thread
-            let _guard = current_span.enter();
-
-            // Create a new current-thread runtime for Deno ops that use deno_unsync
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to create runtime: {e}"))?;
-
-            rt.block_on(async {
-                code_mode
-                    .execute(&code, None)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Execution error: {e}"))
-            })
+        Ok(res)
+    }
+
+    /// Waits for a free execution slot of the client, identified by its MCP session or
+    /// address (see [`client_key`])
+    async fn acquire_slot(
+        &self,
+        ctx: &RequestContext<RoleServer>,
+        priority: ExecutePriority,
+    ) -> McpResult<Option<ExecutionPermit>> {
+        let extensions = ctx
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .map(|parts| &parts.extensions);
+        let client = client_key(session_id(ctx), extensions);
+        self.limiter.acquire(&client, priority).await.map_err(|e| {
+            warn!("Rejecting execution: {e}");
+            e.to_error_data()
         })
+    }
+
+    /// Code mode executing the code of a request: the environment selected by the input
+    /// or the request, with the session's stores & handlers reaching the client
+    async fn request_code_mode(
+        &self,
+        input: &ExecuteInput,
+        ctx: RequestContext<RoleServer>,
+    ) -> McpResult<CodeMode> {
+        // the profile & ephemeral servers of the input or the request select the environment
+        let headers = ctx
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .map(|parts| &parts.headers);
+        let mut code_mode = self
+            .environments
+            .resolve(input, headers)
+            .await
+            .map_err(|e| rmcp::ErrorData::invalid_params(e, None))?;
+        if let Some(artifacts) = &self.artifacts {
+            code_mode.set_artifacts(artifacts.clone());
+        }
+        if let Some(results) = &self.results {
+            code_mode.set_results(results.clone());
+        }
+
+        // stdio servers of other sessions must not send their requests to this client
+        if code_mode.client_handlers().owner.is_none() {
+            code_mode.set_handlers_owner(session_id(&ctx).map(String::from));
+        }
+
+        // upstream servers prompting for input mid-operation ask our client,
+        // unless the host handles elicitation itself
+        if code_mode.client_handlers().elicitation.is_none()
+            && let Some(handler) = PeerElicitation::for_peer(ctx.peer.clone())
+        {
+            code_mode.set_elicitation_handler(Some(Arc::new(handler)));
+        }
+
+        // workspace roots of the client scope filesystem-oriented upstream servers & callbacks
+        if code_mode.client_handlers().roots.is_none()
+            && let Some(roots) = list_roots(&ctx.peer).await
+        {
+            code_mode.set_roots(Some(roots));
+        }
+
+        // progress of long-running upstream calls is reported if the client asked for it
+        if code_mode.client_handlers().progress.is_none()
+            && let Some(token) = ctx.meta.get_progress_token()
+        {
+            code_mode.set_progress_handler(Some(Arc::new(PeerProgress::new(ctx.peer, token))));
+        }
+
+        Ok(code_mode)
+    }
+
+    /// Executes the saved script called as an MCP tool, the arguments are its input
+    async fn call_script(
+        &self,
+        name: String,
+        arguments: Option<JsonObject>,
+        ctx: RequestContext<RoleServer>,
+    ) -> McpResult<CallToolResult> {
+        let _permit = self
+            .acquire_slot(&ctx, ExecutePriority::Interactive)
+            .await?;
+        let session_id = session_id(&ctx).map(String::from);
+        let mut code_mode = self
+            .request_code_mode(&ExecuteInput::default(), ctx)
+            .await?;
+        let span = execution_span(&mut code_mode, session_id.as_deref());
+        let input = arguments.map(serde_json::Value::Object);
+        let execution_output = pctx_code_mode::run_blocking(move || async move {
+            code_mode.execute_script(&name, input, None).await
+        })
+        .instrument(span)
         .await
-        .map_err(|e| {
-            error!("Task join failed: {e}");
-            rmcp::ErrorData::internal_error(format!("Task join failed: {e}"), None)
-        })?
-        .map_err(|e| {
-            error!("Sandbox execution error: {e}");
-            rmcp::ErrorData::internal_error(format!("Execution failed: {e}"), None)
-        })?;
+        .map_err(|e| execution_error(&e))?;
 
         let mut res = CallToolResult::success(vec![Content::text(execution_output.markdown())]);
         res.structured_content = Some(json!(execution_output));
@@ -170,6 +363,38 @@ impl PctxMcpService {
     }
 }
 
+/// MCP session of the request, if the server is stateful
+fn session_id(ctx: &RequestContext<RoleServer>) -> Option<&str> {
+    ctx.extensions
+        .get::<axum::http::request::Parts>()
+        .and_then(|parts| parts.headers.get("mcp-session-id"))
+        .and_then(|v| v.to_str().ok())
+}
+
+/// Internal error of a failed execution, logged with its cause
+fn execution_error(e: &pctx_code_mode::Error) -> rmcp::ErrorData {
+    error!("Sandbox execution error: {e}");
+    rmcp::ErrorData::internal_error(format!("Execution failed: {e}"), None)
+}
+
+/// MCP tool of a saved script, any object is accepted as its input if it has no schema
+fn script_tool(script: &Script) -> Tool {
+    let input_schema = match &script.input_schema {
+        Some(serde_json::Value::Object(schema)) => schema.clone(),
+        _ => [("type".to_string(), json!("object"))]
+            .into_iter()
+            .collect(),
+    };
+    let description = script
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("Runs the saved script `{}`", script.name));
+
+    let mut tool = Tool::new(script.name.clone(), description, Arc::new(input_schema));
+    tool.output_schema = Some(cached_schema_for_type::<ExecuteOutput>());
+    tool
+}
+
 impl ServerHandler for PctxMcpService {
     fn get_info(&self) -> ServerInfo {
         let default_description = format!(
@@ -202,7 +427,15 @@ impl ServerHandler for PctxMcpService {
         ctx: RequestContext<RoleServer>,
     ) -> McpResult<ListToolsResult> {
         let start = std::time::Instant::now();
-        let res = ListToolsResult::with_all_items(self.tool_router.list_all());
+        let mut tools = self.tool_router.list_all();
+        tools.extend(
+            self.code_mode
+                .scripts()
+                .iter()
+                .filter(|s| !self.tool_router.has_route(&s.name))
+                .map(script_tool),
+        );
+        let res = ListToolsResult::with_all_items(tools);
         let latency = start.elapsed();
         info!(
             tools.length = res.tools.len(),
@@ -226,8 +459,16 @@ impl ServerHandler for PctxMcpService {
         let start = std::time::Instant::now();
         let tool_name = req.name.clone();
 
-        let tcc = ToolCallContext::new(self, req, ctx);
-        let res = self.tool_router.call(tcc).await;
+        // saved scripts are tools of their own, built-in tools take precedence
+        let is_script = !self.tool_router.has_route(&req.name)
+            && self.code_mode.scripts().iter().any(|s| s.name == req.name);
+        let res = if is_script {
+            self.call_script(req.name.to_string(), req.arguments, ctx)
+                .await
+        } else {
+            let tcc = ToolCallContext::new(self, req, ctx);
+            self.tool_router.call(tcc).await
+        };
 
         let latency = start.elapsed();
         let is_error = res
@@ -250,3 +491,37 @@ impl ServerHandler for PctxMcpService {
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pctx_code_mode::model::Script;
+    use serde_json::json;
+
+    use super::script_tool;
+
+    #[test]
+    fn test_script_tool() {
+        let mut script = Script {
+            name: "open_tickets".into(),
+            description: None,
+            code: "async function run(input: { team: string }) { return []; }".into(),
+            input_schema: Some(json!({
+                "type": "object",
+                "properties": { "team": { "type": "string" } },
+                "required": ["team"]
+            })),
+        };
+
+        let tool = script_tool(&script);
+        assert_eq!(tool.name, "open_tickets");
+        assert_eq!(
+            tool.description.as_deref(),
+            Some("Runs the saved script `open_tickets`")
+        );
+        assert_eq!(tool.input_schema["required"], json!(["team"]));
+        assert!(tool.output_schema.is_some());
+
+        script.input_schema = None;
+        assert_eq!(script_tool(&script).input_schema["type"], json!("object"));
+    }
+}
@@ -1,3 +1,70 @@
 pub(crate) mod styles;
 
 pub(crate) static LOGO: &str = include_str!("../../../../assets/ascii-logo.txt");
+
+/// Span of an execution logging its ids. Ids the host set are kept, the execution is
+/// assigned a new id and the `session_id` otherwise
+pub(crate) fn execution_span(
+    code_mode: &mut pctx_code_mode::CodeMode,
+    session_id: Option<&str>,
+) -> tracing::Span {
+    let ids = code_mode.execution_ids();
+    let ids = pctx_code_mode::ExecutionIds {
+        execution_id: ids
+            .execution_id
+            .clone()
+            .or_else(|| Some(uuid::Uuid::new_v4().to_string())),
+        session_id: ids
+            .session_id
+            .clone()
+            .or_else(|| session_id.map(String::from)),
+    };
+    let span = tracing::info_span!(
+        "execution",
+        execution_id = ids.execution_id.as_deref(),
+        session_id = ids.session_id.as_deref(),
+    );
+    code_mode.set_execution_ids(ids);
+    span
+}
+
+#[cfg(test)]
+mod tests {
+    use pctx_code_mode::{CodeMode, ExecutionIds};
+
+    use super::execution_span;
+
+    #[test]
+    fn test_execution_span_merges_ids() {
+        let mut code_mode = CodeMode::default();
+        execution_span(&mut code_mode, Some("session"));
+        let ids = code_mode.execution_ids();
+        assert!(ids.execution_id.is_some());
+        assert_eq!(ids.session_id.as_deref(), Some("session"));
+
+        // the session is kept when the host only set the execution id
+        code_mode.set_execution_ids(ExecutionIds {
+            execution_id: Some("host".into()),
+            session_id: None,
+        });
+        execution_span(&mut code_mode, Some("session"));
+        assert_eq!(
+            code_mode.execution_ids(),
+            &ExecutionIds {
+                execution_id: Some("host".into()),
+                session_id: Some("session".into()),
+            }
+        );
+
+        // ids the host set win
+        code_mode.set_execution_ids(ExecutionIds {
+            execution_id: Some("host".into()),
+            session_id: Some("host-session".into()),
+        });
+        execution_span(&mut code_mode, Some("session"));
+        assert_eq!(
+            code_mode.execution_ids().session_id.as_deref(),
+            Some("host-session")
+        );
+    }
+}
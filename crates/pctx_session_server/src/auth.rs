@@ -0,0 +1,403 @@
+//! Token authentication of the session server's REST API and WebSocket upgrades
+//!
+//! Clients send a token as `Authorization: Bearer <token>` or in the `x-pctx-api-key`
+//! header. A token is accepted if it is one of the static tokens, or a JWT signed with
+//! the configured HS256 secret with an unexpired `exp` claim and valid `nbf`, `iss` and
+//! `aud` claims. Tokens identify a [`Tenant`] if they are tenant tokens or JWTs with the
+//! tenant claim, any other request belongs to the default tenant. If a tenant claim is
+//! configured, JWTs without it are rejected.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{
+    Json,
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use pctx_config::auth::token_accepted;
+use ring::hmac;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::{
+    AppState, PctxSessionBackend,
+    model::{ErrorCode, ErrorData},
+    state::tenants::Tenant,
+};
+
+pub static API_KEY_HEADER: &str = "x-pctx-api-key";
+
+/// Accepted tokens, requests are not authenticated if neither static tokens nor a
+/// JWT secret are configured
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    tokens: Vec<(String, Tenant)>,
+    jwt: Option<JwtConfig>,
+}
+
+/// Validation of HS256 signed JWTs
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    secret: Vec<u8>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    tenant_claim: Option<String>,
+}
+
+impl JwtConfig {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into(),
+            issuer: None,
+            audience: None,
+            tenant_claim: None,
+        }
+    }
+
+    /// Require the `iss` claim to equal `issuer`
+    #[must_use]
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require the `aud` claim to be or contain `audience`
+    #[must_use]
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Identify the tenant by the string value of `claim` (e.g. `sub` or `tenant_id`),
+    /// JWTs without the claim are rejected
+    #[must_use]
+    pub fn with_tenant_claim(mut self, claim: impl Into<String>) -> Self {
+        self.tenant_claim = Some(claim.into());
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("Missing token")]
+    Missing,
+    #[error("Invalid token")]
+    Invalid,
+    #[error("Token expired")]
+    Expired,
+    #[error("Token has no expiration")]
+    NoExpiration,
+    #[error("Token not valid yet")]
+    NotYetValid,
+    #[error("Invalid token issuer")]
+    Issuer,
+    #[error("Invalid token audience")]
+    Audience,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    exp: Option<u64>,
+    nbf: Option<u64>,
+    iss: Option<String>,
+    aud: Option<Value>,
+    #[serde(flatten)]
+    other: serde_json::Map<String, Value>,
+}
+
+impl AuthConfig {
+    /// Accept any of the static tokens
+    #[must_use]
+    pub fn with_tokens(mut self, tokens: Vec<String>) -> Self {
+        self.tokens
+            .extend(tokens.into_iter().map(|t| (t, Tenant::default())));
+        self
+    }
+
+    /// Accept a static token identifying `tenant`
+    #[must_use]
+    pub fn with_tenant_token(
+        mut self,
+        tenant: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        self.tokens.push((token.into(), Tenant(tenant.into())));
+        self
+    }
+
+    /// Accept JWTs passing the validation
+    #[must_use]
+    pub fn with_jwt(mut self, jwt: JwtConfig) -> Self {
+        self.jwt = Some(jwt);
+        self
+    }
+
+    /// Returns `true` if requests must be authenticated
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty() || self.jwt.is_some()
+    }
+
+    /// Validates the token sent in the request headers, returning the tenant it identifies
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if authentication is enabled and the token is missing or not accepted
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Tenant, AuthError> {
+        if !self.is_enabled() {
+            return Ok(Tenant::default());
+        }
+
+        let token = request_token(headers).ok_or(AuthError::Missing)?;
+        if let Some((_, tenant)) = self
+            .tokens
+            .iter()
+            .find(|(t, _)| token_accepted([t.as_str()], token))
+        {
+            return Ok(tenant.clone());
+        }
+
+        match &self.jwt {
+            Some(jwt) => validate_jwt(jwt, token),
+            None => Err(AuthError::Invalid),
+        }
+    }
+}
+
+/// Middleware rejecting unauthenticated requests with `401 Unauthorized`, adding the
+/// [`Tenant`] of authenticated requests to their extensions
+pub(crate) async fn require_auth<B: PctxSessionBackend>(
+    State(state): State<AppState<B>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match state.auth.authenticate(request.headers()) {
+        Ok(tenant) => {
+            request.extensions_mut().insert(tenant);
+        }
+        Err(e) => {
+            warn!(uri = %request.uri(), "Rejecting unauthenticated request: {e}");
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ErrorData {
+                    code: ErrorCode::Unauthorized,
+                    message: e.to_string(),
+                    details: None,
+                }),
+            )
+                .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+fn request_token(headers: &HeaderMap) -> Option<&str> {
+    if let Some(bearer) = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(bearer.trim());
+    }
+
+    headers
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+}
+
+fn validate_jwt(jwt: &JwtConfig, token: &str) -> Result<Tenant, AuthError> {
+    let (signed, signature) = token.rsplit_once('.').ok_or(AuthError::Invalid)?;
+    let (header, payload) = signed.split_once('.').ok_or(AuthError::Invalid)?;
+
+    let header: JwtHeader = decode_part(header)?;
+    if header.alg != "HS256" {
+        return Err(AuthError::Invalid);
+    }
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| AuthError::Invalid)?;
+    let key = hmac::Key::new(hmac::HMAC_SHA256, &jwt.secret);
+    hmac::verify(&key, signed.as_bytes(), &signature).map_err(|_| AuthError::Invalid)?;
+
+    let claims: Claims = decode_part(payload)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    match claims.exp {
+        None => return Err(AuthError::NoExpiration),
+        Some(exp) if exp <= now => return Err(AuthError::Expired),
+        Some(_) => {}
+    }
+    if claims.nbf.is_some_and(|nbf| nbf > now) {
+        return Err(AuthError::NotYetValid);
+    }
+    if let Some(issuer) = &jwt.issuer
+        && claims.iss.as_ref() != Some(issuer)
+    {
+        return Err(AuthError::Issuer);
+    }
+    if let Some(audience) = &jwt.audience {
+        let matches = match &claims.aud {
+            Some(Value::String(aud)) => aud == audience,
+            Some(Value::Array(auds)) => auds.iter().any(|a| a.as_str() == Some(audience)),
+            _ => false,
+        };
+        if !matches {
+            return Err(AuthError::Audience);
+        }
+    }
+
+    match &jwt.tenant_claim {
+        Some(claim) => claims
+            .other
+            .get(claim)
+            .and_then(Value::as_str)
+            .map(|t| Tenant(t.to_string()))
+            .ok_or(AuthError::Invalid),
+        None => Ok(Tenant::default()),
+    }
+}
+
+fn decode_part<T: serde::de::DeserializeOwned>(part: &str) -> Result<T, AuthError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| AuthError::Invalid)?;
+    serde_json::from_slice(&bytes).map_err(|_| AuthError::Invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue};
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ring::hmac;
+    use serde_json::json;
+
+    use super::{AuthConfig, AuthError, JwtConfig};
+    use crate::state::tenants::Tenant;
+
+    fn sign(secret: &[u8], claims: &serde_json::Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({"alg": "HS256", "typ": "JWT"}).to_string());
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+        let signature = hmac::sign(&key, format!("{header}.{payload}").as_bytes());
+        format!(
+            "{header}.{payload}.{}",
+            URL_SAFE_NO_PAD.encode(signature.as_ref())
+        )
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_static_tokens() {
+        assert_eq!(
+            AuthConfig::default().authenticate(&HeaderMap::new()),
+            Ok(Tenant::default())
+        );
+
+        let auth = AuthConfig::default().with_tokens(vec!["secret".into()]);
+        assert_eq!(
+            auth.authenticate(&HeaderMap::new()),
+            Err(AuthError::Missing)
+        );
+        assert_eq!(auth.authenticate(&bearer("other")), Err(AuthError::Invalid));
+        assert_eq!(auth.authenticate(&bearer("secret")), Ok(Tenant::default()));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-pctx-api-key", HeaderValue::from_static("secret"));
+        assert_eq!(auth.authenticate(&headers), Ok(Tenant::default()));
+
+        let auth = auth.with_tenant_token("acme", "acme-secret");
+        assert_eq!(
+            auth.authenticate(&bearer("acme-secret")),
+            Ok(Tenant("acme".into()))
+        );
+    }
+
+    #[test]
+    fn test_jwt() {
+        let auth = AuthConfig::default().with_jwt(
+            JwtConfig::new("jwt-secret")
+                .with_issuer("https://auth.example.com")
+                .with_audience("pctx")
+                .with_tenant_claim("org"),
+        );
+        let valid = json!({
+            "iss": "https://auth.example.com",
+            "aud": ["pctx"],
+            "exp": 4_102_444_800_u64,
+            "org": "acme"
+        });
+
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(b"jwt-secret", &valid))),
+            Ok(Tenant("acme".into()))
+        );
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(b"wrong-secret", &valid))),
+            Err(AuthError::Invalid)
+        );
+
+        let mut expired = valid.clone();
+        expired["exp"] = json!(1);
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(b"jwt-secret", &expired))),
+            Err(AuthError::Expired)
+        );
+
+        let mut no_expiration = valid.clone();
+        no_expiration.as_object_mut().unwrap().remove("exp");
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(b"jwt-secret", &no_expiration))),
+            Err(AuthError::NoExpiration)
+        );
+
+        let mut no_tenant = valid.clone();
+        no_tenant.as_object_mut().unwrap().remove("org");
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(b"jwt-secret", &no_tenant))),
+            Err(AuthError::Invalid)
+        );
+
+        let mut other_issuer = valid.clone();
+        other_issuer["iss"] = json!("https://evil.example.com");
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(b"jwt-secret", &other_issuer))),
+            Err(AuthError::Issuer)
+        );
+
+        let mut other_audience = valid;
+        other_audience["aud"] = json!("other");
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(b"jwt-secret", &other_audience))),
+            Err(AuthError::Audience)
+        );
+
+        // without a tenant claim JWTs belong to the default tenant
+        let auth = AuthConfig::default().with_jwt(JwtConfig::new("jwt-secret"));
+        assert_eq!(
+            auth.authenticate(&bearer(&sign(
+                b"jwt-secret",
+                &json!({"exp": 4_102_444_800_u64, "org": "acme"})
+            ))),
+            Ok(Tenant::default())
+        );
+    }
+}
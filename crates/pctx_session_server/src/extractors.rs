@@ -1,4 +1,8 @@
-use crate::model::{ErrorCode, ErrorData};
+use crate::{
+    AppState, PctxSessionBackend,
+    model::{ErrorCode, ErrorData},
+    state::tenants::Tenant,
+};
 use axum::{
     Json,
     extract::FromRequestParts,
@@ -13,15 +17,17 @@ pub static CODE_MODE_SESSION_HEADER: &str = "x-code-mode-session";
 ///
 /// This extractor will parse the `x-code-mode-session` header value as a UUID.
 /// If the header is missing or invalid, it will return a 400 Bad Request error.
+/// Unknown sessions and sessions of other tenants are rejected with a 404 Not Found
+/// error.
 pub struct CodeModeSession(pub Uuid);
 
-impl<S> FromRequestParts<S> for CodeModeSession
-where
-    S: Send + Sync,
-{
+impl<B: PctxSessionBackend> FromRequestParts<AppState<B>> for CodeModeSession {
     type Rejection = (StatusCode, Json<ErrorData>);
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState<B>,
+    ) -> Result<Self, Self::Rejection> {
         // Get the header value
         let header_key = "x-code-mode-session";
         let header_value = parts.headers.get(header_key).ok_or_else(|| {
@@ -59,6 +65,32 @@ where
             )
         })?;
 
+        let tenant = parts
+            .extensions
+            .get::<Tenant>()
+            .cloned()
+            .unwrap_or_default();
+        let can_access = state.can_access(session_id, &tenant).await.map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorData {
+                    code: ErrorCode::Internal,
+                    message: format!("Failed getting code mode session owner: {e}"),
+                    details: None,
+                }),
+            )
+        })?;
+        if !can_access {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorData {
+                    code: ErrorCode::InvalidSession,
+                    message: format!("Code mode session {session_id} does not exist"),
+                    details: None,
+                }),
+            ));
+        }
+
         Ok(CodeModeSession(session_id))
     }
 }
@@ -1,3 +1,4 @@
+pub mod auth;
 pub mod extractors;
 pub mod model;
 mod routes;
@@ -5,10 +6,17 @@ pub mod server;
 // mod session;
 mod state;
 pub mod websocket;
+pub mod worker;
 
 pub use extractors::CODE_MODE_SESSION_HEADER;
 pub use server::start_server;
 pub use state::{
-    AppState,
-    backend::{LocalBackend, PctxSessionBackend},
+    AppState, CreateSessionError, SessionLimits,
+    backend::{LocalBackend, PctxSessionBackend, RedisBackend, SqliteBackend},
+    execution_queue::{
+        Admission, ExecutionQueue, ExecutionQueueConfig, QueueFull, QueuedExecution,
+    },
+    session_locks::SessionLocks,
+    tenants::{DEFAULT_TENANT, Tenant, TenantError, TenantLimits, TenantManager},
+    ws_manager::{HeartbeatConfig, WsManager, WsSession},
 };
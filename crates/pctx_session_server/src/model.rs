@@ -70,6 +70,8 @@ pub enum ErrorCode {
     InvalidSession,
     Internal,
     Execution,
+    Unauthorized,
+    LimitExceeded,
 }
 
 /// Request to register tools
@@ -112,7 +114,8 @@ pub struct CloseSessionResponse {
 
 // ----------- Websocket JRPC Message structs -----------
 
-pub type WsJsonRpcMessage = rmcp::model::JsonRpcMessage<PctxJsonRpcRequest, PctxJsonRpcResponse>;
+pub type WsJsonRpcMessage =
+    rmcp::model::JsonRpcMessage<PctxJsonRpcRequest, PctxJsonRpcResponse, PctxJsonRpcNotification>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "method")]
@@ -133,6 +136,18 @@ pub struct ExecuteToolParams {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecuteCodeParams {
     pub code: String,
+    /// JSON value passed as the argument of `run(input)`
+    #[serde(default)]
+    pub input: Option<serde_json::Value>,
+    /// Timeout in seconds of callbacks during this execution, overriding the session's
+    #[serde(default)]
+    pub callback_timeout_secs: Option<f64>,
+    /// Timeout in seconds of the execution, pending tool calls are cancelled once it passes
+    #[serde(default)]
+    pub timeout_secs: Option<f64>,
+    /// Execute the code without type checking it
+    #[serde(default)]
+    pub skip_type_check: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -146,3 +161,54 @@ pub enum PctxJsonRpcResponse {
 pub struct ExecuteToolResult {
     pub output: Option<serde_json::Value>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method")]
+pub enum PctxJsonRpcNotification {
+    #[serde(rename = "progress")]
+    Progress { params: ProgressParams },
+    /// An `execute_code` request waits for a free execution slot
+    #[serde(rename = "queued")]
+    Queued { params: QueuedParams },
+    /// Event pushed by the server, see [`crate::websocket::event`]
+    #[serde(rename = "event")]
+    Event { params: EventParams },
+    /// Subscribes the client to event topics
+    #[serde(rename = "subscribe")]
+    Subscribe { params: SubscribeParams },
+    #[serde(rename = "unsubscribe")]
+    Unsubscribe { params: SubscribeParams },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedParams {
+    /// ID of the `execute_code` request
+    pub request_id: rmcp::model::RequestId,
+    /// 1-based position in the execution queue
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventParams {
+    pub topic: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscribeParams {
+    pub topics: Vec<String>,
+}
+
+/// Progress an upstream MCP server reported during an `execute_code` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressParams {
+    /// ID of the `execute_code` request
+    pub request_id: rmcp::model::RequestId,
+    pub server: String,
+    pub progress: f64,
+    #[serde(default)]
+    pub total: Option<f64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
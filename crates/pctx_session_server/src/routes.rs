@@ -1,14 +1,11 @@
 use anyhow::Context;
-use axum::{Json, extract::State, http::StatusCode};
+use axum::{Extension, Json, extract::State, http::StatusCode};
 
-use pctx_code_mode::{
-    CodeMode,
-    model::{
-        CallbackConfig, GetFunctionDetailsInput, GetFunctionDetailsOutput, ListFunctionsOutput,
-    },
+use pctx_code_mode::model::{
+    CallbackConfig, GetFunctionDetailsInput, GetFunctionDetailsOutput, ListFunctionsOutput,
 };
+use serde_json::json;
 use tracing::info;
-use uuid::Uuid;
 
 use crate::extractors::CodeModeSession;
 use crate::model::{
@@ -16,7 +13,8 @@ use crate::model::{
     HealthResponse, RegisterMcpServersRequest, RegisterMcpServersResponse, RegisterToolsRequest,
     RegisterToolsResponse,
 };
-use crate::state::{AppState, backend::PctxSessionBackend};
+use crate::state::{AppState, CreateSessionError, backend::PctxSessionBackend, tenants::Tenant};
+use crate::websocket::TOOLS_CHANGED_TOPIC;
 
 /// Health check endpoint
 #[utoipa::path(
@@ -41,24 +39,30 @@ pub(crate) async fn health() -> Json<HealthResponse> {
     tag = "CodeMode",
     responses(
         (status = 200, description = "Session created successfully", body = CreateSessionResponse),
+        (status = 429, description = "Tenant session limit exceeded", body = ErrorData),
         (status = 500, description = "Internal server error", body = ErrorData)
     )
 )]
 pub(crate) async fn create_session<B: PctxSessionBackend>(
     State(state): State<AppState<B>>,
+    tenant: Option<Extension<Tenant>>,
 ) -> ApiResult<Json<CreateSessionResponse>> {
-    let session_id = Uuid::new_v4();
-    info!(
-        session_id =? session_id,
-        "Creating new CodeMode session"
-    );
+    let tenant = tenant.map(|Extension(t)| t).unwrap_or_default();
+    info!(tenant =? tenant, "Creating new CodeMode session");
 
-    let code_mode = CodeMode::default();
-    state
-        .backend
-        .insert(session_id, code_mode)
-        .await
-        .context("Failed inserting code mode session into backend")?;
+    let session_id = state.create_session(&tenant).await.map_err(|e| match e {
+        CreateSessionError::Tenant(e) => ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            ErrorData {
+                code: ErrorCode::LimitExceeded,
+                message: e.to_string(),
+                details: None,
+            },
+        ),
+        CreateSessionError::Backend(e) => e
+            .context("Failed inserting code mode session into backend")
+            .into(),
+    })?;
 
     info!(
         session_id =? session_id,
@@ -93,6 +97,7 @@ pub(crate) async fn close_session<B: PctxSessionBackend>(
         .delete(session_id)
         .await
         .context("Failed deleting code mode session from backend")?;
+    state.session_locks.remove(session_id);
 
     if !existed {
         return Err(ApiError::new(
@@ -205,6 +210,7 @@ pub(crate) async fn get_function_details<B: PctxSessionBackend>(
     request_body = RegisterToolsRequest,
     responses(
         (status = 200, description = "Tools registered successfully", body = RegisterToolsResponse),
+        (status = 400, description = "Session tool limit exceeded", body = ErrorData),
         (status = 404, description = "Session not found", body = ErrorData),
         (status = 500, description = "Internal server error", body = ErrorData)
     )
@@ -225,6 +231,7 @@ pub(crate) async fn register_tools<B: PctxSessionBackend>(
         "Registering tools...",
     );
 
+    let _lock = state.session_locks.lock(session_id).await;
     let mut code_mode = state
         .backend
         .get(session_id)
@@ -238,6 +245,21 @@ pub(crate) async fn register_tools<B: PctxSessionBackend>(
                 details: None,
             },
         ))?;
+    if let Some(max) = state.limits.max_tools
+        && code_mode.callbacks().len() + request.tools.len() > max
+    {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            ErrorData {
+                code: ErrorCode::LimitExceeded,
+                message: format!(
+                    "Registering {} tools exceeds the limit of {max} tools per session",
+                    request.tools.len()
+                ),
+                details: None,
+            },
+        ));
+    }
     code_mode
         .add_callbacks(&request.tools)
         .context("Failed adding callbacks")?;
@@ -250,6 +272,14 @@ pub(crate) async fn register_tools<B: PctxSessionBackend>(
         tools =? &tool_ids,
         "Registered tools",
     );
+    state
+        .ws_manager
+        .notify_code_mode_session(
+            session_id,
+            TOOLS_CHANGED_TOPIC,
+            json!({ "tools": tool_ids }),
+        )
+        .await;
 
     Ok(Json(RegisterToolsResponse {
         registered: request.tools.len(),
@@ -280,6 +310,7 @@ pub(crate) async fn register_servers<B: PctxSessionBackend>(
         request.servers.len()
     );
 
+    let _lock = state.session_locks.lock(session_id).await;
     let mut code_mode = state
         .backend
         .get(session_id)
@@ -312,6 +343,19 @@ pub(crate) async fn register_servers<B: PctxSessionBackend>(
         registered =% request.servers.len(),
         "Registered MCP servers",
     );
+    let servers = request
+        .servers
+        .iter()
+        .map(|s| s.name.clone())
+        .collect::<Vec<_>>();
+    state
+        .ws_manager
+        .notify_code_mode_session(
+            session_id,
+            TOOLS_CHANGED_TOPIC,
+            json!({ "servers": servers }),
+        )
+        .await;
 
     Ok(Json(RegisterMcpServersResponse {
         registered: request.servers.len(),
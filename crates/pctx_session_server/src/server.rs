@@ -2,7 +2,7 @@
 
 use anyhow::Result;
 use axum::{
-    Router,
+    Router, middleware,
     routing::{get, post},
 };
 use opentelemetry::{global, trace::TraceContextExt};
@@ -13,14 +13,15 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
-    AppState, PctxSessionBackend,
+    AppState, PctxSessionBackend, auth,
     extractors::HeaderExtractor,
     model::{
         CloseSessionResponse, CreateSessionResponse, ErrorData, HealthResponse,
         RegisterMcpServersRequest, RegisterMcpServersResponse, RegisterToolsRequest,
         RegisterToolsResponse,
     },
-    routes, websocket,
+    routes,
+    websocket::{self, SHUTDOWN_TOPIC},
 };
 use pctx_code_mode::model::{
     CallbackConfig, FunctionDetails, GetFunctionDetailsInput, GetFunctionDetailsOutput,
@@ -84,6 +85,7 @@ pub async fn start_server<B: PctxSessionBackend>(
     port: u16,
     state: AppState<B>,
 ) -> Result<()> {
+    let ws_manager = state.ws_manager.clone();
     let app = create_router(state);
 
     let addr = format!("{host}:{port}");
@@ -96,7 +98,13 @@ pub async fn start_server<B: PctxSessionBackend>(
     info!("WebSocket endpoint at ws://{addr}/ws for tool callbacks.",);
 
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            let notified = ws_manager
+                .broadcast(websocket::event(SHUTDOWN_TOPIC, serde_json::Value::Null))
+                .await;
+            debug!("Notified {notified} WebSocket sessions of shutdown");
+        })
         .await?;
 
     Ok(())
@@ -104,9 +112,9 @@ pub async fn start_server<B: PctxSessionBackend>(
 
 /// Create the Axum router with all routes
 pub fn create_router<B: PctxSessionBackend>(state: AppState<B>) -> Router {
-    Router::new()
-        // Health check
-        .route("/health", get(routes::health))
+    // everything but health checks & docs requires a token if auth is configured,
+    // unauthenticated WebSocket upgrades are rejected before upgrading
+    let authenticated = Router::new()
         // Session management
         .route("/code-mode/session/create", post(routes::create_session))
         .route("/code-mode/session/close", post(routes::close_session))
@@ -120,6 +128,15 @@ pub fn create_router<B: PctxSessionBackend>(state: AppState<B>) -> Router {
         .route("/register/servers", post(routes::register_servers))
         // WebSocket endpoint
         .route("/ws", get(websocket::ws_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_auth::<B>,
+        ));
+
+    Router::new()
+        // Health check
+        .route("/health", get(routes::health))
+        .merge(authenticated)
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         // Add state
@@ -164,7 +181,7 @@ pub fn create_router<B: PctxSessionBackend>(state: AppState<B>) -> Router {
 }
 
 /// Graceful shutdown signal handler
-async fn shutdown_signal() {
+pub(crate) async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
             .await
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -9,18 +12,36 @@ use pctx_code_mode::{
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::state::tenants::Tenant;
+
+mod redis;
+mod sqlite;
+
+pub use self::redis::RedisBackend;
+pub use self::sqlite::SqliteBackend;
+
 #[async_trait]
 pub trait PctxSessionBackend: Clone + Send + Sync + 'static {
     /// Retrieve a `CodeMode` struct by it's session ID from the backend
     async fn get(&self, session_id: Uuid) -> Result<Option<CodeMode>>;
 
-    /// Add a new `CodeMode` struct to the backend
-    async fn insert(&self, session_id: Uuid, code_mode: CodeMode) -> Result<()>;
+    /// Add a new `CodeMode` struct owned by `owner` to the backend
+    async fn insert(&self, session_id: Uuid, code_mode: CodeMode, owner: Tenant) -> Result<()>;
 
     /// Update a `CodeMode` struct as a full replacement (PUT not PATCH)
     /// in the backend
+    ///
+    /// Callers hold the session's [lock](crate::SessionLocks) between getting and
+    /// updating the session, persistent backends additionally reject updates of sessions
+    /// another server instance updated in between.
     async fn update(&self, session_id: Uuid, code_mode: CodeMode) -> Result<()>;
 
+    /// Tenant owning the session, `None` if the session does not exist
+    async fn owner(&self, session_id: Uuid) -> Result<Option<Tenant>>;
+
+    /// Returns the number of `CodeMode` sessions owned by `tenant`
+    async fn count_owned(&self, tenant: &Tenant) -> Result<usize>;
+
     /// Deletes a `CodeMode` struct from the backend, returning the deleted
     /// instance if it exists.
     async fn delete(&self, session_id: Uuid) -> Result<bool>;
@@ -51,9 +72,14 @@ pub trait PctxSessionBackend: Clone + Send + Sync + 'static {
 /// smart references and read/write locks
 #[derive(Clone, Default)]
 pub struct LocalBackend {
-    /// Map of `session_id` -> `Arc<RwLock<CodeMode>>`
+    /// Map of `session_id` -> `LocalSession`
     /// Each `CodeMode` has its own lock for better concurrency
-    sessions: Arc<RwLock<HashMap<Uuid, Arc<RwLock<CodeMode>>>>>,
+    sessions: Arc<RwLock<HashMap<Uuid, LocalSession>>>,
+}
+
+struct LocalSession {
+    owner: Tenant,
+    code_mode: Arc<RwLock<CodeMode>>,
 }
 
 #[async_trait]
@@ -61,17 +87,17 @@ impl PctxSessionBackend for LocalBackend {
     async fn get(&self, session_id: Uuid) -> Result<Option<CodeMode>> {
         let sessions = self.sessions.read().await;
         match sessions.get(&session_id) {
-            Some(code_mode_lock) => Ok(Some(code_mode_lock.read().await.clone())),
+            Some(session) => Ok(Some(session.code_mode.read().await.clone())),
             None => Ok(None),
         }
     }
 
-    async fn insert(&self, session_id: Uuid, code_mode: CodeMode) -> Result<()> {
-        let code_mode_lock = Arc::new(RwLock::new(code_mode));
-        self.sessions
-            .write()
-            .await
-            .insert(session_id, code_mode_lock);
+    async fn insert(&self, session_id: Uuid, code_mode: CodeMode, owner: Tenant) -> Result<()> {
+        let session = LocalSession {
+            owner,
+            code_mode: Arc::new(RwLock::new(code_mode)),
+        };
+        self.sessions.write().await.insert(session_id, session);
 
         Ok(())
     }
@@ -82,11 +108,30 @@ impl PctxSessionBackend for LocalBackend {
             .get(&session_id)
             .context(format!("CodeMode session {session_id} does not exist"))?;
 
-        *to_update.write().await = code_mode;
+        *to_update.code_mode.write().await = code_mode;
 
         Ok(())
     }
 
+    async fn owner(&self, session_id: Uuid) -> Result<Option<Tenant>> {
+        Ok(self
+            .sessions
+            .read()
+            .await
+            .get(&session_id)
+            .map(|s| s.owner.clone()))
+    }
+
+    async fn count_owned(&self, tenant: &Tenant) -> Result<usize> {
+        Ok(self
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| &s.owner == tenant)
+            .count())
+    }
+
     async fn delete(&self, session_id: Uuid) -> Result<bool> {
         let deleted = self.sessions.write().await.remove(&session_id);
         Ok(deleted.is_some())
@@ -104,3 +149,44 @@ impl PctxSessionBackend for LocalBackend {
         Ok(self.sessions.read().await.keys().copied().collect())
     }
 }
+
+/// Live `CodeMode` sessions of a persistent backend in this process
+///
+/// Persistent backends only store the configuration of sessions, their runtime state
+/// (tool caches, circuit breakers, rate limiters, stdio server processes, ...) lives in
+/// the cached instance so it is kept between the requests of a session. Entries are
+/// tagged with the revision of the stored session they were loaded from, sessions
+/// another server instance updated are loaded again.
+#[derive(Clone, Default)]
+struct SessionCache {
+    sessions: Arc<Mutex<HashMap<Uuid, (u64, CodeMode)>>>,
+}
+
+impl SessionCache {
+    /// Cached session, if it was loaded from revision `rev`
+    fn get(&self, session_id: Uuid, rev: u64) -> Option<CodeMode> {
+        self.lock()
+            .get(&session_id)
+            .filter(|(cached, _)| *cached == rev)
+            .map(|(_, code_mode)| code_mode.clone())
+    }
+
+    /// Revision the cached session was loaded from
+    fn rev(&self, session_id: Uuid) -> Option<u64> {
+        self.lock().get(&session_id).map(|(rev, _)| *rev)
+    }
+
+    fn insert(&self, session_id: Uuid, rev: u64, code_mode: CodeMode) {
+        self.lock().insert(session_id, (rev, code_mode));
+    }
+
+    fn remove(&self, session_id: Uuid) {
+        self.lock().remove(&session_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, (u64, CodeMode)>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use pctx_code_mode::CodeMode;
+use redis::{AsyncCommands, aio::ConnectionManager};
+use uuid::Uuid;
+
+use super::{PctxSessionBackend, SessionCache};
+use crate::state::tenants::Tenant;
+
+/// Replaces the configuration of a session if it is still at the expected revision
+/// (`ARGV[2]`, empty to replace any revision), returning the new revision, `-1` if the
+/// session does not exist or `-2` if it was updated concurrently
+const UPDATE_SCRIPT: &str = r"
+local rev = redis.call('HGET', KEYS[1], 'rev')
+if not rev then return -1 end
+if ARGV[2] ~= '' and rev ~= ARGV[2] then return -2 end
+redis.call('HSET', KEYS[1], 'code_mode', ARGV[1])
+return redis.call('HINCRBY', KEYS[1], 'rev', 1)
+";
+
+/// Deletes a session & removes it from the session index and its owner's index,
+/// returning the number of deleted sessions
+const DELETE_SCRIPT: &str = r"
+local owner = redis.call('HGET', KEYS[1], 'owner')
+if not owner then return 0 end
+redis.call('DEL', KEYS[1])
+redis.call('SREM', KEYS[2], ARGV[1])
+redis.call('SREM', ARGV[2] .. owner .. ':sessions', ARGV[1])
+return 1
+";
+
+/// Persists `CodeMode` sessions in Redis, so they survive restarts and are shared by
+/// every server instance using the same Redis
+///
+/// Sessions are stored as their serialized configuration (servers, callbacks & tool
+/// sets) next to their owner. Process-local state like tool caches lives in the loaded
+/// sessions each instance keeps in memory, and is rebuilt when another instance
+/// updated the session.
+#[derive(Clone)]
+pub struct RedisBackend {
+    conn: ConnectionManager,
+    prefix: String,
+    cache: SessionCache,
+}
+
+impl RedisBackend {
+    /// Connects to the Redis server at `url` (e.g. `redis://localhost:6379/0`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is invalid or the server cannot be reached
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).context("Invalid Redis URL")?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .context("Failed connecting to Redis")?;
+
+        Ok(Self {
+            conn,
+            prefix: "pctx:".into(),
+            cache: SessionCache::default(),
+        })
+    }
+
+    /// Prefix of the keys of the backend, `pctx:` by default
+    #[must_use]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn session_key(&self, session_id: Uuid) -> String {
+        format!("{}session:{session_id}", self.prefix)
+    }
+
+    fn index_key(&self) -> String {
+        format!("{}sessions", self.prefix)
+    }
+
+    fn tenant_prefix(&self) -> String {
+        format!("{}tenant:", self.prefix)
+    }
+
+    fn tenant_key(&self, tenant: &Tenant) -> String {
+        format!("{}{}:sessions", self.tenant_prefix(), tenant.0)
+    }
+}
+
+#[async_trait]
+impl PctxSessionBackend for RedisBackend {
+    async fn get(&self, session_id: Uuid) -> Result<Option<CodeMode>> {
+        let (rev, json): (Option<u64>, Option<String>) = self
+            .conn
+            .clone()
+            .hget(self.session_key(session_id), &["rev", "code_mode"])
+            .await?;
+        let (Some(rev), Some(json)) = (rev, json) else {
+            self.cache.remove(session_id);
+            return Ok(None);
+        };
+        if let Some(code_mode) = self.cache.get(session_id, rev) {
+            return Ok(Some(code_mode));
+        }
+
+        let code_mode: CodeMode =
+            serde_json::from_str(&json).context("Failed deserializing CodeMode session")?;
+        self.cache.insert(session_id, rev, code_mode.clone());
+        Ok(Some(code_mode))
+    }
+
+    async fn insert(&self, session_id: Uuid, code_mode: CodeMode, owner: Tenant) -> Result<()> {
+        let json = serde_json::to_string(&code_mode)?;
+        let () = redis::pipe()
+            .atomic()
+            .del(self.session_key(session_id))
+            .ignore()
+            .hset_multiple(
+                self.session_key(session_id),
+                &[
+                    ("code_mode", json.as_str()),
+                    ("owner", owner.0.as_str()),
+                    ("rev", "0"),
+                ],
+            )
+            .ignore()
+            .sadd(self.index_key(), session_id.to_string())
+            .ignore()
+            .sadd(self.tenant_key(&owner), session_id.to_string())
+            .ignore()
+            .query_async(&mut self.conn.clone())
+            .await?;
+        self.cache.insert(session_id, 0, code_mode);
+
+        Ok(())
+    }
+
+    async fn update(&self, session_id: Uuid, code_mode: CodeMode) -> Result<()> {
+        let json = serde_json::to_string(&code_mode)?;
+        // only replaces the revision the cached session was loaded from
+        let expected = self
+            .cache
+            .rev(session_id)
+            .map(|rev| rev.to_string())
+            .unwrap_or_default();
+        let rev: i64 = redis::Script::new(UPDATE_SCRIPT)
+            .key(self.session_key(session_id))
+            .arg(json)
+            .arg(expected)
+            .invoke_async(&mut self.conn.clone())
+            .await?;
+
+        let Ok(rev) = u64::try_from(rev) else {
+            self.cache.remove(session_id);
+            anyhow::ensure!(
+                rev != -2,
+                "CodeMode session {session_id} was updated concurrently"
+            );
+            anyhow::bail!("CodeMode session {session_id} does not exist");
+        };
+        self.cache.insert(session_id, rev, code_mode);
+
+        Ok(())
+    }
+
+    async fn owner(&self, session_id: Uuid) -> Result<Option<Tenant>> {
+        let owner: Option<String> = self
+            .conn
+            .clone()
+            .hget(self.session_key(session_id), "owner")
+            .await?;
+        Ok(owner.map(Tenant))
+    }
+
+    async fn count_owned(&self, tenant: &Tenant) -> Result<usize> {
+        Ok(self.conn.clone().scard(self.tenant_key(tenant)).await?)
+    }
+
+    async fn delete(&self, session_id: Uuid) -> Result<bool> {
+        self.cache.remove(session_id);
+        let deleted: usize = redis::Script::new(DELETE_SCRIPT)
+            .key(self.session_key(session_id))
+            .key(self.index_key())
+            .arg(session_id.to_string())
+            .arg(self.tenant_prefix())
+            .invoke_async(&mut self.conn.clone())
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    async fn exists(&self, session_id: Uuid) -> Result<bool> {
+        Ok(self
+            .conn
+            .clone()
+            .exists(self.session_key(session_id))
+            .await?)
+    }
+
+    async fn count(&self) -> Result<usize> {
+        Ok(self.conn.clone().scard(self.index_key()).await?)
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<Uuid>> {
+        let ids: Vec<String> = self.conn.clone().smembers(self.index_key()).await?;
+        Ok(ids
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect())
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use pctx_code_mode::CodeMode;
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+
+use super::{PctxSessionBackend, SessionCache};
+use crate::state::tenants::Tenant;
+
+/// Persists `CodeMode` sessions in a `SQLite` database, so they survive restarts
+///
+/// Sessions are stored as their serialized configuration (servers, callbacks & tool
+/// sets) next to their owner. Process-local state like tool caches lives in the loaded
+/// sessions this backend keeps in memory, and is only rebuilt after a restart.
+#[derive(Clone)]
+pub struct SqliteBackend {
+    conn: Arc<Mutex<Connection>>,
+    cache: SessionCache,
+}
+
+impl SqliteBackend {
+    /// Opens or creates the database at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or migrated
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed creating directory {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed opening SQLite database {}", path.display()))?;
+        Self::from_connection(conn)
+    }
+
+    /// Creates a database that only lives as long as the backend
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be created
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS code_mode_sessions (
+                id TEXT PRIMARY KEY,
+                code_mode TEXT NOT NULL,
+                owner TEXT NOT NULL,
+                rev INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS code_mode_sessions_owner ON code_mode_sessions (owner);",
+        )
+        .context("Failed creating sessions table")?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            cache: SessionCache::default(),
+        })
+    }
+
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|_| anyhow::anyhow!("SQLite connection lock poisoned"))?;
+            f(&conn)
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl PctxSessionBackend for SqliteBackend {
+    async fn get(&self, session_id: Uuid) -> Result<Option<CodeMode>> {
+        let stored: Option<(u64, String)> = self
+            .with_conn(move |conn| {
+                Ok(conn
+                    .query_row(
+                        "SELECT rev, code_mode FROM code_mode_sessions WHERE id = ?1",
+                        params![session_id.to_string()],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()?)
+            })
+            .await?;
+
+        let Some((rev, json)) = stored else {
+            self.cache.remove(session_id);
+            return Ok(None);
+        };
+        if let Some(code_mode) = self.cache.get(session_id, rev) {
+            return Ok(Some(code_mode));
+        }
+
+        let code_mode: CodeMode =
+            serde_json::from_str(&json).context("Failed deserializing CodeMode session")?;
+        self.cache.insert(session_id, rev, code_mode.clone());
+        Ok(Some(code_mode))
+    }
+
+    async fn insert(&self, session_id: Uuid, code_mode: CodeMode, owner: Tenant) -> Result<()> {
+        let json = serde_json::to_string(&code_mode)?;
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO code_mode_sessions (id, code_mode, owner, rev)
+                VALUES (?1, ?2, ?3, 0)",
+                params![session_id.to_string(), json, owner.0],
+            )?;
+            Ok(())
+        })
+        .await?;
+        self.cache.insert(session_id, 0, code_mode);
+
+        Ok(())
+    }
+
+    async fn update(&self, session_id: Uuid, code_mode: CodeMode) -> Result<()> {
+        let json = serde_json::to_string(&code_mode)?;
+        // only replaces the revision the cached session was loaded from
+        let expected = self.cache.rev(session_id);
+        let rev: Option<u64> = self
+            .with_conn(move |conn| {
+                Ok(conn
+                    .query_row(
+                        "UPDATE code_mode_sessions SET code_mode = ?2, rev = rev + 1
+                        WHERE id = ?1 AND (?3 IS NULL OR rev = ?3)
+                        RETURNING rev",
+                        params![session_id.to_string(), json, expected],
+                        |row| row.get(0),
+                    )
+                    .optional()?)
+            })
+            .await?;
+
+        let Some(rev) = rev else {
+            self.cache.remove(session_id);
+            anyhow::ensure!(
+                !self.exists(session_id).await?,
+                "CodeMode session {session_id} was updated concurrently"
+            );
+            anyhow::bail!("CodeMode session {session_id} does not exist");
+        };
+        self.cache.insert(session_id, rev, code_mode);
+
+        Ok(())
+    }
+
+    async fn owner(&self, session_id: Uuid) -> Result<Option<Tenant>> {
+        self.with_conn(move |conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT owner FROM code_mode_sessions WHERE id = ?1",
+                    params![session_id.to_string()],
+                    |row| row.get(0).map(Tenant),
+                )
+                .optional()?)
+        })
+        .await
+    }
+
+    async fn count_owned(&self, tenant: &Tenant) -> Result<usize> {
+        let tenant = tenant.0.clone();
+        self.with_conn(move |conn| {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM code_mode_sessions WHERE owner = ?1",
+                params![tenant],
+                |row| row.get(0),
+            )?;
+            Ok(usize::try_from(count)?)
+        })
+        .await
+    }
+
+    async fn delete(&self, session_id: Uuid) -> Result<bool> {
+        self.cache.remove(session_id);
+        let deleted = self
+            .with_conn(move |conn| {
+                Ok(conn.execute(
+                    "DELETE FROM code_mode_sessions WHERE id = ?1",
+                    params![session_id.to_string()],
+                )?)
+            })
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    async fn exists(&self, session_id: Uuid) -> Result<bool> {
+        self.with_conn(move |conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT 1 FROM code_mode_sessions WHERE id = ?1",
+                    params![session_id.to_string()],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some())
+        })
+        .await
+    }
+
+    async fn count(&self) -> Result<usize> {
+        self.with_conn(|conn| {
+            let count: i64 =
+                conn.query_row("SELECT COUNT(*) FROM code_mode_sessions", [], |row| {
+                    row.get(0)
+                })?;
+            Ok(usize::try_from(count)?)
+        })
+        .await
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<Uuid>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT id FROM code_mode_sessions")?;
+            let ids = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .map(|id| Ok(Uuid::parse_str(&id?)?))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pctx_code_mode::CodeMode;
+    use uuid::Uuid;
+
+    use super::SqliteBackend;
+    use crate::{PctxSessionBackend, state::tenants::Tenant};
+
+    #[tokio::test]
+    async fn test_sqlite_backend() {
+        let backend = SqliteBackend::open_in_memory().unwrap();
+        let session_id = Uuid::new_v4();
+        let acme = Tenant("acme".into());
+
+        assert!(backend.get(session_id).await.unwrap().is_none());
+        assert!(backend.owner(session_id).await.unwrap().is_none());
+        assert!(
+            backend
+                .update(session_id, CodeMode::default())
+                .await
+                .is_err()
+        );
+
+        backend
+            .insert(session_id, CodeMode::default(), acme.clone())
+            .await
+            .unwrap();
+        assert!(backend.exists(session_id).await.unwrap());
+        assert!(backend.get(session_id).await.unwrap().is_some());
+        assert_eq!(backend.owner(session_id).await.unwrap(), Some(acme.clone()));
+        assert_eq!(backend.count_owned(&acme).await.unwrap(), 1);
+        assert_eq!(backend.count_owned(&Tenant::default()).await.unwrap(), 0);
+        assert_eq!(backend.count().await.unwrap(), 1);
+        assert_eq!(backend.list_sessions().await.unwrap(), vec![session_id]);
+        backend
+            .update(session_id, CodeMode::default())
+            .await
+            .unwrap();
+
+        assert!(backend.delete(session_id).await.unwrap());
+        assert!(!backend.delete(session_id).await.unwrap());
+        assert_eq!(backend.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_backend_keeps_runtime_state() {
+        let path = std::env::temp_dir().join(format!("pctx-sessions-{}.db", Uuid::new_v4()));
+        let instance = SqliteBackend::open(&path).unwrap();
+        let other_instance = SqliteBackend::open(&path).unwrap();
+        let session_id = Uuid::new_v4();
+
+        instance
+            .insert(
+                session_id,
+                CodeMode::default().with_max_tool_calls(3),
+                Tenant::default(),
+            )
+            .await
+            .unwrap();
+
+        // runtime state is kept by the instance that created the session, other
+        // instances only load its configuration
+        let code_mode = instance.get(session_id).await.unwrap().unwrap();
+        assert_eq!(code_mode.max_tool_calls(), Some(3));
+        let other = other_instance.get(session_id).await.unwrap().unwrap();
+        assert_eq!(other.max_tool_calls(), None);
+
+        // updates of a session another instance updated in between are rejected
+        other_instance.update(session_id, other).await.unwrap();
+        assert!(instance.update(session_id, code_mode).await.is_err());
+
+        let reloaded = instance.get(session_id).await.unwrap().unwrap();
+        instance.update(session_id, reloaded).await.unwrap();
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
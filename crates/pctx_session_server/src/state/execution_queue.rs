@@ -0,0 +1,155 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Concurrency of code executions across all sessions
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutionQueueConfig {
+    /// Maximum number of concurrently running executions, unlimited if unset
+    pub max_concurrent: Option<usize>,
+    /// Maximum number of executions waiting for a slot, further executions are rejected
+    pub max_queued: usize,
+}
+
+impl Default for ExecutionQueueConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: None,
+            max_queued: 100,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("Execution queue is full, {0} executions are already waiting")]
+pub struct QueueFull(pub usize);
+
+/// Bounded queue of code executions, so bursts of requests wait for a slot instead of
+/// all starting a runtime at once
+#[derive(Debug, Clone)]
+pub struct ExecutionQueue {
+    slots: Arc<Semaphore>,
+    waiting: Arc<AtomicUsize>,
+    max_queued: usize,
+}
+
+/// Admission of an execution to the queue
+#[derive(Debug)]
+pub enum Admission {
+    /// A slot was free, the execution can run while holding the permit
+    Ready(OwnedSemaphorePermit),
+    /// The execution waits for a slot
+    Queued(QueuedExecution),
+}
+
+/// Execution waiting for a slot, leaving the queue when dropped
+#[derive(Debug)]
+pub struct QueuedExecution {
+    slots: Arc<Semaphore>,
+    waiting: Arc<AtomicUsize>,
+    position: usize,
+}
+
+impl ExecutionQueue {
+    pub fn new(config: ExecutionQueueConfig) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(
+                config.max_concurrent.unwrap_or(Semaphore::MAX_PERMITS),
+            )),
+            waiting: Arc::default(),
+            max_queued: config.max_queued,
+        }
+    }
+
+    /// Number of executions waiting for a slot
+    pub fn queued(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Admits an execution, taking a free slot or queueing it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no slot is free and the queue is full
+    pub fn admit(&self) -> Result<Admission, QueueFull> {
+        if let Ok(permit) = self.slots.clone().try_acquire_owned() {
+            return Ok(Admission::Ready(permit));
+        }
+
+        let position = self
+            .waiting
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |waiting| {
+                (waiting < self.max_queued).then_some(waiting + 1)
+            })
+            .map_err(|_| QueueFull(self.max_queued))?
+            + 1;
+
+        Ok(Admission::Queued(QueuedExecution {
+            slots: self.slots.clone(),
+            waiting: self.waiting.clone(),
+            position,
+        }))
+    }
+}
+
+impl Default for ExecutionQueue {
+    fn default() -> Self {
+        Self::new(ExecutionQueueConfig::default())
+    }
+}
+
+impl QueuedExecution {
+    /// 1-based position in the queue when the execution was admitted
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Waits for a slot
+    ///
+    /// # Panics
+    ///
+    /// Panics if the semaphore was closed, which never happens
+    pub async fn acquire(self) -> OwnedSemaphorePermit {
+        self.slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("execution slots are never closed")
+    }
+}
+
+impl Drop for QueuedExecution {
+    fn drop(&mut self) {
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Admission, ExecutionQueue, ExecutionQueueConfig, QueueFull};
+
+    #[tokio::test]
+    async fn test_admission() {
+        let queue = ExecutionQueue::new(ExecutionQueueConfig {
+            max_concurrent: Some(1),
+            max_queued: 1,
+        });
+
+        let Ok(Admission::Ready(running)) = queue.admit() else {
+            panic!("expected a free slot");
+        };
+        let Ok(Admission::Queued(queued)) = queue.admit() else {
+            panic!("expected to be queued");
+        };
+        assert_eq!(queued.position(), 1);
+        assert_eq!(queue.admit().unwrap_err(), QueueFull(1));
+
+        drop(running);
+        let _permit = queued.acquire().await;
+        assert_eq!(queue.queued(), 0);
+        assert!(matches!(queue.admit(), Ok(Admission::Queued(_))));
+    }
+}
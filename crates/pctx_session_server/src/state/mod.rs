@@ -1,18 +1,67 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
+
+use pctx_code_mode::CodeMode;
+use uuid::Uuid;
 
 use crate::{
     LocalBackend,
-    state::{backend::PctxSessionBackend, ws_manager::WsManager},
+    auth::AuthConfig,
+    state::{
+        backend::PctxSessionBackend,
+        execution_queue::{ExecutionQueue, ExecutionQueueConfig},
+        session_locks::SessionLocks,
+        tenants::{Tenant, TenantError, TenantLimits, TenantManager},
+        ws_manager::{HeartbeatConfig, WsManager},
+    },
 };
 
 pub(crate) mod backend;
+pub(crate) mod execution_queue;
+pub(crate) mod session_locks;
+pub(crate) mod tenants;
 pub(crate) mod ws_manager;
 
+pub(crate) const DEFAULT_CALLBACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Limits applied to every code mode session
+#[derive(Debug, Clone)]
+pub struct SessionLimits {
+    /// Timeout of callbacks, unless an `execute_code` request sets its own
+    pub callback_timeout: Duration,
+    /// Maximum number of tools registered per session
+    pub max_tools: Option<usize>,
+    /// Maximum number of concurrently pending callback executions per session
+    pub max_pending_executions: Option<usize>,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            callback_timeout: DEFAULT_CALLBACK_TIMEOUT,
+            max_tools: None,
+            max_pending_executions: None,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateSessionError {
+    #[error(transparent)]
+    Tenant(#[from] TenantError),
+    #[error(transparent)]
+    Backend(#[from] anyhow::Error),
+}
+
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState<B: PctxSessionBackend> {
     pub ws_manager: Arc<WsManager>,
     pub backend: Arc<B>,
+    pub auth: Arc<AuthConfig>,
+    pub limits: Arc<SessionLimits>,
+    pub tenants: Arc<TenantManager>,
+    pub executions: Arc<ExecutionQueue>,
+    pub session_locks: Arc<SessionLocks>,
 }
 
 impl<B: PctxSessionBackend> AppState<B> {
@@ -20,8 +69,75 @@ impl<B: PctxSessionBackend> AppState<B> {
         Self {
             ws_manager: Arc::default(),
             backend: Arc::new(backend),
+            auth: Arc::default(),
+            limits: Arc::default(),
+            tenants: Arc::default(),
+            executions: Arc::default(),
+            session_locks: Arc::default(),
         }
     }
+
+    /// Creates an empty `CodeMode` session owned by `tenant`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tenant has reached its session limit or the session
+    /// cannot be inserted into the backend
+    pub async fn create_session(&self, tenant: &Tenant) -> Result<Uuid, CreateSessionError> {
+        let session_id = Uuid::new_v4();
+        let _creating = self.tenants.creating().await;
+        let open = self.backend.count_owned(tenant).await?;
+        self.tenants.check_session_limit(open)?;
+        self.backend
+            .insert(session_id, CodeMode::default(), tenant.clone())
+            .await?;
+
+        Ok(session_id)
+    }
+
+    /// Returns `true` if the session exists and is owned by `tenant`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the owner cannot be read from the backend
+    pub async fn can_access(&self, session_id: Uuid, tenant: &Tenant) -> anyhow::Result<bool> {
+        Ok(self.backend.owner(session_id).await?.as_ref() == Some(tenant))
+    }
+
+    /// Ping WebSocket clients & drop dead connections with the provided keepalive
+    #[must_use]
+    pub fn with_heartbeat(mut self, heartbeat: HeartbeatConfig) -> Self {
+        self.ws_manager = Arc::new(WsManager::new(heartbeat));
+        self
+    }
+
+    /// Apply the limits to every code mode session
+    #[must_use]
+    pub fn with_limits(mut self, limits: SessionLimits) -> Self {
+        self.limits = Arc::new(limits);
+        self
+    }
+
+    /// Queue code executions beyond the configured concurrency
+    #[must_use]
+    pub fn with_execution_queue(mut self, config: ExecutionQueueConfig) -> Self {
+        self.executions = Arc::new(ExecutionQueue::new(config));
+        self
+    }
+
+    /// Apply the quotas to every tenant
+    #[must_use]
+    pub fn with_tenant_limits(mut self, limits: TenantLimits) -> Self {
+        self.tenants = Arc::new(TenantManager::new(limits));
+        self
+    }
+
+    /// Require requests & WebSocket upgrades to authenticate with an accepted token
+    #[must_use]
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = Arc::new(auth);
+        self
+    }
 }
 
 impl AppState<LocalBackend> {
@@ -29,6 +145,11 @@ impl AppState<LocalBackend> {
         Self {
             ws_manager: Arc::default(),
             backend: Arc::new(LocalBackend::default()),
+            auth: Arc::default(),
+            limits: Arc::default(),
+            tenants: Arc::default(),
+            executions: Arc::default(),
+            session_locks: Arc::default(),
         }
     }
 }
@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::OwnedMutexGuard;
+use uuid::Uuid;
+
+/// Serializes the modifications of each code mode session
+///
+/// Registering tools or servers gets the session from the backend, modifies it & updates
+/// it in the backend. Holding the session's lock in between keeps concurrent requests
+/// from overwriting each other's changes.
+#[derive(Default)]
+pub struct SessionLocks {
+    locks: Mutex<HashMap<Uuid, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl SessionLocks {
+    /// Waits until no other request modifies the session
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub async fn lock(&self, session_id: Uuid) -> OwnedMutexGuard<()> {
+        let lock = self
+            .locks
+            .lock()
+            .unwrap()
+            .entry(session_id)
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    /// Drops the lock of a closed session
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn remove(&self, session_id: Uuid) {
+        self.locks.lock().unwrap().remove(&session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use uuid::Uuid;
+
+    use super::SessionLocks;
+
+    #[tokio::test]
+    async fn test_session_locks() {
+        let locks = Arc::new(SessionLocks::default());
+        let session_id = Uuid::new_v4();
+
+        let guard = locks.lock(session_id).await;
+        // other sessions are not blocked
+        let _other = locks.lock(Uuid::new_v4()).await;
+
+        let waiting = tokio::spawn({
+            let locks = locks.clone();
+            async move { drop(locks.lock(session_id).await) }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        drop(guard);
+        tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
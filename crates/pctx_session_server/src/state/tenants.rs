@@ -0,0 +1,142 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::MutexGuard;
+
+/// Identity of the tenant a request was authenticated as, set by the auth middleware
+///
+/// Requests without a tenant (e.g. if auth is disabled or the token is not a tenant
+/// token) belong to the [default](Tenant::default) tenant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tenant(pub String);
+
+/// Id of the tenant of requests that do not identify one
+pub const DEFAULT_TENANT: &str = "default";
+
+impl Default for Tenant {
+    fn default() -> Self {
+        Self(DEFAULT_TENANT.into())
+    }
+}
+
+/// Quotas applied to each tenant
+#[derive(Debug, Clone, Default)]
+pub struct TenantLimits {
+    /// Maximum number of open code mode sessions
+    pub max_sessions: Option<usize>,
+    /// Maximum number of `execute_code` requests in any 60 second window
+    pub max_executions_per_minute: Option<usize>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum TenantError {
+    #[error("Tenant has reached the limit of {0} sessions")]
+    TooManySessions(usize),
+    #[error("Tenant has reached the limit of {0} executions per minute")]
+    RateLimited(usize),
+}
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Quotas & usage of the tenants
+///
+/// Sessions are only accessible to the tenant that created them, so tools one tenant
+/// registered are never callable from another tenant's executions. Owners are stored
+/// next to the sessions in the backend.
+#[derive(Default)]
+pub struct TenantManager {
+    limits: TenantLimits,
+    creating: tokio::sync::Mutex<()>,
+    executions: Mutex<HashMap<Tenant, VecDeque<Instant>>>,
+}
+
+impl TenantManager {
+    pub fn new(limits: TenantLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Serializes the creation of sessions, held while counting a tenant's sessions &
+    /// inserting the new one so concurrent requests cannot exceed the session limit
+    pub(crate) async fn creating(&self) -> MutexGuard<'_, ()> {
+        self.creating.lock().await
+    }
+
+    /// Checks the tenant may open another session besides its `open` sessions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tenant has reached its session limit
+    pub fn check_session_limit(&self, open: usize) -> Result<(), TenantError> {
+        match self.limits.max_sessions {
+            Some(max) if open >= max => Err(TenantError::TooManySessions(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records an execution of the tenant
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tenant has reached its execution rate limit
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn record_execution(&self, tenant: &Tenant) -> Result<(), TenantError> {
+        let Some(max) = self.limits.max_executions_per_minute else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        let mut executions = self.executions.lock().unwrap();
+        let window = executions.entry(tenant.clone()).or_default();
+        while window
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW)
+        {
+            window.pop_front();
+        }
+        if window.len() >= max {
+            return Err(TenantError::RateLimited(max));
+        }
+
+        window.push_back(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tenant, TenantError, TenantLimits, TenantManager};
+
+    #[test]
+    fn test_tenant_limits() {
+        let tenants = TenantManager::new(TenantLimits {
+            max_sessions: Some(1),
+            max_executions_per_minute: Some(2),
+        });
+        let acme = Tenant("acme".into());
+        let globex = Tenant("globex".into());
+
+        assert_eq!(tenants.check_session_limit(0), Ok(()));
+        assert_eq!(
+            tenants.check_session_limit(1),
+            Err(TenantError::TooManySessions(1))
+        );
+        assert_eq!(TenantManager::default().check_session_limit(100), Ok(()));
+
+        assert_eq!(tenants.record_execution(&acme), Ok(()));
+        assert_eq!(tenants.record_execution(&acme), Ok(()));
+        assert_eq!(
+            tenants.record_execution(&acme),
+            Err(TenantError::RateLimited(2))
+        );
+        assert_eq!(tenants.record_execution(&globex), Ok(()));
+    }
+}
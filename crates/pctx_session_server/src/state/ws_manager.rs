@@ -1,11 +1,19 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use rmcp::model::RequestId;
-use tokio::sync::{RwLock, mpsc as tokio_mpsc};
+use tokio::sync::{RwLock, mpsc as tokio_mpsc, oneshot};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
-use crate::model::{ExecuteToolParams, ExecuteToolResult, PctxJsonRpcRequest, WsJsonRpcMessage};
+use crate::{
+    model::{ExecuteToolParams, ExecuteToolResult, PctxJsonRpcRequest, WsJsonRpcMessage},
+    state::{DEFAULT_CALLBACK_TIMEOUT, SessionLimits},
+    websocket::event,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ExecuteCallbackError {
@@ -15,17 +23,49 @@ pub enum ExecuteCallbackError {
     ExecutionFailed(rmcp::model::ErrorData),
     #[error("Response channel closed")]
     ChannelClosed,
-    #[error("Execution timeout")]
-    Timeout,
+    #[error("Execution timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("Too many pending executions, the session allows {0}")]
+    TooManyPending(usize),
+}
+
+/// Keepalive of WebSocket connections
+#[derive(Debug, Clone, Copy)]
+pub struct HeartbeatConfig {
+    /// Interval of pings sent to the client
+    pub ping_interval: Duration,
+    /// Sessions receiving nothing (not even pongs) for this long are considered dead
+    pub idle_timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(90),
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct WsManager {
     /// Active sessions by ID
     pub(crate) sessions: Arc<RwLock<HashMap<Uuid, Arc<RwLock<WsSession>>>>>,
+    heartbeat: HeartbeatConfig,
 }
 
 impl WsManager {
+    pub fn new(heartbeat: HeartbeatConfig) -> Self {
+        Self {
+            sessions: Arc::default(),
+            heartbeat,
+        }
+    }
+
+    pub fn heartbeat(&self) -> HeartbeatConfig {
+        self.heartbeat
+    }
+
     /// Lists current sessions
     pub async fn list_sessions(&self) -> Vec<Uuid> {
         self.sessions.read().await.keys().copied().collect()
@@ -39,10 +79,67 @@ impl WsManager {
         session_id
     }
 
-    /// Remove a session
+    /// Remove a session, failing its pending executions
     pub async fn remove_session(&self, session_id: Uuid) {
-        let mut sessions = self.sessions.write().await;
-        sessions.remove(&session_id);
+        let removed = self.sessions.write().await.remove(&session_id);
+        if let Some(session) = removed {
+            session.read().await.close().await;
+        }
+    }
+
+    /// Removes sessions whose socket died without a close frame, i.e. that received
+    /// nothing for longer than the idle timeout, returning their IDs
+    ///
+    /// Their code mode sessions can connect again and pending executions fail right away.
+    pub async fn reap_stale(&self) -> Vec<Uuid> {
+        let mut stale = vec![];
+        for (id, session) in self.sessions.read().await.iter() {
+            if session.read().await.idle_for() > self.heartbeat.idle_timeout {
+                stale.push(*id);
+            }
+        }
+
+        for id in &stale {
+            warn!("Reaping stale WebSocket session {id}");
+            self.remove_session(*id).await;
+        }
+        stale
+    }
+
+    /// Sends a message to every session, returning the number of sessions it was sent to
+    pub async fn broadcast(&self, message: WsJsonRpcMessage) -> usize {
+        let mut sent = 0;
+        for session in self.sessions.read().await.values() {
+            if session.read().await.sender.send(message.clone()).is_ok() {
+                sent += 1;
+            }
+        }
+        sent
+    }
+
+    /// Sends an event to the sessions subscribed to its topic, returning the number of
+    /// sessions it was sent to
+    pub async fn publish(&self, topic: &str, data: serde_json::Value) -> usize {
+        let mut sent = 0;
+        for session in self.sessions.read().await.values() {
+            if session.read().await.notify(topic, data.clone()) {
+                sent += 1;
+            }
+        }
+        sent
+    }
+
+    /// Sends an event to the session of a code mode session if it subscribed to its topic
+    pub async fn notify_code_mode_session(
+        &self,
+        code_mode_session_id: Uuid,
+        topic: &str,
+        data: serde_json::Value,
+    ) -> bool {
+        match self.get_for_code_mode_session(code_mode_session_id).await {
+            Some(session) => session.read().await.notify(topic, data),
+            None => false,
+        }
     }
 
     pub async fn get_for_code_mode_session(
@@ -96,14 +193,30 @@ impl WsManager {
 }
 
 type PendingExecutionsMap = Arc<
-    RwLock<
-        HashMap<
-            RequestId,
-            std::sync::mpsc::Sender<Result<ExecuteToolResult, rmcp::model::ErrorData>>,
-        >,
-    >,
+    RwLock<HashMap<RequestId, oneshot::Sender<Result<ExecuteToolResult, rmcp::model::ErrorData>>>>,
 >;
 
+/// Removes a pending execution when its callback completes, times out or is cancelled
+/// (i.e. the waiting future is dropped), so it no longer counts towards the session limit
+struct PendingGuard {
+    pending_executions: PendingExecutionsMap,
+    request_id: RequestId,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.pending_executions.try_write() {
+            pending.remove(&self.request_id);
+        } else if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let pending_executions = self.pending_executions.clone();
+            let request_id = self.request_id.clone();
+            handle.spawn(async move {
+                pending_executions.write().await.remove(&request_id);
+            });
+        }
+    }
+}
+
 /// WebSocket session representing a connected client
 #[derive(Clone)]
 pub struct WsSession {
@@ -113,6 +226,14 @@ pub struct WsSession {
     pub sender: tokio_mpsc::UnboundedSender<WsJsonRpcMessage>,
     /// Pending execution requests waiting for responses
     pending_executions: PendingExecutionsMap,
+    /// Time anything was last received from the client
+    last_seen: Arc<Mutex<Instant>>,
+    /// Timeout of callbacks executed without their own timeout
+    callback_timeout: Duration,
+    /// Maximum number of concurrently pending executions
+    max_pending_executions: Option<usize>,
+    /// Event topics the client subscribed to
+    subscriptions: Arc<Mutex<HashSet<String>>>,
 }
 impl WsSession {
     pub fn new(
@@ -124,23 +245,109 @@ impl WsSession {
             sender,
             code_mode_session_id,
             pending_executions: Arc::new(RwLock::new(HashMap::new())),
+            last_seen: Arc::new(Mutex::new(Instant::now())),
+            callback_timeout: DEFAULT_CALLBACK_TIMEOUT,
+            max_pending_executions: None,
+            subscriptions: Arc::default(),
         }
     }
 
+    /// Applies the per-session limits
+    #[must_use]
+    pub fn with_limits(mut self, limits: &SessionLimits) -> Self {
+        self.callback_timeout = limits.callback_timeout;
+        self.max_pending_executions = limits.max_pending_executions;
+        self
+    }
+
+    /// Records that the client is alive
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since anything was received from the client
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn idle_for(&self) -> Duration {
+        self.last_seen.lock().unwrap().elapsed()
+    }
+
+    /// Subscribes the client to events of the topics
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn subscribe(&self, topics: impl IntoIterator<Item = String>) {
+        self.subscriptions.lock().unwrap().extend(topics);
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn unsubscribe(&self, topics: &[String]) {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        for topic in topics {
+            subscriptions.remove(topic);
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned (i.e., a thread panicked while holding the lock)
+    pub fn is_subscribed(&self, topic: &str) -> bool {
+        self.subscriptions.lock().unwrap().contains(topic)
+    }
+
+    /// Sends an event if the client subscribed to its topic, returning whether it was sent
+    pub fn notify(&self, topic: &str, data: serde_json::Value) -> bool {
+        self.is_subscribed(topic) && self.sender.send(event(topic, data)).is_ok()
+    }
+
+    /// Fails pending executions, their response channels are dropped
+    async fn close(&self) {
+        self.pending_executions.write().await.clear();
+    }
+
     /// Execute a callback on this session, sending a message and waiting for a response
+    /// for the session's callback timeout
     pub async fn execute_callback(
         &self,
         params: ExecuteToolParams,
+    ) -> Result<ExecuteToolResult, ExecuteCallbackError> {
+        self.execute_callback_with_timeout(params, self.callback_timeout)
+            .await
+    }
+
+    /// Execute a callback on this session, sending a message and waiting for a response
+    /// for at most `timeout`
+    pub async fn execute_callback_with_timeout(
+        &self,
+        params: ExecuteToolParams,
+        timeout: Duration,
     ) -> Result<ExecuteToolResult, ExecuteCallbackError> {
         let req_id = RequestId::String(Uuid::new_v4().to_string().into());
-        // Create std::sync::mpsc channel for response
-        let (response_tx, response_rx) = std::sync::mpsc::channel();
+        let (response_tx, response_rx) = oneshot::channel();
 
-        // Store pending execution
-        self.pending_executions
-            .write()
-            .await
-            .insert(req_id.clone(), response_tx);
+        // Store pending execution, unless the session has too many already
+        {
+            let mut pending = self.pending_executions.write().await;
+            if let Some(max) = self.max_pending_executions
+                && pending.len() >= max
+            {
+                return Err(ExecuteCallbackError::TooManyPending(max));
+            }
+            pending.insert(req_id.clone(), response_tx);
+        }
+        let _guard = PendingGuard {
+            pending_executions: self.pending_executions.clone(),
+            request_id: req_id.clone(),
+        };
 
         // Send message to client
         self.sender
@@ -151,21 +358,11 @@ impl WsSession {
             .map_err(|_| ExecuteCallbackError::SendFailed)?;
 
         // Wait for response with timeout
-        let result = tokio::time::timeout(
-            tokio::time::Duration::from_secs(30),
-            tokio::task::spawn_blocking(move || response_rx.recv()),
-        )
-        .await;
-
-        // Clean up pending execution
-        self.pending_executions.write().await.remove(&req_id);
-
-        match result {
-            Ok(Ok(Ok(Ok(value)))) => Ok(value),
-            Ok(Ok(Ok(Err(error)))) => Err(ExecuteCallbackError::ExecutionFailed(error)),
-            Ok(Ok(Err(_))) => Err(ExecuteCallbackError::ChannelClosed),
+        match tokio::time::timeout(timeout, response_rx).await {
+            Ok(Ok(Ok(value))) => Ok(value),
+            Ok(Ok(Err(error))) => Err(ExecuteCallbackError::ExecutionFailed(error)),
             Ok(Err(_)) => Err(ExecuteCallbackError::ChannelClosed),
-            Err(_) => Err(ExecuteCallbackError::Timeout),
+            Err(_) => Err(ExecuteCallbackError::Timeout(timeout)),
         }
     }
 
@@ -175,15 +372,16 @@ impl WsSession {
         request_id: RequestId,
         result: Result<ExecuteToolResult, rmcp::model::ErrorData>,
     ) -> Result<(), ()> {
-        let pending_read = self.pending_executions.read().await;
+        let mut pending = self.pending_executions.write().await;
         info!(
-            pending_count = pending_read.len(),
+            pending_count = pending.len(),
             "Handling execution response for request_id: {request_id:?}",
         );
-        if let Some(response_tx) = pending_read.get(&request_id) {
+        if let Some(response_tx) = pending.remove(&request_id) {
             debug!("Found pending execution, sending result");
-            let send_result = response_tx.send(result);
-            debug!("mpsc send result: {send_result:?}");
+            if response_tx.send(result).is_err() {
+                debug!("Execution of request_id {request_id:?} is no longer waiting");
+            }
             Ok(())
         } else {
             warn!("No pending execution found for request_id: {request_id:?}");
@@ -1,36 +1,45 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
     PctxSessionBackend,
     extractors::CodeModeSession,
     model::{
-        ExecuteCodeParams, ExecuteToolParams, PctxJsonRpcRequest, PctxJsonRpcResponse,
-        WsJsonRpcMessage,
+        ExecuteCodeParams, ExecuteToolParams, PctxJsonRpcNotification, PctxJsonRpcRequest,
+        PctxJsonRpcResponse, ProgressParams, QueuedParams, WsJsonRpcMessage,
     },
-    state::ws_manager::WsSession,
+    state::{
+        execution_queue::Admission,
+        ws_manager::{HeartbeatConfig, WsSession},
+    },
+    websocket::{EXECUTION_QUEUE_FULL, WsEncoding},
 };
 use anyhow::anyhow;
+use async_trait::async_trait;
 use axum::{
     extract::{
         State,
         ws::{Message, WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
 use futures::{
     SinkExt, StreamExt,
     stream::{SplitSink, SplitStream},
 };
-use pctx_code_execution_runtime::{CallbackFn, CallbackRegistry};
-use pctx_code_mode::model::ExecuteInput;
+use pctx_code_execution_runtime::{CallbackFn, CallbackRegistry, ExecutionIds};
+use pctx_code_mode::{
+    execute_blocking,
+    model::{CallbackConfig, ExecuteInput},
+};
+use pctx_config::client::ProgressHandler;
 use rmcp::{
     ErrorData,
-    model::{ErrorCode, JsonRpcMessage, RequestId},
+    model::{ErrorCode, JsonRpcMessage, ProgressNotificationParam, RequestId},
 };
 use serde_json::json;
 use tokio::sync::mpsc;
-use tracing::{debug, error, info, warn};
+use tracing::{Instrument, debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::AppState;
@@ -40,6 +49,7 @@ pub async fn ws_handler<B: PctxSessionBackend>(
     ws: WebSocketUpgrade,
     State(state): State<AppState<B>>,
     CodeModeSession(code_mode_session): CodeModeSession,
+    headers: HeaderMap,
 ) -> Response {
     // Verify that a code mode session exists with this ID
     if !state
@@ -56,6 +66,9 @@ pub async fn ws_handler<B: PctxSessionBackend>(
             .into_response();
     }
 
+    // Sockets that died without a close frame would block reconnecting
+    state.ws_manager.reap_stale().await;
+
     // Check if there's already a WebSocket session for this code mode ID
     if state
         .ws_manager
@@ -75,7 +88,9 @@ pub async fn ws_handler<B: PctxSessionBackend>(
             .into_response();
     }
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state, code_mode_session))
+    let encoding = WsEncoding::negotiate(&headers);
+    ws.protocols([encoding.protocol()])
+        .on_upgrade(move |socket| handle_socket(socket, state, code_mode_session, encoding))
 }
 
 /// Handle an individual WebSocket connection
@@ -83,8 +98,9 @@ async fn handle_socket<B: PctxSessionBackend>(
     socket: WebSocket,
     state: AppState<B>,
     code_mode_session: Uuid,
+    encoding: WsEncoding,
 ) {
-    info!(session_id =? code_mode_session, "New WebSocket connection");
+    info!(session_id =? code_mode_session, ?encoding, "New WebSocket connection");
 
     // Split socket into sender and receiver
     let (sender, receiver) = socket.split();
@@ -93,8 +109,9 @@ async fn handle_socket<B: PctxSessionBackend>(
     let (tx, rx) = mpsc::unbounded_channel::<WsJsonRpcMessage>();
 
     // Create session
-    let session = WsSession::new(tx.clone(), code_mode_session);
+    let session = WsSession::new(tx.clone(), code_mode_session).with_limits(&state.limits);
     let ws_session = session.id;
+    let liveness = session.clone();
 
     debug!(
         session_id =? code_mode_session,
@@ -105,11 +122,18 @@ async fn handle_socket<B: PctxSessionBackend>(
     state.ws_manager.add(session).await;
 
     // Spawn task to handle outgoing messages (notifications/execute_tool requests)
-    let mut send_task = tokio::spawn(write_messages(sender, rx));
+    let heartbeat = state.ws_manager.heartbeat();
+    let mut send_task = tokio::spawn(write_messages(
+        sender,
+        rx,
+        liveness.clone(),
+        heartbeat,
+        encoding,
+    ));
 
     // Spawn task to handle incoming messages (execute_tool responses)
     let state_clone = state.clone(); // cloning state here is ok because state just has Arc attributes
-    let mut recv_task = tokio::spawn(read_messages(receiver, ws_session, state_clone));
+    let mut recv_task = tokio::spawn(read_messages(receiver, liveness, state_clone, encoding));
 
     // Wait for either task to finish
     tokio::select! {
@@ -128,16 +152,40 @@ async fn handle_socket<B: PctxSessionBackend>(
     info!("WebSocket connection closed for session {ws_session}");
 }
 
-/// Handle outgoing WebSocket messages (`execute_tool` requests from server)
+/// Handle outgoing WebSocket messages (`execute_tool` requests from server) & pings,
+/// closing the connection once the client stops responding
 async fn write_messages(
     mut sender: SplitSink<WebSocket, Message>,
     mut rx: mpsc::UnboundedReceiver<WsJsonRpcMessage>,
+    session: WsSession,
+    heartbeat: HeartbeatConfig,
+    encoding: WsEncoding,
 ) {
-    while let Some(msg) = rx.recv().await {
-        if let Err(e) = sender
-            .send(Message::Text(json!(msg).to_string().into()))
-            .await
-        {
+    let mut ping = tokio::time::interval(heartbeat.ping_interval);
+    ping.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        let msg = tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(msg) => match encoding.encode(&msg) {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        error!("Error encoding WebSocket message: {e}");
+                        continue;
+                    }
+                },
+                None => break,
+            },
+            _ = ping.tick() => {
+                if session.idle_for() > heartbeat.idle_timeout {
+                    warn!("Closing idle WebSocket session {}", session.id);
+                    break;
+                }
+                Message::Ping(Default::default())
+            }
+        };
+
+        if let Err(e) = sender.send(msg).await {
             error!("Error sending WebSocket message: {e}");
             break;
         }
@@ -147,13 +195,17 @@ async fn write_messages(
 /// Handle incoming WebSocket messages (`execute_tool` responses from client)
 async fn read_messages<B: PctxSessionBackend>(
     mut receiver: SplitStream<WebSocket>,
-    ws_session: Uuid,
+    session: WsSession,
     state: AppState<B>,
+    encoding: WsEncoding,
 ) {
+    let ws_session = session.id;
     while let Some(result) = receiver.next().await {
         match result {
             Ok(msg) => {
-                if let Err(e) = handle_message(msg, ws_session, &state).await {
+                // any message, including pongs, proves the client is alive
+                session.touch();
+                if let Err(e) = handle_message(msg, &session, &state, encoding).await {
                     error!("Error handling message for session {ws_session}: {e}");
                 }
             }
@@ -203,8 +255,66 @@ async fn handle_execute_code_request<B: PctxSessionBackend>(
 
     debug!("Found CodeMode session with ID: {code_mode_session_id}");
 
+    let tenant = state
+        .backend
+        .owner(code_mode_session_id)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    if let Err(e) = state.tenants.record_execution(&tenant) {
+        warn!(tenant =? tenant, "Rejecting execution: {e}");
+        let err_res = WsJsonRpcMessage::error(
+            ErrorData {
+                code: ErrorCode::INVALID_REQUEST,
+                message: e.to_string().into(),
+                data: None,
+            },
+            req_id,
+        );
+        let _ = sender.send(err_res);
+        return Ok(());
+    }
+
+    // bursts of executions wait for a slot instead of all starting a runtime
+    let admission = match state.executions.admit() {
+        Ok(admission) => admission,
+        Err(e) => {
+            warn!("Rejecting execution: {e}");
+            let err_res = WsJsonRpcMessage::error(
+                ErrorData {
+                    code: EXECUTION_QUEUE_FULL,
+                    message: e.to_string().into(),
+                    data: None,
+                },
+                req_id,
+            );
+            let _ = sender.send(err_res);
+            return Ok(());
+        }
+    };
+    if let Admission::Queued(queued) = &admission {
+        debug!(position = queued.position(), "Queued execution");
+        let _ = sender.send(WsJsonRpcMessage::notification(
+            PctxJsonRpcNotification::Queued {
+                params: QueuedParams {
+                    request_id: req_id.clone(),
+                    position: queued.position(),
+                },
+            },
+        ));
+    }
+
     let execution_id = Uuid::new_v4();
 
+    let callback_timeout = params
+        .callback_timeout_secs
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .unwrap_or(state.limits.callback_timeout);
+    let execution_timeout = params
+        .timeout_secs
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+
     let callback_registry = CallbackRegistry::default();
     for callback_cfg in code_mode.callbacks() {
         let ws_session_lock_clone = ws_session_lock.clone();
@@ -218,11 +328,14 @@ async fn handle_execute_code_request<B: PctxSessionBackend>(
                 let ws_session = ws_session_lock_clone.read().await;
 
                 let callback_res = ws_session
-                    .execute_callback(ExecuteToolParams {
-                        namespace: cfg.namespace,
-                        name: cfg.name,
-                        args,
-                    })
+                    .execute_callback_with_timeout(
+                        ExecuteToolParams {
+                            namespace: cfg.namespace,
+                            name: cfg.name,
+                            args,
+                        },
+                        callback_timeout,
+                    )
                     .await
                     .map_err(|e| e.to_string())?;
 
@@ -253,67 +366,76 @@ async fn handle_execute_code_request<B: PctxSessionBackend>(
         execution_id = %execution_id,
     );
 
+    // progress of upstream tool calls is sent to the client as notifications
+    let progress = WsProgress {
+        request_id: req_id.clone(),
+        sender: sender.clone(),
+    };
+
     tokio::spawn(async move {
-        let code_mode_clone = code_mode.clone();
-        let code_clone = params.code.clone();
-
-        let output = tokio::task::spawn_blocking(move || -> Result<_, anyhow::Error> {
-            let _guard = execution_span.enter();
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to create runtime: {e}"))?;
-
-            // create callback registry to execute callback requests over the same ws which
-            // initiated the request
-            rt.block_on(async {
-                code_mode_clone
-                    .execute(&code_clone, Some(callback_registry))
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Execution error: {e}"))
-            })
-        })
+        let _permit = match admission {
+            Admission::Ready(permit) => permit,
+            Admission::Queued(queued) => queued.acquire().await,
+        };
+        let mut code_mode_clone = code_mode.clone();
+        code_mode_clone.set_progress_handler(Some(Arc::new(progress)));
+        code_mode_clone.set_execution_timeout(execution_timeout);
+        code_mode_clone.set_execution_ids(ExecutionIds {
+            execution_id: Some(execution_id.to_string()),
+            session_id: Some(code_mode_session_id.to_string()),
+        });
+        let execute_input = ExecuteInput {
+            code: params.code,
+            input: params.input,
+            skip_type_check: params.skip_type_check,
+            ..Default::default()
+        };
+
+        // callback requests are executed over the same ws which initiated the request
+        let output = execute_blocking(
+            code_mode_clone,
+            execute_input.clone(),
+            Some(callback_registry),
+        )
+        .instrument(execution_span)
         .await;
 
         let (msg, execution_res) = match output {
-            Ok(Ok(exec_output)) => (
+            Ok(exec_output) => (
                 WsJsonRpcMessage::response(
                     PctxJsonRpcResponse::ExecuteCode(exec_output.clone()),
                     req_id,
                 ),
                 Ok(exec_output),
             ),
-            Ok(Err(e)) => (
-                WsJsonRpcMessage::error(
-                    ErrorData {
-                        code: ErrorCode::INTERNAL_ERROR,
-                        message: format!("Execution failed: {e}").into(),
-                        data: None,
-                    },
-                    req_id,
-                ),
-                Err(anyhow!(e)),
-            ),
             Err(e) => (
                 WsJsonRpcMessage::error(
                     ErrorData {
                         code: ErrorCode::INTERNAL_ERROR,
-                        message: format!("Task join failed: {e}").into(),
+                        message: format!("Execution failed: {e}").into(),
                         data: None,
                     },
                     req_id,
                 ),
-                Err(anyhow!(e)),
+                Err(anyhow!("{e}")),
             ),
         };
 
+        if let Ok(exec_output) = &execution_res
+            && !exec_output.registered_tools.is_empty()
+            && let Err(e) =
+                add_registered_tools(&state, code_mode_session_id, &exec_output.registered_tools)
+                    .await
+        {
+            error!("Failed adding registered tools to session: {e}");
+        }
         if let Err(e) = state
             .backend
             .post_execution(
                 code_mode_session_id,
                 execution_id,
                 code_mode,
-                ExecuteInput { code: params.code },
+                execute_input,
                 execution_res,
             )
             .await
@@ -328,63 +450,133 @@ async fn handle_execute_code_request<B: PctxSessionBackend>(
     Ok(())
 }
 
+/// Adds the tools registered by an execution to its session, their calls in subsequent
+/// executions are forwarded to the client like any callback
+async fn add_registered_tools<B: PctxSessionBackend>(
+    state: &AppState<B>,
+    session_id: Uuid,
+    tools: &[CallbackConfig],
+) -> anyhow::Result<()> {
+    let _lock = state.session_locks.lock(session_id).await;
+    let Some(mut code_mode) = state.backend.get(session_id).await? else {
+        return Ok(());
+    };
+    let existing: Vec<String> = code_mode
+        .callbacks()
+        .iter()
+        .map(CallbackConfig::id)
+        .collect();
+    code_mode.add_callbacks(tools.iter().filter(|t| !existing.contains(&t.id())))?;
+    state.backend.update(session_id, code_mode).await
+}
+
 /// Handle a single WebSocket message
 /// Messages coming from a client, needs to be routed to the correct `WsSession` for handling.
 async fn handle_message<B: PctxSessionBackend>(
     msg: Message,
-    ws_session: Uuid,
+    session: &WsSession,
     state: &AppState<B>,
+    encoding: WsEncoding,
 ) -> Result<(), String> {
-    match msg {
+    let ws_session = session.id;
+    let (text, decoded) = match msg {
         Message::Text(text) => {
             debug!("Received text message from {ws_session}: {text}");
-
-            let jrpc_msg = serde_json::from_str::<WsJsonRpcMessage>(&text)
-                .map_err(|e| format!("Received invalid JsonRpc message from websocket: {e}"))?;
-
-            match jrpc_msg {
-                JsonRpcMessage::Request(req) => match req.request {
-                    PctxJsonRpcRequest::ExecuteCode { params } => {
-                        debug!("Executing code...");
-                        handle_execute_code_request(req.id, params, ws_session, state.clone()).await
-                    }
-                    PctxJsonRpcRequest::ExecuteTool { .. } => {
-                        // the server is only responsible for servicing execute_code requests, execute_tool
-                        // is handled by the client
-                        Err(format!("Received unsupported JsonRpc request: {text}"))
-                    }
-                },
-                JsonRpcMessage::Response(res) => match res.result {
-                    PctxJsonRpcResponse::ExecuteTool(result) => state
-                        .ws_manager
-                        .handle_execute_callback_response(res.id, Ok(result))
-                        .await
-                        .map_err(|()| "Failed to handle execute callback response".to_string()),
-                    PctxJsonRpcResponse::ExecuteCode(_) => {
-                        // the server is only responsible for handling execute_tool responses, execute_tool
-                        // responses should be sent to the client
-                        Err(format!("Received unsupported JsonRpc response: {text}"))
-                    }
-                },
-                JsonRpcMessage::Error(err_msg) => state
-                    .ws_manager
-                    .handle_execute_callback_response(err_msg.id, Err(err_msg.error))
-                    .await
-                    .map_err(|()| "Failed to handle execute callback response".to_string()),
-                JsonRpcMessage::Notification(_) => {
-                    info!("Received JsonRpc Notification: {text}");
-                    Ok(())
-                }
-            }
+            let decoded = WsEncoding::decode_text(&text);
+            (text.to_string(), decoded)
         }
-        Message::Binary(_) => {
-            warn!("Received binary message, ignoring");
-            Ok(())
+        Message::Binary(frame) => {
+            debug!(
+                "Received binary message from {ws_session} ({} bytes)",
+                frame.len()
+            );
+            let decoded = encoding.decode_binary(&frame);
+            let text = decoded
+                .as_ref()
+                .map(|m| json!(m).to_string())
+                .unwrap_or_default();
+            (text, decoded)
         }
         Message::Close(_) => {
             info!("Received close message for session {ws_session}");
+            return Ok(());
+        }
+        Message::Ping(_) | Message::Pong(_) => return Ok(()),
+    };
+    let jrpc_msg =
+        decoded.map_err(|e| format!("Received invalid JsonRpc message from websocket: {e}"))?;
+
+    match jrpc_msg {
+        JsonRpcMessage::Request(req) => match req.request {
+            PctxJsonRpcRequest::ExecuteCode { params } => {
+                debug!("Executing code...");
+                handle_execute_code_request(req.id, params, ws_session, state.clone()).await
+            }
+            PctxJsonRpcRequest::ExecuteTool { .. } => {
+                // the server is only responsible for servicing execute_code requests, execute_tool
+                // is handled by the client
+                Err(format!("Received unsupported JsonRpc request: {text}"))
+            }
+        },
+        JsonRpcMessage::Response(res) => match res.result {
+            PctxJsonRpcResponse::ExecuteTool(result) => state
+                .ws_manager
+                .handle_execute_callback_response(res.id, Ok(result))
+                .await
+                .map_err(|()| "Failed to handle execute callback response".to_string()),
+            PctxJsonRpcResponse::ExecuteCode(_) => {
+                // the server is only responsible for handling execute_tool responses, execute_tool
+                // responses should be sent to the client
+                Err(format!("Received unsupported JsonRpc response: {text}"))
+            }
+        },
+        JsonRpcMessage::Error(err_msg) => state
+            .ws_manager
+            .handle_execute_callback_response(err_msg.id, Err(err_msg.error))
+            .await
+            .map_err(|()| "Failed to handle execute callback response".to_string()),
+        JsonRpcMessage::Notification(notification) => {
+            match notification.notification {
+                PctxJsonRpcNotification::Subscribe { params } => {
+                    debug!("Session {ws_session} subscribed to {:?}", params.topics);
+                    session.subscribe(params.topics);
+                }
+                PctxJsonRpcNotification::Unsubscribe { params } => {
+                    debug!("Session {ws_session} unsubscribed from {:?}", params.topics);
+                    session.unsubscribe(&params.topics);
+                }
+                PctxJsonRpcNotification::Progress { .. }
+                | PctxJsonRpcNotification::Queued { .. }
+                | PctxJsonRpcNotification::Event { .. } => {
+                    info!("Received JsonRpc Notification: {text}");
+                }
+            }
             Ok(())
         }
-        Message::Ping(_) | Message::Pong(_) => Ok(()),
+    }
+}
+
+/// Sends progress of upstream tool calls during an `execute_code` request to the client
+#[derive(Debug)]
+struct WsProgress {
+    request_id: RequestId,
+    sender: mpsc::UnboundedSender<WsJsonRpcMessage>,
+}
+
+#[async_trait]
+impl ProgressHandler for WsProgress {
+    async fn on_progress(&self, server: &str, params: ProgressNotificationParam) {
+        let notification = WsJsonRpcMessage::notification(PctxJsonRpcNotification::Progress {
+            params: ProgressParams {
+                request_id: self.request_id.clone(),
+                server: server.to_string(),
+                progress: params.progress,
+                total: params.total,
+                message: params.message,
+            },
+        });
+        if let Err(e) = self.sender.send(notification) {
+            debug!("Failed to send progress notification: {e}");
+        }
     }
 }
@@ -1,5 +1,5 @@
 mod handler;
-// mod protocol;
+mod protocol;
 
 pub use handler::*;
-// pub use protocol::*;
+pub use protocol::*;
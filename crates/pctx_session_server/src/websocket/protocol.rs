@@ -0,0 +1,247 @@
+//! Encoding of JSON-RPC messages on the WebSocket
+//!
+//! Clients negotiate the encoding with the `Sec-WebSocket-Protocol` header, listing
+//! `pctx.msgpack`, `pctx.cbor` or `pctx.json` by preference. JSON messages are sent as
+//! text frames. MessagePack & CBOR messages are sent as binary frames whose first byte
+//! is a flag: `0` for an uncompressed payload, `1` for a zlib compressed payload, used
+//! for payloads larger than [`COMPRESSION_THRESHOLD`]. Text frames are always accepted.
+
+use std::io::{Read, Write};
+
+use axum::{
+    extract::ws::Message,
+    http::{HeaderMap, header::SEC_WEBSOCKET_PROTOCOL},
+};
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+
+use rmcp::model::ErrorCode;
+
+use crate::model::{EventParams, PctxJsonRpcNotification, WsJsonRpcMessage};
+
+pub static JSON_PROTOCOL: &str = "pctx.json";
+pub static MSGPACK_PROTOCOL: &str = "pctx.msgpack";
+pub static CBOR_PROTOCOL: &str = "pctx.cbor";
+
+/// Topic of events sent when the tools or servers of a code mode session changed
+pub static TOOLS_CHANGED_TOPIC: &str = "tools/list_changed";
+/// Topic of the event broadcast to every session when the server shuts down
+pub static SHUTDOWN_TOPIC: &str = "server/shutdown";
+
+/// JSON-RPC error code of `execute_code` requests rejected because the execution queue
+/// is full, clients should retry later
+pub const EXECUTION_QUEUE_FULL: ErrorCode = ErrorCode(-32029);
+
+/// Notification of an event, sent to clients subscribed to `topic` or broadcast to all
+pub fn event(topic: &str, data: serde_json::Value) -> WsJsonRpcMessage {
+    WsJsonRpcMessage::notification(PctxJsonRpcNotification::Event {
+        params: EventParams {
+            topic: topic.to_string(),
+            data,
+        },
+    })
+}
+
+/// Binary payloads larger than this many bytes are compressed
+pub const COMPRESSION_THRESHOLD: usize = 16 * 1024;
+
+const UNCOMPRESSED: u8 = 0;
+const ZLIB: u8 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProtocolError {
+    #[error("Invalid JSON message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid MessagePack message: {0}")]
+    MsgPack(String),
+    #[error("Invalid CBOR message: {0}")]
+    Cbor(String),
+    #[error("Invalid compression of binary message: {0}")]
+    Compression(#[from] std::io::Error),
+    #[error("Empty binary message")]
+    Empty,
+    #[error("Unknown binary frame flag {0}")]
+    UnknownFlag(u8),
+    #[error("Received binary message on a JSON connection")]
+    UnexpectedBinary,
+}
+
+/// Encoding of the messages of a WebSocket connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WsEncoding {
+    #[default]
+    Json,
+    MsgPack,
+    Cbor,
+}
+
+impl WsEncoding {
+    /// WebSocket subprotocol selecting the encoding
+    pub fn protocol(self) -> &'static str {
+        match self {
+            WsEncoding::Json => JSON_PROTOCOL,
+            WsEncoding::MsgPack => MSGPACK_PROTOCOL,
+            WsEncoding::Cbor => CBOR_PROTOCOL,
+        }
+    }
+
+    pub fn from_protocol(protocol: &str) -> Option<Self> {
+        [WsEncoding::Json, WsEncoding::MsgPack, WsEncoding::Cbor]
+            .into_iter()
+            .find(|e| e.protocol() == protocol)
+    }
+
+    /// First supported encoding of the subprotocols requested in the upgrade request,
+    /// JSON if none is supported
+    pub fn negotiate(headers: &HeaderMap) -> Self {
+        headers
+            .get_all(SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .find_map(|p| Self::from_protocol(p.trim()))
+            .unwrap_or_default()
+    }
+
+    /// Encodes a message as a frame
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message cannot be serialized
+    pub fn encode(self, msg: &WsJsonRpcMessage) -> Result<Message, ProtocolError> {
+        let payload = match self {
+            WsEncoding::Json => return Ok(Message::Text(serde_json::to_string(msg)?.into())),
+            WsEncoding::MsgPack => {
+                rmp_serde::to_vec_named(msg).map_err(|e| ProtocolError::MsgPack(e.to_string()))?
+            }
+            WsEncoding::Cbor => {
+                let mut buf = vec![];
+                ciborium::into_writer(msg, &mut buf)
+                    .map_err(|e| ProtocolError::Cbor(e.to_string()))?;
+                buf
+            }
+        };
+
+        let mut frame = Vec::with_capacity(payload.len() + 1);
+        if payload.len() > COMPRESSION_THRESHOLD {
+            frame.push(ZLIB);
+            let mut encoder = ZlibEncoder::new(frame, Compression::fast());
+            encoder.write_all(&payload)?;
+            frame = encoder.finish()?;
+        } else {
+            frame.push(UNCOMPRESSED);
+            frame.extend_from_slice(&payload);
+        }
+
+        Ok(Message::Binary(frame.into()))
+    }
+
+    /// Decodes a JSON text frame
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a valid message
+    pub fn decode_text(text: &str) -> Result<WsJsonRpcMessage, ProtocolError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Decodes a binary frame
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection uses JSON or the frame is not a valid message
+    pub fn decode_binary(self, frame: &[u8]) -> Result<WsJsonRpcMessage, ProtocolError> {
+        if self == WsEncoding::Json {
+            return Err(ProtocolError::UnexpectedBinary);
+        }
+
+        let (flag, payload) = frame.split_first().ok_or(ProtocolError::Empty)?;
+        let decompressed;
+        let payload = match *flag {
+            UNCOMPRESSED => payload,
+            ZLIB => {
+                let mut buf = vec![];
+                ZlibDecoder::new(payload).read_to_end(&mut buf)?;
+                decompressed = buf;
+                &decompressed
+            }
+            other => return Err(ProtocolError::UnknownFlag(other)),
+        };
+
+        // decoded through JSON values, so messages are read exactly like text frames
+        let value: serde_json::Value =
+            match self {
+                WsEncoding::MsgPack => rmp_serde::from_slice(payload)
+                    .map_err(|e| ProtocolError::MsgPack(e.to_string()))?,
+                WsEncoding::Cbor => ciborium::from_reader(payload)
+                    .map_err(|e| ProtocolError::Cbor(e.to_string()))?,
+                WsEncoding::Json => unreachable!("JSON binary frames are rejected"),
+            };
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::ws::Message,
+        http::{HeaderMap, HeaderValue},
+    };
+    use rmcp::model::RequestId;
+    use serde_json::json;
+
+    use super::{COMPRESSION_THRESHOLD, WsEncoding};
+    use crate::model::{ExecuteToolParams, PctxJsonRpcRequest, WsJsonRpcMessage};
+
+    fn request(args: serde_json::Value) -> WsJsonRpcMessage {
+        WsJsonRpcMessage::request(
+            PctxJsonRpcRequest::ExecuteTool {
+                params: ExecuteToolParams {
+                    namespace: "files".into(),
+                    name: "upload".into(),
+                    args: Some(args),
+                },
+            },
+            RequestId::String("req-1".into()),
+        )
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(WsEncoding::negotiate(&HeaderMap::new()), WsEncoding::Json);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "sec-websocket-protocol",
+            HeaderValue::from_static("other, pctx.cbor, pctx.msgpack"),
+        );
+        assert_eq!(WsEncoding::negotiate(&headers), WsEncoding::Cbor);
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let small = request(json!({"name": "a.txt"}));
+        let large = request(json!({"data": "a".repeat(COMPRESSION_THRESHOLD * 2)}));
+
+        for encoding in [WsEncoding::MsgPack, WsEncoding::Cbor] {
+            for msg in [&small, &large] {
+                let Message::Binary(frame) = encoding.encode(msg).unwrap() else {
+                    panic!("expected binary frame");
+                };
+                let decoded = encoding.decode_binary(&frame).unwrap();
+                assert_eq!(json!(decoded), json!(msg));
+            }
+
+            let Message::Binary(frame) = encoding.encode(&large).unwrap() else {
+                panic!("expected binary frame");
+            };
+            assert_eq!(frame[0], 1);
+            assert!(frame.len() < COMPRESSION_THRESHOLD);
+        }
+
+        assert!(WsEncoding::Json.decode_binary(&[0]).is_err());
+        assert!(matches!(
+            WsEncoding::Json.encode(&small).unwrap(),
+            Message::Text(_)
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+//! Remote execution worker
+//!
+//! Workers execute the code of servers configured with
+//! [`RemoteWorkers`](pctx_code_mode::RemoteWorkers), so untrusted code runs in a separate
+//! pool of processes or machines. Executions beyond the worker's concurrency are
+//! queued, and rejected with `503 Service Unavailable` once the queue is full so the
+//! server fails over to another worker.
+//!
+//! Circuit breakers & the results of tools cached with a `ttl_secs` are shared between
+//! the executions of a worker.
+//!
+//! Workers only connect to HTTP MCP servers, requests with stdio servers are rejected so
+//! a caller cannot spawn arbitrary commands on the worker. Workers listening on
+//! non-loopback addresses require auth tokens.
+
+use std::net::IpAddr;
+
+use anyhow::Result;
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use pctx_code_execution_runtime::{CircuitBreakers, ToolCache};
+use pctx_code_mode::run_blocking;
+use pctx_executor::{Isolation, RemoteRequest, remote::EXECUTE_PATH};
+use tower_http::trace::TraceLayer;
+use tracing::{info, warn};
+
+use crate::{
+    Admission, ExecutionQueue,
+    auth::AuthConfig,
+    model::{ErrorCode, ErrorData},
+    routes, server,
+};
+
+#[derive(Clone, Default)]
+pub struct WorkerState {
+    pub auth: AuthConfig,
+    pub executions: ExecutionQueue,
+    pub isolation: Isolation,
+    pub circuit_breakers: CircuitBreakers,
+    pub tool_cache: ToolCache,
+}
+
+/// Start the worker
+///
+/// # Errors
+///
+/// This function will return an error if the worker would accept unauthenticated
+/// executions on a non-loopback address, or axum fails binding to the provided host/port
+pub async fn start_worker(host: &str, port: u16, state: WorkerState) -> Result<()> {
+    anyhow::ensure!(
+        state.auth.is_enabled() || is_loopback(host),
+        "Refusing to start a worker without auth tokens on {host}, bind to a loopback address or set auth tokens"
+    );
+
+    let addr = format!("{host}:{port}");
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+    info!("pctx worker listening on http://{addr}");
+
+    axum::serve(listener, create_worker_router(state))
+        .with_graceful_shutdown(server::shutdown_signal())
+        .await?;
+
+    Ok(())
+}
+
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Create the Axum router of the worker
+pub fn create_worker_router(state: WorkerState) -> Router {
+    Router::new()
+        .route("/health", get(routes::health))
+        .route(EXECUTE_PATH, post(execute))
+        .with_state(state)
+        .layer(TraceLayer::new_for_http())
+}
+
+fn error(status: StatusCode, code: ErrorCode, message: String) -> Response {
+    (
+        status,
+        Json(ErrorData {
+            code,
+            message,
+            details: None,
+        }),
+    )
+        .into_response()
+}
+
+async fn execute(
+    State(state): State<WorkerState>,
+    headers: HeaderMap,
+    Json(request): Json<RemoteRequest>,
+) -> Response {
+    if let Err(e) = state.auth.authenticate(&headers) {
+        warn!("Rejecting unauthenticated execution: {e}");
+        return error(
+            StatusCode::UNAUTHORIZED,
+            ErrorCode::Unauthorized,
+            e.to_string(),
+        );
+    }
+    if let Some(server) = request.servers.iter().find(|s| s.stdio().is_some()) {
+        warn!(server = %server.name, "Rejecting execution with a stdio MCP server");
+        return error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::Execution,
+            format!(
+                "MCP server `{}` uses the stdio transport, workers only connect to HTTP MCP servers",
+                server.name
+            ),
+        );
+    }
+
+    let _permit = match state.executions.admit() {
+        Ok(Admission::Ready(permit)) => permit,
+        Ok(Admission::Queued(queued)) => queued.acquire().await,
+        Err(e) => {
+            return error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::LimitExceeded,
+                e.to_string(),
+            );
+        }
+    };
+
+    let output = run_blocking(move || async move {
+        let options = request
+            .options()
+            .with_isolation(state.isolation)
+            .with_circuit_breakers(state.circuit_breakers)
+            .with_tool_cache(state.tool_cache);
+        Ok(pctx_executor::execute(&request.code, options).await?)
+    })
+    .await;
+
+    match output {
+        Ok(result) => Json(result).into_response(),
+        Err(pctx_code_mode::Error::Execution(e)) => error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::Execution,
+            format!("Execution error: {e}"),
+        ),
+        Err(e) => error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::Internal,
+            e.to_string(),
+        ),
+    }
+}
@@ -1,8 +1,16 @@
 mod utils;
 
 use crate::utils::{callback_tools, connect_websocket, create_test_server_with_session};
-use pctx_code_mode::model::CallbackConfig;
-use pctx_session_server::{CODE_MODE_SESSION_HEADER, model::WsJsonRpcMessage};
+use axum_test::TestServer;
+use pctx_code_mode::{CodeMode, RemoteWorkers, model::CallbackConfig};
+use pctx_config::server::{ServerConfig, ToolConfig};
+use pctx_executor::ExecuteOptions;
+use pctx_session_server::{
+    CODE_MODE_SESSION_HEADER,
+    auth::AuthConfig,
+    model::WsJsonRpcMessage,
+    worker::{WorkerState, create_worker_router, start_worker},
+};
 use serde_json::json;
 use serial_test::serial;
 use similar_asserts::assert_serde_eq;
@@ -45,6 +53,43 @@ async fn test_exec_code_only() {
     );
 }
 
+#[tokio::test]
+#[serial]
+async fn test_exec_code_with_input() {
+    let (session_id, server, _) = create_test_server_with_session().await;
+    let mut ws = connect_websocket(&server, session_id)
+        .await
+        .into_websocket()
+        .await;
+
+    ws.send_json(&json!({
+        "jsonrpc": "2.0",
+        "id": "test-1",
+        "method": "execute_code",
+        "params": {
+            "code": "async function run(input: { a: number; b: number }) { return input.a + input.b; }",
+            "input": { "a": 1, "b": 2 }
+        }
+    }))
+    .await;
+
+    let response: serde_json::Value = ws.receive_json().await;
+
+    assert_serde_eq!(
+        response,
+        json!({
+            "jsonrpc": "2.0",
+            "id": "test-1",
+            "result": {
+                "success": true,
+                "stdout": "",
+                "stderr": "",
+                "output": 3
+            }
+        })
+    );
+}
+
 #[tokio::test]
 #[serial]
 async fn test_exec_code_console_output() {
@@ -369,6 +414,12 @@ async fn test_exec_type_error_with_rich_diagnostics() {
     let response: serde_json::Value = ws.receive_json().await;
 
     assert_eq!(response["result"]["success"], false);
+    assert!(
+        response["result"]["diagnostics"]
+            .as_array()
+            .is_some_and(|d| !d.is_empty()),
+        "Should return the structured diagnostics: {response}"
+    );
 
     // Verify the diagnostic points to the exact error location and has all the information
     // Error is at line 3 (where "wrong" is passed), column 45 (the "wrong" string literal)
@@ -396,3 +447,96 @@ async fn test_exec_type_error_with_rich_diagnostics() {
         "Should show exact type mismatch: {stderr}"
     );
 }
+
+#[tokio::test]
+#[serial]
+async fn test_remote_worker() {
+    let worker = TestServer::builder()
+        .http_transport()
+        .build(create_worker_router(WorkerState {
+            auth: AuthConfig::default().with_tokens(vec!["worker-secret".into()]),
+            ..Default::default()
+        }))
+        .expect("Failed starting test worker");
+    let worker_url = worker.server_address().unwrap().to_string();
+
+    worker
+        .post("/execute")
+        .json(&json!({ "code": "export default 1;" }))
+        .await
+        .assert_status_unauthorized();
+
+    // the first worker is unreachable, executions fail over to the test worker
+    let workers = RemoteWorkers::new(vec!["http://127.0.0.1:1".into(), worker_url])
+        .with_token("worker-secret");
+    let code_mode = CodeMode::default().with_remote_workers(workers);
+    for _ in 0..2 {
+        let output = code_mode
+            .execute("async function run() { return 1 + 1; }", None)
+            .await
+            .unwrap();
+        assert!(output.success);
+        assert_eq!(output.output, Some(json!(2)));
+    }
+}
+
+#[tokio::test]
+async fn test_remote_worker_rejects_stdio_servers() {
+    let worker = TestServer::builder()
+        .http_transport()
+        .build(create_worker_router(WorkerState::default()))
+        .expect("Failed starting test worker");
+
+    let res = worker
+        .post("/execute")
+        .json(&json!({
+            "code": "export default 1;",
+            "servers": [{ "name": "evil", "command": "sh", "args": ["-c", "touch /tmp/pwned"] }]
+        }))
+        .await;
+    res.assert_status_bad_request();
+    res.assert_json_contains(&json!({"code": "execution"}));
+}
+
+#[tokio::test]
+async fn test_remote_workers_reject_shared_limits() {
+    let mut server =
+        ServerConfig::new("upstream".into(), "http://127.0.0.1:1/mcp".parse().unwrap());
+    server.tools.insert(
+        "search".into(),
+        ToolConfig {
+            rate_limit_per_minute: Some(30),
+            ..Default::default()
+        },
+    );
+
+    // rejected before any worker is contacted
+    let options = ExecuteOptions::new()
+        .with_servers(vec![server])
+        .with_remote_workers(RemoteWorkers::new(vec!["http://127.0.0.1:1".into()]));
+    let err = pctx_executor::execute("export default 1;", options)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("rate limited"), "{err}");
+}
+
+#[tokio::test]
+async fn test_worker_requires_token_off_loopback() {
+    let err = start_worker("0.0.0.0", 0, WorkerState::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("without auth tokens"), "{err}");
+
+    // binding with tokens passes the check, failing on the invalid host instead
+    let err = start_worker(
+        "invalid host",
+        0,
+        WorkerState {
+            auth: AuthConfig::default().with_tokens(vec!["worker-secret".into()]),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(!err.to_string().contains("without auth tokens"), "{err}");
+}
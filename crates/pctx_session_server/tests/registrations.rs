@@ -1,5 +1,9 @@
+use axum_test::TestServer;
 use pctx_code_mode::model::CallbackConfig;
-use pctx_session_server::CODE_MODE_SESSION_HEADER;
+use pctx_session_server::{
+    AppState, CODE_MODE_SESSION_HEADER, SessionLimits, TenantLimits, auth::AuthConfig,
+    server::create_router,
+};
 use serde_json::json;
 
 use crate::utils::{
@@ -143,3 +147,85 @@ async fn test_register_tools_not_shared() {
     details_res.assert_status_ok();
     details_res.assert_json_contains(&json!({"functions": []}));
 }
+
+#[tokio::test]
+async fn test_register_tools_limit() {
+    let state = AppState::new_local().with_limits(SessionLimits {
+        max_tools: Some(2),
+        ..Default::default()
+    });
+    let server = TestServer::builder()
+        .http_transport()
+        .build(create_router(state))
+        .unwrap();
+    let session_id = create_session(&server).await;
+    let test_tools: Vec<CallbackConfig> = callback_tools().into_iter().map(|(c, _)| c).collect();
+    assert!(test_tools.len() > 2);
+
+    let res = server
+        .post("/register/tools")
+        .add_header(CODE_MODE_SESSION_HEADER, session_id.to_string())
+        .json(&json!({"tools": &test_tools[..2]}))
+        .await;
+    res.assert_status_ok();
+
+    let res = server
+        .post("/register/tools")
+        .add_header(CODE_MODE_SESSION_HEADER, session_id.to_string())
+        .json(&json!({"tools": &test_tools[2..3]}))
+        .await;
+    res.assert_status_bad_request();
+    res.assert_json_contains(&json!({"code": "limit_exceeded"}));
+}
+
+#[tokio::test]
+async fn test_tenant_isolation() {
+    let state = AppState::new_local()
+        .with_auth(
+            AuthConfig::default()
+                .with_tenant_token("acme", "acme-secret")
+                .with_tenant_token("globex", "globex-secret"),
+        )
+        .with_tenant_limits(TenantLimits {
+            max_sessions: Some(1),
+            ..Default::default()
+        });
+    let server = TestServer::builder()
+        .http_transport()
+        .build(create_router(state))
+        .unwrap();
+
+    let res = server
+        .post("/code-mode/session/create")
+        .add_header("authorization", "Bearer acme-secret")
+        .await;
+    res.assert_status_ok();
+    let session_id = res.json::<serde_json::Value>()["session_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    server
+        .post("/code-mode/session/create")
+        .add_header("authorization", "Bearer acme-secret")
+        .await
+        .assert_status(axum::http::StatusCode::TOO_MANY_REQUESTS);
+
+    let test_tools: Vec<CallbackConfig> = callback_tools().into_iter().map(|(c, _)| c).collect();
+    let res = server
+        .post("/register/tools")
+        .add_header("authorization", "Bearer globex-secret")
+        .add_header(CODE_MODE_SESSION_HEADER, session_id.clone())
+        .json(&json!({"tools": test_tools}))
+        .await;
+    res.assert_status_not_found();
+    res.assert_json_contains(&json!({"code": "invalid_session"}));
+
+    server
+        .post("/register/tools")
+        .add_header("authorization", "Bearer acme-secret")
+        .add_header(CODE_MODE_SESSION_HEADER, session_id)
+        .json(&json!({"tools": test_tools}))
+        .await
+        .assert_status_ok();
+}
@@ -6,7 +6,8 @@ use axum_test::{TestResponse, TestServer};
 use pctx_code_execution_runtime::CallbackFn;
 use pctx_code_mode::{CodeMode, model::CallbackConfig};
 use pctx_session_server::{
-    AppState, LocalBackend, PctxSessionBackend, model::CreateSessionResponse, server::create_router,
+    AppState, LocalBackend, PctxSessionBackend, Tenant, model::CreateSessionResponse,
+    server::create_router,
 };
 use serde::Deserialize;
 use serde_json::json;
@@ -31,7 +32,7 @@ pub(crate) async fn create_test_server_with_session() -> (Uuid, TestServer, AppS
     let session_id = Uuid::new_v4();
     state
         .backend
-        .insert(session_id, CodeMode::default())
+        .insert(session_id, CodeMode::default(), Tenant::default())
         .await
         .expect("Failed adding test codemode session");
     (
@@ -2,7 +2,15 @@
 
 mod utils;
 
-use axum_test::WsMessage;
+use axum_test::{TestServer, WsMessage};
+use pctx_code_mode::CodeMode;
+use pctx_session_server::{
+    AppState, PctxSessionBackend, Tenant,
+    auth::AuthConfig,
+    server::create_router,
+    websocket::{SHUTDOWN_TOPIC, TOOLS_CHANGED_TOPIC, event},
+};
+use serde_json::json;
 use similar_asserts::assert_eq;
 use uuid::Uuid;
 
@@ -107,3 +115,105 @@ async fn test_websocket_close() {
 
     assert!(state.ws_manager.list_sessions().await.is_empty());
 }
+
+/// Tests unauthenticated websocket upgrades are rejected if auth is configured
+#[tokio::test]
+async fn test_websocket_connection_unauthenticated() {
+    let state =
+        AppState::new_local().with_auth(AuthConfig::default().with_tokens(vec!["secret".into()]));
+    let session_id = Uuid::new_v4();
+    state
+        .backend
+        .insert(session_id, CodeMode::default(), Tenant::default())
+        .await
+        .unwrap();
+    let server = TestServer::builder()
+        .http_transport()
+        .build(create_router(state.clone()))
+        .unwrap();
+
+    connect_websocket(&server, session_id)
+        .await
+        .assert_status_unauthorized();
+    assert!(state.ws_manager.list_sessions().await.is_empty());
+
+    let _ws = server
+        .get_websocket("/ws")
+        .add_header("x-code-mode-session", session_id.to_string())
+        .add_header("authorization", "Bearer secret")
+        .await
+        .into_websocket()
+        .await;
+    assert_eq!(state.ws_manager.list_sessions().await.len(), 1);
+
+    // health checks stay open
+    server.get("/health").await.assert_status_ok();
+}
+
+/// Tests sessions that received nothing for longer than the idle timeout are reaped
+#[tokio::test]
+async fn test_reap_stale_sessions() {
+    let manager = WsManager::new(HeartbeatConfig {
+        ping_interval: std::time::Duration::from_millis(10),
+        idle_timeout: std::time::Duration::from_millis(50),
+    });
+    let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+    let stale = WsSession::new(tx.clone(), Uuid::new_v4());
+    let stale_id = manager.add(stale).await;
+
+    tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+    let alive = WsSession::new(tx, Uuid::new_v4());
+    let alive_id = manager.add(alive).await;
+
+    assert_eq!(manager.reap_stale().await, vec![stale_id]);
+    assert_eq!(manager.list_sessions().await, vec![alive_id]);
+}
+
+/// Tests events are published to subscribed sessions & broadcast to all sessions
+#[tokio::test]
+async fn test_websocket_events() {
+    let (session_id, server, state) = create_test_server_with_session().await;
+    let mut ws = connect_websocket(&server, session_id)
+        .await
+        .into_websocket()
+        .await;
+
+    ws.send_json(&json!({
+        "jsonrpc": "2.0",
+        "method": "subscribe",
+        "params": {"topics": [TOOLS_CHANGED_TOPIC]}
+    }))
+    .await;
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    assert_eq!(
+        state.ws_manager.publish("other/topic", json!(null)).await,
+        0
+    );
+    assert_eq!(
+        state
+            .ws_manager
+            .publish(TOOLS_CHANGED_TOPIC, json!({"tools": ["a.b"]}))
+            .await,
+        1
+    );
+    let received: serde_json::Value = ws.receive_json().await;
+    assert_eq!(
+        received,
+        json!({
+            "jsonrpc": "2.0",
+            "method": "event",
+            "params": {"topic": TOOLS_CHANGED_TOPIC, "data": {"tools": ["a.b"]}}
+        })
+    );
+
+    assert_eq!(
+        state
+            .ws_manager
+            .broadcast(event(SHUTDOWN_TOPIC, json!(null)))
+            .await,
+        1
+    );
+    let received: serde_json::Value = ws.receive_json().await;
+    assert_eq!(received["params"]["topic"], json!(SHUTDOWN_TOPIC));
+}
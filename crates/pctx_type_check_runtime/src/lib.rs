@@ -15,6 +15,7 @@
 //! - **Fast Startup**: TypeScript compiler embedded in V8 snapshot (~20s build time, instant runtime)
 //! - **JavaScript Compatible**: Filters TypeScript-only errors to allow valid JavaScript code
 //! - **Async Support**: Provides both sync and async APIs
+//! - **Configurable Strictness**: Compiler options via [`type_check_with_options`]
 //!
 //! ## Quick Start
 //!
@@ -104,6 +105,9 @@ pub struct Diagnostic {
     pub severity: String,
     /// TypeScript diagnostic code (e.g., 2322 for type mismatch)
     pub code: Option<u32>,
+    /// Suggested fix (e.g. "did you mean 'title'?"), from compiler hints & the known properties of the expected type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
 }
 
 /// Result of a type checking operation
@@ -115,6 +119,31 @@ pub struct CheckResult {
     pub diagnostics: Vec<Diagnostic>,
 }
 
+/// TypeScript compiler options for a type check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeCheckOptions {
+    /// Enables all strict type checking flags
+    pub strict: bool,
+    /// Report expressions & declarations with an implied `any` type
+    pub no_implicit_any: bool,
+    /// ECMAScript target version (e.g. `ES2020`), unknown targets fall back on `ES2020`
+    pub target: String,
+    /// Global names declared as `any`
+    pub allowed_globals: Vec<String>,
+}
+
+impl Default for TypeCheckOptions {
+    fn default() -> Self {
+        Self {
+            strict: true,
+            no_implicit_any: true,
+            target: "ES2020".into(),
+            allowed_globals: vec![],
+        }
+    }
+}
+
 /// Pre-compiled V8 snapshot containing the TypeScript compiler
 ///
 /// This snapshot is created at build time and includes:
@@ -175,6 +204,21 @@ static TYPE_CHECK_MUTEX: std::sync::LazyLock<Mutex<()>> = std::sync::LazyLock::n
 /// # }
 /// ```
 pub async fn type_check(code: &str) -> Result<CheckResult> {
+    type_check_with_options(code, &TypeCheckOptions::default()).await
+}
+
+/// Type check TypeScript code with the provided compiler options
+///
+/// See [`type_check`], which type checks with the default (strict) options.
+///
+/// # Errors
+///
+/// Returns [`TypeCheckError::ParseError`] if the code has syntax errors.
+/// Returns [`TypeCheckError::InternalError`] if the type checking runtime fails.
+pub async fn type_check_with_options(
+    code: &str,
+    options: &TypeCheckOptions,
+) -> Result<CheckResult> {
     // First do a quick syntax check with deno_ast
     let parse_result = deno_ast::parse_module(deno_ast::ParseParams {
         specifier: deno_ast::ModuleSpecifier::parse("file:///check.ts")
@@ -196,6 +240,7 @@ pub async fn type_check(code: &str) -> Result<CheckResult> {
                 column: None,
                 severity: "error".to_string(),
                 code: None,
+                suggestion: None,
             }],
         });
     }
@@ -215,12 +260,15 @@ pub async fn type_check(code: &str) -> Result<CheckResult> {
     // Call the type checking function from the runtime
     let code_json =
         serde_json::to_string(code).map_err(|e| TypeCheckError::InternalError(e.to_string()))?;
+    let options_json =
+        serde_json::to_string(options).map_err(|e| TypeCheckError::InternalError(e.to_string()))?;
 
     let check_script = format!(
         r"
         (function() {{
             const code = {code_json};
-            return globalThis.typeCheckCode(code);
+            const options = {options_json};
+            return globalThis.typeCheckCode(code, options);
         }})()
         "
     );
@@ -277,6 +325,7 @@ pub async fn type_check(code: &str) -> Result<CheckResult> {
 ///     column: Some(1),
 ///     severity: "error".to_string(),
 ///     code: Some(2322),
+///     suggestion: None,
 /// };
 /// assert!(is_relevant_error(&type_error));
 ///
@@ -287,6 +336,7 @@ pub async fn type_check(code: &str) -> Result<CheckResult> {
 ///     column: Some(1),
 ///     severity: "error".to_string(),
 ///     code: Some(2580),
+///     suggestion: None,
 /// };
 /// assert!(!is_relevant_error(&console_error));
 /// ```
@@ -332,6 +382,60 @@ mod tests {
         assert!(!result.diagnostics.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_type_check_with_options() {
+        let code = r"
+            let name: string = undefined;
+            export default name;
+        ";
+        let strict = type_check(code).await.expect("type check should not fail");
+        assert!(
+            strict
+                .diagnostics
+                .iter()
+                .any(|d| d.code == Some(2322) && is_relevant_error(d)),
+            "strict mode should reject undefined for string"
+        );
+
+        let options = TypeCheckOptions {
+            strict: false,
+            no_implicit_any: false,
+            target: "ES2022".into(),
+            allowed_globals: vec!["describe".into()],
+        };
+        let loose = type_check_with_options(code, &options)
+            .await
+            .expect("type check should not fail");
+        assert!(loose.success, "{:?}", loose.diagnostics);
+    }
+
+    #[tokio::test]
+    async fn test_type_check_suggestions() {
+        let code = r#"
+            function createIssue(input: { title: string; body?: string }) {}
+            createIssue({ titel: "bug" });
+        "#;
+        let result = type_check(code).await.expect("type check should not fail");
+        let unknown = result
+            .diagnostics
+            .iter()
+            .find(|d| d.code == Some(2353))
+            .expect("unknown property should be reported");
+        assert_eq!(unknown.suggestion.as_deref(), Some("did you mean 'title'?"));
+
+        let code = r#"
+            function createIssue(input: { title: string; body?: string }) {}
+            createIssue({ body: "bug" });
+        "#;
+        let result = type_check(code).await.expect("type check should not fail");
+        assert!(
+            result
+                .diagnostics
+                .iter()
+                .any(|d| { d.suggestion.as_deref() == Some("add the required property 'title'") })
+        );
+    }
+
     #[test]
     fn test_is_relevant_error_function() {
         // Relevant error (type mismatch TS2322)
@@ -341,6 +445,7 @@ mod tests {
             column: Some(1),
             severity: "error".to_string(),
             code: Some(2322),
+            suggestion: None,
         };
         assert!(is_relevant_error(&relevant), "TS2322 should be relevant");
 
@@ -351,6 +456,7 @@ mod tests {
             column: Some(1),
             severity: "error".to_string(),
             code: Some(2580),
+            suggestion: None,
         };
         assert!(
             !is_relevant_error(&irrelevant_console),
@@ -364,6 +470,7 @@ mod tests {
             column: Some(1),
             severity: "error".to_string(),
             code: Some(2591),
+            suggestion: None,
         };
         assert!(
             !is_relevant_error(&irrelevant_promise),
@@ -377,6 +484,7 @@ mod tests {
             column: Some(1),
             severity: "error".to_string(),
             code: Some(7006),
+            suggestion: None,
         };
         assert!(
             !is_relevant_error(&irrelevant_implicit_any),
@@ -390,6 +498,7 @@ mod tests {
             column: Some(1),
             severity: "error".to_string(),
             code: None,
+            suggestion: None,
         };
         assert!(
             is_relevant_error(&no_code),
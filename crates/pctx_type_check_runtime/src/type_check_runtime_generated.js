@@ -40,21 +40,141 @@ declare function callMCPTool<T = any>(call: MCPToolProps): Promise<T>;
 declare function invokeCallback<T = any>(call: InvokeCallbackProps): Promise<T>;
 `;
 
+/**
+ * Resolves a target name (e.g. "ES2022", "esnext") to a TypeScript script target,
+ * falling back on ES2020 for unknown targets
+ *
+ * @param {string | undefined} target
+ */
+function scriptTarget(target) {
+  if (target) {
+    const key = Object.keys(ts.ScriptTarget).find(
+      (k) => k.toLowerCase() === target.toLowerCase(),
+    );
+    if (key !== undefined && typeof ts.ScriptTarget[key] === "number") {
+      return ts.ScriptTarget[key];
+    }
+  }
+  return ts.ScriptTarget.ES2020;
+}
+
+/**
+ * Declarations of the allowed globals as `any`, skipping names that are not identifiers
+ *
+ * @param {{allowedGlobals?: string[]}} options
+ */
+function allowedGlobalsLib(options) {
+  return (options.allowedGlobals ?? [])
+    .filter((name) => /^[A-Za-z_$][A-Za-z0-9_$]*$/.test(name))
+    .map((name) => `\ndeclare var ${name}: any;`)
+    .join("");
+}
+
+/**
+ * Edit distance between two strings, used to suggest the closest known property
+ *
+ * @param {string} a
+ * @param {string} b
+ */
+function editDistance(a, b) {
+  const row = Array.from({ length: b.length + 1 }, (_, i) => i);
+  for (let i = 1; i <= a.length; i++) {
+    let prev = row[0];
+    row[0] = i;
+    for (let j = 1; j <= b.length; j++) {
+      const tmp = row[j];
+      row[j] = Math.min(
+        row[j] + 1,
+        row[j - 1] + 1,
+        prev + (a[i - 1].toLowerCase() === b[j - 1].toLowerCase() ? 0 : 1),
+      );
+      prev = tmp;
+    }
+  }
+  return row[b.length];
+}
+
+/**
+ * Finds the innermost node containing the position
+ *
+ * @param {ts.SourceFile} sourceFile
+ * @param {number} pos
+ */
+function nodeAt(sourceFile, pos) {
+  let found = undefined;
+  const visit = (node) => {
+    if (pos >= node.getStart(sourceFile) && pos < node.getEnd()) {
+      found = node;
+      ts.forEachChild(node, visit);
+    }
+  };
+  ts.forEachChild(sourceFile, visit);
+  return found;
+}
+
+/**
+ * Suggested fix for a diagnostic, from the compiler's "did you mean" hints or
+ * the properties known from the function input types
+ *
+ * @param {ts.Diagnostic} diagnostic
+ * @param {string} message
+ * @param {ts.TypeChecker} checker
+ * @returns {string | undefined}
+ */
+function suggestionFor(diagnostic, message, checker) {
+  // compiler hints, e.g. "Did you mean 'title'?" / "Did you mean to write 'title'?"
+  const hint = message.match(/Did you mean(?: to write)? '([^']+)'\?/);
+  if (hint) {
+    return `did you mean '${hint[1]}'?`;
+  }
+
+  // "Property 'x' is missing in type ... but required in type ..."
+  const missing = message.match(/Property '([^']+)' is missing in type/);
+  if (missing) {
+    return `add the required property '${missing[1]}'`;
+  }
+
+  // "Object literal may only specify known properties, and 'x' does not exist in type ..."
+  if (diagnostic.code === 2353 && diagnostic.file && diagnostic.start !== undefined) {
+    const node = nodeAt(diagnostic.file, diagnostic.start);
+    const literal = node?.parent?.parent;
+    if (!literal || !ts.isObjectLiteralExpression(literal)) return undefined;
+    const type = checker.getContextualType(literal);
+    if (!type) return undefined;
+
+    const unknown = node.getText(diagnostic.file);
+    const known = checker.getPropertiesOfType(type).map((p) => p.name);
+    const closest = known
+      .map((name) => ({ name, distance: editDistance(unknown, name) }))
+      .sort((a, b) => a.distance - b.distance)[0];
+    if (closest && closest.distance <= Math.max(2, Math.floor(unknown.length / 3))) {
+      return `did you mean '${closest.name}'?`;
+    }
+    if (known.length > 0) {
+      return `known properties are ${known.map((k) => `'${k}'`).join(", ")}`;
+    }
+  }
+
+  return undefined;
+}
+
 /**
  * Type check TypeScript code using the full TypeScript compiler
  *
  * @param {string} code - The TypeScript code to check
- * @returns {{success: boolean, diagnostics: Array<{message: string, line?: number, column?: number, severity: string, code?: number}>}}
+ * @param {{strict?: boolean, noImplicitAny?: boolean, target?: string, allowedGlobals?: string[]}} [options] - Compiler options
+ * @returns {{success: boolean, diagnostics: Array<{message: string, line?: number, column?: number, severity: string, code?: number, suggestion?: string}>}}
  */
-function typeCheckCode(code) {
+function typeCheckCode(code, options = {}) {
   const diagnostics = [];
+  const strict = options.strict ?? true;
 
   try {
     // Create a virtual file system for the TypeScript compiler
     const fileName = "check.ts";
     const files = new Map();
     files.set(fileName, code);
-    files.set("lib.deno.d.ts", LIB_DENO_NS);
+    files.set("lib.deno.d.ts", LIB_DENO_NS + allowedGlobalsLib(options));
 
     // Create a custom compiler host
     const compilerHost = {
@@ -82,13 +202,13 @@ function typeCheckCode(code) {
       getNewLine: () => "\n",
     };
 
-    // TODO: more granular control over type check strictness
     const program = ts.createProgram({
       rootNames: [fileName, "lib.deno.d.ts"],
       options: {
-        target: ts.ScriptTarget.ES2020,
+        target: scriptTarget(options.target),
         module: ts.ModuleKind.ES2020,
-        strict: true,
+        strict,
+        noImplicitAny: options.noImplicitAny ?? strict,
         noEmit: true,
         skipLibCheck: false,
         noLib: false,
@@ -96,6 +216,8 @@ function typeCheckCode(code) {
       host: compilerHost,
     });
 
+    const checker = program.getTypeChecker();
+
     // Get all diagnostics
     const allDiagnostics = [
       ...program.getSyntacticDiagnostics(),
@@ -133,6 +255,14 @@ function typeCheckCode(code) {
             ? "error"
             : "warning",
         code: diagnostic.code,
+        suggestion: (() => {
+          try {
+            return suggestionFor(diagnostic, message, checker);
+          } catch {
+            // suggestions are best effort
+            return undefined;
+          }
+        })(),
       });
     }
   } catch (error) {
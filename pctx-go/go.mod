@@ -0,0 +1,3 @@
+module github.com/portofcontext/pctx/pctx-go
+
+go 1.22
@@ -0,0 +1,78 @@
+package pctx
+
+import "encoding/json"
+
+// Handler answers a call of a tool with the JSON arguments executed code passed it.
+// The returned value is marshalled to JSON.
+type Handler func(args json.RawMessage) (any, error)
+
+// Tool is a local tool executed code calls as `Namespace.name(args)`.
+type Tool struct {
+	Namespace    string          `json:"namespace"`
+	Name         string          `json:"name"`
+	Description  string          `json:"description,omitempty"`
+	InputSchema  json.RawMessage `json:"input_schema,omitempty"`
+	OutputSchema json.RawMessage `json:"output_schema,omitempty"`
+	Handler      Handler         `json:"-"`
+}
+
+// ListedFunction is a function executed code can call.
+type ListedFunction struct {
+	Namespace   string  `json:"namespace"`
+	Name        string  `json:"name"`
+	Description *string `json:"description"`
+}
+
+// ListFunctionsOutput lists the functions executed code can call and their
+// TypeScript declarations.
+type ListFunctionsOutput struct {
+	Functions []ListedFunction `json:"functions"`
+	Code      string           `json:"code"`
+}
+
+// FunctionDetails holds the TypeScript declarations of a function.
+type FunctionDetails struct {
+	ListedFunction
+	InputType  string `json:"input_type"`
+	OutputType string `json:"output_type"`
+	Types      string `json:"types"`
+}
+
+// GetFunctionDetailsOutput holds the details of the requested functions.
+type GetFunctionDetailsOutput struct {
+	Functions []FunctionDetails `json:"functions"`
+	Code      string            `json:"code"`
+}
+
+// ExecuteOptions are the options of an execution.
+type ExecuteOptions struct {
+	// Input is passed as the argument of `run(input)`.
+	Input any
+	// SkipTypeCheck executes the code without type checking it first.
+	SkipTypeCheck bool
+}
+
+type executeInput struct {
+	Code          string `json:"code"`
+	SkipTypeCheck bool   `json:"skip_type_check"`
+	Input         any    `json:"input,omitempty"`
+}
+
+// CodeDiagnostic is a type error that prevented code from executing.
+type CodeDiagnostic struct {
+	Message    string `json:"message"`
+	Line       *int   `json:"line"`
+	Column     *int   `json:"column"`
+	Code       *int   `json:"code"`
+	Suggestion string `json:"suggestion,omitempty"`
+}
+
+// ExecuteOutput is the result of an execution.
+type ExecuteOutput struct {
+	Success     bool             `json:"success"`
+	Stdout      string           `json:"stdout"`
+	Stderr      string           `json:"stderr"`
+	Output      json.RawMessage  `json:"output"`
+	Unchecked   bool             `json:"unchecked"`
+	Diagnostics []CodeDiagnostic `json:"diagnostics"`
+}
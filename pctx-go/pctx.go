@@ -0,0 +1,317 @@
+// Package pctx runs pctx code mode in-process through the pctx_ffi C API.
+//
+// Go agent backends register local tools and MCP servers on a [CodeMode] and execute
+// TypeScript code calling them, without running a separate pctx server:
+//
+//	cm := pctx.New()
+//	defer cm.Close()
+//
+//	err := cm.AddTool(pctx.Tool{
+//		Namespace: "Greeter",
+//		Name:      "greet",
+//		Handler: func(args json.RawMessage) (any, error) {
+//			return "Hello!", nil
+//		},
+//	})
+//
+//	out, err := cm.Execute(ctx, `async function run() { return await Greeter.greet({}); }`, nil)
+//
+// The package links against the pctx_ffi library built with
+// `cargo build --release -p pctx_ffi`.
+package pctx
+
+/*
+#cgo CFLAGS: -I${SRCDIR}/../crates/pctx_ffi/include
+#cgo LDFLAGS: -L${SRCDIR}/../target/release -lpctx_ffi
+#include <stdint.h>
+#include <stdlib.h>
+#include "pctx.h"
+
+extern void pctxGoCallback(void *user_data, char *args_json, PctxCallResult *result);
+*/
+import "C"
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"runtime"
+	"runtime/cgo"
+	"sync"
+	"time"
+	"unsafe"
+)
+
+// ErrClosed is returned by the methods of a closed [CodeMode].
+var ErrClosed = errors.New("pctx: code mode is closed")
+
+// CodeMode holds the tools & MCP servers executed code can call.
+//
+// Its methods are safe for concurrent use, executions run concurrently while adding
+// tools or servers waits for running executions.
+type CodeMode struct {
+	mu      sync.RWMutex
+	ptr     *C.PctxCodeMode
+	handles []*C.uintptr_t
+}
+
+// New creates a code mode without tools, release it with [CodeMode.Close].
+func New() *CodeMode {
+	c := &CodeMode{ptr: C.pctx_code_mode_new()}
+	runtime.SetFinalizer(c, (*CodeMode).Close)
+	return c
+}
+
+// Close releases the code mode and the handlers of its tools.
+func (c *CodeMode) Close() {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	if c.ptr == nil {
+		return
+	}
+	C.pctx_code_mode_free(c.ptr)
+	c.ptr = nil
+	for _, h := range c.handles {
+		cgo.Handle(*h).Delete()
+		C.free(unsafe.Pointer(h))
+	}
+	c.handles = nil
+}
+
+// AddServer connects to an MCP server and adds its tools. The server is configured like
+// in pctx configuration files, e.g.
+//
+//	map[string]any{"name": "github", "url": "https://api.githubcopilot.com/mcp/"}
+func (c *CodeMode) AddServer(ctx context.Context, server any) error {
+	config, err := json.Marshal(server)
+	if err != nil {
+		return fmt.Errorf("pctx: invalid server: %w", err)
+	}
+	_, err = await(ctx, func() (struct{}, error) {
+		c.mu.Lock()
+		defer c.mu.Unlock()
+		if c.ptr == nil {
+			return struct{}{}, ErrClosed
+		}
+		cConfig := C.CString(string(config))
+		defer C.free(unsafe.Pointer(cConfig))
+		return struct{}{}, callStatus(func() C.PctxStatus {
+			return C.pctx_code_mode_add_server(c.ptr, cConfig)
+		})
+	})
+	return err
+}
+
+// AddTool adds a tool answering calls with its handler. Handlers are called from other
+// goroutines while executions are running.
+func (c *CodeMode) AddTool(tool Tool) error {
+	if tool.Handler == nil {
+		return fmt.Errorf("pctx: tool %s.%s has no handler", tool.Namespace, tool.Name)
+	}
+	config, err := json.Marshal(tool)
+	if err != nil {
+		return fmt.Errorf("pctx: invalid tool: %w", err)
+	}
+
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	if c.ptr == nil {
+		return ErrClosed
+	}
+	cConfig := C.CString(string(config))
+	defer C.free(unsafe.Pointer(cConfig))
+
+	// the handle is passed through C memory as Go pointers cannot be kept by C
+	handle := (*C.uintptr_t)(C.malloc(C.size_t(unsafe.Sizeof(C.uintptr_t(0)))))
+	*handle = C.uintptr_t(cgo.NewHandle(tool.Handler))
+	err = callStatus(func() C.PctxStatus {
+		return C.pctx_code_mode_add_callback(
+			c.ptr,
+			cConfig,
+			C.PctxCallback(C.pctxGoCallback),
+			unsafe.Pointer(handle),
+		)
+	})
+	if err != nil {
+		cgo.Handle(*handle).Delete()
+		C.free(unsafe.Pointer(handle))
+		return err
+	}
+	c.handles = append(c.handles, handle)
+	return nil
+}
+
+// ListFunctions lists the functions executed code can call.
+func (c *CodeMode) ListFunctions() (*ListFunctionsOutput, error) {
+	c.mu.RLock()
+	defer c.mu.RUnlock()
+	if c.ptr == nil {
+		return nil, ErrClosed
+	}
+	out, err := callJSON(func() *C.char {
+		return C.pctx_code_mode_list_functions(c.ptr)
+	})
+	if err != nil {
+		return nil, err
+	}
+	var listed ListFunctionsOutput
+	if err := json.Unmarshal(out, &listed); err != nil {
+		return nil, fmt.Errorf("pctx: invalid functions: %w", err)
+	}
+	return &listed, nil
+}
+
+// GetFunctionDetails returns the TypeScript declarations of functions, identified as
+// `Namespace.name`.
+func (c *CodeMode) GetFunctionDetails(functions ...string) (*GetFunctionDetailsOutput, error) {
+	input, err := json.Marshal(map[string][]string{"functions": functions})
+	if err != nil {
+		return nil, err
+	}
+
+	c.mu.RLock()
+	defer c.mu.RUnlock()
+	if c.ptr == nil {
+		return nil, ErrClosed
+	}
+	cInput := C.CString(string(input))
+	defer C.free(unsafe.Pointer(cInput))
+	out, err := callJSON(func() *C.char {
+		return C.pctx_code_mode_get_function_details(c.ptr, cInput)
+	})
+	if err != nil {
+		return nil, err
+	}
+	var details GetFunctionDetailsOutput
+	if err := json.Unmarshal(out, &details); err != nil {
+		return nil, fmt.Errorf("pctx: invalid function details: %w", err)
+	}
+	return &details, nil
+}
+
+// Execute runs TypeScript code defining `async function run()`, returning its output.
+//
+// The deadline of ctx stops the execution and cancels its pending tool calls. When ctx
+// is cancelled without a deadline, Execute returns ctx.Err() while the execution runs to
+// completion in the background.
+func (c *CodeMode) Execute(ctx context.Context, code string, opts *ExecuteOptions) (*ExecuteOutput, error) {
+	in := executeInput{Code: code}
+	if opts != nil {
+		in.Input = opts.Input
+		in.SkipTypeCheck = opts.SkipTypeCheck
+	}
+	input, err := json.Marshal(in)
+	if err != nil {
+		return nil, fmt.Errorf("pctx: invalid input: %w", err)
+	}
+	if err := ctx.Err(); err != nil {
+		return nil, err
+	}
+	deadline, hasDeadline := ctx.Deadline()
+
+	return await(ctx, func() (*ExecuteOutput, error) {
+		c.mu.RLock()
+		defer c.mu.RUnlock()
+		if c.ptr == nil {
+			return nil, ErrClosed
+		}
+		cInput := C.CString(string(input))
+		defer C.free(unsafe.Pointer(cInput))
+		out, err := callJSON(func() *C.char {
+			if hasDeadline {
+				timeout := max(time.Until(deadline).Milliseconds(), 1)
+				return C.pctx_code_mode_execute_with_timeout(c.ptr, cInput, C.uint64_t(timeout))
+			}
+			return C.pctx_code_mode_execute(c.ptr, cInput)
+		})
+		if err != nil {
+			return nil, err
+		}
+		var output ExecuteOutput
+		if err := json.Unmarshal(out, &output); err != nil {
+			return nil, fmt.Errorf("pctx: invalid output: %w", err)
+		}
+		return &output, nil
+	})
+}
+
+// await runs f in a goroutine, returning early with ctx.Err() if ctx is done first.
+func await[T any](ctx context.Context, f func() (T, error)) (T, error) {
+	type result struct {
+		value T
+		err   error
+	}
+	done := make(chan result, 1)
+	go func() {
+		value, err := f()
+		done <- result{value, err}
+	}()
+
+	select {
+	case <-ctx.Done():
+		var zero T
+		return zero, ctx.Err()
+	case res := <-done:
+		return res.value, res.err
+	}
+}
+
+// callStatus calls f on a locked OS thread, so the thread-local error of the library
+// is read from the thread f failed on.
+func callStatus(f func() C.PctxStatus) error {
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	if f() != C.PCTX_STATUS_OK {
+		return lastError()
+	}
+	return nil
+}
+
+// callJSON calls f on a locked OS thread and takes ownership of the returned JSON.
+func callJSON(f func() *C.char) ([]byte, error) {
+	runtime.LockOSThread()
+	defer runtime.UnlockOSThread()
+	out := f()
+	if out == nil {
+		return nil, lastError()
+	}
+	defer C.pctx_string_free(out)
+	return []byte(C.GoString(out)), nil
+}
+
+func lastError() error {
+	msg := C.pctx_last_error()
+	if msg == nil {
+		return errors.New("pctx: unknown error")
+	}
+	return errors.New("pctx: " + C.GoString(msg))
+}
+
+//export pctxGoCallback
+func pctxGoCallback(userData unsafe.Pointer, argsJSON *C.char, result *C.PctxCallResult) {
+	handler := cgo.Handle(*(*C.uintptr_t)(userData)).Value().(Handler)
+	output, err := callHandler(handler, json.RawMessage(C.GoString(argsJSON)))
+	if err != nil {
+		msg := C.CString(err.Error())
+		defer C.free(unsafe.Pointer(msg))
+		C.pctx_call_result_set_error(result, msg)
+		return
+	}
+	cOutput := C.CString(string(output))
+	defer C.free(unsafe.Pointer(cOutput))
+	C.pctx_call_result_set_output(result, cOutput)
+}
+
+func callHandler(handler Handler, args json.RawMessage) (output []byte, err error) {
+	defer func() {
+		if r := recover(); r != nil {
+			err = fmt.Errorf("tool panicked: %v", r)
+		}
+	}()
+	value, err := handler(args)
+	if err != nil {
+		return nil, err
+	}
+	return json.Marshal(value)
+}
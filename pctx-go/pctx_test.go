@@ -0,0 +1,102 @@
+package pctx
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+)
+
+func newMathCodeMode(t *testing.T) *CodeMode {
+	t.Helper()
+	cm := New()
+	t.Cleanup(cm.Close)
+
+	err := cm.AddTool(Tool{
+		Namespace:   "MyMath",
+		Name:        "add",
+		Description: "Adds two numbers",
+		InputSchema: json.RawMessage(`{
+			"type": "object",
+			"properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+			"required": ["a", "b"]
+		}`),
+		OutputSchema: json.RawMessage(`{ "type": "number" }`),
+		Handler: func(args json.RawMessage) (any, error) {
+			var in struct{ A, B int }
+			if err := json.Unmarshal(args, &in); err != nil {
+				return nil, err
+			}
+			return in.A + in.B, nil
+		},
+	})
+	if err != nil {
+		t.Fatal(err)
+	}
+	err = cm.AddTool(Tool{
+		Namespace: "MyMath",
+		Name:      "fail",
+		Handler: func(json.RawMessage) (any, error) {
+			return nil, errors.New("cannot compute")
+		},
+	})
+	if err != nil {
+		t.Fatal(err)
+	}
+	return cm
+}
+
+func TestListFunctions(t *testing.T) {
+	cm := newMathCodeMode(t)
+
+	listed, err := cm.ListFunctions()
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(listed.Functions) != 2 || listed.Functions[0].Name != "add" {
+		t.Fatalf("unexpected functions: %+v", listed.Functions)
+	}
+
+	details, err := cm.GetFunctionDetails("MyMath.add")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(details.Functions) != 1 || details.Functions[0].InputType == "" {
+		t.Fatalf("unexpected details: %+v", details.Functions)
+	}
+}
+
+func TestExecute(t *testing.T) {
+	cm := newMathCodeMode(t)
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+
+	out, err := cm.Execute(ctx, `async function run(input: { a: number }) {
+		return await MyMath.add({ a: input.a, b: 2 });
+	}`, &ExecuteOptions{Input: map[string]int{"a": 1}})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !out.Success || string(out.Output) != "3" {
+		t.Fatalf("unexpected output: %+v", out)
+	}
+
+	out, err = cm.Execute(ctx, `async function run() {
+		return await MyMath.fail({});
+	}`, nil)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if out.Success {
+		t.Fatalf("expected the failing tool to fail the execution: %+v", out)
+	}
+}
+
+func TestClosed(t *testing.T) {
+	cm := New()
+	cm.Close()
+	if _, err := cm.ListFunctions(); !errors.Is(err, ErrClosed) {
+		t.Fatalf("expected ErrClosed, got %v", err)
+	}
+}
@@ -1,14 +1,32 @@
 from ._client import Pctx
 from ._convert import tool
 from ._tool import AsyncTool, Tool
-from .models import HttpServerConfig, ServerConfig, StdioServerConfig
+from ._toolkit import PctxToolkit
+from ._tools import Tools
+from .models import (
+    CodeDiagnostic,
+    ExecuteOutput,
+    ExecutionMetrics,
+    HttpServerConfig,
+    EventParams,
+    ProgressParams,
+    ServerConfig,
+    StdioServerConfig,
+)
 
 __all__ = [
     "Pctx",
+    "PctxToolkit",
     "Tool",
     "AsyncTool",
+    "Tools",
     "tool",
+    "ExecuteOutput",
+    "CodeDiagnostic",
+    "ExecutionMetrics",
     "HttpServerConfig",
     "StdioServerConfig",
     "ServerConfig",
+    "EventParams",
+    "ProgressParams",
 ]
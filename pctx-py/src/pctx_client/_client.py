@@ -4,7 +4,7 @@ PCTX Client
 Main client for executing code with both MCP tools and local Python tools.
 """
 
-from typing import TYPE_CHECKING
+from typing import TYPE_CHECKING, Any
 from urllib.parse import urlparse
 
 from httpx import AsyncClient
@@ -12,7 +12,12 @@ from pydantic import BaseModel
 
 from pctx_client._tool import AsyncTool, Tool
 from pctx_client._utils import to_snake_case
-from pctx_client._websocket_client import WebSocketClient
+from pctx_client._websocket_client import (
+    EventCallback,
+    ProgressCallback,
+    WebSocketClient,
+    WsEncoding,
+)
 from pctx_client.exceptions import ConnectionError, SessionError
 from pctx_client.models import (
     ExecuteInput,
@@ -59,6 +64,9 @@ class Pctx:
         url: str = "http://localhost:8080",
         api_key: str | None = None,
         execute_timeout: float = 30.0,
+        ws_encoding: WsEncoding = "json",
+        on_event: EventCallback | None = None,
+        event_topics: list[str] | None = None,
     ):
         """
         Initialize the PCTX client.
@@ -69,7 +77,14 @@ class Pctx:
                 - HTTP server: {"name": "...", "url": "...", "auth": {...}}
                 - stdio server: {"name": "...", "command": "...", "args": [...], "env": {...}}
             url: PCTX server URL (default: http://localhost:8080)
+            api_key: Token of servers started with `--auth-token`, or a JWT
             execute_timeout: Timeout for code execution in seconds (default: 30.0)
+            ws_encoding: Encoding of WebSocket messages, `msgpack` or `cbor` send large
+                tool arguments & results as compressed binary frames (default: json)
+            on_event: Called with events the server pushes during executions, may be a
+                coroutine function
+            event_topics: Topics of events to subscribe to, e.g. `tools/list_changed`,
+                `server/shutdown` is always sent
         """
 
         # Parse and normalize the URL
@@ -92,7 +107,12 @@ class Pctx:
         ws_scheme = "wss" if http_scheme == "https" else "ws"
 
         self._ws_client = WebSocketClient(
-            url=f"{ws_scheme}://{host}{parsed.path}/ws", api_key=api_key, tools=tools
+            url=f"{ws_scheme}://{host}{parsed.path}/ws",
+            api_key=api_key,
+            tools=tools,
+            encoding=ws_encoding,
+            on_event=on_event,
+            event_topics=event_topics,
         )
         self._client = AsyncClient(
             base_url=f"{http_scheme}://{host}{parsed.path}",
@@ -298,7 +318,15 @@ class Pctx:
 
         return GetFunctionDetailsOutput.model_validate(list_res.json())
 
-    async def execute(self, code: str) -> ExecuteOutput:
+    async def execute(
+        self,
+        code: str,
+        input: Any = None,
+        on_progress: ProgressCallback | None = None,
+        callback_timeout: float | None = None,
+        timeout: float | None = None,
+        skip_type_check: bool = False,
+    ) -> ExecuteOutput:
         """
         Execute TypeScript code that calls namespaced functions.
 
@@ -309,6 +337,15 @@ class Pctx:
             code: TypeScript code to execute. Must include an async `run()` function
                 that serves as the entry point. Functions must be called with their
                 namespace prefix (e.g., 'Weather.getCurrentWeather()').
+            input: JSON serializable value passed as the argument of `run(input)`.
+            on_progress: Called with the progress long-running MCP server tools
+                report during the execution, may be a coroutine function.
+            callback_timeout: Timeout in seconds of each local tool call, defaults to
+                the server's `--callback-timeout`.
+            timeout: Timeout in seconds of this execution, defaults to the client's
+                `execute_timeout`.
+            skip_type_check: Execute the code without type checking it, type errors
+                surface as runtime errors.
 
         Returns:
             ExecuteOutput: An object containing execution results with attributes:
@@ -318,7 +355,7 @@ class Pctx:
 
         Raises:
             SessionError: If called before establishing a session via connect().
-            TimeoutError: If execution exceeds the configured timeout (default 30s).
+            TimeoutError: If execution exceeds the timeout (default 30s).
 
         Notes:
             - Code must define an `async function run()` as the entry point
@@ -345,7 +382,13 @@ class Pctx:
                 "No code mode session exists, run Pctx(...).connect() before calling"
             )
         return await self._ws_client.execute_code(
-            self._session_id, code, timeout=self._execute_timeout
+            self._session_id,
+            code,
+            timeout=self._execute_timeout if timeout is None else timeout,
+            input=input,
+            on_progress=on_progress,
+            callback_timeout=callback_timeout,
+            skip_type_check=skip_type_check,
         )
 
     # ========== Registrations ==========
@@ -10,6 +10,7 @@ def tool(
     *args: Any,
     namespace: str = "tools",
     description: str | None = None,
+    timeout: float | None = None,
 ) -> Callable[[Callable], Tool | AsyncTool]: ...
 @overload
 def tool(
@@ -17,6 +18,7 @@ def tool(
     *args: Any,
     namespace: str = "tools",
     description: str | None = None,
+    timeout: float | None = None,
 ) -> Tool | AsyncTool: ...
 
 
@@ -25,6 +27,7 @@ def tool(
     *args: Any,
     namespace: str = "tools",
     description: str | None = None,
+    timeout: float | None = None,
 ) -> Tool | AsyncTool | Callable[[Callable], Tool | AsyncTool]:
     """
     Decorator that converts a function into a Tool or AsyncTool instance.
@@ -34,10 +37,14 @@ def tool(
     - @tool("custom_name") - Uses custom name for the tool
     - @tool(namespace="custom", description="...") - With additional options
 
+    `async def` functions become an `AsyncTool` awaited on the client's event loop,
+    other functions a `Tool` called on a worker thread.
+
     Args:
         name_or_callable: Either a custom tool name (str) or the function to wrap (Callable)
         namespace: The namespace the tool belongs to (default: "tools")
         description: Optional description override (default: uses function docstring)
+        timeout: Seconds after which calls of the tool are cancelled (default: no timeout)
 
     Returns:
         Either a Tool/AsyncTool instance or a decorator function that creates one
@@ -51,6 +58,11 @@ def tool(
         >>> @tool("custom_name", namespace="math")
         ... def add_two(x: int) -> int:
         ...     return x + 2
+
+        >>> @tool(namespace="http", timeout=10)
+        ... async def fetch(url: str) -> str:
+        ...     async with httpx.AsyncClient() as client:
+        ...         return (await client.get(url)).text
     """
 
     def _crate_tool_factory(tool_name: str) -> Callable[[Callable], Tool | AsyncTool]:
@@ -72,6 +84,7 @@ def tool(
                 name=tool_name,
                 namespace=namespace,
                 description=tool_desc,
+                timeout=timeout,
             )
 
         return _tool_factory
@@ -13,6 +13,7 @@ from pydantic import (
     TypeAdapter,
     create_model,
 )
+from pydantic.fields import FieldInfo
 
 
 class BaseTool(BaseModel):
@@ -39,6 +40,11 @@ class BaseTool(BaseModel):
         default=None, description="The return type schema."
     )
 
+    timeout: float | None = None
+    """
+    Seconds after which calls of the tool are cancelled, `None` to wait until they finish
+    """
+
     def validate_input(self, obj: Any):
         if self.input_schema is not None:
             self.input_schema.model_validate(obj)
@@ -68,9 +74,13 @@ class BaseTool(BaseModel):
         name: str | None = None,
         namespace: str = "tools",
         description: str | None = None,
+        timeout: float | None = None,
     ) -> "Tool | AsyncTool":
         """
         Creates a tool from a given function.
+
+        Coroutine functions create an `AsyncTool` awaited on the client's event loop,
+        other functions a `Tool` called on a worker thread so they don't block it.
         """
 
         if description is None:
@@ -104,6 +114,7 @@ class BaseTool(BaseModel):
                 description=_desc,
                 input_schema=input_schema,
                 output_schema=output_schema,
+                timeout=timeout,
             )
         else:
             # Synchronous tool
@@ -121,6 +132,7 @@ class BaseTool(BaseModel):
                 description=_desc,
                 input_schema=input_schema,
                 output_schema=output_schema,
+                timeout=timeout,
             )
 
 
@@ -228,6 +240,7 @@ def create_input_schema(
         A dynamically created Pydantic BaseModel class
     """
     sig = inspect.signature(func)
+    arg_descriptions = parse_arg_descriptions(func.__doc__ or "")
 
     # Build field definitions for create_model
     fields: dict[str, Any] = {}
@@ -246,16 +259,60 @@ def create_input_schema(
         )
 
         # Determine if the parameter is required or has a default value
-        if param.default == inspect.Parameter.empty:
-            # Required field - use ... as the Pydantic sentinel for required
-            fields[param_name] = (annotation, ...)
-        else:
-            # Optional field with default value
-            fields[param_name] = (annotation, param.default)
+        # Required field - use ... as the Pydantic sentinel for required
+        default = ... if param.default == inspect.Parameter.empty else param.default
+
+        # Describe the field with its documentation in the docstring's `Args:` section
+        if param_name in arg_descriptions and not isinstance(default, FieldInfo):
+            default = Field(default, description=arg_descriptions[param_name])
+
+        fields[param_name] = (annotation, default)
 
     return create_model(model_name, __config__=_MODEL_CONFIG, **fields)
 
 
+def parse_arg_descriptions(docstring: str) -> dict[str, str]:
+    """
+    Extracts the descriptions of parameters from the `Args:` section of a
+    Google style docstring.
+
+    Args:
+        docstring: The docstring of the function
+
+    Returns:
+        The descriptions keyed on parameter name
+    """
+    descriptions: dict[str, str] = {}
+    in_args = False
+    args_indent = 0
+    current: str | None = None
+    current_indent = 0
+
+    for line in inspect.cleandoc(docstring).splitlines():
+        stripped = line.strip()
+        indent = len(line) - len(line.lstrip())
+        if stripped in ("Args:", "Arguments:", "Parameters:"):
+            in_args, args_indent, current = True, indent, None
+            continue
+        if not in_args or not stripped:
+            continue
+        if indent <= args_indent:
+            # next section
+            in_args = False
+            continue
+
+        name, sep, description = stripped.partition(":")
+        # `name (type): description` entries, deeper indented lines continue the last one
+        name = name.split("(")[0].strip().lstrip("*")
+        if sep and name.isidentifier() and (current is None or indent <= current_indent):
+            current, current_indent = name, indent
+            descriptions[name] = description.strip()
+        elif current is not None:
+            descriptions[current] = f"{descriptions[current]} {stripped}".strip()
+
+    return descriptions
+
+
 def create_output_schema(
     func: Callable,
 ) -> Any:
@@ -0,0 +1,152 @@
+"""
+PCTX Toolkit
+
+Adapter exposing the Code Mode tools of a connected Pctx client as LangChain tools or
+LlamaIndex tools, with both sync and async entrypoints.
+"""
+
+import asyncio
+import functools
+from collections.abc import Callable, Coroutine
+from typing import TYPE_CHECKING, Any
+
+from pctx_client._client import CODE_MODE_TOOL_DESCRIPTIONS, HAS_SEARCH, Pctx
+
+if TYPE_CHECKING:
+    try:
+        from langchain_core.tools import BaseTool as LangchainBaseTool
+        from llama_index.core.tools import FunctionTool as LlamaIndexFunctionTool
+    except ImportError:
+        pass
+
+
+class PctxToolkit:
+    """
+    Code Mode tools of a Pctx client for LangChain & LlamaIndex agents
+
+    Follows the toolkit interface of LangChain (``get_tools()``) and the tool spec
+    interface of LlamaIndex (``to_tool_list()``). Agents awaiting the tools call the
+    client directly, sync calls from other threads are run on the event loop the
+    toolkit was created on.
+
+    Example:
+        async with Pctx(tools=[...]) as p:
+            toolkit = PctxToolkit(p)
+            agent = create_agent(model, tools=toolkit.get_tools())
+    """
+
+    def __init__(self, pctx: Pctx, sync_timeout: float | None = None):
+        """
+        Args:
+            pctx: Client the tools call, connect it before running the agent
+            sync_timeout: Timeout in seconds of sync tool calls, defaults to no timeout
+        """
+        self.pctx = pctx
+        self._sync_timeout = sync_timeout
+        try:
+            self._loop: asyncio.AbstractEventLoop | None = asyncio.get_running_loop()
+        except RuntimeError:
+            self._loop = None
+
+    async def list_functions(self) -> str:
+        return (await self.pctx.list_functions()).code
+
+    async def search_functions(self, query: str, k: int = 10) -> str:
+        functions = await self.pctx.search_functions(query, k)
+        return self.pctx._search_functions_result_to_string(functions)
+
+    async def get_function_details(self, functions: list[str]) -> str:
+        return (await self.pctx.get_function_details(functions)).code
+
+    async def execute(self, code: str) -> str:
+        return (await self.pctx.execute(code)).markdown()
+
+    def get_tools(self) -> "list[LangchainBaseTool]":
+        """
+        Code Mode tools as LangChain tools
+
+        Requires the 'langchain' extra to be installed:
+            pip install pctx[langchain]
+
+        Raises:
+            ImportError: If langchain is not installed.
+        """
+        try:
+            from langchain_core.tools import StructuredTool
+        except ImportError as e:
+            raise ImportError(
+                "LangChain is not installed. Install it with: pip install pctx[langchain]"
+            ) from e
+
+        return [
+            StructuredTool.from_function(
+                func=self._sync(fn),
+                coroutine=fn,
+                name=name,
+                description=CODE_MODE_TOOL_DESCRIPTIONS[name],
+            )
+            for name, fn in self._tool_functions()
+        ]
+
+    def to_tool_list(self) -> "list[LlamaIndexFunctionTool]":
+        """
+        Code Mode tools as LlamaIndex tools
+
+        Requires the 'llama-index' extra to be installed:
+            pip install pctx[llama-index]
+
+        Raises:
+            ImportError: If llama-index is not installed.
+        """
+        try:
+            from llama_index.core.tools import FunctionTool
+        except ImportError as e:
+            raise ImportError(
+                "LlamaIndex is not installed. Install it with: pip install pctx[llama-index]"
+            ) from e
+
+        return [
+            FunctionTool.from_defaults(
+                fn=self._sync(fn),
+                async_fn=fn,
+                name=name,
+                description=CODE_MODE_TOOL_DESCRIPTIONS[name],
+            )
+            for name, fn in self._tool_functions()
+        ]
+
+    def _tool_functions(self) -> list[tuple[str, Callable[..., Coroutine[Any, Any, str]]]]:
+        functions: list[tuple[str, Callable[..., Coroutine[Any, Any, str]]]] = [
+            ("list_functions", self.list_functions)
+        ]
+        if HAS_SEARCH:
+            functions.append(("search_functions", self.search_functions))
+        functions.extend(
+            [
+                ("get_function_details", self.get_function_details),
+                ("execute", self.execute),
+            ]
+        )
+        return functions
+
+    def _sync(self, fn: Callable[..., Coroutine[Any, Any, str]]) -> Callable[..., str]:
+        @functools.wraps(fn)
+        def wrapper(*args: Any, **kwargs: Any) -> str:
+            if self._loop is None or self._loop.is_closed():
+                # no event loop captured, the client runs on a new one
+                return asyncio.run(fn(*args, **kwargs))
+
+            try:
+                running = asyncio.get_running_loop()
+            except RuntimeError:
+                running = None
+            if running is self._loop:
+                raise RuntimeError(
+                    "Sync pctx tools cannot be called from the event loop of the client, "
+                    "use the async entrypoint of the tool instead"
+                )
+
+            future = asyncio.run_coroutine_threadsafe(fn(*args, **kwargs), self._loop)
+            return future.result(timeout=self._sync_timeout)
+
+        return wrapper
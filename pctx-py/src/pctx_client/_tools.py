@@ -0,0 +1,97 @@
+from collections.abc import Callable
+from typing import Any, overload
+
+from pctx_client._convert import tool as _tool
+from pctx_client._tool import AsyncTool, Tool
+
+
+class Tools(list[Tool | AsyncTool]):
+    """
+    Collection of tools registered with the `tool` decorator, pass it as the
+    `tools` of `Pctx`.
+
+    Examples:
+        >>> tools = Tools(namespace="utils")
+
+        >>> @tools.tool
+        ... def slugify(text: str) -> str:
+        ...     '''Converts text to a URL slug'''
+        ...     return text.lower().replace(" ", "-")
+
+        >>> @tools.tool(namespace="http", timeout=10)
+        ... async def fetch(url: str) -> str:
+        ...     '''Fetches the text of a web page'''
+        ...     ...
+
+        >>> pctx = Pctx(tools=tools)
+    """
+
+    def __init__(self, namespace: str = "tools"):
+        """
+        Args:
+            namespace: Namespace of tools registered without one (default: "tools")
+        """
+        super().__init__()
+        self.namespace = namespace
+
+    @overload
+    def tool(
+        self,
+        name_or_callable: str | None = None,
+        *,
+        namespace: str | None = None,
+        description: str | None = None,
+        timeout: float | None = None,
+    ) -> Callable[[Callable], Tool | AsyncTool]: ...
+    @overload
+    def tool(
+        self,
+        name_or_callable: Callable,
+        *,
+        namespace: str | None = None,
+        description: str | None = None,
+        timeout: float | None = None,
+    ) -> Tool | AsyncTool: ...
+
+    def tool(
+        self,
+        name_or_callable: str | Callable | None = None,
+        *,
+        namespace: str | None = None,
+        description: str | None = None,
+        timeout: float | None = None,
+    ) -> Tool | AsyncTool | Callable[[Callable], Tool | AsyncTool]:
+        """
+        Decorator converting a function into a tool & adding it to the collection,
+        its input schema is built from the function's type hints & docstring.
+
+        Can be used with or without parameters like the `tool` decorator:
+        - @tools.tool - Uses function name as tool name
+        - @tools.tool("custom_name") - Uses custom name for the tool
+        - @tools.tool(namespace="custom", timeout=10) - With additional options
+
+        Args:
+            name_or_callable: Either a custom tool name (str) or the function to wrap (Callable)
+            namespace: The namespace the tool belongs to (default: the collection's namespace)
+            description: Optional description override (default: uses function docstring)
+            timeout: Seconds after which calls of the tool are cancelled (default: no timeout)
+
+        Returns:
+            Either a Tool/AsyncTool instance or a decorator function that creates one
+        """
+        options: dict[str, Any] = {
+            "namespace": namespace or self.namespace,
+            "description": description,
+            "timeout": timeout,
+        }
+
+        def _register(fn: Callable, name: str | None = None) -> Tool | AsyncTool:
+            created = _tool(name or fn.__name__, **options)(fn)
+            self.append(created)
+            return created
+
+        if name_or_callable is None:
+            return _register
+        if isinstance(name_or_callable, str):
+            return lambda fn: _register(fn, name_or_callable)
+        return _register(name_or_callable)
@@ -6,9 +6,12 @@ and execute TypeScript code.
 """
 
 import asyncio
+import inspect
 import json
+import time
 import uuid
-from typing import Any, Union
+import zlib
+from typing import Any, Callable, Literal, Union
 
 import pydantic
 import websockets
@@ -18,6 +21,8 @@ from pctx_client._tool import AsyncTool, Tool
 from pctx_client.models import (
     ErrorCode,
     ErrorData,
+    EventNotification,
+    EventParams,
     ExecuteCodeParams,
     ExecuteCodeRequest,
     ExecuteCodeResponse,
@@ -25,7 +30,13 @@ from pctx_client.models import (
     ExecuteToolRequest,
     ExecuteToolResponse,
     ExecuteToolResult,
+    ExecutionMetrics,
     JsonRpcError,
+    ProgressNotification,
+    ProgressParams,
+    QueuedNotification,
+    SubscribeNotification,
+    SubscribeParams,
 )
 
 from .exceptions import ConnectionError
@@ -36,8 +47,52 @@ WebSocketMessage = Union[
     ExecuteToolRequest,
     ExecuteToolResponse,
     JsonRpcError,
+    ProgressNotification,
+    QueuedNotification,
+    EventNotification,
+    SubscribeNotification,
 ]
 
+ProgressCallback = Callable[[ProgressParams], Any]
+EventCallback = Callable[[EventParams], Any]
+
+WsEncoding = Literal["json", "msgpack", "cbor"]
+
+# binary payloads larger than this many bytes are compressed, like the server does
+COMPRESSION_THRESHOLD = 16 * 1024
+
+
+def _encode_binary(encoding: WsEncoding, payload: Any) -> bytes:
+    if encoding == "msgpack":
+        import msgpack
+
+        data = msgpack.packb(payload)
+    else:
+        import cbor2
+
+        data = cbor2.dumps(payload)
+
+    if len(data) > COMPRESSION_THRESHOLD:
+        return b"\x01" + zlib.compress(data, 1)
+    return b"\x00" + data
+
+
+def _decode_binary(encoding: WsEncoding, frame: bytes) -> Any:
+    flag, data = frame[:1], frame[1:]
+    if flag == b"\x01":
+        data = zlib.decompress(data)
+    elif flag != b"\x00":
+        raise ValueError(f"Unknown binary frame flag {flag!r}")
+
+    if encoding == "msgpack":
+        import msgpack
+
+        return msgpack.unpackb(data)
+
+    import cbor2
+
+    return cbor2.loads(data)
+
 
 class WebSocketClient:
     """
@@ -52,18 +107,32 @@ class WebSocketClient:
         url: str,
         api_key: str | None = None,
         tools: list[Tool | AsyncTool] | None = None,
+        encoding: WsEncoding = "json",
+        on_event: EventCallback | None = None,
+        event_topics: list[str] | None = None,
     ):
         """
         Initialize the WebSocket client.
 
         Args:
             url: WebSocket server URL (e.g., "ws://localhost:8080/ws")
+            encoding: Encoding of messages, `msgpack` and `cbor` send smaller binary
+                frames and require the `msgpack` or `cbor` extra
+            on_event: Called with events the server pushes while connected (e.g. its
+                shutdown), may be a coroutine function
+            event_topics: Topics of events to subscribe to, e.g. `tools/list_changed`
         """
         self.url = url
         self.ws: ClientConnection | None = None
         self.tools = tools or []
         self._api_key = api_key
+        self._encoding: WsEncoding = encoding
+        self._on_event = on_event
+        self._event_topics = event_topics or []
         self._pending_executions: dict[str | int, asyncio.Future] = {}
+        self._progress_callbacks: dict[str | int, ProgressCallback] = {}
+        self._tool_tasks: set[asyncio.Task] = set()
+        self._tool_calls = 0
         self._request_counter = 0
 
     async def _connect(self, code_mode_session: str):
@@ -74,21 +143,34 @@ class WebSocketClient:
             ConnectionError: If connection fails
         """
         try:
-            headers = {
-                "x-code-mode-session": code_mode_session,
-                "x-pctx-api-key": self._api_key,
-            }
-            self.ws = await websockets.connect(self.url, additional_headers=headers)
+            headers = {"x-code-mode-session": code_mode_session}
+            if self._api_key:
+                headers["x-pctx-api-key"] = self._api_key
+            self.ws = await websockets.connect(
+                self.url,
+                additional_headers=headers,
+                subprotocols=[f"pctx.{self._encoding}"],
+            )
         except Exception as e:
             raise ConnectionError(f"Failed to connect to {self.url}: {e}") from e
 
         # Start message handler after receiving session_created
         self._message_handler_task = asyncio.create_task(self._handle_messages())
 
+        if self._event_topics:
+            await self._send(
+                SubscribeNotification(
+                    method="subscribe",
+                    params=SubscribeParams(topics=self._event_topics),
+                )
+            )
+
     async def _disconnect(self):
         """Disconnect from the WebSocket server."""
         if self._message_handler_task:
             self._message_handler_task.cancel()
+        for task in self._tool_tasks:
+            task.cancel()
 
         if self.ws:
             await self.ws.close()
@@ -103,10 +185,22 @@ class WebSocketClient:
                 "Cannot send messages when websocket is not connected"
             )
 
-        await self.ws.send(message.model_dump_json())
+        if self.ws.subprotocol in ("pctx.msgpack", "pctx.cbor"):
+            await self.ws.send(
+                _encode_binary(self._encoding, message.model_dump(mode="json"))
+            )
+        else:
+            await self.ws.send(message.model_dump_json())
 
     async def execute_code(
-        self, code_mode_session: str, code: str, timeout: float = 30.0
+        self,
+        code_mode_session: str,
+        code: str,
+        timeout: float = 30.0,
+        input: Any = None,
+        on_progress: ProgressCallback | None = None,
+        callback_timeout: float | None = None,
+        skip_type_check: bool = False,
     ) -> ExecuteOutput:
         """
         Execute code via WebSocket instead of REST.
@@ -114,10 +208,19 @@ class WebSocketClient:
         Args:
             code_mode_session: CodeMode session to run execution in
             code: TypeScript/JavaScript code to execute
-            timeout: Timeout in seconds (default 30)
+            timeout: Timeout in seconds (default 30), the server stops the execution
+                and cancels its pending tool calls once it passes
+            input: JSON serializable value passed as the argument of `run(input)`
+            on_progress: Called with the progress upstream MCP servers report
+                during the execution, may be a coroutine function
+            callback_timeout: Timeout in seconds of each local tool call, defaults to
+                the server's callback timeout
+            skip_type_check: Execute the code without type checking it, type errors
+                surface as runtime errors
 
         Returns:
-            ExecuteOutput with success, stdout, stderr, and output
+            ExecuteOutput with success, stdout, stderr, output, the type errors
+            preventing the execution and metrics of the execution
 
         Raises:
             TimeoutError: If execution times out
@@ -125,6 +228,8 @@ class WebSocketClient:
         """
         if self.ws is None:
             await self._connect(code_mode_session)
+        started = time.monotonic()
+        self._tool_calls = 0
 
         # Generate unique request ID
         request_id = str(uuid.uuid4())
@@ -132,10 +237,20 @@ class WebSocketClient:
         # Create future for response
         future: asyncio.Future[dict[str, Any]] = asyncio.Future()
         self._pending_executions[request_id] = future
+        if on_progress is not None:
+            self._progress_callbacks[request_id] = on_progress
 
         # Send request
         request = ExecuteCodeRequest(
-            id=request_id, method="execute_code", params=ExecuteCodeParams(code=code)
+            id=request_id,
+            method="execute_code",
+            params=ExecuteCodeParams(
+                code=code,
+                input=input,
+                callback_timeout_secs=callback_timeout,
+                timeout_secs=timeout,
+                skip_type_check=skip_type_check,
+            ),
         )
 
         try:
@@ -143,12 +258,18 @@ class WebSocketClient:
 
             # Wait for response with timeout
             result = await asyncio.wait_for(future, timeout=timeout)
-            return ExecuteOutput.model_validate(result)
+            output = ExecuteOutput.model_validate(result)
+            output.metrics = ExecutionMetrics(
+                duration_secs=time.monotonic() - started,
+                tool_calls=self._tool_calls,
+            )
+            return output
         except asyncio.TimeoutError:
             self._pending_executions.pop(request_id, None)
             raise TimeoutError(f"Code execution timed out after {timeout}s")
         finally:
             self._pending_executions.pop(request_id, None)
+            self._progress_callbacks.pop(request_id, None)
             await self._disconnect()
 
     async def _handle_messages(self):
@@ -162,15 +283,39 @@ class WebSocketClient:
             async for message_data in self.ws:
                 try:
                     adapter = pydantic.TypeAdapter(WebSocketMessage)
-                    message: WebSocketMessage = adapter.validate_json(message_data)
+                    if isinstance(message_data, bytes):
+                        message: WebSocketMessage = adapter.validate_python(
+                            _decode_binary(self._encoding, message_data)
+                        )
+                    else:
+                        message = adapter.validate_json(message_data)
 
                     if isinstance(message, ExecuteToolRequest):
-                        res = await self._handle_execute_tool(message)
-                        await self._send(res)
+                        # tool calls run concurrently, so slow tools don't hold up
+                        # other calls & messages of the execution
+                        task = asyncio.create_task(self._run_tool(message))
+                        self._tool_tasks.add(task)
+                        task.add_done_callback(self._tool_tasks.discard)
                     elif isinstance(message, ExecuteCodeResponse):
                         future = self._pending_executions.get(message.id)
                         if future is not None:
                             future.set_result(message.result)
+                    elif isinstance(message, ProgressNotification):
+                        callback = self._progress_callbacks.get(
+                            message.params.request_id
+                        )
+                        if callback is not None:
+                            res = callback(message.params)
+                            if inspect.isawaitable(res):
+                                await res
+                    elif isinstance(message, QueuedNotification):
+                        # the execution timeout includes the time spent queued
+                        pass
+                    elif isinstance(message, EventNotification):
+                        if self._on_event is not None:
+                            res = self._on_event(message.params)
+                            if inspect.isawaitable(res):
+                                await res
                     elif isinstance(message, JsonRpcError):
                         future = self._pending_executions.get(message.id)
                         if future is not None:
@@ -189,9 +334,17 @@ class WebSocketClient:
         except Exception as e:
             print(f"Message handler error: {e}")
 
+    async def _run_tool(self, req: ExecuteToolRequest):
+        res = await self._handle_execute_tool(req)
+        try:
+            await self._send(res)
+        except Exception as e:
+            print(f"Failed sending tool result: {e}")
+
     async def _handle_execute_tool(
         self, req: ExecuteToolRequest
     ) -> ExecuteToolResponse | JsonRpcError:
+        self._tool_calls += 1
         # Find tool to execute
         tool = next(
             (
@@ -210,22 +363,27 @@ class WebSocketClient:
                 ),
             )
 
-        args = req.params.args or {}
+        args = (req.params.args or {}) if tool.input_schema is not None else {}
         try:
             if isinstance(tool, Tool):
-                if tool.input_schema is None:
-                    output = tool.invoke()
-                else:
-                    output = tool.invoke(**args)
+                # sync tools run on a worker thread, releasing the event loop (and the
+                # GIL while they wait on I/O)
+                call = asyncio.to_thread(tool.invoke, **args)
             else:
-                if tool.input_schema is None:
-                    output = await tool.ainvoke()
-                else:
-                    output = await tool.ainvoke(**args)
+                call = tool.ainvoke(**args)
+            output = await asyncio.wait_for(call, timeout=tool.timeout)
 
             return ExecuteToolResponse(
                 id=req.id, result=ExecuteToolResult(output=output)
             )
+        except asyncio.TimeoutError:
+            return JsonRpcError(
+                id=req.id,
+                error=ErrorData(
+                    code=ErrorCode.INTERNAL_ERROR,
+                    message=f"Tool `{req.params.namespace}.{req.params.name}` timed out after {tool.timeout}s",
+                ),
+            )
         except pydantic.ValidationError as e:
             return JsonRpcError(
                 id=req.id,
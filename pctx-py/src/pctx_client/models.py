@@ -33,21 +33,118 @@ class HeadersAuth(TypedDict):
     headers: dict[str, str]
 
 
-class HttpServerConfig(TypedDict):
+class BasicAuth(TypedDict):
+    """HTTP basic authentication"""
+
+    type: Literal["basic"]
+    username: str
+    password: str
+
+
+class OAuthClientCredentialsAuth(TypedDict):
+    """OAuth 2.1 client credentials flow, the access token is cached until it expires"""
+
+    type: Literal["oauth_client_credentials"]
+    client_id: str
+    client_secret: str
+    token_url: str
+    scope: NotRequired[str]
+
+
+class ExecAuth(TypedDict):
+    """Bearer token printed by an external command"""
+
+    type: Literal["exec"]
+    command: str
+    refresh_secs: NotRequired[int]
+
+
+AuthConfig = (
+    BearerAuth | HeadersAuth | BasicAuth | OAuthClientCredentialsAuth | ExecAuth
+)
+
+
+class TlsConfig(TypedDict, total=False):
+    """Custom root CAs, mutual TLS and certificate verification of a connection"""
+
+    ca_cert: str
+    client_cert: str
+    client_key: str
+    insecure_skip_verify: bool
+
+
+class RetryConfig(TypedDict, total=False):
+    """Retry policy of tool calls"""
+
+    max_retries: int
+    initial_backoff_ms: int
+    max_backoff_ms: int
+    retry_on_codes: list[int]
+
+
+class CircuitBreakerConfig(TypedDict, total=False):
+    """Circuit breaker policy of a server"""
+
+    failure_threshold: int
+    cooldown_secs: int
+
+
+class ToolCacheConfig(TypedDict, total=False):
+    """Caching of results of identical calls, only enable for idempotent tools"""
+
+    ttl_secs: int
+
+
+class UpstreamToolConfig(TypedDict, total=False):
+    """Configuration of an upstream tool, keyed on its name in `tools`"""
+
+    cache: ToolCacheConfig
+    category: str
+    deprecated: bool | str
+    hidden: bool
+    requires_approval: bool
+    rate_limit_per_minute: int
+    max_calls_per_execution: int
+    idempotent: bool
+
+
+class GroupingConfig(TypedDict, total=False):
+    """Grouping of tools into nested namespaces by their name prefix"""
+
+    separator: str
+
+
+class _ServerOptions(TypedDict, total=False):
+    """Options of both HTTP and stdio servers"""
+
+    tools: dict[str, UpstreamToolConfig]
+    retry: RetryConfig
+    circuit_breaker: CircuitBreakerConfig
+    lazy: bool
+    namespace: str
+    grouping: GroupingConfig
+    case: Literal["preserve", "camelCase", "snake_case"]
+    infer_output_schemas: bool
+
+
+class HttpServerConfig(_ServerOptions):
     """Configuration for an HTTP MCP server connection"""
 
     name: str
     url: str
-    auth: NotRequired[BearerAuth | HeadersAuth]
+    auth: NotRequired[AuthConfig]
+    tls: NotRequired[TlsConfig]
 
 
-class StdioServerConfig(TypedDict):
+class StdioServerConfig(_ServerOptions):
     """Configuration for a stdio MCP server connection"""
 
     name: str
     command: str
     args: NotRequired[list[str]]
     env: NotRequired[dict[str, str]]
+    idle_timeout_secs: NotRequired[int]
+    restart_backoff_ms: NotRequired[int]
 
 
 ServerConfig = HttpServerConfig | StdioServerConfig
@@ -97,6 +194,29 @@ class ExecuteInput(BaseModel):
     code: str
 
 
+class CodeDiagnostic(BaseModel):
+    """Type error found in executed code"""
+
+    message: str
+    line: int | None = None
+    """Line of the error in the code (1-indexed)"""
+    column: int | None = None
+    """Column of the error in the code (1-indexed)"""
+    code: int | None = None
+    """TypeScript diagnostic code (e.g., 2322 for type mismatch)"""
+    suggestion: str | None = None
+    """Suggested fix (e.g. "did you mean 'title'?")"""
+
+
+class ExecutionMetrics(BaseModel):
+    """Metrics of an execution measured by the client"""
+
+    duration_secs: float
+    """Time from sending the execution until its output was received"""
+    tool_calls: int
+    """Calls of local tools made by the execution"""
+
+
 class ExecuteOutput(BaseModel):
     """Output from executing TypeScript code"""
 
@@ -104,9 +224,19 @@ class ExecuteOutput(BaseModel):
     stdout: str
     stderr: str
     output: Any | None = None
+    unchecked: bool = False
+    diagnostics: list[CodeDiagnostic] = []
+    """Type errors that prevented the code from executing"""
+    metrics: ExecutionMetrics | None = None
+    """Metrics of the execution, set by the client"""
 
     def markdown(self) -> str:
-        return f"""Code Executed Successfully: {self.success}
+        unchecked = (
+            "\nType Checked: false (skipped, type errors surface as runtime errors)"
+            if self.unchecked
+            else ""
+        )
+        return f"""Code Executed Successfully: {self.success}{unchecked}
 
 # Return Value
 ```json
@@ -148,6 +278,10 @@ class JsonRpcError(JsonRpcBase):
 
 class ExecuteCodeParams(BaseModel):
     code: str
+    input: Any = None
+    callback_timeout_secs: float | None = None
+    timeout_secs: float | None = None
+    skip_type_check: bool = False
 
 
 class ExecuteCodeRequest(JsonRpcBase):
@@ -176,3 +310,55 @@ class ExecuteToolResult(BaseModel):
 
 class ExecuteToolResponse(JsonRpcBase):
     result: ExecuteToolResult
+
+
+class ProgressParams(BaseModel):
+    """Progress an upstream MCP server reported during code execution"""
+
+    request_id: str | int
+    server: str
+    progress: float
+    total: float | None = None
+    message: str | None = None
+
+
+class ProgressNotification(BaseModel):
+    jsonrpc: Literal["2.0"] = "2.0"
+    method: Literal["progress"]
+    params: ProgressParams
+
+
+class QueuedParams(BaseModel):
+    """Code execution waiting for a free execution slot of the server"""
+
+    request_id: str | int
+    position: int
+
+
+class QueuedNotification(BaseModel):
+    jsonrpc: Literal["2.0"] = "2.0"
+    method: Literal["queued"]
+    params: QueuedParams
+
+
+class EventParams(BaseModel):
+    """Event pushed by the server, e.g. `tools/list_changed` or `server/shutdown`"""
+
+    topic: str
+    data: Any = None
+
+
+class EventNotification(BaseModel):
+    jsonrpc: Literal["2.0"] = "2.0"
+    method: Literal["event"]
+    params: EventParams
+
+
+class SubscribeParams(BaseModel):
+    topics: list[str]
+
+
+class SubscribeNotification(BaseModel):
+    jsonrpc: Literal["2.0"] = "2.0"
+    method: Literal["subscribe", "unsubscribe"]
+    params: SubscribeParams
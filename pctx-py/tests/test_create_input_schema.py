@@ -288,3 +288,29 @@ def test_tuple_type_annotation():
     # Test validation - wrong types
     with pytest.raises(ValidationError):
         Model(point=("a", "b"))
+
+
+def test_docstring_arg_descriptions():
+    """Test parameters are described with the docstring's Args section"""
+
+    def forecast(city: str, days: int = 3) -> str:
+        """
+        Gets the weather forecast.
+
+        Args:
+            city: Name of the city,
+                e.g. Paris
+            days (int): Number of days
+
+        Returns:
+            The forecast
+        """
+        return city
+
+    Model = create_input_schema("ForecastModel", forecast)
+    properties = Model.model_json_schema()["properties"]
+
+    assert properties["city"]["description"] == "Name of the city, e.g. Paris"
+    assert properties["days"]["description"] == "Number of days"
+    assert properties["days"]["default"] == 3
+    assert Model(city="Paris").days == 3
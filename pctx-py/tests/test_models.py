@@ -0,0 +1,21 @@
+"""Tests for the models of code mode outputs"""
+
+from pctx_client import ExecuteOutput
+
+
+def test_execute_output_diagnostics():
+    """Test execution outputs parse the structured diagnostics of type errors"""
+    output = ExecuteOutput.model_validate(
+        {
+            "success": False,
+            "stdout": "",
+            "stderr": "Type error",
+            "diagnostics": [
+                {"message": "Type 'string' is not assignable", "line": 3, "code": 2322}
+            ],
+        }
+    )
+
+    assert output.diagnostics[0].line == 3
+    assert output.diagnostics[0].code == 2322
+    assert output.metrics is None
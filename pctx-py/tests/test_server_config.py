@@ -86,3 +86,23 @@ def test_stdio_server_config_with_env():
     assert config["args"] == ["./server.js"]
     assert config["env"]["NODE_ENV"] == "production"
     assert config["env"]["API_KEY"] == "secret"
+
+
+def test_http_server_config_with_oauth_and_options():
+    """Test HTTP server configuration with OAuth, TLS & server options"""
+    config: HttpServerConfig = {
+        "name": "test-http",
+        "url": "https://example.com/mcp",
+        "auth": {
+            "type": "oauth_client_credentials",
+            "client_id": "id",
+            "client_secret": "${env:CLIENT_SECRET}",
+            "token_url": "https://example.com/oauth/token",
+        },
+        "tls": {"ca_cert": "certs/ca.pem"},
+        "namespace": "Example",
+        "tools": {"delete_item": {"requires_approval": True}},
+    }
+    assert config["auth"]["type"] == "oauth_client_credentials"
+    assert config["tls"]["ca_cert"] == "certs/ca.pem"
+    assert config["tools"]["delete_item"]["requires_approval"] is True
@@ -13,7 +13,7 @@ import pytest
 from crewai.tools import BaseTool as CrewAIBaseTool
 from pydantic_ai.tools import Tool as PydanticAITool
 
-from pctx_client import Pctx
+from pctx_client import Pctx, PctxToolkit
 
 
 @pytest.fixture
@@ -262,6 +262,54 @@ class TestPydanticAIConverter:
             assert inspect.iscoroutinefunction(tool.function)
 
 
+# ============== PctxToolkit Tests ==============
+
+
+class TestPctxToolkit:
+    """Tests for the LangChain & LlamaIndex toolkit adapter"""
+
+    def test_langchain_tools_have_sync_and_async_entrypoints(self, pctx_client):
+        """Test that toolkit LangChain tools support both invoke and ainvoke"""
+        tools = PctxToolkit(pctx_client).get_tools()
+        assert {tool.name for tool in tools} == {
+            "list_functions",
+            "search_functions",
+            "get_function_details",
+            "execute",
+        }
+        for tool in tools:
+            assert tool.description
+            assert tool.func is not None
+            assert inspect.iscoroutinefunction(tool.coroutine)
+
+    def test_langchain_tool_args(self, pctx_client):
+        """Test that toolkit LangChain tools infer their arguments"""
+        tools = {tool.name: tool for tool in PctxToolkit(pctx_client).get_tools()}
+        assert set(tools["execute"].args) == {"code"}
+        assert set(tools["get_function_details"].args) == {"functions"}
+        assert tools["list_functions"].args == {}
+
+    def test_llama_index_tools(self, pctx_client):
+        """Test that toolkit LlamaIndex tools have metadata & async functions"""
+        pytest.importorskip("llama_index.core")
+        tools = PctxToolkit(pctx_client).to_tool_list()
+        assert {tool.metadata.name for tool in tools} == {
+            "list_functions",
+            "search_functions",
+            "get_function_details",
+            "execute",
+        }
+        for tool in tools:
+            assert tool.metadata.description
+            assert tool.async_fn is not None
+
+    async def test_sync_call_on_client_loop_fails(self, pctx_client):
+        """Test that sync calls from the client's event loop fail instead of blocking it"""
+        tools = {tool.name: tool for tool in PctxToolkit(pctx_client).get_tools()}
+        with pytest.raises(RuntimeError, match="async entrypoint"):
+            tools["execute"].func(code="async function run() {}")
+
+
 # ============== Integration Tests ==============
 
 
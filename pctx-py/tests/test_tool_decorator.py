@@ -2,11 +2,20 @@
 
 from __future__ import annotations
 
+import asyncio
+
 import pytest
 from pydantic import ValidationError
 
-from pctx_client import Tool, tool
+from pctx_client import Tool, Tools, tool
 from pctx_client._tool import AsyncTool
+from pctx_client._websocket_client import WebSocketClient
+from pctx_client.models import (
+    ExecuteToolParams,
+    ExecuteToolRequest,
+    ExecuteToolResponse,
+    JsonRpcError,
+)
 
 # ============================================================================
 # SECTION 1: REGISTRATION TESTS
@@ -499,3 +508,81 @@ async def test_validation_async_valid_input() -> None:
     # Should not raise any validation error
     result = await fetch_data.ainvoke(url="https://example.com", timeout=60)
     assert result == "Data from https://example.com with timeout 60"
+
+
+# ============================================================================
+# SECTION 4: WEBSOCKET TOOL CALL TESTS
+# Tests for calls of tools requested by the server
+# ============================================================================
+
+
+def _tool_request(name: str, args: dict | None = None) -> ExecuteToolRequest:
+    return ExecuteToolRequest(
+        jsonrpc="2.0",
+        id="1",
+        method="execute_tool",
+        params=ExecuteToolParams(namespace="tools", name=name, args=args),
+    )
+
+
+@pytest.mark.asyncio
+async def test_websocket_calls_sync_and_async_tools() -> None:
+    """Test sync tools run on a worker thread & async tools are awaited"""
+
+    @tool
+    def add(a: int, b: int) -> int:
+        return a + b
+
+    @tool
+    async def greet(name: str) -> str:
+        await asyncio.sleep(0)
+        return f"Hello {name}"
+
+    client = WebSocketClient(url="ws://localhost:8080/ws", tools=[add, greet])
+
+    res = await client._handle_execute_tool(_tool_request("add", {"a": 1, "b": 2}))
+    assert isinstance(res, ExecuteToolResponse)
+    assert res.result.output == 3
+
+    res = await client._handle_execute_tool(_tool_request("greet", {"name": "Ada"}))
+    assert isinstance(res, ExecuteToolResponse)
+    assert res.result.output == "Hello Ada"
+
+
+@pytest.mark.asyncio
+async def test_websocket_tool_timeout() -> None:
+    """Test calls of tools with a timeout are cancelled once it passes"""
+
+    @tool(timeout=0.01)
+    async def slow() -> str:
+        await asyncio.sleep(1)
+        return "done"
+
+    assert slow.timeout == 0.01
+    client = WebSocketClient(url="ws://localhost:8080/ws", tools=[slow])
+
+    res = await client._handle_execute_tool(_tool_request("slow"))
+    assert isinstance(res, JsonRpcError)
+    assert "timed out" in res.error.message
+
+
+def test_tools_collection_decorator() -> None:
+    """Test tools decorated with a Tools collection are added to it"""
+
+    tools = Tools(namespace="utils")
+
+    @tools.tool
+    def slugify(text: str) -> str:
+        """Converts text to a URL slug"""
+        return text.lower().replace(" ", "-")
+
+    @tools.tool("get_page", namespace="http", timeout=5)
+    async def fetch(url: str) -> str:
+        return url
+
+    assert [t.name for t in tools] == ["slugify", "get_page"]
+    assert slugify.namespace == "utils"
+    assert isinstance(fetch, AsyncTool)
+    assert fetch.namespace == "http"
+    assert fetch.timeout == 5
+    assert slugify.invoke(text="Hello World") == "hello-world"